@@ -32,6 +32,12 @@ fn b40_suppressed_logs(b: &mut Bencher) {
     b.iter(use_trace);
 }
 
+// Bypasses the check of the log macros, so that the early exit of the logger is measured
+#[bench]
+fn b50_suppressed_records(b: &mut Bencher) {
+    b.iter(log_trace_records);
+}
+
 fn use_error() {
     for _ in 1..100 {
         error!("This is an error message");
@@ -42,3 +48,14 @@ fn use_trace() {
         trace!("This is a trace message");
     }
 }
+fn log_trace_records() {
+    for _ in 1..100 {
+        log::logger().log(
+            &log::Record::builder()
+                .level(log::Level::Trace)
+                .target("bench_standard")
+                .args(format_args!("This is a trace message"))
+                .build(),
+        );
+    }
+}
@@ -0,0 +1,121 @@
+#![feature(test)]
+
+// Measures the cost of writing log lines to a file with the different write modes,
+// and the cost of rotation and cleanup.
+//
+// Run with `cargo +nightly bench --bench bench_write_modes --features async`.
+// With WriteMode::Async, the numbers show the cost for the logging thread;
+// the output itself is done by the background thread.
+
+extern crate test;
+use flexi_logger::{
+    writers::{FileLogWriter, LogWriter},
+    Cleanup, Criterion, DeferredNow, FileSpec, Naming, WriteMode,
+};
+use test::Bencher;
+
+const SHORT_MESSAGE: &str = "This is a short log message";
+
+#[bench]
+fn b10_direct_short(b: &mut Bencher) {
+    bench_write(b, WriteMode::Direct, SHORT_MESSAGE);
+}
+
+#[bench]
+fn b11_direct_long(b: &mut Bencher) {
+    bench_write(b, WriteMode::Direct, &long_message());
+}
+
+#[bench]
+fn b20_buffer_and_flush_short(b: &mut Bencher) {
+    bench_write(b, WriteMode::BufferAndFlush, SHORT_MESSAGE);
+}
+
+#[bench]
+fn b21_buffer_and_flush_long(b: &mut Bencher) {
+    bench_write(b, WriteMode::BufferAndFlush, &long_message());
+}
+
+#[cfg(feature = "async")]
+#[bench]
+fn b30_async_short(b: &mut Bencher) {
+    bench_write(b, WriteMode::Async, SHORT_MESSAGE);
+}
+
+#[cfg(feature = "async")]
+#[bench]
+fn b31_async_long(b: &mut Bencher) {
+    bench_write(b, WriteMode::Async, &long_message());
+}
+
+#[bench]
+fn b40_rotation(b: &mut Bencher) {
+    bench_rotate(b, "log_files/bench_write_modes/rotation", Cleanup::Never);
+}
+
+#[bench]
+fn b41_rotation_with_cleanup(b: &mut Bencher) {
+    bench_rotate(
+        b,
+        "log_files/bench_write_modes/rotation_with_cleanup",
+        Cleanup::KeepLogFiles(5),
+    );
+}
+
+// Writes 100 log lines per iteration
+fn bench_write(b: &mut Bencher, write_mode: WriteMode, message: &str) {
+    let flw = FileLogWriter::builder(
+        FileSpec::default()
+            .directory("log_files/bench_write_modes")
+            .suppress_timestamp(),
+    )
+    .write_mode(write_mode)
+    .try_build()
+    .unwrap();
+
+    b.iter(|| {
+        for _ in 0..100 {
+            flw.write(
+                &mut DeferredNow::new(),
+                &log::Record::builder()
+                    .level(log::Level::Info)
+                    .target("bench_write_modes")
+                    .args(format_args!("{message}"))
+                    .build(),
+            )
+            .unwrap();
+        }
+    });
+    flw.shutdown();
+}
+
+// Writes one log line and rotates, per iteration; the cleanup is done in the same thread
+fn bench_rotate(b: &mut Bencher, directory: &str, cleanup: Cleanup) {
+    let flw = FileLogWriter::builder(
+        FileSpec::default()
+            .directory(directory)
+            .suppress_timestamp(),
+    )
+    .rotate(Criterion::Size(u64::MAX), Naming::Numbers, cleanup)
+    .cleanup_in_background_thread(false)
+    .try_build()
+    .unwrap();
+
+    b.iter(|| {
+        flw.write(
+            &mut DeferredNow::new(),
+            &log::Record::builder()
+                .level(log::Level::Info)
+                .target("bench_write_modes")
+                .args(format_args!("{SHORT_MESSAGE}"))
+                .build(),
+        )
+        .unwrap();
+        flw.rotate().unwrap();
+    });
+    flw.shutdown();
+}
+
+fn long_message() -> String {
+    SHORT_MESSAGE.repeat(15)
+}
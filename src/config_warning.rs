@@ -0,0 +1,69 @@
+/// A combination of options that is accepted, but does not have the intended effect.
+///
+/// Provided by [`Logger::check`](crate::Logger::check) and
+/// [`FileLogWriterBuilder::check`](crate::writers::FileLogWriterBuilder::check).
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum ConfigWarning {
+    /// `append` is used, but the names of the log files contain the start time of the program,
+    /// so every run writes to a new file.
+    ///
+    /// Use [`FileSpec::suppress_timestamp`](crate::FileSpec::suppress_timestamp)
+    /// or rotation.
+    AppendWithTimestamp,
+    /// Duplication to stderr or stdout is configured, but the logs are written to stderr
+    /// or stdout, where duplication is not supported.
+    DuplicationWithoutFile,
+    /// Duplication to an additional writer is configured, but no writer is registered
+    /// under this name.
+    ///
+    /// Contains the name of the writer.
+    DuplicationToUnknownWriter(String),
+    /// Options for the log files are configured, but the logs are not written to files.
+    ///
+    /// Contains the name of the ignored option.
+    FileOptionWithoutFile(&'static str),
+    /// An option for the rotation or the cleanup is configured, but rotation is not used.
+    ///
+    /// Contains the name of the ignored option.
+    OptionWithoutRotation(&'static str),
+    /// The format of [`Naming::TimestampsCustomFormat`](crate::Naming::TimestampsCustomFormat)
+    /// produces infixes that can't be parsed back, so the cleanup can't recognize the rotated
+    /// files; starting the logger will fail.
+    ///
+    /// Contains the description of the problem.
+    UnparsableNamingFormat(String),
+    /// [`Logger::config_file_header`](crate::Logger::config_file_header) is used,
+    /// but the file log writer has a file header already, which is kept.
+    FileHeaderConflict,
+}
+impl std::fmt::Display for ConfigWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::AppendWithTimestamp => {
+                f.write_str("append has no effect because the file names contain a timestamp")
+            }
+            Self::DuplicationWithoutFile => f.write_str(
+                "duplication has no effect because the logs are written to stderr or stdout",
+            ),
+            Self::DuplicationToUnknownWriter(name) => write!(
+                f,
+                "duplication to writer \"{name}\" has no effect because no such writer is added"
+            ),
+            Self::FileOptionWithoutFile(option) => write!(
+                f,
+                "{option} has no effect because the logs are not written to files"
+            ),
+            Self::OptionWithoutRotation(option) => {
+                write!(f, "{option} has no effect because rotation is not used")
+            }
+            Self::UnparsableNamingFormat(problem) => write!(
+                f,
+                "the naming format can't be used for finding rotated files: {problem}"
+            ),
+            Self::FileHeaderConflict => f.write_str(
+                "config_file_header has no effect because a file header is configured already",
+            ),
+        }
+    }
+}
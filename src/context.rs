@@ -0,0 +1,315 @@
+//! Context values that are added to every log line of the current thread or task.
+//!
+//! This is what log4j and logback call the "mapped diagnostic context" (MDC):
+//! you can register key-value pairs, like a request id, that the provided format functions
+//! then add to each log line, without having to pass them to every log call.
+//!
+//! ```rust
+//! flexi_logger::context::insert("request_id", "a7f3");
+//! log::info!("Request received");
+//! // -> INFO [my_prog] (request_id=a7f3) Request received
+//! flexi_logger::context::remove("request_id");
+//! ```
+//!
+//! The context is maintained per thread. For asynchronous tasks, which can move between threads,
+//! wrap the task's future with [`Context::wrap`], so that the task's context is used
+//! whenever the task is executed:
+//!
+//! ```rust
+//! # async fn handle_request() {}
+//! # fn spawn<F>(_f: F) {}
+//! use flexi_logger::context::Context;
+//!
+//! spawn(Context::new().with("request_id", "a7f3").wrap(handle_request()));
+//! ```
+//!
+//! Custom format functions can access the context of the current thread with [`for_each`],
+//! [`get`], or [`current`].
+//!
+//! Note that the context is evaluated when the log line is formatted, which happens in the
+//! thread that issues the log call, also with the asynchronous write modes.
+use std::{
+    cell::RefCell,
+    future::Future,
+    marker::PhantomData,
+    pin::Pin,
+    task::{self, Poll},
+};
+
+thread_local! {
+    static CONTEXT: RefCell<Context> = RefCell::new(Context::new());
+}
+
+/// Adds a key-value pair to the context of the current thread.
+///
+/// An existing value for the same key is replaced and returned.
+pub fn insert<K: Into<String>, V: ToString + ?Sized>(key: K, value: &V) -> Option<String> {
+    CONTEXT.with(|context| context.borrow_mut().insert(key, value))
+}
+
+/// Removes a key from the context of the current thread and returns its value.
+#[allow(clippy::must_use_candidate)] // is mostly called for its side effect
+pub fn remove(key: &str) -> Option<String> {
+    CONTEXT.with(|context| context.borrow_mut().remove(key))
+}
+
+/// Removes all key-value pairs from the context of the current thread.
+pub fn clear() {
+    CONTEXT.with(|context| context.borrow_mut().0.clear());
+}
+
+/// Returns the value of the given key in the context of the current thread.
+#[must_use]
+pub fn get(key: &str) -> Option<String> {
+    CONTEXT.with(|context| context.borrow().get(key).map(ToString::to_string))
+}
+
+/// Returns a copy of the context of the current thread.
+///
+/// Can be used to hand over the context to another thread or task.
+#[must_use]
+pub fn current() -> Context {
+    CONTEXT.with(|context| context.borrow().clone())
+}
+
+/// Calls the given function for each key-value pair of the context of the current thread,
+/// in the order in which the keys were inserted.
+pub fn for_each<F: FnMut(&str, &str)>(mut f: F) {
+    CONTEXT.with(|context| {
+        // is only unavailable if the context is modified while a log line is being formatted
+        if let Ok(context) = context.try_borrow() {
+            for (key, value) in context.iter() {
+                f(key, value);
+            }
+        }
+    });
+}
+
+/// A set of key-value pairs, to be used as context of a thread or a task.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Context(Vec<(String, String)>);
+impl Context {
+    /// Creates an empty context.
+    #[must_use]
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    /// Adds a key-value pair and returns the modified context.
+    #[must_use]
+    pub fn with<K: Into<String>, V: ToString + ?Sized>(mut self, key: K, value: &V) -> Self {
+        self.insert(key, value);
+        self
+    }
+
+    /// Adds a key-value pair.
+    ///
+    /// An existing value for the same key is replaced and returned.
+    pub fn insert<K: Into<String>, V: ToString + ?Sized>(
+        &mut self,
+        key: K,
+        value: &V,
+    ) -> Option<String> {
+        let key = key.into();
+        let value = value.to_string();
+        if let Some((_, v)) = self.0.iter_mut().find(|(k, _)| *k == key) {
+            Some(std::mem::replace(v, value))
+        } else {
+            self.0.push((key, value));
+            None
+        }
+    }
+
+    /// Removes a key and returns its value.
+    pub fn remove(&mut self, key: &str) -> Option<String> {
+        let index = self.0.iter().position(|(k, _)| k == key)?;
+        Some(self.0.remove(index).1)
+    }
+
+    /// Returns the value of the given key.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.0
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Returns an iterator over the key-value pairs, in the order in which the keys were inserted.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.0.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// Returns `true` if the context contains no key-value pairs.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// Makes this the context of the current thread, until the returned guard is dropped;
+    /// then the previous context of the thread is restored.
+    #[must_use = "the context is detached again when the guard is dropped"]
+    pub fn attach(self) -> ContextGuard {
+        ContextGuard {
+            previous: CONTEXT.with(|context| context.replace(self)),
+            _not_send: PhantomData,
+        }
+    }
+
+    /// Wraps the given future such that this context is the context of the executing thread
+    /// whenever the future is polled.
+    ///
+    /// Modifications of the context that are done by the future are kept in the wrapped future.
+    pub fn wrap<F: Future>(self, future: F) -> WithContext<F> {
+        WithContext {
+            context: self,
+            future: Box::pin(future),
+        }
+    }
+}
+
+/// Restores the previous context of the thread when it is dropped.
+///
+/// Is returned by [`Context::attach`].
+#[derive(Debug)]
+pub struct ContextGuard {
+    previous: Context,
+    // the guard must be dropped in the thread in which it was created
+    _not_send: PhantomData<*const ()>,
+}
+impl Drop for ContextGuard {
+    fn drop(&mut self) {
+        let previous = std::mem::take(&mut self.previous);
+        CONTEXT.with(|context| context.replace(previous));
+    }
+}
+
+/// A future that runs with its own context.
+///
+/// Is returned by [`Context::wrap`].
+pub struct WithContext<F: Future> {
+    context: Context,
+    future: Pin<Box<F>>,
+}
+impl<F: Future> Future for WithContext<F> {
+    type Output = F::Output;
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> Poll<Self::Output> {
+        let this = &mut *self;
+        let _swapped = SwappedContext::new(&mut this.context);
+        this.future.as_mut().poll(cx)
+    }
+}
+
+// Swaps the given context with the context of the current thread, and swaps it back
+// when it is dropped, also if the future panics.
+struct SwappedContext<'a>(&'a mut Context);
+impl<'a> SwappedContext<'a> {
+    fn new(context: &'a mut Context) -> Self {
+        CONTEXT.with(|current| std::mem::swap(&mut *current.borrow_mut(), context));
+        Self(context)
+    }
+}
+impl Drop for SwappedContext<'_> {
+    fn drop(&mut self) {
+        CONTEXT.with(|current| std::mem::swap(&mut *current.borrow_mut(), self.0));
+    }
+}
+impl<F: Future> std::fmt::Debug for WithContext<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        f.debug_struct("WithContext")
+            .field("context", &self.context)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Context;
+    use std::{
+        future::Future,
+        sync::Arc,
+        task::{self, Poll, Wake, Waker},
+    };
+
+    #[test]
+    fn test_thread_context() {
+        assert_eq!(super::insert("a", &1), None);
+        assert_eq!(super::insert("b", "x"), None);
+        assert_eq!(super::insert("a", &2), Some("1".to_string()));
+        assert_eq!(
+            super::current(),
+            Context::new().with("a", &2).with("b", "x")
+        );
+
+        {
+            let _guard = Context::new().with("c", &3).attach();
+            assert_eq!(super::get("a"), None);
+            assert_eq!(super::get("c"), Some("3".to_string()));
+        }
+        assert_eq!(super::remove("a"), Some("2".to_string()));
+        assert_eq!(super::remove("a"), None);
+
+        let mut pairs = Vec::new();
+        super::for_each(|k, v| pairs.push(format!("{k}={v}")));
+        assert_eq!(pairs, vec!["b=x"]);
+
+        std::thread::spawn(|| assert!(super::current().is_empty()))
+            .join()
+            .unwrap();
+        super::clear();
+        assert!(super::current().is_empty());
+    }
+
+    #[test]
+    fn test_task_context() {
+        struct NoopWaker;
+        impl Wake for NoopWaker {
+            fn wake(self: Arc<Self>) {}
+        }
+
+        // a future that needs to be polled twice
+        let mut yielded = false;
+        let future = std::future::poll_fn(move |_cx| {
+            let value = super::get("task");
+            if yielded {
+                Poll::Ready(value)
+            } else {
+                yielded = true;
+                super::insert("polled", &true);
+                Poll::Pending
+            }
+        });
+
+        let mut wrapped = Box::pin(Context::new().with("task", &1).wrap(future));
+        let waker = Waker::from(Arc::new(NoopWaker));
+        let mut cx = task::Context::from_waker(&waker);
+
+        assert_eq!(wrapped.as_mut().poll(&mut cx), Poll::Pending);
+        assert_eq!(super::get("task"), None);
+        assert_eq!(super::get("polled"), None);
+        assert_eq!(
+            wrapped.as_mut().poll(&mut cx),
+            Poll::Ready(Some("1".to_string()))
+        );
+    }
+
+    #[test]
+    fn test_task_context_with_panic() {
+        struct NoopWaker;
+        impl Wake for NoopWaker {
+            fn wake(self: Arc<Self>) {}
+        }
+
+        let future = std::future::poll_fn(|_cx| -> Poll<()> { panic!("in task") });
+        let mut wrapped = Box::pin(Context::new().with("task", &1).wrap(future));
+        let waker = Waker::from(Arc::new(NoopWaker));
+        let mut cx = task::Context::from_waker(&waker);
+
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            wrapped.as_mut().poll(&mut cx)
+        }));
+        assert!(result.is_err());
+        assert_eq!(super::get("task"), None);
+    }
+}
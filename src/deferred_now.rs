@@ -1,35 +1,82 @@
+use chrono::TimeZone;
 use chrono::{
     format::{DelayedFormat, StrftimeItems},
     DateTime, Local, SecondsFormat, Utc,
 };
 #[cfg(feature = "syslog_writer")]
 use chrono::{Datelike, Timelike};
-use std::sync::{Mutex, OnceLock};
+use std::sync::{
+    atomic::{AtomicBool, AtomicI64, Ordering},
+    Arc, Mutex, OnceLock, PoisonError, RwLock,
+};
 
 /// Deferred timestamp creation.
 ///
 /// Is used to ensure that a log record that is sent to multiple outputs
 /// (in maybe different formats) always uses the same timestamp.
-#[derive(Debug, Default)]
-pub struct DeferredNow(Option<DateTime<Local>>);
+///
+/// `flexi_logger` creates a single instance per log record and hands it to all outputs
+/// of the record: the primary writer, the duplication to stderr and stdout,
+/// and all additional writers, including the syslog writer.
+/// The timestamp is generated when it is accessed for the first time,
+/// and all subsequent accesses return the same value.
+///
+/// An output that processes the record later, e.g. in another thread,
+/// can clone the instance after accessing the timestamp, to keep using the same value.
+///
+/// If [`Logger::use_sequence_numbers`](crate::Logger::use_sequence_numbers) is used,
+/// the instance also carries the sequence number of the log record.
+#[derive(Clone, Debug, Default)]
+pub struct DeferredNow {
+    o_now: Option<DateTime<Local>>,
+    o_sequence_number: Option<u64>,
+}
 impl<'a> DeferredNow {
     /// Constructs a new instance, but does not generate the timestamp.
     #[must_use]
     pub fn new() -> Self {
-        Self(None)
+        Self::default()
+    }
+
+    pub(crate) fn new_with_sequence_number(sequence_number: u64) -> Self {
+        Self {
+            o_now: None,
+            o_sequence_number: Some(sequence_number),
+        }
     }
 
     #[cfg(test)]
     #[must_use]
     fn new_from_datetime(dt: DateTime<Local>) -> Self {
-        Self(Some(dt))
+        Self {
+            o_now: Some(dt),
+            o_sequence_number: None,
+        }
+    }
+
+    /// Returns the sequence number of the log record, if the logger is configured with
+    /// [`Logger::use_sequence_numbers`](crate::Logger::use_sequence_numbers).
+    ///
+    /// The sequence numbers are increased with every log record that is handed over to the
+    /// logger; they allow ordering the log lines of different outputs deterministically.
+    /// Records that are dropped by a text filter or a [`LogLineFilter`](crate::filter::LogLineFilter)
+    /// consume a sequence number as well, so the written sequence numbers can have gaps.
+    #[must_use]
+    pub fn sequence_number(&self) -> Option<u64> {
+        self.o_sequence_number
     }
 
     /// Retrieve the timestamp for local time zone.
     ///
     /// Requires mutability because the first caller will generate the timestamp.
     pub fn now(&'a mut self) -> &'a DateTime<Local> {
-        self.0.get_or_insert_with(Local::now)
+        self.o_now.get_or_insert_with(|| {
+            if MONOTONIC.load(Ordering::Relaxed) {
+                monotonic(clock_now(), &LATEST_MICROS)
+            } else {
+                clock_now()
+            }
+        })
     }
 
     /// Retrieve the UTC timestamp.
@@ -127,6 +174,113 @@ impl<'a> DeferredNow {
             None => *cfg_force_utc = Some(true),
         }
     }
+
+    /// Ensure that the timestamps never go backwards.
+    ///
+    /// By default, the timestamps follow the system clock, also when it is set back,
+    /// e.g. by NTP or manually. By calling early in your program either
+    /// `Logger::use_monotonic_timestamps()` or directly this method, a timestamp that would be
+    /// earlier than the latest one is replaced by the latest one, until the clock has caught up.
+    pub fn force_monotonic() {
+        MONOTONIC.store(true, Ordering::Relaxed);
+    }
+
+    /// Replaces the system clock as source of the timestamps.
+    ///
+    /// This is necessary on targets where the system clock is not available,
+    /// like `wasm32-unknown-unknown`, where `chrono::Local::now()` panics;
+    /// the clock can then e.g. be based on `js_sys::Date::now()`.
+    ///
+    /// It also allows tests to simulate the passing of time, rather than sleeping:
+    /// the clock is not only used for the timestamps of the log lines, but also for
+    /// the rotation by age, for the timestamps in the names of rotated files,
+    /// as creation time of newly opened output files,
+    /// and for the age of the log files that the cleanup compares with a retention period.
+    ///
+    /// The clock provides UTC timestamps; they are converted to local time, if the
+    /// local time zone is known on the target.
+    ///
+    /// A later call replaces the clock that was set before.
+    /// See also [`Logger::clock`](crate::Logger::clock).
+    ///
+    /// ## Example
+    ///
+    /// ```rust
+    /// use chrono::{Duration, Utc};
+    /// use flexi_logger::DeferredNow;
+    /// use std::sync::{Arc, Mutex};
+    ///
+    /// let simulated_now = Arc::new(Mutex::new(Utc::now()));
+    /// let clock_now = Arc::clone(&simulated_now);
+    /// DeferredNow::set_clock(move || *clock_now.lock().unwrap());
+    ///
+    /// // let two hours pass
+    /// *simulated_now.lock().unwrap() += Duration::hours(2);
+    /// # DeferredNow::reset_clock();
+    /// ```
+    pub fn set_clock<F>(clock: F)
+    where
+        F: Fn() -> DateTime<Utc> + Send + Sync + 'static,
+    {
+        *CLOCK.write().unwrap_or_else(PoisonError::into_inner) = Some(Arc::new(clock));
+        CLOCK_IS_SET.store(true, Ordering::Release);
+    }
+
+    /// Makes the system clock again the source of the timestamps,
+    /// after a clock was set with [`DeferredNow::set_clock`].
+    pub fn reset_clock() {
+        CLOCK_IS_SET.store(false, Ordering::Release);
+        *CLOCK.write().unwrap_or_else(PoisonError::into_inner) = None;
+    }
+}
+
+pub(crate) type Clock = Arc<dyn Fn() -> DateTime<Utc> + Send + Sync>;
+
+static MONOTONIC: AtomicBool = AtomicBool::new(false);
+// allows skipping the lock as long as the system clock is used
+static CLOCK_IS_SET: AtomicBool = AtomicBool::new(false);
+
+static CLOCK: RwLock<Option<Clock>> = RwLock::new(None);
+
+// The current time, from the clock that was set with DeferredNow::set_clock,
+// or from the system clock.
+pub(crate) fn clock_now() -> DateTime<Local> {
+    if CLOCK_IS_SET.load(Ordering::Acquire) {
+        if let Some(clock) = CLOCK
+            .read()
+            .unwrap_or_else(PoisonError::into_inner)
+            .as_ref()
+        {
+            return clock().with_timezone(&Local);
+        }
+    }
+    Local::now()
+}
+
+// Whether a clock was set with DeferredNow::set_clock
+pub(crate) fn clock_is_set() -> bool {
+    CLOCK_IS_SET.load(Ordering::Acquire)
+}
+
+// the latest timestamp that was handed out, in microseconds since the epoch
+static LATEST_MICROS: AtomicI64 = AtomicI64::new(i64::MIN);
+
+// Returns the given timestamp, or the latest one if that is later.
+fn monotonic(ts: DateTime<Local>, latest_micros: &AtomicI64) -> DateTime<Local> {
+    let micros = ts.timestamp() * 1_000_000 + i64::from(ts.timestamp_subsec_micros());
+    let latest = latest_micros.fetch_max(micros, Ordering::Relaxed);
+    if latest > micros {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        Local
+            .timestamp_opt(
+                latest.div_euclid(1_000_000),
+                (latest.rem_euclid(1_000_000) * 1000) as u32,
+            )
+            .single()
+            .unwrap_or(ts)
+    } else {
+        ts
+    }
 }
 
 fn cfg_force_utc() -> &'static Mutex<Option<bool>> {
@@ -156,8 +310,32 @@ pub(crate) fn set_force_utc(b: bool) {
 mod test {
     use crate::DeferredNow;
     use chrono::{
-        DateTime, FixedOffset, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat, TimeZone, Utc,
+        DateTime, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime, SecondsFormat, TimeZone,
+        Utc,
     };
+    use std::sync::atomic::AtomicI64;
+
+    #[test]
+    fn test_monotonic() {
+        let latest_micros = AtomicI64::new(i64::MIN);
+        let ts = |s| {
+            Utc.with_ymd_and_hms(2024, 3, 7, 13, 45, s)
+                .unwrap()
+                .with_timezone(&Local)
+        };
+
+        assert_eq!(super::monotonic(ts(10), &latest_micros), ts(10));
+        assert_eq!(super::monotonic(ts(12), &latest_micros), ts(12));
+        // the clock is set back
+        assert_eq!(super::monotonic(ts(5), &latest_micros), ts(12));
+        assert_eq!(super::monotonic(ts(11), &latest_micros), ts(12));
+        // the clock has caught up
+        assert_eq!(super::monotonic(ts(13), &latest_micros), ts(13));
+
+        let with_micros = ts(20) + chrono::Duration::microseconds(123_456);
+        assert_eq!(super::monotonic(with_micros, &latest_micros), with_micros);
+        assert_eq!(super::monotonic(ts(14), &latest_micros), with_micros);
+    }
 
     #[test]
     fn test_timestamp_taken_only_once() {
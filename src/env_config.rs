@@ -0,0 +1,345 @@
+//! Builds a [`Logger`] from environment variables, see [`Logger::try_from_env_config`].
+use crate::{
+    Age, Cleanup, Criterion, Duplicate, FlexiLoggerError, FormatFunction, LogSpecification, Logger,
+    Naming, WriteMode,
+};
+use std::time::Duration;
+#[cfg(not(target_family = "wasm"))]
+use {crate::FileSpec, std::path::PathBuf};
+
+const CONF: &str = "FLEXI_LOGGER_CONF";
+const SPEC: &str = "RUST_LOG";
+const FILE: &str = "FLEXI_LOGGER_FILE";
+const ROTATE: &str = "FLEXI_LOGGER_ROTATE";
+const FORMAT: &str = "FLEXI_LOGGER_FORMAT";
+const DUPLICATE: &str = "FLEXI_LOGGER_DUPLICATE";
+const WRITE_MODE: &str = "FLEXI_LOGGER_WRITE_MODE";
+
+// The raw values, as found in the environment.
+#[derive(Debug, Default, PartialEq, Eq)]
+struct EnvConfig {
+    spec: Option<String>,
+    file: Option<String>,
+    rotate: Option<String>,
+    format: Option<String>,
+    duplicate: Option<String>,
+    write_mode: Option<String>,
+}
+impl EnvConfig {
+    // The entries of FLEXI_LOGGER_CONF are overridden by the dedicated variables.
+    fn from_env() -> Result<Self, FlexiLoggerError> {
+        let mut env_config = match std::env::var(CONF) {
+            Ok(conf) => Self::parse_conf(&conf)?,
+            Err(_) => Self::default(),
+        };
+        for (name, value) in [
+            (SPEC, &mut env_config.spec),
+            (FILE, &mut env_config.file),
+            (ROTATE, &mut env_config.rotate),
+            (FORMAT, &mut env_config.format),
+            (DUPLICATE, &mut env_config.duplicate),
+            (WRITE_MODE, &mut env_config.write_mode),
+        ] {
+            if let Ok(s) = std::env::var(name) {
+                *value = Some(s);
+            }
+        }
+        Ok(env_config)
+    }
+
+    // Parses `key=value` pairs, separated by `;`
+    fn parse_conf(conf: &str) -> Result<Self, FlexiLoggerError> {
+        let mut env_config = Self::default();
+        for entry in conf.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+            let (key, value) = entry
+                .split_once('=')
+                .ok_or_else(|| env_error(CONF, &format!("entry without '=': {entry}")))?;
+            let value = Some(value.trim().to_string());
+            match key.trim() {
+                "spec" => env_config.spec = value,
+                "file" => env_config.file = value,
+                "rotate" => env_config.rotate = value,
+                "format" => env_config.format = value,
+                "duplicate" => env_config.duplicate = value,
+                "write_mode" => env_config.write_mode = value,
+                key => return Err(env_error(CONF, &format!("unknown key: {key}"))),
+            }
+        }
+        Ok(env_config)
+    }
+
+    fn into_logger(self) -> Result<Logger, FlexiLoggerError> {
+        let spec = match self.spec {
+            Some(spec) => LogSpecification::parse(spec)?,
+            None => LogSpecification::default(),
+        };
+        let mut logger = Logger::with(spec);
+
+        #[cfg(not(target_family = "wasm"))]
+        if let Some(file) = self.file {
+            logger = logger.log_to_file(parse_file_spec(&file)?);
+        }
+        #[cfg(target_family = "wasm")]
+        if self.file.is_some() {
+            return Err(env_error(FILE, "files are not supported"));
+        }
+        if let Some(rotate) = self.rotate {
+            if !logger.logs_to_file() {
+                return Err(env_error(ROTATE, &format!("{FILE} is not specified")));
+            }
+            let (criterion, naming, cleanup) =
+                parse_rotation(&rotate).map_err(|e| env_error(ROTATE, &e))?;
+            logger = logger.rotate(criterion, naming, cleanup);
+        }
+        if let Some(format) = self.format {
+            logger = apply_format(logger, &format).map_err(|e| env_error(FORMAT, &e))?;
+        }
+        if let Some(duplicate) = self.duplicate {
+            logger = logger.duplicate_to_stderr(
+                duplicate
+                    .parse::<Duplicate>()
+                    .map_err(|_| env_error(DUPLICATE, &duplicate))?,
+            );
+        }
+        if let Some(write_mode) = self.write_mode {
+            logger = logger
+                .write_mode(parse_write_mode(&write_mode).map_err(|e| env_error(WRITE_MODE, &e))?);
+        }
+        Ok(logger)
+    }
+}
+
+pub(crate) fn logger_from_env() -> Result<Logger, FlexiLoggerError> {
+    EnvConfig::from_env()?.into_logger()
+}
+
+fn env_error(variable: &str, msg: &str) -> FlexiLoggerError {
+    FlexiLoggerError::EnvConfig(format!("{variable}: {msg}"))
+}
+
+// The following parsers are also used for the values of `LoggerConfig`;
+// they return only the error message, without the name of the variable.
+
+// A path that ends with a separator or is an existing directory is used as directory,
+// otherwise as path of the log file.
+#[cfg(not(target_family = "wasm"))]
+fn parse_file_spec(file: &str) -> Result<FileSpec, FlexiLoggerError> {
+    let path = PathBuf::from(file);
+    if file.ends_with(std::path::is_separator) || path.is_dir() {
+        Ok(FileSpec::default().directory(path))
+    } else {
+        FileSpec::try_from(path)
+    }
+}
+
+// <criterion>[,<naming>[,<cleanup>]]
+pub(crate) fn parse_rotation(rotate: &str) -> Result<(Criterion, Naming, Cleanup), String> {
+    let mut parts = rotate.split(',').map(str::trim);
+    let criterion = parse_criterion(parts.next().unwrap_or_default())?;
+    let naming = parse_naming(parts.next().unwrap_or("numbers"))?;
+    let cleanup = parse_cleanup(parts.next().unwrap_or("never"))?;
+    if let Some(part) = parts.next() {
+        return Err(format!("unexpected part: {part}"));
+    }
+    Ok((criterion, naming, cleanup))
+}
+
+// <size> | <age> | <age>|<size>
+pub(crate) fn parse_criterion(criterion: &str) -> Result<Criterion, String> {
+    match criterion.split_once('|') {
+        Some((age, size)) => Ok(Criterion::AgeOrSize(parse_age(age)?, parse_size(size)?)),
+        None => parse_age(criterion)
+            .map(Criterion::Age)
+            .or_else(|_| parse_size(criterion).map(Criterion::Size)),
+    }
+}
+
+fn parse_age(age: &str) -> Result<Age, String> {
+    match age.trim() {
+        "month" => Ok(Age::Month),
+        "week" => Ok(Age::Week(chrono::Weekday::Mon)),
+        "day" => Ok(Age::Day),
+        "hour" => Ok(Age::Hour),
+        "minute" => Ok(Age::Minute),
+        "second" => Ok(Age::Second),
+        age => Err(format!("unknown age: {age}")),
+    }
+}
+
+// A number of bytes, optionally with one of the factors K, M, G (as powers of 1024)
+fn parse_size(size: &str) -> Result<u64, String> {
+    let size = size.trim();
+    let (number, factor) = match size.char_indices().last() {
+        Some((idx, 'k' | 'K')) => (&size[..idx], 1024),
+        Some((idx, 'm' | 'M')) => (&size[..idx], 1024 * 1024),
+        Some((idx, 'g' | 'G')) => (&size[..idx], 1024 * 1024 * 1024),
+        _ => (size, 1),
+    };
+    number
+        .trim()
+        .parse::<u64>()
+        .ok()
+        .and_then(|n| n.checked_mul(factor))
+        .ok_or_else(|| format!("invalid criterion: {size}"))
+}
+
+pub(crate) fn parse_naming(naming: &str) -> Result<Naming, String> {
+    match naming.trim() {
+        "numbers" => Ok(Naming::Numbers),
+        "numbers_direct" => Ok(Naming::NumbersDirect),
+        "numbers_shifted" => Ok(Naming::NumbersShifted),
+        "timestamps" => Ok(Naming::Timestamps),
+        "timestamps_direct" => Ok(Naming::TimestampsDirect),
+        naming => Err(format!("unknown naming: {naming}")),
+    }
+}
+
+pub(crate) fn parse_cleanup(cleanup: &str) -> Result<Cleanup, String> {
+    let mut parts = cleanup.split(':').map(str::trim);
+    let variant = parts.next().unwrap_or_default();
+    let mut next_number = || {
+        parts
+            .next()
+            .and_then(|n| n.parse::<usize>().ok())
+            .ok_or_else(|| format!("invalid cleanup: {cleanup}"))
+    };
+    match variant {
+        "never" => Ok(Cleanup::Never),
+        "keep_log_files" => Ok(Cleanup::KeepLogFiles(next_number()?)),
+        "keep_log_files_and_duration" => Ok(Cleanup::KeepLogFilesAndDuration(
+            next_number()?,
+            Duration::from_secs(next_number()? as u64),
+        )),
+        #[cfg(feature = "compress")]
+        "keep_compressed_files" => Ok(Cleanup::KeepCompressedFiles(next_number()?)),
+        #[cfg(feature = "compress")]
+        "keep_log_and_compressed_files" => Ok(Cleanup::KeepLogAndCompressedFiles(
+            next_number()?,
+            next_number()?,
+        )),
+        _ => Err(format!("unknown cleanup: {cleanup}")),
+    }
+}
+
+// Colored formats are only used for stderr and stdout.
+pub(crate) fn apply_format(logger: Logger, format: &str) -> Result<Logger, String> {
+    let format_function: FormatFunction = match format {
+        "default" => crate::default_format,
+        "opt" => crate::opt_format,
+        "detailed" => crate::detailed_format,
+        "with_thread" => crate::with_thread,
+        "with_thread_id" => crate::with_thread_id,
+        "with_target" => crate::with_target,
+        #[cfg(feature = "json")]
+        "json" => crate::json_format,
+        #[cfg(feature = "colors")]
+        "colored_default"
+        | "colored_opt"
+        | "colored_detailed"
+        | "colored_with_thread"
+        | "colored_with_thread_id"
+        | "colored_with_target" => {
+            let colored_format: FormatFunction = match format {
+                "colored_default" => crate::colored_default_format,
+                "colored_opt" => crate::colored_opt_format,
+                "colored_detailed" => crate::colored_detailed_format,
+                "colored_with_thread" => crate::colored_with_thread,
+                "colored_with_thread_id" => crate::colored_with_thread_id,
+                _ => crate::colored_with_target,
+            };
+            return Ok(logger
+                .format_for_stderr(colored_format)
+                .format_for_stdout(colored_format));
+        }
+        format => return Err(format!("unknown format: {format}")),
+    };
+    Ok(logger.format(format_function))
+}
+
+pub(crate) fn parse_write_mode(write_mode: &str) -> Result<WriteMode, String> {
+    match write_mode {
+        "direct" => Ok(WriteMode::Direct),
+        "support_capture" => Ok(WriteMode::SupportCapture),
+        "buffer_and_flush" => Ok(WriteMode::BufferAndFlush),
+        "buffer_dont_flush" => Ok(WriteMode::BufferDontFlush),
+        #[cfg(feature = "async")]
+        "async" => Ok(WriteMode::Async),
+        write_mode => Err(format!("unknown write mode: {write_mode}")),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{parse_criterion, parse_rotation, parse_size, EnvConfig};
+    use crate::{Age, Cleanup, Criterion, Naming};
+    use std::time::Duration;
+
+    #[test]
+    fn test_parse_conf() {
+        let env_config = EnvConfig::parse_conf(
+            "spec = info, my_mod=trace; file=/var/log/app/;rotate=10M,numbers,keep_log_files:7 ;",
+        )
+        .unwrap();
+        assert_eq!(
+            env_config,
+            EnvConfig {
+                spec: Some("info, my_mod=trace".to_string()),
+                file: Some("/var/log/app/".to_string()),
+                rotate: Some("10M,numbers,keep_log_files:7".to_string()),
+                ..EnvConfig::default()
+            }
+        );
+        assert!(EnvConfig::parse_conf("spec").is_err());
+        assert!(EnvConfig::parse_conf("colour=blue").is_err());
+    }
+
+    #[test]
+    fn test_parse_rotation() {
+        assert_eq!(parse_size("1000").unwrap(), 1000);
+        assert_eq!(parse_size("10k").unwrap(), 10 * 1024);
+        assert_eq!(parse_size("2 M").unwrap(), 2 * 1024 * 1024);
+        assert!(parse_size("M").is_err());
+        assert!(parse_size("99999999999G").is_err());
+
+        assert!(matches!(
+            parse_criterion("day|1G").unwrap(),
+            Criterion::AgeOrSize(Age::Day, 1_073_741_824)
+        ));
+        assert!(matches!(
+            parse_criterion("hour").unwrap(),
+            Criterion::Age(Age::Hour)
+        ));
+        assert!(matches!(
+            parse_criterion("week|10M").unwrap(),
+            Criterion::AgeOrSize(Age::Week(chrono::Weekday::Mon), 10_485_760)
+        ));
+        assert!(matches!(
+            parse_criterion("month").unwrap(),
+            Criterion::Age(Age::Month)
+        ));
+        assert!(parse_criterion("fortnight").is_err());
+
+        assert!(matches!(
+            parse_rotation("10M").unwrap(),
+            (Criterion::Size(10_485_760), Naming::Numbers, Cleanup::Never)
+        ));
+        assert!(matches!(
+            parse_rotation("minute, timestamps_direct, keep_log_files:3").unwrap(),
+            (
+                Criterion::Age(Age::Minute),
+                Naming::TimestampsDirect,
+                Cleanup::KeepLogFiles(3)
+            )
+        ));
+        assert!(matches!(
+            parse_rotation("day, numbers, keep_log_files_and_duration:5:3600").unwrap(),
+            (
+                Criterion::Age(Age::Day),
+                Naming::Numbers,
+                Cleanup::KeepLogFilesAndDuration(5, retention)
+            ) if retention == Duration::from_secs(3600)
+        ));
+        assert!(parse_rotation("10M,numbers,keep_log_files").is_err());
+        assert!(parse_rotation("10M,numbers,never,more").is_err());
+    }
+}
@@ -21,6 +21,33 @@
 //! It is then dropped immediately, and in its `Drop` impl it cleans up all resources,
 //! including the asynchronous writer. So the next log output will fail with this error.
 //!
+//! The [`StdSinkWriter`](crate::writers::StdSinkWriter) also reports with this error code
+//! when its child process has exited and is restarted.
+//!
+//! If writing to a log file fails repeatedly, e.g. because the disk is full, this error is
+//! reported for every log line. A [`WriteFailurePolicy`](crate::WriteFailurePolicy) lets the
+//! file log writer report only once that it switches to a degraded mode
+//! (and once more with [`WriteResumed`](#writeresumed) when writing works again).
+//!
+//! Likewise, a [`CircuitBreaker`](crate::CircuitBreaker) lets the logger report only once
+//! that it skips a failing additional writer
+//! (and once more with [`WriteResumed`](#writeresumed) when the writer works again).
+//!
+//! The [`ChildOutputLogger`](crate::writers::ChildOutputLogger) reports with this error code
+//! if reading the output of a child process fails.
+//!
+//! ## `WriteResumed`
+//!
+//! An output for which writing failed before works again; this is not an error,
+//! but a notice that complements the earlier error report.
+//!
+//! ```text
+//! [flexi_logger][ERRCODE::WriteResumed] writing to the log file works again, after 3.2s; ...
+//! ```
+//!
+//! This can only occur if you use a [`WriteFailurePolicy`](crate::WriteFailurePolicy)
+//! or a [`CircuitBreaker`](crate::CircuitBreaker), see [`Write`](#write).
+//!
 //! ## `Flush`
 //!
 //! Explicit or automatic flushing of buffered log lines to the output failed.
@@ -58,10 +85,34 @@
 //! A typical root cause for this is some `panic!` in a `Debug` or `Display` implementation
 //! of a logged object.
 //!
+//! ## `Archive`
+//!
+//! The callback that was registered with `Logger::cleanup_archiver` failed to archive
+//! a log file that the cleanup was going to delete. The file is kept, and archiving it is
+//! retried with the next cleanup. The reason should be printed as well.
+//!
+//! ## `Cleanup`
+//!
+//! The cleanup of log files failed, or its background thread died, e.g. due to a panic in
+//! the archiver callback, and was restarted. The reason should be printed as well.
+//! See `LoggerHandle::cleanup_status` and `Logger::on_cleanup_failure`
+//! for monitoring the cleanup programmatically.
+//!
 //! ## `LogFile`
 //!
 //! The `FileLogWriter` is not able to rotate the log file. The reason should be printed as well.
 //!
+//! With `Logger::reopen_on_sighup`, this error is also reported if re-opening the output
+//! after a `SIGHUP` failed.
+//!
+//! With `Logger::manifest`, this error is also reported if writing the manifest failed.
+//!
+//! With `Logger::fallback`, this error is reported once when the log files can't be written
+//! where they are configured and the logger switches to a fallback.
+//!
+//! With `Naming::NumbersShifted`, this error is also reported for a rotated file whose number
+//! is too big to be increased; such a file is not shifted.
+//!
 //! ## `LogFileWatcher`
 //!
 //! The `FileLogWriter` is not able to watch the log file. The reason should be printed as well.
@@ -88,13 +139,34 @@
 //!
 //! Watching the log-specification-file failed.
 //!
+//! ## `Recovery`
+//!
+//! This error can only occur if you use `Logger::recover_on_append`.
+//!
+//! ```text
+//! [flexi_logger][ERRCODE::Recovery] removed 17 bytes of an incomplete last line from ...
+//! ```
+//!
+//! The log file that is appended ended with an incomplete line, likely because a previous
+//! run of the program crashed while writing to it. The incomplete line was removed.
+//!
+//! ```text
+//! [flexi_logger][ERRCODE::Recovery] repairing the log file failed, caused by ...
+//! ```
+//!
+//! The log file could not be checked or repaired; the program continues to append to it.
+//!
 //! ## `Symlink`
 //!
-//! This error can only occur on unix systems, and when you use `Logger::create_symlink`, and
-//! indicates an issue with creating or replacing the symbolic link to the log file.
+//! This error can only occur when you use `Logger::create_symlink`, and
+//! indicates an issue with creating or replacing the symbolic link to the log file
+//! (or, on windows, of its replacements).
 //!
 //! ## `WriterSpec`
 //!
 //! The code uses in some log macro call the syntax to send the log line to a certain `LogWriter`,
 //! but this log writer does not exist.
 //!
+//! This is only reported if the same log call also addresses an existing `LogWriter`
+//! or `_Default` or `_All`; otherwise the target is not considered as a list of writers.
+//!
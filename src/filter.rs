@@ -1,5 +1,7 @@
 //! This module contains two traits which allow adding a stateful filter
-//! using [`Logger::filter`](crate::Logger::filter).
+//! using [`Logger::filter`](crate::Logger::filter), and the trait [`LogLineRewriter`],
+//! which allows modifying the messages, e.g. for redacting sensitive data,
+//! using [`Logger::rewriter`](crate::Logger::rewriter).
 //!
 //! # Example
 //!
@@ -37,6 +39,15 @@
 //! ```
 use crate::DeferredNow;
 use log::Record;
+#[cfg(feature = "textfilter")]
+use regex::{Regex, RegexSet};
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Mutex,
+    },
+};
 
 /// Trait of the filter object.
 #[allow(clippy::module_name_repetitions)]
@@ -67,3 +78,369 @@ pub trait LogLineWriter {
     /// If writing to the configured output channel fails.
     fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()>;
 }
+
+/// Trait of objects that can modify the message of log records before they are written.
+///
+/// Other than a [`LogLineFilter`], a rewriter is applied to all outputs of a log record,
+/// including the additional writers.
+///
+/// # Example
+///
+/// ```rust
+/// use flexi_logger::filter::LogLineRewriter;
+///
+/// pub struct NoPasswords;
+/// impl LogLineRewriter for NoPasswords {
+///     fn rewrite(&self, record: &log::Record) -> Option<String> {
+///         let text = record.args().to_string();
+///         text.contains("password").then(|| text.replace("password", "********"))
+///     }
+/// }
+/// ```
+#[allow(clippy::module_name_repetitions)]
+pub trait LogLineRewriter {
+    /// Returns the modified message of the log record,
+    /// or `None` if the log record is to be written unchanged.
+    fn rewrite(&self, record: &Record) -> Option<String>;
+
+    /// Returns the modified value of a key-value pair of the log record,
+    /// or `None` if the value is to be written unchanged.
+    ///
+    /// The default implementation leaves all values unchanged.
+    /// If a value is modified, all values of the record are handed over to the outputs
+    /// as strings.
+    #[cfg_attr(docsrs, doc(cfg(feature = "kv")))]
+    #[cfg(feature = "kv")]
+    fn rewrite_value(&self, _key: &str, _value: &str) -> Option<String> {
+        None
+    }
+}
+
+/// A [`LogLineRewriter`] that replaces all matches of a list of regular expressions,
+/// in the message and in the values of the key-value pairs.
+///
+/// The regular expressions are compiled once, when the `Redactor` is created,
+/// and texts without any match are not copied.
+///
+/// ```rust
+/// # use flexi_logger::{filter::Redactor, Logger};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// Logger::try_with_str("info")?
+///     .rewriter(Box::new(Redactor::new(&[
+///         (r"\b\d{4}([ -]?\d{4}){2}[ -]?(\d{4})\b", "****-****-****-$2"),
+///         (r"api_key=\S+", "api_key=<redacted>"),
+///     ])?))
+///     .start()?;
+/// log::info!("Charged card 4111 1111 1111 1234"); // -> Charged card ****-****-****-1234
+/// # Ok(())
+/// # }
+/// ```
+#[cfg_attr(docsrs, doc(cfg(feature = "textfilter")))]
+#[cfg(feature = "textfilter")]
+#[derive(Debug)]
+pub struct Redactor {
+    regex_set: RegexSet,
+    rules: Vec<(Regex, String)>,
+    hashing: bool,
+}
+#[cfg(feature = "textfilter")]
+impl Redactor {
+    /// Creates a `Redactor` that replaces all matches of the given regular expressions
+    /// with the respective replacement.
+    ///
+    /// The replacements can refer to capture groups of the regular expression,
+    /// like `$1` or `${name}`, see [`regex::Regex::replace_all`].
+    ///
+    /// # Errors
+    ///
+    /// If one of the patterns is not a valid regular expression.
+    pub fn new<P: AsRef<str>, R: AsRef<str>>(rules: &[(P, R)]) -> Result<Self, regex::Error> {
+        Ok(Self {
+            regex_set: RegexSet::new(rules.iter().map(|(pattern, _)| pattern.as_ref()))?,
+            rules: rules
+                .iter()
+                .map(|(pattern, replacement)| {
+                    Regex::new(pattern.as_ref())
+                        .map(|regex| (regex, replacement.as_ref().to_string()))
+                })
+                .collect::<Result<_, _>>()?,
+            hashing: false,
+        })
+    }
+
+    /// Makes the `Redactor` append a hash of the matched text to the replacement,
+    /// like in `<card>:c3ab8ff13720e8ad`.
+    ///
+    /// This allows correlating log lines that contain the same sensitive value,
+    /// without revealing the value. The hash is stable across program runs,
+    /// but it is not a cryptographic hash.
+    #[must_use]
+    pub fn hashing(mut self) -> Self {
+        self.hashing = true;
+        self
+    }
+
+    /// Returns the redacted text, or `None` if the text contains nothing to redact.
+    #[must_use]
+    pub fn redact(&self, text: &str) -> Option<String> {
+        let matches = self.regex_set.matches(text);
+        if !matches.matched_any() {
+            return None;
+        }
+        let mut text = text.to_string();
+        for index in &matches {
+            let (regex, replacement) = &self.rules[index];
+            let redacted = if self.hashing {
+                regex.replace_all(&text, |captures: &regex::Captures| {
+                    let mut redacted = String::new();
+                    captures.expand(replacement, &mut redacted);
+                    format!("{redacted}:{:016x}", fnv1a(&captures[0]))
+                })
+            } else {
+                regex.replace_all(&text, replacement.as_str())
+            };
+            if let std::borrow::Cow::Owned(redacted) = redacted {
+                text = redacted;
+            }
+        }
+        Some(text)
+    }
+}
+#[cfg(feature = "textfilter")]
+impl LogLineRewriter for Redactor {
+    fn rewrite(&self, record: &Record) -> Option<String> {
+        self.redact(&record.args().to_string())
+    }
+
+    #[cfg(feature = "kv")]
+    fn rewrite_value(&self, _key: &str, value: &str) -> Option<String> {
+        self.redact(value)
+    }
+}
+
+/// A [`LogLineFilter`] that passes only a fraction of the verbose log records.
+///
+/// This allows keeping e.g. trace-level instrumentation enabled in production,
+/// at bounded cost.
+/// Log records with level `Warn` or `Error` are always passed; the log records with
+/// the chosen level or a more verbose level are passed either with a given probability,
+/// or every n-th record of each module.
+///
+/// ```rust
+/// # use flexi_logger::{filter::Sampler, Logger};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// Logger::try_with_str("trace")?
+///     // write only every 100th debug or trace record of each module
+///     .filter(Box::new(Sampler::every_nth(100, log::Level::Debug)))
+///     .start()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug)]
+pub struct Sampler {
+    level: log::Level,
+    rate: SampleRate,
+}
+#[derive(Debug)]
+enum SampleRate {
+    EveryNth(u64, Mutex<HashMap<String, u64>>),
+    // threshold for the output of the random number generator, and its state
+    Probability(u64, AtomicU64),
+}
+impl Sampler {
+    /// Creates a `Sampler` that passes the first and then every n-th log record of each module
+    /// that has the given level or a more verbose level.
+    ///
+    /// With `n` = 0 or 1, all log records are passed.
+    #[must_use]
+    pub fn every_nth(n: u64, level: log::Level) -> Self {
+        Self {
+            level,
+            rate: SampleRate::EveryNth(n.max(1), Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Creates a `Sampler` that passes the log records that have the given level or a more
+    /// verbose level with the given probability.
+    ///
+    /// `probability` is clamped to the range `0.0..=1.0`.
+    #[must_use]
+    pub fn with_probability(probability: f64, level: log::Level) -> Self {
+        #[allow(
+            clippy::cast_possible_truncation,
+            clippy::cast_precision_loss,
+            clippy::cast_sign_loss
+        )]
+        let threshold = (probability.clamp(0.0, 1.0) * u64::MAX as f64) as u64;
+        let seed = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map_or(0, |d| d.as_secs() ^ u64::from(d.subsec_nanos()));
+        Self {
+            level,
+            rate: SampleRate::Probability(threshold, AtomicU64::new(seed)),
+        }
+    }
+
+    fn is_sampled(&self, record: &Record) -> bool {
+        if record.level() <= log::Level::Warn || record.level() < self.level {
+            return true;
+        }
+        match self.rate {
+            SampleRate::EveryNth(n, ref m_counters) => {
+                let module = record.module_path().unwrap_or_default();
+                let Ok(mut counters) = m_counters.lock() else {
+                    return true;
+                };
+                let counter = if let Some(counter) = counters.get_mut(module) {
+                    counter
+                } else {
+                    counters.entry(module.to_string()).or_insert(0)
+                };
+                let is_sampled = *counter % n == 0;
+                *counter = counter.wrapping_add(1);
+                is_sampled
+            }
+            SampleRate::Probability(threshold, ref state) => {
+                // splitmix64
+                let mut z = state
+                    .fetch_add(0x9e37_79b9_7f4a_7c15, Ordering::Relaxed)
+                    .wrapping_add(0x9e37_79b9_7f4a_7c15);
+                z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+                z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+                z ^= z >> 31;
+                threshold == u64::MAX || z < threshold
+            }
+        }
+    }
+}
+impl LogLineFilter for Sampler {
+    fn write(
+        &self,
+        now: &mut DeferredNow,
+        record: &Record,
+        log_line_writer: &dyn LogLineWriter,
+    ) -> std::io::Result<()> {
+        if self.is_sampled(record) {
+            log_line_writer.write(now, record)?;
+        }
+        Ok(())
+    }
+}
+
+// 64-bit FNV-1a, which is, other than std's DefaultHasher, stable across Rust versions
+#[cfg(feature = "textfilter")]
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "textfilter")]
+    use super::Redactor;
+    use super::{LogLineFilter, LogLineWriter, Sampler};
+    use crate::DeferredNow;
+    use log::{Level, Record};
+    use std::sync::Mutex;
+
+    #[cfg(feature = "textfilter")]
+    #[test]
+    fn test_redactor() {
+        let redactor = Redactor::new(&[
+            (r"\b\d{4}([ -]?\d{4}){2}[ -]?(\d{4})\b", "****-****-****-$2"),
+            (r"api_key=\S+", "api_key=<redacted>"),
+        ])
+        .unwrap();
+
+        assert_eq!(redactor.redact("nothing to hide"), None);
+        assert_eq!(
+            redactor.redact("card 4111-1111-1111-1234 with api_key=abc$1 used"),
+            Some("card ****-****-****-1234 with api_key=<redacted> used".to_string())
+        );
+        assert!(Redactor::new(&[("(unclosed", "")]).is_err());
+    }
+
+    #[cfg(feature = "textfilter")]
+    #[test]
+    fn test_redactor_with_hashing() {
+        let redactor = Redactor::new(&[(r"user=\w+", "<user>")]).unwrap().hashing();
+
+        let first = redactor.redact("login of user=alice").unwrap();
+        assert!(first.starts_with("login of <user>:"), "{first}");
+        assert_eq!(first.len(), "login of <user>:".len() + 16);
+        assert_eq!(
+            redactor.redact("logout of user=alice").unwrap()[10..],
+            first[9..]
+        );
+        assert_ne!(redactor.redact("login of user=bob").unwrap(), first);
+    }
+
+    struct CountingWriter(Mutex<Vec<Level>>);
+    impl LogLineWriter for CountingWriter {
+        fn write(&self, _now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+            self.0.lock().unwrap().push(record.level());
+            Ok(())
+        }
+    }
+
+    fn count(sampler: &Sampler, level: Level, module: &str, records: usize) -> usize {
+        let writer = CountingWriter(Mutex::new(Vec::new()));
+        for _ in 0..records {
+            sampler
+                .write(
+                    &mut DeferredNow::new(),
+                    &Record::builder()
+                        .level(level)
+                        .module_path(Some(module))
+                        .args(format_args!("test"))
+                        .build(),
+                    &writer,
+                )
+                .unwrap();
+        }
+        let count = writer.0.lock().unwrap().len();
+        count
+    }
+
+    #[test]
+    fn test_sampler_every_nth() {
+        let sampler = Sampler::every_nth(10, Level::Debug);
+        assert_eq!(count(&sampler, Level::Error, "a", 25), 25);
+        assert_eq!(count(&sampler, Level::Info, "a", 25), 25);
+        assert_eq!(count(&sampler, Level::Debug, "a", 25), 3);
+        assert_eq!(count(&sampler, Level::Trace, "a", 5), 0);
+        assert_eq!(count(&sampler, Level::Trace, "b", 5), 1);
+
+        let sampler = Sampler::every_nth(10, Level::Error);
+        assert_eq!(count(&sampler, Level::Warn, "a", 25), 25);
+        assert_eq!(count(&sampler, Level::Info, "a", 25), 3);
+    }
+
+    #[test]
+    fn test_sampler_with_probability() {
+        let sampler = Sampler::with_probability(0.1, Level::Trace);
+        assert_eq!(count(&sampler, Level::Debug, "a", 100), 100);
+        let passed = count(&sampler, Level::Trace, "a", 10_000);
+        assert!((700..1300).contains(&passed), "{passed}");
+
+        assert_eq!(
+            count(
+                &Sampler::with_probability(0.0, Level::Trace),
+                Level::Trace,
+                "a",
+                100
+            ),
+            0
+        );
+        assert_eq!(
+            count(
+                &Sampler::with_probability(1.0, Level::Trace),
+                Level::Trace,
+                "a",
+                100
+            ),
+            100
+        );
+    }
+}
@@ -26,12 +26,33 @@ pub enum FlexiLoggerError {
     #[error("Log file cannot be written because the specified path is a directory")]
     OutputBadFile,
 
+    /// Log file cannot be written because another `FileLogWriter` of this process
+    /// writes to the same files.
+    ///
+    /// Contains the path of the log file (without infix).
+    #[error("Log file {0} is already written by another FileLogWriter of this process")]
+    OutputInUse(std::path::PathBuf),
+
     /// Spawning the cleanup thread failed.
     ///
     /// This error can safely be avoided with `Logger::cleanup_in_background_thread(false)`.
     #[error("Spawning the cleanup thread failed.")]
     OutputCleanupThread(std::io::Error),
 
+    /// The format of the timestamp infix in
+    /// [`Naming::TimestampsCustomFormat`](crate::Naming::TimestampsCustomFormat) is not usable.
+    ///
+    /// The String describes the problems.
+    #[error("Invalid format for timestamp infixes: {0}")]
+    BadNamingFormat(String),
+
+    /// A name part of the [`FileSpec`](crate::FileSpec) contains characters or names
+    /// that are not usable in file names.
+    ///
+    /// The String describes the problems.
+    #[error("Invalid file specification: {0}")]
+    BadFileSpec(String),
+
     /// Log cannot be written, e.g. because the configured output directory is not accessible.
     #[error(
         "Log cannot be written, e.g. because the configured output directory is not accessible"
@@ -67,6 +88,16 @@ pub enum FlexiLoggerError {
     #[error("Failed to parse log specification: {0}")]
     Parse(String, LogSpecification),
 
+    /// The configuration in the environment variables is invalid.
+    #[error("Invalid configuration in the environment: {0}")]
+    EnvConfig(String),
+
+    /// The given [`LoggerConfig`](crate::LoggerConfig) is invalid.
+    #[cfg(feature = "config")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "config")))]
+    #[error("Invalid logger configuration: {0}")]
+    Config(String),
+
     /// Logger initialization failed.
     #[error("Logger initialization failed")]
     Log(#[from] log::SetLoggerError),
@@ -75,10 +106,25 @@ pub enum FlexiLoggerError {
     #[error("Some synchronization object is poisoned")]
     Poison,
 
+    /// Some writers failed their healthcheck, see
+    /// [`LoggerHandle::healthcheck`](crate::LoggerHandle::healthcheck).
+    ///
+    /// Contains the name of each failing writer, together with its error.
+    #[error("Writers are not healthy: {}", names_of(.0))]
+    Unhealthy(Vec<(String, FlexiLoggerError)>),
+
     /// Palette parsing failed
+    #[deprecated(since = "0.29.9", note = "is not used anymore, see `BadPalette`")]
     #[error("Palette parsing failed")]
     Palette(#[from] std::num::ParseIntError),
 
+    /// The palette given to [`Logger::set_palette`](crate::Logger::set_palette)
+    /// or in the environment variable `FLEXI_LOGGER_PALETTE` cannot be parsed.
+    ///
+    /// The String describes the problem.
+    #[error("Invalid palette: {0}")]
+    BadPalette(String),
+
     /// Logger is shut down.
     #[cfg(feature = "async")]
     #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
@@ -92,6 +138,205 @@ pub enum FlexiLoggerError {
     TracingSetup(#[from] tracing::subscriber::SetGlobalDefaultError),
 }
 
+impl FlexiLoggerError {
+    /// Renders a detailed, multi-line description of the error.
+    ///
+    /// Other than the one-line `Display` output, the rendering contains, as far as known,
+    /// the underlying causes, the offending value, the method with which the value was set,
+    /// and a suggestion how to solve the problem.
+    /// This is meant for reporting configuration problems at startup.
+    ///
+    /// ```rust
+    /// # use flexi_logger::Logger;
+    /// if let Err(e) = Logger::try_with_str("info, my_crate = verbose") {
+    ///     eprintln!("{}", e.render_verbose());
+    /// }
+    /// ```
+    #[must_use]
+    pub fn render_verbose(&self) -> String {
+        let mut diagnostic = self.diagnostic();
+        let mut lines = vec![self.to_string()];
+
+        let mut o_source = std::error::Error::source(self);
+        while let Some(source) = o_source {
+            if let Some(io_context) = source
+                .downcast_ref::<std::io::Error>()
+                .and_then(std::io::Error::get_ref)
+                .and_then(|inner| inner.downcast_ref::<IoContext>())
+            {
+                diagnostic.o_value = Some(io_context.value.clone());
+                diagnostic.o_method = Some(io_context.method);
+                diagnostic.o_suggestion = Some(io_context.suggestion);
+            }
+            lines.push(format!("caused by: {source}"));
+            o_source = source.source();
+        }
+        lines.extend(diagnostic.details);
+        if let Some(value) = diagnostic.o_value {
+            lines.push(format!("value: {value}"));
+        }
+        if let Some(method) = diagnostic.o_method {
+            lines.push(format!("set with: {method}"));
+        }
+        if let Some(suggestion) = diagnostic.o_suggestion {
+            lines.push(format!("help: {suggestion}"));
+        }
+        lines.join("\n  ")
+    }
+
+    fn diagnostic(&self) -> Diagnostic {
+        let mut diagnostic = Diagnostic::default();
+        match self {
+            Self::OutputBadDirectory => {
+                diagnostic.o_method = Some("FileSpec::directory");
+                diagnostic.o_suggestion =
+                    Some("Specify a path that is a directory, or that does not exist yet.");
+            }
+            Self::OutputBadFile => {
+                diagnostic.o_method = Some("FileSpec::try_from");
+                diagnostic.o_suggestion = Some("Specify the path of a file, not of a directory.");
+            }
+            Self::OutputInUse(_) => {
+                diagnostic.o_method = Some("FileSpec::basename or FileSpec::discriminant");
+                diagnostic.o_suggestion = Some(
+                    "Give each FileLogWriter its own file names, e.g. with different discriminants, \
+                     or share one FileLogWriter, e.g. via FileLogWriterBuilder::try_build_with_handle.",
+                );
+            }
+            Self::OutputCleanupThread(_) => {
+                diagnostic.o_suggestion = Some("Use Logger::cleanup_in_background_thread(false).");
+            }
+            Self::OutputIo(_) => {
+                diagnostic.o_suggestion =
+                    Some("Check that the output directory exists and can be written.");
+            }
+            Self::BadNamingFormat(_) => {
+                diagnostic.o_method = Some("Naming::TimestampsCustomFormat");
+                diagnostic.o_suggestion = Some(
+                    "Use strftime specifiers that produce characters that are valid \
+                     in file names, like \"%Y-%m-%d_%H-%M-%S\".",
+                );
+            }
+            Self::BadFileSpec(_) => {
+                diagnostic.o_method =
+                    Some("FileSpec::basename, FileSpec::discriminant, or FileSpec::suffix");
+                diagnostic.o_suggestion =
+                    Some("Remove the offending characters, or use FileSpec::sanitize.");
+            }
+            #[cfg(feature = "specfile_without_notification")]
+            Self::SpecfileToml(_) | Self::SpecfileIo(_) | Self::SpecfileExtension(_) => {
+                diagnostic.o_method = Some("Logger::start_with_specfile");
+                diagnostic.o_suggestion =
+                    Some("Use an accessible file with extension \"toml\" as specfile.");
+            }
+            Self::LevelFilter(value) => {
+                diagnostic.o_value = Some(value.clone());
+                diagnostic.o_suggestion = Some(
+                    "Use one of off, error, warn, info, debug, trace, or a number from 0 to 5.",
+                );
+            }
+            Self::Parse(_, logspec) => {
+                diagnostic.details = logspec
+                    .issues()
+                    .iter()
+                    .map(|issue| {
+                        let severity = if issue.is_error() { "error" } else { "warning" };
+                        format!("{severity}: {issue}")
+                    })
+                    .collect();
+                diagnostic.o_method = Some(
+                    "Logger::try_with_str, Logger::try_with_env_or_str, \
+                     or LogSpecification::parse",
+                );
+                diagnostic.o_suggestion = Some(
+                    "Use a comma-separated list of a default level and `module = level` parts, \
+                     like \"info, my_crate::net = debug\".",
+                );
+            }
+            Self::EnvConfig(_) => {
+                diagnostic.o_method = Some("Logger::try_from_env_config");
+            }
+            #[cfg(feature = "config")]
+            Self::Config(_) => {
+                diagnostic.o_method = Some("Logger::try_from_config");
+            }
+            Self::Log(_) => {
+                diagnostic.o_suggestion =
+                    Some("Start the logger only once, and use the LoggerHandle to reconfigure it.");
+            }
+            Self::Unhealthy(unhealthy) => {
+                diagnostic.details = unhealthy
+                    .iter()
+                    .map(|(name, e)| match std::error::Error::source(e) {
+                        Some(source) => format!("{name}: {e}: {source}"),
+                        None => format!("{name}: {e}"),
+                    })
+                    .collect();
+            }
+            #[allow(deprecated)]
+            Self::Palette(_) | Self::BadPalette(_) => {
+                diagnostic.o_method =
+                    Some("Logger::set_palette, or environment variable FLEXI_LOGGER_PALETTE");
+                diagnostic.o_suggestion =
+                    Some("Use five ';'-separated colors, like \"196;208;-;7;8\".");
+            }
+            _ => {}
+        }
+        diagnostic
+    }
+}
+
+fn names_of(unhealthy: &[(String, FlexiLoggerError)]) -> String {
+    unhealthy
+        .iter()
+        .map(|(name, _)| name.as_str())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[derive(Default)]
+struct Diagnostic {
+    details: Vec<String>,
+    o_value: Option<String>,
+    o_method: Option<&'static str>,
+    o_suggestion: Option<&'static str>,
+}
+
+// Carries the context of a failed io operation through a std::io::Error,
+// so that it can be shown by FlexiLoggerError::render_verbose.
+#[derive(Debug)]
+struct IoContext {
+    value: String,
+    method: &'static str,
+    suggestion: &'static str,
+    source: std::io::Error,
+}
+impl std::fmt::Display for IoContext {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.value, self.source)
+    }
+}
+impl std::error::Error for IoContext {}
+
+// Adds the offending value, the method with which it was set,
+// and a suggestion to an io error, retaining its kind.
+pub(crate) fn io_err_with_context(
+    source: std::io::Error,
+    value: String,
+    method: &'static str,
+    suggestion: &'static str,
+) -> std::io::Error {
+    std::io::Error::new(
+        source.kind(),
+        IoContext {
+            value,
+            method,
+            suggestion,
+            source,
+        },
+    )
+}
+
 impl From<std::convert::Infallible> for FlexiLoggerError {
     fn from(_other: std::convert::Infallible) -> FlexiLoggerError {
         unreachable!("lkjl,mnkjiu")
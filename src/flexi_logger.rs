@@ -1,47 +1,192 @@
 use crate::{
-    filter::LogLineFilter,
+    filter::{LogLineFilter, LogLineRewriter},
     primary_writer::PrimaryWriter,
+    shared_spec::SharedSpec,
     util::{eprint_err, eprint_msg, ErrorCode},
-    writers::LogWriter,
-    DeferredNow, LogSpecification,
+    writers::{self, LogWriter},
+    DeferredNow,
 };
 
-#[cfg(feature = "textfilter")]
-use regex::Regex;
 use std::collections::HashMap;
-use std::sync::{Arc, RwLock};
+use std::sync::{
+    atomic::{AtomicU64, AtomicUsize, Ordering},
+    Arc,
+};
 
 // Implements log::Log to plug into the log crate.
 //
 // Delegates the real logging to the configured PrimaryWriter and optionally to other writers.
 pub(crate) struct FlexiLogger {
-    log_specification: Arc<RwLock<LogSpecification>>,
+    log_specification: Arc<SharedSpec>,
+    // The highest level that any writer can write, maintained by the LoggerHandle;
+    // allows discarding all other records without taking any lock.
+    a_max_level: Arc<AtomicUsize>,
     primary_writer: Arc<PrimaryWriter>,
     other_writers: Arc<HashMap<String, Box<dyn LogWriter>>>,
+    // The other writers that get a copy of the records for the default output,
+    // up to the given level
+    writer_duplicates: Vec<(String, log::LevelFilter)>,
     filter: Option<Box<dyn LogLineFilter + Send + Sync>>,
+    #[cfg(all(feature = "textfilter", feature = "kv"))]
+    textfilter_with_kv: bool,
+    o_sequence_numbers: Option<AtomicU64>,
+    o_rewriter: Option<Box<dyn LogLineRewriter + Send + Sync>>,
+    // The characters that enclose the writer names in targets like `{Alert,_Default}`
+    o_writer_target_delimiters: Option<(char, char)>,
 }
 
 impl FlexiLogger {
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
-        log_specification: Arc<RwLock<LogSpecification>>,
+        log_specification: Arc<SharedSpec>,
+        a_max_level: Arc<AtomicUsize>,
         primary_writer: Arc<PrimaryWriter>,
         other_writers: Arc<HashMap<String, Box<dyn LogWriter>>>,
+        writer_duplicates: Vec<(String, log::LevelFilter)>,
         filter: Option<Box<dyn LogLineFilter + Send + Sync>>,
+        #[cfg(all(feature = "textfilter", feature = "kv"))] textfilter_with_kv: bool,
+        use_sequence_numbers: bool,
+        o_rewriter: Option<Box<dyn LogLineRewriter + Send + Sync>>,
+        o_writer_target_delimiters: Option<(char, char)>,
     ) -> Self {
         Self {
             log_specification,
+            a_max_level,
             primary_writer,
             other_writers,
+            writer_duplicates,
             filter,
+            #[cfg(all(feature = "textfilter", feature = "kv"))]
+            textfilter_with_kv,
+            o_sequence_numbers: use_sequence_numbers.then(|| AtomicU64::new(0)),
+            o_rewriter,
+            o_writer_target_delimiters,
+        }
+    }
+
+    // Interprets the target as list of writer names, if it has the configured syntax
+    // and addresses at least one writer; otherwise it is a normal target.
+    fn special_target<'a>(&self, target: &'a str) -> Option<SpecialTarget<'a>> {
+        let (open, close) = self.o_writer_target_delimiters?;
+        let rest = target.strip_prefix(open)?;
+        let (writer_names, original_target) = rest.split_once(close).unwrap_or((rest, ""));
+        let special_target = SpecialTarget {
+            writer_names,
+            o_original_target: (!original_target.is_empty()).then_some(original_target),
+        };
+        special_target
+            .writer_names()
+            .any(|t| {
+                t == writers::DEFAULT_TARGET_NAME
+                    || t == writers::ALL_TARGET_NAME
+                    || self.other_writers.contains_key(t)
+            })
+            .then_some(special_target)
+    }
+
+    fn write(&self, record: &log::Record, o_special_target: Option<SpecialTarget>) {
+        if let Some(special_target) = o_special_target {
+            // the writers see the original target, not the list of writer names
+            let original_target = special_target
+                .o_original_target
+                .or(record.module_path())
+                .unwrap_or_default();
+            self.write_retargeted(
+                &retargeted_record(record, original_target),
+                Some(special_target.writer_names),
+            );
+        } else {
+            self.write_retargeted(record, None);
+        }
+    }
+
+    fn write_retargeted(&self, record: &log::Record, o_writer_names: Option<&str>) {
+        let mut now = match self.o_sequence_numbers {
+            Some(ref counter) => {
+                DeferredNow::new_with_sequence_number(counter.fetch_add(1, Ordering::Relaxed))
+            }
+            None => DeferredNow::new(),
+        };
+        if let Some(writer_names) = o_writer_names {
+            let use_all = addresses_all(writer_names);
+            let mut use_default = use_all;
+            if use_all {
+                for (name, writer) in self.other_writers.iter() {
+                    if record.level() <= writer.max_log_level() {
+                        write_to_other_writer(name, &**writer, &mut now, record);
+                    }
+                }
+            }
+            for t in writer_names.split(',') {
+                if t == writers::DEFAULT_TARGET_NAME {
+                    use_default = true;
+                } else if t != writers::ALL_TARGET_NAME {
+                    match self.other_writers.get(t) {
+                        None => {
+                            eprint_msg(ErrorCode::WriterSpec, &format!("bad writer spec: {t}"));
+                        }
+                        // with _All, the writer got the record already
+                        Some(_) if use_all => {}
+                        Some(writer) => write_to_other_writer(t, &**writer, &mut now, record),
+                    }
+                }
+            }
+            if !use_default || !self.primary_enabled(record.level(), record.target()) {
+                return;
+            }
+        }
+
+        #[cfg(feature = "textfilter")]
+        if !self.log_specification.with_current(|spec| {
+            spec.text_filters_accept(record.target(), || self.filter_text(record))
+        }) {
+            return;
+        }
+
+        for (name, max_level) in &self.writer_duplicates {
+            if record.level() <= *max_level
+                && !o_writer_names.is_some_and(|writer_names| {
+                    addresses_all(writer_names) || writer_names.split(',').any(|t| t == name)
+                })
+            {
+                if let Some(writer) = self.other_writers.get(name) {
+                    if record.level() <= writer.max_log_level() {
+                        write_to_other_writer(name, &**writer, &mut now, record);
+                    }
+                }
+            }
         }
+
+        if let Some(ref filter) = self.filter {
+            filter.write(&mut now, record, &(*self.primary_writer))
+        } else {
+            self.primary_writer.write(&mut now, record)
+        }
+        .unwrap_or_else(|e| {
+            eprint_err(ErrorCode::Write, "writing log line failed", &e);
+        });
+    }
+
+    // The text to which the text filter is applied
+    #[cfg(feature = "textfilter")]
+    #[cfg_attr(not(feature = "kv"), allow(clippy::unused_self))]
+    fn filter_text(&self, record: &log::Record) -> String {
+        #[cfg(feature = "kv")]
+        if self.textfilter_with_kv {
+            let mut text = record.args().to_string();
+            record.key_values().visit(&mut KvText(&mut text)).ok();
+            return text;
+        }
+        record.args().to_string()
+    }
+
+    fn level_is_disabled(&self, level: log::Level) -> bool {
+        level as usize > self.a_max_level.load(Ordering::Relaxed)
     }
 
     fn primary_enabled(&self, level: log::Level, module: &str) -> bool {
         self.log_specification
-            .read()
-            .map_err(|e| eprint_err(ErrorCode::Poison, "rwlock on log spec is poisoned", &e))
-            .unwrap()
-            .enabled(level, module)
+            .with_current(|spec| spec.enabled(level, module))
     }
 }
 
@@ -54,20 +199,30 @@ impl log::Log for FlexiLogger {
     //      - target is filled with module path and level is accepted by log specification
     //      - target is filled with crap and ???
     fn enabled(&self, metadata: &log::Metadata) -> bool {
-        let target = metadata.target();
         let level = metadata.level();
+        if self.level_is_disabled(level) {
+            return false;
+        }
+        let target = metadata.target();
 
-        if !self.other_writers.is_empty() && target.starts_with('{') {
+        if let Some(special_target) = self.special_target(target) {
             // at least one other writer is configured _and_ addressed
-            let targets: Vec<&str> = target[1..(target.len() - 1)].split(',').collect();
-            for t in targets {
-                if t != "_Default" {
+            for t in special_target.writer_names() {
+                if t == writers::ALL_TARGET_NAME {
+                    if self
+                        .other_writers
+                        .values()
+                        .any(|writer| level <= writer.max_log_level())
+                    {
+                        return true;
+                    }
+                } else if t != writers::DEFAULT_TARGET_NAME {
                     match self.other_writers.get(t) {
                         None => {
                             eprint_msg(ErrorCode::WriterSpec, &format!("bad writer spec: {t}"));
                         }
                         Some(writer) => {
-                            if level < writer.max_log_level() {
+                            if level <= writer.max_log_level() {
                                 return true;
                             }
                         }
@@ -80,68 +235,46 @@ impl log::Log for FlexiLogger {
     }
 
     fn log(&self, record: &log::Record) {
-        let target = record.metadata().target();
-        let mut now = DeferredNow::new();
-        let special_target_is_used = target.starts_with('{');
-        if special_target_is_used {
-            let mut use_default = false;
-            let targets: Vec<&str> = target[1..(target.len() - 1)].split(',').collect();
-            for t in targets {
-                if t == "_Default" {
-                    use_default = true;
-                } else {
-                    match self.other_writers.get(t) {
-                        None => {
-                            eprint_msg(ErrorCode::WriterSpec, &format!("bad writer spec: {t}"));
-                        }
-                        Some(writer) => {
-                            writer.write(&mut now, record).unwrap_or_else(|e| {
-                                eprint_err(
-                                    ErrorCode::Write,
-                                    &format!("writing log line to custom writer \"{t}\" failed"),
-                                    &e,
-                                );
-                            });
-                        }
-                    }
-                }
-            }
-            if !use_default {
-                return;
-            }
+        // fast path for records that no writer would write
+        if self.level_is_disabled(record.level()) {
+            return;
         }
-
-        let effective_target = if special_target_is_used {
-            record.module_path().unwrap_or_default()
-        } else {
-            target
-        };
-        if !self.primary_enabled(record.level(), effective_target) {
+        let target = record.metadata().target();
+        let o_special_target = self.special_target(target);
+        // check early, to not consume a sequence number for records that are not written
+        if o_special_target.is_none() && !self.primary_enabled(record.level(), target) {
             return;
         }
 
-        #[cfg(feature = "textfilter")]
-        {
-            // closure that we need below
-            let check_text_filter = |text_filter: Option<&Regex>| {
-                text_filter.map_or(true, |filter| filter.is_match(&record.args().to_string()))
-            };
+        if let Some(ref rewriter) = self.o_rewriter {
+            let o_text = rewriter.rewrite(record);
+            #[cfg(feature = "kv")]
+            let o_kv_pairs = rewritten_key_values(&**rewriter, record);
+            #[cfg(feature = "kv")]
+            let is_rewritten = o_text.is_some() || o_kv_pairs.is_some();
+            #[cfg(not(feature = "kv"))]
+            let is_rewritten = o_text.is_some();
 
-            if !check_text_filter(
-                self.log_specification.read().as_ref().unwrap(/* expose this? */).text_filter(),
-            ) {
+            if is_rewritten {
+                let write_rewritten = |args: std::fmt::Arguments| {
+                    self.write(
+                        &rewritten_record(
+                            record,
+                            args,
+                            #[cfg(feature = "kv")]
+                            o_kv_pairs.as_ref(),
+                        ),
+                        o_special_target,
+                    );
+                };
+                match o_text {
+                    Some(text) => write_rewritten(format_args!("{text}")),
+                    None => write_rewritten(*record.args()),
+                }
                 return;
             }
         }
-
-        if let Some(ref filter) = self.filter {
-            filter.write(&mut now, record, &(*self.primary_writer))
-        } else {
-            self.primary_writer.write(&mut now, record)
-        }
-        .unwrap_or_else(|e| {
-            eprint_err(ErrorCode::Write, "writing log line failed", &e);
-        });
+        self.write(record, o_special_target);
     }
 
     fn flush(&self) {
@@ -155,3 +288,148 @@ impl log::Log for FlexiLogger {
         }
     }
 }
+
+// The logger that is handed out for the log crate;
+// shares the FlexiLogger with the LoggerHandle, which can then also write log lines.
+pub(crate) struct SharedFlexiLogger(pub(crate) Arc<FlexiLogger>);
+impl log::Log for SharedFlexiLogger {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        self.0.enabled(metadata)
+    }
+
+    fn log(&self, record: &log::Record) {
+        self.0.log(record);
+    }
+
+    fn flush(&self) {
+        self.0.flush();
+    }
+}
+
+fn write_to_other_writer(
+    name: &str,
+    writer: &dyn LogWriter,
+    now: &mut DeferredNow,
+    record: &log::Record,
+) {
+    writers::dispatch(writer, now, record).unwrap_or_else(|e| {
+        eprint_err(
+            ErrorCode::Write,
+            &format!("writing log line to custom writer \"{name}\" failed"),
+            &e,
+        );
+    });
+}
+
+fn addresses_all(writer_names: &str) -> bool {
+    writer_names
+        .split(',')
+        .any(|t| t == writers::ALL_TARGET_NAME)
+}
+
+// A target of the form `{Name1,Name2,...}` or `{Name1,Name2,...}original_target`
+#[derive(Clone, Copy)]
+struct SpecialTarget<'a> {
+    writer_names: &'a str,
+    o_original_target: Option<&'a str>,
+}
+impl<'a> SpecialTarget<'a> {
+    fn writer_names(self) -> std::str::Split<'a, char> {
+        self.writer_names.split(',')
+    }
+}
+
+// A copy of the record, with the given target
+fn retargeted_record<'a>(record: &'a log::Record, target: &'a str) -> log::Record<'a> {
+    let mut builder = log::Record::builder();
+    builder
+        .metadata(
+            log::Metadata::builder()
+                .level(record.level())
+                .target(target)
+                .build(),
+        )
+        .args(*record.args())
+        .module_path(record.module_path())
+        .file(record.file())
+        .line(record.line());
+    #[cfg(feature = "kv")]
+    builder.key_values(record.key_values());
+    builder.build()
+}
+
+// A copy of the record, with the given message and, if given, the given key-value pairs
+fn rewritten_record<'a>(
+    record: &'a log::Record,
+    args: std::fmt::Arguments<'a>,
+    #[cfg(feature = "kv")] o_kv_pairs: Option<&'a Vec<(String, String)>>,
+) -> log::Record<'a> {
+    let mut builder = log::Record::builder();
+    builder
+        .metadata(record.metadata().clone())
+        .args(args)
+        .module_path(record.module_path())
+        .file(record.file())
+        .line(record.line());
+    #[cfg(feature = "kv")]
+    match o_kv_pairs {
+        Some(kv_pairs) => builder.key_values(kv_pairs),
+        None => builder.key_values(record.key_values()),
+    };
+    builder.build()
+}
+
+// All key-value pairs of the record as strings, if the rewriter modifies at least one value
+#[cfg(feature = "kv")]
+fn rewritten_key_values(
+    rewriter: &dyn LogLineRewriter,
+    record: &log::Record,
+) -> Option<Vec<(String, String)>> {
+    struct Rewrite<'r> {
+        rewriter: &'r dyn LogLineRewriter,
+        kv_pairs: Vec<(String, String)>,
+        is_rewritten: bool,
+    }
+    impl<'kvs> log::kv::VisitSource<'kvs> for Rewrite<'_> {
+        fn visit_pair(
+            &mut self,
+            key: log::kv::Key<'kvs>,
+            value: log::kv::Value<'kvs>,
+        ) -> Result<(), log::kv::Error> {
+            let value = value.to_string();
+            let value = match self.rewriter.rewrite_value(key.as_str(), &value) {
+                Some(rewritten) => {
+                    self.is_rewritten = true;
+                    rewritten
+                }
+                None => value,
+            };
+            self.kv_pairs.push((key.to_string(), value));
+            Ok(())
+        }
+    }
+
+    let mut rewrite = Rewrite {
+        rewriter,
+        kv_pairs: Vec::new(),
+        is_rewritten: false,
+    };
+    record.key_values().visit(&mut rewrite).ok();
+    rewrite.is_rewritten.then_some(rewrite.kv_pairs)
+}
+
+// Appends the key-value pairs as ` key=value` to a String
+#[cfg(all(feature = "textfilter", feature = "kv"))]
+struct KvText<'a>(&'a mut String);
+#[cfg(all(feature = "textfilter", feature = "kv"))]
+impl<'kvs> log::kv::VisitSource<'kvs> for KvText<'_> {
+    fn visit_pair(
+        &mut self,
+        key: log::kv::Key<'kvs>,
+        value: log::kv::Value<'kvs>,
+    ) -> Result<(), log::kv::Error> {
+        use std::fmt::Write;
+        write!(self.0, " {key}={value}")?;
+        Ok(())
+    }
+}
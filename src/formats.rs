@@ -15,16 +15,65 @@ use std::thread;
 /// Time stamp format that is used by the provided format functions.
 pub const TS_DASHES_BLANK_COLONS_DOT_BLANK: &str = "%Y-%m-%d %H:%M:%S%.6f %:z";
 
+/// Key of the key-value pair that carries a stable message identifier.
+///
+/// The provided format functions render the message identifier in brackets in front of
+/// the other key-value pairs, and [`json_format`] renders it as a separate field `msg_id`,
+/// so that e.g. alerting can be based on the identifier rather than on the message text.
+///
+/// Use the macro [`log_with_id!`](crate::log_with_id) to add the message identifier.
+#[cfg(feature = "kv")]
+#[cfg_attr(docsrs, doc(cfg(feature = "kv")))]
+pub const MESSAGE_ID_KEY: &str = "msg_id";
+
+/// Logs a message with the given level and a stable message identifier.
+///
+/// The identifier is added as key-value pair with key [`MESSAGE_ID_KEY`].
+///
+/// ```rust
+/// # use log::Level;
+/// flexi_logger::log_with_id!(Level::Error, E1234, "Connection to {} lost", "db");
+/// ```
+/// produces with [`default_format`] a log line like
+/// ```ERROR [my_prog] [E1234] Connection to db lost```.
+#[cfg(feature = "kv")]
+#[cfg_attr(docsrs, doc(cfg(feature = "kv")))]
+#[macro_export]
+macro_rules! log_with_id {
+    ($lvl:expr, $id:ident, $($arg:tt)+) => {
+        $crate::__log::log!($lvl, msg_id = stringify!($id); $($arg)+)
+    };
+}
+
+// Writes the context of the current thread, see module context, like `(a=17, b=foo) `
+fn write_context(w: &mut dyn std::io::Write) -> Result<(), std::io::Error> {
+    let mut result = Ok(());
+    let mut is_empty = true;
+    crate::context::for_each(|key, value| {
+        if result.is_ok() {
+            result = write!(w, "{}{key}={value}", if is_empty { "(" } else { ", " });
+        }
+        is_empty = false;
+    });
+    if !is_empty {
+        result = result.and_then(|()| write!(w, ") "));
+    }
+    result
+}
+
 // Helpers for printing key-value pairs
 #[cfg(feature = "kv")]
 fn write_key_value_pairs(
     w: &mut dyn std::io::Write,
     record: &Record<'_>,
 ) -> Result<(), std::io::Error> {
-    if record.key_values().count() > 0 {
-        write!(w, "{{")?;
-        let mut kv_stream = KvStream(w, false);
-        record.key_values().visit(&mut kv_stream).ok();
+    let key_values = record.key_values();
+    if let Some(msg_id) = key_values.get(Key::from_str(MESSAGE_ID_KEY)) {
+        write!(w, "[{msg_id}] ")?;
+    }
+    let mut kv_stream = KvStream(w, false);
+    key_values.visit(&mut kv_stream).ok();
+    if kv_stream.1 {
         write!(w, "}} ")?;
     }
     Ok(())
@@ -41,9 +90,10 @@ where
         key: log::kv::Key<'kvs>,
         value: log::kv::Value<'kvs>,
     ) -> Result<(), log::kv::Error> {
-        if self.1 {
-            write!(self.0, ", ")?;
+        if key.as_str() == MESSAGE_ID_KEY {
+            return Ok(());
         }
+        write!(self.0, "{}", if self.1 { ", " } else { "{" })?;
         write!(self.0, "{key}={value:?}")?;
         self.1 = true;
         Ok(())
@@ -56,6 +106,9 @@ where
 /// If the kv-feature is used, the log lines look like
 /// ```INFO [my_prog::some_submodule] {a=17, b="foo"} Task successfully read from conf.json```
 ///
+/// Values of the [`context`](crate::context) are shown in parentheses, like
+/// ```INFO [my_prog::some_submodule] (request_id=a7f3) Task successfully read from conf.json```
+///
 /// # Errors
 ///
 /// See `std::write`
@@ -71,6 +124,8 @@ pub fn default_format(
         record.module_path().unwrap_or("<unnamed>"),
     )?;
 
+    write_context(w)?;
+
     #[cfg(feature = "kv")]
     write_key_value_pairs(w, record)?;
 
@@ -83,7 +138,9 @@ pub fn default_format(
 /// <code><span style="color:red">ERROR</span> &#91;`my_prog::some_submodule`&#93; <span
 /// style="color:red">File not found</span></code>
 ///
-/// See method [`style`](crate::style) if you want to influence coloring.
+/// See method [`style`](crate::style) if you want to influence coloring, and
+/// [`Logger::color_scope`](crate::Logger::color_scope) if you want to choose
+/// which parts of the line are colored.
 ///
 /// # Errors
 ///
@@ -96,17 +153,25 @@ pub fn colored_default_format(
     record: &Record,
 ) -> Result<(), std::io::Error> {
     let level = record.level();
+    write_line_prefix(w, level)?;
     write!(
         w,
         "{} [{}] ",
-        style(level).paint(level.to_string()),
+        token_style(level, Token::Level).paint(level.to_string()),
         record.module_path().unwrap_or("<unnamed>"),
     )?;
 
+    write_context(w)?;
+
     #[cfg(feature = "kv")]
     write_key_value_pairs(w, record)?;
 
-    write!(w, "{}", style(level).paint(record.args().to_string()))
+    write!(
+        w,
+        "{}",
+        token_style(level, Token::Message).paint(record.args().to_string())
+    )?;
+    write_line_suffix(w, level)
 }
 
 /// A logline-formatter that produces log lines with timestamp and file location, like
@@ -131,6 +196,8 @@ pub fn opt_format(
         record.line().unwrap_or(0),
     )?;
 
+    write_context(w)?;
+
     #[cfg(feature = "kv")]
     write_key_value_pairs(w, record)?;
 
@@ -139,7 +206,9 @@ pub fn opt_format(
 
 /// A colored version of the logline-formatter `opt_format`.
 ///
-/// See method [`style`](crate::style) if you want to influence coloring.
+/// See method [`style`](crate::style) if you want to influence coloring, and
+/// [`Logger::color_scope`](crate::Logger::color_scope) if you want to choose
+/// which parts of the line are colored.
 ///
 /// # Errors
 ///
@@ -152,19 +221,28 @@ pub fn colored_opt_format(
     record: &Record,
 ) -> Result<(), std::io::Error> {
     let level = record.level();
+    write_line_prefix(w, level)?;
     write!(
         w,
         "[{}] {} [{}:{}] ",
-        style(level).paint(now.format(TS_DASHES_BLANK_COLONS_DOT_BLANK).to_string()),
-        style(level).paint(level.to_string()),
+        token_style(level, Token::Timestamp)
+            .paint(now.format(TS_DASHES_BLANK_COLONS_DOT_BLANK).to_string()),
+        token_style(level, Token::Level).paint(level.to_string()),
         record.file().unwrap_or("<unnamed>"),
         record.line().unwrap_or(0),
     )?;
 
+    write_context(w)?;
+
     #[cfg(feature = "kv")]
     write_key_value_pairs(w, record)?;
 
-    write!(w, "{}", style(level).paint(record.args().to_string()))
+    write!(
+        w,
+        "{}",
+        token_style(level, Token::Message).paint(record.args().to_string())
+    )?;
+    write_line_suffix(w, level)
 }
 
 /// A logline-formatter that produces log lines like <br>
@@ -190,6 +268,8 @@ pub fn detailed_format(
         record.line().unwrap_or(0),
     )?;
 
+    write_context(w)?;
+
     #[cfg(feature = "kv")]
     write_key_value_pairs(w, record)?;
 
@@ -198,7 +278,9 @@ pub fn detailed_format(
 
 /// A colored version of the logline-formatter `detailed_format`.
 ///
-/// See method [`style`](crate::style) if you want to influence coloring.
+/// See method [`style`](crate::style) if you want to influence coloring, and
+/// [`Logger::color_scope`](crate::Logger::color_scope) if you want to choose
+/// which parts of the line are colored.
 ///
 /// # Errors
 ///
@@ -211,20 +293,29 @@ pub fn colored_detailed_format(
     record: &Record,
 ) -> Result<(), std::io::Error> {
     let level = record.level();
+    write_line_prefix(w, level)?;
     write!(
         w,
         "[{}] {} [{}] {}:{}: ",
-        style(level).paint(now.format(TS_DASHES_BLANK_COLONS_DOT_BLANK).to_string()),
-        style(level).paint(record.level().to_string()),
+        token_style(level, Token::Timestamp)
+            .paint(now.format(TS_DASHES_BLANK_COLONS_DOT_BLANK).to_string()),
+        token_style(level, Token::Level).paint(level.to_string()),
         record.module_path().unwrap_or("<unnamed>"),
         record.file().unwrap_or("<unnamed>"),
         record.line().unwrap_or(0),
     )?;
 
+    write_context(w)?;
+
     #[cfg(feature = "kv")]
     write_key_value_pairs(w, record)?;
 
-    write!(w, "{}", style(level).paint(record.args().to_string()))
+    write!(
+        w,
+        "{}",
+        token_style(level, Token::Message).paint(record.args().to_string())
+    )?;
+    write_line_suffix(w, level)
 }
 
 /// A logline-formatter that produces log lines like <br>
@@ -250,6 +341,8 @@ pub fn with_thread(
         record.line().unwrap_or(0),
     )?;
 
+    write_context(w)?;
+
     #[cfg(feature = "kv")]
     write_key_value_pairs(w, record)?;
 
@@ -258,7 +351,9 @@ pub fn with_thread(
 
 /// A colored version of the logline-formatter `with_thread`.
 ///
-/// See method [`style`](crate::style) if you want to influence coloring.
+/// See method [`style`](crate::style) if you want to influence coloring, and
+/// [`Logger::color_scope`](crate::Logger::color_scope) if you want to choose
+/// which parts of the line are colored.
 ///
 /// # Errors
 ///
@@ -271,20 +366,211 @@ pub fn colored_with_thread(
     record: &Record,
 ) -> Result<(), std::io::Error> {
     let level = record.level();
+    write_line_prefix(w, level)?;
     write!(
         w,
         "[{}] T[{}] {} [{}:{}] ",
-        style(level).paint(now.format(TS_DASHES_BLANK_COLONS_DOT_BLANK).to_string()),
-        style(level).paint(thread::current().name().unwrap_or("<unnamed>")),
-        style(level).paint(level.to_string()),
+        token_style(level, Token::Timestamp)
+            .paint(now.format(TS_DASHES_BLANK_COLONS_DOT_BLANK).to_string()),
+        token_style(level, Token::Thread).paint(thread::current().name().unwrap_or("<unnamed>")),
+        token_style(level, Token::Level).paint(level.to_string()),
         record.file().unwrap_or("<unnamed>"),
         record.line().unwrap_or(0),
     )?;
 
+    write_context(w)?;
+
     #[cfg(feature = "kv")]
     write_key_value_pairs(w, record)?;
 
-    write!(w, "{}", style(level).paint(record.args().to_string()))
+    write!(
+        w,
+        "{}",
+        token_style(level, Token::Message).paint(record.args().to_string())
+    )?;
+    write_line_suffix(w, level)
+}
+
+/// A logline-formatter that produces log lines like <br>
+/// ```[2016-01-13 15:25:01.640870 +01:00] T[taskreader:12] INFO [src/foo/bar:26] Task successfully read from conf.json```
+///
+/// I.e. with timestamp, thread name and numeric thread id, and file location.
+///
+/// Other than [`with_thread`], this format allows distinguishing unnamed threads,
+/// like the threads of a thread pool.
+/// The thread id is the number of the [`std::thread::ThreadId`], not the id of the OS thread.
+///
+/// # Errors
+///
+/// See `std::write`
+pub fn with_thread_id(
+    w: &mut dyn std::io::Write,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    write!(
+        w,
+        "[{}] T[{}:{}] {} [{}:{}] ",
+        now.format(TS_DASHES_BLANK_COLONS_DOT_BLANK),
+        thread::current().name().unwrap_or("<unnamed>"),
+        thread_id(),
+        record.level(),
+        record.file().unwrap_or("<unnamed>"),
+        record.line().unwrap_or(0),
+    )?;
+
+    write_context(w)?;
+
+    #[cfg(feature = "kv")]
+    write_key_value_pairs(w, record)?;
+
+    write!(w, "{}", &record.args())
+}
+
+/// A colored version of the logline-formatter `with_thread_id`.
+///
+/// See method [`style`](crate::style) if you want to influence coloring, and
+/// [`Logger::color_scope`](crate::Logger::color_scope) if you want to choose
+/// which parts of the line are colored.
+///
+/// # Errors
+///
+/// See `std::write`
+#[cfg_attr(docsrs, doc(cfg(feature = "colors")))]
+#[cfg(feature = "colors")]
+pub fn colored_with_thread_id(
+    w: &mut dyn std::io::Write,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    let level = record.level();
+    write_line_prefix(w, level)?;
+    write!(
+        w,
+        "[{}] T[{}] {} [{}:{}] ",
+        token_style(level, Token::Timestamp)
+            .paint(now.format(TS_DASHES_BLANK_COLONS_DOT_BLANK).to_string()),
+        token_style(level, Token::Thread).paint(format!(
+            "{}:{}",
+            thread::current().name().unwrap_or("<unnamed>"),
+            thread_id()
+        )),
+        token_style(level, Token::Level).paint(level.to_string()),
+        record.file().unwrap_or("<unnamed>"),
+        record.line().unwrap_or(0),
+    )?;
+
+    write_context(w)?;
+
+    #[cfg(feature = "kv")]
+    write_key_value_pairs(w, record)?;
+
+    write!(
+        w,
+        "{}",
+        token_style(level, Token::Message).paint(record.args().to_string())
+    )?;
+    write_line_suffix(w, level)
+}
+
+/// A logline-formatter that produces log lines like [`default_format`], but also shows
+/// the target of the log call if it differs from the module path, like <br>
+/// ```INFO [my_prog::some_submodule => audit] Task successfully read from conf.json```.
+///
+/// # Errors
+///
+/// See `std::write`
+pub fn with_target(
+    w: &mut dyn std::io::Write,
+    _now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    write!(
+        w,
+        "{} [{}{}] ",
+        record.level(),
+        record.module_path().unwrap_or("<unnamed>"),
+        TargetSuffix(record),
+    )?;
+
+    write_context(w)?;
+
+    #[cfg(feature = "kv")]
+    write_key_value_pairs(w, record)?;
+
+    write!(w, "{}", record.args())
+}
+
+/// A colored version of the logline-formatter `with_target`.
+///
+/// See method [`style`](crate::style) if you want to influence coloring, and
+/// [`Logger::color_scope`](crate::Logger::color_scope) if you want to choose
+/// which parts of the line are colored.
+///
+/// # Errors
+///
+/// See `std::write`
+#[cfg_attr(docsrs, doc(cfg(feature = "colors")))]
+#[cfg(feature = "colors")]
+pub fn colored_with_target(
+    w: &mut dyn std::io::Write,
+    _now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    let level = record.level();
+    write_line_prefix(w, level)?;
+    write!(
+        w,
+        "{} [{}{}] ",
+        token_style(level, Token::Level).paint(level.to_string()),
+        record.module_path().unwrap_or("<unnamed>"),
+        TargetSuffix(record),
+    )?;
+
+    write_context(w)?;
+
+    #[cfg(feature = "kv")]
+    write_key_value_pairs(w, record)?;
+
+    write!(
+        w,
+        "{}",
+        token_style(level, Token::Message).paint(record.args().to_string())
+    )?;
+    write_line_suffix(w, level)
+}
+
+// Displays ` => target` if the target of the record differs from its module path.
+struct TargetSuffix<'a, 'b>(&'a Record<'b>);
+impl std::fmt::Display for TargetSuffix<'_, '_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match explicit_target(self.0) {
+            Some(target) => write!(f, " => {target}"),
+            None => Ok(()),
+        }
+    }
+}
+
+// The target of the record, if it differs from the module path
+fn explicit_target<'a>(record: &Record<'a>) -> Option<&'a str> {
+    let target = record.target();
+    (Some(target) != record.module_path()).then_some(target)
+}
+
+// Returns the number of the current thread's ThreadId.
+//
+// ThreadId::as_u64 is not stable, so we take the number from the debug output
+// of the ThreadId ("ThreadId(42)"), computed once per thread.
+fn thread_id() -> u64 {
+    thread_local! {
+        static THREAD_ID: u64 = format!("{:?}", thread::current().id())
+            .chars()
+            .filter(char::is_ascii_digit)
+            .collect::<String>()
+            .parse()
+            .unwrap_or(0);
+    }
+    THREAD_ID.with(|id| *id)
 }
 
 /// A logline-formatter that produces log lines in json format.
@@ -305,18 +591,34 @@ pub fn json_format(
         timestamp: now.format(TS_DASHES_BLANK_COLONS_DOT_BLANK).to_string(),
         thread: current_thread.name(),
         module_path: record.module_path(),
+        target: explicit_target(record),
         file: record.file(),
         line: record.line(),
 
+        #[cfg(feature = "kv")]
+        msg_id: record
+            .key_values()
+            .get(Key::from_str(MESSAGE_ID_KEY))
+            .map(|msg_id| msg_id.to_string()),
         #[cfg(feature = "kv")]
         kv: {
-            let key_values = record.key_values();
-            if key_values.count() > 0 {
-                let mut collect = Collect(BTreeMap::new());
-                key_values.visit(&mut collect).ok();
-                Some(collect.0)
+            let mut collect = Collect(BTreeMap::new());
+            record.key_values().visit(&mut collect).ok();
+            if collect.0.is_empty() {
+                None
             } else {
+                Some(collect.0)
+            }
+        },
+        context: {
+            let mut context = serde_json::Map::new();
+            crate::context::for_each(|key, value| {
+                context.insert(key.to_string(), value.into());
+            });
+            if context.is_empty() {
                 None
+            } else {
+                Some(context)
             }
         },
         text: record.args(),
@@ -340,12 +642,19 @@ struct LogLine<'a> {
     #[serde(skip_serializing_if = "Option::is_none")]
     module_path: Option<&'a str>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    target: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     file: Option<&'a str>,
     #[serde(skip_serializing_if = "Option::is_none")]
     line: Option<u32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     #[cfg(feature = "kv")]
+    msg_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[cfg(feature = "kv")]
     kv: Option<BTreeMap<Key<'a>, Value<'a>>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    context: Option<serde_json::Map<String, serde_json::Value>>,
     text: &'a std::fmt::Arguments<'a>,
 }
 
@@ -355,12 +664,177 @@ struct Collect<'kvs>(BTreeMap<Key<'kvs>, Value<'kvs>>);
 #[cfg(feature = "kv")]
 impl<'kvs> VisitSource<'kvs> for Collect<'kvs> {
     fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
-        self.0.insert(key, value);
+        if key.as_str() != MESSAGE_ID_KEY {
+            self.0.insert(key, value);
+        }
 
         Ok(())
     }
 }
 
+/// Column names of the log lines that are produced by [`csv_format`].
+pub const CSV_COLUMNS: &str = "timestamp,level,module_path,file,line,message,kv,msg_id,context";
+
+/// A logline-formatter that produces log lines in CSV format, with the columns
+/// [`CSV_COLUMNS`], like <br>
+/// ```2016-01-13 15:25:01.640870 +01:00,INFO,foo::bar,src/foo/bar.rs,26,"Task read from a, b, and c",,,```.
+///
+/// Fields are quoted if they contain commas, quotes, or line breaks, and embedded quotes
+/// are doubled, according to RFC 4180.
+///
+/// With the kv-feature, the message identifier (see [`MESSAGE_ID_KEY`]) is written into
+/// the column `msg_id`, and the other key-value pairs are written into the column `kv`
+/// as a JSON object with string values, like `"{""a"":""17"",""b"":""foo""}"`;
+/// otherwise, these columns are empty.
+///
+/// The values of the [`context`](crate::context) are written into the last column,
+/// like `"request_id=a7f3, user=bob"`.
+///
+/// Use [`csv_file_header`] with
+/// [`FileLogWriterBuilder::file_header`](crate::writers::FileLogWriterBuilder::file_header)
+/// to start every log file with the column names.
+///
+/// # Errors
+///
+/// See `std::write`
+pub fn csv_format(
+    w: &mut dyn std::io::Write,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    write_csv_field(w, &now.format(TS_DASHES_BLANK_COLONS_DOT_BLANK).to_string())?;
+    w.write_all(b",")?;
+    write_csv_field(w, record.level().as_str())?;
+    w.write_all(b",")?;
+    write_csv_field(w, record.module_path().unwrap_or_default())?;
+    w.write_all(b",")?;
+    write_csv_field(w, record.file().unwrap_or_default())?;
+    w.write_all(b",")?;
+    if let Some(line) = record.line() {
+        write!(w, "{line}")?;
+    }
+    w.write_all(b",")?;
+    write_csv_field(w, &record.args().to_string())?;
+    w.write_all(b",")?;
+
+    #[cfg(feature = "kv")]
+    {
+        let mut kv_json = KvJson(String::new());
+        record.key_values().visit(&mut kv_json).ok();
+        if !kv_json.0.is_empty() {
+            kv_json.0.push('}');
+            write_csv_field(w, &kv_json.0)?;
+        }
+    }
+    w.write_all(b",")?;
+    #[cfg(feature = "kv")]
+    if let Some(msg_id) = record.key_values().get(Key::from_str(MESSAGE_ID_KEY)) {
+        write_csv_field(w, &msg_id.to_string())?;
+    }
+    w.write_all(b",")?;
+
+    let mut context = String::new();
+    crate::context::for_each(|key, value| {
+        if !context.is_empty() {
+            context.push_str(", ");
+        }
+        context.push_str(key);
+        context.push('=');
+        context.push_str(value);
+    });
+    write_csv_field(w, &context)
+}
+
+/// Writes the column names of [`csv_format`], followed by `\n`.
+///
+/// Can be used with
+/// [`FileLogWriterBuilder::file_header`](crate::writers::FileLogWriterBuilder::file_header).
+///
+/// # Errors
+///
+/// See `std::write`
+pub fn csv_file_header(w: &mut dyn std::io::Write) -> Result<(), std::io::Error> {
+    writeln!(w, "{CSV_COLUMNS}")
+}
+
+// The name of the format function, if it is one of the provided ones.
+// Comparing function addresses can fail if a function has several instances,
+// which then only leads to "custom".
+pub(crate) fn format_name(format: FormatFunction) -> &'static str {
+    let provided: &[(FormatFunction, &'static str)] = &[
+        (default_format, "default_format"),
+        (opt_format, "opt_format"),
+        (detailed_format, "detailed_format"),
+        (with_thread, "with_thread"),
+        (with_thread_id, "with_thread_id"),
+        (with_target, "with_target"),
+        (csv_format, "csv_format"),
+        #[cfg(feature = "json")]
+        (json_format, "json_format"),
+        #[cfg(feature = "colors")]
+        (colored_default_format, "colored_default_format"),
+        #[cfg(feature = "colors")]
+        (colored_opt_format, "colored_opt_format"),
+        #[cfg(feature = "colors")]
+        (colored_detailed_format, "colored_detailed_format"),
+        #[cfg(feature = "colors")]
+        (colored_with_thread, "colored_with_thread"),
+        #[cfg(feature = "colors")]
+        (colored_with_thread_id, "colored_with_thread_id"),
+        #[cfg(feature = "colors")]
+        (colored_with_target, "colored_with_target"),
+    ];
+    provided
+        .iter()
+        .find(|(f, _)| *f as usize == format as usize)
+        .map_or("custom", |(_, name)| name)
+}
+
+// Quotes the field if necessary, see RFC 4180
+fn write_csv_field(w: &mut dyn std::io::Write, field: &str) -> Result<(), std::io::Error> {
+    if field.contains([',', '"', '\n', '\r']) {
+        write!(w, "\"{}\"", field.replace('"', "\"\""))
+    } else {
+        w.write_all(field.as_bytes())
+    }
+}
+
+// Collects the key-value pairs into a JSON object with string values
+#[cfg(feature = "kv")]
+struct KvJson(String);
+#[cfg(feature = "kv")]
+impl<'kvs> VisitSource<'kvs> for KvJson {
+    fn visit_pair(&mut self, key: Key<'kvs>, value: Value<'kvs>) -> Result<(), kv::Error> {
+        if key.as_str() == MESSAGE_ID_KEY {
+            return Ok(());
+        }
+        self.0.push(if self.0.is_empty() { '{' } else { ',' });
+        push_json_string(&mut self.0, key.as_str());
+        self.0.push(':');
+        push_json_string(&mut self.0, &value.to_string());
+        Ok(())
+    }
+}
+#[cfg(feature = "kv")]
+fn push_json_string(target: &mut String, s: &str) {
+    use std::fmt::Write;
+    target.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => target.push_str("\\\""),
+            '\\' => target.push_str("\\\\"),
+            '\n' => target.push_str("\\n"),
+            '\r' => target.push_str("\\r"),
+            '\t' => target.push_str("\\t"),
+            c if c.is_control() => {
+                write!(target, "\\u{:04x}", u32::from(c)).ok();
+            }
+            c => target.push(c),
+        }
+    }
+    target.push('"');
+}
+
 #[cfg(feature = "colors")]
 const DEFAULT_PALETTE: Palette = Palette::default();
 
@@ -376,17 +850,26 @@ fn palette() -> &'static Palette {
 // or from the parameter, if filled.
 // Returns an error if parsing failed.
 #[cfg(feature = "colors")]
-pub(crate) fn set_palette(input: Option<&str>) -> Result<(), std::num::ParseIntError> {
+pub(crate) fn set_palette(
+    input: Option<&str>,
+    color_scope: ColorScope,
+) -> Result<(), crate::FlexiLoggerError> {
     use crate::util::{eprint_msg, ErrorCode};
 
+    let mut palette = match std::env::var_os("FLEXI_LOGGER_PALETTE") {
+        Some(ref env_osstring) => Palette::from(env_osstring.to_string_lossy().as_ref())
+            .map_err(crate::FlexiLoggerError::BadPalette)?,
+        None => match input {
+            Some(input_string) => {
+                Palette::from(input_string).map_err(crate::FlexiLoggerError::BadPalette)?
+            }
+            None => DEFAULT_PALETTE,
+        },
+    };
+    palette.color_scope = color_scope;
+
     PALETTE
-        .set(match std::env::var_os("FLEXI_LOGGER_PALETTE") {
-            Some(ref env_osstring) => Palette::from(env_osstring.to_string_lossy().as_ref())?,
-            None => match input {
-                Some(input_string) => Palette::from(input_string)?,
-                None => DEFAULT_PALETTE,
-            },
-        })
+        .set(palette)
         .map_err(|_palette| {
             eprint_msg(
                 ErrorCode::Palette,
@@ -415,6 +898,68 @@ pub fn style(level: log::Level) -> Style {
     }
 }
 
+/// Determines which parts of a log line are colored by the provided coloring format functions.
+///
+/// See [`Logger::color_scope`](crate::Logger::color_scope).
+#[cfg_attr(docsrs, doc(cfg(feature = "colors")))]
+#[cfg(feature = "colors")]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorScope {
+    /// The level token, the message, and, depending on the format function,
+    /// also the timestamp and the thread name are colored.
+    #[default]
+    Default,
+    /// Only the level token is colored.
+    Level,
+    /// The level token and the message are colored.
+    LevelAndMessage,
+    /// The complete line is colored.
+    Line,
+}
+
+// The parts of a log line that the provided coloring format functions color individually.
+#[cfg(feature = "colors")]
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Token {
+    Timestamp,
+    Thread,
+    Level,
+    Message,
+}
+
+// Returns the style to be used for the given token, considering the color scope.
+#[cfg(feature = "colors")]
+fn token_style(level: log::Level, token: Token) -> Style {
+    let colored = match palette().color_scope {
+        ColorScope::Default => true,
+        ColorScope::Level => token == Token::Level,
+        ColorScope::LevelAndMessage => matches!(token, Token::Level | Token::Message),
+        // the line is colored as a whole, see write_line_prefix and write_line_suffix
+        ColorScope::Line => false,
+    };
+    if colored {
+        style(level)
+    } else {
+        Style::new()
+    }
+}
+
+#[cfg(feature = "colors")]
+fn write_line_prefix(w: &mut dyn std::io::Write, level: log::Level) -> std::io::Result<()> {
+    if palette().color_scope == ColorScope::Line {
+        write!(w, "{}", style(level).prefix())?;
+    }
+    Ok(())
+}
+
+#[cfg(feature = "colors")]
+fn write_line_suffix(w: &mut dyn std::io::Write, level: log::Level) -> std::io::Result<()> {
+    if palette().color_scope == ColorScope::Line {
+        write!(w, "{}", style(level).suffix())?;
+    }
+    Ok(())
+}
+
 #[cfg(feature = "colors")]
 const fn default_style() -> Style {
     Style {
@@ -439,6 +984,7 @@ struct Palette {
     pub info: Style,
     pub debug: Style,
     pub trace: Style,
+    pub color_scope: ColorScope,
 }
 #[cfg(feature = "colors")]
 impl Palette {
@@ -449,10 +995,11 @@ impl Palette {
             info: default_style(),
             debug: default_style().fg(Color::Fixed(27)),
             trace: default_style().fg(Color::Fixed(8)),
+            color_scope: ColorScope::Default,
         }
     }
 
-    fn from(palette_string: &str) -> Result<Palette, std::num::ParseIntError> {
+    fn from(palette_string: &str) -> Result<Palette, String> {
         let mut items = palette_string.split(';');
         Ok(Palette {
             error: parse_style(items.next().unwrap_or("196").trim())?,
@@ -460,19 +1007,93 @@ impl Palette {
             info: parse_style(items.next().unwrap_or("-").trim())?,
             debug: parse_style(items.next().unwrap_or("27").trim())?,
             trace: parse_style(items.next().unwrap_or("8").trim())?,
+            color_scope: ColorScope::Default,
         })
     }
 }
 
+// Parses a palette item, see Logger::set_palette for the syntax.
 #[cfg(feature = "colors")]
-fn parse_style(input: &str) -> Result<Style, std::num::ParseIntError> {
-    Ok(if input == "-" {
-        Style::new()
-    } else {
-        match input.strip_prefix('b') {
-            None => Style::new().fg(Color::Fixed(input.parse()?)),
-            Some(s) => Style::new().bold().fg(Color::Fixed(s.parse()?)),
+fn parse_style(input: &str) -> Result<Style, String> {
+    if input == "-" {
+        return Ok(Style::new());
+    }
+    let (mut foreground, o_background) = match input.split_once('/') {
+        Some((fg, bg)) => (fg, Some(bg)),
+        None => (input, None),
+    };
+
+    // modifiers are only considered as such if the remainder is not a color name,
+    // like in "blue" vs "bblue"
+    let mut style = Style::new();
+    while parse_color(foreground).is_err() {
+        if let Some(s) = foreground.strip_prefix('b') {
+            style = style.bold();
+            foreground = s;
+        } else if let Some(s) = foreground.strip_prefix('u') {
+            style = style.underline();
+            foreground = s;
+        } else {
+            break;
+        }
+    }
+
+    if !(foreground.is_empty() || foreground == "-") {
+        style = style.fg(parse_color(foreground).map_err(|e| format!("{input:?}: {e}"))?);
+    }
+    if let Some(background) = o_background {
+        style = style.on(parse_color(background).map_err(|e| format!("{input:?}: {e}"))?);
+    }
+    Ok(style)
+}
+
+// Parses a color index (0..=255), a 24-bit color ("#RRGGBB" or "R,G,B"), or a color name.
+#[cfg(feature = "colors")]
+fn parse_color(input: &str) -> Result<Color, String> {
+    if let Ok(index) = input.parse::<u8>() {
+        return Ok(Color::Fixed(index));
+    }
+    if let Some(hex) = input.strip_prefix('#') {
+        if hex.len() == 6 && hex.is_ascii() {
+            let component = |i: usize| {
+                u8::from_str_radix(&hex[i..i + 2], 16)
+                    .map_err(|_| format!("invalid hex color {input:?}"))
+            };
+            return Ok(Color::Rgb(component(0)?, component(2)?, component(4)?));
         }
+        return Err(format!("invalid hex color {input:?}"));
+    }
+    if input.contains(',') {
+        let components = input
+            .split(',')
+            .map(|c| c.trim().parse::<u8>())
+            .collect::<Result<Vec<u8>, _>>()
+            .map_err(|_| format!("invalid RGB color {input:?}"))?;
+        return match components[..] {
+            [r, g, b] => Ok(Color::Rgb(r, g, b)),
+            _ => Err(format!("invalid RGB color {input:?}")),
+        };
+    }
+    Ok(match input.to_ascii_lowercase().as_str() {
+        "black" => Color::Black,
+        "dark_gray" => Color::DarkGray,
+        "red" => Color::Red,
+        "light_red" => Color::LightRed,
+        "green" => Color::Green,
+        "light_green" => Color::LightGreen,
+        "yellow" => Color::Yellow,
+        "light_yellow" => Color::LightYellow,
+        "blue" => Color::Blue,
+        "light_blue" => Color::LightBlue,
+        "purple" => Color::Purple,
+        "light_purple" => Color::LightPurple,
+        "magenta" => Color::Magenta,
+        "light_magenta" => Color::LightMagenta,
+        "cyan" => Color::Cyan,
+        "light_cyan" => Color::LightCyan,
+        "white" => Color::White,
+        "light_gray" => Color::LightGray,
+        _ => return Err(format!("unknown color {input:?}")),
     })
 }
 
@@ -483,6 +1104,9 @@ fn parse_style(input: &str) -> Result<Style, std::num::ParseIntError> {
 /// [`Logger::adaptive_format_for_stdout`](crate::Logger::adaptive_format_for_stdout),
 /// which switch off coloring if the output is not going to a tty but is piped into another
 /// program, because then color control byte sequences are usually not expected.
+///
+/// The environment variables `NO_COLOR` and `CLICOLOR_FORCE` are respected,
+/// see [`ColorMode::Auto`].
 #[derive(Clone, Copy)]
 pub enum AdaptiveFormat {
     /// Chooses between [`default_format`](crate::default_format)
@@ -505,6 +1129,16 @@ pub enum AdaptiveFormat {
     #[cfg_attr(docsrs, doc(cfg(feature = "colors")))]
     #[cfg(feature = "colors")]
     WithThread,
+    /// Chooses between [`with_thread_id`](crate::with_thread_id)
+    /// and [`colored_with_thread_id`](crate::colored_with_thread_id).
+    #[cfg_attr(docsrs, doc(cfg(feature = "colors")))]
+    #[cfg(feature = "colors")]
+    WithThreadId,
+    /// Chooses between [`with_target`](crate::with_target)
+    /// and [`colored_with_target`](crate::colored_with_target).
+    #[cfg_attr(docsrs, doc(cfg(feature = "colors")))]
+    #[cfg(feature = "colors")]
+    WithTarget,
     /// Chooses between the first format function (which is supposed to be uncolored)
     /// and the second (which is supposed to be colored).
     ///
@@ -515,8 +1149,8 @@ pub enum AdaptiveFormat {
 
 impl AdaptiveFormat {
     #[must_use]
-    pub(crate) fn format_function(self, is_tty: bool) -> FormatFunction {
-        if is_tty {
+    pub(crate) fn format_function(self, color_mode: ColorMode, is_tty: bool) -> FormatFunction {
+        if color_mode.use_colors(is_tty) {
             match self {
                 #[cfg(feature = "colors")]
                 Self::Default => colored_default_format,
@@ -526,6 +1160,10 @@ impl AdaptiveFormat {
                 Self::Opt => colored_opt_format,
                 #[cfg(feature = "colors")]
                 Self::WithThread => colored_with_thread,
+                #[cfg(feature = "colors")]
+                Self::WithThreadId => colored_with_thread_id,
+                #[cfg(feature = "colors")]
+                Self::WithTarget => colored_with_target,
                 Self::Custom(_, colored) => colored,
             }
         } else {
@@ -538,12 +1176,66 @@ impl AdaptiveFormat {
                 Self::Opt => opt_format,
                 #[cfg(feature = "colors")]
                 Self::WithThread => with_thread,
+                #[cfg(feature = "colors")]
+                Self::WithThreadId => with_thread_id,
+                #[cfg(feature = "colors")]
+                Self::WithTarget => with_target,
                 Self::Custom(uncolored, _) => uncolored,
             }
         }
     }
 }
 
+/// Controls if an [`AdaptiveFormat`] chooses its colored or its uncolored format function.
+///
+/// See [`Logger::color_mode`](crate::Logger::color_mode).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ColorMode {
+    /// Colors are used if the output goes to a tty.
+    ///
+    /// The informal standards for the environment variables `NO_COLOR` and `CLICOLOR_FORCE`
+    /// are respected: if `NO_COLOR` is set to a non-empty value, no colors are used;
+    /// otherwise, if `CLICOLOR_FORCE` is set to a value other than `0`, colors are used
+    /// even if the output does not go to a tty.
+    #[default]
+    Auto,
+    /// Colors are always used.
+    Always,
+    /// Colors are never used.
+    Never,
+}
+impl ColorMode {
+    fn use_colors(self, is_tty: bool) -> bool {
+        self.use_colors_with_env(
+            is_tty,
+            std::env::var_os("NO_COLOR").as_deref(),
+            std::env::var_os("CLICOLOR_FORCE").as_deref(),
+        )
+    }
+
+    // Takes the values of the environment variables NO_COLOR and CLICOLOR_FORCE as parameters.
+    fn use_colors_with_env(
+        self,
+        is_tty: bool,
+        o_no_color: Option<&std::ffi::OsStr>,
+        o_clicolor_force: Option<&std::ffi::OsStr>,
+    ) -> bool {
+        match self {
+            Self::Auto => {
+                if o_no_color.is_some_and(|v| !v.is_empty()) {
+                    false
+                } else if o_clicolor_force.is_some_and(|v| !v.is_empty() && v != "0") {
+                    true
+                } else {
+                    is_tty
+                }
+            }
+            Self::Always => true,
+            Self::Never => false,
+        }
+    }
+}
+
 /// Function type for format functions.
 ///
 /// If you want to write the log lines in your own format,
@@ -604,4 +1296,213 @@ mod test {
         assert_eq!(s.as_str(), " INFO [a:1] test message");
         println!("s: {s}");
     }
+
+    #[test]
+    fn test_with_thread_id() {
+        let format = || {
+            let mut buf = Vec::<u8>::new();
+            let record = log::Record::builder()
+                .file(Some("a"))
+                .line(Some(1))
+                .args(format_args!("test message"))
+                .build();
+            super::with_thread_id(&mut buf, &mut DeferredNow::new(), &record).unwrap();
+            String::from_utf8(buf[35..].to_vec()).unwrap()
+        };
+
+        let line = format();
+        let other_line = std::thread::spawn(format).join().unwrap();
+        assert!(line.starts_with(" T["), "{line}");
+        assert!(line.ends_with("] INFO [a:1] test message"), "{line}");
+        assert!(other_line.starts_with(" T[<unnamed>:"), "{other_line}");
+        assert_ne!(super::thread_id(), 0);
+        assert_eq!(
+            format!("{:?}", std::thread::current().id()),
+            format!("ThreadId({})", super::thread_id())
+        );
+
+        // thread names can contain colons, like the name of this test thread
+        let thread_id = |line: &str| {
+            let thread = &line[..line.find(']').unwrap()];
+            thread[thread.rfind(':').unwrap() + 1..]
+                .parse::<u64>()
+                .unwrap()
+        };
+        assert_ne!(thread_id(&line), thread_id(&other_line));
+    }
+
+    #[test]
+    fn test_with_target() {
+        let format = |target: &str| {
+            let mut buf = Vec::<u8>::new();
+            let record = log::Record::builder()
+                .module_path(Some("a::b"))
+                .target(target)
+                .args(format_args!("test message"))
+                .build();
+            super::with_target(&mut buf, &mut DeferredNow::new(), &record).unwrap();
+            String::from_utf8(buf).unwrap()
+        };
+        assert_eq!(format("a::b"), "INFO [a::b] test message");
+        assert_eq!(format("audit"), "INFO [a::b => audit] test message");
+    }
+
+    #[test]
+    fn test_default_format_with_context() {
+        let mut buf = Vec::<u8>::new();
+        let record = log::Record::builder()
+            .module_path(Some("a::b"))
+            .args(format_args!("test message"))
+            .build();
+
+        let _guard = crate::context::Context::new()
+            .with("request_id", "a7f3")
+            .with("user", &17)
+            .attach();
+        super::default_format(&mut buf, &mut DeferredNow::new(), &record).unwrap();
+        assert_eq!(
+            String::from_utf8(buf).unwrap(),
+            "INFO [a::b] (request_id=a7f3, user=17) test message"
+        );
+    }
+
+    #[test]
+    fn test_csv_format() {
+        let mut buf = Vec::<u8>::new();
+        let record = log::Record::builder()
+            .level(log::Level::Warn)
+            .module_path(Some("a::b"))
+            .file(Some("src/a/b.rs"))
+            .line(Some(17))
+            .args(format_args!("said \"hi, there\"\nand left"))
+            .build();
+
+        super::csv_format(&mut buf, &mut DeferredNow::new(), &record).unwrap();
+        let s = String::from_utf8(buf).unwrap();
+        // 2016-01-13 15:25:01.640870 +01:00
+        assert_eq!(&s[4..5], "-");
+        assert_eq!(
+            &s[33..],
+            ",WARN,a::b,src/a/b.rs,17,\"said \"\"hi, there\"\"\nand left\",,,"
+        );
+
+        let mut buf = Vec::<u8>::new();
+        super::csv_file_header(&mut buf).unwrap();
+        assert_eq!(
+            buf,
+            b"timestamp,level,module_path,file,line,message,kv,msg_id,context\n"
+        );
+    }
+
+    #[cfg(feature = "kv")]
+    #[test]
+    fn test_csv_format_with_kv() {
+        let mut buf = Vec::<u8>::new();
+        let kvs = [("a", "17"), ("msg_id", "E1234"), ("b", "x\"y")];
+        let record = log::Record::builder()
+            .args(format_args!("test message"))
+            .key_values(&kvs)
+            .build();
+
+        let _context = crate::context::Context::new()
+            .with("request_id", "a7f3")
+            .attach();
+        super::csv_format(&mut buf, &mut DeferredNow::new(), &record).unwrap();
+        let s = String::from_utf8(buf).unwrap();
+        assert!(
+            s.ends_with(r#",test message,"{""a"":""17"",""b"":""x\""y""}",E1234,request_id=a7f3"#),
+            "{s}"
+        );
+    }
+
+    #[cfg(feature = "colors")]
+    #[test]
+    fn test_parse_style() {
+        use super::parse_style;
+        use nu_ansi_term::{Color, Style};
+
+        assert_eq!(parse_style("-").unwrap(), Style::new());
+        assert_eq!(
+            parse_style("196").unwrap(),
+            Style::new().fg(Color::Fixed(196))
+        );
+        assert_eq!(
+            parse_style("b1").unwrap(),
+            Style::new().bold().fg(Color::Fixed(1))
+        );
+        assert_eq!(
+            parse_style("#FF8000").unwrap(),
+            Style::new().fg(Color::Rgb(255, 128, 0))
+        );
+        assert_eq!(
+            parse_style("255,128,0").unwrap(),
+            Style::new().fg(Color::Rgb(255, 128, 0))
+        );
+        assert_eq!(parse_style("blue").unwrap(), Style::new().fg(Color::Blue));
+        assert_eq!(
+            parse_style("bblue").unwrap(),
+            Style::new().bold().fg(Color::Blue)
+        );
+        assert_eq!(
+            parse_style("ulight_red/#202020").unwrap(),
+            Style::new()
+                .underline()
+                .fg(Color::LightRed)
+                .on(Color::Rgb(32, 32, 32))
+        );
+        assert_eq!(
+            parse_style("-/white").unwrap(),
+            Style::new().on(Color::White)
+        );
+
+        assert!(parse_style("256").is_err());
+        assert!(parse_style("#12345").is_err());
+        assert!(parse_style("1,2").is_err());
+        assert!(parse_style("pink").is_err());
+    }
+
+    #[test]
+    fn test_color_mode() {
+        use super::ColorMode;
+        use std::ffi::OsStr;
+
+        let use_colors =
+            |mode: ColorMode, is_tty, no_color: Option<&str>, clicolor_force: Option<&str>| {
+                mode.use_colors_with_env(
+                    is_tty,
+                    no_color.map(OsStr::new),
+                    clicolor_force.map(OsStr::new),
+                )
+            };
+
+        assert!(use_colors(ColorMode::Auto, true, None, None));
+        assert!(!use_colors(ColorMode::Auto, false, None, None));
+
+        assert!(use_colors(ColorMode::Auto, false, None, Some("1")));
+        assert!(!use_colors(ColorMode::Auto, false, None, Some("0")));
+        assert!(!use_colors(ColorMode::Auto, false, Some(""), Some("")));
+
+        assert!(!use_colors(ColorMode::Auto, true, Some("1"), Some("1")));
+        assert!(!use_colors(ColorMode::Auto, false, Some("1"), Some("1")));
+        assert!(use_colors(ColorMode::Always, false, Some("1"), None));
+        assert!(!use_colors(ColorMode::Never, true, None, Some("1")));
+    }
+
+    #[cfg(all(feature = "json", feature = "kv"))]
+    #[test]
+    fn test_json_format_with_message_id() {
+        let mut buf = Vec::<u8>::new();
+        let kvs = [("msg_id", "E1234"), ("a", "b")];
+        let record = log::Record::builder()
+            .args(format_args!("test message"))
+            .key_values(&kvs)
+            .build();
+
+        super::json_format(&mut buf, &mut DeferredNow::new(), &record).unwrap();
+        let s = String::from_utf8(buf).unwrap();
+        assert!(
+            s.ends_with(r#","msg_id":"E1234","kv":{"a":"b"},"text":"test message"}"#),
+            "{s}"
+        );
+    }
 }
@@ -40,15 +40,24 @@
 //! See the documentation of method [`Logger::set_palette`]
 //! for a description how this can be done.
 
+mod config_warning;
 mod deferred_now;
+mod env_config;
 mod flexi_error;
 mod flexi_logger;
 mod formats;
 mod log_specification;
 mod logger;
+#[cfg(feature = "config")]
+mod logger_config;
 mod logger_handle;
 mod parameters;
 mod primary_writer;
+mod resources;
+mod scoped_logger;
+mod shared_spec;
+#[cfg(all(feature = "signal_hook", unix))]
+mod sighup;
 mod threads;
 #[cfg(feature = "trc")]
 #[cfg_attr(docsrs, doc(cfg(feature = "trc")))]
@@ -56,8 +65,10 @@ pub mod trc;
 mod write_mode;
 
 pub mod code_examples;
+pub mod context;
 pub mod filter;
 mod util;
+#[allow(clippy::needless_doctest_main)]
 pub mod writers;
 
 pub mod error_info;
@@ -66,19 +77,45 @@ pub(crate) use crate::write_mode::EffectiveWriteMode;
 #[cfg(feature = "async")]
 pub use crate::write_mode::{DEFAULT_MESSAGE_CAPA, DEFAULT_POOL_CAPA};
 pub use crate::{
+    config_warning::ConfigWarning,
     deferred_now::DeferredNow,
     flexi_error::FlexiLoggerError,
     formats::*,
-    log_specification::{LogSpecBuilder, LogSpecification, ModuleFilter},
+    log_specification::{
+        LogSpecBuilder, LogSpecIssue, LogSpecification, ModuleFilter, ModuleFilterChange,
+    },
     logger::{Duplicate, ErrorChannel, Logger},
-    logger_handle::{LogfileSelector, LoggerHandle},
-    parameters::{Age, Cleanup, Criterion, FileSpec, Naming},
+    logger_handle::{
+        LogfileSelector, LoggerGuard, LoggerHandle, ShutdownFuture, SpecGuard, SubtreeHandle,
+    },
+    parameters::{
+        Age, CircuitBreaker, Cleanup, CleanupStatus, Compressor, Criterion, Fallback, FileSpec,
+        Naming, SyncPolicy, WriteFailurePolicy,
+    },
+    resources::ResourceReport,
+    scoped_logger::ScopedLoggerGuard,
     write_mode::{WriteMode, DEFAULT_BUFFER_CAPACITY, DEFAULT_FLUSH_INTERVAL},
 };
 
+#[cfg(feature = "compress")]
+#[cfg_attr(docsrs, doc(cfg(feature = "compress")))]
+pub use crate::parameters::GzCompressor;
+
+#[cfg(feature = "config")]
+#[cfg_attr(docsrs, doc(cfg(feature = "config")))]
+pub use crate::logger_config::{FileConfig, LoggerConfig, RotateConfig};
+
+#[cfg(feature = "textfilter")]
+#[cfg_attr(docsrs, doc(cfg(feature = "textfilter")))]
+pub use crate::log_specification::TextFilter;
+
 /// Re-exports from log crate
 pub use log::{Level, LevelFilter, Record};
 
+// Used by the exported macros, so that they work without a dependency to log in the calling crate
+#[doc(hidden)]
+pub use log as __log;
+
 pub(crate) const ZERO_DURATION: std::time::Duration = std::time::Duration::from_secs(0);
 
 /// Shortest form to get started.
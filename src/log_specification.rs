@@ -16,9 +16,9 @@ use std::{collections::HashMap, env};
 /// described with some Backus-Naur-form:
 ///
 /// ```text
-/// <log_level_spec> ::= single_log_level_spec[{,single_log_level_spec}][/<text_filter>]
+/// <log_level_spec> ::= single_log_level_spec[{,single_log_level_spec}][{/<text_filter>}]
 /// <single_log_level_spec> ::= <path_to_module>|<log_level>|<path_to_module>=<log_level>
-/// <text_filter> ::= <regex>
+/// <text_filter> ::= ["{"<path_to_module>"}"]["!"]<regex>
 /// ```
 ///
 /// * Examples:
@@ -34,6 +34,12 @@ use std::{collections::HashMap, env};
 ///   explicit log level assigment.
 ///   (You see that for modules named error, warn, info, debug or trace,
 ///   it is necessary to specify their loglevel explicitly).
+/// * Besides `off`, `error`, `warn`, `info`, `debug`, and `trace`, log levels can be given
+///   as numbers from `0` (= `off`) to `5` (= `trace`), and as `none` (= `off`)
+///   and `all` (= `trace`).
+/// * Problems with the input are reported with their position, see
+///   [`LogSpecification::issues`]; inputs that are accepted, but look suspicious,
+///   like a module that is specified twice, produce warnings.
 /// * The module names are compared as Strings, with the side effect that a specified module filter
 ///   affects all modules whose name starts with this String.<br>
 ///   Example: `"foo"` affects e.g.
@@ -43,7 +49,19 @@ use std::{collections::HashMap, env};
 ///   * `foobaz` (!)
 ///   * `foobaz::bar` (!)
 ///
-/// The optional text filter is applied for all modules.
+/// The optional text filters are regular expressions that are applied to the text
+/// of the log lines (see [`TextFilter`]):
+///
+///   * `"info/foo"`: only log lines that contain `foo` are written
+///   * `"info/!heartbeat"`: log lines that contain `heartbeat` are suppressed
+///   * `"info/{crate1::mod_a}!timeout"`: log lines of `crate1::mod_a` that contain `timeout`
+///     are suppressed, the other modules are not affected
+///   * `"info/foo/!bar"`: only log lines that contain `foo`, but not `bar`, are written
+///
+/// A text filter without module applies to all modules;
+/// a log line is only written if it passes all text filters that apply to its module.
+/// The module names of text filters are compared like those of the module filters.
+/// To filter for text that starts with `!`, escape it as `\!`.
 ///
 /// Note that external module names are to be specified like in ```"extern crate ..."```, i.e.,
 /// for crates with a dash in their name this means: the dash is to be replaced with
@@ -56,7 +74,8 @@ use std::{collections::HashMap, env};
 pub struct LogSpecification {
     module_filters: Vec<ModuleFilter>,
     #[cfg(feature = "textfilter")]
-    textfilter: Option<Box<Regex>>,
+    textfilters: Vec<TextFilter>,
+    issues: Vec<LogSpecIssue>,
 }
 
 /// Defines which loglevel filter to use for the specified module.
@@ -69,6 +88,15 @@ pub struct ModuleFilter {
     /// The level filter.
     pub level_filter: LevelFilter,
 }
+impl ModuleFilter {
+    fn belongs_to_subtree(&self, module_prefix: &str) -> bool {
+        self.module_name.as_ref().is_some_and(|module_name| {
+            module_name
+                .strip_prefix(module_prefix)
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+        })
+    }
+}
 
 impl LogSpecification {
     pub(crate) fn update_from(&mut self, other: Self) {
@@ -76,10 +104,75 @@ impl LogSpecification {
 
         #[cfg(feature = "textfilter")]
         {
-            self.textfilter = other.textfilter;
+            self.textfilters = other.textfilters;
         }
     }
 
+    // Returns a copy with the module filters modified by the given function.
+    pub(crate) fn modified_with<F>(&self, f: F) -> Self
+    where
+        F: FnOnce(&mut LogSpecBuilder),
+    {
+        let mut builder = LogSpecBuilder::from_module_filters(&self.module_filters);
+        f(&mut builder);
+        Self {
+            module_filters: builder.module_filters.into_vec_module_filter(),
+            #[cfg(feature = "textfilter")]
+            textfilters: self.textfilters.clone(),
+            issues: Vec::new(),
+        }
+    }
+
+    // Returns true if the text, which is only produced if needed, passes all text filters
+    // that apply to the writing module.
+    #[cfg(feature = "textfilter")]
+    pub(crate) fn text_filters_accept<F>(&self, writing_module: &str, text: F) -> bool
+    where
+        F: FnOnce() -> String,
+    {
+        let mut text_filters = self
+            .textfilters
+            .iter()
+            .filter(|tf| tf.applies_to(writing_module))
+            .peekable();
+        if text_filters.peek().is_none() {
+            return true;
+        }
+        let text = text();
+        text_filters.all(|tf| tf.accepts(&text))
+    }
+
+    // Replaces the module filters that belong to the subtree of the given module
+    // with the given ones, and returns the replaced ones.
+    pub(crate) fn replace_subtree(
+        &mut self,
+        module_prefix: &str,
+        module_filters: Vec<ModuleFilter>,
+    ) -> Vec<ModuleFilter> {
+        let (replaced, mut kept): (Vec<ModuleFilter>, Vec<ModuleFilter>) =
+            std::mem::take(&mut self.module_filters)
+                .into_iter()
+                .partition(|mf| mf.belongs_to_subtree(module_prefix));
+        kept.extend(module_filters);
+        self.module_filters = kept.level_sort();
+        replaced
+    }
+
+    // Converts the module filters, which are interpreted relative to the given module,
+    // into absolute module filters; the default filter becomes the filter for the module itself.
+    pub(crate) fn into_subtree_filters(self, module_prefix: &str) -> Vec<ModuleFilter> {
+        self.module_filters
+            .into_iter()
+            .map(|mf| ModuleFilter {
+                module_name: Some(match mf.module_name {
+                    Some(module_name) => format!("{module_prefix}::{module_name}"),
+                    None => module_prefix.to_string(),
+                }),
+                level_filter: mf.level_filter,
+            })
+            .collect()
+    }
+
     pub(crate) fn max_level(&self) -> log::LevelFilter {
         self.module_filters
             .iter()
@@ -132,7 +225,8 @@ impl LogSpecification {
                 level_filter,
             }],
             #[cfg(feature = "textfilter")]
-            textfilter: None,
+            textfilters: Vec::new(),
+            issues: Vec::new(),
         }
     }
 
@@ -142,22 +236,13 @@ impl LogSpecification {
     ///
     /// [`FlexiLoggerError::Parse`] if the input is malformed.
     pub fn parse<S: AsRef<str>>(spec: S) -> Result<Self, FlexiLoggerError> {
-        let mut parse_errs = String::new();
-        let mut dirs = Vec::<ModuleFilter>::new();
         let spec = spec.as_ref();
-        let mut parts = spec.split('/');
-        let mods = parts.next();
-        #[cfg(feature = "textfilter")]
-        let filter = parts.next();
-        if parts.next().is_some() {
-            push_err(
-                &format!("invalid log spec '{spec}' (too many '/'s), ignoring it"),
-                &mut parse_errs,
-            );
-            return parse_err(parse_errs, Self::off());
-        }
-        if let Some(m) = mods {
-            for s in m.split(',') {
+        let mut issues = Issues::default();
+        let mut dirs = Vec::<ModuleFilter>::new();
+        let mut parts = split_with_positions(spec, '/');
+        if let Some((_, mods)) = parts.next() {
+            for (position, s) in split_with_positions(mods, ',') {
+                let position = position + (s.len() - s.trim_start().len());
                 let s = s.trim();
                 if s.is_empty() {
                     continue;
@@ -169,44 +254,49 @@ impl LogSpecification {
                     parts.next(),
                 ) {
                     (Some(part_0), None, None) => {
-                        if contains_whitespace(part_0, &mut parse_errs) {
-                            continue;
-                        }
                         // if the single argument is a log-level string or number,
                         // treat that as a global fallback setting
-                        match parse_level_filter(part_0.trim()) {
+                        match parse_level_filter(part_0) {
                             Ok(num) => (num, None),
                             Err(_) => (LevelFilter::max(), Some(part_0)),
                         }
                     }
-
-                    (Some(part_0), Some(""), None) => {
-                        if contains_whitespace(part_0, &mut parse_errs) {
-                            continue;
-                        }
-                        (LevelFilter::max(), Some(part_0))
-                    }
-
+                    (Some(part_0), Some(""), None) => (LevelFilter::max(), Some(part_0)),
                     (Some(part_0), Some(part_1), None) => {
-                        if contains_whitespace(part_0, &mut parse_errs) {
+                        if let Ok(num) = parse_level_filter(part_1) {
+                            (num, Some(part_0))
+                        } else {
+                            let description = format!("unknown level filter '{part_1}'");
+                            issues.error(position, s, description);
                             continue;
                         }
-                        match parse_level_filter(part_1.trim()) {
-                            Ok(num) => (num, Some(part_0.trim())),
-                            Err(e) => {
-                                push_err(&e.to_string(), &mut parse_errs);
-                                continue;
-                            }
-                        }
                     }
                     _ => {
-                        push_err(
-                            &format!("invalid part in log spec '{s}', ignoring it"),
-                            &mut parse_errs,
-                        );
+                        issues.error(position, s, "too many '='");
                         continue;
                     }
                 };
+                if let Some(name) = name {
+                    if name.chars().any(char::is_whitespace) {
+                        issues.error(position, s, "module name contains a whitespace");
+                        continue;
+                    }
+                    if name.contains('-') {
+                        issues.warning(
+                            position,
+                            s,
+                            "module names use '_' instead of '-', this filter only matches explicit targets",
+                        );
+                    }
+                }
+                if dirs.iter().any(|mf| mf.module_name.as_deref() == name) {
+                    issues.warning(
+                        position,
+                        s,
+                        "the module was already specified, this filter is ignored",
+                    );
+                    continue;
+                }
                 dirs.push(ModuleFilter {
                     module_name: name.map(ToString::to_string),
                     level_filter: log_level,
@@ -215,25 +305,30 @@ impl LogSpecification {
         }
 
         #[cfg(feature = "textfilter")]
-        let textfilter = filter.and_then(|filter| match Regex::new(filter) {
-            Ok(re) => Some(Box::new(re)),
-            Err(e) => {
-                push_err(&format!("invalid regex filter - {e}"), &mut parse_errs);
-                None
-            }
-        });
+        let textfilters = parts
+            .filter_map(|(position, filter)| match TextFilter::parse(filter) {
+                Ok(tf) => Some(tf),
+                Err(e) => {
+                    issues.error(position, filter, e);
+                    None
+                }
+            })
+            .collect();
+        #[cfg(not(feature = "textfilter"))]
+        for (position, filter) in parts {
+            issues.error(
+                position,
+                filter,
+                "text filters are only supported with feature `textfilter`",
+            );
+        }
 
-        let logspec = Self {
+        issues.into_result(Self {
             module_filters: dirs.level_sort(),
             #[cfg(feature = "textfilter")]
-            textfilter,
-        };
-
-        if parse_errs.is_empty() {
-            Ok(logspec)
-        } else {
-            parse_err(parse_errs, logspec)
-        }
+            textfilters,
+            issues: Vec::new(),
+        })
     }
 
     /// Returns a log specification based on the value of the environment variable `RUST_LOG`,
@@ -286,7 +381,7 @@ impl LogSpecification {
         }
         let s = s.as_ref();
         let logspec_ff: LogSpecFileFormat = toml::from_str(s)?;
-        let mut parse_errs = String::new();
+        let mut issues = Issues::default();
         let mut module_filters = Vec::<ModuleFilter>::new();
 
         if let Some(s) = logspec_ff.global_level {
@@ -304,27 +399,23 @@ impl LogSpecification {
         }
 
         #[cfg(feature = "textfilter")]
-        let textfilter = match logspec_ff.global_pattern {
-            None => None,
-            Some(s) => match Regex::new(&s) {
-                Ok(re) => Some(Box::new(re)),
+        let textfilters = match logspec_ff.global_pattern {
+            None => Vec::new(),
+            Some(s) => match TextFilter::parse(&s) {
+                Ok(tf) => vec![tf],
                 Err(e) => {
-                    push_err(&format!("invalid regex filter - {e}"), &mut parse_errs);
-                    None
+                    issues.error(0, &s, e);
+                    Vec::new()
                 }
             },
         };
 
-        let logspec = Self {
+        issues.into_result(Self {
             module_filters: module_filters.level_sort(),
             #[cfg(feature = "textfilter")]
-            textfilter,
-        };
-        if parse_errs.is_empty() {
-            Ok(logspec)
-        } else {
-            parse_err(parse_errs, logspec)
-        }
+            textfilters,
+            issues: Vec::new(),
+        })
     }
 
     /// Serializes itself in toml format.
@@ -413,11 +504,222 @@ impl LogSpecification {
 
     /// Provides a reference to the text filter.
     ///
+    /// If the specification has several text filters, the first one that applies to all modules
+    /// and that is not negated is returned; use [`LogSpecification::text_filters`]
+    /// to see all text filters.
+    ///
     /// This method is only avaible if the default feature `textfilter` is not switched off.
     #[cfg(feature = "textfilter")]
     #[must_use]
     pub fn text_filter(&self) -> Option<&Regex> {
-        self.textfilter.as_deref()
+        self.textfilters
+            .iter()
+            .find(|tf| tf.module_name.is_none() && !tf.negated)
+            .map(|tf| &tf.regex)
+    }
+
+    /// Provides a reference to the text filters.
+    ///
+    /// This method is only avaible if the default feature `textfilter` is not switched off.
+    #[cfg(feature = "textfilter")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "textfilter")))]
+    #[must_use]
+    pub fn text_filters(&self) -> &[TextFilter] {
+        &self.textfilters
+    }
+
+    /// Returns a `LogSpecification` that contains the module filters of both specifications.
+    ///
+    /// Where both specifications have a filter for the same module (or a default filter),
+    /// the one from `other` wins.
+    /// The text filters of `other` are used, if it has any, otherwise the own text filters
+    /// are kept.
+    ///
+    /// ```rust
+    /// # use flexi_logger::LogSpecification;
+    /// let base = LogSpecification::parse("info, mod1 = debug").unwrap();
+    /// let overlay = LogSpecification::parse("mod1 = trace, mod2 = warn").unwrap();
+    /// assert_eq!(
+    ///     base.merge(&overlay).to_string(),
+    ///     LogSpecification::parse("info, mod1 = trace, mod2 = warn").unwrap().to_string()
+    /// );
+    /// ```
+    #[must_use]
+    pub fn merge(&self, other: &Self) -> Self {
+        let mut builder = LogSpecBuilder::from_module_filters(&self.module_filters);
+        for module_filter in &other.module_filters {
+            builder.module_filters.insert(
+                module_filter.module_name.clone(),
+                module_filter.level_filter,
+            );
+        }
+        Self {
+            module_filters: builder.module_filters.into_vec_module_filter(),
+            #[cfg(feature = "textfilter")]
+            textfilters: if other.textfilters.is_empty() {
+                self.textfilters.clone()
+            } else {
+                other.textfilters.clone()
+            },
+            issues: Vec::new(),
+        }
+    }
+
+    /// Provides the problems that were detected when the specification was parsed.
+    ///
+    /// If a specification is parsed successfully, this can still contain warnings.
+    /// If parsing fails, the [`FlexiLoggerError::Parse`] contains the `LogSpecification`
+    /// that could be derived from the valid parts of the input,
+    /// and this method provides all errors and warnings.
+    #[must_use]
+    pub fn issues(&self) -> &[LogSpecIssue] {
+        &self.issues
+    }
+
+    /// Returns the changes of the module filters that lead from this specification to `other`.
+    ///
+    /// The changes are sorted by module name, a change of the default filter comes first.
+    /// Text filters are not compared.
+    #[must_use]
+    pub fn diff(&self, other: &Self) -> Vec<ModuleFilterChange> {
+        let find = |spec: &Self, module_name: &Option<String>| {
+            spec.module_filters
+                .iter()
+                .find(|mf| mf.module_name == *module_name)
+                .map(|mf| mf.level_filter)
+        };
+
+        let mut changes: Vec<ModuleFilterChange> = self
+            .module_filters
+            .iter()
+            .filter_map(|mf| match find(other, &mf.module_name) {
+                None => Some(ModuleFilterChange::Removed(mf.clone())),
+                Some(to) if to != mf.level_filter => Some(ModuleFilterChange::Changed {
+                    module_name: mf.module_name.clone(),
+                    from: mf.level_filter,
+                    to,
+                }),
+                Some(_) => None,
+            })
+            .collect();
+        changes.extend(
+            other
+                .module_filters
+                .iter()
+                .filter(|mf| find(self, &mf.module_name).is_none())
+                .map(|mf| ModuleFilterChange::Added(mf.clone())),
+        );
+        changes.sort_by(|a, b| a.module_name().cmp(&b.module_name()));
+        changes
+    }
+}
+
+/// Describes how a module filter differs between two [`LogSpecification`]s.
+///
+/// Returned from [`LogSpecification::diff`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum ModuleFilterChange {
+    /// The module filter exists only in the second specification.
+    Added(ModuleFilter),
+    /// The module filter exists only in the first specification.
+    Removed(ModuleFilter),
+    /// The module filter exists in both specifications, with different level filters.
+    Changed {
+        /// The module name, or `None` for the default filter.
+        module_name: Option<String>,
+        /// The level filter in the first specification.
+        from: LevelFilter,
+        /// The level filter in the second specification.
+        to: LevelFilter,
+    },
+}
+impl ModuleFilterChange {
+    /// The module name of the affected module filter, or `None` for the default filter.
+    #[must_use]
+    pub fn module_name(&self) -> Option<&str> {
+        match self {
+            Self::Added(mf) | Self::Removed(mf) => mf.module_name.as_deref(),
+            Self::Changed { module_name, .. } => module_name.as_deref(),
+        }
+    }
+}
+
+/// A text filter of a [`LogSpecification`].
+///
+/// A log line passes a text filter if its text matches the regular expression,
+/// or, if the text filter is negated, if its text does not match.
+/// A text filter with a module name applies only to the log lines of that module
+/// and its submodules, a text filter without module name applies to all log lines.
+///
+/// Text filters are part of the log specification string, see [`LogSpecification`].
+///
+/// This type is only avaible if the default feature `textfilter` is not switched off.
+#[cfg(feature = "textfilter")]
+#[cfg_attr(docsrs, doc(cfg(feature = "textfilter")))]
+#[derive(Clone, Debug)]
+pub struct TextFilter {
+    module_name: Option<String>,
+    negated: bool,
+    regex: Regex,
+}
+#[cfg(feature = "textfilter")]
+impl TextFilter {
+    fn new(regex: Regex) -> Self {
+        Self {
+            module_name: None,
+            negated: false,
+            regex,
+        }
+    }
+
+    // Parses `[{<path_to_module>}][!]<regex>`.
+    fn parse(s: &str) -> Result<Self, String> {
+        let (module_name, rest) = match s.trim_start().strip_prefix('{') {
+            Some(rest) => match rest.split_once('}') {
+                Some((module_name, rest)) if !module_name.trim().is_empty() => {
+                    (Some(module_name.trim().to_string()), rest)
+                }
+                _ => return Err("invalid module in text filter".to_string()),
+            },
+            None => (None, s),
+        };
+        let (negated, pattern) = match rest.strip_prefix('!') {
+            Some(pattern) => (true, pattern),
+            None => (false, rest),
+        };
+        Ok(Self {
+            module_name,
+            negated,
+            regex: Regex::new(pattern).map_err(|e| format!("invalid regex filter - {e}"))?,
+        })
+    }
+
+    /// The module to which the text filter applies, or `None` if it applies to all modules.
+    #[must_use]
+    pub fn module_name(&self) -> Option<&str> {
+        self.module_name.as_deref()
+    }
+
+    /// Returns true if the log lines must not match the regular expression.
+    #[must_use]
+    pub fn is_negated(&self) -> bool {
+        self.negated
+    }
+
+    /// The regular expression.
+    #[must_use]
+    pub fn regex(&self) -> &Regex {
+        &self.regex
+    }
+
+    fn applies_to(&self, writing_module: &str) -> bool {
+        self.module_name
+            .as_ref()
+            .map_or(true, |module_name| writing_module.starts_with(module_name))
+    }
+
+    fn accepts(&self, text: &str) -> bool {
+        self.regex.is_match(text) != self.negated
     }
 }
 
@@ -477,28 +779,111 @@ impl From<LevelFilter> for LogSpecification {
     }
 }
 
-fn push_err(s: &str, parse_errs: &mut String) {
-    if !parse_errs.is_empty() {
-        parse_errs.push_str("; ");
+/// A problem that was detected when parsing a log specification.
+///
+/// Provided by [`LogSpecification::issues`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LogSpecIssue {
+    position: usize,
+    fragment: String,
+    description: String,
+    is_error: bool,
+}
+impl LogSpecIssue {
+    /// The byte offset of the offending fragment in the parsed String.
+    ///
+    /// For specifications that are read from a toml document, the position is 0.
+    #[must_use]
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// The offending fragment of the parsed String.
+    #[must_use]
+    pub fn fragment(&self) -> &str {
+        &self.fragment
+    }
+
+    /// A description of the problem.
+    #[must_use]
+    pub fn description(&self) -> &str {
+        &self.description
+    }
+
+    /// Returns true for errors, which make the parsing fail, and false for warnings.
+    #[must_use]
+    pub fn is_error(&self) -> bool {
+        self.is_error
     }
-    parse_errs.push_str(s);
 }
+impl std::fmt::Display for LogSpecIssue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} at position {} ('{}')",
+            self.description, self.position, self.fragment
+        )
+    }
+}
+
+// Collects the issues that are found while parsing.
+#[derive(Default)]
+struct Issues(Vec<LogSpecIssue>);
+impl Issues {
+    fn error<D: Into<String>>(&mut self, position: usize, fragment: &str, description: D) {
+        self.push(position, fragment, description.into(), true);
+    }
 
-fn parse_err(
-    errors: String,
-    logspec: LogSpecification,
-) -> Result<LogSpecification, FlexiLoggerError> {
-    Err(FlexiLoggerError::Parse(errors, logspec))
+    fn warning<D: Into<String>>(&mut self, position: usize, fragment: &str, description: D) {
+        self.push(position, fragment, description.into(), false);
+    }
+
+    fn push(&mut self, position: usize, fragment: &str, description: String, is_error: bool) {
+        self.0.push(LogSpecIssue {
+            position,
+            fragment: fragment.to_string(),
+            description,
+            is_error,
+        });
+    }
+
+    fn into_result(
+        self,
+        mut logspec: LogSpecification,
+    ) -> Result<LogSpecification, FlexiLoggerError> {
+        let errors = self
+            .0
+            .iter()
+            .filter(|issue| issue.is_error)
+            .map(ToString::to_string)
+            .collect::<Vec<String>>();
+        logspec.issues = self.0;
+        if errors.is_empty() {
+            Ok(logspec)
+        } else {
+            Err(FlexiLoggerError::Parse(errors.join("; "), logspec))
+        }
+    }
+}
+
+// Splits the String at the separator, and provides the parts together with their byte offset.
+fn split_with_positions(s: &str, separator: char) -> impl Iterator<Item = (usize, &str)> {
+    let mut position = 0;
+    s.split(separator).map(move |part| {
+        let part_position = position;
+        position += part.len() + separator.len_utf8();
+        (part_position, part)
+    })
 }
 
 fn parse_level_filter<S: AsRef<str>>(s: S) -> Result<LevelFilter, FlexiLoggerError> {
     match s.as_ref().to_lowercase().as_ref() {
-        "off" => Ok(LevelFilter::Off),
-        "error" => Ok(LevelFilter::Error),
-        "warn" => Ok(LevelFilter::Warn),
-        "info" => Ok(LevelFilter::Info),
-        "debug" => Ok(LevelFilter::Debug),
-        "trace" => Ok(LevelFilter::Trace),
+        "off" | "none" | "0" => Ok(LevelFilter::Off),
+        "error" | "1" => Ok(LevelFilter::Error),
+        "warn" | "2" => Ok(LevelFilter::Warn),
+        "info" | "3" => Ok(LevelFilter::Info),
+        "debug" | "4" => Ok(LevelFilter::Debug),
+        "trace" | "all" | "5" => Ok(LevelFilter::Trace),
         _ => Err(FlexiLoggerError::LevelFilter(format!(
             "unknown level filter: {}",
             s.as_ref()
@@ -506,17 +891,6 @@ fn parse_level_filter<S: AsRef<str>>(s: S) -> Result<LevelFilter, FlexiLoggerErr
     }
 }
 
-fn contains_whitespace(s: &str, parse_errs: &mut String) -> bool {
-    let result = s.chars().any(char::is_whitespace);
-    if result {
-        push_err(
-            &format!("ignoring invalid part in log spec '{s}' (contains a whitespace)"),
-            parse_errs,
-        );
-    }
-    result
-}
-
 #[allow(clippy::needless_doctest_main)]
 /// Builder for [`LogSpecification`].
 ///
@@ -619,7 +993,8 @@ impl LogSpecBuilder {
         LogSpecification {
             module_filters: self.module_filters.into_vec_module_filter(),
             #[cfg(feature = "textfilter")]
-            textfilter: None,
+            textfilters: Vec::new(),
+            issues: Vec::new(),
         }
     }
 
@@ -631,7 +1006,8 @@ impl LogSpecBuilder {
     pub fn finalize_with_textfilter(self, tf: Regex) -> LogSpecification {
         LogSpecification {
             module_filters: self.module_filters.into_vec_module_filter(),
-            textfilter: Some(Box::new(tf)),
+            textfilters: vec![TextFilter::new(tf)],
+            issues: Vec::new(),
         }
     }
 
@@ -641,7 +1017,8 @@ impl LogSpecBuilder {
         LogSpecification {
             module_filters: self.module_filters.clone().into_vec_module_filter(),
             #[cfg(feature = "textfilter")]
-            textfilter: None,
+            textfilters: Vec::new(),
+            issues: Vec::new(),
         }
     }
 
@@ -654,7 +1031,8 @@ impl LogSpecBuilder {
     pub fn build_with_textfilter(&self, tf: Option<Regex>) -> LogSpecification {
         LogSpecification {
             module_filters: self.module_filters.clone().into_vec_module_filter(),
-            textfilter: tf.map(Box::new),
+            textfilters: tf.map(TextFilter::new).into_iter().collect(),
+            issues: Vec::new(),
         }
     }
 }
@@ -800,6 +1178,41 @@ mod tests {
         );
     }
 
+    #[test]
+    #[cfg(feature = "textfilter")]
+    fn parse_logging_spec_negated_and_scoped_filters() {
+        let spec =
+            LogSpecification::parse("info, crate1 = debug /abc/!heartbeat/{crate1::mod1}!timeout")
+                .unwrap();
+        assert_eq!(spec.module_filters().len(), 2);
+        let text_filters = spec.text_filters();
+        assert_eq!(text_filters.len(), 3);
+        assert_eq!(text_filters[0].module_name(), None);
+        assert!(!text_filters[0].is_negated());
+        assert_eq!(text_filters[0].regex().as_str(), "abc");
+        assert!(text_filters[1].is_negated());
+        assert_eq!(text_filters[1].regex().as_str(), "heartbeat");
+        assert_eq!(text_filters[2].module_name(), Some("crate1::mod1"));
+        assert!(text_filters[2].is_negated());
+        assert_eq!(text_filters[2].regex().as_str(), "timeout");
+        assert_eq!(spec.text_filter().unwrap().as_str(), "abc");
+
+        let accepts = |module: &str, text: &str| spec.text_filters_accept(module, || text.into());
+        assert!(accepts("crate2", "abc timeout"));
+        assert!(!accepts("crate2", "xyz"));
+        assert!(!accepts("crate2", "abc heartbeat"));
+        assert!(!accepts("crate1::mod1::sub", "abc timeout"));
+        assert!(accepts("crate1::mod2", "abc timeout"));
+
+        assert!(LogSpecification::parse("info/!a(c").is_err());
+        assert!(LogSpecification::parse("info/{crate1!abc").is_err());
+        assert!(LogSpecification::parse("info/{}abc").is_err());
+        // a text filter can match a leading '!' if it is escaped
+        let spec = LogSpecification::parse(r"info/\!abc").unwrap();
+        assert!(!spec.text_filters()[0].is_negated());
+        assert!(spec.text_filters_accept("crate1", || "!abc".into()));
+    }
+
     #[test]
     fn parse_logging_spec_invalid_crate_filter() {
         assert!(LogSpecification::parse("crate1::mod1=error=warn,crate2=debug/a.c").is_err());
@@ -975,12 +1388,113 @@ mod tests {
         assert_eq!(spec.module_filters()[4].level_filter, LevelFilter::Info);
     }
 
+    #[test]
+    fn level_aliases() {
+        for (spec, level_filter) in [
+            ("0", LevelFilter::Off),
+            ("none", LevelFilter::Off),
+            ("1", LevelFilter::Error),
+            ("2", LevelFilter::Warn),
+            ("3", LevelFilter::Info),
+            ("4", LevelFilter::Debug),
+            ("5", LevelFilter::Trace),
+            ("ALL", LevelFilter::Trace),
+        ] {
+            let spec = LogSpecification::parse(spec).unwrap();
+            assert_eq!(spec.module_filters()[0].module_name, None);
+            assert_eq!(spec.module_filters()[0].level_filter, level_filter);
+        }
+        let spec = LogSpecification::parse("all, crate1 = 0, crate2 = none, crate3 = 2").unwrap();
+        assert!(spec.enabled(Level::Trace, "crate4"));
+        assert!(!spec.enabled(Level::Error, "crate1"));
+        assert!(!spec.enabled(Level::Error, "crate2"));
+        assert!(spec.enabled(Level::Warn, "crate3"));
+        assert!(!spec.enabled(Level::Info, "crate3"));
+        assert!(LogSpecification::parse("crate1 = 6").is_err());
+    }
+
     #[test]
     fn zero_level() {
         let spec = LogSpecification::parse("info,crate1::mod1=off").unwrap();
         assert!(!spec.enabled(Level::Error, "crate1::mod1"));
         assert!(spec.enabled(Level::Info, "crate2::mod2"));
     }
+
+    #[test]
+    fn replace_subtree() {
+        let mut spec =
+            LogSpecification::parse("info, app::net = warn, app::network = error, app::net::tcp")
+                .unwrap();
+        let subtree_filters = LogSpecification::parse("debug, udp = trace")
+            .unwrap()
+            .into_subtree_filters("app::net");
+        let replaced = spec.replace_subtree("app::net", subtree_filters);
+        assert_eq!(replaced.len(), 2);
+        assert_eq!(
+            spec.to_string(),
+            "info, app::net::udp = trace, app::network = error, app::net = debug"
+        );
+        assert!(spec.enabled(Level::Debug, "app::net::tcp"));
+        assert!(!spec.enabled(Level::Warn, "app::network"));
+
+        spec.replace_subtree("app::net", replaced);
+        assert!(spec.enabled(Level::Trace, "app::net::tcp"));
+        assert!(!spec.enabled(Level::Info, "app::net::udp"));
+    }
+
+    #[test]
+    fn merge() {
+        let base = LogSpecification::parse("info, mod1 = debug, mod2 = warn").unwrap();
+        let overlay = LogSpecification::parse("mod1 = trace, mod3 = error").unwrap();
+        let merged = base.merge(&overlay);
+        assert!(merged.enabled(Level::Info, "mod4"));
+        assert!(merged.enabled(Level::Trace, "mod1"));
+        assert!(!merged.enabled(Level::Info, "mod2"));
+        assert!(!merged.enabled(Level::Warn, "mod3"));
+        assert_eq!(merged.module_filters().len(), 4);
+
+        let merged = overlay.merge(&LogSpecification::debug());
+        assert!(merged.enabled(Level::Debug, "mod4"));
+    }
+
+    #[test]
+    #[cfg(feature = "textfilter")]
+    fn merge_text_filters() {
+        let base = LogSpecification::parse("info /foo").unwrap();
+        let merged = base.merge(&LogSpecification::parse("mod1 = debug").unwrap());
+        assert_eq!(merged.text_filter().unwrap().as_str(), "foo");
+
+        let merged = base.merge(&LogSpecification::parse("debug /bar/!baz").unwrap());
+        assert_eq!(merged.text_filters().len(), 2);
+        assert_eq!(merged.text_filter().unwrap().as_str(), "bar");
+    }
+
+    #[test]
+    fn diff() {
+        use crate::{ModuleFilter, ModuleFilterChange};
+
+        let spec1 = LogSpecification::parse("info, mod1 = debug, mod2 = warn").unwrap();
+        let spec2 = LogSpecification::parse("warn, mod2 = warn, mod3 = trace").unwrap();
+        assert_eq!(
+            spec1.diff(&spec2),
+            vec![
+                ModuleFilterChange::Changed {
+                    module_name: None,
+                    from: LevelFilter::Info,
+                    to: LevelFilter::Warn,
+                },
+                ModuleFilterChange::Removed(ModuleFilter {
+                    module_name: Some("mod1".to_string()),
+                    level_filter: LevelFilter::Debug,
+                }),
+                ModuleFilterChange::Added(ModuleFilter {
+                    module_name: Some("mod3".to_string()),
+                    level_filter: LevelFilter::Trace,
+                }),
+            ]
+        );
+        assert!(spec1.diff(&spec1).is_empty());
+    }
 }
 
 #[cfg(test)]
@@ -1035,12 +1549,9 @@ mod test_with_specfile {
         let ls_spec = LogSpecification::parse(spec_string).unwrap();
 
         assert_eq!(ls_toml.module_filters, ls_spec.module_filters);
-        assert_eq!(ls_toml.textfilter.is_none(), ls_spec.textfilter.is_none());
-        if ls_toml.textfilter.is_some() && ls_spec.textfilter.is_some() {
-            assert_eq!(
-                ls_toml.textfilter.unwrap().to_string(),
-                ls_spec.textfilter.unwrap().to_string()
-            );
+        assert_eq!(ls_toml.textfilters.len(), ls_spec.textfilters.len());
+        for (tf_toml, tf_spec) in ls_toml.textfilters.iter().zip(&ls_spec.textfilters) {
+            assert_eq!(tf_toml.regex.to_string(), tf_spec.regex.to_string());
         }
     }
 }
@@ -1,37 +1,51 @@
-#[cfg(feature = "colors")]
-use crate::set_palette;
 use crate::{
-    filter::LogLineFilter,
-    flexi_logger::FlexiLogger,
-    formats::default_format,
+    deferred_now::Clock,
+    filter::{LogLineFilter, LogLineRewriter},
+    flexi_logger::{FlexiLogger, SharedFlexiLogger},
+    formats::{default_format, format_name},
     primary_writer::PrimaryWriter,
+    shared_spec::SharedSpec,
     threads::start_flusher_thread,
-    util::{set_error_channel, set_panic_on_error_channel_error},
-    writers::{FileLogWriter, FileLogWriterBuilder, LogWriter},
-    Cleanup, Criterion, DeferredNow, FileSpec, FlexiLoggerError, FormatFunction, LogSpecification,
-    LoggerHandle, Naming, WriteMode,
+    util::{
+        eprint_err, hostname, set_error_channel, set_error_file_rotation,
+        set_panic_on_error_channel_error, with_banner_record, ErrorCode, PostFormat,
+    },
+    writers::{
+        file_log_writer::{FileLogWriter, FileLogWriterBuilder},
+        BreakerWriter, LogWriter,
+    },
+    CircuitBreaker, Cleanup, ConfigWarning, Criterion, DeferredNow, Fallback, FileSpec,
+    FlexiLoggerError, FormatFunction, LogSpecification, LoggerGuard, LoggerHandle, Naming,
+    ScopedLoggerGuard, SyncPolicy, WriteFailurePolicy, WriteMode,
 };
-use crate::{formats::AdaptiveFormat, ZERO_DURATION};
+use crate::{
+    formats::{AdaptiveFormat, ColorMode},
+    ZERO_DURATION,
+};
+#[cfg(feature = "colors")]
+use crate::{set_palette, ColorScope};
 
+use chrono::{DateTime, Utc};
 use log::LevelFilter;
 #[cfg(feature = "specfile")]
+use notify_debouncer_mini::{
+    new_debouncer,
+    notify::{RecommendedWatcher, RecursiveMode},
+    DebounceEventResult, Debouncer,
+};
+#[cfg(feature = "specfile")]
 use std::sync::Mutex;
 use std::{
     collections::HashMap,
     io::IsTerminal,
     path::PathBuf,
-    sync::{Arc, RwLock},
+    sync::{atomic::AtomicUsize, Arc},
 };
 #[cfg(feature = "specfile_without_notification")]
-use {crate::logger_handle::LogSpecSubscriber, std::io::Read, std::path::Path};
-#[cfg(feature = "specfile")]
 use {
-    crate::util::{eprint_err, ErrorCode},
-    notify_debouncer_mini::{
-        new_debouncer,
-        notify::{RecommendedWatcher, RecursiveMode},
-        DebounceEventResult, Debouncer,
-    },
+    crate::logger_handle::LogSpecSubscriber,
+    std::io::{Read, Write},
+    std::path::Path,
 };
 
 /// The entry-point for using `flexi_logger`.
@@ -51,6 +65,7 @@ use {
 /// # Usage
 ///
 /// See [`code_examples`](code_examples/index.html) for a comprehensive list of usage possibilities.
+#[allow(clippy::struct_excessive_bools)]
 pub struct Logger {
     spec: LogSpecification,
     log_target: LogTarget,
@@ -60,21 +75,47 @@ pub struct Logger {
     format_for_stderr: FormatFunction,
     format_for_stdout: FormatFunction,
     format_for_writer: FormatFunction,
+    o_adaptive_format_for_stderr: Option<AdaptiveFormat>,
+    o_adaptive_format_for_stdout: Option<AdaptiveFormat>,
+    color_mode: ColorMode,
     #[cfg(feature = "colors")]
     o_palette: Option<String>,
+    #[cfg(feature = "colors")]
+    color_scope: ColorScope,
     flush_interval: std::time::Duration,
     flwb: FileLogWriterBuilder,
+    o_split_rotation: Option<(Criterion, Naming, Cleanup)>,
     other_writers: HashMap<String, Box<dyn LogWriter>>,
+    o_circuit_breaker: Option<CircuitBreaker>,
+    writer_duplicates: Vec<(String, Duplicate)>,
+    o_writer_target_delimiters: Option<(char, char)>,
     filter: Option<Box<dyn LogLineFilter + Send + Sync>>,
+    o_rewriter: Option<Box<dyn LogLineRewriter + Send + Sync>>,
+    #[cfg(all(feature = "textfilter", feature = "kv"))]
+    textfilter_with_kv: bool,
     error_channel: ErrorChannel,
+    o_error_file_rotation: Option<(u64, usize)>,
+    o_banner: Option<String>,
+    config_file_header: bool,
     use_utc: bool,
+    use_sequence_numbers: bool,
+    use_monotonic_timestamps: bool,
+    o_clock: Option<Clock>,
     panic_on_error_channel_error: bool,
+    #[cfg(feature = "signal_hook")]
+    reopen_on_sighup: bool,
+    o_max_line_length: Option<usize>,
+    escape_control_chars_for_stderr: bool,
+    escape_control_chars_for_stdout: bool,
 }
 
 enum LogTarget {
     StdErr,
     StdOut,
     Multi(bool, Option<Box<dyn LogWriter>>),
+    // main file, and an additional file for the records up to the given level
+    #[cfg(not(target_family = "wasm"))]
+    SplitFiles(FileSpec, LevelFilter),
 }
 
 /// Create a Logger instance and define how to access the (initial)
@@ -135,6 +176,74 @@ impl Logger {
         Ok(Self::from_spec_and_errs(LogSpecification::env_or_parse(s)?))
     }
 
+    /// Creates a Logger that is completely configured with environment variables.
+    ///
+    /// This allows e.g. containerized deployments to switch the output file, the rotation,
+    /// or the format without a rebuild.
+    ///
+    /// The following variables are evaluated:
+    ///
+    /// | Variable | Meaning | Example |
+    /// |----------|---------|---------|
+    /// | `RUST_LOG` | the [`LogSpecification`]; if not set, nothing is logged | `info, my_mod=trace` |
+    /// | `FLEXI_LOGGER_FILE` | write to this file (see [`FileSpec::try_from`]), or, if the value ends with a path separator or is a directory, to a file with default name in this directory; if not set, logs are written to stderr | `/var/log/myapp/` |
+    /// | `FLEXI_LOGGER_ROTATE` | `<criterion>[,<naming>[,<cleanup>]]`, see below | `10M,numbers,keep_log_files:7` |
+    /// | `FLEXI_LOGGER_FORMAT` | one of `default`, `opt`, `detailed`, `with_thread`, `with_thread_id`, `with_target`, `json`, `colored_default`, `colored_opt`, `colored_detailed`, `colored_with_thread`, `colored_with_thread_id`, `colored_with_target`; the colored formats are only used for stderr and stdout | `detailed` |
+    /// | `FLEXI_LOGGER_DUPLICATE` | duplicate to stderr, see [`Duplicate`] | `warn` |
+    /// | `FLEXI_LOGGER_WRITE_MODE` | one of `direct`, `support_capture`, `buffer_and_flush`, `buffer_dont_flush`, `async` | `buffer_and_flush` |
+    ///
+    /// Alternatively, all settings can be provided in the single variable `FLEXI_LOGGER_CONF`,
+    /// as `;`-separated list of `<key>=<value>` pairs with the keys
+    /// `spec`, `file`, `rotate`, `format`, `duplicate`, and `write_mode`, e.g.
+    /// `FLEXI_LOGGER_CONF="spec=info; file=/var/log/myapp/; rotate=day,timestamps"`.
+    /// If both are used, the dedicated variables override the values from `FLEXI_LOGGER_CONF`.
+    ///
+    /// The rotation is specified with
+    /// - a criterion: a size in bytes, optionally with factor `K`, `M`, or `G` (e.g. `10M`),
+    ///   or an age (`month`, `week` (starting on Monday), `day`, `hour`, `minute`, or `second`),
+    ///   or both, separated by `|` (e.g. `day|10M`), see [`Criterion`]
+    /// - optionally a naming: `numbers` (default), `numbers_direct`, `numbers_shifted`,
+    ///   `timestamps`, or `timestamps_direct`, see [`Naming`]
+    /// - optionally a cleanup: `never` (default), `keep_log_files:<n>`,
+    ///   `keep_log_files_and_duration:<n>:<seconds>`,
+    ///   `keep_compressed_files:<n>`, or `keep_log_and_compressed_files:<n>:<m>`,
+    ///   see [`Cleanup`].
+    ///
+    /// The returned Logger can be further configured before it is started.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::Parse` if the log specification is malformed,
+    /// `FlexiLoggerError::EnvConfig` if one of the other values is invalid,
+    /// `FlexiLoggerError::OutputBadFile` if the given file is a directory.
+    pub fn try_from_env_config() -> Result<Self, FlexiLoggerError> {
+        crate::env_config::logger_from_env()
+    }
+
+    /// Creates a Logger from the given [`LoggerConfig`](crate::LoggerConfig),
+    /// which is typically deserialized from the configuration file of the application.
+    ///
+    /// The returned Logger can be further configured before it is started.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::Parse` if the log specification is malformed,
+    /// `FlexiLoggerError::Config` if one of the other values is invalid.
+    #[cfg(feature = "config")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "config")))]
+    pub fn try_from_config(config: crate::LoggerConfig) -> Result<Self, FlexiLoggerError> {
+        crate::logger_config::logger_from_config(config)
+    }
+
+    pub(crate) fn logs_to_file(&self) -> bool {
+        match self.log_target {
+            LogTarget::Multi(use_file, _) => use_file,
+            #[cfg(not(target_family = "wasm"))]
+            LogTarget::SplitFiles(_, _) => true,
+            LogTarget::StdErr | LogTarget::StdOut => false,
+        }
+    }
+
     fn from_spec_and_errs(spec: LogSpecification) -> Self {
         #[cfg(feature = "colors")]
         #[cfg(windows)]
@@ -148,29 +257,50 @@ impl Logger {
             duplicate_err: Duplicate::None,
             duplicate_out: Duplicate::None,
             format_for_file: default_format,
+            format_for_stdout: default_format,
+            format_for_stderr: default_format,
+            format_for_writer: default_format,
 
             #[cfg(feature = "colors")]
-            format_for_stdout: AdaptiveFormat::Default
-                .format_function(std::io::stdout().is_terminal()),
+            o_adaptive_format_for_stderr: Some(AdaptiveFormat::Default),
             #[cfg(feature = "colors")]
-            format_for_stderr: AdaptiveFormat::Default
-                .format_function(std::io::stderr().is_terminal()),
+            o_adaptive_format_for_stdout: Some(AdaptiveFormat::Default),
 
             #[cfg(not(feature = "colors"))]
-            format_for_stdout: default_format,
+            o_adaptive_format_for_stderr: None,
             #[cfg(not(feature = "colors"))]
-            format_for_stderr: default_format,
+            o_adaptive_format_for_stdout: None,
 
-            format_for_writer: default_format,
+            color_mode: ColorMode::Auto,
             #[cfg(feature = "colors")]
             o_palette: None,
+            #[cfg(feature = "colors")]
+            color_scope: ColorScope::Default,
             flush_interval: ZERO_DURATION,
             flwb: FileLogWriter::builder(FileSpec::default()),
+            o_split_rotation: None,
             other_writers: HashMap::<String, Box<dyn LogWriter>>::new(),
+            o_circuit_breaker: None,
+            writer_duplicates: Vec::new(),
+            o_writer_target_delimiters: Some(('{', '}')),
             filter: None,
+            o_rewriter: None,
+            #[cfg(all(feature = "textfilter", feature = "kv"))]
+            textfilter_with_kv: false,
             error_channel: ErrorChannel::default(),
+            o_error_file_rotation: None,
+            o_banner: None,
+            config_file_header: false,
             use_utc: false,
+            use_sequence_numbers: false,
+            use_monotonic_timestamps: false,
+            o_clock: None,
             panic_on_error_channel_error: true,
+            #[cfg(feature = "signal_hook")]
+            reopen_on_sighup: false,
+            o_max_line_length: None,
+            escape_control_chars_for_stderr: false,
+            escape_control_chars_for_stdout: false,
         }
     }
 }
@@ -197,12 +327,45 @@ impl Logger {
     ///
     /// You can duplicate to stdout and stderr, and you can add additional writers.
     #[must_use]
+    #[cfg(not(target_family = "wasm"))]
     pub fn log_to_file(mut self, file_spec: FileSpec) -> Self {
         self.log_target = LogTarget::Multi(true, None);
         self.flwb = self.flwb.file_spec(file_spec);
         self
     }
 
+    /// Log is written to the given, already opened file,
+    /// e.g. to a file descriptor that was inherited from the parent process.
+    ///
+    /// Rotation is not possible in this mode, and
+    /// [`LoggerHandle::reopen_output`] and [`LoggerHandle::trigger_rotation`] return an error.
+    /// See [`FileLogWriterBuilder::with_open_file`](crate::writers::FileLogWriterBuilder::with_open_file)
+    /// for details.
+    ///
+    /// You can duplicate to stdout and stderr, and you can add additional writers.
+    #[must_use]
+    #[cfg(not(target_family = "wasm"))]
+    pub fn log_to_file_handle(mut self, file: std::fs::File) -> Self {
+        self.log_target = LogTarget::Multi(true, None);
+        self.flwb = self.flwb.with_open_file(file);
+        self
+    }
+
+    /// Makes the logger write the log lines, that would otherwise be written to the log file,
+    /// into an in-memory buffer.
+    ///
+    /// This is meant for tests that want to check the log output without touching
+    /// the filesystem: the configuration of the logger can stay as it is in production,
+    /// and the log output can be retrieved with [`LoggerHandle::captured_output`].
+    ///
+    /// No file or directory is created, and rotation is switched off.
+    /// This option only has an effect if logs are written to files.
+    #[must_use]
+    pub fn capture_file_output(mut self) -> Self {
+        self.flwb = self.flwb.capture_output();
+        self
+    }
+
     /// Log is written to the provided writer.
     ///
     /// You can duplicate to stdout and stderr, and you can add additional writers.
@@ -217,12 +380,41 @@ impl Logger {
     ///
     /// And you can duplicate to stdout and stderr, and you can add additional writers.
     #[must_use]
+    #[cfg(not(target_family = "wasm"))]
     pub fn log_to_file_and_writer(mut self, file_spec: FileSpec, w: Box<dyn LogWriter>) -> Self {
         self.log_target = LogTarget::Multi(true, Some(w));
         self.flwb = self.flwb.file_spec(file_spec);
         self
     }
 
+    /// Log is written to a file, as with [`Logger::log_to_file`], and the records with a level
+    /// up to the given threshold are additionally written to a second file set.
+    ///
+    /// E.g. with `LevelFilter::Warn`, all records are written to the files described by
+    /// `file_spec`, and the errors and warnings are also written to the files described by
+    /// `split_file_spec`.
+    ///
+    /// Both file sets use the same settings, e.g. for [rotation](Logger::rotate) and cleanup,
+    /// but they are rotated and cleaned up independently.
+    /// With [`Logger::rotate_split`], the second file set can be rotated and cleaned up
+    /// differently.
+    /// Use a different basename or discriminant for `split_file_spec`,
+    /// otherwise the cleanup of one file set would affect the other.
+    ///
+    /// And you can duplicate to stdout and stderr, and you can add additional writers.
+    #[must_use]
+    #[cfg(not(target_family = "wasm"))]
+    pub fn log_to_file_split(
+        mut self,
+        file_spec: FileSpec,
+        split_file_spec: FileSpec,
+        threshold: LevelFilter,
+    ) -> Self {
+        self.log_target = LogTarget::SplitFiles(split_file_spec, threshold);
+        self.flwb = self.flwb.file_spec(file_spec);
+        self
+    }
+
     /// Log is processed, including duplication, but not written to any destination.
     ///
     /// This can be useful e.g. for running application tests with all log-levels active and still
@@ -250,6 +442,9 @@ impl Logger {
 
     /// Makes the logger write messages with the specified minimum severity additionally to stderr.
     ///
+    /// With a buffering [`WriteMode`], the duplicated messages are buffered as well,
+    /// and are flushed together with the other output.
+    ///
     /// Does not work with [`Logger::log_to_stdout`] or [`Logger::log_to_stderr`].
     #[must_use]
     pub fn duplicate_to_stderr(mut self, dup: Duplicate) -> Self {
@@ -259,6 +454,9 @@ impl Logger {
 
     /// Makes the logger write messages with the specified minimum severity additionally to stdout.
     ///
+    /// With a buffering [`WriteMode`], the duplicated messages are buffered as well,
+    /// and are flushed together with the other output.
+    ///
     /// Does not work with [`Logger::log_to_stdout`] or [`Logger::log_to_stderr`].
     #[must_use]
     pub fn duplicate_to_stdout(mut self, dup: Duplicate) -> Self {
@@ -266,6 +464,35 @@ impl Logger {
         self
     }
 
+    /// Makes the logger write messages with the specified minimum severity additionally to the
+    /// writer that was registered with [`Logger::add_writer`] under the given name.
+    ///
+    /// This applies to all messages that are written to the default output,
+    /// so that e.g. all errors also go to an alert writer, without addressing it in the target
+    /// of each log call. Messages whose target addresses the writer explicitly are not
+    /// written twice to it. The [`LogLineFilter`] does not apply to the duplicated messages.
+    ///
+    /// Calling this method again for the same writer replaces the earlier setting.
+    ///
+    /// # Example
+    ///
+    /// ```rust,ignore
+    /// Logger::try_with_str("info")?
+    ///     .log_to_file(FileSpec::default())
+    ///     .add_writer("Alert", alert_writer)
+    ///     .duplicate_to_writer_named("Alert", Duplicate::Error)
+    ///     .start()?;
+    /// ```
+    #[must_use]
+    pub fn duplicate_to_writer_named<S: Into<String>>(mut self, name: S, dup: Duplicate) -> Self {
+        let name = name.into();
+        self.writer_duplicates.retain(|(n, _)| *n != name);
+        if dup != Duplicate::None {
+            self.writer_duplicates.push((name, dup));
+        }
+        self
+    }
+
     /// Makes the logger use the provided format function for all messages
     /// that are written to files, stderr, stdout, or to an additional writer.
     ///
@@ -288,6 +515,8 @@ impl Logger {
         self.format_for_file = format;
         self.format_for_stderr = format;
         self.format_for_stdout = format;
+        self.o_adaptive_format_for_stderr = None;
+        self.o_adaptive_format_for_stdout = None;
         self.format_for_writer = format;
         self
     }
@@ -309,16 +538,17 @@ impl Logger {
     #[must_use]
     pub fn format_for_stderr(mut self, format_function: FormatFunction) -> Self {
         self.format_for_stderr = format_function;
+        self.o_adaptive_format_for_stderr = None;
         self
     }
 
     /// Makes the logger use the specified format for messages that are written to `stderr`.
-    /// Coloring is used if `stderr` is a tty.
+    /// Coloring is used if `stderr` is a tty, see [`Logger::color_mode`] for details.
     ///
     /// Regarding the default, see [`Logger::format`].
     #[must_use]
     pub fn adaptive_format_for_stderr(mut self, adaptive_format: AdaptiveFormat) -> Self {
-        self.format_for_stderr = adaptive_format.format_function(std::io::stderr().is_terminal());
+        self.o_adaptive_format_for_stderr = Some(adaptive_format);
         self
     }
 
@@ -329,16 +559,28 @@ impl Logger {
     #[must_use]
     pub fn format_for_stdout(mut self, format_function: FormatFunction) -> Self {
         self.format_for_stdout = format_function;
+        self.o_adaptive_format_for_stdout = None;
         self
     }
 
     /// Makes the logger use the specified format for messages that are written to `stdout`.
-    /// Coloring is used if `stdout` is a tty.
+    /// Coloring is used if `stdout` is a tty, see [`Logger::color_mode`] for details.
     ///
     /// Regarding the default, see [`Logger::format`].
     #[must_use]
     pub fn adaptive_format_for_stdout(mut self, adaptive_format: AdaptiveFormat) -> Self {
-        self.format_for_stdout = adaptive_format.format_function(std::io::stdout().is_terminal());
+        self.o_adaptive_format_for_stdout = Some(adaptive_format);
+        self
+    }
+
+    /// Determines if the [`AdaptiveFormat`]s for `stderr` and `stdout` use coloring.
+    ///
+    /// By default, [`ColorMode::Auto`] is used, which uses coloring if the output goes to a tty
+    /// and respects the environment variables `NO_COLOR` and `CLICOLOR_FORCE`.
+    /// With [`ColorMode::Always`] or [`ColorMode::Never`] you can override this.
+    #[must_use]
+    pub fn color_mode(mut self, color_mode: ColorMode) -> Self {
+        self.color_mode = color_mode;
         self
     }
 
@@ -353,23 +595,102 @@ impl Logger {
         self
     }
 
+    /// Limits the length of the formatted log lines that are written to stderr, stdout,
+    /// or to the log file.
+    ///
+    /// Longer log lines are cut off after `max_len` bytes (at a character boundary),
+    /// and a marker like `… [truncated 12345 bytes]` is appended.
+    /// In colored lines, the coloring is reset before the marker.
+    /// This protects downstream tooling against runaway log lines, e.g. from `Debug`
+    /// implementations that produce megabytes of output.
+    ///
+    /// For the file output, see also [`FileLogWriterBuilder::max_line_length`];
+    /// additional writers can provide their own limit with
+    /// [`LogWriter::max_line_length`](crate::writers::LogWriter::max_line_length).
+    #[must_use]
+    pub fn max_line_length(mut self, max_len: usize) -> Self {
+        self.o_max_line_length = Some(max_len);
+        self.flwb = self.flwb.max_line_length(max_len);
+        self
+    }
+
+    /// Makes the logger escape control characters in the formatted log lines,
+    /// for all outputs.
+    ///
+    /// Line breaks e.g. are then written as `\n`, and the escape character that starts
+    /// ANSI escape sequences is written as `\u{1b}`; bytes that are no valid UTF-8
+    /// are written like `\xff`.
+    /// This protects against log injection and terminal escape attacks if untrusted data
+    /// is logged.
+    ///
+    /// Note that the coloring of the provided coloring format functions is escaped as well;
+    /// so you should either use a format function without coloring, or limit the escaping
+    /// to the outputs that are not colored, with [`Logger::escape_control_chars_for_file`],
+    /// [`Logger::escape_control_chars_for_stderr`], or [`Logger::escape_control_chars_for_stdout`].
+    ///
+    /// Additional writers decide with
+    /// [`LogWriter::escape_control_chars`](crate::writers::LogWriter::escape_control_chars)
+    /// whether they want their log lines to be escaped.
+    #[must_use]
+    pub fn escape_control_chars(self) -> Self {
+        self.escape_control_chars_for_file()
+            .escape_control_chars_for_stderr()
+            .escape_control_chars_for_stdout()
+    }
+
+    /// Makes the logger escape control characters in the log lines that are written to files.
+    ///
+    /// See [`Logger::escape_control_chars`].
+    #[must_use]
+    pub fn escape_control_chars_for_file(mut self) -> Self {
+        self.flwb = self.flwb.escape_control_chars();
+        self
+    }
+
+    /// Makes the logger escape control characters in the log lines that are written to stderr.
+    ///
+    /// See [`Logger::escape_control_chars`].
+    #[must_use]
+    pub fn escape_control_chars_for_stderr(mut self) -> Self {
+        self.escape_control_chars_for_stderr = true;
+        self
+    }
+
+    /// Makes the logger escape control characters in the log lines that are written to stdout.
+    ///
+    /// See [`Logger::escape_control_chars`].
+    #[must_use]
+    pub fn escape_control_chars_for_stdout(mut self) -> Self {
+        self.escape_control_chars_for_stdout = true;
+        self
+    }
+
     /// Sets the color palette for function [`style`](crate::style), which is used in the
     /// provided coloring format functions.
     ///
     /// The palette given here overrides the default palette.
     ///
     /// The palette is specified in form of a String that contains a semicolon-separated list
-    /// of numbers (0..=255) and/or dashes (´-´).
-    /// The first five values denote the fixed color that is
+    /// of colors and/or dashes (´-´).
+    /// The first five values denote the color that is
     /// used for coloring `error`, `warn`, `info`, `debug`, and `trace` messages.
     ///
     /// The String `"196;208;-;7;8"` describes the default palette, where color 196 is
     /// used for error messages, and so on. The `-` means that no coloring is done,
     /// i.e., with `"-;-;-;-;-"` all coloring is switched off.
     ///
-    /// Prefixing a number with 'b' makes the output being written in bold.
+    /// A color can be given as
+    /// - a number (0..=255), which denotes a fixed color of the 256-color table,
+    /// - a 24-bit color, like `#FF8000` or `255,128,0`,
+    /// - or a name, like `red`, `light_red`, or `dark_gray`.
+    ///
+    /// Prefixing a color with 'b' makes the output being written in bold,
+    /// prefixing it with 'u' makes it being underlined.
     /// The String `"b1;3;2;4;6"` e.g. describes the palette used by `env_logger`.
     ///
+    /// A background color can be appended after a slash, like in `"bwhite/red"`
+    /// or `"-/#303030"`.
+    ///
     /// The palette can further be overridden at runtime by setting the environment variable
     /// `FLEXI_LOGGER_PALETTE` to a palette String. This allows adapting the used text colors to
     /// differently colored terminal backgrounds.
@@ -384,6 +705,22 @@ impl Logger {
         self
     }
 
+    /// Determines which parts of the log lines are colored by the provided coloring
+    /// format functions.
+    ///
+    /// By default, the level token and the message are colored, and, depending on the
+    /// format function, also the timestamp and the thread name.
+    /// Since completely colored lines can be hard to read with some palettes,
+    /// you can restrict the coloring e.g. to the level token with [`ColorScope::Level`],
+    /// or extend it to the complete line with [`ColorScope::Line`].
+    #[cfg_attr(docsrs, doc(cfg(feature = "colors")))]
+    #[cfg(feature = "colors")]
+    #[must_use]
+    pub fn color_scope(mut self, color_scope: ColorScope) -> Self {
+        self.color_scope = color_scope;
+        self
+    }
+
     /// Prevent indefinite growth of the log file by applying file rotation
     /// and a clean-up strategy for older log files.
     ///
@@ -427,6 +764,16 @@ impl Logger {
         self
     }
 
+    /// Defines the rotation and cleanup of the second file set
+    /// of [`Logger::log_to_file_split`], if it should differ from [`Logger::rotate`].
+    ///
+    /// Has no effect with other log targets.
+    #[must_use]
+    pub fn rotate_split(mut self, criterion: Criterion, naming: Naming, cleanup: Cleanup) -> Self {
+        self.o_split_rotation = Some((criterion, naming, cleanup));
+        self
+    }
+
     /// When [`Logger::rotate`] is used with some [`Cleanup`] variant other than [`Cleanup::Never`],
     /// then this method can be used to define
     /// if the cleanup activities (finding files, deleting files, evtl compressing files) are
@@ -444,6 +791,87 @@ impl Logger {
         self
     }
 
+    /// When the logger is shut down, rotate the current log file and run the cleanup,
+    /// so that only rotated log files are left behind.
+    ///
+    /// See [`FileLogWriterBuilder::rotate_on_shutdown`] for details.
+    ///
+    /// This option only has an effect if logs are written to files with rotation.
+    #[must_use]
+    pub fn rotate_on_shutdown(mut self, rotate_on_shutdown: bool) -> Self {
+        self.flwb = self.flwb.rotate_on_shutdown(rotate_on_shutdown);
+        self
+    }
+
+    /// Records the rotated log files, with size, times and SHA-256 checksum,
+    /// in a manifest file in the log directory.
+    ///
+    /// See [`FileLogWriterBuilder::manifest`] for details.
+    ///
+    /// This option only has an effect if logs are written to files with rotation.
+    #[cfg(feature = "manifest")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "manifest")))]
+    #[must_use]
+    pub fn manifest(mut self, manifest: bool) -> Self {
+        self.flwb = self.flwb.manifest(manifest);
+        self
+    }
+
+    /// Registers a callback that is called with each log file that the cleanup is going
+    /// to delete, e.g. to upload the file or move it to a cold storage.
+    ///
+    /// See [`FileLogWriterBuilder::cleanup_archiver`] for details.
+    ///
+    /// This option only has an effect if logs are written to files with rotation,
+    /// and with a [`Cleanup`] strategy that deletes files.
+    #[must_use]
+    pub fn cleanup_archiver<F>(mut self, archiver: F) -> Self
+    where
+        F: Fn(&std::path::Path) -> std::io::Result<()> + Send + Sync + 'static,
+    {
+        self.flwb = self.flwb.cleanup_archiver(archiver);
+        self
+    }
+
+    /// Makes the cleanup use the given [`Compressor`](crate::Compressor) instead of gzip.
+    ///
+    /// See [`FileLogWriterBuilder::compressor`] for details.
+    ///
+    /// This option only has an effect if logs are written to files with rotation,
+    /// and with a [`Cleanup`] strategy that compresses files.
+    #[must_use]
+    pub fn compressor<C: crate::Compressor + 'static>(mut self, compressor: C) -> Self {
+        self.flwb = self.flwb.compressor(compressor);
+        self
+    }
+
+    /// Makes the cleanup compress up to the given number of files in parallel.
+    ///
+    /// See [`FileLogWriterBuilder::compression_workers`] for details.
+    ///
+    /// This option only has an effect if logs are written to files with rotation,
+    /// and with a [`Cleanup`] strategy that compresses files.
+    #[must_use]
+    pub fn compression_workers(mut self, workers: usize) -> Self {
+        self.flwb = self.flwb.compression_workers(workers);
+        self
+    }
+
+    /// Registers a callback that is called after each failure of the cleanup of log files.
+    ///
+    /// See [`FileLogWriterBuilder::on_cleanup_failure`] for details.
+    ///
+    /// This option only has an effect if logs are written to files with rotation
+    /// and cleanup.
+    #[must_use]
+    pub fn on_cleanup_failure<F>(mut self, observer: F) -> Self
+    where
+        F: Fn(&crate::CleanupStatus) + Send + Sync + 'static,
+    {
+        self.flwb = self.flwb.on_cleanup_failure(observer);
+        self
+    }
+
     /// Apply the provided filter before really writing log lines.
     ///
     /// See the documentation of module [`filter`](crate::filter) for a usage example.
@@ -453,6 +881,80 @@ impl Logger {
         self
     }
 
+    /// Apply the provided rewriter to the messages of the log records before they are
+    /// written to any output.
+    ///
+    /// This can be used e.g. for redacting sensitive data, see
+    /// [`Redactor`](crate::filter::Redactor), and the documentation of module
+    /// [`filter`](crate::filter).
+    #[must_use]
+    pub fn rewriter(mut self, rewriter: Box<dyn LogLineRewriter + Send + Sync>) -> Self {
+        self.o_rewriter = Some(rewriter);
+        self
+    }
+
+    /// Makes the text filter of the log specification (the regex after the `/`)
+    /// also see the key-value pairs of the log records.
+    ///
+    /// By default, the text filter is only applied to the message text.
+    /// With this option, it is applied to the message text, followed by the key-value pairs,
+    /// each rendered as ` key=value`.
+    ///
+    /// Example: with the log specification `"info/tenant=acme"`,
+    /// only log lines with the key-value pair `tenant = "acme"` are written.
+    #[cfg(all(feature = "textfilter", feature = "kv"))]
+    #[cfg_attr(docsrs, doc(cfg(all(feature = "textfilter", feature = "kv"))))]
+    #[must_use]
+    pub fn textfilter_with_kv(mut self) -> Self {
+        self.textfilter_with_kv = true;
+        self
+    }
+
+    /// Makes the logger write a banner, as a log record with level `INFO` and target
+    /// `flexi_logger`, when it is started, and again after every rotation of the output file.
+    ///
+    /// The banner consists of the given application info (e.g. name and version),
+    /// the process id, the host name, and the log specification that is active at start.
+    /// It is written to all writers, independent of the log specification,
+    /// and helps with stitching together log files and syslog streams after restarts.
+    ///
+    /// After a rotation, the banner is written into the new output file and to the
+    /// additional writers right away.
+    ///
+    /// Example: `.banner(concat!(env!("CARGO_PKG_NAME"), " ", env!("CARGO_PKG_VERSION")))`
+    #[must_use]
+    pub fn banner<S: Into<String>>(mut self, app_info: S) -> Self {
+        self.o_banner = Some(app_info.into());
+        self
+    }
+
+    /// Makes the logger write the configuration into the first lines of every new log file,
+    /// so that a log file shows how it was produced.
+    ///
+    /// The header contains the version of `flexi_logger`, the log specification that is active
+    /// when the file is created, the format function (if it is one of the provided ones),
+    /// the rotation settings, and the write mode, e.g.
+    ///
+    /// ```text
+    /// flexi_logger version: 0.29.8
+    /// log spec: info, my_module = debug
+    /// format: detailed_format
+    /// rotation: Size(10000000), Numbers, KeepLogFiles(7)
+    /// write mode: BufferAndFlush
+    /// ```
+    ///
+    /// The two options are mutually exclusive: if the file log writer has a header already,
+    /// e.g. from [`FileLogWriterBuilder::file_header`] or
+    /// [`FileLogWriterBuilder::app_info_file_header`], that header is kept,
+    /// and [`Logger::check`] reports [`ConfigWarning::FileHeaderConflict`].
+    ///
+    /// This option only has an effect if logs are written to files.
+    #[must_use]
+    pub fn config_file_header(mut self) -> Self {
+        self.config_file_header = true;
+        self
+    }
+
     /// Makes the logger append to the specified output file, if it exists already;
     /// by default, the file would be truncated.
     ///
@@ -464,18 +966,176 @@ impl Logger {
         self
     }
 
+    /// Makes the logger remove an incomplete last line from the output file
+    /// before appending to it, optionally keeping the removed bytes in a sidecar file.
+    ///
+    /// This option only has an effect if logs are written to files and
+    /// [`Logger::append`] is used.
+    /// See [`FileLogWriterBuilder::recover_on_append`] for details.
+    #[must_use]
+    pub fn recover_on_append(mut self, keep_corrupt_tail: bool) -> Self {
+        self.flwb = self.flwb.recover_on_append(keep_corrupt_tail);
+        self
+    }
+
+    /// Makes the logger safe for writing to the same output file as other processes.
+    ///
+    /// See [`FileLogWriterBuilder::multi_process_append`] for details.
+    ///
+    /// This option only has an effect if logs are written to files, and it
+    /// will hardly make sense if [`FileSpec::suppress_timestamp`] is not used.
+    #[must_use]
+    pub fn multi_process_append(mut self) -> Self {
+        self.flwb = self.flwb.multi_process_append();
+        self
+    }
+
+    /// Defines how the logger behaves if writing to the log file fails repeatedly,
+    /// e.g. because the disk is full.
+    ///
+    /// See [`WriteFailurePolicy`] for details.
+    ///
+    /// This option only has an effect if logs are written to files.
+    #[must_use]
+    pub fn write_failure_policy(mut self, policy: WriteFailurePolicy) -> Self {
+        self.flwb = self.flwb.write_failure_policy(policy);
+        self
+    }
+
+    /// Adds an alternative output for the case that the log file can't be written where it is
+    /// configured, e.g. because the directory is on a read-only file system.
+    ///
+    /// Can be called several times; the fallbacks are then tried in the given order.
+    /// See [`Fallback`] for details.
+    ///
+    /// This option only has an effect if logs are written to files.
+    #[must_use]
+    pub fn fallback(mut self, fallback: Fallback) -> Self {
+        self.flwb = self.flwb.fallback(fallback);
+        self
+    }
+
+    /// Makes the logger detect if the output file was rotated by an external tool,
+    /// like linux' `logrotate`, and re-open the output file in that case.
+    ///
+    /// See [`FileLogWriterBuilder::detect_external_rotation`] for details.
+    ///
+    /// This option only has an effect if logs are written to files.
+    #[must_use]
+    pub fn detect_external_rotation(mut self, check_interval: std::time::Duration) -> Self {
+        self.flwb = self.flwb.detect_external_rotation(check_interval);
+        self
+    }
+
+    /// Makes the logger protect rotation and cleanup with a lock file, so that several
+    /// processes can share the same rotation scheme.
+    ///
+    /// See [`FileLogWriterBuilder::rotation_lock`] for details.
+    ///
+    /// This option only has an effect if logs are written to files with rotation.
+    #[cfg(feature = "rotation_lock")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rotation_lock")))]
+    #[must_use]
+    pub fn rotation_lock(mut self) -> Self {
+        self.flwb = self.flwb.rotation_lock();
+        self
+    }
+
     /// Makes the logger use UTC timestamps rather than local timestamps.
+    ///
+    /// This implies [`Logger::use_utc_for_file_names`] and [`Logger::use_utc_for_rotation`].
     #[must_use]
     pub fn use_utc(mut self) -> Self {
         self.use_utc = true;
         self
     }
 
-    /// The specified path will be used on unix systems to create a symbolic link
-    /// to the current log file.
+    /// Makes the logger use UTC for the timestamps in the names of the log files,
+    /// while the timestamps in the log lines stay in local time.
+    ///
+    /// This is useful if log files from hosts in different time zones are collected
+    /// in one place.
+    ///
+    /// This option only has an effect if logs are written to files,
+    /// and it is implied by [`Logger::use_utc`].
+    #[must_use]
+    pub fn use_utc_for_file_names(mut self) -> Self {
+        self.flwb = self.flwb.use_utc();
+        self
+    }
+
+    /// Makes the logger evaluate the age criterion of the file rotation in UTC,
+    /// rather than in local time, so that e.g. with `Age::Day` the log file is rotated
+    /// at UTC midnight.
     ///
-    /// This option has no effect on filesystems where symlinks are not supported,
-    /// and it only has an effect if logs are written to files.
+    /// This option only has an effect if logs are written to files with age-based rotation,
+    /// and it is implied by [`Logger::use_utc`].
+    #[must_use]
+    pub fn use_utc_for_rotation(mut self) -> Self {
+        self.flwb = self.flwb.use_utc_for_rotation();
+        self
+    }
+
+    /// Defines when the log file is synced to the storage device.
+    ///
+    /// By default, the log file is never synced explicitly, see [`SyncPolicy`] for the options.
+    ///
+    /// This option only has an effect if logs are written to files.
+    #[must_use]
+    pub fn sync_policy(mut self, sync_policy: SyncPolicy) -> Self {
+        self.flwb = self.flwb.sync_policy(sync_policy);
+        self
+    }
+
+    /// Makes the logger never go back in time with the timestamps of the log lines.
+    ///
+    /// If the system clock is set back, e.g. by NTP or manually, the log lines
+    /// get the latest timestamp that was used before, until the clock has caught up.
+    /// See [`DeferredNow::force_monotonic`].
+    #[must_use]
+    pub fn use_monotonic_timestamps(mut self) -> Self {
+        self.use_monotonic_timestamps = true;
+        self
+    }
+
+    /// Makes the logger take the timestamps of the log lines from the given clock,
+    /// rather than from the system clock.
+    ///
+    /// This is needed on targets without system clock, like `wasm32-unknown-unknown`,
+    /// and allows tests to simulate the passing of time.
+    /// See [`DeferredNow::set_clock`] for details.
+    #[must_use]
+    pub fn clock<F>(mut self, clock: F) -> Self
+    where
+        F: Fn() -> DateTime<Utc> + Send + Sync + 'static,
+    {
+        self.o_clock = Some(Arc::new(clock));
+        self
+    }
+
+    /// Makes the logger number the log records.
+    ///
+    /// Each log record that is handed over to the logger gets a sequence number,
+    /// starting with 0, which is shared by all outputs of the record.
+    /// Format functions and writers can access it with
+    /// [`DeferredNow::sequence_number`].
+    ///
+    /// This allows merging the outputs of several writers afterwards in the order
+    /// in which the records were logged.
+    #[must_use]
+    pub fn use_sequence_numbers(mut self) -> Self {
+        self.use_sequence_numbers = true;
+        self
+    }
+
+    /// The specified path will be used to create a symbolic link to the current log file.
+    ///
+    /// On windows, where creating symbolic links needs a privilege or the developer mode,
+    /// a hard link or, if that fails too, a small text file with the path of the current
+    /// log file is created instead.
+    /// See [`FileLogWriterBuilder::create_symlink`] for details.
+    ///
+    /// This option only has an effect if logs are written to files.
     ///
     /// ### Example
     ///
@@ -494,6 +1154,39 @@ impl Logger {
         self
     }
 
+    /// With true, removes stale symbolic links and temporary files that an earlier run
+    /// left behind, e.g. after a crash, when the output file is opened.
+    ///
+    /// See [`FileLogWriterBuilder::remove_stale_artifacts`] for details.
+    ///
+    /// This option only has an effect if logs are written to files.
+    #[must_use]
+    pub fn remove_stale_artifacts(mut self, remove: bool) -> Self {
+        self.flwb = self.flwb.remove_stale_artifacts(remove);
+        self
+    }
+
+    /// Makes `flexi_logger` register a handler for `SIGHUP` that re-opens the output,
+    /// like [`LoggerHandle::reopen_output`] does.
+    ///
+    /// External log file rotators like linux' `logrotate` can then be configured to rename the
+    /// current log file and send `SIGHUP` to your program, without your program having to
+    /// handle the signal.
+    ///
+    /// The signal is handled with the crate `signal-hook`. When the logger is shut down,
+    /// it stops listening for `SIGHUP`; later signals are then ignored, rather than
+    /// terminating the process.
+    /// If you start several loggers with this option, each of them re-opens its output.
+    ///
+    /// This option has only an effect on unix systems.
+    #[cfg(feature = "signal_hook")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "signal_hook")))]
+    #[must_use]
+    pub fn reopen_on_sighup(mut self, reopen: bool) -> Self {
+        self.reopen_on_sighup = reopen;
+        self
+    }
+
     /// Registers a [`LogWriter`] implementation under the given target name.
     ///
     /// The target name must not start with an underscore.
@@ -508,6 +1201,34 @@ impl Logger {
         self
     }
 
+    /// Makes the logger temporarily skip a writer that was registered with
+    /// [`Logger::add_writer`], if it fails repeatedly.
+    ///
+    /// Without a circuit breaker, every failure of such a writer is reported, and a failing
+    /// writer, e.g. one that sends the logs over the network, can slow down every log call.
+    /// See [`CircuitBreaker`] for details.
+    #[must_use]
+    pub fn circuit_breaker(mut self, breaker: CircuitBreaker) -> Self {
+        self.o_circuit_breaker = Some(breaker);
+        self
+    }
+
+    /// Changes the syntax for addressing the writers that were registered with
+    /// [`Logger::add_writer`], or switches it off.
+    ///
+    /// By default, a target like `{Alert,_Default}` addresses writers, see module
+    /// [`writers`](crate::writers); a target that starts with `{` but does not name
+    /// any registered writer (or `_Default` or `_All`) is treated as a normal target.
+    ///
+    /// If some of your dependencies use targets that start with `{`, you can choose
+    /// other enclosing characters, like with `Some(('<', '>'))`,
+    /// or switch the special interpretation off with `None`.
+    #[must_use]
+    pub fn writer_target_syntax(mut self, o_delimiters: Option<(char, char)>) -> Self {
+        self.o_writer_target_delimiters = o_delimiters;
+        self
+    }
+
     /// Sets the write mode for the logger.
     ///
     /// See [`WriteMode`] for more (important!) details.
@@ -537,6 +1258,24 @@ impl Logger {
         self
     }
 
+    /// Rotates the error output file, if [`ErrorChannel::File`] is used.
+    ///
+    /// Before an error message is written, the error file is checked;
+    /// if its size has reached `max_size` bytes,
+    /// it is renamed by appending `.1` to its name, while existing rotated files are renamed
+    /// from `.1` to `.2` etc. Only `keep` rotated files are kept, older ones are deleted.
+    ///
+    /// The error file and its rotated files are included in
+    /// [`LoggerHandle::existing_log_files`] if [`LogfileSelector::with_error_files`](crate::LogfileSelector::with_error_files)
+    /// is used.
+    ///
+    /// Note that the error channel is a process-wide setting.
+    #[must_use]
+    pub fn rotate_error_file(mut self, max_size: u64, keep: usize) -> Self {
+        self.o_error_file_rotation = Some((max_size, keep));
+        self
+    }
+
     /// Decides what `flexi_logger` should do if the error output channel cannot be written to.
     ///
     /// By default, it will panic if error messages cannot be written to the chosen
@@ -556,7 +1295,9 @@ impl Logger {
 /// so under normal circumstances no single message shuld appear.
 ///
 /// By default these error messages are printed to `stderr`.
-#[derive(Debug, Default)]
+///
+/// The error channel can be changed at runtime with [`LoggerHandle::set_error_channel`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub enum ErrorChannel {
     /// Write `flexi_logger`'s own error messages to `stderr`.
     #[default]
@@ -621,6 +1362,60 @@ impl Logger {
 
 /// Finally, start logging, optionally with a spec-file.
 impl Logger {
+    /// Returns the combinations of options that are accepted, but don't have the intended
+    /// effect, like [`Logger::duplicate_to_stderr`] together with [`Logger::log_to_stderr`],
+    /// or [`Logger::append`] with timestamps in the file names.
+    ///
+    /// The check is optional; the logger starts regardless of the warnings.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use flexi_logger::{ConfigWarning, Duplicate, Logger};
+    /// let logger = Logger::try_with_str("info")
+    ///     .unwrap()
+    ///     .log_to_stderr()
+    ///     .duplicate_to_stderr(Duplicate::Warn);
+    /// assert_eq!(logger.check(), vec![ConfigWarning::DuplicationWithoutFile]);
+    /// ```
+    #[must_use]
+    pub fn check(&self) -> Vec<ConfigWarning> {
+        let mut warnings = if self.logs_to_file() {
+            let mut warnings = self.flwb.check();
+            if self.config_file_header && self.flwb.has_file_header() {
+                warnings.push(ConfigWarning::FileHeaderConflict);
+            }
+            warnings
+        } else {
+            self.check_without_file()
+        };
+        for (name, _) in &self.writer_duplicates {
+            if !self.other_writers.contains_key(name) {
+                warnings.push(ConfigWarning::DuplicationToUnknownWriter(name.clone()));
+            }
+        }
+        warnings
+    }
+
+    fn check_without_file(&self) -> Vec<ConfigWarning> {
+        let mut warnings = Vec::new();
+        if let LogTarget::StdErr | LogTarget::StdOut = self.log_target {
+            if self.duplicate_err != Duplicate::None || self.duplicate_out != Duplicate::None {
+                warnings.push(ConfigWarning::DuplicationWithoutFile);
+            }
+        }
+        for (is_set, option) in [
+            (self.flwb.uses_rotation(), "rotate"),
+            (self.flwb.uses_append(), "append"),
+            (self.config_file_header, "config_file_header"),
+        ] {
+            if is_set {
+                warnings.push(ConfigWarning::FileOptionWithoutFile(option));
+            }
+        }
+        warnings
+    }
+
     /// Consumes the Logger object and initializes `flexi_logger`.
     ///
     /// **Keep the [`LoggerHandle`] alive up to the very end of your program!**
@@ -659,11 +1454,99 @@ impl Logger {
         Ok(handle)
     }
 
+    /// Consumes the Logger object and initializes `flexi_logger`, like [`Logger::start`],
+    /// but returns only a [`LoggerGuard`] that does not allow reconfiguring the logger.
+    ///
+    /// This is sufficient for programs that never change the log specification or
+    /// the writers at runtime.
+    ///
+    /// **Keep the [`LoggerGuard`] alive up to the very end of your program!**
+    /// Dropping it has the same effect as dropping the [`LoggerHandle`],
+    /// see [`Logger::start`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use flexi_logger::{FileSpec, Logger};
+    /// fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let _guard = Logger::try_with_str("info")?
+    ///         .log_to_file(FileSpec::default())
+    ///         .start_detached()?;
+    ///
+    ///     // ... do all your work ...
+    ///
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Several variants of [`FlexiLoggerError`] can occur.
+    pub fn start_detached(self) -> Result<LoggerGuard, FlexiLoggerError> {
+        self.start().map(LoggerGuard::new)
+    }
+
+    /// Consumes the Logger object and makes it the active logger until the returned guard
+    /// is dropped; then the previously active scoped logger becomes active again.
+    ///
+    /// Since [`log::set_boxed_logger`] can only be called once per process,
+    /// [`Logger::start`] can't be used in several tests of the same test binary.
+    /// The first call of this method instead installs a dispatcher as global logger,
+    /// which forwards all log calls to the logger that was started most recently
+    /// and is still in scope. Each test can thus use its own configuration:
+    ///
+    /// ```rust
+    /// # use flexi_logger::Logger;
+    /// // in each test:
+    /// let logger = Logger::try_with_str("debug").unwrap().start_scoped().unwrap();
+    /// log::debug!("This is written with the configuration of this test");
+    /// // the guard can be used like a LoggerHandle
+    /// logger.parse_new_spec("info").unwrap();
+    /// ```
+    ///
+    /// Note that there is only one active logger per process:
+    /// tests that run in parallel (which `cargo test` does by default) see the
+    /// configuration of the most recently started logger. Run such tests sequentially,
+    /// e.g. with `cargo test -- --test-threads=1`.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::Log` if another global logger was installed before,
+    /// and several other variants of [`FlexiLoggerError`].
+    pub fn start_scoped(self) -> Result<ScopedLoggerGuard, FlexiLoggerError> {
+        // build() already sets the global max level
+        let previous_max_level = log::max_level();
+        let (boxed_logger, handle) = self.build()?;
+        crate::scoped_logger::activate(boxed_logger, handle, previous_max_level)
+    }
+
     /// Builds a boxed logger and a `LoggerHandle` for it,
     /// but does not initialize the global logger.
     ///
     /// The returned boxed logger implements the [`Log`](log::Log) trait
-    /// and can be installed manually or nested within another logger.
+    /// and can be installed manually.
+    /// Use [`Logger::build_nested`] if it is to be nested within another logger.
+    ///
+    /// **Keep the [`LoggerHandle`] alive up to the very end of your program!**
+    /// See [`Logger::start`] for more details.
+    ///
+    /// # Errors
+    ///
+    /// Several variants of [`FlexiLoggerError`] can occur.
+    pub fn build(self) -> Result<(Box<dyn log::Log>, LoggerHandle), FlexiLoggerError> {
+        self.build_impl(true)
+    }
+
+    /// Builds a boxed logger and a `LoggerHandle` for it, for being used within
+    /// another logger, e.g. one that dispatches each log record to several loggers.
+    ///
+    /// Other than with [`Logger::build`], `flexi_logger` never calls [`log::set_max_level`],
+    /// also not when the log specification is changed later.
+    /// The enclosing logger is responsible for the global max level and can use
+    /// [`LoggerHandle::max_level`] to take the needs of `flexi_logger` into account.
+    ///
+    /// The method [`Log::enabled`](log::Log::enabled) of the returned logger, and its filtering
+    /// in [`Log::log`](log::Log::log), reflect its own log specification.
     ///
     /// **Keep the [`LoggerHandle`] alive up to the very end of your program!**
     /// See [`Logger::start`] for more details.
@@ -671,39 +1554,144 @@ impl Logger {
     /// # Errors
     ///
     /// Several variants of [`FlexiLoggerError`] can occur.
-    pub fn build(mut self) -> Result<(Box<dyn log::Log>, LoggerHandle), FlexiLoggerError> {
+    pub fn build_nested(self) -> Result<(Box<dyn log::Log>, LoggerHandle), FlexiLoggerError> {
+        self.build_impl(false)
+    }
+
+    #[allow(clippy::too_many_lines)]
+    fn build_impl(
+        mut self,
+        set_global_max_level: bool,
+    ) -> Result<(Box<dyn log::Log>, LoggerHandle), FlexiLoggerError> {
         #[cfg(feature = "colors")]
-        set_palette(self.o_palette.as_deref())?;
+        set_palette(self.o_palette.as_deref(), self.color_scope)?;
+
+        // the clock must be in place before the output files are opened
+        if let Some(clock) = self.o_clock.take() {
+            DeferredNow::set_clock(move || clock());
+        }
+
+        if let Some(adaptive_format) = self.o_adaptive_format_for_stderr {
+            self.format_for_stderr =
+                adaptive_format.format_function(self.color_mode, std::io::stderr().is_terminal());
+        }
+        if let Some(adaptive_format) = self.o_adaptive_format_for_stdout {
+            self.format_for_stdout =
+                adaptive_format.format_function(self.color_mode, std::io::stdout().is_terminal());
+        }
 
         if self.use_utc {
-            self.flwb = self.flwb.use_utc();
+            self.flwb = self.flwb.use_utc().use_utc_for_rotation();
         }
         set_panic_on_error_channel_error(self.panic_on_error_channel_error);
 
+        let o_banner = self.o_banner.as_ref().map(|app_info| {
+            format!(
+                "{app_info}; pid: {}; host: {}; log spec: {}",
+                std::process::id(),
+                hostname(),
+                self.spec
+            )
+        });
+        if let Some(ref banner) = o_banner {
+            self.flwb = self.flwb.banner(banner);
+        }
+
+        let max_level = self.spec.max_level();
+        let a_l_spec = Arc::new(SharedSpec::new(self.spec));
+        if self.config_file_header && !self.flwb.has_file_header() {
+            let config_lines = [
+                format!("format: {}", format_name(self.format_for_file)),
+                format!("rotation: {}", self.flwb.describe_rotation()),
+                format!("write mode: {:?}", self.flwb.get_write_mode()),
+            ];
+            let line_ending = self.flwb.get_line_ending();
+            let a_l_spec = Arc::clone(&a_l_spec);
+            self.flwb = self.flwb.file_header(move |w| {
+                w.write_all(b"flexi_logger version: ")?;
+                w.write_all(env!("CARGO_PKG_VERSION").as_bytes())?;
+                w.write_all(line_ending)?;
+                // the log specification can have been changed since the start
+                a_l_spec.with_current(|spec| write!(w, "log spec: {spec}"))?;
+                w.write_all(line_ending)?;
+                for line in &config_lines {
+                    w.write_all(line.as_bytes())?;
+                    w.write_all(line_ending)?;
+                }
+                Ok(())
+            });
+        }
+
+        let a_other_writers = Arc::new(match self.o_circuit_breaker {
+            None => self.other_writers,
+            Some(breaker) => self
+                .other_writers
+                .into_iter()
+                .map(|(name, writer)| {
+                    let writer: Box<dyn LogWriter> =
+                        Box::new(BreakerWriter::new(&name, writer, breaker));
+                    (name, writer)
+                })
+                .collect(),
+        });
+        // the banner that the output file gets after a rotation goes to the other writers, too
+        let o_banner_forward = o_banner
+            .as_ref()
+            .filter(|_| !a_other_writers.is_empty())
+            .map(|_| {
+                let a_other_writers = Arc::clone(&a_other_writers);
+                let forward: Arc<dyn Fn(&log::Record) + Send + Sync> = Arc::new(move |record| {
+                    write_banner_record(&a_other_writers, &mut DeferredNow::new(), record);
+                });
+                forward
+            });
+
+        let write_mode = *self.flwb.get_write_mode();
+        let post_format_for_stderr = PostFormat {
+            o_max_line_length: self.o_max_line_length,
+            escape_control_chars: self.escape_control_chars_for_stderr,
+        };
+        let post_format_for_stdout = PostFormat {
+            o_max_line_length: self.o_max_line_length,
+            escape_control_chars: self.escape_control_chars_for_stdout,
+        };
         let a_primary_writer = Arc::new(match self.log_target {
             LogTarget::StdOut => {
                 if let WriteMode::SupportCapture = self.flwb.get_write_mode() {
-                    PrimaryWriter::test(true, self.format_for_stdout)
+                    PrimaryWriter::test(true, self.format_for_stdout, post_format_for_stdout)
                 } else {
-                    PrimaryWriter::stdout(self.format_for_stdout, self.flwb.get_write_mode())
+                    PrimaryWriter::stdout(
+                        self.format_for_stdout,
+                        post_format_for_stdout,
+                        self.flwb.get_write_mode(),
+                    )
                 }
             }
             LogTarget::StdErr => {
                 if let WriteMode::SupportCapture = self.flwb.get_write_mode() {
-                    PrimaryWriter::test(false, self.format_for_stderr)
+                    PrimaryWriter::test(false, self.format_for_stderr, post_format_for_stderr)
                 } else {
-                    PrimaryWriter::stderr(self.format_for_stderr, self.flwb.get_write_mode())
+                    PrimaryWriter::stderr(
+                        self.format_for_stderr,
+                        post_format_for_stderr,
+                        self.flwb.get_write_mode(),
+                    )
                 }
             }
             LogTarget::Multi(use_file, mut o_writer) => PrimaryWriter::multi(
                 self.duplicate_err,
                 self.duplicate_out,
-                WriteMode::SupportCapture == *self.flwb.get_write_mode(),
+                &write_mode,
                 self.format_for_stderr,
                 self.format_for_stdout,
+                post_format_for_stderr,
+                post_format_for_stdout,
                 if use_file {
                     Some(Box::new(
-                        self.flwb.format(self.format_for_file).try_build()?,
+                        self.flwb
+                            .format(self.format_for_file)
+                            .o_forward_banner(o_banner_forward)
+                            .try_build()?,
                     ))
                 } else {
                     None
@@ -715,39 +1703,91 @@ impl Logger {
                     o_writer
                 },
             ),
+            #[cfg(not(target_family = "wasm"))]
+            LogTarget::SplitFiles(split_file_spec, threshold) => {
+                let flwb = self.flwb.format(self.format_for_file);
+                let mut split_flwb = flwb.clone().file_spec(split_file_spec).max_level(threshold);
+                if let Some((criterion, naming, cleanup)) = self.o_split_rotation {
+                    split_flwb = split_flwb.rotate(criterion, naming, cleanup);
+                }
+                let split_writer = split_flwb.try_build()?;
+                PrimaryWriter::multi(
+                    self.duplicate_err,
+                    self.duplicate_out,
+                    &write_mode,
+                    self.format_for_stderr,
+                    self.format_for_stdout,
+                    post_format_for_stderr,
+                    post_format_for_stdout,
+                    Some(Box::new(
+                        flwb.o_forward_banner(o_banner_forward).try_build()?,
+                    )),
+                    Some(Box::new(split_writer)),
+                )
+            }
         });
 
-        let a_other_writers = Arc::new(self.other_writers);
-
-        if self.flush_interval != ZERO_DURATION {
-            start_flusher_thread(
+        let o_flusher = if self.flush_interval == ZERO_DURATION {
+            None
+        } else {
+            Some(Arc::new(start_flusher_thread(
                 Arc::clone(&a_primary_writer),
                 Arc::clone(&a_other_writers),
                 self.flush_interval,
-            )?;
-        }
+            )?))
+        };
 
-        let max_level = self.spec.max_level();
-        let a_l_spec = Arc::new(RwLock::new(self.spec));
         set_error_channel(self.error_channel);
+        set_error_file_rotation(self.o_error_file_rotation);
 
         // initialize the lazy_statics in DeferredNow before threads are spawned
         if self.use_utc {
             DeferredNow::force_utc();
         }
+        if self.use_monotonic_timestamps {
+            DeferredNow::force_monotonic();
+        }
         let mut now = DeferredNow::new();
         now.now();
 
-        let flexi_logger = FlexiLogger::new(
+        let a_max_level = Arc::new(AtomicUsize::new(log::LevelFilter::max() as usize));
+        let a_flexi_logger = Arc::new(FlexiLogger::new(
             Arc::clone(&a_l_spec),
+            Arc::clone(&a_max_level),
             Arc::clone(&a_primary_writer),
             Arc::clone(&a_other_writers),
+            self.writer_duplicates
+                .into_iter()
+                .map(|(name, dup)| (name, dup.into()))
+                .collect(),
             self.filter,
-        );
+            #[cfg(all(feature = "textfilter", feature = "kv"))]
+            self.textfilter_with_kv,
+            self.use_sequence_numbers,
+            self.o_rewriter,
+            self.o_writer_target_delimiters,
+        ));
 
-        let handle = LoggerHandle::new(a_l_spec, a_primary_writer, a_other_writers);
+        if let Some(banner) = o_banner {
+            write_banner(&banner, &a_primary_writer, &a_other_writers, &mut now);
+        }
+
+        #[allow(unused_mut)]
+        let mut handle = LoggerHandle::new(
+            a_l_spec,
+            a_max_level,
+            a_primary_writer,
+            a_other_writers,
+            o_flusher,
+            set_global_max_level,
+            Arc::clone(&a_flexi_logger),
+        );
         handle.reconfigure(max_level);
-        Ok((Box::new(flexi_logger), handle))
+        #[cfg(all(feature = "signal_hook", unix))]
+        if self.reopen_on_sighup {
+            handle.start_sighup_listener()?;
+        }
+        Ok((Box::new(SharedFlexiLogger(a_flexi_logger)), handle))
     }
 
     /// Consumes the Logger object and initializes `flexi_logger` in a way that
@@ -798,6 +1838,21 @@ impl Logger {
     /// #'mod2::mod3' = 'trace'
     /// ```
     ///
+    /// If the logger writes to a file or another writer, and thus supports duplication
+    /// (see [`Logger::duplicate_to_stderr`] and [`Logger::duplicate_to_stdout`]),
+    /// the file additionally starts with the current duplication levels:
+    ///
+    /// ```toml
+    /// ### Optional: Duplicate log lines to stderr or stdout (none, error, warn, info, debug, trace, all)
+    /// duplicate_to_stderr = 'none'
+    /// duplicate_to_stdout = 'none'
+    /// ```
+    ///
+    /// Changes to these values are applied like with
+    /// [`LoggerHandle::adapt_duplication_to_stderr`] and
+    /// [`LoggerHandle::adapt_duplication_to_stdout`];
+    /// if they are omitted, the duplication remains unchanged.
+    ///
     /// You can subsequently edit and modify the file according to your needs,
     /// while the program is running, and it will immediately take your changes into account.
     ///
@@ -857,6 +1912,34 @@ impl Logger {
     }
 }
 
+// Writes the banner to all writers, independent of the log specification
+fn write_banner(
+    banner: &str,
+    primary_writer: &PrimaryWriter,
+    other_writers: &HashMap<String, Box<dyn LogWriter>>,
+    now: &mut DeferredNow,
+) {
+    with_banner_record(banner, |record| {
+        primary_writer
+            .write(now, record)
+            .unwrap_or_else(|e| eprint_err(ErrorCode::Write, "writing banner failed", &e));
+        write_banner_record(other_writers, now, record);
+    });
+}
+
+// Writes the banner record to the additional writers
+fn write_banner_record(
+    other_writers: &HashMap<String, Box<dyn LogWriter>>,
+    now: &mut DeferredNow,
+    record: &log::Record,
+) {
+    for writer in other_writers.values() {
+        writer
+            .write(now, record)
+            .unwrap_or_else(|e| eprint_err(ErrorCode::Write, "writing banner failed", &e));
+    }
+}
+
 // Reread the specfile when it was updated
 #[cfg(feature = "specfile")]
 pub(crate) fn create_specfile_watcher<S: LogSpecSubscriber>(
@@ -870,18 +1953,13 @@ pub(crate) fn create_specfile_watcher<S: LogSpecSubscriber>(
     let parent = clone.parent().unwrap(/*cannot fail*/);
 
     let mut debouncer = new_debouncer(
-        std::time::Duration::from_millis(1000),
+        std::time::Duration::from_secs(1),
         move |res: DebounceEventResult| match res {
             Ok(events) => events.iter().for_each(|e| {
-                if e.path
-                    .canonicalize()
-                    .map(|x| x == specfile)
-                    .unwrap_or(false)
-                {
+                if e.path.canonicalize().is_ok_and(|x| x == specfile) {
                     log_spec_string_from_file(&specfile)
                         .map_err(FlexiLoggerError::SpecfileIo)
-                        .and_then(LogSpecification::from_toml)
-                        .and_then(|spec| subscriber.set_new_spec(spec))
+                        .and_then(|s| update_subscriber_from_toml(&mut subscriber, &s))
                         .map_err(|e| {
                             eprint_err(
                                 ErrorCode::LogSpecFile,
@@ -931,7 +2009,7 @@ pub(crate) fn synchronize_subscriber_with_specfile<S: LogSpecSubscriber>(
 
     if Path::is_file(specfile) {
         let s = log_spec_string_from_file(specfile).map_err(FlexiLoggerError::SpecfileIo)?;
-        subscriber.set_new_spec(LogSpecification::from_toml(s)?)?;
+        update_subscriber_from_toml(subscriber, &s)?;
     } else {
         if let Some(specfolder) = specfile.parent() {
             std::fs::DirBuilder::new()
@@ -945,11 +2023,57 @@ pub(crate) fn synchronize_subscriber_with_specfile<S: LogSpecSubscriber>(
             .open(specfile)
             .map_err(FlexiLoggerError::SpecfileIo)?;
 
+        if let Some((dup_err, dup_out)) = subscriber.initial_duplication() {
+            write!(
+                file,
+                "### Optional: Duplicate log lines to stderr or stdout \
+                 (none, error, warn, info, debug, trace, all)\n\
+                 duplicate_to_stderr = '{}'\n\
+                 duplicate_to_stdout = '{}'\n\n",
+                format!("{dup_err:?}").to_lowercase(),
+                format!("{dup_out:?}").to_lowercase(),
+            )
+            .map_err(FlexiLoggerError::SpecfileIo)?;
+        }
         subscriber.initial_spec()?.to_toml(&mut file)?;
     }
     Ok(())
 }
 
+// Parses the content of the specfile and updates the subscriber's logspec and duplication.
+#[cfg(feature = "specfile_without_notification")]
+fn update_subscriber_from_toml<S: LogSpecSubscriber>(
+    subscriber: &mut S,
+    s: &str,
+) -> Result<(), FlexiLoggerError> {
+    let spec = LogSpecification::from_toml(s)?;
+    let (o_dup_err, o_dup_out) = duplication_from_toml(s)?;
+    subscriber.set_new_spec(spec)?;
+    subscriber.set_new_duplication(o_dup_err, o_dup_out)
+}
+
+#[cfg(feature = "specfile_without_notification")]
+fn duplication_from_toml(
+    s: &str,
+) -> Result<(Option<Duplicate>, Option<Duplicate>), FlexiLoggerError> {
+    #[derive(serde_derive::Deserialize)]
+    struct DuplicationFileFormat {
+        duplicate_to_stderr: Option<String>,
+        duplicate_to_stdout: Option<String>,
+    }
+    let dup_ff: DuplicationFileFormat = toml::from_str(s)?;
+    Ok((
+        dup_ff
+            .duplicate_to_stderr
+            .map(|s| s.parse::<Duplicate>())
+            .transpose()?,
+        dup_ff
+            .duplicate_to_stdout
+            .map(|s| s.parse::<Duplicate>())
+            .transpose()?,
+    ))
+}
+
 #[cfg(feature = "specfile_without_notification")]
 pub(crate) fn log_spec_string_from_file<P: AsRef<Path>>(
     specfile: P,
@@ -961,7 +2085,14 @@ pub(crate) fn log_spec_string_from_file<P: AsRef<Path>>(
 }
 
 /// Used to control which messages are to be duplicated to stderr, when `log_to_file()` is used.
-#[derive(Debug, Clone, Copy)]
+///
+/// `Duplicate` can be converted from and into [`LevelFilter`];
+/// the conversions round-trip for all variants except `Duplicate::All`,
+/// which is converted into `LevelFilter::Trace` and thus comes back as `Duplicate::Trace`.
+///
+/// `Duplicate` also implements [`FromStr`](std::str::FromStr), so that values like
+/// `--duplicate-stderr=warn` can be taken directly from a command line or a config file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Duplicate {
     /// No messages are duplicated.
     None = 0,
@@ -1005,6 +2136,7 @@ impl From<LevelFilter> for Duplicate {
         }
     }
 }
+
 impl From<Duplicate> for LevelFilter {
     fn from(level: Duplicate) -> Self {
         match level {
@@ -1017,3 +2149,95 @@ impl From<Duplicate> for LevelFilter {
         }
     }
 }
+
+impl std::str::FromStr for Duplicate {
+    type Err = FlexiLoggerError;
+
+    /// Parses the given string case-insensitively.
+    ///
+    /// Accepted values are `none` (or `off`), `error`, `warn`, `info`, `debug`, `trace`,
+    /// and `all`.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::LevelFilter` if the string is not one of the accepted values.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_ref() {
+            "none" | "off" => Ok(Duplicate::None),
+            "error" => Ok(Duplicate::Error),
+            "warn" => Ok(Duplicate::Warn),
+            "info" => Ok(Duplicate::Info),
+            "debug" => Ok(Duplicate::Debug),
+            "trace" => Ok(Duplicate::Trace),
+            "all" => Ok(Duplicate::All),
+            _ => Err(FlexiLoggerError::LevelFilter(format!(
+                "unknown value for Duplicate: {s}"
+            ))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::Duplicate;
+    use crate::{ConfigWarning, FileSpec, Logger};
+    use log::LevelFilter;
+
+    #[cfg(feature = "specfile_without_notification")]
+    #[test]
+    fn duplication_from_toml() {
+        assert_eq!(
+            super::duplication_from_toml("global_level = 'info'\n[modules]\n").unwrap(),
+            (None, None)
+        );
+        assert_eq!(
+            super::duplication_from_toml(
+                "duplicate_to_stderr = 'warn'\nduplicate_to_stdout = 'None'\n[modules]\n"
+            )
+            .unwrap(),
+            (Some(Duplicate::Warn), Some(Duplicate::None))
+        );
+        assert!(super::duplication_from_toml("duplicate_to_stderr = 'warning'\n").is_err());
+    }
+
+    #[test]
+    fn duplicate_from_str() {
+        assert_eq!("none".parse::<Duplicate>().unwrap(), Duplicate::None);
+        assert_eq!("Off".parse::<Duplicate>().unwrap(), Duplicate::None);
+        assert_eq!("error".parse::<Duplicate>().unwrap(), Duplicate::Error);
+        assert_eq!("WARN".parse::<Duplicate>().unwrap(), Duplicate::Warn);
+        assert_eq!("info".parse::<Duplicate>().unwrap(), Duplicate::Info);
+        assert_eq!("debug".parse::<Duplicate>().unwrap(), Duplicate::Debug);
+        assert_eq!("trace".parse::<Duplicate>().unwrap(), Duplicate::Trace);
+        assert_eq!(" all ".parse::<Duplicate>().unwrap(), Duplicate::All);
+        assert!("warning".parse::<Duplicate>().is_err());
+        assert!("".parse::<Duplicate>().is_err());
+    }
+
+    #[test]
+    fn duplicate_level_filter_round_trip() {
+        for lf in [
+            LevelFilter::Off,
+            LevelFilter::Error,
+            LevelFilter::Warn,
+            LevelFilter::Info,
+            LevelFilter::Debug,
+            LevelFilter::Trace,
+        ] {
+            assert_eq!(LevelFilter::from(Duplicate::from(lf)), lf);
+        }
+        assert_eq!(LevelFilter::from(Duplicate::All), LevelFilter::Trace);
+    }
+
+    #[test]
+    fn config_file_header_keeps_file_header() {
+        let mut logger = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(FileSpec::default())
+            .config_file_header();
+        assert!(logger.check().is_empty());
+
+        logger.flwb = logger.flwb.file_header(|w| w.write_all(b"my header\n"));
+        assert_eq!(logger.check(), vec![ConfigWarning::FileHeaderConflict]);
+    }
+}
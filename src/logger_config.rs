@@ -0,0 +1,176 @@
+//! Builds a [`Logger`] from a deserializable configuration, see [`Logger::try_from_config`].
+#[cfg(not(target_family = "wasm"))]
+use crate::FileSpec;
+use crate::{
+    env_config::{apply_format, parse_cleanup, parse_criterion, parse_naming, parse_write_mode},
+    Duplicate, FlexiLoggerError, LogSpecification, Logger,
+};
+use serde_derive::Deserialize;
+use std::path::PathBuf;
+
+/// Configuration of a [`Logger`] that can be read with `serde`,
+/// e.g. as part of the configuration file of an application.
+///
+/// All fields are optional. The values use the same syntax as
+/// [`Logger::try_from_env_config`], e.g. in TOML:
+///
+/// ```toml
+/// spec = "info, my_crate::db = debug"
+/// format = "detailed"
+/// duplicate_to_stderr = "warn"
+/// write_mode = "buffer_and_flush"
+///
+/// [file]
+/// directory = "/var/log/myapp"
+/// basename = "myapp"
+/// suppress_timestamp = true
+///
+/// [rotate]
+/// criterion = "day|10M"
+/// naming = "timestamps"
+/// cleanup = "keep_log_files:7"
+/// ```
+///
+/// See [`Logger::try_from_config`].
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct LoggerConfig {
+    /// The log specification, see [`LogSpecification::parse`]; default: `off`.
+    pub spec: Option<String>,
+    /// If set, the log is written to files, otherwise to stderr.
+    pub file: Option<FileConfig>,
+    /// The rotation of the log files; requires [`LoggerConfig::file`].
+    pub rotate: Option<RotateConfig>,
+    /// The name of a format function, e.g. `default`, `detailed`, or `colored_opt`.
+    pub format: Option<String>,
+    /// Which log lines are duplicated to stderr: `none`, `error`, `warn`, `info`,
+    /// `debug`, `trace`, or `all`.
+    pub duplicate_to_stderr: Option<String>,
+    /// Which log lines are duplicated to stdout, with the same values as
+    /// [`LoggerConfig::duplicate_to_stderr`].
+    pub duplicate_to_stdout: Option<String>,
+    /// The write mode: `direct`, `support_capture`, `buffer_and_flush`,
+    /// `buffer_dont_flush`, or `async`.
+    pub write_mode: Option<String>,
+    /// Append to existing log files rather than truncating them, see [`Logger::append`].
+    pub append: bool,
+    /// Use UTC timestamps, see [`Logger::use_utc`].
+    pub use_utc: bool,
+    /// Use UTC timestamps only in the file names, see [`Logger::use_utc_for_file_names`].
+    pub use_utc_for_file_names: bool,
+}
+
+/// Part of [`LoggerConfig`] that describes the log files, see [`FileSpec`].
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+#[non_exhaustive]
+pub struct FileConfig {
+    /// The directory of the log files; default: the current working directory.
+    pub directory: Option<PathBuf>,
+    /// The basename of the log files; default: the program name.
+    pub basename: Option<String>,
+    /// An additional part of the file names.
+    pub discriminant: Option<String>,
+    /// The suffix of the log files; default: `log`.
+    pub suffix: Option<String>,
+    /// Omit the timestamp from the file names.
+    pub suppress_timestamp: bool,
+}
+
+/// Part of [`LoggerConfig`] that describes the rotation of the log files.
+#[derive(Clone, Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+#[non_exhaustive]
+pub struct RotateConfig {
+    /// A size in bytes, optionally with factor `K`, `M`, or `G` (e.g. `10M`),
+    /// or an age (`month`, `week` (starting on Monday), `day`, `hour`, `minute`, or `second`),
+    /// or both, separated by `|`, see [`Criterion`](crate::Criterion).
+    pub criterion: String,
+    /// `numbers` (default), `numbers_direct`, `numbers_shifted`, `timestamps`,
+    /// or `timestamps_direct`, see [`Naming`](crate::Naming).
+    #[serde(default)]
+    pub naming: Option<String>,
+    /// `never` (default), `keep_log_files:<n>`, `keep_log_files_and_duration:<n>:<seconds>`,
+    /// `keep_compressed_files:<n>`, or `keep_log_and_compressed_files:<n>:<m>`,
+    /// see [`Cleanup`](crate::Cleanup).
+    #[serde(default)]
+    pub cleanup: Option<String>,
+}
+
+impl FileConfig {
+    #[cfg(not(target_family = "wasm"))]
+    fn file_spec(&self) -> FileSpec {
+        let file_spec = FileSpec::default()
+            .o_directory(self.directory.as_ref())
+            .o_basename(self.basename.as_ref())
+            .o_discriminant(self.discriminant.as_ref())
+            .use_timestamp(!self.suppress_timestamp);
+        match self.suffix {
+            Some(ref suffix) => file_spec.suffix(suffix),
+            None => file_spec,
+        }
+    }
+}
+
+pub(crate) fn logger_from_config(config: LoggerConfig) -> Result<Logger, FlexiLoggerError> {
+    let spec = match config.spec {
+        Some(spec) => LogSpecification::parse(spec)?,
+        None => LogSpecification::default(),
+    };
+    let mut logger = Logger::with(spec);
+
+    #[cfg(not(target_family = "wasm"))]
+    if let Some(ref file) = config.file {
+        logger = logger.log_to_file(file.file_spec());
+    }
+    #[cfg(target_family = "wasm")]
+    if config.file.is_some() {
+        return Err(config_error("file", "files are not supported"));
+    }
+    if let Some(rotate) = config.rotate {
+        if config.file.is_none() {
+            return Err(config_error("rotate", "file is not specified"));
+        }
+        let criterion =
+            parse_criterion(&rotate.criterion).map_err(|e| config_error("rotate", &e))?;
+        let naming = parse_naming(rotate.naming.as_deref().unwrap_or("numbers"))
+            .map_err(|e| config_error("rotate", &e))?;
+        let cleanup = parse_cleanup(rotate.cleanup.as_deref().unwrap_or("never"))
+            .map_err(|e| config_error("rotate", &e))?;
+        logger = logger.rotate(criterion, naming, cleanup);
+    }
+    if let Some(format) = config.format {
+        logger = apply_format(logger, &format).map_err(|e| config_error("format", &e))?;
+    }
+    if let Some(duplicate) = config.duplicate_to_stderr {
+        logger = logger.duplicate_to_stderr(parse_duplicate("duplicate_to_stderr", &duplicate)?);
+    }
+    if let Some(duplicate) = config.duplicate_to_stdout {
+        logger = logger.duplicate_to_stdout(parse_duplicate("duplicate_to_stdout", &duplicate)?);
+    }
+    if let Some(write_mode) = config.write_mode {
+        logger = logger
+            .write_mode(parse_write_mode(&write_mode).map_err(|e| config_error("write_mode", &e))?);
+    }
+    if config.append {
+        logger = logger.append();
+    }
+    if config.use_utc {
+        logger = logger.use_utc();
+    }
+    if config.use_utc_for_file_names {
+        logger = logger.use_utc_for_file_names();
+    }
+    Ok(logger)
+}
+
+fn config_error(field: &str, msg: &str) -> FlexiLoggerError {
+    FlexiLoggerError::Config(format!("{field}: {msg}"))
+}
+
+fn parse_duplicate(field: &str, duplicate: &str) -> Result<Duplicate, FlexiLoggerError> {
+    duplicate
+        .parse::<Duplicate>()
+        .map_err(|_| config_error(field, &format!("unknown value: {duplicate}")))
+}
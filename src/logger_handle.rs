@@ -1,19 +1,37 @@
+#[cfg(all(feature = "signal_hook", unix))]
+use crate::sighup::SighupListener;
 use crate::{
+    flexi_logger::FlexiLogger,
     primary_writer::PrimaryWriter,
-    util::{eprint_err, ErrorCode},
-    writers::{FileLogWriterBuilder, FileLogWriterConfig, LogWriter},
-    Duplicate, FlexiLoggerError, LogSpecification,
+    resources::ThreadRegistration,
+    shared_spec::SharedSpec,
+    threads::FlusherHandle,
+    util::{existing_error_files, get_error_channel, set_error_channel},
+    writers::{
+        file_log_writer::{FileLogWriterConfig, RotationEvent},
+        LogWriter,
+    },
+    CleanupStatus, Duplicate, ErrorChannel, FlexiLoggerError, LogSpecBuilder, LogSpecification,
+    ModuleFilter, ResourceReport,
 };
 #[cfg(feature = "specfile")]
 use notify_debouncer_mini::{notify::RecommendedWatcher, Debouncer};
-#[cfg(feature = "specfile")]
-use std::sync::Mutex;
 use std::{
     collections::HashMap,
+    future::Future,
     path::PathBuf,
-    sync::{Arc, RwLock},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::Receiver,
+        Arc, Mutex,
+    },
+    task::{Context, Poll, Waker},
+    thread::Builder as ThreadBuilder,
 };
 
+const ASYNC_SHUTDOWN: &str = "flexi_logger-async_shutdown";
+
 /// Allows reconfiguring the logger while the program is running, and
 /// **shuts down the logger when it is dropped**.
 ///
@@ -75,7 +93,7 @@ use std::{
 /// # }
 /// ```
 ///
-/// However, when debugging, you often want to modify the log spec only temporarily, for  
+/// However, when debugging, you often want to modify the log spec only temporarily, for
 /// one or few method calls only; this is easier done with the following method, because
 /// it allows switching back to the previous spec:
 ///
@@ -106,22 +124,33 @@ where
     //     between threads safely
 {
     pub(crate) writers_handle: WritersHandle,
+    a_flexi_logger: Arc<FlexiLogger>,
     #[cfg(feature = "specfile")]
     pub(crate) oam_specfile_watcher: Option<Arc<Mutex<Debouncer<RecommendedWatcher>>>>,
 }
 impl LoggerHandle {
     pub(crate) fn new(
-        spec: Arc<RwLock<LogSpecification>>,
+        spec: Arc<SharedSpec>,
+        a_max_level: Arc<AtomicUsize>,
         primary_writer: Arc<PrimaryWriter>,
         other_writers: Arc<HashMap<String, Box<dyn LogWriter>>>,
+        o_flusher: Option<Arc<FlusherHandle>>,
+        set_global_max_level: bool,
+        a_flexi_logger: Arc<FlexiLogger>,
     ) -> Self {
         Self {
             writers_handle: WritersHandle {
                 spec,
+                a_max_level,
                 spec_stack: Vec::default(),
                 primary_writer,
                 other_writers,
+                o_flusher,
+                #[cfg(all(feature = "signal_hook", unix))]
+                o_sighup_listener: None,
+                set_global_max_level,
             },
+            a_flexi_logger,
             #[cfg(feature = "specfile")]
             oam_specfile_watcher: None,
         }
@@ -132,12 +161,70 @@ impl LoggerHandle {
         self.writers_handle.reconfigure(max_level);
     }
 
+    // Lets the output be re-opened whenever the process receives SIGHUP.
+    #[cfg(all(feature = "signal_hook", unix))]
+    pub(crate) fn start_sighup_listener(&mut self) -> Result<(), FlexiLoggerError> {
+        // the listener must not keep the writers alive
+        let w_primary_writer = Arc::downgrade(&self.writers_handle.primary_writer);
+        let w_other_writers = Arc::downgrade(&self.writers_handle.other_writers);
+        let sighup_listener = SighupListener::start(move || {
+            match (w_primary_writer.upgrade(), w_other_writers.upgrade()) {
+                (Some(primary_writer), Some(other_writers)) => {
+                    reopen_output(&primary_writer, &other_writers)
+                }
+                _ => Ok(()),
+            }
+        })?;
+        self.writers_handle.o_sighup_listener = Some(Arc::new(sighup_listener));
+        Ok(())
+    }
+
+    /// Writes a log record that was not produced with the macros of the `log` crate,
+    /// e.g. because it comes from a C library that reports its log messages
+    /// through a callback.
+    ///
+    /// The record is handled exactly like records that are logged with the macros:
+    /// it is filtered with the log specification, using `target` as module path,
+    /// and it is then written, formatted and duplicated like any other log line;
+    /// a `target` of the form `{Alert,_Default}` addresses additional writers.
+    ///
+    /// The record has no module path, file, or line number.
+    ///
+    /// ```rust
+    /// # use flexi_logger::Logger;
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let logger = Logger::try_with_str("info, libfoo=warn")?.start()?;
+    /// // in the callback of libfoo:
+    /// logger.write_record(log::Level::Warn, "libfoo", "disk almost full");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn write_record(&self, level: log::Level, target: &str, message: &str) {
+        log::Log::log(
+            &*self.a_flexi_logger,
+            &log::Record::builder()
+                .level(level)
+                .target(target)
+                .args(format_args!("{message}"))
+                .build(),
+        );
+    }
+
+    /// Returns the most verbose level that is accepted by the active `LogSpecification`
+    /// or by one of the additional writers.
+    ///
+    /// This is the level that `flexi_logger` sets with [`log::set_max_level`];
+    /// a logger that is built with [`Logger::build_nested`](crate::Logger::build_nested)
+    /// leaves this to the enclosing logger, which can use this method.
+    #[must_use]
+    pub fn max_level(&self) -> log::LevelFilter {
+        let spec_max_level = self.writers_handle.spec.read().max_level();
+        self.writers_handle.effective_max_level(spec_max_level)
+    }
+
     /// Replaces the active `LogSpecification`.
     pub fn set_new_spec(&self, new_spec: LogSpecification) {
-        self.writers_handle
-            .set_new_spec(new_spec)
-            .map_err(|e| eprint_err(ErrorCode::Poison, "rwlock on log spec is poisoned", &e))
-            .ok();
+        self.writers_handle.set_new_spec(new_spec);
     }
 
     /// Tries to replace the active `LogSpecification` with the result from parsing the given String.
@@ -150,12 +237,41 @@ impl LoggerHandle {
         Ok(())
     }
 
+    /// Modifies the active `LogSpecification` with the given function.
+    ///
+    /// The function is called with a [`LogSpecBuilder`] that is initialized with
+    /// the module filters of the active `LogSpecification`;
+    /// the text filter of the active `LogSpecification` is retained.
+    /// This allows adding or removing individual module filters without recreating
+    /// the complete specification. The modification is done atomically,
+    /// i.e., concurrent modifications of the log specification cannot get lost.
+    ///
+    /// The function must not write log lines, because the log specification is locked
+    /// while it is executed.
+    ///
+    /// ```rust
+    /// # use flexi_logger::Logger;
+    /// # use log::LevelFilter;
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let logger = Logger::try_with_str("info, mod1 = debug")?.start()?;
+    /// logger.update_spec_with(|builder| {
+    ///     builder.module("mod2", LevelFilter::Trace).remove("mod1");
+    /// });
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn update_spec_with<F>(&self, f: F)
+    where
+        F: FnOnce(&mut LogSpecBuilder),
+    {
+        self.writers_handle.update_spec_with(f);
+    }
+
     /// Replaces the active `LogSpecification` and pushes the previous one to a stack.
-    #[allow(clippy::missing_panics_doc)]
     pub fn push_temp_spec(&mut self, new_spec: LogSpecification) {
-        self.writers_handle
-            .spec_stack
-            .push(self.writers_handle.spec.read().unwrap(/* catch and expose error? */).clone());
+        let previous_spec = LogSpecification::clone(&self.writers_handle.spec.read());
+        self.writers_handle.spec_stack.push(previous_spec);
         self.set_new_spec(new_spec);
     }
 
@@ -169,14 +285,8 @@ impl LoggerHandle {
         &mut self,
         new_spec: S,
     ) -> Result<(), FlexiLoggerError> {
-        self.writers_handle.spec_stack.push(
-            self.writers_handle
-                .spec
-                .read()
-                .map_err(|_| FlexiLoggerError::Poison)?
-                .clone(),
-        );
-        self.set_new_spec(LogSpecification::parse(new_spec)?);
+        let new_spec = LogSpecification::parse(new_spec)?;
+        self.push_temp_spec(new_spec);
         Ok(())
     }
 
@@ -187,7 +297,99 @@ impl LoggerHandle {
         }
     }
 
+    /// Replaces the active `LogSpecification` until the returned [`SpecGuard`] is dropped;
+    /// then the previous `LogSpecification` is restored.
+    ///
+    /// Other than with [`LoggerHandle::push_temp_spec`] and [`LoggerHandle::pop_temp_spec`],
+    /// the previous spec is also restored if the scope is left early, e.g. with `?`
+    /// or by a panic.
+    ///
+    /// ```rust
+    /// # use flexi_logger::{Logger, LogSpecification};
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let logger = Logger::try_with_str("info")?.start()?;
+    /// {
+    ///     let _guard = logger.scoped_spec(LogSpecification::trace());
+    ///     // critical calls
+    /// }
+    /// // Continue with the log spec you had before.
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn scoped_spec(&self, new_spec: LogSpecification) -> SpecGuard<'_> {
+        let previous_spec = LogSpecification::clone(&self.writers_handle.spec.read());
+        self.set_new_spec(new_spec);
+        SpecGuard {
+            logger_handle: self,
+            o_previous_spec: Some(previous_spec),
+        }
+    }
+
+    /// Tries to replace the active `LogSpecification` with the result from parsing the given
+    /// String until the returned [`SpecGuard`] is dropped.
+    ///
+    /// See [`LoggerHandle::scoped_spec`].
+    ///
+    /// # Errors
+    ///
+    /// [`FlexiLoggerError::Parse`] if the input is malformed.
+    pub fn parse_scoped_spec<S: AsRef<str>>(
+        &self,
+        new_spec: S,
+    ) -> Result<SpecGuard<'_>, FlexiLoggerError> {
+        Ok(self.scoped_spec(LogSpecification::parse(new_spec)?))
+    }
+
+    /// Returns a [`SubtreeHandle`] that controls only the log specification
+    /// of the given module and its submodules.
+    ///
+    /// This allows e.g. plugins or components of a large application to adjust their own
+    /// verbosity without touching the log specification of the rest of the program.
+    ///
+    /// ```rust
+    /// # use flexi_logger::Logger;
+    /// # use std::error::Error;
+    /// # fn main() -> Result<(), Box<dyn Error>> {
+    /// let logger = Logger::try_with_str("info")?.start()?;
+    /// let mut net_handle = logger.scoped("myapp::net");
+    /// // is applied as "info, myapp::net = debug, myapp::net::tcp = trace"
+    /// net_handle.parse_new_spec("debug, tcp = trace")?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    #[must_use]
+    pub fn scoped<S: Into<String>>(&self, module_prefix: S) -> SubtreeHandle {
+        SubtreeHandle {
+            module_prefix: module_prefix.into(),
+            spec: Arc::clone(&self.writers_handle.spec),
+            a_max_level: Arc::clone(&self.writers_handle.a_max_level),
+            other_writers: Arc::clone(&self.writers_handle.other_writers),
+            set_global_max_level: self.writers_handle.set_global_max_level,
+            spec_stack: Vec::new(),
+        }
+    }
+
+    /// Returns the current output channel for `flexi_logger`'s own error messages.
+    #[must_use]
+    pub fn error_channel(&self) -> ErrorChannel {
+        get_error_channel()
+    }
+
+    /// Redirects `flexi_logger`'s own error messages to the given channel.
+    ///
+    /// The initial channel is chosen with [`Logger::error_channel`](crate::Logger::error_channel).
+    /// Note that the error channel is a process-wide setting.
+    pub fn set_error_channel(&self, error_channel: ErrorChannel) {
+        set_error_channel(error_channel);
+    }
+
     /// Flush all writers.
+    ///
+    /// Note that with [`WriteMode::Async`](crate::WriteMode::Async) and
+    /// [`WriteMode::AsyncWith`](crate::WriteMode::AsyncWith), this method only triggers
+    /// the flush; use [`LoggerHandle::flush_and_wait`] to wait until the log lines
+    /// have reached the output.
     pub fn flush(&self) {
         self.writers_handle.primary_writer.flush().ok();
         for writer in self.writers_handle.other_writers.values() {
@@ -195,11 +397,33 @@ impl LoggerHandle {
         }
     }
 
+    /// Flushes all writers and waits until all log lines that were logged before
+    /// have reached their output, but at most for the given timeout.
+    ///
+    /// This is useful e.g. in tests that check the content of log files right after logging,
+    /// especially with [`WriteMode::Async`](crate::WriteMode::Async) and
+    /// [`WriteMode::AsyncWith`](crate::WriteMode::AsyncWith).
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::OutputIo` if flushing failed, with [`std::io::ErrorKind::TimedOut`]
+    /// if the timeout was reached.
+    pub fn flush_and_wait(&self, timeout: std::time::Duration) -> Result<(), FlexiLoggerError> {
+        let deadline = std::time::Instant::now() + timeout;
+        let remaining = || deadline.saturating_duration_since(std::time::Instant::now());
+        self.writers_handle
+            .primary_writer
+            .flush_and_wait(remaining())?;
+        for writer in self.writers_handle.other_writers.values() {
+            writer.flush_and_wait(remaining())?;
+        }
+        Ok(())
+    }
+
     /// Replaces parts of the configuration of the file log writer.
     ///
-    /// Note that neither the write mode nor the format function can be reset and
-    /// that the provided `FileLogWriterBuilder` must have the same values for these as the
-    /// currently used `FileLogWriter`.
+    /// The write mode can be changed as well, see [`FileLogWriter::reset`](crate::writers::FileLogWriter::reset).
+    /// Note that the format function cannot be reset; the current one is kept.
     ///
     /// # Example
     ///
@@ -209,12 +433,14 @@ impl LoggerHandle {
     ///
     /// `FlexiLoggerError::NoFileLogger` if no file log writer is configured.
     ///
-    /// `FlexiLoggerError::Reset` if a reset was tried with a different write mode.
-    ///
     /// `FlexiLoggerError::Io` if the specified path doesn't work.
     ///
     /// `FlexiLoggerError::Poison` if some mutex is poisoned.
-    pub fn reset_flw(&self, flwb: &FileLogWriterBuilder) -> Result<(), FlexiLoggerError> {
+    #[cfg(not(target_family = "wasm"))]
+    pub fn reset_flw(
+        &self,
+        flwb: &crate::writers::FileLogWriterBuilder,
+    ) -> Result<(), FlexiLoggerError> {
         if let PrimaryWriter::Multi(ref mw) = &*self.writers_handle.primary_writer {
             mw.reset_file_log_writer(flwb)
         } else {
@@ -229,7 +455,12 @@ impl LoggerHandle {
     /// `FlexiLoggerError::NoFileLogger` if no file log writer is configured.
     ///
     /// `FlexiLoggerError::Poison` if some mutex is poisoned.
-    pub fn flw_config(&self) -> Result<FileLogWriterConfig, FlexiLoggerError> {
+    #[cfg(not(target_family = "wasm"))]
+    pub fn flw_config(&self) -> Result<crate::writers::FileLogWriterConfig, FlexiLoggerError> {
+        self.file_log_writer_config()
+    }
+
+    fn file_log_writer_config(&self) -> Result<FileLogWriterConfig, FlexiLoggerError> {
         if let PrimaryWriter::Multi(ref mw) = &*self.writers_handle.primary_writer {
             mw.flw_config()
         } else {
@@ -237,6 +468,28 @@ impl LoggerHandle {
         }
     }
 
+    /// Returns the log output that was captured in memory,
+    /// if [`Logger::capture_file_output`](crate::Logger::capture_file_output) was used.
+    ///
+    /// With buffering or asynchronous write modes, call [`LoggerHandle::flush_and_wait`] before,
+    /// to make sure that all log lines have arrived.
+    #[must_use]
+    pub fn captured_output(&self) -> Option<String> {
+        let config = self.file_log_writer_config().ok()?;
+        let buffer = config.captured_output()?.lock().ok()?;
+        Some(String::from_utf8_lossy(&buffer).into_owned())
+    }
+
+    /// Discards the log output that was captured in memory so far,
+    /// if [`Logger::capture_file_output`](crate::Logger::capture_file_output) was used.
+    pub fn clear_captured_output(&self) {
+        if let Ok(config) = self.file_log_writer_config() {
+            if let Some(Ok(mut buffer)) = config.captured_output().map(|b| b.lock()) {
+                buffer.clear();
+            }
+        }
+    }
+
     /// Makes the logger re-open the current log file.
     ///
     /// If the log is written to a file, `flexi_logger` expects that nobody else modifies the file,
@@ -252,10 +505,12 @@ impl LoggerHandle {
     ///
     /// # Example
     ///
-    /// `logrotate` e.g. can be configured to send a `SIGHUP` signal to your program. You need to
-    /// handle `SIGHUP` in your program explicitly,
-    /// e.g. using a crate like [`ctrlc`](https://docs.rs/ctrlc/latest/ctrlc/),
-    /// and call this function from the registered signal handler.
+    /// `logrotate` e.g. can be configured to send a `SIGHUP` signal to your program.
+    /// With feature `signal_hook`, you can let `flexi_logger` handle `SIGHUP` for you,
+    /// see [`Logger::reopen_on_sighup`](crate::Logger::reopen_on_sighup).
+    /// Otherwise you need to handle `SIGHUP` in your program explicitly,
+    /// e.g. using a crate like [`signal-hook`](https://docs.rs/signal-hook/latest/signal_hook/),
+    /// and call this function when the signal was received.
     ///
     /// # Errors
     ///
@@ -263,24 +518,43 @@ impl LoggerHandle {
     ///
     /// Other variants of `FlexiLoggerError`, depending on the used writers.
     pub fn reopen_output(&self) -> Result<(), FlexiLoggerError> {
+        reopen_output(
+            &self.writers_handle.primary_writer,
+            &self.writers_handle.other_writers,
+        )
+    }
+
+    /// Trigger an extra log file rotation.
+    ///
+    /// Does nothing if rotation is not configured.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::Poison` if some mutex is poisoned.
+    ///
+    /// IO errors.
+    pub fn trigger_rotation(&self) -> Result<(), FlexiLoggerError> {
         let mut result = if let PrimaryWriter::Multi(ref mw) = &*self.writers_handle.primary_writer
         {
-            mw.reopen_output()
+            mw.trigger_rotation()
         } else {
             Ok(())
         };
 
         for blw in self.writers_handle.other_writers.values() {
-            let result2 = blw.reopen_output();
+            let result2 = blw.rotate();
             if result.is_ok() && result2.is_err() {
                 result = result2;
             }
         }
-
         result
     }
 
-    /// Trigger an extra log file rotation.
+    /// Runs the cleanup of the rotated log files, independent of a rotation.
+    ///
+    /// This allows reclaiming disk space immediately, e.g. after reducing the number
+    /// of files to keep with [`LoggerHandle::reset_flw`].
+    /// If the cleanup is done in a background thread, this method only triggers it.
     ///
     /// Does nothing if rotation is not configured.
     ///
@@ -289,16 +563,88 @@ impl LoggerHandle {
     /// `FlexiLoggerError::Poison` if some mutex is poisoned.
     ///
     /// IO errors.
-    pub fn trigger_rotation(&self) -> Result<(), FlexiLoggerError> {
+    pub fn trigger_cleanup(&self) -> Result<(), FlexiLoggerError> {
         let mut result = if let PrimaryWriter::Multi(ref mw) = &*self.writers_handle.primary_writer
         {
-            mw.trigger_rotation()
+            mw.trigger_cleanup()
         } else {
             Ok(())
         };
 
         for blw in self.writers_handle.other_writers.values() {
-            let result2 = blw.rotate();
+            let result2 = blw.trigger_cleanup();
+            if result.is_ok() && result2.is_err() {
+                result = result2;
+            }
+        }
+        result
+    }
+
+    /// Checks cheaply if all writers are able to write their output,
+    /// e.g. if the log file is still writable or the syslog is reachable.
+    ///
+    /// Calls [`LogWriter::healthcheck`](crate::writers::LogWriter::healthcheck)
+    /// on the writers of the primary writer and on all additional writers.
+    /// The writers of the primary writer are reported with their
+    /// [`LogWriter::name`](crate::writers::LogWriter::name),
+    /// the additional writers with the name with which they were registered.
+    ///
+    /// For the log file, only the existence and the permissions of the file (or, before
+    /// it is opened, of its directory) are checked. Problems that only show up when
+    /// writing are not detected, e.g. a full disk, a file system that is mounted read-only,
+    /// or a parent directory that is not writable, which prevents the rotation.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::Unhealthy` with the names and errors of all writers
+    /// whose healthcheck failed.
+    pub fn healthcheck(&self) -> Result<(), FlexiLoggerError> {
+        let mut unhealthy =
+            if let PrimaryWriter::Multi(ref mw) = &*self.writers_handle.primary_writer {
+                mw.unhealthy_writers()
+            } else {
+                Vec::new()
+            };
+
+        let mut other_writers = self.writers_handle.other_writers.iter().collect::<Vec<_>>();
+        other_writers.sort_by_key(|(name, _)| *name);
+        for (name, blw) in other_writers {
+            if let Err(e) = blw.healthcheck() {
+                unhealthy.push((name.clone(), e));
+            }
+        }
+
+        if unhealthy.is_empty() {
+            Ok(())
+        } else {
+            Err(FlexiLoggerError::Unhealthy(unhealthy))
+        }
+    }
+
+    /// Renames the current log file to a file with the given infix, like `"before-upgrade"`,
+    /// and continues writing to a fresh log file.
+    ///
+    /// This is useful for marking boundaries like deployments in the log files.
+    /// See [`FileLogWriter::rotate_to`](crate::writers::FileLogWriter::rotate_to) for details.
+    ///
+    /// The additional writers are called with
+    /// [`LogWriter::rotate_to`](crate::writers::LogWriter::rotate_to).
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::Poison` if some mutex is poisoned.
+    ///
+    /// `FlexiLoggerError::OutputIo` if the infix is not usable, and for other IO errors.
+    pub fn rotate_to(&self, infix: &str) -> Result<(), FlexiLoggerError> {
+        let mut result = if let PrimaryWriter::Multi(ref mw) = &*self.writers_handle.primary_writer
+        {
+            mw.rotate_to(infix)
+        } else {
+            Ok(())
+        };
+
+        for blw in self.writers_handle.other_writers.values() {
+            let result2 = blw.rotate_to(infix);
             if result.is_ok() && result2.is_err() {
                 result = result2;
             }
@@ -316,14 +662,73 @@ impl LoggerHandle {
     ///   which could leave unexpected files in the filesystem
     /// - you use your own writer(s), and they need to clean up resources
     ///
+    /// Shutting down stops all threads that `flexi_logger` has started for this logger,
+    /// and closes the log files (a [`FileLogWriter`](crate::writers::FileLogWriter) that
+    /// does not write asynchronously reopens its file if further log lines are written).
+    ///
     /// See also [`writers::LogWriter::shutdown`](crate::writers::LogWriter::shutdown).
     pub fn shutdown(&self) {
-        self.writers_handle.primary_writer.shutdown();
-        for writer in self.writers_handle.other_writers.values() {
-            writer.shutdown();
+        self.writers_handle.shutdown();
+    }
+
+    /// Shuts down all participating writers, like [`LoggerHandle::shutdown`], but without
+    /// blocking the calling thread.
+    ///
+    /// This is the variant for async programs, where blocking an executor thread
+    /// while the log lines are flushed and the writer threads are drained is undesirable.
+    /// The shutdown is done by a separate thread, which is started when the returned future
+    /// is polled for the first time, and which wakes the future when it is done.
+    /// The future thus works with any executor, e.g. with `tokio`:
+    ///
+    /// ```rust,ignore
+    /// #[tokio::main]
+    /// async fn main() -> Result<(), Box<dyn std::error::Error>> {
+    ///     let logger = flexi_logger::Logger::try_with_str("info")?.start()?;
+    ///     // ...
+    ///     logger.shutdown_async().await;
+    ///     Ok(())
+    /// }
+    /// ```
+    ///
+    /// Like all futures, the returned future does nothing unless it is polled:
+    /// if it is dropped without having been polled, no shutdown happens.
+    /// If it is dropped after the first poll, but before it completes,
+    /// the shutdown is still completed in the background.
+    pub fn shutdown_async(&self) -> ShutdownFuture {
+        ShutdownFuture {
+            o_writers_handle: Some(self.writers_handle.clone()),
+            am_state: Arc::new(Mutex::new(ShutdownState::default())),
         }
     }
 
+    /// Returns a snapshot of the threads and open files that `flexi_logger` currently holds.
+    ///
+    /// This is meant for tests that want to verify that [`LoggerHandle::shutdown`]
+    /// releases everything:
+    ///
+    /// ```rust
+    /// # use flexi_logger::{FileSpec, Logger, WriteMode};
+    /// let logger = Logger::try_with_str("info")
+    ///     .unwrap()
+    ///     .log_to_file(FileSpec::default())
+    /// #   .log_to_file(FileSpec::default().directory("log_files/debug_resources"))
+    ///     .write_mode(WriteMode::BufferAndFlush)
+    ///     .start()
+    ///     .unwrap();
+    /// log::info!("something");
+    /// assert!(!logger.debug_resources().is_empty());
+    ///
+    /// logger.shutdown();
+    /// assert!(logger.debug_resources().is_empty());
+    /// ```
+    ///
+    /// Note that the report covers all resources of `flexi_logger` in the current process,
+    /// not only those of this logger.
+    #[must_use]
+    pub fn debug_resources(&self) -> ResourceReport {
+        ResourceReport::current()
+    }
+
     /// Returns the list of existing log files according to the current `FileSpec`.
     ///
     /// Depending on the given selector, the list may include the CURRENT log file
@@ -341,10 +746,58 @@ impl LoggerHandle {
             .writers_handle
             .primary_writer
             .existing_log_files(selector)?;
+        if selector.with_error_files {
+            log_files.append(&mut existing_error_files());
+        }
         log_files.sort();
         Ok(log_files)
     }
 
+    /// Returns the path of the log file that is currently written;
+    /// the path changes with every rotation.
+    ///
+    /// Returns `None` if the logger is not configured for writing to files,
+    /// and as long as the log file is not yet opened, which happens with the first log line.
+    /// See [`FileLogWriter::current_log_file`](crate::writers::FileLogWriter::current_log_file).
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::Poison` if some mutex is poisoned.
+    pub fn current_log_file(&self) -> Result<Option<PathBuf>, FlexiLoggerError> {
+        self.writers_handle.primary_writer.current_log_file()
+    }
+
+    /// Returns information about failures of the cleanup of log files.
+    ///
+    /// The status is empty if the logger is not configured for writing to files
+    /// with rotation and cleanup.
+    /// See [`FileLogWriterBuilder::on_cleanup_failure`](crate::writers::FileLogWriterBuilder::on_cleanup_failure).
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::Poison` if some mutex is poisoned.
+    pub fn cleanup_status(&self) -> Result<CleanupStatus, FlexiLoggerError> {
+        self.writers_handle.primary_writer.cleanup_status()
+    }
+
+    /// Returns a receiver that is informed about each rotation of the log file.
+    ///
+    /// Each [`RotationEvent`](crate::writers::RotationEvent) contains the path of the completed
+    /// log file and the path of the file that is written from now on.
+    /// This allows e.g. uploading completed log files without polling
+    /// [`LoggerHandle::existing_log_files`].
+    ///
+    /// The receiver is disconnected without any event if the logger is not configured for
+    /// writing to files.
+    /// See [`FileLogWriter::subscribe_rotation`](crate::writers::FileLogWriter::subscribe_rotation).
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::Poison` if some mutex is poisoned.
+    pub fn subscribe_rotation(&self) -> Result<Receiver<RotationEvent>, FlexiLoggerError> {
+        self.writers_handle.primary_writer.subscribe_rotation()
+    }
+
     /// Allows re-configuring duplication to stderr.
     ///
     ///  # Errors
@@ -409,11 +862,13 @@ impl LoggerHandle {
 ///         .with_compressed_files()
 /// );
 /// ```
+#[allow(clippy::struct_field_names, clippy::struct_excessive_bools)]
 pub struct LogfileSelector {
     pub(crate) with_plain_files: bool,
     pub(crate) with_r_current: bool,
     pub(crate) with_compressed_files: bool,
     pub(crate) with_configured_current: Option<String>,
+    pub(crate) with_error_files: bool,
 }
 impl Default for LogfileSelector {
     /// Selects plain log files without the `rCURRENT` file.
@@ -423,6 +878,7 @@ impl Default for LogfileSelector {
             with_r_current: false,
             with_compressed_files: false,
             with_configured_current: None,
+            with_error_files: false,
         }
     }
 }
@@ -435,6 +891,7 @@ impl LogfileSelector {
             with_r_current: false,
             with_compressed_files: false,
             with_configured_current: None,
+            with_error_files: false,
         }
     }
     /// Selects additionally the `rCURRENT` file.
@@ -452,44 +909,308 @@ impl LogfileSelector {
     }
 
     /// Selects additionally the compressed log files.
+    ///
+    /// These are recognized by the suffix of the configured [`Compressor`](crate::Compressor),
+    /// which is `gz` by default.
     #[must_use]
     pub fn with_compressed_files(mut self) -> Self {
         self.with_compressed_files = true;
         self
     }
+
+    /// Selects additionally the file with `flexi_logger`'s own error messages,
+    /// and its rotated files, if [`ErrorChannel::File`] is used.
+    ///
+    /// See [`Logger::rotate_error_file`](crate::Logger::rotate_error_file).
+    #[must_use]
+    pub fn with_error_files(mut self) -> Self {
+        self.with_error_files = true;
+        self
+    }
+}
+
+/// Completes when the shutdown of the logger is done.
+///
+/// Returned from [`LoggerHandle::shutdown_async`].
+#[must_use = "futures do nothing unless they are awaited or polled"]
+pub struct ShutdownFuture {
+    o_writers_handle: Option<WritersHandle>,
+    am_state: Arc<Mutex<ShutdownState>>,
+}
+#[derive(Default)]
+struct ShutdownState {
+    done: bool,
+    o_waker: Option<Waker>,
+}
+impl Future for ShutdownFuture {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+        {
+            let Ok(mut state) = self.am_state.lock() else {
+                return Poll::Ready(());
+            };
+            if state.done {
+                return Poll::Ready(());
+            }
+            state.o_waker = Some(cx.waker().clone());
+        }
+
+        if let Some(writers_handle) = self.o_writers_handle.take() {
+            let am_state = Arc::clone(&self.am_state);
+            let registration = ThreadRegistration::new(ASYNC_SHUTDOWN);
+            let fallback_handle = writers_handle.clone();
+            let spawn_result =
+                ThreadBuilder::new()
+                    .name(ASYNC_SHUTDOWN.to_string())
+                    .spawn(move || {
+                        writers_handle.shutdown();
+                        // the thread is done when the future completes
+                        drop(registration);
+                        if let Ok(mut state) = am_state.lock() {
+                            state.done = true;
+                            if let Some(waker) = state.o_waker.take() {
+                                waker.wake();
+                            }
+                        }
+                    });
+            if spawn_result.is_err() {
+                // shut down in the calling thread then
+                fallback_handle.shutdown();
+                return Poll::Ready(());
+            }
+        }
+        Poll::Pending
+    }
+}
+
+/// Keeps `flexi_logger` alive, without allowing to reconfigure it.
+///
+/// Returned from [`Logger::start_detached`](crate::Logger::start_detached).
+/// Dropping the guard flushes and shuts down the writers, like dropping a [`LoggerHandle`].
+#[must_use = "logging is shut down immediately if the guard is not kept"]
+pub struct LoggerGuard {
+    _logger_handle: LoggerHandle,
+}
+impl LoggerGuard {
+    pub(crate) fn new(logger_handle: LoggerHandle) -> Self {
+        Self {
+            _logger_handle: logger_handle,
+        }
+    }
+}
+
+/// Restores the previous `LogSpecification` when it is dropped.
+///
+/// Returned from [`LoggerHandle::scoped_spec`] and [`LoggerHandle::parse_scoped_spec`].
+#[must_use = "the previous log specification is restored immediately if the guard is not kept"]
+pub struct SpecGuard<'a> {
+    logger_handle: &'a LoggerHandle,
+    o_previous_spec: Option<LogSpecification>,
+}
+impl Drop for SpecGuard<'_> {
+    fn drop(&mut self) {
+        if let Some(previous_spec) = self.o_previous_spec.take() {
+            self.logger_handle.set_new_spec(previous_spec);
+        }
+    }
+}
+
+/// Controls the log specification for a module and its submodules.
+///
+/// Is created with [`LoggerHandle::scoped`].
+///
+/// The log specifications that are given to a `SubtreeHandle` are interpreted relative
+/// to its module: the default level applies to the module itself,
+/// and the module names are treated as names of submodules.
+/// Applying a log specification replaces all module filters of the subtree
+/// in the active log specification, while the module filters outside the subtree
+/// and the text filter remain untouched.
+/// Modules of the subtree for which no filter is set inherit their level as usual,
+/// i.e., from the closest parent module that has a filter, or from the default level.
+///
+/// Other than [`LoggerHandle::push_temp_spec`] and [`LoggerHandle::pop_temp_spec`],
+/// [`SubtreeHandle::push_temp_spec`] and [`SubtreeHandle::pop_temp_spec`]
+/// only save and restore the module filters of the subtree.
+///
+/// Dropping a `SubtreeHandle` has no effect on the logger.
+pub struct SubtreeHandle {
+    module_prefix: String,
+    spec: Arc<SharedSpec>,
+    a_max_level: Arc<AtomicUsize>,
+    other_writers: Arc<HashMap<String, Box<dyn LogWriter>>>,
+    set_global_max_level: bool,
+    spec_stack: Vec<Vec<ModuleFilter>>,
+}
+impl SubtreeHandle {
+    /// Returns the module whose subtree is controlled by this handle.
+    #[must_use]
+    pub fn module_prefix(&self) -> &str {
+        &self.module_prefix
+    }
+
+    /// Replaces the module filters of the subtree with those of the given `LogSpecification`.
+    pub fn set_new_spec(&self, new_spec: LogSpecification) {
+        self.replace_subtree(new_spec.into_subtree_filters(&self.module_prefix));
+    }
+
+    /// Tries to replace the module filters of the subtree with those from parsing
+    /// the given String.
+    ///
+    /// # Errors
+    ///
+    /// [`FlexiLoggerError::Parse`] if the input is malformed.
+    pub fn parse_new_spec(&self, spec: &str) -> Result<(), FlexiLoggerError> {
+        self.set_new_spec(LogSpecification::parse(spec)?);
+        Ok(())
+    }
+
+    /// Replaces the module filters of the subtree with those of the given `LogSpecification`
+    /// and pushes the previous ones to a stack.
+    pub fn push_temp_spec(&mut self, new_spec: LogSpecification) {
+        let previous_filters =
+            self.replace_subtree(new_spec.into_subtree_filters(&self.module_prefix));
+        self.spec_stack.push(previous_filters);
+    }
+
+    /// Tries to replace the module filters of the subtree with those from parsing
+    /// the given String and pushes the previous ones to a stack.
+    ///
+    /// # Errors
+    ///
+    /// [`FlexiLoggerError::Parse`] if the input is malformed.
+    pub fn parse_and_push_temp_spec<S: AsRef<str>>(
+        &mut self,
+        new_spec: S,
+    ) -> Result<(), FlexiLoggerError> {
+        self.push_temp_spec(LogSpecification::parse(new_spec)?);
+        Ok(())
+    }
+
+    /// Reverts to the previous module filters of the subtree, if any.
+    pub fn pop_temp_spec(&mut self) {
+        if let Some(previous_filters) = self.spec_stack.pop() {
+            self.replace_subtree(previous_filters);
+        }
+    }
+
+    fn replace_subtree(&self, module_filters: Vec<ModuleFilter>) -> Vec<ModuleFilter> {
+        self.spec.update(|spec| {
+            let replaced = spec.replace_subtree(&self.module_prefix, module_filters);
+            apply_max_level(
+                effective_max_level(&self.other_writers, spec.max_level()),
+                &self.a_max_level,
+                self.set_global_max_level,
+            );
+            replaced
+        })
+    }
 }
 
 #[derive(Clone)]
 pub(crate) struct WritersHandle {
-    spec: Arc<RwLock<LogSpecification>>,
+    spec: Arc<SharedSpec>,
+    a_max_level: Arc<AtomicUsize>,
     spec_stack: Vec<LogSpecification>,
     primary_writer: Arc<PrimaryWriter>,
     other_writers: Arc<HashMap<String, Box<dyn LogWriter>>>,
+    o_flusher: Option<Arc<FlusherHandle>>,
+    #[cfg(all(feature = "signal_hook", unix))]
+    o_sighup_listener: Option<Arc<SighupListener>>,
+    set_global_max_level: bool,
 }
 impl WritersHandle {
-    fn set_new_spec(&self, new_spec: LogSpecification) -> Result<(), FlexiLoggerError> {
+    fn shutdown(&self) {
+        #[cfg(all(feature = "signal_hook", unix))]
+        if let Some(ref sighup_listener) = self.o_sighup_listener {
+            sighup_listener.stop();
+        }
+        if let Some(ref flusher) = self.o_flusher {
+            flusher.stop();
+        }
+        self.primary_writer.shutdown();
+        for writer in self.other_writers.values() {
+            writer.shutdown();
+        }
+    }
+
+    fn set_new_spec(&self, new_spec: LogSpecification) {
         let max_level = new_spec.max_level();
-        self.spec
-            .write()
-            .map_err(|_| FlexiLoggerError::Poison)?
-            .update_from(new_spec);
+        self.spec.update(|spec| spec.update_from(new_spec));
+        self.reconfigure(max_level);
+    }
+
+    fn update_spec_with<F>(&self, f: F)
+    where
+        F: FnOnce(&mut LogSpecBuilder),
+    {
+        let max_level = self.spec.update(|spec| {
+            let new_spec = spec.modified_with(f);
+            let max_level = new_spec.max_level();
+            spec.update_from(new_spec);
+            max_level
+        });
         self.reconfigure(max_level);
-        Ok(())
     }
 
-    pub(crate) fn reconfigure(&self, mut max_level: log::LevelFilter) {
-        for w in self.other_writers.as_ref().values() {
-            max_level = std::cmp::max(max_level, w.max_log_level());
+    pub(crate) fn reconfigure(&self, max_level: log::LevelFilter) {
+        apply_max_level(
+            self.effective_max_level(max_level),
+            &self.a_max_level,
+            self.set_global_max_level,
+        );
+    }
+
+    fn effective_max_level(&self, max_level: log::LevelFilter) -> log::LevelFilter {
+        effective_max_level(&self.other_writers, max_level)
+    }
+}
+
+// Re-opens the outputs; only the first error is reported.
+fn reopen_output(
+    primary_writer: &PrimaryWriter,
+    other_writers: &HashMap<String, Box<dyn LogWriter>>,
+) -> Result<(), FlexiLoggerError> {
+    let mut result = if let PrimaryWriter::Multi(ref mw) = primary_writer {
+        mw.reopen_output()
+    } else {
+        Ok(())
+    };
+
+    for blw in other_writers.values() {
+        let result2 = blw.reopen_output();
+        if result.is_ok() && result2.is_err() {
+            result = result2;
         }
+    }
+
+    result
+}
+
+// Publishes the max level to the FlexiLogger and, if configured, to the log crate.
+fn apply_max_level(
+    max_level: log::LevelFilter,
+    a_max_level: &AtomicUsize,
+    set_global_max_level: bool,
+) {
+    a_max_level.store(max_level as usize, Ordering::Relaxed);
+    if set_global_max_level {
         log::set_max_level(max_level);
     }
 }
+
+fn effective_max_level(
+    other_writers: &HashMap<String, Box<dyn LogWriter>>,
+    mut max_level: log::LevelFilter,
+) -> log::LevelFilter {
+    for w in other_writers.values() {
+        max_level = std::cmp::max(max_level, w.max_log_level());
+    }
+    max_level
+}
 impl Drop for WritersHandle {
     fn drop(&mut self) {
-        self.primary_writer.shutdown();
-        for writer in self.other_writers.values() {
-            writer.shutdown();
-        }
+        self.shutdown();
     }
 }
 
@@ -506,14 +1227,65 @@ pub trait LogSpecSubscriber: 'static + Send {
     ///
     /// # Errors
     fn initial_spec(&self) -> Result<LogSpecification, FlexiLoggerError>;
+
+    /// Apply the duplication levels that are given in the specfile.
+    ///
+    /// The default implementation ignores them.
+    ///
+    /// # Errors
+    fn set_new_duplication(
+        &mut self,
+        _o_dup_err: Option<Duplicate>,
+        _o_dup_out: Option<Duplicate>,
+    ) -> Result<(), FlexiLoggerError> {
+        Ok(())
+    }
+
+    /// Provide the current duplication levels for stderr and stdout,
+    /// if duplication is supported.
+    ///
+    /// The default implementation returns `None`.
+    fn initial_duplication(&self) -> Option<(Duplicate, Duplicate)> {
+        None
+    }
 }
 #[cfg(feature = "specfile_without_notification")]
 impl LogSpecSubscriber for WritersHandle {
     fn set_new_spec(&mut self, new_spec: LogSpecification) -> Result<(), FlexiLoggerError> {
-        WritersHandle::set_new_spec(self, new_spec)
+        WritersHandle::set_new_spec(self, new_spec);
+        Ok(())
     }
 
     fn initial_spec(&self) -> Result<LogSpecification, FlexiLoggerError> {
-        Ok((*self.spec.read().map_err(|_e| FlexiLoggerError::Poison)?).clone())
+        Ok(LogSpecification::clone(&self.spec.read()))
+    }
+
+    fn set_new_duplication(
+        &mut self,
+        o_dup_err: Option<Duplicate>,
+        o_dup_out: Option<Duplicate>,
+    ) -> Result<(), FlexiLoggerError> {
+        if o_dup_err.is_none() && o_dup_out.is_none() {
+            return Ok(());
+        }
+        if let PrimaryWriter::Multi(ref mw) = &*self.primary_writer {
+            if let Some(dup) = o_dup_err {
+                mw.adapt_duplication_to_stderr(dup);
+            }
+            if let Some(dup) = o_dup_out {
+                mw.adapt_duplication_to_stdout(dup);
+            }
+            Ok(())
+        } else {
+            Err(FlexiLoggerError::NoDuplication)
+        }
+    }
+
+    fn initial_duplication(&self) -> Option<(Duplicate, Duplicate)> {
+        if let PrimaryWriter::Multi(ref mw) = &*self.primary_writer {
+            Some((mw.duplication_to_stderr(), mw.duplication_to_stdout()))
+        } else {
+            None
+        }
     }
 }
@@ -1,11 +1,25 @@
 mod age;
+mod circuit_breaker;
 mod cleanup;
+mod compressor;
 mod criterion;
+mod fallback;
 mod file_spec;
 mod naming;
+mod sync_policy;
+mod write_failure_policy;
 
 pub use age::Age;
-pub use cleanup::Cleanup;
+pub use circuit_breaker::CircuitBreaker;
+pub(crate) use cleanup::{Archiver, CleanupMonitor, CleanupObserver};
+pub use cleanup::{Cleanup, CleanupStatus};
+pub use compressor::Compressor;
+#[cfg(feature = "compress")]
+pub use compressor::GzCompressor;
+pub(crate) use compressor::{Compression, SharedCompressor};
 pub use criterion::Criterion;
+pub use fallback::Fallback;
 pub use file_spec::FileSpec;
 pub use naming::Naming;
+pub use sync_policy::SyncPolicy;
+pub use write_failure_policy::WriteFailurePolicy;
@@ -2,6 +2,14 @@
 /// when [`Criterion::Age`](crate::Criterion::Age) is chosen.
 #[derive(Copy, Clone, Debug)]
 pub enum Age {
+    /// Rotate the log file when the local clock has started a new month since the
+    /// current file had been created.
+    Month,
+    /// Rotate the log file when the local clock has started a new week since the
+    /// current file had been created; weeks start with the given weekday.
+    ///
+    /// Example: `Age::Week(chrono::Weekday::Mon)`.
+    Week(chrono::Weekday),
     /// Rotate the log file when the local clock has started a new day since the
     /// current file had been created.
     Day,
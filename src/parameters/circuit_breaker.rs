@@ -0,0 +1,21 @@
+use std::time::Duration;
+
+/// Defines when an additional writer that fails repeatedly is temporarily skipped.
+///
+/// If the writer fails `max_failures` times within the duration `within`,
+/// the circuit breaker opens: this is reported once to the [error channel](crate::ErrorChannel),
+/// and the writer is skipped for the duration `retry_after`, so that its failures neither slow
+/// down the log calls nor flood the error channel.
+/// Then the writer is tried again; if writing succeeds, this is reported once more,
+/// if it fails, the writer is skipped again.
+///
+/// Used in [`Logger::circuit_breaker`](crate::Logger::circuit_breaker).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CircuitBreaker {
+    /// Number of failures after which the writer is skipped.
+    pub max_failures: u32,
+    /// Duration in which the failures must occur.
+    pub within: Duration,
+    /// Duration for which the writer is skipped before it is tried again.
+    pub retry_after: Duration,
+}
@@ -1,3 +1,9 @@
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
 /// Defines the strategy for handling older log files.
 ///
 /// Is used in [`Logger::rotate`](crate::Logger::rotate).
@@ -20,6 +26,19 @@ pub enum Cleanup {
     /// Older files are deleted, if necessary.
     KeepLogFiles(usize),
 
+    /// The specified number of rotated log files are kept, and additionally all rotated
+    /// log files that were modified within the specified duration.
+    ///
+    /// So a file is only deleted if it is not among the youngest `n` files and if it is older
+    /// than the duration. With frequent rotations, e.g. during a burst of log output,
+    /// more than `n` files can thus exist for a while.
+    ///
+    /// ## Example
+    ///
+    /// `KeepLogFilesAndDuration(10, Duration::from_secs(24 * 3600))` keeps at least
+    /// the youngest 10 log files, and all log files of the last day.
+    KeepLogFilesAndDuration(usize, Duration),
+
     /// The specified number of rotated log files are compressed and kept.
     /// Older files are deleted, if necessary.
     #[cfg_attr(docsrs, doc(cfg(feature = "compress")))]
@@ -33,6 +52,8 @@ pub enum Cleanup {
     /// `KeepLogAndCompressedFiles(5,30)` ensures that the youngest five log files are
     /// kept as text files, the next 30 are kept as compressed files with additional suffix `.gz`,
     /// and older files are removed.
+    ///
+    /// See [`Compressor`](crate::Compressor) for using another compression format.
     #[cfg_attr(docsrs, doc(cfg(feature = "compress")))]
     #[cfg(feature = "compress")]
     KeepLogAndCompressedFiles(usize, usize),
@@ -45,3 +66,110 @@ impl Cleanup {
         !matches!(self, Self::Never)
     }
 }
+
+// Callback that is called with each log file that the cleanup is going to delete;
+// the file is only deleted if the callback succeeds.
+#[derive(Clone)]
+pub(crate) struct Archiver(Arc<ArchiveFn>);
+type ArchiveFn = dyn Fn(&Path) -> std::io::Result<()> + Send + Sync;
+impl Archiver {
+    pub(crate) fn new<F>(archiver: F) -> Self
+    where
+        F: Fn(&Path) -> std::io::Result<()> + Send + Sync + 'static,
+    {
+        Self(Arc::new(archiver))
+    }
+
+    pub(crate) fn archive(&self, path: &Path) -> std::io::Result<()> {
+        (self.0)(path)
+    }
+}
+impl std::fmt::Debug for Archiver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("Archiver")
+    }
+}
+
+/// Information about failures of the cleanup of log files.
+///
+/// See [`LoggerHandle::cleanup_status`](crate::LoggerHandle::cleanup_status)
+/// and [`FileLogWriterBuilder::on_cleanup_failure`](crate::writers::FileLogWriterBuilder::on_cleanup_failure).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct CleanupStatus {
+    /// Number of failed cleanup runs.
+    pub failures: u64,
+    /// Description of the latest failure.
+    pub last_failure: Option<String>,
+    /// Number of times the cleanup thread was found dead and was restarted.
+    pub thread_restarts: u64,
+}
+
+// Callback that is called with the updated status after each cleanup failure.
+#[derive(Clone)]
+pub(crate) struct CleanupObserver(Arc<ObserverFn>);
+type ObserverFn = dyn Fn(&CleanupStatus) + Send + Sync;
+impl CleanupObserver {
+    pub(crate) fn new<F>(observer: F) -> Self
+    where
+        F: Fn(&CleanupStatus) + Send + Sync + 'static,
+    {
+        Self(Arc::new(observer))
+    }
+}
+impl std::fmt::Debug for CleanupObserver {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CleanupObserver")
+    }
+}
+
+// Collects the cleanup failures and informs the observer, if any.
+#[derive(Debug, Default)]
+pub(crate) struct CleanupMonitor {
+    status: Mutex<CleanupStatus>,
+    o_observer: Option<CleanupObserver>,
+}
+impl CleanupMonitor {
+    pub(crate) fn new(o_observer: Option<CleanupObserver>) -> Self {
+        Self {
+            status: Mutex::new(CleanupStatus::default()),
+            o_observer,
+        }
+    }
+
+    pub(crate) fn status(&self) -> CleanupStatus {
+        self.status
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone()
+    }
+
+    pub(crate) fn failure(&self, description: String) {
+        self.update(|status| {
+            status.failures += 1;
+            status.last_failure = Some(description);
+        });
+    }
+
+    pub(crate) fn thread_restart(&self, description: String) {
+        self.update(|status| {
+            status.failures += 1;
+            status.thread_restarts += 1;
+            status.last_failure = Some(description);
+        });
+    }
+
+    fn update(&self, f: impl FnOnce(&mut CleanupStatus)) {
+        let status = {
+            let mut status = self
+                .status
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            f(&mut status);
+            status.clone()
+        };
+        if let Some(ref observer) = self.o_observer {
+            (observer.0)(&status);
+        }
+    }
+}
@@ -0,0 +1,126 @@
+use std::{fs::File, sync::Arc};
+
+/// Compresses rotated log files.
+///
+/// The cleanup uses the compressor for the files that it is to keep as compressed files,
+/// see [`Cleanup`](crate::Cleanup). By default, [`GzCompressor`] is used.
+///
+/// Implement this trait to use another compression format, e.g. zstd or xz,
+/// or an external command, and register your implementation with
+/// [`FileLogWriterBuilder::compressor`](crate::writers::FileLogWriterBuilder::compressor)
+/// or [`Logger::compressor`](crate::Logger::compressor).
+///
+/// ## Example
+///
+/// ```rust
+/// use flexi_logger::Compressor;
+/// use std::{fs::File, process::Command};
+///
+/// struct Xz;
+/// impl Compressor for Xz {
+///     fn suffix(&self) -> &str {
+///         "xz"
+///     }
+///     fn compress(&self, source: &mut File, target: &mut File) -> std::io::Result<()> {
+///         let status = Command::new("xz")
+///             .arg("--stdout")
+///             .stdin(source.try_clone()?)
+///             .stdout(target.try_clone()?)
+///             .status()?;
+///         if status.success() {
+///             Ok(())
+///         } else {
+///             Err(std::io::Error::new(
+///                 std::io::ErrorKind::Other,
+///                 format!("xz failed with {status}"),
+///             ))
+///         }
+///     }
+/// }
+/// ```
+pub trait Compressor: Send + Sync {
+    /// The suffix that is appended to the name of a compressed file, without the dot,
+    /// e.g. `"gz"`.
+    ///
+    /// The suffix is also used to recognize the compressed files, e.g. for
+    /// [`LogfileSelector::with_compressed_files`](crate::LogfileSelector::with_compressed_files).
+    fn suffix(&self) -> &str;
+
+    /// Writes the compressed content of `source` to `target`.
+    ///
+    /// `target` is a freshly created file.
+    /// If the method succeeds, `source` is deleted by the cleanup;
+    /// if it fails, the error is reported, `target` is deleted, and `source` is kept.
+    ///
+    /// # Errors
+    ///
+    /// `std::io::Error` if reading, compressing or writing fails.
+    fn compress(&self, source: &mut File, target: &mut File) -> std::io::Result<()>;
+}
+
+/// The default [`Compressor`], which produces gzip files with suffix `gz`.
+#[cfg_attr(docsrs, doc(cfg(feature = "compress")))]
+#[cfg(feature = "compress")]
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GzCompressor;
+#[cfg(feature = "compress")]
+impl Compressor for GzCompressor {
+    fn suffix(&self) -> &'static str {
+        "gz"
+    }
+
+    fn compress(&self, source: &mut File, target: &mut File) -> std::io::Result<()> {
+        let mut gz_encoder = flate2::write::GzEncoder::new(target, flate2::Compression::fast());
+        std::io::copy(source, &mut gz_encoder)?;
+        gz_encoder.finish()?;
+        Ok(())
+    }
+}
+
+// How the cleanup compresses files.
+#[derive(Clone, Debug)]
+pub(crate) struct Compression {
+    // is used instead of gzip
+    pub(crate) o_compressor: Option<SharedCompressor>,
+    // the maximal number of files that are compressed in parallel
+    pub(crate) workers: usize,
+}
+impl Default for Compression {
+    fn default() -> Self {
+        Self {
+            o_compressor: None,
+            workers: 1,
+        }
+    }
+}
+impl Compression {
+    // the suffix of the compressed files, without the dot
+    pub(crate) fn suffix(&self) -> &str {
+        match self.o_compressor {
+            Some(ref compressor) => compressor.0.suffix(),
+            None => "gz",
+        }
+    }
+
+    #[cfg(feature = "compress")]
+    pub(crate) fn compress(&self, source: &mut File, target: &mut File) -> std::io::Result<()> {
+        match self.o_compressor {
+            Some(ref compressor) => compressor.0.compress(source, target),
+            None => GzCompressor.compress(source, target),
+        }
+    }
+}
+
+// The compressor that the cleanup uses instead of the default.
+#[derive(Clone)]
+pub(crate) struct SharedCompressor(Arc<dyn Compressor>);
+impl SharedCompressor {
+    pub(crate) fn new<C: Compressor + 'static>(compressor: C) -> Self {
+        Self(Arc::new(compressor))
+    }
+}
+impl std::fmt::Debug for SharedCompressor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("Compressor({})", self.0.suffix()))
+    }
+}
@@ -0,0 +1,20 @@
+use std::path::PathBuf;
+
+/// An alternative output for the logs, if the log file can't be written where it is configured,
+/// e.g. because the directory is on a read-only file system or lacks permissions.
+///
+/// The fallbacks are used when the directory can't be created at startup, or when opening the
+/// log file fails, also after a rotation.
+/// Switching to a fallback is reported once to the [error channel](crate::ErrorChannel).
+///
+/// Used in [`Logger::fallback`](crate::Logger::fallback) and
+/// [`FileLogWriterBuilder::fallback`](crate::writers::FileLogWriterBuilder::fallback).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Fallback {
+    /// The log files are written to the given directory, with the configured file names.
+    ///
+    /// Example: `Fallback::Directory(std::env::temp_dir())`.
+    Directory(PathBuf),
+    /// The logs are written to stderr; rotation is then switched off.
+    Stderr,
+}
@@ -1,3 +1,4 @@
+use crate::flexi_error::io_err_with_context;
 use crate::writers::file_log_writer::InfixFilter;
 use crate::{DeferredNow, FlexiLoggerError};
 use std::{
@@ -51,11 +52,16 @@ pub struct FileSpec {
     timestamp_cfg: TimestampCfg,
     o_suffix: Option<String>,
     pub(crate) use_utc: bool,
+    sanitize: bool,
+    // the suffix of compressed log files, without the dot;
+    // is taken from the compression settings when the FileLogWriter is built
+    pub(crate) compressed_suffix: String,
 }
 impl Default for FileSpec {
     /// Describes a file in the current folder,
     /// using, as its filestem, the program name followed by the current timestamp,
     /// and the suffix ".log".
+    #[allow(unused_attributes)]
     #[must_use]
     fn default() -> Self {
         FileSpec {
@@ -65,6 +71,8 @@ impl Default for FileSpec {
             timestamp_cfg: TimestampCfg::Default,
             o_suffix: Some(String::from("log")),
             use_utc: false,
+            sanitize: false,
+            compressed_suffix: String::from("gz"),
         }
     }
 }
@@ -78,6 +86,9 @@ impl FileSpec {
     /// If it exists, it must be a file, not a folder.
     /// If necessary, parent folders will be created.
     ///
+    /// A relative path is resolved against the current working directory, so that
+    /// also a bare filename like `"test.log"` can be used.
+    ///
     /// ```rust
     /// # use flexi_logger::FileSpec;
     /// assert_eq!(
@@ -91,25 +102,60 @@ impl FileSpec {
     /// ```
     /// # Errors
     ///
-    /// [`FlexiLoggerError::OutputBadFile`] if the given path exists and is a folder.
-    ///
-    /// # Panics
+    /// [`FlexiLoggerError::OutputBadFile`] if the given path exists and is a folder,
+    /// or if it has no filename.
     ///
-    /// Panics if the basename of the given path has no filename
+    /// [`FlexiLoggerError::OutputIo`] if the current working directory cannot be determined.
     pub fn try_from<P: Into<PathBuf>>(p: P) -> Result<Self, FlexiLoggerError> {
         let p: PathBuf = p.into();
+        let current_dir = std::env::current_dir().map_err(|e| {
+            io_err_with_context(
+                e,
+                p.display().to_string(),
+                "FileSpec::try_from",
+                "Specify an absolute path, or use FileSpec::try_from_with_base.",
+            )
+        })?;
+        Self::try_from_with_base(current_dir, p)
+    }
+
+    /// Like [`FileSpec::try_from`], but a relative path is resolved against the given directory.
+    ///
+    /// ```rust
+    /// # use flexi_logger::FileSpec;
+    /// assert_eq!(
+    ///     FileSpec::try_from_with_base("/var/log", "myapp/foo.log").unwrap(),
+    ///     FileSpec::try_from("/var/log/myapp/foo.log").unwrap()
+    /// );
+    /// ```
+    /// # Errors
+    ///
+    /// [`FlexiLoggerError::OutputBadFile`] if the resulting path exists and is a folder,
+    /// or if it has no filename.
+    pub fn try_from_with_base<D: Into<PathBuf>, P: Into<PathBuf>>(
+        dir: D,
+        p: P,
+    ) -> Result<Self, FlexiLoggerError> {
+        let mut p: PathBuf = p.into();
+        if p.is_relative() {
+            p = dir.into().join(p);
+        }
         if p.is_dir() {
-            Err(FlexiLoggerError::OutputBadFile)
-        } else {
-            Ok(FileSpec {
-                directory: p.parent().unwrap(/*cannot fail*/).to_path_buf(),
-                basename: p.file_stem().unwrap(/*ok*/).to_string_lossy().to_string(),
-                o_discriminant: None,
-                o_suffix: p.extension().map(|s| s.to_string_lossy().to_string()),
-                timestamp_cfg: TimestampCfg::No,
-                use_utc: false,
-            })
+            return Err(FlexiLoggerError::OutputBadFile);
         }
+        let (Some(directory), Some(basename)) = (p.parent(), p.file_stem()) else {
+            return Err(FlexiLoggerError::OutputBadFile);
+        };
+        Ok(FileSpec {
+            directory: directory.to_path_buf(),
+            basename: basename.to_string_lossy().to_string(),
+            o_discriminant: None,
+            o_suffix: p.extension().map(|s| s.to_string_lossy().to_string()),
+            timestamp_cfg: TimestampCfg::No,
+            use_utc: false,
+            sanitize: false,
+            compressed_suffix: String::from("gz"),
+        })
     }
 
     /// Makes the logger not include a basename into the names of the log files
@@ -121,7 +167,11 @@ impl FileSpec {
     }
 
     /// The specified String is used as the basename of the log file name,
-    /// instead of the program name. Using a file separator within the argument is discouraged.
+    /// instead of the program name.
+    ///
+    /// Path separators, control characters, and names that are reserved on Windows
+    /// (like `CON` or `LPT1`) are rejected when the file log writer is built,
+    /// unless [`FileSpec::sanitize`] is used.
     #[must_use]
     pub fn basename<S: Into<String>>(mut self, basename: S) -> Self {
         self.basename = basename.into();
@@ -168,6 +218,26 @@ impl FileSpec {
         self.o_discriminant = o_discriminant.map(Into::into);
         self
     }
+
+    /// The specified template is resolved and the result is used as discriminant.
+    ///
+    /// The following placeholders are replaced:
+    ///
+    /// - `{pid}`: the id of the current process
+    /// - `{hostname}`: the name of the host, as reported by the operating system
+    ///
+    /// Other text is used unchanged.
+    /// This allows several processes or hosts to write into the same directory
+    /// without colliding file names.
+    ///
+    /// ```rust
+    /// # use flexi_logger::FileSpec;
+    /// let file_spec = FileSpec::default().discriminant_from_template("{hostname}-{pid}");
+    /// ```
+    #[must_use]
+    pub fn discriminant_from_template<S: AsRef<str>>(self, template: S) -> Self {
+        self.discriminant(resolve_template(template.as_ref()))
+    }
     /// Specifies a suffix for the log files.
     ///
     /// Equivalent to `o_suffix(Some(suffix))`.
@@ -185,6 +255,21 @@ impl FileSpec {
         self
     }
 
+    /// Makes the file log writer replace unusable characters in the basename, the discriminant,
+    /// and the suffix with `_`, and append `_` to names that are reserved on Windows,
+    /// rather than failing with [`FlexiLoggerError::BadFileSpec`].
+    ///
+    /// ```rust
+    /// # use flexi_logger::FileSpec;
+    /// // logs are written to files like "myprog_eu_west_1_2024-06-09_13-24-35.log"
+    /// let file_spec = FileSpec::default().discriminant("eu/west/1").sanitize();
+    /// ```
+    #[must_use]
+    pub fn sanitize(mut self) -> Self {
+        self.sanitize = true;
+        self
+    }
+
     /// Makes the logger not include the start time into the names of the log files
     ///
     /// Equivalent to `use_timestamp(false)`.
@@ -213,14 +298,10 @@ impl FileSpec {
     pub fn used_directory(&self) -> PathBuf {
         self.directory.clone()
     }
-    pub(crate) fn has_basename(&self) -> bool {
-        !self.basename.is_empty()
-    }
-    pub(crate) fn has_discriminant(&self) -> bool {
-        self.o_discriminant.is_some()
-    }
-    pub(crate) fn uses_timestamp(&self) -> bool {
-        matches!(self.timestamp_cfg, TimestampCfg::Yes)
+
+    // Without a decision, the timestamp is included.
+    pub(crate) fn includes_timestamp(&self) -> bool {
+        !matches!(self.timestamp_cfg, TimestampCfg::No)
     }
 
     // If no decision was done yet, decide now whether to include a timestamp
@@ -235,6 +316,24 @@ impl FileSpec {
         }
     }
 
+    // Checks that the basename, the discriminant, and the suffix are usable in file names,
+    // or, with `sanitize`, makes them usable.
+    pub(crate) fn validated(mut self) -> Result<Self, FlexiLoggerError> {
+        let mut problems = Vec::<String>::new();
+        validate_name_part("basename", &mut self.basename, self.sanitize, &mut problems);
+        if let Some(ref mut discriminant) = self.o_discriminant {
+            validate_name_part("discriminant", discriminant, self.sanitize, &mut problems);
+        }
+        if let Some(ref mut suffix) = self.o_suffix {
+            validate_name_part("suffix", suffix, self.sanitize, &mut problems);
+        }
+        if problems.is_empty() {
+            Ok(self)
+        } else {
+            Err(FlexiLoggerError::BadFileSpec(problems.join("; ")))
+        }
+    }
+
     pub(crate) fn get_directory(&self) -> PathBuf {
         self.directory.clone()
     }
@@ -269,7 +368,7 @@ impl FileSpec {
                 append_underscore_if_not_empty(&mut filename);
                 filename.push_str(infix);
             }
-        };
+        }
         if let Some(suffix) = &self.o_suffix {
             filename.push('.');
             filename.push_str(suffix);
@@ -286,18 +385,20 @@ impl FileSpec {
             &InfixFilter::Equls(infix.to_string()),
             self.o_suffix.as_deref(),
         );
-        let compressed_files =
-            self.list_of_files(&InfixFilter::Equls(infix.to_string()), Some("gz"));
+        let compressed_files = self.list_of_files(
+            &InfixFilter::Equls(infix.to_string()),
+            Some(&self.compressed_suffix),
+        );
 
         let mut restart_siblings = uncompressed_files
             .into_iter()
             .chain(compressed_files)
             .filter(|pb| {
-                // ignore .gz suffix
+                // ignore the suffix of compressed files
                 let mut pb2 = PathBuf::from(pb);
-                if pb2.extension() == Some(OsString::from("gz").as_ref()) {
+                if pb2.extension() == Some(OsString::from(&self.compressed_suffix).as_ref()) {
                     pb2.set_extension("");
-                };
+                }
                 // suffix must match the given suffix, if one is given
                 match self.o_suffix {
                     Some(ref sfx) => pb2.extension() == Some(OsString::from(sfx).as_ref()),
@@ -315,8 +416,14 @@ impl FileSpec {
         let new_path = self.as_pathbuf(Some(infix));
         let new_path_with_gz = {
             let mut new_path_with_gz = new_path.clone();
-            new_path_with_gz
-                .set_extension([self.o_suffix.as_deref().unwrap_or(""), ".gz"].concat());
+            new_path_with_gz.set_extension(
+                [
+                    self.o_suffix.as_deref().unwrap_or(""),
+                    ".",
+                    &self.compressed_suffix,
+                ]
+                .concat(),
+            );
             new_path_with_gz
         };
 
@@ -375,6 +482,39 @@ impl FileSpec {
         log_files
     }
 
+    // Extracts the infix from the name of a file that belongs to this spec, i.e.,
+    // the part between the fixed name part and the suffix, without the compression suffix
+    // and without a collision extension like ".restart-0001".
+    // The infix itself may contain dots, e.g. with a custom timestamp format.
+    pub(crate) fn infix_of<'a>(
+        &self,
+        fixed_name_part: &str,
+        file_name: &'a str,
+    ) -> Option<&'a str> {
+        let mut rest = file_name.strip_prefix(fixed_name_part)?;
+        if !fixed_name_part.is_empty() {
+            rest = rest.strip_prefix('_')?;
+        }
+        rest = rest
+            .strip_suffix(self.compressed_suffix.as_str())
+            .and_then(|r| r.strip_suffix('.'))
+            .unwrap_or(rest);
+        if let Some(ref suffix) = self.o_suffix {
+            rest = rest
+                .strip_suffix(suffix.as_str())
+                .and_then(|r| r.strip_suffix('.'))
+                .unwrap_or(rest);
+        }
+        if let Some(idx) = rest.rfind(".restart-") {
+            rest = &rest[..idx];
+        }
+        if rest.is_empty() {
+            None
+        } else {
+            Some(rest)
+        }
+    }
+
     pub(crate) fn filter_files(
         &self,
         files: &[PathBuf],
@@ -397,18 +537,9 @@ impl FileSpec {
             })
             .filter(|path| {
                 // infix filter must pass
-                let stem = path.file_stem().unwrap(/* CANNOT FAIL*/).to_string_lossy();
-                let infix_start = if fixed_name_part.is_empty() {
-                    0
-                } else {
-                    fixed_name_part.len() + 1 // underscore at the end
-                };
-                if stem.len() <= infix_start {
-                    return false;
-                }
-                let maybe_infix = &stem[infix_start..];
-                let end = maybe_infix.find('.').unwrap_or(maybe_infix.len());
-                infix_filter.filter_infix(&maybe_infix[..end])
+                let file_name = path.file_name().unwrap(/* CANNOT FAIL*/).to_string_lossy();
+                self.infix_of(&fixed_name_part, &file_name)
+                    .is_some_and(|infix| infix_filter.filter_infix(infix))
             })
             .map(PathBuf::clone)
             .collect::<Vec<PathBuf>>()
@@ -420,6 +551,45 @@ impl FileSpec {
     }
 }
 
+const WINDOWS_RESERVED_NAMES: [&str; 22] = [
+    "CON", "PRN", "AUX", "NUL", "COM1", "COM2", "COM3", "COM4", "COM5", "COM6", "COM7", "COM8",
+    "COM9", "LPT1", "LPT2", "LPT3", "LPT4", "LPT5", "LPT6", "LPT7", "LPT8", "LPT9",
+];
+
+fn is_unusable_char(c: char) -> bool {
+    c == '/' || c == '\\' || c.is_control()
+}
+
+fn validate_name_part(kind: &str, value: &mut String, sanitize: bool, problems: &mut Vec<String>) {
+    let is_reserved = WINDOWS_RESERVED_NAMES.iter().any(|name| {
+        value
+            .split('.')
+            .next()
+            .unwrap_or_default()
+            .eq_ignore_ascii_case(name)
+    });
+    if sanitize {
+        *value = value
+            .chars()
+            .map(|c| if is_unusable_char(c) { '_' } else { c })
+            .collect();
+        if is_reserved {
+            value.push('_');
+        }
+    } else {
+        if value.contains(is_unusable_char) {
+            problems.push(format!(
+                "the {kind} {value:?} contains path separators or control characters"
+            ));
+        }
+        if is_reserved {
+            problems.push(format!(
+                "the {kind} {value:?} is a reserved name on Windows"
+            ));
+        }
+    }
+}
+
 fn append_underscore_if_not_empty(filename: &mut String) {
     if !filename.is_empty() {
         filename.push('_');
@@ -447,10 +617,22 @@ impl TimestampCfg {
     }
 }
 
+const PID_PLACEHOLDER: &str = "{pid}";
+const HOSTNAME_PLACEHOLDER: &str = "{hostname}";
+
+fn resolve_template(template: &str) -> String {
+    let mut resolved = template.replace(PID_PLACEHOLDER, &std::process::id().to_string());
+    if resolved.contains(HOSTNAME_PLACEHOLDER) {
+        resolved = resolved.replace(HOSTNAME_PLACEHOLDER, &crate::util::hostname());
+    }
+    resolved
+}
+
 #[cfg(test)]
 mod test {
     use super::{FileSpec, TimestampCfg};
     use crate::writers::file_log_writer::InfixFilter;
+    use crate::FlexiLoggerError;
     use std::{
         fs::File,
         path::{Path, PathBuf},
@@ -468,6 +650,22 @@ mod test {
         assert_eq!(bytes[16], b'-');
     }
 
+    #[test]
+    fn test_discriminant_from_template() {
+        let pid = std::process::id().to_string();
+        let hostname = hostname::get().unwrap().to_string_lossy().to_string();
+
+        let file_spec = FileSpec::default()
+            .directory("logs")
+            .basename("myprog")
+            .suppress_timestamp()
+            .discriminant_from_template("{hostname}-{pid}-{unknown}");
+        assert_eq!(
+            file_spec.as_pathbuf(None),
+            PathBuf::from(format!("logs/myprog_{hostname}-{pid}-{{unknown}}.log"))
+        );
+    }
+
     #[test]
     fn test_default() {
         let path = FileSpec::default().as_pathbuf(None);
@@ -572,6 +770,63 @@ mod test {
         assert_eq!(path.extension().unwrap(), "trc");
     }
 
+    #[test]
+    fn test_from_relative_path() {
+        let current_dir = std::env::current_dir().unwrap();
+
+        let file_spec = FileSpec::try_from("test.log").unwrap();
+        assert_eq!(file_spec.directory, current_dir);
+        assert_eq!(file_spec.as_pathbuf(None), current_dir.join("test.log"));
+
+        let file_spec = FileSpec::try_from("a/b/test").unwrap();
+        assert_eq!(file_spec.as_pathbuf(None), current_dir.join("a/b/test"));
+
+        let file_spec = FileSpec::try_from_with_base("/x/y", "a/test.trc").unwrap();
+        assert_eq!(file_spec.directory, PathBuf::from("/x/y/a"));
+        assert_eq!(file_spec.as_pathbuf(None), PathBuf::from("/x/y/a/test.trc"));
+
+        let file_spec = FileSpec::try_from_with_base("/x/y", "/a/test.trc").unwrap();
+        assert_eq!(file_spec.as_pathbuf(None), PathBuf::from("/a/test.trc"));
+
+        assert!(FileSpec::try_from_with_base("/x/y", "..").is_err());
+        assert!(FileSpec::try_from(".").is_err());
+    }
+
+    #[test]
+    fn test_validated() {
+        let file_spec = FileSpec::default()
+            .basename("my/prog")
+            .discriminant("a\0b")
+            .suffix("Con");
+        match file_spec.clone().validated() {
+            Err(FlexiLoggerError::BadFileSpec(problems)) => {
+                assert!(problems.contains("basename \"my/prog\""), "{problems}");
+                assert!(problems.contains("discriminant \"a\\0b\""), "{problems}");
+                assert!(
+                    problems.contains("suffix \"Con\" is a reserved name"),
+                    "{problems}"
+                );
+            }
+            other => panic!("unexpected result: {other:?}"),
+        }
+
+        let file_spec = file_spec.sanitize().validated().unwrap();
+        assert_eq!(file_spec.basename, "my_prog");
+        assert_eq!(file_spec.o_discriminant.as_deref(), Some("a_b"));
+        assert_eq!(file_spec.o_suffix.as_deref(), Some("Con_"));
+
+        assert!(FileSpec::default()
+            .basename("lpt1.x")
+            .discriminant("eu\\west")
+            .validated()
+            .is_err());
+        assert!(FileSpec::default()
+            .basename("console")
+            .discriminant("eu-west.1")
+            .validated()
+            .is_ok());
+    }
+
     #[test]
     fn test_basename() {
         {
@@ -732,6 +987,27 @@ mod test {
         fn1
     }
 
+    #[test]
+    fn test_infix_of() {
+        let file_spec = FileSpec::default().basename("app").suppress_timestamp();
+        let fixed = file_spec.fixed_name_part();
+        for (file_name, expected) in [
+            ("app_r00003.log", Some("r00003")),
+            ("app_r2024.03.07.log", Some("r2024.03.07")),
+            ("app_r2024.03.07.log.gz", Some("r2024.03.07")),
+            ("app_2024-03-07.restart-0001.log", Some("2024-03-07")),
+            ("app_rCURRENT.log", Some("rCURRENT")),
+            ("app.log", None),
+            ("other_r00003.log", None),
+        ] {
+            assert_eq!(
+                file_spec.infix_of(&fixed, file_name),
+                expected,
+                "{file_name}"
+            );
+        }
+    }
+
     fn create_file(dir: &Path, filename: &str) {
         File::create(dir.join(filename)).unwrap();
     }
@@ -1,7 +1,7 @@
 /// The naming convention for rotated log files.
 ///
-/// Common rule for all variants is that the names of the current output file
-/// and the rotated log files only differ in the infix.
+/// Common rule for all variants, except [`Naming::NumbersShifted`], is that the names
+/// of the current output file and the rotated log files only differ in the infix.
 ///
 /// See [`Logger::log_to_file`](crate::Logger::log_to_file)
 /// for a description of how the filename is built, including the infix.
@@ -88,6 +88,14 @@ pub enum Naming {
         /// `"%Y-%m-%d"` produces timestamp infixes like `"2024-06-09"`.
         ///
         /// `"%Y-%m-%d_%H-%M-%S"` produces timestamp infixes like `"2024-06-09_13-24-35"`.
+        ///
+        /// `"%Y-%m-%d_%H-%M-%S.%3f%z"` produces timestamp infixes like
+        /// `"2024-06-09_13-24-35.123+0200"`; with
+        /// [`Logger::use_utc_for_file_names`](crate::Logger::use_utc_for_file_names)
+        /// the infixes are rendered in UTC, like `"2024-06-09_11-24-35.123+0000"`.
+        ///
+        /// The format is checked when the file log writer is built; an unusable format
+        /// is reported with [`FlexiLoggerError::BadNamingFormat`](crate::FlexiLoggerError::BadNamingFormat).
         format: &'static str,
     },
 
@@ -103,12 +111,45 @@ pub enum Naming {
     ///
     /// File rotation switches over to the next file.
     NumbersDirect,
+
+    /// Like [`Naming::Numbers`] or [`Naming::NumbersDirect`], with a configurable width
+    /// and start index of the number infix.
+    ///
+    /// Example: `Naming::NumbersWith { direct: false, width: 3, start: 1 }` produces
+    /// rotated files with infixes `"r001"`, `"r002"`, etc.
+    ///
+    /// Existing files with number infixes of another width are recognized as well,
+    /// so that changing the width continues the numbering.
+    NumbersWith {
+        /// With `false`, the logs are written to a file with infix `rCURRENT`,
+        /// like with [`Naming::Numbers`];
+        /// with `true`, they are written directly to the numbered files,
+        /// like with [`Naming::NumbersDirect`].
+        direct: bool,
+        /// The minimal number of digits; smaller numbers are padded with leading zeros.
+        width: usize,
+        /// The index of the first file.
+        start: u32,
+    },
+
+    /// Logs are written to a file without infix, like `app.log`.
+    ///
+    /// File rotation renames this file to `app.log.1`, after renaming the previously
+    /// rotated files `app.log.1`, `app.log.2`, etc. to `app.log.2`, `app.log.3`, etc.,
+    /// so the number of a rotated file tells its age, like with `logrotate`.
+    /// Compressed files are shifted as well, like `app.log.3.gz` to `app.log.4.gz`.
+    ///
+    /// The renames start with the oldest file, so no file is overwritten.
+    /// With this naming, the [cleanup](crate::Cleanup) is never done in a background thread,
+    /// because it must not interfere with the renames.
+    NumbersShifted,
 }
 impl Naming {
     pub(crate) fn writes_direct(self) -> bool {
         matches!(
             self,
             Naming::NumbersDirect
+                | Naming::NumbersWith { direct: true, .. }
                 | Naming::TimestampsDirect
                 | Naming::TimestampsCustomFormat {
                     current_infix: None | Some(""),
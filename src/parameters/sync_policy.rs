@@ -0,0 +1,28 @@
+/// Defines when the log file is synced to the storage device (with `fsync` or its equivalent).
+///
+/// By default, the log file is never synced explicitly; written data reach the storage device
+/// when the operating system decides to write them. Audit logs e.g. might require a
+/// stronger durability.
+///
+/// With all policies except `Never`, the file is also synced before it is closed,
+/// i.e., on rotation and when the program shuts down.
+///
+/// Used in [`Logger::sync_policy`](crate::Logger::sync_policy) and
+/// [`FileLogWriterBuilder::sync_policy`](crate::writers::FileLogWriterBuilder::sync_policy).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum SyncPolicy {
+    /// The log file is never synced explicitly.
+    #[default]
+    Never,
+    /// The log file is synced after the specified number of log lines was written.
+    Records(u64),
+    /// The log file is synced when at least the specified number of bytes was written
+    /// since the last sync.
+    Bytes(u64),
+    /// The log file is synced whenever the output is flushed, i.e., with the flush interval
+    /// of the [`WriteMode`](crate::WriteMode), and with explicit flushes
+    /// like [`LoggerHandle::flush`](crate::LoggerHandle::flush).
+    OnFlush,
+    /// The log file is synced only before it is closed, on rotation and on shutdown.
+    OnRotation,
+}
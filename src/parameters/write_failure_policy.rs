@@ -0,0 +1,34 @@
+use std::time::Duration;
+
+/// Defines how a file log writer behaves if writing to the log file fails repeatedly,
+/// e.g. because the disk is full.
+///
+/// By default, every failed write is reported to the
+/// [error channel](crate::ErrorChannel), which can then be flooded with error messages.
+/// With the other policies, the writer switches to a degraded mode after the given number
+/// of consecutive write failures, and reports this once.
+/// In the degraded mode, it tries to write to the file again with the given probe interval;
+/// when writing succeeds again, it reports one summary and returns to normal operation.
+///
+/// Used in [`Logger::write_failure_policy`](crate::Logger::write_failure_policy) and
+/// [`FileLogWriterBuilder::write_failure_policy`](crate::writers::FileLogWriterBuilder::write_failure_policy).
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum WriteFailurePolicy {
+    /// Every write failure is reported.
+    #[default]
+    Report,
+    /// In the degraded mode, log lines are dropped.
+    Drop {
+        /// Number of consecutive write failures after which the degraded mode is entered.
+        after_failures: u32,
+        /// Interval in which writing to the file is attempted again.
+        probe_interval: Duration,
+    },
+    /// In the degraded mode, log lines are written to stderr.
+    RedirectToStderr {
+        /// Number of consecutive write failures after which the degraded mode is entered.
+        after_failures: u32,
+        /// Interval in which writing to the file is attempted again.
+        probe_interval: Duration,
+    },
+}
@@ -10,11 +10,18 @@ use self::{
 use crate::{
     filter::LogLineWriter,
     logger::Duplicate,
-    writers::{FileLogWriter, LogWriter},
-    DeferredNow, FlexiLoggerError, FormatFunction, LogfileSelector, WriteMode,
+    util::PostFormat,
+    writers::{
+        file_log_writer::{FileLogWriter, RotationEvent},
+        LogWriter,
+    },
+    CleanupStatus, DeferredNow, FlexiLoggerError, FormatFunction, LogfileSelector, WriteMode,
 };
 use log::Record;
-use std::path::PathBuf;
+use std::{
+    path::PathBuf,
+    sync::mpsc::{channel, Receiver},
+};
 
 // Primary writer
 //
@@ -28,43 +35,50 @@ pub(crate) enum PrimaryWriter {
     Test(TestWriter),
 }
 impl PrimaryWriter {
+    #[allow(clippy::too_many_arguments)]
     pub fn multi(
         duplicate_stderr: Duplicate,
         duplicate_stdout: Duplicate,
-        support_capture: bool,
+        write_mode: &WriteMode,
         format_for_stderr: FormatFunction,
         format_for_stdout: FormatFunction,
+        post_format_for_stderr: PostFormat,
+        post_format_for_stdout: PostFormat,
         o_file_writer: Option<Box<FileLogWriter>>,
         o_other_writer: Option<Box<dyn LogWriter>>,
     ) -> Self {
         Self::Multi(MultiWriter::new(
             duplicate_stderr,
             duplicate_stdout,
-            support_capture,
+            write_mode,
             format_for_stderr,
             format_for_stdout,
+            post_format_for_stderr,
+            post_format_for_stdout,
             o_file_writer,
             o_other_writer,
         ))
     }
-    pub fn stderr(format: FormatFunction, write_mode: &WriteMode) -> Self {
+    pub fn stderr(format: FormatFunction, post_format: PostFormat, write_mode: &WriteMode) -> Self {
         Self::Std(StdWriter::new(
             StdStream::Err(std::io::stderr()),
             format,
+            post_format,
             write_mode,
         ))
     }
 
-    pub fn stdout(format: FormatFunction, write_mode: &WriteMode) -> Self {
+    pub fn stdout(format: FormatFunction, post_format: PostFormat, write_mode: &WriteMode) -> Self {
         Self::Std(StdWriter::new(
             StdStream::Out(std::io::stdout()),
             format,
+            post_format,
             write_mode,
         ))
     }
 
-    pub fn test(stdout: bool, format: FormatFunction) -> Self {
-        Self::Test(TestWriter::new(stdout, format))
+    pub fn test(stdout: bool, format: FormatFunction, post_format: PostFormat) -> Self {
+        Self::Test(TestWriter::new(stdout, format, post_format))
     }
 
     // Write out a log line.
@@ -85,6 +99,14 @@ impl PrimaryWriter {
         }
     }
 
+    pub fn flush_and_wait(&self, timeout: std::time::Duration) -> std::io::Result<()> {
+        match *self {
+            Self::Std(ref w) => w.flush_and_wait(timeout),
+            Self::Multi(ref w) => w.flush_and_wait(timeout),
+            Self::Test(ref w) => w.flush_and_wait(timeout),
+        }
+    }
+
     pub fn validate_logs(&self, expected: &[(&'static str, &'static str, &'static str)]) {
         self.shutdown();
         match self {
@@ -115,6 +137,27 @@ impl PrimaryWriter {
         }
     }
 
+    pub fn subscribe_rotation(&self) -> Result<Receiver<RotationEvent>, FlexiLoggerError> {
+        match self {
+            Self::Multi(multi_writer) => multi_writer.subscribe_rotation(),
+            _ => Ok(channel().1),
+        }
+    }
+
+    pub fn cleanup_status(&self) -> Result<CleanupStatus, FlexiLoggerError> {
+        match self {
+            Self::Multi(multi_writer) => multi_writer.cleanup_status(),
+            _ => Ok(CleanupStatus::default()),
+        }
+    }
+
+    pub fn current_log_file(&self) -> Result<Option<PathBuf>, FlexiLoggerError> {
+        match self {
+            Self::Multi(multi_writer) => multi_writer.current_log_file(),
+            _ => Ok(None),
+        }
+    }
+
     pub fn existing_log_files(
         &self,
         selector: &LogfileSelector,
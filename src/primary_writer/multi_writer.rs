@@ -1,14 +1,28 @@
 use crate::{
     logger::Duplicate,
-    util::{eprint_err, write_buffered, ErrorCode},
-    writers::{FileLogWriter, FileLogWriterBuilder, FileLogWriterConfig, LogWriter},
-    LogfileSelector, {DeferredNow, FlexiLoggerError, FormatFunction},
+    util::{eprint_err, format_line, io_err, write_buffered, ErrorCode, PostFormat},
+    writers::{
+        self,
+        file_log_writer::{FileLogWriter, FileLogWriterConfig, RotationEvent},
+        LogWriter,
+    },
+    LogfileSelector, WriteMode, {CleanupStatus, DeferredNow, FlexiLoggerError, FormatFunction},
 };
 use log::Record;
 use std::{
-    io::Write,
+    io::{BufWriter, Stderr, Stdout, Write},
     path::PathBuf,
-    sync::atomic::{AtomicU8, Ordering},
+    sync::{
+        atomic::{AtomicU8, Ordering},
+        mpsc::{channel, Receiver},
+        Mutex,
+    },
+};
+#[cfg(feature = "async")]
+use {
+    super::{std_stream::StdStream, std_writer::StdWriter},
+    crate::EffectiveWriteMode,
+    std::sync::OnceLock,
 };
 
 // The `MultiWriter` writes logs to a FileLogWriter and/or another Writer,
@@ -17,35 +31,61 @@ pub(crate) struct MultiWriter {
     duplicate_stderr: AtomicU8,
     duplicate_stdout: AtomicU8,
     support_capture: bool,
+    // only used with buffering write modes
+    o_duplication_buffers: Option<DuplicationBuffers>,
+    // only used with asynchronous write modes
+    #[cfg(feature = "async")]
+    o_async_duplication: Option<Box<AsyncDuplication>>,
     format_for_stderr: FormatFunction,
     format_for_stdout: FormatFunction,
+    post_format_for_stderr: PostFormat,
+    post_format_for_stdout: PostFormat,
     o_file_writer: Option<Box<FileLogWriter>>,
     o_other_writer: Option<Box<dyn LogWriter>>,
 }
 
 impl MultiWriter {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         duplicate_stderr: Duplicate,
         duplicate_stdout: Duplicate,
-        support_capture: bool,
+        write_mode: &WriteMode,
         format_for_stderr: FormatFunction,
         format_for_stdout: FormatFunction,
+        post_format_for_stderr: PostFormat,
+        post_format_for_stdout: PostFormat,
         o_file_writer: Option<Box<FileLogWriter>>,
         o_other_writer: Option<Box<dyn LogWriter>>,
     ) -> Self {
         MultiWriter {
             duplicate_stderr: AtomicU8::new(duplicate_stderr as u8),
             duplicate_stdout: AtomicU8::new(duplicate_stdout as u8),
-            support_capture,
+            support_capture: WriteMode::SupportCapture == *write_mode,
+            o_duplication_buffers: write_mode.buffersize().map(|capacity| DuplicationBuffers {
+                stderr: Mutex::new(BufWriter::with_capacity(capacity, std::io::stderr())),
+                stdout: Mutex::new(BufWriter::with_capacity(capacity, std::io::stdout())),
+            }),
+            #[cfg(feature = "async")]
+            o_async_duplication: match write_mode.effective_write_mode() {
+                EffectiveWriteMode::AsyncWith { .. } => Some(Box::new(AsyncDuplication {
+                    write_mode: *write_mode,
+                    stderr: OnceLock::new(),
+                    stdout: OnceLock::new(),
+                })),
+                _ => None,
+            },
             format_for_stderr,
             format_for_stdout,
+            post_format_for_stderr,
+            post_format_for_stdout,
             o_file_writer,
             o_other_writer,
         }
     }
+    #[cfg(not(target_family = "wasm"))]
     pub(crate) fn reset_file_log_writer(
         &self,
-        flwb: &FileLogWriterBuilder,
+        flwb: &writers::FileLogWriterBuilder,
     ) -> Result<(), FlexiLoggerError> {
         self.o_file_writer
             .as_ref()
@@ -86,6 +126,59 @@ impl MultiWriter {
             }
         }
     }
+    pub(crate) fn rotate_to(&self, infix: &str) -> Result<(), FlexiLoggerError> {
+        match (&self.o_file_writer, &self.o_other_writer) {
+            (None, None) => Ok(()),
+            (Some(ref w), None) => w.rotate_to(infix),
+            (None, Some(w)) => w.rotate_to(infix),
+            (Some(w1), Some(w2)) => {
+                let r1 = w1.rotate_to(infix);
+                let r2 = w2.rotate_to(infix);
+                match (r1, r2) {
+                    (Ok(()), Ok(())) => Ok(()),
+                    (Err(e), _) | (Ok(()), Err(e)) => Err(e),
+                }
+            }
+        }
+    }
+    pub(crate) fn trigger_cleanup(&self) -> Result<(), FlexiLoggerError> {
+        match (&self.o_file_writer, &self.o_other_writer) {
+            (None, None) => Ok(()),
+            (Some(ref w), None) => w.trigger_cleanup(),
+            (None, Some(w)) => w.trigger_cleanup(),
+            (Some(w1), Some(w2)) => {
+                let r1 = w1.trigger_cleanup();
+                let r2 = w2.trigger_cleanup();
+                match (r1, r2) {
+                    (Ok(()), Ok(())) => Ok(()),
+                    (Err(e), _) | (Ok(()), Err(e)) => Err(e),
+                }
+            }
+        }
+    }
+    pub(crate) fn subscribe_rotation(&self) -> Result<Receiver<RotationEvent>, FlexiLoggerError> {
+        if let Some(fw) = self.o_file_writer.as_ref() {
+            fw.subscribe_rotation()
+        } else {
+            Ok(channel().1)
+        }
+    }
+    pub(crate) fn cleanup_status(&self) -> Result<CleanupStatus, FlexiLoggerError> {
+        if let Some(fw) = self.o_file_writer.as_ref() {
+            fw.cleanup_status()
+        } else {
+            Ok(CleanupStatus::default())
+        }
+    }
+
+    pub(crate) fn current_log_file(&self) -> Result<Option<PathBuf>, FlexiLoggerError> {
+        if let Some(fw) = self.o_file_writer.as_ref() {
+            fw.current_log_file()
+        } else {
+            Ok(None)
+        }
+    }
+
     pub(crate) fn existing_log_files(
         &self,
         selector: &LogfileSelector,
@@ -97,6 +190,16 @@ impl MultiWriter {
         }
     }
 
+    // Collects the names and errors of the contained writers whose healthcheck fails.
+    pub(crate) fn unhealthy_writers(&self) -> Vec<(String, FlexiLoggerError)> {
+        self.o_file_writer
+            .iter()
+            .map(|fw| &**fw as &dyn LogWriter)
+            .chain(self.o_other_writer.as_deref())
+            .filter_map(|w| w.healthcheck().err().map(|e| (w.name().to_string(), e)))
+            .collect()
+    }
+
     pub(crate) fn adapt_duplication_to_stderr(&self, dup: Duplicate) {
         self.duplicate_stderr.store(dup as u8, Ordering::Relaxed);
     }
@@ -105,12 +208,44 @@ impl MultiWriter {
         self.duplicate_stdout.store(dup as u8, Ordering::Relaxed);
     }
 
-    fn duplication_to_stderr(&self) -> Duplicate {
+    pub(crate) fn duplication_to_stderr(&self) -> Duplicate {
         Duplicate::from(self.duplicate_stderr.load(Ordering::Relaxed))
     }
-    fn duplication_to_stdout(&self) -> Duplicate {
+    pub(crate) fn duplication_to_stdout(&self) -> Duplicate {
         Duplicate::from(self.duplicate_stdout.load(Ordering::Relaxed))
     }
+
+    // With asynchronous write modes, the duplicates are written by a background thread;
+    // it is only started when the first duplicate is written
+    #[cfg(feature = "async")]
+    fn async_duplication_writer(&self, stdout: bool) -> Option<&StdWriter> {
+        self.o_async_duplication.as_ref().map(|ad| {
+            if stdout {
+                ad.stdout.get_or_init(|| {
+                    StdWriter::new(
+                        StdStream::Out(std::io::stdout()),
+                        self.format_for_stdout,
+                        self.post_format_for_stdout,
+                        &ad.write_mode,
+                    )
+                })
+            } else {
+                ad.stderr.get_or_init(|| {
+                    StdWriter::new(
+                        StdStream::Err(std::io::stderr()),
+                        self.format_for_stderr,
+                        self.post_format_for_stderr,
+                        &ad.write_mode,
+                    )
+                })
+            }
+        })
+    }
+    #[cfg(not(feature = "async"))]
+    #[allow(clippy::unused_self)]
+    fn async_duplication_writer(&self, _stdout: bool) -> Option<&dyn LogWriter> {
+        None
+    }
 }
 
 impl LogWriter for MultiWriter {
@@ -132,14 +267,33 @@ impl LogWriter for MultiWriter {
             Duplicate::Trace | Duplicate::All => true,
             Duplicate::None => false,
         } {
-            if self.support_capture {
+            if let Some(async_writer) = self.async_duplication_writer(false) {
+                async_writer.write(now, record)?;
+            } else if self.support_capture {
                 let mut tmp_buf = Vec::<u8>::with_capacity(200);
-                (self.format_for_stderr)(&mut tmp_buf, now, record)
-                    .unwrap_or_else(|e| eprint_err(ErrorCode::Format, "formatting failed", &e));
+                format_line(
+                    self.format_for_stderr,
+                    self.post_format_for_stderr,
+                    &mut tmp_buf,
+                    now,
+                    record,
+                )
+                .unwrap_or_else(|e| eprint_err(ErrorCode::Format, "formatting failed", &e));
                 eprintln!("{}", String::from_utf8_lossy(&tmp_buf));
+            } else if let Some(ref buffers) = self.o_duplication_buffers {
+                write_buffered(
+                    self.format_for_stderr,
+                    self.post_format_for_stderr,
+                    now,
+                    record,
+                    &mut *buffers.stderr.lock().map_err(|_| io_err("Poison"))?,
+                    #[cfg(test)]
+                    None,
+                )?;
             } else {
                 write_buffered(
                     self.format_for_stderr,
+                    self.post_format_for_stderr,
                     now,
                     record,
                     &mut std::io::stderr(),
@@ -157,14 +311,33 @@ impl LogWriter for MultiWriter {
             Duplicate::Trace | Duplicate::All => true,
             Duplicate::None => false,
         } {
-            if self.support_capture {
+            if let Some(async_writer) = self.async_duplication_writer(true) {
+                async_writer.write(now, record)?;
+            } else if self.support_capture {
                 let mut tmp_buf = Vec::<u8>::with_capacity(200);
-                (self.format_for_stdout)(&mut tmp_buf, now, record)
-                    .unwrap_or_else(|e| eprint_err(ErrorCode::Format, "formatting failed", &e));
+                format_line(
+                    self.format_for_stdout,
+                    self.post_format_for_stdout,
+                    &mut tmp_buf,
+                    now,
+                    record,
+                )
+                .unwrap_or_else(|e| eprint_err(ErrorCode::Format, "formatting failed", &e));
                 println!("{}", String::from_utf8_lossy(&tmp_buf));
+            } else if let Some(ref buffers) = self.o_duplication_buffers {
+                write_buffered(
+                    self.format_for_stdout,
+                    self.post_format_for_stdout,
+                    now,
+                    record,
+                    &mut *buffers.stdout.lock().map_err(|_| io_err("Poison"))?,
+                    #[cfg(test)]
+                    None,
+                )?;
             } else {
                 write_buffered(
                     self.format_for_stdout,
+                    self.post_format_for_stdout,
                     now,
                     record,
                     &mut std::io::stdout(),
@@ -178,7 +351,7 @@ impl LogWriter for MultiWriter {
             writer.write(now, record)?;
         }
         if let Some(ref writer) = self.o_other_writer {
-            writer.write(now, record)?;
+            writers::dispatch(&**writer, now, record)?;
         }
         Ok(())
     }
@@ -208,6 +381,27 @@ impl LogWriter for MultiWriter {
             writer.flush()?;
         }
 
+        #[cfg(feature = "async")]
+        if let Some(ref async_duplication) = self.o_async_duplication {
+            for async_writer in [&async_duplication.stderr, &async_duplication.stdout]
+                .into_iter()
+                .filter_map(OnceLock::get)
+            {
+                async_writer.flush()?;
+            }
+        }
+        if let Some(ref buffers) = self.o_duplication_buffers {
+            buffers
+                .stderr
+                .lock()
+                .map_err(|_| io_err("Poison"))?
+                .flush()?;
+            buffers
+                .stdout
+                .lock()
+                .map_err(|_| io_err("Poison"))?
+                .flush()?;
+        }
         if !matches!(self.duplication_to_stderr(), Duplicate::None) {
             std::io::stderr().flush()?;
         }
@@ -217,6 +411,28 @@ impl LogWriter for MultiWriter {
         Ok(())
     }
 
+    fn flush_and_wait(&self, timeout: std::time::Duration) -> std::io::Result<()> {
+        let deadline = std::time::Instant::now() + timeout;
+        let remaining = || deadline.saturating_duration_since(std::time::Instant::now());
+        if let Some(ref writer) = self.o_file_writer {
+            writer.flush_and_wait(remaining())?;
+        }
+        if let Some(ref writer) = self.o_other_writer {
+            writer.flush_and_wait(remaining())?;
+        }
+
+        #[cfg(feature = "async")]
+        if let Some(ref async_duplication) = self.o_async_duplication {
+            for async_writer in [&async_duplication.stderr, &async_duplication.stdout]
+                .into_iter()
+                .filter_map(OnceLock::get)
+            {
+                async_writer.flush_and_wait(remaining())?;
+            }
+        }
+        self.flush()
+    }
+
     fn shutdown(&self) {
         if let Some(ref writer) = self.o_file_writer {
             writer.shutdown();
@@ -224,5 +440,26 @@ impl LogWriter for MultiWriter {
         if let Some(ref writer) = self.o_other_writer {
             writer.shutdown();
         }
+        #[cfg(feature = "async")]
+        if let Some(ref async_duplication) = self.o_async_duplication {
+            for async_writer in [&async_duplication.stderr, &async_duplication.stdout]
+                .into_iter()
+                .filter_map(OnceLock::get)
+            {
+                async_writer.shutdown();
+            }
+        }
     }
 }
+
+struct DuplicationBuffers {
+    stderr: Mutex<BufWriter<Stderr>>,
+    stdout: Mutex<BufWriter<Stdout>>,
+}
+
+#[cfg(feature = "async")]
+struct AsyncDuplication {
+    write_mode: WriteMode,
+    stderr: OnceLock<StdWriter>,
+    stdout: OnceLock<StdWriter>,
+}
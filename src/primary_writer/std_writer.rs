@@ -1,7 +1,10 @@
 #[cfg(feature = "async")]
 use {
     crate::{
-        util::{eprint_err, ErrorCode, ASYNC_FLUSH, ASYNC_SHUTDOWN},
+        util::{
+            eprint_err, format_line, ErrorCode, FlushAcks, ASYNC_FLUSH, ASYNC_FLUSH_AND_ACK,
+            ASYNC_SHUTDOWN,
+        },
         ZERO_DURATION,
     },
     crossbeam_channel::{SendError, Sender},
@@ -11,7 +14,7 @@ use {
 use {
     super::std_stream::StdStream,
     crate::{
-        util::{io_err, write_buffered},
+        util::{io_err, write_buffered, PostFormat},
         writers::LogWriter,
         DeferredNow, EffectiveWriteMode, FormatFunction, WriteMode,
     },
@@ -31,6 +34,7 @@ use std::thread::JoinHandle;
 // `StdWriter` writes logs to stdout or stderr.
 pub(crate) struct StdWriter {
     format: FormatFunction,
+    post_format: PostFormat,
     writer: InnerStdWriter,
     #[cfg(test)]
     validation_buffer: Arc<Mutex<Cursor<Vec<u8>>>>,
@@ -48,6 +52,7 @@ struct AsyncHandle {
     mo_thread_handle: Mutex<Option<JoinHandle<()>>>,
     a_pool: Arc<ArrayQueue<Vec<u8>>>,
     msg_capa: usize,
+    a_flush_acks: Arc<FlushAcks>,
 }
 #[cfg(feature = "async")]
 impl AsyncHandle {
@@ -59,12 +64,14 @@ impl AsyncHandle {
     ) -> Self {
         let (sender, receiver) = crossbeam_channel::unbounded::<Vec<u8>>();
         let a_pool = Arc::new(ArrayQueue::new(pool_capa));
+        let a_flush_acks = Arc::new(FlushAcks::default());
 
         let mo_thread_handle = crate::threads::start_async_stdwriter(
             stdstream,
             receiver,
             Arc::clone(&a_pool),
             msg_capa,
+            Arc::clone(&a_flush_acks),
             #[cfg(test)]
             Arc::clone(validation_buffer),
         );
@@ -74,6 +81,7 @@ impl AsyncHandle {
             mo_thread_handle,
             a_pool,
             msg_capa,
+            a_flush_acks,
         }
     }
 
@@ -92,6 +100,7 @@ impl StdWriter {
     pub(crate) fn new(
         stdstream: StdStream,
         format: FormatFunction,
+        post_format: PostFormat,
         write_mode: &WriteMode,
     ) -> Self {
         #[cfg(test)]
@@ -126,6 +135,7 @@ impl StdWriter {
         };
         Self {
             format,
+            post_format,
             writer,
             #[cfg(test)]
             validation_buffer,
@@ -140,6 +150,7 @@ impl LogWriter for StdWriter {
                 let mut w = stdstream.lock();
                 write_buffered(
                     self.format,
+                    self.post_format,
                     now,
                     record,
                     &mut w,
@@ -151,6 +162,7 @@ impl LogWriter for StdWriter {
                 let mut w = m_w.lock().map_err(|_e| io_err("Poison"))?;
                 write_buffered(
                     self.format,
+                    self.post_format,
                     now,
                     record,
                     &mut *w,
@@ -161,7 +173,7 @@ impl LogWriter for StdWriter {
             #[cfg(feature = "async")]
             InnerStdWriter::Async(handle) => {
                 let mut buffer = handle.pop_buffer();
-                (self.format)(&mut buffer, now, record)
+                format_line(self.format, self.post_format, &mut buffer, now, record)
                     .unwrap_or_else(|e| eprint_err(ErrorCode::Format, "formatting failed", &e));
                 buffer
                     .write_all(b"\n")
@@ -193,6 +205,24 @@ impl LogWriter for StdWriter {
         }
     }
 
+    fn flush_and_wait(&self, timeout: std::time::Duration) -> std::io::Result<()> {
+        match &self.writer {
+            #[cfg(feature = "async")]
+            InnerStdWriter::Async(handle) => handle.a_flush_acks.request_and_wait(
+                || {
+                    let mut buffer = handle.pop_buffer();
+                    buffer.extend(ASYNC_FLUSH_AND_ACK);
+                    handle.send(buffer).map_err(|_e| io_err("Send"))
+                },
+                timeout,
+            ),
+            _ => {
+                _ = timeout;
+                self.flush()
+            }
+        }
+    }
+
     fn shutdown(&self) {
         #[cfg(feature = "async")]
         if let InnerStdWriter::Async(handle) = &self.writer {
@@ -205,6 +235,10 @@ impl LogWriter for StdWriter {
         }
     }
 
+    fn name(&self) -> &'static str {
+        "StdWriter"
+    }
+
     #[cfg(not(test))]
     fn validate_logs(&self, _expected: &[(&'static str, &'static str, &'static str)]) {}
     #[cfg(test)]
@@ -223,7 +257,7 @@ impl LogWriter for StdWriter {
             }
             buf.clear();
             reader.read_line(&mut buf).unwrap();
-            assert!(buf.is_empty(), "Found more log lines than expected: {buf} ",);
+            assert!(buf.is_empty(), "Found more log lines than expected: {buf} ");
         }
     }
 }
@@ -231,7 +265,7 @@ impl LogWriter for StdWriter {
 #[cfg(test)]
 mod test {
     use super::{StdStream, StdWriter};
-    use crate::{opt_format, writers::LogWriter, DeferredNow, WriteMode};
+    use crate::{opt_format, util::PostFormat, writers::LogWriter, DeferredNow, WriteMode};
     use log::Level::{Error, Info, Warn};
 
     #[test]
@@ -239,6 +273,7 @@ mod test {
         let writer = StdWriter::new(
             StdStream::Err(std::io::stderr()),
             opt_format,
+            PostFormat::default(),
             &WriteMode::Direct,
         );
         let mut rb = log::Record::builder();
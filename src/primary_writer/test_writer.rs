@@ -13,23 +13,25 @@ use std::{
     sync::{Arc, Mutex},
 };
 
-use crate::util::{eprint_err, ErrorCode};
+use crate::util::{eprint_err, format_line, ErrorCode, PostFormat};
 
 // `TestWriter` writes logs using println!
 pub(crate) struct TestWriter {
     format: FormatFunction,
+    post_format: PostFormat,
     stdout: bool,
     #[cfg(test)]
     validation_buffer: Arc<Mutex<Cursor<Vec<u8>>>>,
 }
 
 impl TestWriter {
-    pub(crate) fn new(stdout: bool, format: FormatFunction) -> Self {
+    pub(crate) fn new(stdout: bool, format: FormatFunction, post_format: PostFormat) -> Self {
         #[cfg(test)]
         let validation_buffer = Arc::new(Mutex::new(Cursor::new(Vec::<u8>::new())));
 
         Self {
             format,
+            post_format,
             stdout,
             #[cfg(test)]
             validation_buffer,
@@ -41,7 +43,7 @@ impl LogWriter for TestWriter {
     fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
         buffer_with(|tl_buf| match tl_buf.try_borrow_mut() {
             Ok(mut buffer) => {
-                (self.format)(&mut *buffer, now, record)
+                format_line(self.format, self.post_format, &mut *buffer, now, record)
                     .unwrap_or_else(|e| eprint_err(ErrorCode::Format, "formatting failed", &e));
                 if self.stdout {
                     println!("{}", String::from_utf8_lossy(&buffer));
@@ -64,7 +66,7 @@ impl LogWriter for TestWriter {
                 // we print the inner calls, in chronological order, before finally the
                 // outer most message is printed
                 let mut tmp_buf = Vec::<u8>::with_capacity(200);
-                (self.format)(&mut tmp_buf, now, record)
+                format_line(self.format, self.post_format, &mut tmp_buf, now, record)
                     .unwrap_or_else(|e| eprint_err(ErrorCode::Format, "formatting failed", &e));
                 if self.stdout {
                     println!("{}", String::from_utf8_lossy(&tmp_buf));
@@ -108,7 +110,7 @@ impl LogWriter for TestWriter {
             }
             buf.clear();
             reader.read_line(&mut buf).unwrap();
-            assert!(buf.is_empty(), "Found more log lines than expected: {buf} ",);
+            assert!(buf.is_empty(), "Found more log lines than expected: {buf} ");
         }
     }
 }
@@ -127,12 +129,12 @@ where
 #[cfg(test)]
 mod test {
     use super::TestWriter;
-    use crate::{opt_format, writers::LogWriter, DeferredNow};
+    use crate::{opt_format, util::PostFormat, writers::LogWriter, DeferredNow};
     use log::Level::{Error, Info, Warn};
 
     #[test]
     fn test_with_validation() {
-        let writer = TestWriter::new(true, opt_format);
+        let writer = TestWriter::new(true, opt_format, PostFormat::default());
         let mut rb = log::Record::builder();
         rb.target("myApp")
             .file(Some("std_writer.rs"))
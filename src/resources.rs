@@ -0,0 +1,108 @@
+use std::{
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+// Names of the threads that were spawned by flexi_logger and are still running.
+static LIVE_THREADS: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+// Paths of the log files that are currently held open by flexi_logger.
+static OPEN_FILES: Mutex<Vec<PathBuf>> = Mutex::new(Vec::new());
+
+/// Snapshot of the resources that `flexi_logger` holds in the current process.
+///
+/// Can be obtained with [`LoggerHandle::debug_resources`](crate::LoggerHandle::debug_resources),
+/// e.g. to verify in tests that
+/// [`LoggerHandle::shutdown`](crate::LoggerHandle::shutdown) releases everything.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct ResourceReport {
+    /// Names of the threads that were spawned by `flexi_logger` and are still running.
+    pub threads: Vec<String>,
+    /// Paths of the log files that are currently held open by `flexi_logger`.
+    pub open_files: Vec<PathBuf>,
+}
+impl ResourceReport {
+    /// Returns true if neither threads nor open files are reported.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.threads.is_empty() && self.open_files.is_empty()
+    }
+
+    pub(crate) fn current() -> Self {
+        Self {
+            threads: LIVE_THREADS
+                .lock()
+                .map(|threads| threads.iter().map(ToString::to_string).collect())
+                .unwrap_or_default(),
+            open_files: OPEN_FILES
+                .lock()
+                .map(|files| files.clone())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+// Registers a thread as running, as long as the registration is alive.
+//
+// Should be created before the thread is spawned and then be moved into the thread's closure.
+pub(crate) struct ThreadRegistration(&'static str);
+impl ThreadRegistration {
+    pub(crate) fn new(name: &'static str) -> Self {
+        if let Ok(mut threads) = LIVE_THREADS.lock() {
+            threads.push(name);
+        }
+        Self(name)
+    }
+}
+impl Drop for ThreadRegistration {
+    fn drop(&mut self) {
+        if let Ok(mut threads) = LIVE_THREADS.lock() {
+            if let Some(idx) = threads.iter().position(|name| *name == self.0) {
+                threads.swap_remove(idx);
+            }
+        }
+    }
+}
+
+// A writer that registers its path as open file, as long as it is alive.
+pub(crate) struct RegisteredFile<W: Write> {
+    // field order matters: the writer must be dropped (and thus closed) before
+    // the registration is removed
+    writer: W,
+    _registration: FileRegistration,
+}
+impl<W: Write> RegisteredFile<W> {
+    pub(crate) fn new(writer: W, path: &Path) -> Self {
+        if let Ok(mut files) = OPEN_FILES.lock() {
+            files.push(path.to_path_buf());
+        }
+        Self {
+            writer,
+            _registration: FileRegistration(path.to_path_buf()),
+        }
+    }
+}
+impl<W: Write> Write for RegisteredFile<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.writer.write(buf)
+    }
+    fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        self.writer.write_all(buf)
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+}
+
+struct FileRegistration(PathBuf);
+impl Drop for FileRegistration {
+    fn drop(&mut self) {
+        if let Ok(mut files) = OPEN_FILES.lock() {
+            if let Some(idx) = files.iter().position(|path| *path == self.0) {
+                files.swap_remove(idx);
+            }
+        }
+    }
+}
@@ -0,0 +1,115 @@
+use crate::{FlexiLoggerError, LoggerHandle};
+use std::sync::{
+    atomic::{AtomicU64, Ordering},
+    Arc, Mutex, RwLock,
+};
+
+// Is installed as global logger by the first call to `Logger::start_scoped`,
+// and forwards all calls to the most recently started scoped logger.
+struct ScopedDispatcher;
+static DISPATCHER: ScopedDispatcher = ScopedDispatcher;
+static DISPATCHER_INSTALLED: Mutex<bool> = Mutex::new(false);
+
+// The scoped loggers, in the order in which they were started; the last one is active.
+static SCOPED_LOGGERS: RwLock<Vec<ScopedLogger>> = RwLock::new(Vec::new());
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+struct ScopedLogger {
+    id: u64,
+    logger: Arc<dyn log::Log>,
+    // the global max level from before the logger was started, is restored when it ends
+    previous_max_level: log::LevelFilter,
+}
+
+impl ScopedDispatcher {
+    fn active_logger() -> Option<Arc<dyn log::Log>> {
+        // the lock is not held while logging, so that log calls within a log call are possible
+        SCOPED_LOGGERS
+            .read()
+            .ok()?
+            .last()
+            .map(|scoped| Arc::clone(&scoped.logger))
+    }
+}
+impl log::Log for ScopedDispatcher {
+    fn enabled(&self, metadata: &log::Metadata) -> bool {
+        Self::active_logger().is_some_and(|logger| logger.enabled(metadata))
+    }
+
+    fn log(&self, record: &log::Record) {
+        if let Some(logger) = Self::active_logger() {
+            logger.log(record);
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(logger) = Self::active_logger() {
+            logger.flush();
+        }
+    }
+}
+
+// Makes the given logger the active scoped logger.
+pub(crate) fn activate(
+    logger: Box<dyn log::Log>,
+    handle: LoggerHandle,
+    previous_max_level: log::LevelFilter,
+) -> Result<ScopedLoggerGuard, FlexiLoggerError> {
+    {
+        let mut installed = DISPATCHER_INSTALLED
+            .lock()
+            .map_err(|_| FlexiLoggerError::Poison)?;
+        if !*installed {
+            log::set_logger(&DISPATCHER)?;
+            *installed = true;
+        }
+    }
+
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    let mut scoped_loggers = SCOPED_LOGGERS
+        .write()
+        .map_err(|_| FlexiLoggerError::Poison)?;
+    scoped_loggers.push(ScopedLogger {
+        id,
+        logger: Arc::from(logger),
+        previous_max_level,
+    });
+    log::set_max_level(handle.max_level());
+    Ok(ScopedLoggerGuard { id, handle })
+}
+
+/// Keeps a logger active that was started with
+/// [`Logger::start_scoped`](crate::Logger::start_scoped).
+///
+/// Gives access to the [`LoggerHandle`] of the logger.
+/// When the guard is dropped, the logger is deactivated, and the previously active
+/// scoped logger, if any, becomes active again, together with the global max level
+/// that was in place when this logger was started.
+#[must_use = "the logger is deactivated immediately if the guard is not kept"]
+pub struct ScopedLoggerGuard {
+    id: u64,
+    handle: LoggerHandle,
+}
+impl std::ops::Deref for ScopedLoggerGuard {
+    type Target = LoggerHandle;
+    fn deref(&self) -> &LoggerHandle {
+        &self.handle
+    }
+}
+impl Drop for ScopedLoggerGuard {
+    fn drop(&mut self) {
+        if let Ok(mut scoped_loggers) = SCOPED_LOGGERS.write() {
+            if let Some(index) = scoped_loggers
+                .iter()
+                .position(|scoped| scoped.id == self.id)
+            {
+                let scoped = scoped_loggers.remove(index);
+                match scoped_loggers.get_mut(index) {
+                    // a later started logger is active and restores the level when it ends
+                    Some(next) => next.previous_max_level = scoped.previous_max_level,
+                    None => log::set_max_level(scoped.previous_max_level),
+                }
+            }
+        }
+    }
+}
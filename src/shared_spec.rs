@@ -0,0 +1,167 @@
+use crate::LogSpecification;
+use std::{
+    cell::RefCell,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, PoisonError, RwLock,
+    },
+};
+
+// Every SharedSpec gets a unique key, under which its snapshot is cached in the thread-locals.
+static NEXT_KEY: AtomicU64 = AtomicU64::new(1);
+
+// The number of SharedSpecs whose snapshots are cached per thread, e.g. for nested loggers;
+// if more are used alternately, the least recently used snapshot is dropped.
+const CACHE_SIZE: usize = 8;
+
+thread_local! {
+    // The snapshots that were used on this thread, with their keys,
+    // the most recently used at the end.
+    static CACHED_SNAPSHOTS: RefCell<Vec<(u64, Snapshot)>> = const { RefCell::new(Vec::new()) };
+}
+
+type Snapshot = (u64, Arc<LogSpecification>);
+
+// Holds the active LogSpecification, which is read for every log call,
+// but is modified only rarely.
+//
+// Modifications replace the complete snapshot. Readers use a snapshot that they cached in a
+// thread-local, as long as its id is still current; so the hot path is a single atomic load
+// and does not write to any memory that is shared between threads.
+pub(crate) struct SharedSpec {
+    key: u64,
+    current_id: AtomicU64,
+    snapshot: RwLock<Snapshot>,
+}
+impl SharedSpec {
+    pub(crate) fn new(spec: LogSpecification) -> Self {
+        Self {
+            key: NEXT_KEY.fetch_add(1, Ordering::Relaxed),
+            current_id: AtomicU64::new(0),
+            snapshot: RwLock::new((0, Arc::new(spec))),
+        }
+    }
+
+    // Returns the current snapshot, without using the thread-local cache.
+    pub(crate) fn read(&self) -> Arc<LogSpecification> {
+        Arc::clone(&self.load().1)
+    }
+
+    // Calls the function with the current snapshot.
+    pub(crate) fn with_current<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&LogSpecification) -> R,
+    {
+        let current_id = self.current_id.load(Ordering::Acquire);
+        let mut o_f = Some(f);
+        CACHED_SNAPSHOTS
+            .try_with(|cell| {
+                // the cache is not available if the function itself writes a log line,
+                // or if the thread is being torn down
+                let mut cached = cell.try_borrow_mut().ok()?;
+                match cached.iter().position(|(key, _)| *key == self.key) {
+                    Some(index) if index + 1 == cached.len() => {}
+                    Some(index) => {
+                        let entry = cached.remove(index);
+                        cached.push(entry);
+                    }
+                    None => {
+                        if cached.len() == CACHE_SIZE {
+                            cached.remove(0);
+                        }
+                        cached.push((self.key, self.load()));
+                    }
+                }
+                let (_, snapshot) = cached.last_mut()?;
+                if snapshot.0 != current_id {
+                    *snapshot = self.load();
+                }
+                o_f.take().map(|f| f(&snapshot.1))
+            })
+            .ok()
+            .flatten()
+            .unwrap_or_else(|| {
+                let f = o_f.take().unwrap(/* is only taken when a result is produced */);
+                f(&self.load().1)
+            })
+    }
+
+    // Modifies a copy of the current snapshot with the given function and makes it current.
+    //
+    // Modifications are serialized, so concurrent modifications cannot get lost.
+    pub(crate) fn update<F, R>(&self, f: F) -> R
+    where
+        F: FnOnce(&mut LogSpecification) -> R,
+    {
+        // a panic in f leaves the current snapshot untouched, so poisoning can be ignored
+        let mut snapshot = self
+            .snapshot
+            .write()
+            .unwrap_or_else(PoisonError::into_inner);
+        let mut spec = LogSpecification::clone(&snapshot.1);
+        let result = f(&mut spec);
+        let id = snapshot.0 + 1;
+        *snapshot = (id, Arc::new(spec));
+        self.current_id.store(id, Ordering::Release);
+        result
+    }
+
+    fn load(&self) -> Snapshot {
+        let snapshot = self.snapshot.read().unwrap_or_else(PoisonError::into_inner);
+        (snapshot.0, Arc::clone(&snapshot.1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::SharedSpec;
+    use crate::LogSpecification;
+    use log::Level;
+
+    #[test]
+    fn snapshot_follows_updates() {
+        let shared_spec = SharedSpec::new(LogSpecification::info());
+        assert!(!shared_spec.with_current(|spec| spec.enabled(Level::Debug, "foo")));
+
+        shared_spec.update(|spec| spec.update_from(LogSpecification::debug()));
+        assert!(shared_spec.with_current(|spec| spec.enabled(Level::Debug, "foo")));
+
+        // a second instance does not use the snapshot of the first one
+        let other = SharedSpec::new(LogSpecification::off());
+        assert!(!other.with_current(|spec| spec.enabled(Level::Error, "foo")));
+        assert!(shared_spec.with_current(|spec| spec.enabled(Level::Debug, "foo")));
+
+        // the snapshots of both are cached
+        let cached_keys = || {
+            super::CACHED_SNAPSHOTS.with(|cached| {
+                cached
+                    .borrow()
+                    .iter()
+                    .map(|(key, _)| *key)
+                    .collect::<Vec<_>>()
+            })
+        };
+        assert_eq!(cached_keys(), vec![other.key, shared_spec.key]);
+        other.update(|spec| spec.update_from(LogSpecification::error()));
+        assert!(other.with_current(|spec| spec.enabled(Level::Error, "foo")));
+        assert_eq!(cached_keys(), vec![shared_spec.key, other.key]);
+
+        // only the most recently used snapshots are kept
+        let more = (0..super::CACHE_SIZE)
+            .map(|_| SharedSpec::new(LogSpecification::off()))
+            .collect::<Vec<_>>();
+        for spec in &more {
+            spec.with_current(|_| ());
+        }
+        assert_eq!(
+            cached_keys(),
+            more.iter().map(|spec| spec.key).collect::<Vec<_>>()
+        );
+
+        // nested reads bypass the thread-local cache
+        assert!(shared_spec.with_current(|outer| {
+            outer.enabled(Level::Debug, "foo")
+                && shared_spec.with_current(|inner| inner.enabled(Level::Debug, "foo"))
+        }));
+    }
+}
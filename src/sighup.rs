@@ -0,0 +1,78 @@
+// Lets the logger re-open its output when the process receives SIGHUP.
+//
+// The signal handling is done by the crate `signal-hook`: its signal handler only
+// records the signal, and a separate thread, which iterates over the received signals,
+// does the real work.
+
+use crate::{
+    resources::ThreadRegistration,
+    util::{eprint_err, ErrorCode},
+    FlexiLoggerError,
+};
+use signal_hook::{consts::SIGHUP, iterator::Signals};
+use std::{
+    sync::Mutex,
+    thread::{Builder as ThreadBuilder, JoinHandle},
+};
+
+const SIGHUP_LISTENER: &str = "flexi_logger-sighup_listener";
+
+// Handle to the thread that re-opens the output when SIGHUP is received.
+pub(crate) struct SighupListener {
+    signals_handle: signal_hook::iterator::Handle,
+    mo_join_handle: Mutex<Option<JoinHandle<()>>>,
+}
+impl SighupListener {
+    // Registers for SIGHUP and starts the thread that calls `reopen` for every SIGHUP.
+    //
+    // If several loggers register, each of them is informed.
+    pub(crate) fn start<F>(reopen: F) -> Result<Self, FlexiLoggerError>
+    where
+        F: Fn() -> Result<(), FlexiLoggerError> + Send + 'static,
+    {
+        let mut signals = Signals::new([SIGHUP])?;
+        let signals_handle = signals.handle();
+
+        let registration = ThreadRegistration::new(SIGHUP_LISTENER);
+        let join_handle = ThreadBuilder::new()
+            .name(SIGHUP_LISTENER.to_string())
+            .spawn(move || {
+                let _registration = registration;
+                // ends when the handle is closed
+                for _signal in signals.forever() {
+                    reopen().unwrap_or_else(|e| {
+                        eprint_err(
+                            ErrorCode::LogFile,
+                            "re-opening the output after SIGHUP failed",
+                            &e,
+                        );
+                    });
+                }
+            })?;
+
+        Ok(Self {
+            signals_handle,
+            mo_join_handle: Mutex::new(Some(join_handle)),
+        })
+    }
+
+    // Unregisters from SIGHUP and stops the thread.
+    //
+    // Note that the signal handler of `signal-hook` stays installed, so that a later SIGHUP
+    // is ignored, rather than terminating the process.
+    pub(crate) fn stop(&self) {
+        if let Ok(mut o_join_handle) = self.mo_join_handle.lock() {
+            if let Some(join_handle) = o_join_handle.take() {
+                self.signals_handle.close();
+                if join_handle.thread().id() != std::thread::current().id() {
+                    join_handle.join().ok();
+                }
+            }
+        }
+    }
+}
+impl Drop for SighupListener {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
@@ -1,24 +1,30 @@
 use {
-    crate::{primary_writer::PrimaryWriter, writers::LogWriter, FlexiLoggerError},
+    crate::{
+        primary_writer::PrimaryWriter, resources::ThreadRegistration, writers::LogWriter,
+        FlexiLoggerError,
+    },
     std::{
         collections::HashMap,
         sync::{
-            mpsc::{channel, Receiver, Sender},
-            Arc,
+            mpsc::{channel, RecvTimeoutError, Sender},
+            Arc, Mutex,
         },
-        thread::Builder as ThreadBuilder,
+        thread::{Builder as ThreadBuilder, JoinHandle},
+        time::Duration,
     },
 };
 
 #[cfg(feature = "async")]
 use {
+    crate::writers::AsyncMessage,
     crate::{
         primary_writer::std_stream::StdStream,
-        util::{eprint_err, ErrorCode, ASYNC_FLUSH, ASYNC_SHUTDOWN},
+        util::{
+            eprint_err, ErrorCode, FlushAcks, ASYNC_FLUSH, ASYNC_FLUSH_AND_ACK, ASYNC_SHUTDOWN,
+        },
     },
     crossbeam_channel::Receiver as CrossbeamReceiver,
     crossbeam_queue::ArrayQueue,
-    std::{sync::Mutex, thread::JoinHandle},
 };
 
 #[cfg(feature = "async")]
@@ -27,29 +33,77 @@ use std::io::Write;
 
 #[cfg(feature = "async")]
 const ASYNC_STD_WRITER: &str = "flexi_logger-async_std_writer";
+#[cfg(feature = "async")]
+const ASYNC_LOG_WRITER: &str = "flexi_logger-async_log_writer";
 const FLUSHER: &str = "flexi_logger-flusher";
 
-// Used in Logger
-pub(crate) fn start_flusher_thread(
-    primary_writer: Arc<PrimaryWriter>,
-    other_writers: Arc<HashMap<String, Box<dyn LogWriter>>>,
-    flush_interval: std::time::Duration,
-) -> Result<(), FlexiLoggerError> {
-    let builder = ThreadBuilder::new().name(FLUSHER.to_string());
+// Handle to a flusher thread, allows stopping it.
+pub(crate) struct FlusherHandle {
+    mo_stop_sender: Mutex<Option<Sender<()>>>,
+    mo_join_handle: Mutex<Option<JoinHandle<()>>>,
+}
+impl FlusherHandle {
+    // Stops the flusher thread and waits for its termination.
+    pub(crate) fn stop(&self) {
+        if let Ok(mut o_stop_sender) = self.mo_stop_sender.lock() {
+            // dropping the sender disconnects the channel, which ends the thread's loop
+            o_stop_sender.take();
+        }
+        if let Ok(mut o_join_handle) = self.mo_join_handle.lock() {
+            if let Some(join_handle) = o_join_handle.take() {
+                if join_handle.thread().id() != std::thread::current().id() {
+                    join_handle.join().ok();
+                }
+            }
+        }
+    }
+}
+impl std::fmt::Debug for FlusherHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> Result<(), std::fmt::Error> {
+        f.debug_struct("FlusherHandle").finish_non_exhaustive()
+    }
+}
+
+// Spawns a thread that calls the given flush function in the given interval,
+// until the returned handle is stopped.
+pub(crate) fn spawn_flusher<F>(
+    name: &'static str,
+    flush_interval: Duration,
+    mut flush: F,
+) -> Result<FlusherHandle, std::io::Error>
+where
+    F: FnMut() + Send + 'static,
+{
+    let builder = ThreadBuilder::new().name(name.to_string());
     #[cfg(not(feature = "dont_minimize_extra_stacks"))]
     let builder = builder.stack_size(1024);
 
-    builder.spawn(move || {
-        let (_sender, receiver): (Sender<()>, Receiver<()>) = channel();
-        loop {
-            receiver.recv_timeout(flush_interval).ok();
-            primary_writer.flush().ok();
-            for w in other_writers.values() {
-                w.flush().ok();
-            }
+    let (stop_sender, stop_receiver) = channel::<()>();
+    let registration = ThreadRegistration::new(name);
+    let join_handle = builder.spawn(move || {
+        let _registration = registration;
+        while let Err(RecvTimeoutError::Timeout) = stop_receiver.recv_timeout(flush_interval) {
+            flush();
         }
     })?;
-    Ok(())
+    Ok(FlusherHandle {
+        mo_stop_sender: Mutex::new(Some(stop_sender)),
+        mo_join_handle: Mutex::new(Some(join_handle)),
+    })
+}
+
+// Used in Logger
+pub(crate) fn start_flusher_thread(
+    primary_writer: Arc<PrimaryWriter>,
+    other_writers: Arc<HashMap<String, Box<dyn LogWriter>>>,
+    flush_interval: Duration,
+) -> Result<FlusherHandle, FlexiLoggerError> {
+    Ok(spawn_flusher(FLUSHER, flush_interval, move || {
+        primary_writer.flush().ok();
+        for w in other_writers.values() {
+            w.flush().ok();
+        }
+    })?)
 }
 
 #[cfg(feature = "async")]
@@ -58,14 +112,17 @@ pub(crate) fn start_async_stdwriter(
     receiver: CrossbeamReceiver<std::vec::Vec<u8>>,
     t_pool: Arc<ArrayQueue<Vec<u8>>>,
     msg_capa: usize,
+    a_flush_acks: Arc<FlushAcks>,
     #[cfg(test)] t_validation_buffer: Arc<Mutex<std::io::Cursor<Vec<u8>>>>,
 ) -> Mutex<Option<JoinHandle<()>>> {
+    let registration = ThreadRegistration::new(ASYNC_STD_WRITER);
     Mutex::new(Some(
         ThreadBuilder::new()
             .name(
                 ASYNC_STD_WRITER.to_string()
             )
             .spawn(move || {
+                let _registration = registration;
                 loop {
                     match receiver.recv() {
                         Err(_) => break,
@@ -79,6 +136,15 @@ pub(crate) fn start_async_stdwriter(
                                             |e| eprint_err(ErrorCode::Flush, "flushing failed", &e)
                                         );
                                 }
+                                ASYNC_FLUSH_AND_ACK => {
+                                    std_stream
+                                        .deref_mut()
+                                        .flush()
+                                        .unwrap_or_else(
+                                            |e| eprint_err(ErrorCode::Flush, "flushing failed", &e)
+                                        );
+                                    a_flush_acks.acknowledge();
+                                }
                                 ASYNC_SHUTDOWN => {
                                     break;
                                 }
@@ -106,3 +172,71 @@ pub(crate) fn start_async_stdwriter(
             .unwrap(/* yes, let's panic if the thread can't be spawned */),
     ))
 }
+
+// Used in AsyncLogWriter
+#[cfg(feature = "async")]
+pub(crate) fn start_async_log_writer<W: LogWriter + 'static>(
+    a_writer: Arc<W>,
+    receiver: CrossbeamReceiver<AsyncMessage>,
+    a_pool: Arc<ArrayQueue<Vec<u8>>>,
+    message_capa: usize,
+    a_flush_acks: Arc<FlushAcks>,
+) -> Mutex<Option<JoinHandle<()>>> {
+    let registration = ThreadRegistration::new(ASYNC_LOG_WRITER);
+    Mutex::new(Some(
+        ThreadBuilder::new()
+            .name(ASYNC_LOG_WRITER.to_string())
+            .spawn(move || {
+                let _registration = registration;
+                let mut batch = Vec::<Vec<u8>>::with_capacity(a_pool.capacity());
+                while let Ok(message) = receiver.recv() {
+                    // collect the lines that are already queued, up to the next control message
+                    let mut o_control = None;
+                    let mut o_message = Some(message);
+                    while let Some(message) = o_message.take() {
+                        match message {
+                            AsyncMessage::Line(line) => {
+                                batch.push(line);
+                                if batch.len() < a_pool.capacity() {
+                                    o_message = receiver.try_recv().ok();
+                                }
+                            }
+                            control => o_control = Some(control),
+                        }
+                    }
+
+                    if !batch.is_empty() {
+                        a_writer
+                            .write_batch(&mut batch.iter().map(Vec::as_slice))
+                            .unwrap_or_else(|e| eprint_err(ErrorCode::Write, "writing failed", &e));
+                        for mut line in batch.drain(..) {
+                            if line.capacity() <= message_capa {
+                                line.clear();
+                                a_pool.push(line).ok();
+                            }
+                        }
+                    }
+
+                    match o_control {
+                        None | Some(AsyncMessage::Line(_)) => {}
+                        Some(AsyncMessage::Flush) => {
+                            a_writer.flush().unwrap_or_else(|e| {
+                                eprint_err(ErrorCode::Flush, "flushing failed", &e);
+                            });
+                        }
+                        Some(AsyncMessage::FlushAndAck) => {
+                            a_writer.flush().unwrap_or_else(|e| {
+                                eprint_err(ErrorCode::Flush, "flushing failed", &e);
+                            });
+                            a_flush_acks.acknowledge();
+                        }
+                        Some(AsyncMessage::Shutdown) => {
+                            a_writer.shutdown();
+                            break;
+                        }
+                    }
+                }
+            })
+            .unwrap(/* yes, let's panic if the thread can't be spawned */),
+    ))
+}
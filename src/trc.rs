@@ -62,16 +62,51 @@
 //! # Ok(())}}
 //! ```
 //!
+//! ### Composing with other layers
+//!
+//! If your program composes its own `tracing_subscriber::Registry`, e.g. to also send
+//! the traces to OpenTelemetry or to the console, use [`layer`] instead of [`setup_tracing`]:
+//!
+//! ```rust,ignore
+//! use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, Registry};
+//!
+//! // Drop the keep-alive-handles only in the shutdown of your program
+//! let (file_layer, _fw_handle, _spec_file_notifier) = flexi_logger::trc::layer(
+//!     LogSpecification::info(),
+//!     Some(&PathBuf::from("trcspecfile.toml")),
+//!     FileLogWriter::builder(FileSpec::default()),
+//! )?;
+//! Registry::default()
+//!     .with(file_layer)
+//!     .with(tracing_subscriber::fmt::layer())
+//!     .init();
+//! ```
+//!
+//! [`flexi_layer`] works similarly, but formats the `tracing` events with the format function
+//! of the `FileLogWriter`, optionally including the names and fields of the current spans.
+//! This way, file logs from `tracing` events and from `log` macros can share the same format.
+//!
 
 pub use crate::logger_handle::LogSpecSubscriber;
 use crate::{
     logger::{create_specfile_watcher, synchronize_subscriber_with_specfile},
-    writers::{FileLogWriterBuilder, FileLogWriterHandle},
+    util::{eprint_err, ErrorCode},
+    writers::{ArcFileLogWriter, FileLogWriterBuilder, FileLogWriterHandle, LogWriter},
+    DeferredNow,
 };
 use crate::{FlexiLoggerError, LogSpecification};
 use notify_debouncer_mini::{notify::RecommendedWatcher, Debouncer};
-use std::path::{Path, PathBuf};
-use tracing_subscriber::{EnvFilter, FmtSubscriber};
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+use tracing::{
+    field::{Field, Visit},
+    span, Event, Subscriber,
+};
+use tracing_subscriber::{
+    fmt, layer::Context, registry::LookupSpan, reload, EnvFilter, FmtSubscriber, Layer,
+};
 
 /// Allows registering a `LogSpecSubscriber` to a specfile.
 ///
@@ -106,7 +141,7 @@ pub fn subscribe_to_specfile<P: AsRef<Path>>(
 #[cfg(feature = "specfile_without_notification")]
 struct TraceLogSpecSubscriber {
     initial_logspec: LogSpecification,
-    update: Box<(dyn Fn(LogSpecification) + Send + Sync)>,
+    update: Box<dyn Fn(LogSpecification) + Send + Sync>,
 }
 impl TraceLogSpecSubscriber {
     /// Factory method.
@@ -117,7 +152,7 @@ impl TraceLogSpecSubscriber {
     /// update: Closure that implements the update of the log specification to some consumer
     #[must_use]
     pub fn new(
-        update: Box<(dyn Fn(LogSpecification) + Send + Sync)>,
+        update: Box<dyn Fn(LogSpecification) + Send + Sync>,
         initial_logspec: LogSpecification,
     ) -> Self {
         Self {
@@ -167,30 +202,226 @@ pub fn setup_tracing(
         .with_filter_reloading();
 
     // Set up specfile watching
-    let spec_file_notifier = SpecFileNotifier {
-        _watcher: match o_specfile {
-            Some(specfile) => {
-                let reload_handle = Box::new(subscriber_builder.reload_handle());
-                subscribe_to_specfile(
-                    specfile,
-                    Box::new(move |logspec| {
-                        { reload_handle.reload(LogSpecAsFilter(logspec)) }.unwrap(/* OK */);
-                    }),
-                    initial_logspec,
-                )?
-            }
-            None => None,
-        },
-    };
+    let reload_handle = subscriber_builder.reload_handle();
+    let spec_file_notifier = spec_file_notifier(initial_logspec, o_specfile, move |logspec| {
+        { reload_handle.reload(LogSpecAsFilter(logspec)) }.unwrap(/* OK */);
+    })?;
 
     // Get ready to trace
     tracing::subscriber::set_global_default(subscriber_builder.finish())?;
 
     Ok((fw_handle, spec_file_notifier))
 }
+
+/// Creates a `tracing_subscriber` [`Layer`] that writes into the specified `FileLogWriter`,
+/// filters with the given `LogSpecification`, and uses the (optionally) specified specfile.
+///
+/// Other than [`setup_tracing`], this method does not install a global subscriber,
+/// so the layer can be combined with other layers in a `tracing_subscriber::Registry`.
+/// The log specification is applied as a per-layer filter and does not affect the other layers.
+///
+/// The returned handles must be kept alive and should be dropped at the very end of the program.
+///
+/// # Errors
+///
+/// Various variants of `FlexiLoggerError` can occur.
+pub fn layer<S>(
+    initial_logspec: LogSpecification,
+    o_specfile: Option<&PathBuf>,
+    flwb: FileLogWriterBuilder,
+) -> Result<
+    (
+        impl Layer<S> + Send + Sync + 'static,
+        FileLogWriterHandle,
+        SpecFileNotifier,
+    ),
+    FlexiLoggerError,
+>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let (file_writer, fw_handle) = flwb.try_build_with_handle()?;
+    let (filter, spec_file_notifier) = reloadable_filter(initial_logspec, o_specfile)?;
+    let layer = fmt::layer()
+        .with_writer(move || file_writer.clone())
+        .with_ansi(false)
+        .with_filter(filter);
+    Ok((layer, fw_handle, spec_file_notifier))
+}
+
+/// Creates a `tracing_subscriber` [`Layer`] that formats the `tracing` events with the
+/// format function of the specified `FileLogWriter` and writes them into it.
+///
+/// Each event is converted into a [`log::Record`] with the level, target, module path,
+/// file, and line of the event. The message of the record consists of the message
+/// of the event, followed by its other fields, like `message field=value`.
+/// If `with_span_context` is true, the message is prefixed with the names and fields of the
+/// spans in which the event occurred, from the outermost to the innermost one,
+/// like `outer{a=1}:inner: message`.
+///
+/// Filtering and the specfile work as with [`layer`].
+///
+/// The returned handles must be kept alive and should be dropped at the very end of the program.
+///
+/// # Errors
+///
+/// Various variants of `FlexiLoggerError` can occur.
+pub fn flexi_layer<S>(
+    initial_logspec: LogSpecification,
+    o_specfile: Option<&PathBuf>,
+    flwb: FileLogWriterBuilder,
+    with_span_context: bool,
+) -> Result<
+    (
+        impl Layer<S> + Send + Sync + 'static,
+        FileLogWriterHandle,
+        SpecFileNotifier,
+    ),
+    FlexiLoggerError,
+>
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    let (file_writer, fw_handle) = flwb.try_build_with_handle()?;
+    let (filter, spec_file_notifier) = reloadable_filter(initial_logspec, o_specfile)?;
+    let layer = FlexiLayer {
+        file_writer,
+        with_span_context,
+    }
+    .with_filter(filter);
+    Ok((layer, fw_handle, spec_file_notifier))
+}
+
+// Converts tracing events into log records and hands them over to the FileLogWriter
+struct FlexiLayer {
+    file_writer: ArcFileLogWriter,
+    with_span_context: bool,
+}
+impl<S> Layer<S> for FlexiLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &span::Attributes<'_>, id: &span::Id, ctx: Context<'_, S>) {
+        if self.with_span_context {
+            if let Some(span) = ctx.span(id) {
+                let mut fields = SpanFields(String::new());
+                attrs.record(&mut FieldVisitor(&mut fields.0));
+                span.extensions_mut().insert(fields);
+            }
+        }
+    }
+
+    fn on_record(&self, id: &span::Id, values: &span::Record<'_>, ctx: Context<'_, S>) {
+        if let Some(span) = ctx.span(id) {
+            if let Some(fields) = span.extensions_mut().get_mut::<SpanFields>() {
+                values.record(&mut FieldVisitor(&mut fields.0));
+            }
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let mut message = String::new();
+        if self.with_span_context {
+            if let Some(scope) = ctx.event_scope(event) {
+                for span in scope.from_root() {
+                    message.push_str(span.name());
+                    if let Some(fields) = span.extensions().get::<SpanFields>() {
+                        if !fields.0.is_empty() {
+                            write!(message, "{{{}}}", fields.0).ok();
+                        }
+                    }
+                    message.push(':');
+                }
+                if !message.is_empty() {
+                    message.push(' ');
+                }
+            }
+        }
+        event.record(&mut FieldVisitor(&mut message));
+
+        let metadata = event.metadata();
+        self.file_writer
+            .write(
+                &mut DeferredNow::new(),
+                &log::Record::builder()
+                    .args(format_args!("{message}"))
+                    .level(log_level(*metadata.level()))
+                    .target(metadata.target())
+                    .module_path_static(metadata.module_path())
+                    .file_static(metadata.file())
+                    .line(metadata.line())
+                    .build(),
+            )
+            .unwrap_or_else(|e| eprint_err(ErrorCode::Write, "writing failed", &e));
+    }
+}
+
+// The rendered fields of a span, stored in the span's extensions
+struct SpanFields(String);
+
+// Renders the message field as is, and all other fields as `name=value`, separated by blanks
+struct FieldVisitor<'a>(&'a mut String);
+impl Visit for FieldVisitor<'_> {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if !self.0.is_empty() && !self.0.ends_with(' ') {
+            self.0.push(' ');
+        }
+        if field.name() == "message" {
+            write!(self.0, "{value:?}").ok();
+        } else {
+            write!(self.0, "{}={value:?}", field.name()).ok();
+        }
+    }
+}
+
+fn log_level(level: tracing::Level) -> log::Level {
+    match level {
+        tracing::Level::ERROR => log::Level::Error,
+        tracing::Level::WARN => log::Level::Warn,
+        tracing::Level::INFO => log::Level::Info,
+        tracing::Level::DEBUG => log::Level::Debug,
+        _ => log::Level::Trace,
+    }
+}
+
+// Creates a filter from the log specification that is updated when the specfile changes
+fn reloadable_filter<S>(
+    initial_logspec: LogSpecification,
+    o_specfile: Option<&PathBuf>,
+) -> Result<(reload::Layer<EnvFilter, S>, SpecFileNotifier), FlexiLoggerError>
+where
+    S: Subscriber,
+{
+    let (filter, reload_handle) =
+        reload::Layer::new(EnvFilter::from(LogSpecAsFilter(initial_logspec.clone())));
+    let spec_file_notifier = spec_file_notifier(initial_logspec, o_specfile, move |logspec| {
+        // fails only if the layer was dropped already
+        reload_handle
+            .reload(EnvFilter::from(LogSpecAsFilter(logspec)))
+            .ok();
+    })?;
+    Ok((filter, spec_file_notifier))
+}
+
+fn spec_file_notifier<F>(
+    initial_logspec: LogSpecification,
+    o_specfile: Option<&PathBuf>,
+    reload: F,
+) -> Result<SpecFileNotifier, FlexiLoggerError>
+where
+    F: Fn(LogSpecification) + Send + Sync + 'static,
+{
+    Ok(SpecFileNotifier {
+        _watcher: match o_specfile {
+            Some(specfile) => subscribe_to_specfile(specfile, Box::new(reload), initial_logspec)?,
+            None => None,
+        },
+    })
+}
 struct LogSpecAsFilter(pub LogSpecification);
 impl From<LogSpecAsFilter> for EnvFilter {
     fn from(wrapped_logspec: LogSpecAsFilter) -> Self {
-        Self::new(wrapped_logspec.0.to_string())
+        // EnvFilter does not accept blanks in its directives
+        Self::new(wrapped_logspec.0.to_string().replace(' ', ""))
     }
 }
@@ -1,26 +1,81 @@
 use crate::logger::ErrorChannel;
 use crate::{DeferredNow, FormatFunction};
 use log::Record;
+#[cfg(feature = "async")]
+use std::time::Duration;
 use std::{
     cell::RefCell,
+    ffi::OsString,
     io::Write,
-    path::Path,
-    sync::{OnceLock, RwLock},
+    path::{Path, PathBuf},
+    sync::{Mutex, OnceLock, RwLock},
 };
 
 #[cfg(test)]
 use std::io::Cursor;
 #[cfg(test)]
-use std::sync::{Arc, Mutex};
+use std::sync::Arc;
 
 #[cfg(feature = "async")]
 pub(crate) const ASYNC_FLUSH: &[u8] = b"F";
 #[cfg(feature = "async")]
 pub(crate) const ASYNC_SHUTDOWN: &[u8] = b"S";
+#[cfg(feature = "async")]
+pub(crate) const ASYNC_FLUSH_AND_ACK: &[u8] = b"A";
+
+// Lets threads wait until an asynchronous writer thread has processed their flush requests.
+#[cfg(feature = "async")]
+#[derive(Debug, Default)]
+pub(crate) struct FlushAcks {
+    // number of requested and of acknowledged flushes
+    m_counts: Mutex<(u64, u64)>,
+    condvar: std::sync::Condvar,
+}
+#[cfg(feature = "async")]
+impl FlushAcks {
+    // Sends a flush request with the given function and waits until it is acknowledged.
+    pub(crate) fn request_and_wait<F>(&self, send: F, timeout: Duration) -> std::io::Result<()>
+    where
+        F: FnOnce() -> std::io::Result<()>,
+    {
+        let deadline = std::time::Instant::now() + timeout;
+        let mut counts = self.m_counts.lock().map_err(|_| io_err("Poison"))?;
+        // sending while holding the lock keeps the requests in the order of their numbers
+        send()?;
+        counts.0 += 1;
+        let request = counts.0;
+        while counts.1 < request {
+            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+            if remaining.is_zero() {
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::TimedOut,
+                    "the asynchronous writer did not process the flush request in time",
+                ));
+            }
+            counts = self
+                .condvar
+                .wait_timeout(counts, remaining)
+                .map_err(|_| io_err("Poison"))?
+                .0;
+        }
+        Ok(())
+    }
+
+    // Is called by the writer thread after it has processed a flush request.
+    pub(crate) fn acknowledge(&self) {
+        if let Ok(mut counts) = self.m_counts.lock() {
+            counts.1 += 1;
+            self.condvar.notify_all();
+        }
+    }
+}
 
 #[derive(Copy, Clone, Debug)]
 pub(crate) enum ErrorCode {
+    Archive,
+    Cleanup,
     Write,
+    WriteResumed,
     Flush,
     Format,
     LogFile,
@@ -29,6 +84,7 @@ pub(crate) enum ErrorCode {
     #[cfg(feature = "colors")]
     Palette,
     Poison,
+    Recovery,
     #[cfg(target_family = "unix")]
     Symlink,
     WriterSpec,
@@ -36,7 +92,10 @@ pub(crate) enum ErrorCode {
 impl ErrorCode {
     fn as_index(self) -> &'static str {
         match self {
+            Self::Archive => "archive",
+            Self::Cleanup => "cleanup",
             Self::Write => "write",
+            Self::WriteResumed => "writeresumed",
             Self::Flush => "flush",
             Self::Format => "format",
             Self::LogFile => "logfile",
@@ -45,6 +104,7 @@ impl ErrorCode {
             #[cfg(feature = "colors")]
             Self::Palette => "palette",
             Self::Poison => "poison",
+            Self::Recovery => "recovery",
             #[cfg(target_family = "unix")]
             Self::Symlink => "symlink",
             Self::WriterSpec => "writerspec",
@@ -108,6 +168,16 @@ pub(crate) fn set_error_channel(channel: ErrorChannel) {
     }
 }
 
+pub(crate) fn get_error_channel() -> ErrorChannel {
+    match error_channel().read() {
+        Ok(guard) => guard.clone(),
+        Err(e) => {
+            eprint_err(ErrorCode::Poison, "Error channel cannot be read", &e);
+            ErrorChannel::default()
+        }
+    }
+}
+
 fn try_writing_to_error_channel(s: &str) {
     match &*(error_channel().read().unwrap()) {
         ErrorChannel::StdErr => {
@@ -127,7 +197,63 @@ fn try_writing_to_error_channel(s: &str) {
     }
 }
 
+// Rotation of the error file: maximal size in bytes, and number of rotated files to keep;
+// the mutex also serializes the writes to the error file
+static ERROR_FILE_ROTATION: Mutex<Option<(u64, usize)>> = Mutex::new(None);
+pub(crate) fn set_error_file_rotation(o_rotation: Option<(u64, usize)>) {
+    *ERROR_FILE_ROTATION
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner) = o_rotation;
+}
+
+// The error file and its rotated predecessors, if the error channel is a file
+pub(crate) fn existing_error_files() -> Vec<PathBuf> {
+    let ErrorChannel::File(path) = get_error_channel() else {
+        return Vec::new();
+    };
+    let keep = ERROR_FILE_ROTATION
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner)
+        .map_or(0, |(_, keep)| keep);
+    std::iter::once(path.clone())
+        .chain((1..=keep).map(|idx| rotated_error_file(&path, idx)))
+        .filter(|p| p.exists())
+        .collect()
+}
+
+fn rotated_error_file(path: &Path, idx: usize) -> PathBuf {
+    let mut s: OsString = path.as_os_str().to_owned();
+    s.push(format!(".{idx}"));
+    PathBuf::from(s)
+}
+
+// errors.log -> errors.log.1 -> errors.log.2 ... -> removed
+fn rotate_error_file(path: &Path, keep: usize) -> Result<(), std::io::Error> {
+    if keep == 0 {
+        return std::fs::remove_file(path);
+    }
+    let oldest = rotated_error_file(path, keep);
+    if oldest.exists() {
+        std::fs::remove_file(oldest)?;
+    }
+    for idx in (1..keep).rev() {
+        let from = rotated_error_file(path, idx);
+        if from.exists() {
+            std::fs::rename(from, rotated_error_file(path, idx + 1))?;
+        }
+    }
+    std::fs::rename(path, rotated_error_file(path, 1))
+}
+
 fn try_writing_to_file(s: &str, path: &Path) -> Result<(), std::io::Error> {
+    let rotation = ERROR_FILE_ROTATION
+        .lock()
+        .unwrap_or_else(std::sync::PoisonError::into_inner);
+    if let Some((max_size, keep)) = *rotation {
+        if std::fs::metadata(path).is_ok_and(|md| md.len() >= max_size) {
+            rotate_error_file(path, keep)?;
+        }
+    }
     let mut file = std::fs::OpenOptions::new()
         .create(true)
         .append(true)
@@ -141,19 +267,220 @@ pub(crate) fn io_err(s: &'static str) -> std::io::Error {
 }
 
 // Thread-local buffer
-pub(crate) fn buffer_with<F>(f: F)
+pub(crate) fn buffer_with<F, R>(f: F) -> R
 where
-    F: FnOnce(&RefCell<Vec<u8>>),
+    F: FnOnce(&RefCell<Vec<u8>>) -> R,
 {
     thread_local! {
         static BUFFER: RefCell<Vec<u8>> = RefCell::new(Vec::with_capacity(200));
     }
-    BUFFER.with(f);
+    BUFFER.with(f)
+}
+
+// The name of the host, as reported by the operating system
+#[cfg(not(target_family = "wasm"))]
+pub(crate) fn hostname() -> String {
+    hostname::get().map_or_else(
+        |_| String::from("unknown-host"),
+        |h| h.to_string_lossy().to_string(),
+    )
+}
+#[cfg(target_family = "wasm")]
+pub(crate) fn hostname() -> String {
+    String::from("unknown-host")
+}
+
+// Calls the given function with the log record that represents the banner
+pub(crate) fn with_banner_record<R>(banner: &str, f: impl FnOnce(&Record) -> R) -> R {
+    f(&Record::builder()
+        .args(format_args!("{banner}"))
+        .level(log::Level::Info)
+        .target("flexi_logger")
+        .module_path_static(Some("flexi_logger"))
+        .build())
+}
+
+// Optional processing of the formatted log lines of an output channel.
+#[derive(Clone, Copy, Debug, Default)]
+pub(crate) struct PostFormat {
+    // longer lines are cut off at a character boundary,
+    // and a marker with the number of cut-off bytes is appended
+    pub(crate) o_max_line_length: Option<usize>,
+    // control characters are replaced with escape sequences
+    pub(crate) escape_control_chars: bool,
+}
+
+// Formats the record and applies the post-format processing while writing into w.
+pub(crate) fn format_line(
+    format_function: FormatFunction,
+    post_format: PostFormat,
+    w: &mut dyn Write,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    match post_format.o_max_line_length {
+        None => format_escaped(
+            format_function,
+            post_format.escape_control_chars,
+            w,
+            now,
+            record,
+        ),
+        Some(max_line_length) => {
+            let mut truncating = Truncating {
+                w,
+                remaining: max_line_length,
+                cut_off: 0,
+                styled: false,
+                o_escape: None,
+            };
+            format_escaped(
+                format_function,
+                post_format.escape_control_chars,
+                &mut truncating,
+                now,
+                record,
+            )?;
+            let Truncating {
+                cut_off,
+                styled,
+                o_escape,
+                ..
+            } = truncating;
+            if cut_off > 0 {
+                // terminate a cut-off escape sequence, and reset the style,
+                // because the cut-off part may have contained the reset
+                match o_escape {
+                    Some(1) => w.write_all(b"[m")?,
+                    Some(_) => w.write_all(b"m")?,
+                    None => {}
+                }
+                if styled {
+                    w.write_all(ANSI_RESET)?;
+                }
+                write!(w, "… [truncated {cut_off} bytes]")?;
+            }
+            Ok(())
+        }
+    }
+}
+
+fn format_escaped(
+    format_function: FormatFunction,
+    escape_control_chars: bool,
+    w: &mut dyn Write,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> Result<(), std::io::Error> {
+    if escape_control_chars {
+        format_function(&mut Escaping(w), now, record)
+    } else {
+        format_function(w, now, record)
+    }
+}
+
+// Writes up to `remaining` bytes and counts the bytes that didn't fit.
+const ESC: u8 = 0x1b;
+const ANSI_RESET: &[u8] = b"\x1b[0m";
+
+struct Truncating<'a> {
+    w: &'a mut dyn Write,
+    remaining: usize,
+    cut_off: usize,
+    // an ANSI escape sequence was written
+    styled: bool,
+    // the number of bytes of an ANSI escape sequence that is not yet complete
+    o_escape: Option<usize>,
+}
+impl Truncating<'_> {
+    // Keeps track of the ANSI escape sequences, like `ESC [ 1 ; 31 m`,
+    // which end with a byte in 0x40..=0x7e after the `[`
+    fn track_escapes(&mut self, bytes: &[u8]) {
+        for b in bytes {
+            self.o_escape = match self.o_escape {
+                _ if *b == ESC => {
+                    self.styled = true;
+                    Some(1)
+                }
+                Some(1) => Some(2),
+                Some(_) if (0x40..=0x7e).contains(b) => None,
+                Some(n) => Some(n + 1),
+                None => None,
+            };
+        }
+    }
+}
+impl Write for Truncating<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        if self.cut_off == 0 && buf.len() <= self.remaining {
+            self.track_escapes(buf);
+            self.w.write_all(buf)?;
+            self.remaining -= buf.len();
+        } else {
+            let mut keep = if self.cut_off == 0 { self.remaining } else { 0 };
+            // don't split a multi-byte UTF-8 character
+            while keep > 0 && buf[keep] & 0xC0 == 0x80 {
+                keep -= 1;
+            }
+            self.track_escapes(&buf[..keep]);
+            self.w.write_all(&buf[..keep])?;
+            self.remaining = 0;
+            self.cut_off += buf.len() - keep;
+        }
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.w.flush()
+    }
+}
+
+// Replaces control characters (including the ESC of ANSI escape sequences) with escape
+// sequences like `\n` or `\u{1b}`, and bytes that are no valid UTF-8 with `\xNN`.
+struct Escaping<'a>(&'a mut dyn Write);
+impl Escaping<'_> {
+    fn write_str_escaped(&mut self, s: &str) -> std::io::Result<()> {
+        let mut start = 0;
+        for (idx, c) in s.char_indices() {
+            if c.is_control() {
+                self.0.write_all(&s.as_bytes()[start..idx])?;
+                write!(self.0, "{}", c.escape_default())?;
+                start = idx + c.len_utf8();
+            }
+        }
+        self.0.write_all(&s.as_bytes()[start..])
+    }
+}
+impl Write for Escaping<'_> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let mut rest = buf;
+        while !rest.is_empty() {
+            match std::str::from_utf8(rest) {
+                Ok(s) => {
+                    self.write_str_escaped(s)?;
+                    rest = &[];
+                }
+                Err(e) => {
+                    let (valid, invalid) = rest.split_at(e.valid_up_to());
+                    self.write_str_escaped(std::str::from_utf8(valid).unwrap_or_default())?;
+                    let invalid_len = e.error_len().unwrap_or(invalid.len());
+                    for byte in &invalid[..invalid_len] {
+                        write!(self.0, "\\x{byte:02x}")?;
+                    }
+                    rest = &invalid[invalid_len..];
+                }
+            }
+        }
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.0.flush()
+    }
 }
 
 // Use the thread-local buffer for formatting before writing into the given writer
 pub(crate) fn write_buffered(
     format_function: FormatFunction,
+    post_format: PostFormat,
     now: &mut DeferredNow,
     record: &Record,
     w: &mut dyn Write,
@@ -163,7 +490,7 @@ pub(crate) fn write_buffered(
 
     buffer_with(|tl_buf| match tl_buf.try_borrow_mut() {
         Ok(mut buffer) => {
-            (format_function)(&mut *buffer, now, record)
+            format_line(format_function, post_format, &mut *buffer, now, record)
                 .unwrap_or_else(|e| eprint_err(ErrorCode::Format, "formatting failed", &e));
             buffer
                 .write_all(b"\n")
@@ -186,7 +513,7 @@ pub(crate) fn write_buffered(
             // we print the inner calls, in chronological order, before finally the
             // outer most message is printed
             let mut tmp_buf = Vec::<u8>::with_capacity(200);
-            (format_function)(&mut tmp_buf, now, record)
+            format_line(format_function, post_format, &mut tmp_buf, now, record)
                 .unwrap_or_else(|e| eprint_err(ErrorCode::Format, "formatting failed", &e));
             tmp_buf
                 .write_all(b"\n")
@@ -205,3 +532,166 @@ pub(crate) fn write_buffered(
     });
     result
 }
+
+#[cfg(test)]
+mod test {
+    #[test]
+    fn test_format_line() {
+        use super::{format_line, PostFormat};
+        use crate::{DeferredNow, FormatFunction};
+
+        fn format(
+            w: &mut dyn std::io::Write,
+            _now: &mut DeferredNow,
+            record: &log::Record,
+        ) -> std::io::Result<()> {
+            write!(w, "{}", record.args())
+        }
+        fn format_binary(
+            w: &mut dyn std::io::Write,
+            _now: &mut DeferredNow,
+            _record: &log::Record,
+        ) -> std::io::Result<()> {
+            w.write_all(b"a\xffb\xc3")
+        }
+        let formatted = |format: FormatFunction, msg: &str, post_format: PostFormat| {
+            let mut buffer = Vec::new();
+            format_line(
+                format,
+                post_format,
+                &mut buffer,
+                &mut DeferredNow::new(),
+                &log::Record::builder().args(format_args!("{msg}")).build(),
+            )
+            .unwrap();
+            String::from_utf8(buffer).unwrap()
+        };
+        let truncated = |max_len| PostFormat {
+            o_max_line_length: Some(max_len),
+            escape_control_chars: false,
+        };
+        let escaped = PostFormat {
+            o_max_line_length: None,
+            escape_control_chars: true,
+        };
+
+        assert_eq!(
+            formatted(format, "0123456789", PostFormat::default()),
+            "0123456789"
+        );
+        assert_eq!(formatted(format, "0123456789", truncated(10)), "0123456789");
+        assert_eq!(
+            formatted(format, "0123456789", truncated(4)),
+            "0123… [truncated 6 bytes]"
+        );
+        // 'ä' has two bytes and is not split
+        assert_eq!(
+            formatted(format, "012ä456789", truncated(4)),
+            "012… [truncated 8 bytes]"
+        );
+
+        assert_eq!(
+            formatted(format, "a\nfake line\r\t\u{1b}[31mred\u{9b}ä", escaped),
+            "a\\nfake line\\r\\t\\u{1b}[31mred\\u{9b}ä"
+        );
+        assert_eq!(formatted(format_binary, "", escaped), "a\\xffb\\xc3");
+        // the limit applies to the escaped line
+        assert_eq!(
+            formatted(
+                format,
+                "\n\n\n",
+                PostFormat {
+                    o_max_line_length: Some(4),
+                    escape_control_chars: true,
+                }
+            ),
+            "\\n\\n… [truncated 2 bytes]"
+        );
+    }
+
+    #[cfg(feature = "colors")]
+    #[test]
+    fn test_truncate_styled_line() {
+        use super::{format_line, PostFormat};
+        use crate::DeferredNow;
+
+        let formatted = |msg: &str, max_len| {
+            let mut buffer = Vec::new();
+            format_line(
+                crate::colored_default_format,
+                PostFormat {
+                    o_max_line_length: Some(max_len),
+                    escape_control_chars: false,
+                },
+                &mut buffer,
+                &mut DeferredNow::new(),
+                &log::Record::builder()
+                    .level(log::Level::Error)
+                    .module_path(Some("m"))
+                    .args(format_args!("{msg}"))
+                    .build(),
+            )
+            .unwrap();
+            String::from_utf8(buffer).unwrap()
+        };
+
+        let full = formatted("short", 1000);
+        assert!(
+            full.contains('\u{1b}') && full.ends_with("\u{1b}[0m"),
+            "{full:?}"
+        );
+
+        // the line is cut off within the style, so the reset is added
+        let truncated = formatted(&"x".repeat(100), 20);
+        assert!(
+            truncated.contains("\u{1b}[0m… [truncated "),
+            "{truncated:?}"
+        );
+
+        // a cut-off escape sequence is terminated
+        for max_len in 0..full.len() {
+            let truncated = formatted("short", max_len);
+            let kept = &truncated[..truncated.find('…').unwrap()];
+            for sequence in kept.split('\u{1b}').skip(1) {
+                assert!(
+                    sequence.starts_with('[') && sequence[1..].contains('m'),
+                    "{max_len}: {truncated:?}"
+                );
+            }
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_flush_acks() {
+        use super::FlushAcks;
+        use std::{sync::Arc, time::Duration};
+
+        let a_flush_acks = Arc::new(FlushAcks::default());
+
+        // a request that is not acknowledged times out
+        let error = a_flush_acks
+            .request_and_wait(|| Ok(()), Duration::from_millis(10))
+            .unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::TimedOut);
+
+        // the writer thread acknowledges the second request, and the late first one
+        let (sender, receiver) = std::sync::mpsc::channel::<()>();
+        let acks = Arc::clone(&a_flush_acks);
+        let writer = std::thread::spawn(move || {
+            for () in receiver {
+                std::thread::sleep(Duration::from_millis(50));
+                acks.acknowledge();
+                acks.acknowledge();
+            }
+        });
+        a_flush_acks
+            .request_and_wait(
+                || sender.send(()).map_err(|_| super::io_err("Send")),
+                Duration::from_secs(10),
+            )
+            .unwrap();
+        drop(sender);
+        writer.join().unwrap();
+    }
+}
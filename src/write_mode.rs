@@ -101,6 +101,9 @@ pub enum WriteMode {
     /// Uses buffered output to reduce overhead, and a bounded message pool to reduce allocations.
     /// The log output is flushed regularly with the given interval.
     ///
+    /// Output to stderr or stdout, also the duplication of file output, is written by
+    /// separate output threads, so that a slow terminal or pipe does not block the program.
+    ///
     /// See [here](code_examples/index.html#choose-the-write-mode) for an example.
     #[cfg_attr(docsrs, doc(cfg(feature = "async")))]
     #[cfg(feature = "async")]
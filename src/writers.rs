@@ -1,7 +1,8 @@
 //! Describes how to extend `flexi_logger` with additional log writers.
 //!
-//! The module also contains two ready-to-use log writers,
-//! one for writing to files ([`FileLogWriter`]), one for writing to the syslog ([`SyslogWriter`]).
+//! The module also contains ready-to-use log writers
+//! for writing to files ([`FileLogWriter`]), to the syslog ([`SyslogWriter`]),
+//! and to the stdin of a child process ([`StdSinkWriter`]).
 //!
 //! Log writers can be used in two ways:
 //!
@@ -36,13 +37,26 @@
 //!   The message of a log call with a _target value_ that has the form `{Name1,Name2,...}`, i.e.,
 //!   a comma-separated list of _target names_, within braces, is not sent to the default output
 //!   channel, but to the loggers specified explicitly in the list. In such a list
-//!   you can also specify the default output channel with the built-in target name `_Default`.
+//!   you can also specify the default output channel with the built-in target name `_Default`
+//!   ([`DEFAULT_TARGET_NAME`]), or the default output channel and all registered writers
+//!   with the built-in target name `_All` ([`ALL_TARGET_NAME`]).
+//!
+//!   The writers see the module path as target of such log calls, unless you append
+//!   the target they should see to the list, like in `{Alert,_Default}audit`.
+//!   The default output channel applies its log specification to this target then.
+//!
+//!   A target that names none of the registered writers is treated as a normal target.
+//!   With [`Logger::writer_target_syntax`](crate::Logger::writer_target_syntax) you can use
+//!   other characters than the braces, or switch this special interpretation of targets off.
 //!
 //!   Log calls that are directed to an additional output channel will not be affected by
 //!   the value of `flexi_logger`'s log specification;
 //!   they will always be handed over to the respective `LogWriter`,
 //!   as you might want it for alerts or auditing.
 //!
+//!   The macro [`flexi_log_to!`](crate::flexi_log_to) facilitates such calls
+//!   by building the target from the given target names.
+//!
 //!   In the following example we define an alert writer, and a macro to facilitate using it
 //!   (and avoid using the explicit target specification in the macro call), and
 //!   show some example calls.
@@ -72,7 +86,7 @@
 //!       #[macro_export]
 //!       macro_rules! alert_error {
 //!           ($($arg:tt)*) => (
-//!               error!(target: "{Alert,_Default}", $($arg)*);
+//!               flexi_logger::flexi_log_to!([Alert, _Default], error!, $($arg)*);
 //!           )
 //!       }
 //!   }
@@ -91,6 +105,7 @@
 //!       // Explicitly send logs to different loggers
 //!       error!(target : "{Alert}", "This is only an alert");
 //!       error!(target : "{Alert,_Default}", "This is an alert and log message");
+//!       error!(target : "{_All}", "This goes to all writers");
 //!
 //!       // Nicer: use the explicit macro
 //!       alert_error!("This is another alert and log message");
@@ -105,14 +120,80 @@
 //!
 //!   ```
 //!
+//! Own implementations of [`LogWriter`] can avoid allocating a buffer for each record:
+//! they can format into the thread-local buffer that [`buffer_with`] provides,
+//! or they can implement [`LogWriter::formatted_with`] and [`LogWriter::write_formatted`]
+//! to receive the log lines already formatted.
+//!
+//! The other way round, [`LineLogger`] is a [`std::io::Write`] that logs the lines
+//! written to it, e.g. the output of a child process;
+//! [`ChildOutputLogger`] spawns child processes with their output being logged this way.
+//!
 
+mod arc_log_writer;
+#[cfg(feature = "async")]
+mod async_log_writer;
+mod breaker_writer;
+mod buffer;
+mod child_output;
+// On wasm, the file log writer is not offered, because there is no file system;
+// its builder still carries the settings that the logger shares with the other outputs.
+#[cfg_attr(target_family = "wasm", allow(dead_code, unused_imports))]
 pub(crate) mod file_log_writer;
+mod line_logger;
 mod log_writer;
+mod std_sink_writer;
 
 #[cfg(feature = "syslog_writer")]
 #[cfg_attr(docsrs, doc(cfg(feature = "syslog_writer")))]
 mod syslog;
 
+/// Built-in target name that addresses the default output channel,
+/// like in `{Alert,_Default}`.
+pub const DEFAULT_TARGET_NAME: &str = "_Default";
+
+/// Built-in target name that addresses the default output channel
+/// and all writers that were registered with [`Logger::add_writer`](crate::Logger::add_writer),
+/// like in `{_All}`.
+///
+/// Each writer only gets the records up to its [`LogWriter::max_log_level`].
+pub const ALL_TARGET_NAME: &str = "_All";
+
+/// Logs a message to the writers with the given target names, see module
+/// [`writers`](crate::writers).
+///
+/// The first argument is a target name, or a list of target names in brackets,
+/// the second one is the log macro, followed by its usual arguments.
+/// The built-in target names `_Default` and `_All` can be used as well.
+///
+/// ```rust
+/// use flexi_logger::flexi_log_to;
+///
+/// // like error!(target: "{Alert}", "Disk {} is full", "/data")
+/// flexi_log_to!(Alert, error!, "Disk {} is full", "/data");
+/// // like warn!(target: "{Alert,_Default}", "Disk {} is almost full", "/data")
+/// flexi_log_to!([Alert, _Default], warn!, "Disk {} is almost full", "/data");
+/// flexi_log_to!(_All, info!, "Shutting down");
+/// ```
+///
+/// The target is built with the default syntax, so the macro can't be used
+/// together with [`Logger::writer_target_syntax`](crate::Logger::writer_target_syntax).
+#[macro_export]
+macro_rules! flexi_log_to {
+    ([$first:ident $(, $rest:ident)* $(,)?], $mac:ident!, $($arg:tt)+) => {
+        $crate::__log::$mac!(
+            target: concat!("{", stringify!($first), $(",", stringify!($rest),)* "}"),
+            $($arg)+
+        )
+    };
+    ($name:ident, $mac:ident!, $($arg:tt)+) => {
+        $crate::flexi_log_to!([$name], $mac!, $($arg)+)
+    };
+}
+
+#[cfg(all(feature = "syslog_writer", feature = "kv"))]
+#[cfg_attr(docsrs, doc(cfg(all(feature = "syslog_writer", feature = "kv"))))]
+pub use self::syslog::DEFAULT_SD_ID;
 #[cfg(feature = "syslog_writer")]
 #[cfg_attr(docsrs, doc(cfg(feature = "syslog_writer")))]
 pub use self::syslog::{
@@ -120,7 +201,21 @@ pub use self::syslog::{
     SyslogFacility, SyslogLineHeader, SyslogSeverity, SyslogWriter, SyslogWriterBuilder,
 };
 
+pub use self::arc_log_writer::{ArcLogWriter, LogWriterHandle};
+#[cfg(feature = "async")]
+#[cfg_attr(docsrs, doc(cfg(feature = "async")))]
+pub use self::async_log_writer::AsyncLogWriter;
+#[cfg(feature = "async")]
+pub(crate) use self::async_log_writer::AsyncMessage;
+pub(crate) use self::breaker_writer::BreakerWriter;
+pub use self::buffer::buffer_with;
+pub use self::child_output::{ChildOutputLogger, LoggedChild};
+#[cfg(not(target_family = "wasm"))]
 pub use self::file_log_writer::{
-    ArcFileLogWriter, FileLogWriter, FileLogWriterBuilder, FileLogWriterConfig, FileLogWriterHandle,
+    ArcFileLogWriter, FileLogWriter, FileLogWriterBuilder, FileLogWriterConfig,
+    FileLogWriterHandle, RotationEvent,
 };
+pub use self::line_logger::LineLogger;
+pub(crate) use self::log_writer::dispatch;
 pub use self::log_writer::LogWriter;
+pub use self::std_sink_writer::{StdSinkWriter, StdSinkWriterBuilder};
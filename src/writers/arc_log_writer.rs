@@ -0,0 +1,217 @@
+use crate::{
+    writers::{dispatch, LogWriter},
+    DeferredNow, FlexiLoggerError, FormatFunction,
+};
+use log::Record;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc, RwLock,
+};
+
+/// A shareable [`LogWriter`] with a handle.
+///
+/// Allows using the same writer instance, e.g. the same syslog connection,
+/// from `flexi_logger` and from other consumers, like a `tracing` subscriber.
+/// Clones of an `ArcLogWriter` refer to the same writer, and can be registered with
+/// [`Logger::add_writer`](crate::Logger::add_writer) or
+/// [`Logger::log_to_writer`](crate::Logger::log_to_writer).
+///
+/// The writer is shut down only once, either explicitly with [`LogWriter::shutdown`]
+/// (which `flexi_logger` calls when its [`LoggerHandle`](crate::LoggerHandle) is shut down),
+/// or when the [`LogWriterHandle`] is dropped, whatever happens first.
+///
+/// A format function that is set with [`LogWriter::format`], e.g. by
+/// [`Logger::format_for_writer`](crate::Logger::format_for_writer), applies to all clones.
+/// It is only used if the wrapped writer lets `flexi_logger` format its log lines,
+/// see [`LogWriter::formatted_with`].
+///
+/// ```rust
+/// # use flexi_logger::{writers::{ArcLogWriter, FileLogWriter}, FileSpec, Logger};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let (arc_writer, _writer_handle) = ArcLogWriter::new_with_handle(
+///     FileLogWriter::builder(FileSpec::default().directory("log_files/arc_log_writer"))
+///         .try_build()?,
+/// );
+/// let _logger_handle = Logger::try_with_str("info")?
+///     .add_writer("shared", Box::new(arc_writer.clone()))
+///     .start()?;
+/// // hand out further clones of arc_writer to other consumers
+/// # Ok(())
+/// # }
+/// ```
+pub struct ArcLogWriter<W: LogWriter>(Arc<Shared<W>>);
+
+struct Shared<W: LogWriter> {
+    writer: W,
+    // replaces the format function of the writer; the writer itself can't be changed
+    // while it is shared
+    o_format: RwLock<Option<FormatFunction>>,
+    is_shut_down: AtomicBool,
+}
+impl<W: LogWriter> Shared<W> {
+    fn shutdown(&self) {
+        if !self.is_shut_down.swap(true, Ordering::SeqCst) {
+            self.writer.shutdown();
+        }
+    }
+
+    fn o_format(&self) -> Option<FormatFunction> {
+        self.writer.formatted_with()?;
+        *self
+            .o_format
+            .read()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl<W: LogWriter> ArcLogWriter<W> {
+    /// Wraps the given writer and returns a shareable instance and a handle to it.
+    ///
+    /// **Keep the [`LogWriterHandle`] alive** as long as the writer is to be used.
+    #[must_use]
+    pub fn new_with_handle(writer: W) -> (Self, LogWriterHandle<W>) {
+        let a_shared = Arc::new(Shared {
+            writer,
+            o_format: RwLock::new(None),
+            is_shut_down: AtomicBool::new(false),
+        });
+        (Self(Arc::clone(&a_shared)), LogWriterHandle(a_shared))
+    }
+}
+impl<W: LogWriter> std::ops::Deref for ArcLogWriter<W> {
+    type Target = W;
+    fn deref(&self) -> &W {
+        &self.0.writer
+    }
+}
+impl<W: LogWriter> Clone for ArcLogWriter<W> {
+    fn clone(&self) -> Self {
+        Self(Arc::clone(&self.0))
+    }
+}
+impl<W: LogWriter> LogWriter for ArcLogWriter<W> {
+    fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        if self.0.o_format().is_some() {
+            dispatch(self, now, record)
+        } else {
+            self.0.writer.write(now, record)
+        }
+    }
+
+    fn formatted_with(&self) -> Option<FormatFunction> {
+        self.0.o_format().or_else(|| self.0.writer.formatted_with())
+    }
+
+    fn max_line_length(&self) -> Option<usize> {
+        self.0.writer.max_line_length()
+    }
+
+    fn escape_control_chars(&self) -> bool {
+        self.0.writer.escape_control_chars()
+    }
+
+    fn write_formatted(&self, line: &[u8]) -> std::io::Result<()> {
+        self.0.writer.write_formatted(line)
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        self.0.writer.flush()
+    }
+
+    fn flush_and_wait(&self, timeout: std::time::Duration) -> std::io::Result<()> {
+        self.0.writer.flush_and_wait(timeout)
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.0.writer.max_log_level()
+    }
+
+    fn format(&mut self, format: FormatFunction) {
+        *self
+            .0
+            .o_format
+            .write()
+            .unwrap_or_else(std::sync::PoisonError::into_inner) = Some(format);
+    }
+
+    fn shutdown(&self) {
+        self.0.shutdown();
+    }
+
+    fn reopen_output(&self) -> Result<(), FlexiLoggerError> {
+        self.0.writer.reopen_output()
+    }
+
+    fn rotate(&self) -> Result<(), FlexiLoggerError> {
+        self.0.writer.rotate()
+    }
+
+    fn rotate_to(&self, infix: &str) -> Result<(), FlexiLoggerError> {
+        self.0.writer.rotate_to(infix)
+    }
+
+    fn trigger_cleanup(&self) -> Result<(), FlexiLoggerError> {
+        self.0.writer.trigger_cleanup()
+    }
+
+    fn name(&self) -> &str {
+        self.0.writer.name()
+    }
+
+    fn healthcheck(&self) -> Result<(), FlexiLoggerError> {
+        self.0.writer.healthcheck()
+    }
+
+    fn validate_logs(&self, expected: &[(&'static str, &'static str, &'static str)]) {
+        self.0.writer.validate_logs(expected);
+    }
+}
+
+/// Handle to the writer in an [`ArcLogWriter`]
+/// that shuts down the writer in its `Drop` implementation, if not yet done.
+pub struct LogWriterHandle<W: LogWriter>(Arc<Shared<W>>);
+impl<W: LogWriter> Drop for LogWriterHandle<W> {
+    fn drop(&mut self) {
+        self.0.shutdown();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::ArcLogWriter;
+    use crate::{writers::LogWriter, DeferredNow};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[derive(Default)]
+    struct CountingWriter {
+        writes: AtomicUsize,
+        shutdowns: AtomicUsize,
+    }
+    impl LogWriter for CountingWriter {
+        fn write(&self, _now: &mut DeferredNow, _record: &log::Record) -> std::io::Result<()> {
+            self.writes.fetch_add(1, Ordering::SeqCst);
+            Ok(())
+        }
+        fn flush(&self) -> std::io::Result<()> {
+            Ok(())
+        }
+        fn shutdown(&self) {
+            self.shutdowns.fetch_add(1, Ordering::SeqCst);
+        }
+    }
+
+    #[test]
+    fn test_shutdown_once() {
+        let (arc_writer, handle) = ArcLogWriter::new_with_handle(CountingWriter::default());
+        let clone = arc_writer.clone();
+        let record = log::Record::builder().args(format_args!("test")).build();
+        arc_writer.write(&mut DeferredNow::new(), &record).unwrap();
+        clone.write(&mut DeferredNow::new(), &record).unwrap();
+        assert_eq!(arc_writer.writes.load(Ordering::SeqCst), 2);
+
+        clone.shutdown();
+        arc_writer.shutdown();
+        drop(handle);
+        assert_eq!(arc_writer.shutdowns.load(Ordering::SeqCst), 1);
+    }
+}
@@ -0,0 +1,192 @@
+use crate::{
+    default_format,
+    threads::start_async_log_writer,
+    util::{format_line, io_err, FlushAcks},
+    writers::{buffer_with, log_writer::post_format_of, LogWriter},
+    DeferredNow, FlexiLoggerError, FormatFunction, DEFAULT_MESSAGE_CAPA, DEFAULT_POOL_CAPA,
+};
+use crossbeam_channel::Sender;
+use crossbeam_queue::ArrayQueue;
+use log::Record;
+use std::{
+    sync::{Arc, Mutex},
+    thread::JoinHandle,
+};
+
+/// A [`LogWriter`] that hands the log lines over to a background thread,
+/// which passes them in batches to the wrapped writer.
+///
+/// The wrapped writer should accept formatted log lines, i.e., it should implement
+/// [`LogWriter::write_formatted`] or [`LogWriter::write_batch`],
+/// like the [`StdSinkWriter`](crate::writers::StdSinkWriter) does.
+/// The log lines are formatted in the logging thread, with the format function that
+/// the wrapped writer provides with [`LogWriter::formatted_with`]
+/// (or with [`default_format`], if it provides none).
+/// A writer that only implements [`LogWriter::write`] gets each formatted line
+/// as the message of a record, see [`LogWriter::write_formatted`].
+///
+/// The background thread waits for log lines, and then hands all log lines
+/// that are queued at that time (but not more than the pool capacity)
+/// with a single call of [`LogWriter::write_batch`] to the wrapped writer.
+/// Writers with a high cost per write, like network writers, can thus reduce
+/// the number of requests under load.
+///
+/// When the `AsyncLogWriter` is shut down or dropped, the queued log lines are written,
+/// and the wrapped writer is shut down.
+///
+/// ```rust,no_run
+/// # use flexi_logger::{writers::{AsyncLogWriter, StdSinkWriter}, Logger};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let async_writer = AsyncLogWriter::new(StdSinkWriter::builder("my_log_shipper").try_build()?);
+/// let _logger_handle = Logger::try_with_str("info")?
+///     .add_writer("shipped", Box::new(async_writer))
+///     .start()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct AsyncLogWriter<W: LogWriter + 'static> {
+    a_writer: Arc<W>,
+    format: FormatFunction,
+    sender: Sender<AsyncMessage>,
+    mo_thread_handle: Mutex<Option<JoinHandle<()>>>,
+    a_pool: Arc<ArrayQueue<Vec<u8>>>,
+    message_capa: usize,
+    a_flush_acks: Arc<FlushAcks>,
+}
+
+// The messages to the background thread of an AsyncLogWriter.
+pub(crate) enum AsyncMessage {
+    Line(Vec<u8>),
+    Flush,
+    FlushAndAck,
+    Shutdown,
+}
+
+impl<W: LogWriter + 'static> AsyncLogWriter<W> {
+    /// Wraps the given writer, with [`DEFAULT_POOL_CAPA`] and [`DEFAULT_MESSAGE_CAPA`].
+    #[must_use]
+    pub fn new(writer: W) -> Self {
+        Self::with_capacities(writer, DEFAULT_POOL_CAPA, DEFAULT_MESSAGE_CAPA)
+    }
+
+    /// Wraps the given writer.
+    ///
+    /// `pool_capa` is the number of message buffers that are kept for reuse,
+    /// and the maximum number of log lines in a batch;
+    /// `message_capa` is the initial capacity of the message buffers.
+    #[must_use]
+    pub fn with_capacities(writer: W, pool_capa: usize, message_capa: usize) -> Self {
+        let format = writer.formatted_with().unwrap_or(default_format);
+        let a_writer = Arc::new(writer);
+        let (sender, receiver) = crossbeam_channel::unbounded();
+        let a_pool = Arc::new(ArrayQueue::new(pool_capa.max(1)));
+        let a_flush_acks = Arc::new(FlushAcks::default());
+        let mo_thread_handle = start_async_log_writer(
+            Arc::clone(&a_writer),
+            receiver,
+            Arc::clone(&a_pool),
+            message_capa,
+            Arc::clone(&a_flush_acks),
+        );
+        Self {
+            a_writer,
+            format,
+            sender,
+            mo_thread_handle,
+            a_pool,
+            message_capa,
+            a_flush_acks,
+        }
+    }
+
+    fn send(&self, message: AsyncMessage) -> std::io::Result<()> {
+        self.sender.send(message).map_err(|_e| io_err("Send"))
+    }
+}
+impl<W: LogWriter + 'static> LogWriter for AsyncLogWriter<W> {
+    fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        buffer_with(|buffer| {
+            format_line(self.format, post_format_of(self), buffer, now, record)?;
+            self.write_formatted(buffer)
+        })
+    }
+
+    fn formatted_with(&self) -> Option<FormatFunction> {
+        Some(self.format)
+    }
+
+    fn max_line_length(&self) -> Option<usize> {
+        self.a_writer.max_line_length()
+    }
+
+    fn escape_control_chars(&self) -> bool {
+        self.a_writer.escape_control_chars()
+    }
+
+    fn write_formatted(&self, line: &[u8]) -> std::io::Result<()> {
+        let mut message = if line.len() <= self.message_capa {
+            self.a_pool
+                .pop()
+                .unwrap_or_else(|| Vec::with_capacity(self.message_capa))
+        } else {
+            Vec::with_capacity(line.len())
+        };
+        message.extend_from_slice(line);
+        self.send(AsyncMessage::Line(message))
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        self.send(AsyncMessage::Flush)
+    }
+
+    fn flush_and_wait(&self, timeout: std::time::Duration) -> std::io::Result<()> {
+        self.a_flush_acks
+            .request_and_wait(|| self.send(AsyncMessage::FlushAndAck), timeout)
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.a_writer.max_log_level()
+    }
+
+    fn format(&mut self, format: FormatFunction) {
+        self.format = format;
+    }
+
+    fn shutdown(&self) {
+        if let Ok(mut o_thread_handle) = self.mo_thread_handle.lock() {
+            if let Some(thread_handle) = o_thread_handle.take() {
+                self.send(AsyncMessage::Shutdown).ok();
+                thread_handle.join().ok();
+            }
+        }
+    }
+
+    fn reopen_output(&self) -> Result<(), FlexiLoggerError> {
+        self.a_writer.reopen_output()
+    }
+
+    fn rotate(&self) -> Result<(), FlexiLoggerError> {
+        self.a_writer.rotate()
+    }
+
+    fn rotate_to(&self, infix: &str) -> Result<(), FlexiLoggerError> {
+        self.a_writer.rotate_to(infix)
+    }
+
+    fn trigger_cleanup(&self) -> Result<(), FlexiLoggerError> {
+        self.a_writer.trigger_cleanup()
+    }
+
+    fn name(&self) -> &str {
+        self.a_writer.name()
+    }
+
+    fn healthcheck(&self) -> Result<(), FlexiLoggerError> {
+        self.a_writer.healthcheck()
+    }
+}
+impl<W: LogWriter + 'static> Drop for AsyncLogWriter<W> {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
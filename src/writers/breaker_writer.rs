@@ -0,0 +1,284 @@
+//! Skips an additional writer that fails repeatedly, see `CircuitBreaker`.
+use crate::{
+    util::{eprint_err, eprint_msg, ErrorCode},
+    writers::LogWriter,
+    CircuitBreaker, DeferredNow, FlexiLoggerError, FormatFunction,
+};
+use log::Record;
+use std::{collections::VecDeque, sync::Mutex, time::Instant};
+
+// Wraps a writer that was registered with Logger::add_writer, if a circuit breaker is configured.
+pub(crate) struct BreakerWriter {
+    name: String,
+    writer: Box<dyn LogWriter>,
+    breaker: CircuitBreaker,
+    state: Mutex<BreakerState>,
+}
+
+#[derive(Default)]
+struct BreakerState {
+    // the failures within the last `breaker.within`
+    failures: VecDeque<Instant>,
+    o_open: Option<Open>,
+}
+
+// The writer is skipped
+struct Open {
+    since: Instant,
+    last_try: Instant,
+    skipped_lines: u64,
+}
+
+impl BreakerWriter {
+    pub(crate) fn new(name: &str, writer: Box<dyn LogWriter>, breaker: CircuitBreaker) -> Self {
+        Self {
+            name: name.to_string(),
+            writer,
+            breaker: CircuitBreaker {
+                max_failures: breaker.max_failures.max(1),
+                ..breaker
+            },
+            state: Mutex::new(BreakerState::default()),
+        }
+    }
+
+    // Skips the write while the circuit breaker is open, except for one try after `retry_after`.
+    // Errors are only handed back until the circuit breaker opens.
+    fn guarded<F>(&self, write: F) -> std::io::Result<()>
+    where
+        F: FnOnce() -> std::io::Result<()>,
+    {
+        if let Some(ref mut open) = self.lock_state().o_open {
+            if open.last_try.elapsed() < self.breaker.retry_after {
+                open.skipped_lines += 1;
+                return Ok(());
+            }
+            // let the other threads skip while we try
+            open.last_try = Instant::now();
+        }
+
+        let result = write();
+
+        let mut state = self.lock_state();
+        match result {
+            Ok(()) => {
+                if let Some(open) = state.o_open.take() {
+                    state.failures.clear();
+                    eprint_msg(
+                        ErrorCode::WriteResumed,
+                        &format!(
+                            "custom writer \"{}\" works again, after {:?}; \
+                             {} log lines were skipped meanwhile",
+                            self.name,
+                            open.since.elapsed(),
+                            open.skipped_lines,
+                        ),
+                    );
+                }
+                Ok(())
+            }
+            Err(e) => {
+                let now = Instant::now();
+                if let Some(ref mut open) = state.o_open {
+                    open.last_try = now;
+                    open.skipped_lines += 1;
+                    return Ok(());
+                }
+                state.failures.push_back(now);
+                while state
+                    .failures
+                    .front()
+                    .is_some_and(|failure| now.duration_since(*failure) > self.breaker.within)
+                {
+                    state.failures.pop_front();
+                }
+                if state.failures.len() < self.breaker.max_failures as usize {
+                    return Err(e);
+                }
+                eprint_err(
+                    ErrorCode::Write,
+                    &format!(
+                        "custom writer \"{}\" failed {} times within {:?}, \
+                         it is skipped for {:?} before it is tried again",
+                        self.name,
+                        state.failures.len(),
+                        self.breaker.within,
+                        self.breaker.retry_after,
+                    ),
+                    &e,
+                );
+                state.failures.clear();
+                state.o_open = Some(Open {
+                    since: now,
+                    last_try: now,
+                    skipped_lines: 1,
+                });
+                Ok(())
+            }
+        }
+    }
+
+    fn lock_state(&self) -> std::sync::MutexGuard<'_, BreakerState> {
+        self.state
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+    }
+}
+
+impl LogWriter for BreakerWriter {
+    fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        self.guarded(|| self.writer.write(now, record))
+    }
+
+    fn formatted_with(&self) -> Option<FormatFunction> {
+        self.writer.formatted_with()
+    }
+
+    fn max_line_length(&self) -> Option<usize> {
+        self.writer.max_line_length()
+    }
+
+    fn escape_control_chars(&self) -> bool {
+        self.writer.escape_control_chars()
+    }
+
+    fn write_formatted(&self, line: &[u8]) -> std::io::Result<()> {
+        self.guarded(|| self.writer.write_formatted(line))
+    }
+
+    fn write_batch(&self, lines: &mut dyn Iterator<Item = &[u8]>) -> std::io::Result<()> {
+        self.guarded(|| self.writer.write_batch(lines))
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        self.writer.flush()
+    }
+
+    fn flush_and_wait(&self, timeout: std::time::Duration) -> std::io::Result<()> {
+        self.writer.flush_and_wait(timeout)
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.writer.max_log_level()
+    }
+
+    fn format(&mut self, format: FormatFunction) {
+        self.writer.format(format);
+    }
+
+    fn shutdown(&self) {
+        self.writer.shutdown();
+    }
+
+    fn reopen_output(&self) -> Result<(), FlexiLoggerError> {
+        self.writer.reopen_output()
+    }
+
+    fn rotate(&self) -> Result<(), FlexiLoggerError> {
+        self.writer.rotate()
+    }
+
+    fn rotate_to(&self, infix: &str) -> Result<(), FlexiLoggerError> {
+        self.writer.rotate_to(infix)
+    }
+
+    fn trigger_cleanup(&self) -> Result<(), FlexiLoggerError> {
+        self.writer.trigger_cleanup()
+    }
+
+    fn name(&self) -> &str {
+        self.writer.name()
+    }
+
+    // An open circuit breaker means that the writer failed, and that log lines are lost.
+    fn healthcheck(&self) -> Result<(), FlexiLoggerError> {
+        if let Some(ref open) = self.lock_state().o_open {
+            return Err(FlexiLoggerError::OutputIo(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                format!(
+                    "the writer is skipped since {:?} after repeated failures",
+                    open.since.elapsed()
+                ),
+            )));
+        }
+        self.writer.healthcheck()
+    }
+
+    fn validate_logs(&self, expected: &[(&'static str, &'static str, &'static str)]) {
+        self.writer.validate_logs(expected);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::BreakerWriter;
+    use crate::{writers::LogWriter, CircuitBreaker, DeferredNow};
+    use std::{
+        sync::{
+            atomic::{AtomicBool, AtomicUsize, Ordering},
+            Arc,
+        },
+        time::Duration,
+    };
+
+    #[derive(Clone, Default)]
+    struct FlakyWriter {
+        failing: Arc<AtomicBool>,
+        attempts: Arc<AtomicUsize>,
+    }
+    impl LogWriter for FlakyWriter {
+        fn write(&self, _now: &mut DeferredNow, _record: &log::Record) -> std::io::Result<()> {
+            self.attempts.fetch_add(1, Ordering::SeqCst);
+            if self.failing.load(Ordering::SeqCst) {
+                Err(std::io::Error::new(
+                    std::io::ErrorKind::Other,
+                    "unreachable",
+                ))
+            } else {
+                Ok(())
+            }
+        }
+        fn flush(&self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn opens_and_closes() {
+        let flaky = FlakyWriter::default();
+        let writer = BreakerWriter::new(
+            "flaky",
+            Box::new(flaky.clone()),
+            CircuitBreaker {
+                max_failures: 2,
+                within: Duration::from_secs(60),
+                retry_after: Duration::from_millis(50),
+            },
+        );
+        let record = log::Record::builder().args(format_args!("test")).build();
+        let write = || writer.write(&mut DeferredNow::new(), &record);
+
+        flaky.failing.store(true, Ordering::SeqCst);
+        assert!(write().is_err());
+        // the second failure opens the circuit breaker and is not handed back
+        assert!(write().is_ok());
+        assert!(write().is_ok());
+        assert_eq!(flaky.attempts.load(Ordering::SeqCst), 2);
+
+        // a failing try after retry_after keeps the writer skipped
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(write().is_ok());
+        assert!(write().is_ok());
+        assert_eq!(flaky.attempts.load(Ordering::SeqCst), 3);
+
+        // a successful try closes the circuit breaker
+        flaky.failing.store(false, Ordering::SeqCst);
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(write().is_ok());
+        assert!(write().is_ok());
+        assert_eq!(flaky.attempts.load(Ordering::SeqCst), 5);
+
+        flaky.failing.store(true, Ordering::SeqCst);
+        assert!(write().is_err());
+    }
+}
@@ -0,0 +1,69 @@
+use crate::util;
+
+/// Calls the given function with a buffer that is reused for all calls on the current thread.
+///
+/// This allows [`LogWriter`](crate::writers::LogWriter) implementations to format the log lines
+/// without allocating a new `String` or `Vec` for each record.
+/// `flexi_logger` uses the same buffer for its own writers.
+///
+/// The buffer is empty when the function is called, and is cleared afterwards.
+/// If `buffer_with` is called while the buffer is in use, e.g. because the `Display`
+/// implementation of a logged value writes a log line itself, the inner call gets a new buffer.
+///
+/// ```rust
+/// use flexi_logger::{default_format, writers::{buffer_with, LogWriter}, DeferredNow};
+/// use std::io::Write;
+///
+/// struct StdoutWriter;
+/// impl LogWriter for StdoutWriter {
+///     fn write(&self, now: &mut DeferredNow, record: &log::Record) -> std::io::Result<()> {
+///         buffer_with(|buffer| {
+///             default_format(buffer, now, record)?;
+///             buffer.push(b'\n');
+///             std::io::stdout().write_all(buffer)
+///         })
+///     }
+///     fn flush(&self) -> std::io::Result<()> {
+///         std::io::stdout().flush()
+///     }
+/// }
+/// ```
+pub fn buffer_with<F, R>(f: F) -> R
+where
+    F: FnOnce(&mut Vec<u8>) -> R,
+{
+    util::buffer_with(|tl_buf| match tl_buf.try_borrow_mut() {
+        Ok(mut buffer) => {
+            buffer.clear();
+            let result = f(&mut buffer);
+            buffer.clear();
+            result
+        }
+        Err(_e) => f(&mut Vec::with_capacity(200)),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::buffer_with;
+
+    #[test]
+    fn nested_calls_get_own_buffers() {
+        let (outer, inner) = buffer_with(|outer| {
+            outer.extend_from_slice(b"outer");
+            let inner = buffer_with(|inner| {
+                inner.extend_from_slice(b"inner");
+                inner.clone()
+            });
+            (outer.clone(), inner)
+        });
+        assert_eq!(outer, b"outer");
+        assert_eq!(inner, b"inner");
+
+        // the buffer is reused, and is empty again
+        buffer_with(|buffer| {
+            assert!(buffer.is_empty());
+            assert!(buffer.capacity() >= 5);
+        });
+    }
+}
@@ -0,0 +1,137 @@
+use crate::{
+    resources::ThreadRegistration,
+    util::{eprint_err, ErrorCode},
+    writers::LineLogger,
+};
+use std::{
+    io::{Read, Result as IoResult},
+    process::{Child, Command, ExitStatus, Stdio},
+    thread::{Builder as ThreadBuilder, JoinHandle},
+};
+
+const STDOUT_PUMP: &str = "flexi_logger-child_stdout_pump";
+const STDERR_PUMP: &str = "flexi_logger-child_stderr_pump";
+
+/// Spawns child processes with their stdout and stderr being logged line by line.
+///
+/// For each of the two streams, a background thread reads the output and logs each line
+/// (using a [`LineLogger`]) with the level and target that are configured for the stream.
+/// By default, stdout is logged with level `Info`, and stderr with level `Warn`,
+/// both with the target that is given to [`ChildOutputLogger::new`].
+///
+/// ```rust,no_run
+/// use flexi_logger::{writers::ChildOutputLogger, Logger};
+/// use std::process::Command;
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let _logger = Logger::try_with_str("info")?.start()?;
+///
+/// let status = ChildOutputLogger::new("cargo")
+///     .stderr(log::Level::Info, "cargo")
+///     .spawn(Command::new("cargo").arg("build"))?
+///     .wait()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct ChildOutputLogger {
+    stdout: (log::Level, String),
+    stderr: (log::Level, String),
+}
+impl ChildOutputLogger {
+    /// Creates a `ChildOutputLogger` that logs stdout with level `Info` and stderr with
+    /// level `Warn`, both with the given target.
+    #[must_use]
+    pub fn new<T: Into<String>>(target: T) -> Self {
+        let target = target.into();
+        Self {
+            stdout: (log::Level::Info, target.clone()),
+            stderr: (log::Level::Warn, target),
+        }
+    }
+
+    /// Sets the level and target for the lines that the child process writes to stdout.
+    #[must_use]
+    pub fn stdout<T: Into<String>>(mut self, level: log::Level, target: T) -> Self {
+        self.stdout = (level, target.into());
+        self
+    }
+
+    /// Sets the level and target for the lines that the child process writes to stderr.
+    #[must_use]
+    pub fn stderr<T: Into<String>>(mut self, level: log::Level, target: T) -> Self {
+        self.stderr = (level, target.into());
+        self
+    }
+
+    /// Spawns the command, with its stdout and stderr being piped into the logger.
+    ///
+    /// # Errors
+    ///
+    /// `std::io::Error` if the child process or one of the threads cannot be started.
+    pub fn spawn(&self, command: &mut Command) -> IoResult<LoggedChild> {
+        let mut child = command
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let mut pumps = Vec::with_capacity(2);
+        if let Some(stdout) = child.stdout.take() {
+            pumps.push(start_pump(STDOUT_PUMP, stdout, &self.stdout)?);
+        }
+        if let Some(stderr) = child.stderr.take() {
+            pumps.push(start_pump(STDERR_PUMP, stderr, &self.stderr)?);
+        }
+        Ok(LoggedChild { child, pumps })
+    }
+}
+
+/// A child process that was spawned with [`ChildOutputLogger::spawn`].
+///
+/// If it is dropped without calling [`LoggedChild::wait`], the child process continues
+/// to run, and its output continues to be logged.
+#[derive(Debug)]
+pub struct LoggedChild {
+    child: Child,
+    pumps: Vec<JoinHandle<()>>,
+}
+impl LoggedChild {
+    /// Gives access to the child process, e.g. to its id or its stdin.
+    ///
+    /// Note that stdout and stderr of the child process are taken by the logger.
+    pub fn child(&mut self) -> &mut Child {
+        &mut self.child
+    }
+
+    /// Waits until the child process has exited and all of its output is logged.
+    ///
+    /// # Errors
+    ///
+    /// `std::io::Error` if waiting for the child process fails.
+    pub fn wait(mut self) -> IoResult<ExitStatus> {
+        let status = self.child.wait()?;
+        for pump in self.pumps.drain(..) {
+            pump.join().ok();
+        }
+        Ok(status)
+    }
+}
+
+// Logs the output of the child process, until the child process closes the stream.
+fn start_pump<R: Read + Send + 'static>(
+    name: &'static str,
+    mut output: R,
+    (level, target): &(log::Level, String),
+) -> IoResult<JoinHandle<()>> {
+    let mut line_logger = LineLogger::new(*level, target.clone());
+    let registration = ThreadRegistration::new(name);
+    ThreadBuilder::new().name(name.to_string()).spawn(move || {
+        let _registration = registration;
+        std::io::copy(&mut output, &mut line_logger).unwrap_or_else(|e| {
+            eprint_err(
+                ErrorCode::Write,
+                "reading the output of the child process failed",
+                &e,
+            );
+            0
+        });
+    })
+}
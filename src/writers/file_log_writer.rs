@@ -2,21 +2,32 @@
 mod builder;
 mod config;
 mod infix_filter;
+mod registry;
+mod rotation_event;
 mod state;
 mod state_handle;
 mod threads;
 
 pub use self::builder::{ArcFileLogWriter, FileLogWriterBuilder, FileLogWriterHandle};
 pub use self::config::FileLogWriterConfig;
+pub use self::rotation_event::RotationEvent;
 pub(crate) use infix_filter::InfixFilter;
 
 use self::{config::RotationConfig, state::State, state_handle::StateHandle};
 use crate::{
-    writers::LogWriter, DeferredNow, EffectiveWriteMode, FileSpec, FlexiLoggerError,
-    FormatFunction, LogfileSelector,
+    writers::LogWriter, CleanupStatus, DeferredNow, FileSpec, FlexiLoggerError, FormatFunction,
+    LogfileSelector,
 };
 use log::Record;
-use std::path::PathBuf;
+use std::{
+    ops::Deref,
+    path::PathBuf,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::Receiver,
+        Condvar, Mutex, PoisonError, RwLock, RwLockReadGuard, TryLockError,
+    },
+};
 
 const WINDOWS_LINE_ENDING: &[u8] = b"\r\n";
 const UNIX_LINE_ENDING: &[u8] = b"\n";
@@ -29,7 +40,10 @@ pub struct FileLogWriter {
     // the state needs to be mutable; since `Log.log()` requires an unmutable self,
     // which translates into a non-mutating `LogWriter::write()`,
     // we need internal mutability and thread-safety.
-    state_handle: StateHandle,
+    // The state handle itself is only replaced if the write mode is changed.
+    state_handle: RwLock<StateHandle>,
+    // lets a reset of the write mode wait until the state handle is released
+    release_signal: ReleaseSignal,
     max_log_level: log::LevelFilter,
 }
 impl FileLogWriter {
@@ -38,27 +52,24 @@ impl FileLogWriter {
         max_log_level: log::LevelFilter,
         format_function: FormatFunction,
     ) -> FileLogWriter {
-        let state_handle = match state.config().write_mode.effective_write_mode() {
-            EffectiveWriteMode::Direct
-            | EffectiveWriteMode::BufferAndFlushWith(_)
-            | EffectiveWriteMode::BufferDontFlushWith(_) => {
-                StateHandle::new_sync(state, format_function)
-            }
-
-            #[cfg(feature = "async")]
-            EffectiveWriteMode::AsyncWith {
-                pool_capa,
-                message_capa,
-                flush_interval: _,
-            } => StateHandle::new_async(pool_capa, message_capa, state, format_function),
-        };
-
         FileLogWriter {
-            state_handle,
+            state_handle: RwLock::new(StateHandle::new(state, format_function)),
+            release_signal: ReleaseSignal::default(),
             max_log_level,
         }
     }
 
+    fn state_handle(&self) -> StateHandleRef<'_> {
+        StateHandleRef {
+            o_guard: Some(
+                self.state_handle
+                    .read()
+                    .unwrap_or_else(PoisonError::into_inner),
+            ),
+            release_signal: &self.release_signal,
+        }
+    }
+
     /// Instantiates a builder for `FileLogWriter`.
     #[must_use]
     pub fn builder(file_spec: FileSpec) -> FileLogWriterBuilder {
@@ -69,22 +80,23 @@ impl FileLogWriter {
     #[must_use]
     #[inline]
     pub fn format(&self) -> FormatFunction {
-        self.state_handle.format_function()
+        self.state_handle().format_function()
     }
 
     pub(crate) fn plain_write(&self, buffer: &[u8]) -> std::result::Result<usize, std::io::Error> {
-        self.state_handle.plain_write(buffer)
+        self.state_handle().plain_write(buffer)
     }
 
     /// Replaces parts of the configuration of the file log writer.
     ///
-    /// Note that the write mode and the format function cannot be reset and
-    /// that the provided `FileLogWriterBuilder` must have the same values for these as the
-    /// current `FileLogWriter`.
+    /// If the write mode of the provided `FileLogWriterBuilder` differs from the current one,
+    /// the current output is flushed and closed, its helper threads are stopped,
+    /// and the output is continued with the new write mode, e.g. with `WriteMode::Direct`
+    /// instead of `WriteMode::BufferAndFlush` while debugging.
     ///
-    /// # Errors
+    /// Note that the format function cannot be reset; the current one is kept.
     ///
-    /// `FlexiLoggerError::Reset` if a reset was tried with a different write mode.
+    /// # Errors
     ///
     /// `FlexiLoggerError::Io` if the specified path doesn't work.
     ///
@@ -92,7 +104,39 @@ impl FileLogWriter {
     ///
     /// `FlexiLoggerError::Poison` if some mutex is poisoned.
     pub fn reset(&self, flwb: &FileLogWriterBuilder) -> Result<(), FlexiLoggerError> {
-        self.state_handle.reset(flwb)
+        if self.state_handle().config()?.write_mode == *flwb.get_write_mode() {
+            return self.state_handle().reset(flwb);
+        }
+
+        // A blocking write() would block further readers, and thus also recursive log calls
+        // of a thread that is currently writing, so we try to get the write lock
+        // whenever a reader has released the state handle.
+        self.release_signal.waiting.fetch_add(1, Ordering::SeqCst);
+        let mut guard = self
+            .release_signal
+            .mutex
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner);
+        let mut state_handle = loop {
+            match self.state_handle.try_write() {
+                Ok(state_handle) => break state_handle,
+                Err(TryLockError::Poisoned(e)) => break e.into_inner(),
+                Err(TryLockError::WouldBlock) => {
+                    guard = self
+                        .release_signal
+                        .condvar
+                        .wait(guard)
+                        .unwrap_or_else(PoisonError::into_inner);
+                }
+            }
+        };
+        drop(guard);
+        self.release_signal.waiting.fetch_sub(1, Ordering::SeqCst);
+        let state = flwb.try_build_state(state_handle.registration()?.as_ref())?;
+        let format_function = state_handle.format_function();
+        state_handle.shutdown();
+        *state_handle = StateHandle::new(state, format_function);
+        Ok(())
     }
 
     /// Returns the current configuration of the file log writer
@@ -101,7 +145,7 @@ impl FileLogWriter {
     ///
     /// `FlexiLoggerError::Poison` if some mutex is poisoned.
     pub fn config(&self) -> Result<FileLogWriterConfig, FlexiLoggerError> {
-        self.state_handle.config()
+        self.state_handle().config()
     }
 
     /// Makes the `FileLogWriter` re-open the current log file.
@@ -124,8 +168,11 @@ impl FileLogWriter {
     /// # Errors
     ///
     /// `FlexiLoggerError::Poison` if some mutex is poisoned.
+    ///
+    /// `FlexiLoggerError::OutputIo` if the log is written to a provided file handle,
+    /// see [`FileLogWriterBuilder::with_open_file`].
     pub fn reopen_outputfile(&self) -> Result<(), FlexiLoggerError> {
-        self.state_handle.reopen_outputfile()
+        self.state_handle().reopen_outputfile()
     }
 
     /// Trigger an extra log file rotation.
@@ -138,7 +185,69 @@ impl FileLogWriter {
     ///
     /// IO errors.
     pub fn rotate(&self) -> Result<(), FlexiLoggerError> {
-        self.state_handle.rotate()
+        self.state_handle().rotate()
+    }
+
+    /// Renames the current log file to a file with the given infix, like `"before-upgrade"`,
+    /// and continues writing to a fresh log file.
+    ///
+    /// If the configured [`Naming`](crate::Naming) writes directly to files with changing
+    /// infixes, the fresh file gets the next regular infix.
+    /// The file with the given infix is not affected by the cleanup.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::Poison` if some mutex is poisoned.
+    ///
+    /// `FlexiLoggerError::OutputIo` if the infix is empty or contains path separators,
+    /// if the log is written to a provided file handle, and for other IO errors.
+    pub fn rotate_to(&self, infix: &str) -> Result<(), FlexiLoggerError> {
+        self.state_handle().rotate_to(infix)
+    }
+
+    /// Runs the cleanup of the rotated log files, as configured with
+    /// [`FileLogWriterBuilder::rotate`], independent of a rotation.
+    ///
+    /// This allows reclaiming disk space immediately, e.g. after reducing the number
+    /// of files to keep with [`FileLogWriter::reset`].
+    /// If the cleanup is done in a background thread, this method only triggers it.
+    ///
+    /// Does nothing if rotation is not configured.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::Poison` if some mutex is poisoned.
+    ///
+    /// IO errors.
+    pub fn trigger_cleanup(&self) -> Result<(), FlexiLoggerError> {
+        self.state_handle().trigger_cleanup()
+    }
+
+    /// Returns a receiver that is informed about each rotation of the log file.
+    ///
+    /// An event is sent after the completed log file got its final name,
+    /// e.g. for uploading it.
+    /// Rotations by other processes, or by external tools, are not reported.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::Poison` if some mutex is poisoned.
+    pub fn subscribe_rotation(&self) -> Result<Receiver<RotationEvent>, FlexiLoggerError> {
+        self.state_handle().subscribe_rotation()
+    }
+
+    /// Returns the path of the file that is currently written, e.g. the `rCURRENT` file
+    /// or the file with the newest timestamp; the path changes with every rotation.
+    ///
+    /// Returns `None` as long as the output file is not yet opened, which happens
+    /// with the first log line, and if the output is not a file path, e.g. with
+    /// [`FileLogWriterBuilder::with_open_file`].
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::Poison` if some mutex is poisoned.
+    pub fn current_log_file(&self) -> Result<Option<PathBuf>, FlexiLoggerError> {
+        self.state_handle().current_log_file()
     }
 
     /// Returns the list of existing log files according to the current `FileSpec`.
@@ -152,15 +261,60 @@ impl FileLogWriter {
         &self,
         selector: &LogfileSelector,
     ) -> Result<Vec<PathBuf>, FlexiLoggerError> {
-        self.state_handle.existing_log_files(selector)
+        self.state_handle().existing_log_files(selector)
+    }
+
+    /// Returns information about failures of the cleanup of log files.
+    ///
+    /// See [`FileLogWriterBuilder::on_cleanup_failure`].
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::Poison` if some mutex is poisoned.
+    pub fn cleanup_status(&self) -> Result<CleanupStatus, FlexiLoggerError> {
+        self.state_handle().cleanup_status()
+    }
+}
+
+// Read access to the state handle; when released, a waiting `reset()` is woken up.
+struct StateHandleRef<'a> {
+    o_guard: Option<RwLockReadGuard<'a, StateHandle>>,
+    release_signal: &'a ReleaseSignal,
+}
+impl Deref for StateHandleRef<'_> {
+    type Target = StateHandle;
+    fn deref(&self) -> &StateHandle {
+        // the guard is only taken in drop()
+        self.o_guard.as_ref().unwrap(/* ok */)
+    }
+}
+impl Drop for StateHandleRef<'_> {
+    fn drop(&mut self) {
+        self.o_guard = None;
+        if self.release_signal.waiting.load(Ordering::SeqCst) > 0 {
+            // taking the mutex ensures that the waiter is already waiting for the notification
+            let _guard = self
+                .release_signal
+                .mutex
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner);
+            self.release_signal.condvar.notify_all();
+        }
     }
 }
 
+#[derive(Debug, Default)]
+struct ReleaseSignal {
+    waiting: AtomicUsize,
+    mutex: Mutex<()>,
+    condvar: Condvar,
+}
+
 impl LogWriter for FileLogWriter {
     #[inline]
     fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
         if record.level() <= self.max_log_level {
-            self.state_handle.write(now, record)
+            self.state_handle().write(now, record)
         } else {
             Ok(())
         }
@@ -168,7 +322,11 @@ impl LogWriter for FileLogWriter {
 
     #[inline]
     fn flush(&self) -> std::io::Result<()> {
-        self.state_handle.flush()
+        self.state_handle().flush()
+    }
+
+    fn flush_and_wait(&self, timeout: std::time::Duration) -> std::io::Result<()> {
+        self.state_handle().flush_and_wait(timeout)
     }
 
     #[inline]
@@ -181,15 +339,31 @@ impl LogWriter for FileLogWriter {
     }
 
     fn rotate(&self) -> Result<(), FlexiLoggerError> {
-        self.state_handle.rotate()
+        self.state_handle().rotate()
+    }
+
+    fn rotate_to(&self, infix: &str) -> Result<(), FlexiLoggerError> {
+        self.state_handle().rotate_to(infix)
+    }
+
+    fn trigger_cleanup(&self) -> Result<(), FlexiLoggerError> {
+        self.state_handle().trigger_cleanup()
+    }
+
+    fn name(&self) -> &'static str {
+        "FileLogWriter"
+    }
+
+    fn healthcheck(&self) -> Result<(), FlexiLoggerError> {
+        self.state_handle().healthcheck()
     }
 
     fn validate_logs(&self, expected: &[(&'static str, &'static str, &'static str)]) {
-        self.state_handle.validate_logs(expected);
+        self.state_handle().validate_logs(expected);
     }
 
     fn shutdown(&self) {
-        self.state_handle.shutdown();
+        self.state_handle().shutdown();
     }
 }
 
@@ -522,21 +696,35 @@ mod test {
         .unwrap();
         println!("FileLogWriter {flw:?}");
 
-        assert!(flw
-            .reset(
-                &super::FileLogWriter::builder(
-                    FileSpec::default()
-                        .directory(DIRECTORY)
-                        .discriminant("test_reset-3"),
-                )
-                .rotate(
-                    Criterion::Size(28),
-                    Naming::Numbers,
-                    Cleanup::KeepLogFiles(20),
-                )
-                .write_mode(WriteMode::Direct),
+        // the write mode can be changed as well
+        flw.reset(
+            &super::FileLogWriter::builder(
+                FileSpec::default()
+                    .directory(DIRECTORY)
+                    .discriminant("test_reset-3"),
             )
-            .is_err());
+            .rotate(
+                Criterion::Size(28),
+                Naming::Numbers,
+                Cleanup::KeepLogFiles(20),
+            )
+            .write_mode(WriteMode::Direct),
+        )
+        .unwrap();
+        assert_eq!(flw.config().unwrap().write_mode, WriteMode::Direct);
+        flw.write(
+            &mut DeferredNow::new(),
+            &log::Record::builder()
+                .args(format_args!("{}", "test_reset-3"))
+                .level(log::Level::Error)
+                .target("test_reset")
+                .file(Some("server.rs"))
+                .line(Some(144))
+                .module_path(Some("server"))
+                .build(),
+        )
+        .unwrap();
+        println!("FileLogWriter {flw:?}");
     }
 
     #[test]
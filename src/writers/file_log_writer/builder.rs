@@ -1,25 +1,61 @@
-use crate::flexi_error::FlexiLoggerError;
+use crate::deferred_now::clock_now;
+use crate::flexi_error::{io_err_with_context, FlexiLoggerError};
 use crate::formats::default_format;
-use crate::{Cleanup, Criterion, FileSpec, FormatFunction, Naming, WriteMode};
+use crate::{
+    parameters::{Archiver, CleanupObserver, Compression, SharedCompressor},
+    Cleanup, CleanupStatus, Compressor, ConfigWarning, Criterion, Fallback, FileSpec,
+    FormatFunction, Naming, SyncPolicy, WriteFailurePolicy, WriteMode,
+};
+use std::fs::File;
 use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 
-use super::{FileLogWriter, FileLogWriterConfig, LogWriter, RotationConfig, State};
+use super::{
+    config::{Banner, FileFooter, FileHeader, ProvidedOutput, RecordFn},
+    registry::Registration,
+    state::{fall_back, validate_infix_format},
+    FileLogWriter, FileLogWriterConfig, LogWriter, RotationConfig, State,
+};
 
 /// Builder for [`FileLogWriter`].
 #[allow(clippy::struct_excessive_bools, clippy::module_name_repetitions)]
+#[derive(Clone)]
 pub struct FileLogWriterBuilder {
     cfg_print_message: bool,
     cfg_append: bool,
+    cfg_multi_process: bool,
+    cfg_recover_on_append: bool,
+    cfg_keep_corrupt_tail: bool,
+    cfg_rotation_lock: bool,
+    o_banner: Option<String>,
+    o_banner_forward: Option<Arc<RecordFn>>,
+    o_file_header: Option<FileHeader>,
+    o_file_footer: Option<FileFooter>,
     cfg_write_mode: WriteMode,
     file_spec: FileSpec,
     cfg_o_create_symlink: Option<PathBuf>,
+    cfg_remove_stale_artifacts: bool,
     cfg_line_ending: &'static [u8],
     format: FormatFunction,
     o_rotation_config: Option<RotationConfig>,
+    o_archiver: Option<Archiver>,
+    compression: Compression,
+    o_cleanup_observer: Option<CleanupObserver>,
     max_log_level: log::LevelFilter,
     cleanup_in_background_thread: bool,
+    rotate_on_shutdown: bool,
+    #[cfg(feature = "manifest")]
+    manifest: bool,
     use_utc: bool,
+    use_utc_for_rotation: bool,
+    sync_policy: SyncPolicy,
+    o_external_rotation_check: Option<Duration>,
+    write_failure_policy: WriteFailurePolicy,
+    o_max_line_length: Option<usize>,
+    escape_control_chars: bool,
+    o_provided_output: Option<ProvidedOutput>,
+    fallbacks: Vec<Fallback>,
 }
 
 /// Methods for influencing the behavior of the [`FileLogWriter`].
@@ -27,16 +63,39 @@ impl FileLogWriterBuilder {
     pub(crate) fn new(file_spec: FileSpec) -> Self {
         Self {
             o_rotation_config: None,
+            o_archiver: None,
+            compression: Compression::default(),
+            o_cleanup_observer: None,
             cfg_print_message: false,
             file_spec,
             cfg_append: false,
+            cfg_multi_process: false,
+            cfg_recover_on_append: false,
+            cfg_keep_corrupt_tail: false,
+            cfg_rotation_lock: false,
+            o_banner: None,
+            o_banner_forward: None,
+            o_file_header: None,
+            o_file_footer: None,
             cfg_write_mode: WriteMode::Direct,
             cfg_o_create_symlink: None,
+            cfg_remove_stale_artifacts: false,
             cfg_line_ending: super::UNIX_LINE_ENDING,
             format: default_format,
             max_log_level: log::LevelFilter::Trace,
             cleanup_in_background_thread: true,
+            rotate_on_shutdown: false,
+            #[cfg(feature = "manifest")]
+            manifest: false,
             use_utc: false,
+            use_utc_for_rotation: false,
+            sync_policy: SyncPolicy::Never,
+            o_external_rotation_check: None,
+            write_failure_policy: WriteFailurePolicy::Report,
+            o_max_line_length: None,
+            escape_control_chars: false,
+            o_provided_output: None,
+            fallbacks: Vec::new(),
         }
     }
 
@@ -110,11 +169,140 @@ impl FileLogWriterBuilder {
             criterion,
             naming,
             cleanup,
+            o_archiver: None,
+            compression: Compression::default(),
+            o_cleanup_observer: None,
+            rotate_on_shutdown: false,
+            #[cfg(feature = "manifest")]
+            manifest: false,
         });
         self.file_spec.if_default_use_timestamp(false);
         self
     }
 
+    /// Registers a callback that is called with each log file that the cleanup is going
+    /// to delete, e.g. to upload the file or move it to a cold storage.
+    ///
+    /// The file is only deleted if the callback returns `Ok`.
+    /// If the callback fails, the error is reported and the file is kept;
+    /// archiving it is then retried with the next cleanup.
+    ///
+    /// Note that the cleanup runs by default in a background thread,
+    /// see [`FileLogWriterBuilder::cleanup_in_background_thread`];
+    /// otherwise the callback is executed in the thread that triggers the rotation.
+    ///
+    /// This option only has an effect if rotation is used with a [`Cleanup`] strategy
+    /// that deletes files.
+    #[must_use]
+    pub fn cleanup_archiver<F>(mut self, archiver: F) -> Self
+    where
+        F: Fn(&Path) -> std::io::Result<()> + Send + Sync + 'static,
+    {
+        self.o_archiver = Some(Archiver::new(archiver));
+        self
+    }
+
+    /// Makes the cleanup use the given [`Compressor`] instead of gzip.
+    ///
+    /// The compressed files get the [suffix](Compressor::suffix) of the compressor
+    /// instead of `gz`, and only files with this suffix are recognized as compressed files,
+    /// e.g. by the cleanup and by
+    /// [`LogfileSelector::with_compressed_files`](crate::LogfileSelector::with_compressed_files).
+    /// The suffix must not contain a dot.
+    ///
+    /// This option only has an effect if rotation is used with a [`Cleanup`] strategy
+    /// that compresses files.
+    #[must_use]
+    pub fn compressor<C: Compressor + 'static>(mut self, compressor: C) -> Self {
+        self.compression.o_compressor = Some(SharedCompressor::new(compressor));
+        self
+    }
+
+    /// Makes the cleanup compress up to the given number of files in parallel.
+    ///
+    /// This shortens the cleanup if many files are to be compressed,
+    /// e.g. with the first run after a [`Cleanup`] strategy that compresses files
+    /// was configured. The default is 1, values below 1 are treated as 1.
+    ///
+    /// The compressed files are written under a temporary name and only get their
+    /// final name when they are complete. A cleanup that is interrupted, e.g. by the end of
+    /// the program, is continued by the next cleanup: incomplete compressed files are removed,
+    /// and files whose compressed counterpart exists already are compressed again,
+    /// replacing the existing compressed file.
+    ///
+    /// This option only has an effect if rotation is used with a [`Cleanup`] strategy
+    /// that compresses files.
+    #[must_use]
+    pub fn compression_workers(mut self, workers: usize) -> Self {
+        self.compression.workers = std::cmp::max(workers, 1);
+        self
+    }
+
+    /// Makes the [`FileLogWriter`] rotate the current log file when it is shut down,
+    /// and then run the cleanup synchronously.
+    ///
+    /// With [`Naming::Numbers`] or [`Naming::Timestamps`], the `rCURRENT` file
+    /// is renamed like with a regular rotation, and then the [`Cleanup`] strategy is applied.
+    /// So the final file is compressed only with `Cleanup::KeepCompressedFiles`;
+    /// with `Cleanup::KeepLogAndCompressedFiles`, it is one of the youngest files,
+    /// which are kept uncompressed.
+    /// Short-lived programs, like batch jobs, can so leave behind only rotated,
+    /// and maybe compressed, log files.
+    /// A cleanup that runs in a background thread is completed before.
+    ///
+    /// This option only has an effect if rotation is used.
+    #[must_use]
+    pub fn rotate_on_shutdown(mut self, rotate_on_shutdown: bool) -> Self {
+        self.rotate_on_shutdown = rotate_on_shutdown;
+        self
+    }
+
+    /// Makes the [`FileLogWriter`] record the rotated log files in a manifest file.
+    ///
+    /// The manifest is written to the log directory, its name is built from the basename
+    /// and the discriminant of the [`FileSpec`], with the suffix `manifest.jsonl`,
+    /// like `myapp.manifest.jsonl`.
+    /// For each rotated file, a line with a JSON object is appended that contains
+    /// the file name, the size, the times of the first and the last write into the file,
+    /// and the SHA-256 checksum of the content, like
+    ///
+    /// ```text
+    /// {"event":"rotated","time":"2024-03-14T10:04:57.299908+01:00","file":"myapp_r00003.log","size":10213,"sha256":"...","first_write":"...","last_write":"..."}
+    /// ```
+    ///
+    /// The cleanup appends lines with `"event":"compressed"` (with size and checksum of
+    /// the compressed file) and `"event":"deleted"`.
+    /// The manifest itself is never cleaned up.
+    ///
+    /// This option only has an effect if rotation is used.
+    #[cfg(feature = "manifest")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "manifest")))]
+    #[must_use]
+    pub fn manifest(mut self, manifest: bool) -> Self {
+        self.manifest = manifest;
+        self
+    }
+
+    /// Registers a callback that is called after each failure of the cleanup,
+    /// with the accumulated [`CleanupStatus`].
+    ///
+    /// Failures of the cleanup are also written to the error channel,
+    /// and the status can be retrieved with [`FileLogWriter::cleanup_status`]
+    /// or [`LoggerHandle::cleanup_status`](crate::LoggerHandle::cleanup_status).
+    /// If the background thread of the cleanup died, e.g. due to a panic,
+    /// it is restarted with the next rotation, and this counts as a failure as well.
+    ///
+    /// The callback is executed in the thread that runs the cleanup,
+    /// see [`FileLogWriterBuilder::cleanup_in_background_thread`].
+    #[must_use]
+    pub fn on_cleanup_failure<F>(mut self, observer: F) -> Self
+    where
+        F: Fn(&CleanupStatus) + Send + Sync + 'static,
+    {
+        self.o_cleanup_observer = Some(CleanupObserver::new(observer));
+        self
+    }
+
     /// Set the file spec.
     #[must_use]
     pub(crate) fn file_spec(mut self, mut file_spec: FileSpec) -> Self {
@@ -125,6 +313,38 @@ impl FileLogWriterBuilder {
         self
     }
 
+    /// Makes the [`FileLogWriter`] write to the given, already opened file,
+    /// e.g. to a file descriptor that was inherited from the parent process,
+    /// rather than opening a file itself.
+    ///
+    /// The configured [`FileSpec`] is then not used, and options that need the path of the
+    /// output file have no effect: rotation is switched off, no symbolic link is created,
+    /// and [`FileLogWriter::reopen_outputfile`] and [`FileLogWriter::rotate_to`]
+    /// return an error.
+    #[must_use]
+    pub fn with_open_file(mut self, file: File) -> Self {
+        self.o_provided_output = Some(ProvidedOutput::File(Arc::new(file)));
+        self
+    }
+
+    // Makes the FileLogWriter write into an in-memory buffer, rather than to a file.
+    #[must_use]
+    pub(crate) fn capture_output(mut self) -> Self {
+        self.o_provided_output = Some(ProvidedOutput::Memory(Arc::default()));
+        self
+    }
+
+    /// Adds an alternative output for the case that the log file can't be written where it is
+    /// configured, e.g. because the directory is on a read-only file system.
+    ///
+    /// Can be called several times; the fallbacks are then tried in the given order.
+    /// See [`Fallback`] for details.
+    #[must_use]
+    pub fn fallback(mut self, fallback: Fallback) -> Self {
+        self.fallbacks.push(fallback);
+        self
+    }
+
     /// Makes the logger append to the given file, if it exists; by default, the file would be
     /// truncated.
     #[must_use]
@@ -133,6 +353,146 @@ impl FileLogWriterBuilder {
         self
     }
 
+    /// Repairs the end of an existing log file before appending to it.
+    ///
+    /// If a process crashed while writing, its log file can end with a partially written line,
+    /// or, after a power loss on some file systems, with NUL bytes.
+    /// With this option, such trailing bytes behind the last line break are removed
+    /// when the file is opened for appending.
+    /// If `keep_corrupt_tail` is true, the removed bytes are preserved in a sidecar file,
+    /// which has the name of the log file with the additional suffix `.corrupt`.
+    ///
+    /// This option only has an effect in combination with [`FileLogWriterBuilder::append`],
+    /// and it is ignored with [`FileLogWriterBuilder::multi_process_append`],
+    /// because then other processes might still be writing to the file.
+    #[must_use]
+    pub fn recover_on_append(mut self, keep_corrupt_tail: bool) -> Self {
+        self.cfg_recover_on_append = true;
+        self.cfg_keep_corrupt_tail = keep_corrupt_tail;
+        self
+    }
+
+    /// Makes the `FileLogWriter` safe for being used by several processes that write
+    /// to the same log file.
+    ///
+    /// The file is opened in append mode (`O_APPEND` on unix, `FILE_APPEND_DATA` on windows),
+    /// so that every write goes to the current end of the file, and it is never truncated
+    /// (i.e., this method implies [`FileLogWriterBuilder::append`]).
+    ///
+    /// Every log line is handed over to the operating system with a single write call;
+    /// for this, the output file is not buffered, even with a buffering [`WriteMode`].
+    /// On local file systems, such a write call is not interleaved with those of
+    /// other processes, so the lines of the participating processes do not get mixed up.
+    /// This is in general not guaranteed for network file systems.
+    ///
+    /// Note that rotation and cleanup are not coordinated across processes,
+    /// unless [`FileLogWriterBuilder::rotation_lock`] is used;
+    /// otherwise you should either not use rotation, or let only one of the processes rotate.
+    #[must_use]
+    pub fn multi_process_append(mut self) -> Self {
+        self.cfg_multi_process = true;
+        self.cfg_append = true;
+        self
+    }
+
+    /// Protects rotation and cleanup with an advisory lock on a lock file, so that several
+    /// processes can share the same rotation scheme.
+    ///
+    /// The lock file is created in the log directory and is named like the log files,
+    /// without infix, and with suffix `lock`.
+    /// Before a process rotates, it takes the lock and checks if another process
+    /// has rotated the current file in the meantime; in that case it only reopens the
+    /// current file.
+    ///
+    /// This works best in combination with [`FileLogWriterBuilder::multi_process_append`]
+    /// and a [`Naming`] variant that uses a `CURRENT` infix,
+    /// like [`Naming::Numbers`] or [`Naming::Timestamps`],
+    /// because then all processes write to the same current file.
+    ///
+    /// The lock is only effective on unix platforms; on others, this option has no effect.
+    #[cfg(feature = "rotation_lock")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "rotation_lock")))]
+    #[must_use]
+    pub fn rotation_lock(mut self) -> Self {
+        self.cfg_rotation_lock = true;
+        self
+    }
+
+    /// Makes the [`FileLogWriter`] write the given text, as a log record with level `INFO`,
+    /// at the beginning of every new output file that is created by rotation.
+    ///
+    /// See also [`Logger::banner`](crate::Logger::banner).
+    #[must_use]
+    pub fn banner<S: Into<String>>(mut self, banner: S) -> Self {
+        self.o_banner = Some(banner.into());
+        self
+    }
+
+    // Lets the banner, after it was written into a new output file, also be passed
+    // to the given function.
+    pub(crate) fn o_forward_banner(mut self, o_forward: Option<Arc<RecordFn>>) -> Self {
+        self.o_banner_forward = o_forward;
+        self
+    }
+
+    /// Registers a function that writes a header into every new output file,
+    /// e.g. a CSV header line or a schema version.
+    ///
+    /// The function is called whenever a new file is opened, at startup and after rotation,
+    /// before any log line (and before the [banner](FileLogWriterBuilder::banner))
+    /// is written to the file.
+    /// If an existing file is continued, e.g. with [`FileLogWriterBuilder::append`],
+    /// the header is only written if the file is empty.
+    ///
+    /// The function has to write complete lines, including the line endings.
+    #[must_use]
+    pub fn file_header<F>(mut self, header: F) -> Self
+    where
+        F: Fn(&mut dyn std::io::Write) -> std::io::Result<()> + Send + Sync + 'static,
+    {
+        self.o_file_header = Some(FileHeader::Custom(Arc::new(header)));
+        self
+    }
+
+    /// Makes the [`FileLogWriter`] write a header line with the given application name
+    /// and version and the start time into every new output file,
+    /// like `my_app 1.2.3; started: 2024-06-09T13:24:35.123+02:00`.
+    ///
+    /// See [`FileLogWriterBuilder::file_header`].
+    #[must_use]
+    pub fn app_info_file_header<S: AsRef<str>, V: AsRef<str>>(
+        mut self,
+        app_name: S,
+        version: V,
+    ) -> Self {
+        let line = format!(
+            "{} {}; started: {}",
+            app_name.as_ref(),
+            version.as_ref(),
+            clock_now().to_rfc3339_opts(chrono::SecondsFormat::Millis, false)
+        );
+        self.o_file_header = Some(FileHeader::Line(line));
+        self
+    }
+
+    /// Registers a function that writes a footer into every output file
+    /// right before the file is closed, e.g. for terminating a JSON array or an XML document.
+    ///
+    /// The function is called when a file is closed by rotation,
+    /// by [`FileLogWriter::rotate_to`], or by the shutdown of the [`FileLogWriter`].
+    /// If further output is written after the shutdown, the file is reopened and continued
+    /// behind the footer.
+    ///
+    /// See also [`FileLogWriterBuilder::file_header`].
+    #[must_use]
+    pub fn file_footer<F>(mut self, footer: F) -> Self
+    where
+        F: Fn(&mut dyn std::io::Write) -> std::io::Result<()> + Send + Sync + 'static,
+    {
+        self.o_file_footer = Some(FileFooter(Arc::new(footer)));
+        self
+    }
+
     /// Set the maximum log level.
     ///
     /// The default is `log::LevelFilter::Trace`, i.e., all log levels are written.
@@ -142,7 +502,10 @@ impl FileLogWriterBuilder {
         self
     }
 
-    /// Enforces the use of UTC, rather than local time.
+    /// Enforces the use of UTC, rather than local time, for the timestamps in the file names.
+    ///
+    /// The timestamps in the log lines are produced by the format function, usually with
+    /// [`DeferredNow`](crate::DeferredNow), and are not affected by this option.
     #[must_use]
     pub fn use_utc(mut self) -> Self {
         self.file_spec.use_utc = true;
@@ -150,14 +513,110 @@ impl FileLogWriterBuilder {
         self
     }
 
-    /// The specified String will be used on unix systems to create in the current folder
-    /// a symbolic link to the current log file.
+    /// Makes the age criterion of the rotation (see [`Criterion`](crate::Criterion))
+    /// be evaluated in UTC, rather than in local time.
+    ///
+    /// With `Age::Day`, e.g., the log file is then rotated at UTC midnight.
+    #[must_use]
+    pub fn use_utc_for_rotation(mut self) -> Self {
+        self.use_utc_for_rotation = true;
+        self
+    }
+
+    /// Defines when the log file is synced to the storage device.
+    ///
+    /// Default is [`SyncPolicy::Never`].
+    #[must_use]
+    pub fn sync_policy(mut self, sync_policy: SyncPolicy) -> Self {
+        self.sync_policy = sync_policy;
+        self
+    }
+
+    /// Limits the length of the formatted log lines.
+    ///
+    /// Longer log lines are cut off after `max_len` bytes (at a character boundary),
+    /// and a marker like `… [truncated 12345 bytes]` is appended.
+    #[must_use]
+    pub fn max_line_length(mut self, max_len: usize) -> Self {
+        self.o_max_line_length = Some(max_len);
+        self
+    }
+
+    /// Makes the [`FileLogWriter`] escape control characters in the formatted log lines.
+    ///
+    /// Line breaks e.g. are written as `\n`, and the escape character that starts
+    /// ANSI escape sequences is written as `\u{1b}`; bytes that are no valid UTF-8
+    /// are written like `\xff`.
+    /// This protects against log injection and terminal escape attacks if untrusted data
+    /// is logged. Note that with this option the coloring of the provided coloring
+    /// format functions is escaped as well.
+    #[must_use]
+    pub fn escape_control_chars(mut self) -> Self {
+        self.escape_control_chars = true;
+        self
+    }
+
+    /// Defines how the [`FileLogWriter`] behaves if writing to the log file fails repeatedly,
+    /// e.g. because the disk is full.
+    ///
+    /// Default is [`WriteFailurePolicy::Report`].
+    #[must_use]
+    pub fn write_failure_policy(mut self, policy: WriteFailurePolicy) -> Self {
+        self.write_failure_policy = policy;
+        self
+    }
+
+    /// Makes the [`FileLogWriter`] detect if the output file was rotated by an external tool,
+    /// like linux' `logrotate`, and re-open the output file in that case.
+    ///
+    /// When a log line is written, and the last check is at least `check_interval` ago,
+    /// the `FileLogWriter` checks if the path of the output file still refers to the file
+    /// it writes to. It re-opens the output file if the file was renamed or deleted,
+    /// or if it was truncated (which is what `logrotate` does with option `copytruncate`).
+    /// This is an alternative to calling
+    /// [`LoggerHandle::reopen_output`](crate::LoggerHandle::reopen_output)
+    /// from a signal handler.
+    ///
+    /// Each check costs a call to the file system; with `Duration::ZERO`,
+    /// the check is done with every log line.
+    ///
+    /// Renaming and replacing the file is only detected on unix systems;
+    /// on other platforms only the deletion and the truncation of the file are detected.
+    #[must_use]
+    pub fn detect_external_rotation(mut self, check_interval: Duration) -> Self {
+        self.o_external_rotation_check = Some(check_interval);
+        self
+    }
+
+    /// The specified path will be used to create a link to the current log file,
+    /// which is updated with every rotation.
+    ///
+    /// On unix systems, this is a symbolic link.
+    /// On windows, a symbolic link is created if the process is allowed to do so
+    /// (which needs a privilege or the developer mode), otherwise a hard link,
+    /// and if that fails too (e.g. because the link is on another volume),
+    /// a small text file that contains the path of the current log file.
     #[must_use]
     pub fn create_symlink<P: Into<PathBuf>>(mut self, symlink: P) -> Self {
         self.cfg_o_create_symlink = Some(symlink.into());
         self
     }
 
+    /// With true, removes artifacts that an earlier run left behind, e.g. after a crash,
+    /// before the output file is opened.
+    ///
+    /// Removed are symbolic links that point to no longer existing log files
+    /// (including the one that is configured with [`FileLogWriterBuilder::create_symlink`],
+    /// which is then created anew), and the empty temporary files that reopening the output
+    /// file can leave behind.
+    /// Only artifacts are considered whose names (or whose link targets) start with the fixed
+    /// part of the file names of this [`FileSpec`], so foreign files are not touched.
+    #[must_use]
+    pub fn remove_stale_artifacts(mut self, remove: bool) -> Self {
+        self.cfg_remove_stale_artifacts = remove;
+        self
+    }
+
     /// Use Windows line endings, rather than just `\n`.
     #[must_use]
     pub fn use_windows_line_ending(mut self) -> Self {
@@ -174,27 +633,97 @@ impl FileLogWriterBuilder {
         self
     }
 
-    pub(crate) fn assert_write_mode(&self, write_mode: WriteMode) -> Result<(), FlexiLoggerError> {
-        if self.cfg_write_mode == write_mode {
-            Ok(())
-        } else {
-            Err(FlexiLoggerError::Reset)
-        }
-    }
-
     #[must_use]
     pub(crate) fn get_write_mode(&self) -> &WriteMode {
         &self.cfg_write_mode
     }
 
+    /// Returns the combinations of options that are accepted, but don't have the intended
+    /// effect, like [`FileLogWriterBuilder::append`] with timestamps in the file names.
+    ///
+    /// The check is optional; the `FileLogWriter` is built regardless of the warnings.
+    #[must_use]
+    pub fn check(&self) -> Vec<ConfigWarning> {
+        let mut warnings = Vec::new();
+        if self.cfg_append
+            && self.o_provided_output.is_none()
+            && self.file_spec.includes_timestamp()
+        {
+            warnings.push(ConfigWarning::AppendWithTimestamp);
+        }
+        match self.o_rotation_config {
+            None => {
+                for (is_set, option) in [
+                    (self.o_archiver.is_some(), "cleanup_archiver"),
+                    (self.compression.o_compressor.is_some(), "compressor"),
+                    (self.compression.workers > 1, "compression_workers"),
+                    (self.o_cleanup_observer.is_some(), "on_cleanup_failure"),
+                    (self.rotate_on_shutdown, "rotate_on_shutdown"),
+                    #[cfg(feature = "manifest")]
+                    (self.manifest, "manifest"),
+                ] {
+                    if is_set {
+                        warnings.push(ConfigWarning::OptionWithoutRotation(option));
+                    }
+                }
+            }
+            Some(RotationConfig {
+                naming: Naming::TimestampsCustomFormat { format, .. },
+                ..
+            }) => {
+                if let Err(problem) = validate_infix_format(format) {
+                    warnings.push(ConfigWarning::UnparsableNamingFormat(problem));
+                }
+            }
+            Some(_) => {}
+        }
+        warnings
+    }
+
+    pub(crate) fn uses_rotation(&self) -> bool {
+        self.o_rotation_config.is_some()
+    }
+
+    pub(crate) fn uses_append(&self) -> bool {
+        self.cfg_append
+    }
+
+    pub(crate) fn has_file_header(&self) -> bool {
+        self.o_file_header.is_some()
+    }
+
+    pub(crate) fn get_line_ending(&self) -> &'static [u8] {
+        self.cfg_line_ending
+    }
+
+    // Describes the rotation settings, for the config file header
+    pub(crate) fn describe_rotation(&self) -> String {
+        match self.o_rotation_config {
+            None => "none".to_string(),
+            Some(ref rotate_config) => format!(
+                "{:?}, {:?}, {:?}",
+                rotate_config.criterion, rotate_config.naming, rotate_config.cleanup
+            ),
+        }
+    }
+
     /// Produces the `FileLogWriter`.
     ///
     /// # Errors
     ///
     /// `FlexiLoggerError::Io` if the specified path doesn't work.
+    ///
+    /// `FlexiLoggerError::BadNamingFormat` if the format of
+    /// [`Naming::TimestampsCustomFormat`] is not usable.
+    ///
+    /// `FlexiLoggerError::BadFileSpec` if the basename, the discriminant, or the suffix
+    /// are not usable in file names, see [`FileSpec::sanitize`](crate::FileSpec::sanitize).
+    ///
+    /// `FlexiLoggerError::OutputInUse` if another `FileLogWriter` of this process,
+    /// which is not yet shut down, writes to the same files.
     pub fn try_build(self) -> Result<FileLogWriter, FlexiLoggerError> {
         Ok(FileLogWriter::new(
-            self.try_build_state()?,
+            self.try_build_state(None)?,
             self.max_log_level,
             self.format,
         ))
@@ -208,24 +737,39 @@ impl FileLogWriterBuilder {
     /// # Errors
     ///
     /// `FlexiLoggerError::Io` if the specified path doesn't work.
+    ///
+    /// `FlexiLoggerError::BadNamingFormat` if the format of
+    /// [`Naming::TimestampsCustomFormat`] is not usable.
+    ///
+    /// `FlexiLoggerError::BadFileSpec` if the basename, the discriminant, or the suffix
+    /// are not usable in file names, see [`FileSpec::sanitize`](crate::FileSpec::sanitize).
+    ///
+    /// `FlexiLoggerError::OutputInUse` if another `FileLogWriter` of this process,
+    /// which is not yet shut down, writes to the same files.
     pub fn try_build_with_handle(
         self,
     ) -> Result<(ArcFileLogWriter, FileLogWriterHandle), FlexiLoggerError> {
         Ok(ArcFileLogWriter::new_with_handle(FileLogWriter::new(
-            self.try_build_state()?,
+            self.try_build_state(None)?,
             self.max_log_level,
             self.format,
         )))
     }
 
-    pub(super) fn try_build_state(&self) -> Result<State, FlexiLoggerError> {
-        // make sure the folder exists or create it
-        let dir = self.file_spec.get_directory();
-        let p_directory = Path::new(&dir);
-        std::fs::create_dir_all(p_directory)?;
-        if !std::fs::metadata(p_directory)?.is_dir() {
-            return Err(FlexiLoggerError::OutputBadDirectory);
-        };
+    // A FileLogWriter that is reset hands in its current registration.
+    pub(super) fn try_build_state(
+        &self,
+        o_current_registration: Option<&Registration>,
+    ) -> Result<State, FlexiLoggerError> {
+        if let Some(RotationConfig {
+            naming: Naming::TimestampsCustomFormat { format, .. },
+            ..
+        }) = self.o_rotation_config
+        {
+            validate_infix_format(format).map_err(FlexiLoggerError::BadNamingFormat)?;
+        }
+        let mut file_spec = self.file_spec.clone().validated()?;
+        file_spec.compressed_suffix = self.compression.suffix().to_string();
 
         #[cfg(feature = "async")]
         let cleanup_in_background_thread = if let WriteMode::AsyncWith {
@@ -241,18 +785,89 @@ impl FileLogWriterBuilder {
         #[cfg(not(feature = "async"))]
         let cleanup_in_background_thread = self.cleanup_in_background_thread;
 
+        let mut config = FileLogWriterConfig {
+            print_message: self.cfg_print_message,
+            append: self.cfg_append || self.cfg_multi_process,
+            multi_process: self.cfg_multi_process,
+            recover_on_append: self.cfg_recover_on_append,
+            keep_corrupt_tail: self.cfg_keep_corrupt_tail,
+            rotation_lock: self.cfg_rotation_lock,
+            line_ending: self.cfg_line_ending,
+            write_mode: self.cfg_write_mode,
+            file_spec,
+            o_create_symlink: self.cfg_o_create_symlink.clone(),
+            remove_stale_artifacts: self.cfg_remove_stale_artifacts,
+            use_utc: self.use_utc,
+            use_utc_for_rotation: self.use_utc_for_rotation,
+            sync_policy: self.sync_policy,
+            o_external_rotation_check: self.o_external_rotation_check,
+            write_failure_policy: self.write_failure_policy,
+            o_max_line_length: self.o_max_line_length,
+            escape_control_chars: self.escape_control_chars,
+            o_provided_output: self.o_provided_output.clone(),
+            fallbacks: self.fallbacks.clone(),
+        };
+
+        if config.o_provided_output.is_none() {
+            // make sure the folder exists or create it, otherwise try the fallbacks
+            let dir = config.file_spec.get_directory();
+            let p_directory = Path::new(&dir);
+            let with_context = |e| {
+                io_err_with_context(
+                    e,
+                    p_directory.display().to_string(),
+                    "FileSpec::directory",
+                    "Specify a directory that can be created and written by the program.",
+                )
+            };
+            if let Err(e) = std::fs::create_dir_all(p_directory) {
+                if !fall_back(&mut config, &e) {
+                    return Err(with_context(e).into());
+                }
+            } else if !std::fs::metadata(p_directory)
+                .map_err(with_context)?
+                .is_dir()
+            {
+                return Err(FlexiLoggerError::OutputBadDirectory);
+            }
+        }
+
+        // a provided output is not shared by accident
+        let o_registration = if config.o_provided_output.is_none() {
+            Some(Registration::register(
+                &config.file_spec,
+                o_current_registration,
+            )?)
+        } else {
+            None
+        };
+
+        // a provided output can't be rotated
+        let o_rotation_config = self
+            .o_rotation_config
+            .clone()
+            .filter(|_| config.o_provided_output.is_none())
+            .map(|rotation_config| RotationConfig {
+                o_archiver: self.o_archiver.clone(),
+                compression: self.compression.clone(),
+                o_cleanup_observer: self.o_cleanup_observer.clone(),
+                rotate_on_shutdown: self.rotate_on_shutdown,
+                #[cfg(feature = "manifest")]
+                manifest: self.manifest,
+                ..rotation_config
+            });
         Ok(State::new(
-            FileLogWriterConfig {
-                print_message: self.cfg_print_message,
-                append: self.cfg_append,
-                line_ending: self.cfg_line_ending,
-                write_mode: self.cfg_write_mode,
-                file_spec: self.file_spec.clone(),
-                o_create_symlink: self.cfg_o_create_symlink.clone(),
-                use_utc: self.use_utc,
-            },
-            self.o_rotation_config.clone(),
+            config,
+            o_rotation_config,
             cleanup_in_background_thread,
+            self.o_banner.clone().map(|text| Banner {
+                text,
+                format: self.format,
+                o_forward: self.o_banner_forward.clone(),
+            }),
+            self.o_file_header.clone(),
+            self.o_file_footer.clone(),
+            o_registration,
         ))
     }
 }
@@ -286,6 +901,12 @@ impl FileLogWriterBuilder {
                 criterion,
                 naming,
                 cleanup,
+                o_archiver: None,
+                compression: Compression::default(),
+                o_cleanup_observer: None,
+                rotate_on_shutdown: false,
+                #[cfg(feature = "manifest")]
+                manifest: false,
             });
             self.file_spec.if_default_use_timestamp(false);
         } else {
@@ -1,5 +1,16 @@
-use crate::{Cleanup, Criterion, FileSpec, Naming, WriteMode};
-use std::path::PathBuf;
+use crate::{
+    parameters::{Archiver, CleanupObserver, Compression},
+    util::PostFormat,
+    Cleanup, Criterion, Fallback, FileSpec, FormatFunction, Naming, SyncPolicy, WriteFailurePolicy,
+    WriteMode,
+};
+use std::{
+    fs::File,
+    io::Write,
+    path::PathBuf,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 
 /// Describes how rotation should work
 #[derive(Clone, Debug)]
@@ -10,18 +21,108 @@ pub struct RotationConfig {
     pub(crate) naming: Naming,
     // Defines the cleanup strategy
     pub(crate) cleanup: Cleanup,
+    // Is called with each file before the cleanup deletes it
+    pub(crate) o_archiver: Option<Archiver>,
+    // Defines how files are compressed
+    pub(crate) compression: Compression,
+    // Is called with the updated status after each cleanup failure
+    pub(crate) o_cleanup_observer: Option<CleanupObserver>,
+    // Defines if the current file should be rotated and cleaned up on shutdown
+    pub(crate) rotate_on_shutdown: bool,
+    // Defines if rotated files should be recorded in a manifest file
+    #[cfg(feature = "manifest")]
+    pub(crate) manifest: bool,
+}
+
+// Is written at the beginning of every new output file.
+#[derive(Clone)]
+pub(crate) enum FileHeader {
+    Custom(Arc<WriteFn>),
+    // a line of text, to which the configured line ending is appended
+    Line(String),
+}
+pub(crate) type WriteFn = dyn Fn(&mut dyn Write) -> std::io::Result<()> + Send + Sync;
+impl FileHeader {
+    pub(crate) fn write(&self, w: &mut dyn Write, line_ending: &[u8]) -> std::io::Result<()> {
+        match self {
+            Self::Custom(f) => f(w),
+            Self::Line(line) => {
+                w.write_all(line.as_bytes())?;
+                w.write_all(line_ending)
+            }
+        }
+    }
+}
+impl std::fmt::Debug for FileHeader {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Custom(_) => f.write_str("FileHeader::Custom"),
+            Self::Line(line) => f.write_fmt(format_args!("FileHeader::Line({line:?})")),
+        }
+    }
+}
+
+// Is written at the beginning of every new output file that is created by rotation.
+#[derive(Clone)]
+pub(crate) struct Banner {
+    pub(crate) text: String,
+    pub(crate) format: FormatFunction,
+    // is called with the banner record after it was written, to pass it to other writers
+    pub(crate) o_forward: Option<Arc<RecordFn>>,
+}
+pub(crate) type RecordFn = dyn Fn(&log::Record) + Send + Sync;
+impl std::fmt::Debug for Banner {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("Banner({:?})", self.text))
+    }
+}
+
+// Is written at the end of every output file, before it is closed.
+#[derive(Clone)]
+pub(crate) struct FileFooter(pub(crate) Arc<WriteFn>);
+impl std::fmt::Debug for FileFooter {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("FileFooter")
+    }
+}
+
+// Is used as output instead of a file that is opened by path.
+#[derive(Clone, Debug)]
+pub(crate) enum ProvidedOutput {
+    // see FileLogWriterBuilder::with_open_file
+    File(Arc<File>),
+    // see Logger::capture_file_output
+    Memory(Arc<Mutex<Vec<u8>>>),
+    // see Fallback::Stderr
+    Stderr,
 }
 
 /// Configuration of a `FileLogWriter`.
+#[allow(clippy::struct_excessive_bools)]
 #[derive(Debug, Clone)]
 pub struct FileLogWriterConfig {
     pub(crate) print_message: bool,
     pub(crate) append: bool,
+    pub(crate) multi_process: bool,
+    pub(crate) recover_on_append: bool,
+    pub(crate) keep_corrupt_tail: bool,
+    pub(crate) rotation_lock: bool,
     pub(crate) write_mode: WriteMode,
     pub(crate) file_spec: FileSpec,
     pub(crate) o_create_symlink: Option<PathBuf>,
+    pub(crate) remove_stale_artifacts: bool,
     pub(crate) line_ending: &'static [u8],
     pub(crate) use_utc: bool,
+    pub(crate) use_utc_for_rotation: bool,
+    pub(crate) sync_policy: SyncPolicy,
+    pub(crate) o_external_rotation_check: Option<Duration>,
+    pub(crate) write_failure_policy: WriteFailurePolicy,
+    pub(crate) o_max_line_length: Option<usize>,
+    pub(crate) escape_control_chars: bool,
+    // if set, the output is written here, rather than to a file that is opened by path
+    pub(crate) o_provided_output: Option<ProvidedOutput>,
+    // the fallbacks that were not yet used
+    pub(crate) fallbacks: Vec<Fallback>,
 }
 
 impl FileLogWriterConfig {
@@ -55,12 +156,89 @@ impl FileLogWriterConfig {
         self.use_utc
     }
 
+    /// Returns `true` if the age criterion of the rotation is evaluated in UTC.
+    #[must_use]
+    pub fn use_utc_for_rotation(&self) -> bool {
+        self.use_utc_for_rotation
+    }
+
+    /// Returns the policy for syncing the log file to the storage device.
+    #[must_use]
+    pub fn sync_policy(&self) -> SyncPolicy {
+        self.sync_policy
+    }
+
+    /// Returns `true` if the log is written to a provided file handle,
+    /// see [`FileLogWriterBuilder::with_open_file`](crate::writers::FileLogWriterBuilder::with_open_file).
+    #[must_use]
+    pub fn writes_to_open_file(&self) -> bool {
+        matches!(self.o_provided_output, Some(ProvidedOutput::File(_)))
+    }
+
+    // The buffer in which the output is captured, if any.
+    pub(crate) fn captured_output(&self) -> Option<&Arc<Mutex<Vec<u8>>>> {
+        match self.o_provided_output {
+            Some(ProvidedOutput::Memory(ref buffer)) => Some(buffer),
+            _ => None,
+        }
+    }
+
     /// Returns `true` if existing files are appended on program start.
     #[must_use]
     pub fn append(&self) -> bool {
         self.append
     }
 
+    /// Returns `true` if an incomplete last line of an appended file is removed on program start.
+    #[must_use]
+    pub fn recover_on_append(&self) -> bool {
+        self.recover_on_append
+    }
+
+    /// Returns `true` if the file is prepared for being written by several processes.
+    #[must_use]
+    pub fn multi_process_append(&self) -> bool {
+        self.multi_process
+    }
+
+    /// Returns `true` if rotation and cleanup are protected with a lock file.
+    #[must_use]
+    pub fn rotation_lock(&self) -> bool {
+        self.rotation_lock
+    }
+
+    /// Returns the interval in which the output file is checked for external rotation,
+    /// if this check is configured.
+    #[must_use]
+    pub fn external_rotation_check(&self) -> Option<Duration> {
+        self.o_external_rotation_check
+    }
+
+    /// Returns the maximum length of the formatted log lines, if one is configured.
+    #[must_use]
+    pub fn max_line_length(&self) -> Option<usize> {
+        self.o_max_line_length
+    }
+
+    /// Returns `true` if control characters in the log lines are escaped.
+    #[must_use]
+    pub fn escape_control_chars(&self) -> bool {
+        self.escape_control_chars
+    }
+
+    pub(crate) fn post_format(&self) -> PostFormat {
+        PostFormat {
+            o_max_line_length: self.o_max_line_length,
+            escape_control_chars: self.escape_control_chars,
+        }
+    }
+
+    /// Returns the behavior in case of repeated write failures.
+    #[must_use]
+    pub fn write_failure_policy(&self) -> WriteFailurePolicy {
+        self.write_failure_policy
+    }
+
     /// Returns `true` if a message should be printed on program start
     /// to which file the log is written.
     #[must_use]
@@ -1,9 +1,11 @@
 use super::state::{timestamp_from_ts_infix, InfixFormat};
 
-#[derive(Clone)]
+#[derive(Clone, Debug)]
 pub(crate) enum InfixFilter {
     Timstmps(InfixFormat),
     Numbrs,
+    // the rotated files have no infix, but a number after the suffix
+    ShiftedNumbrs,
     #[cfg(test)]
     StartsWth(String),
     Equls(String),
@@ -13,20 +15,16 @@ impl InfixFilter {
     pub(crate) fn filter_infix(&self, infix: &str) -> bool {
         match self {
             InfixFilter::Timstmps(infix_format) => {
-                timestamp_from_ts_infix(infix, infix_format).is_ok()
-            }
-            InfixFilter::Numbrs => {
-                if infix.len() > 2 {
-                    let mut chars = infix.chars();
-                    chars.next().unwrap() == 'r' && chars.next().unwrap().is_ascii_digit()
-                } else {
-                    false
-                }
+                timestamp_from_ts_infix(infix, false, infix_format).is_ok()
             }
+            // the number can have any width
+            InfixFilter::Numbrs => infix.strip_prefix('r').is_some_and(|number| {
+                !number.is_empty() && number.bytes().all(|b| b.is_ascii_digit())
+            }),
             #[cfg(test)]
             InfixFilter::StartsWth(s) => infix.starts_with(s),
             InfixFilter::Equls(s) => infix.eq(s),
-            InfixFilter::None => false,
+            InfixFilter::ShiftedNumbrs | InfixFilter::None => false,
         }
     }
 }
@@ -0,0 +1,52 @@
+//! Detects if several `FileLogWriter`s of the process would write to the same files.
+use crate::{FileSpec, FlexiLoggerError};
+use std::{
+    collections::HashMap,
+    path::PathBuf,
+    sync::{Arc, Mutex, OnceLock, Weak},
+};
+
+// Is held by the state of a FileLogWriter, until it is shut down or dropped.
+#[derive(Clone, Debug)]
+pub(super) struct Registration(Arc<PathBuf>);
+
+// The paths of the registered file specs, without infix
+fn registry() -> &'static Mutex<HashMap<PathBuf, Weak<PathBuf>>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<PathBuf, Weak<PathBuf>>>> = OnceLock::new();
+    REGISTRY.get_or_init(Mutex::default)
+}
+
+impl Registration {
+    // Fails if another FileLogWriter holds a registration for the same files;
+    // the current registration of the resetted FileLogWriter is reused if it fits.
+    pub(super) fn register(
+        file_spec: &FileSpec,
+        o_current: Option<&Registration>,
+    ) -> Result<Self, FlexiLoggerError> {
+        let key = key_of(file_spec);
+        if let Some(current) = o_current {
+            if *current.0 == key {
+                return Ok(current.clone());
+            }
+        }
+
+        let mut registry = registry().lock().map_err(|_| FlexiLoggerError::Poison)?;
+        registry.retain(|_, registration| registration.strong_count() > 0);
+        if registry.contains_key(&key) {
+            return Err(FlexiLoggerError::OutputInUse(key));
+        }
+        let registration = Arc::new(key.clone());
+        registry.insert(key, Arc::downgrade(&registration));
+        Ok(Self(registration))
+    }
+}
+
+// The same directory can be specified in different ways
+fn key_of(file_spec: &FileSpec) -> PathBuf {
+    let path = file_spec.as_pathbuf(None);
+    let directory = file_spec.get_directory();
+    match (directory.canonicalize(), path.file_name()) {
+        (Ok(directory), Some(file_name)) => directory.join(file_name),
+        _ => path,
+    }
+}
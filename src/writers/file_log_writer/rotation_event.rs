@@ -0,0 +1,43 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::mpsc::{channel, Receiver, Sender},
+};
+
+/// Information about a rotation of the log file.
+///
+/// See [`LoggerHandle::subscribe_rotation`](crate::LoggerHandle::subscribe_rotation)
+/// and [`FileLogWriter::subscribe_rotation`](crate::writers::FileLogWriter::subscribe_rotation).
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct RotationEvent {
+    /// The completed log file, with the name it got with the rotation.
+    pub old_path: PathBuf,
+    /// The log file that is written from now on.
+    ///
+    /// Is `None` for the final rotation with
+    /// [`FileLogWriterBuilder::rotate_on_shutdown`](crate::writers::FileLogWriterBuilder::rotate_on_shutdown).
+    pub new_path: Option<PathBuf>,
+}
+
+// The subscribers of rotation events; receivers that were dropped are removed
+// with the next event.
+#[derive(Debug, Default)]
+pub(super) struct RotationSubscribers(Vec<Sender<RotationEvent>>);
+impl RotationSubscribers {
+    pub(super) fn subscribe(&mut self) -> Receiver<RotationEvent> {
+        let (sender, receiver) = channel();
+        self.0.push(sender);
+        receiver
+    }
+
+    pub(super) fn notify(&mut self, old_path: &Path, new_path: Option<&Path>) {
+        if self.0.is_empty() {
+            return;
+        }
+        let event = RotationEvent {
+            old_path: old_path.to_path_buf(),
+            new_path: new_path.map(Path::to_path_buf),
+        };
+        self.0.retain(|sender| sender.send(event.clone()).is_ok());
+    }
+}
@@ -1,36 +1,64 @@
+mod external_rotation;
+mod failure_watchdog;
+mod fallback;
+mod hygiene;
 mod list_and_cleanup;
+#[cfg(feature = "manifest")]
+mod manifest;
 mod numbers;
+mod recovery;
+mod rotation_lock;
+mod shifted_numbers;
 mod timestamps;
 
-pub(crate) use timestamps::timestamp_from_ts_infix;
+pub(crate) use fallback::fall_back;
+pub(crate) use timestamps::{timestamp_from_ts_infix, validate_infix_format};
 
 use super::{
-    config::{FileLogWriterConfig, RotationConfig},
-    InfixFilter,
+    config::{Banner, FileFooter, FileHeader, FileLogWriterConfig, ProvidedOutput, RotationConfig},
+    registry::Registration,
+    rotation_event::RotationSubscribers,
+    InfixFilter, RotationEvent,
 };
 #[cfg(feature = "async")]
-use crate::util::eprint_msg;
+use crate::resources::ThreadRegistration;
 use crate::{
-    util::{eprint_err, ErrorCode},
-    Age, Cleanup, Criterion, FlexiLoggerError, LogfileSelector, Naming,
+    deferred_now::{clock_is_set, clock_now},
+    parameters::{Archiver, CleanupMonitor, Compression},
+    resources::RegisteredFile,
+    threads::{spawn_flusher, FlusherHandle},
+    util::{eprint_err, eprint_msg, io_err, with_banner_record, ErrorCode},
+    Age, Cleanup, CleanupStatus, Criterion, DeferredNow, FlexiLoggerError, LogfileSelector, Naming,
+    SyncPolicy,
 };
-use chrono::{DateTime, Datelike, Local, Timelike};
+use chrono::{DateTime, Datelike, Local, NaiveDate, TimeZone, Timelike, Utc, Weekday};
+use external_rotation::ExternalRotationDetector;
+use failure_watchdog::WriteFailureWatchdog;
+#[cfg(feature = "manifest")]
+use manifest::{Manifest, WriteSpan};
+use numbers::Numbering;
+use rotation_lock::RotationLock;
 #[cfg(feature = "async")]
 use std::thread::JoinHandle;
 use std::{
     fs::{remove_file, File, OpenOptions},
     io::{BufRead, BufReader, BufWriter, Write},
     path::{Path, PathBuf},
-    sync::{Arc, Mutex},
+    sync::{mpsc::Receiver, Arc, Mutex},
 };
 use timestamps::{creation_timestamp_of_currentfile, infix_from_timestamp, latest_timestamp_file};
 
 #[cfg(feature = "async")]
 const ASYNC_FLUSHER: &str = "flexi_logger-fs-async_flusher";
+const SYNC_FLUSHER: &str = "flexi_logger-file_flusher";
+// used in place of the path of the output file if the output was provided
+const PROVIDED_FILE_PATH: &str = "<provided file handle>";
+const MEMORY_PATH: &str = "<captured in memory>";
+const STDERR_PATH: &str = "<stderr>";
 
 #[cfg(feature = "async")]
 use {
-    crate::util::{ASYNC_FLUSH, ASYNC_SHUTDOWN},
+    crate::util::{FlushAcks, ASYNC_FLUSH, ASYNC_FLUSH_AND_ACK, ASYNC_SHUTDOWN},
     crossbeam_channel::Sender as CrossbeamSender,
     crossbeam_queue::ArrayQueue,
 };
@@ -50,17 +78,20 @@ enum NamingState {
         infix_format: InfixFormat,
     },
 
-    // contains the index to which we will rotate
-    NumbersRCurrent(u32),
+    // contains the index to which we will rotate, and the width and start of the numbers
+    NumbersRCurrent(u32, Numbering),
 
-    // contains the index of the current output file
-    NumbersDirect(u32),
+    // contains the index of the current output file, and the width and start of the numbers
+    NumbersDirect(u32, Numbering),
+
+    // the rotated files are found and shifted using the file system
+    NumbersShifted,
 }
 impl NamingState {
     pub(crate) fn writes_direct(&self) -> bool {
         matches!(
             self,
-            NamingState::NumbersDirect(_)
+            NamingState::NumbersDirect(..)
                 | NamingState::Timestamps {
                     current_timestamp: _,
                     the_current_infix: None,
@@ -76,7 +107,10 @@ impl NamingState {
                 the_current_infix: _,
                 infix_format,
             } => InfixFilter::Timstmps(infix_format.clone()),
-            NamingState::NumbersDirect(_) | NamingState::NumbersRCurrent(_) => InfixFilter::Numbrs,
+            NamingState::NumbersDirect(..) | NamingState::NumbersRCurrent(..) => {
+                InfixFilter::Numbrs
+            }
+            NamingState::NumbersShifted => InfixFilter::ShiftedNumbrs,
         }
     }
 }
@@ -107,17 +141,24 @@ enum RollState {
     },
     Age {
         age: Age,
+        use_utc: bool,
         created_at: DateTime<Local>,
     },
     AgeOrSize {
         age: Age,
+        use_utc: bool,
         created_at: DateTime<Local>,
         max_size: u64,
         current_size: u64,
     },
 }
 impl RollState {
-    fn new(criterion: Criterion, append: bool, path: &Path) -> Result<RollState, std::io::Error> {
+    fn new(
+        criterion: Criterion,
+        use_utc: bool,
+        append: bool,
+        path: &Path,
+    ) -> Result<RollState, std::io::Error> {
         let current_size = if append {
             std::fs::metadata(path)?.len()
         } else {
@@ -126,13 +167,18 @@ impl RollState {
         let created_at = get_creation_timestamp(path);
 
         Ok(match criterion {
-            Criterion::Age(age) => RollState::Age { age, created_at },
+            Criterion::Age(age) => RollState::Age {
+                age,
+                use_utc,
+                created_at,
+            },
             Criterion::Size(max_size) => RollState::Size {
                 max_size,
                 current_size,
             },
             Criterion::AgeOrSize(age, max_size) => RollState::AgeOrSize {
                 age,
+                use_utc,
                 created_at,
                 max_size,
                 current_size,
@@ -146,15 +192,20 @@ impl RollState {
                 max_size,
                 current_size,
             } => Self::size_rotation_necessary(*max_size, *current_size),
-            RollState::Age { age, created_at } => Self::age_rotation_necessary(*age, created_at),
+            RollState::Age {
+                age,
+                use_utc,
+                created_at,
+            } => Self::age_rotation_necessary(*age, *use_utc, created_at),
             RollState::AgeOrSize {
                 age,
+                use_utc,
                 created_at,
                 max_size,
                 current_size,
             } => {
                 Self::size_rotation_necessary(*max_size, *current_size)
-                    || Self::age_rotation_necessary(*age, created_at)
+                    || Self::age_rotation_necessary(*age, *use_utc, created_at)
             }
         }
     }
@@ -163,9 +214,43 @@ impl RollState {
         current_size > max_size
     }
 
-    fn age_rotation_necessary(age: Age, created_at: &DateTime<Local>) -> bool {
-        let now = Local::now();
+    fn age_rotation_necessary(age: Age, use_utc: bool, created_at: &DateTime<Local>) -> bool {
+        Self::age_rotation_necessary_at(age, use_utc, created_at, &clock_now())
+    }
+
+    fn age_rotation_necessary_at<Tz: TimeZone>(
+        age: Age,
+        use_utc: bool,
+        created_at: &DateTime<Tz>,
+        now: &DateTime<Tz>,
+    ) -> bool {
+        // if the clock was set back, we wait until it has caught up, rather than rotating
+        // now and again when the clock passes the previous boundary
+        if now < created_at {
+            return false;
+        }
+        if use_utc {
+            Self::age_boundary_crossed(
+                age,
+                &created_at.with_timezone(&Utc),
+                &now.with_timezone(&Utc),
+            )
+        } else {
+            Self::age_boundary_crossed(age, created_at, now)
+        }
+    }
+
+    fn age_boundary_crossed<Tz: TimeZone>(
+        age: Age,
+        created_at: &DateTime<Tz>,
+        now: &DateTime<Tz>,
+    ) -> bool {
         match age {
+            Age::Month => created_at.year() != now.year() || created_at.month() != now.month(),
+            Age::Week(first_weekday) => {
+                start_of_week(created_at.naive_local().date(), first_weekday)
+                    != start_of_week(now.naive_local().date(), first_weekday)
+            }
             Age::Day => {
                 created_at.year() != now.year()
                     || created_at.month() != now.month()
@@ -203,11 +288,16 @@ impl RollState {
             } => {
                 *current_size = 0;
             }
-            RollState::Age { age: _, created_at } => {
+            RollState::Age {
+                age: _,
+                use_utc: _,
+                created_at,
+            } => {
                 *created_at = get_creation_timestamp(path);
             }
             RollState::AgeOrSize {
                 age: _,
+                use_utc: _,
                 created_at,
                 max_size: _,
                 current_size,
@@ -218,6 +308,37 @@ impl RollState {
         }
     }
 
+    // re-reads size and creation date from the file, which might have been replaced
+    // by another process
+    fn refresh(&mut self, path: &Path) {
+        let actual_size = std::fs::metadata(path).map_or(0, |md| md.len());
+        match self {
+            RollState::Size {
+                max_size: _,
+                current_size,
+            } => {
+                *current_size = actual_size;
+            }
+            RollState::Age {
+                age: _,
+                use_utc: _,
+                created_at,
+            } => {
+                *created_at = get_creation_timestamp(path);
+            }
+            RollState::AgeOrSize {
+                age: _,
+                use_utc: _,
+                created_at,
+                max_size: _,
+                current_size,
+            } => {
+                *created_at = get_creation_timestamp(path);
+                *current_size = actual_size;
+            }
+        }
+    }
+
     fn increase_size(&mut self, add: u64) {
         if let RollState::Size {
             max_size: _,
@@ -225,6 +346,7 @@ impl RollState {
         }
         | RollState::AgeOrSize {
             age: _,
+            use_utc: _,
             created_at: _,
             max_size: _,
             ref mut current_size,
@@ -240,9 +362,28 @@ struct RotationState {
     naming_state: NamingState,
     roll_state: RollState,
     cleanup: Cleanup,
+    o_archiver: Option<Archiver>,
+    compression: Compression,
     o_cleanup_thread_handle: Option<list_and_cleanup::CleanupThreadHandle>,
+    a_cleanup_monitor: Arc<CleanupMonitor>,
+    rotate_on_shutdown: bool,
+    #[cfg(feature = "manifest")]
+    o_manifest: Option<Arc<Manifest>>,
+    #[cfg(feature = "manifest")]
+    write_span: WriteSpan,
 }
 impl RotationState {
+    // Adds the rotated file to the manifest, if one is configured.
+    // With the direct namings, rotate_to has already moved the file away.
+    #[cfg(feature = "manifest")]
+    fn add_to_manifest(&mut self, rotated_path: &Path) {
+        if let Some(ref manifest) = self.o_manifest {
+            if rotated_path.exists() {
+                manifest.rotated(rotated_path, self.write_span.take());
+            }
+        }
+    }
+
     fn shutdown(&mut self) {
         // this sets o_cleanup_thread_handle in self.state.o_rotation_state to None:
         let o_cleanup_thread_handle = self.o_cleanup_thread_handle.take();
@@ -256,18 +397,20 @@ impl RotationState {
 enum Inner {
     Initial(Option<RotationConfig>, bool),
     Active(Option<RotationState>, Box<dyn Write + Send>, PathBuf),
+    // after shutdown, the output file is closed; it is reopened if further output is written
+    Closed(Option<RotationState>, PathBuf),
 }
 impl Inner {
     fn uses_rotation(&self) -> bool {
         match self {
             Inner::Initial(o_r, _) => o_r.is_some(),
-            Inner::Active(o_r, _, _) => o_r.is_some(),
+            Inner::Active(o_r, _, _) | Inner::Closed(o_r, _) => o_r.is_some(),
         }
     }
     fn infix_filter(&self) -> InfixFilter {
         match self {
             Inner::Initial(_o_r, _) => InfixFilter::None,
-            Inner::Active(o_r, _, _) => o_r
+            Inner::Active(o_r, _, _) | Inner::Closed(o_r, _) => o_r
                 .as_ref()
                 .map_or(InfixFilter::None, |rs| rs.naming_state.infix_filter()),
         }
@@ -280,6 +423,7 @@ impl std::fmt::Debug for Inner {
             Self::Active(o_rot, _, _) => {
                 f.write_fmt(format_args!("Active({o_rot:?}, <some-writer>) "))
             }
+            Self::Closed(o_rot, _) => f.write_fmt(format_args!("Closed({o_rot:?}) ")),
         }
     }
 }
@@ -289,25 +433,88 @@ impl std::fmt::Debug for Inner {
 pub(super) struct State {
     config: FileLogWriterConfig,
     inner: Inner,
+    o_banner: Option<Banner>,
+    o_file_header: Option<FileHeader>,
+    o_file_footer: Option<FileFooter>,
+    o_external_rotation: Option<ExternalRotationDetector>,
+    o_failure_watchdog: Option<WriteFailureWatchdog>,
+    rotation_subscribers: RotationSubscribers,
+    o_registration: Option<Registration>,
 }
 impl State {
     pub(super) fn new(
         config: FileLogWriterConfig,
         o_rotation_config: Option<RotationConfig>,
         cleanup_in_background_thread: bool,
+        o_banner: Option<Banner>,
+        o_file_header: Option<FileHeader>,
+        o_file_footer: Option<FileFooter>,
+        o_registration: Option<Registration>,
     ) -> Self {
+        // a provided output can't be rotated externally
+        let o_external_rotation = config
+            .o_external_rotation_check
+            .filter(|_| config.o_provided_output.is_none())
+            .map(ExternalRotationDetector::new);
+        let o_failure_watchdog = WriteFailureWatchdog::new(config.write_failure_policy);
         Self {
             config,
             inner: Inner::Initial(o_rotation_config, cleanup_in_background_thread),
+            o_banner,
+            o_file_header,
+            o_file_footer,
+            o_external_rotation,
+            o_failure_watchdog,
+            rotation_subscribers: RotationSubscribers::default(),
+            o_registration,
         }
     }
 
+    pub(super) fn registration(&self) -> Option<&Registration> {
+        self.o_registration.as_ref()
+    }
+
+    pub(super) fn subscribe_rotation(&mut self) -> Receiver<RotationEvent> {
+        self.rotation_subscribers.subscribe()
+    }
+
+    // The subscriptions survive the replacement of the state by a reset.
+    pub(super) fn take_rotation_subscribers(&mut self, old_state: &mut State) {
+        self.rotation_subscribers = std::mem::take(&mut old_state.rotation_subscribers);
+    }
+
+    // If the output file can't be opened, the configured fallbacks are tried.
     fn initialize(&mut self) -> Result<(), std::io::Error> {
+        loop {
+            match self.try_initialize() {
+                Ok(()) => return Ok(()),
+                Err(e) => {
+                    if !fall_back(&mut self.config, &e) {
+                        return Err(e);
+                    }
+                    if self.config.o_provided_output.is_some() {
+                        self.stop_rotation();
+                    }
+                }
+            }
+        }
+    }
+
+    fn try_initialize(&mut self) -> Result<(), std::io::Error> {
         if let Inner::Initial(o_rotation_config, cleanup_in_background_thread) = &self.inner {
+            if self.config.remove_stale_artifacts && self.config.o_provided_output.is_none() {
+                hygiene::remove_stale_artifacts(&self.config);
+            }
             self.inner = match o_rotation_config {
                 None => {
                     // no rotation
-                    let (write, path) = open_log_file(&self.config, None)?;
+                    let (mut write, path) = open_log_file(&self.config, None)?;
+                    write_file_header(
+                        self.o_file_header.as_ref(),
+                        &self.config,
+                        &path,
+                        &mut write,
+                    )?;
                     Inner::Active(None, write, path)
                 }
                 Some(rotate_config) => {
@@ -324,7 +531,13 @@ impl State {
         rotate_config: &RotationConfig,
         cleanup_in_background_thread: bool,
     ) -> Result<Inner, std::io::Error> {
-        let (naming_state, infix) = match rotate_config.naming {
+        let _o_lock = RotationLock::acquire_if_configured(&self.config)?;
+        #[cfg(feature = "manifest")]
+        let o_manifest = rotate_config
+            .manifest
+            .then(|| Arc::new(Manifest::new(&self.config.file_spec)));
+        // the third element is the current file of a previous run, if it is rotated now
+        let (naming_state, infix, o_rotated_path) = match rotate_config.naming {
             Naming::TimestampsDirect => {
                 let ts =
                     latest_timestamp_file(&self.config, !self.config.append, &InfixFormat::Std);
@@ -334,45 +547,51 @@ impl State {
                         the_current_infix: None,
                         infix_format: InfixFormat::Std,
                     },
-                    infix_from_timestamp(&ts, self.config.use_utc, &InfixFormat::Std),
+                    self.new_infix_from_timestamp(&ts, &InfixFormat::Std),
+                    None,
+                )
+            }
+            Naming::Timestamps => {
+                let (current_timestamp, o_rotated_path) = creation_timestamp_of_currentfile(
+                    &self.config,
+                    CURRENT_INFIX,
+                    !self.config.append,
+                    None,
+                    &InfixFormat::Std,
+                )?;
+                (
+                    NamingState::Timestamps {
+                        current_timestamp,
+                        the_current_infix: Some(CURRENT_INFIX.to_string()),
+                        infix_format: InfixFormat::Std,
+                    },
+                    CURRENT_INFIX.to_string(),
+                    o_rotated_path,
                 )
             }
-            Naming::Timestamps => (
-                NamingState::Timestamps {
-                    current_timestamp: creation_timestamp_of_currentfile(
-                        &self.config,
-                        CURRENT_INFIX,
-                        !self.config.append,
-                        None,
-                        &InfixFormat::Std,
-                    )?,
-                    the_current_infix: Some(CURRENT_INFIX.to_string()),
-                    infix_format: InfixFormat::Std,
-                },
-                CURRENT_INFIX.to_string(),
-            ),
             Naming::TimestampsCustomFormat {
                 current_infix: o_current_token,
                 format: ts_fmt,
             } => {
                 if let Some(current_token) = o_current_token {
                     let current_infix = current_token.to_string();
+                    let (current_timestamp, o_rotated_path) = creation_timestamp_of_currentfile(
+                        &self.config,
+                        &current_infix,
+                        !self.config.append,
+                        None,
+                        &InfixFormat::custom(ts_fmt),
+                    )?;
                     let naming_state = NamingState::Timestamps {
-                        current_timestamp: creation_timestamp_of_currentfile(
-                            &self.config,
-                            &current_infix,
-                            !self.config.append,
-                            None,
-                            &InfixFormat::custom(ts_fmt),
-                        )?,
+                        current_timestamp,
                         the_current_infix: Some(current_infix.clone()),
                         infix_format: InfixFormat::custom(ts_fmt),
                     };
-                    (naming_state, current_infix)
+                    (naming_state, current_infix, o_rotated_path)
                 } else {
                     let fmt = InfixFormat::custom(ts_fmt);
                     let ts = latest_timestamp_file(&self.config, !self.config.append, &fmt);
-                    let infix = infix_from_timestamp(&ts, self.config.use_utc, &fmt);
+                    let infix = self.new_infix_from_timestamp(&ts, &fmt);
                     (
                         NamingState::Timestamps {
                             current_timestamp: ts,
@@ -380,47 +599,98 @@ impl State {
                             infix_format: fmt,
                         },
                         infix,
+                        None,
                     )
                 }
             }
-            Naming::Numbers => (
-                NamingState::NumbersRCurrent(numbers::index_for_rcurrent(
-                    &self.config,
-                    None,
-                    !self.config.append,
-                )?),
-                CURRENT_INFIX.to_string(),
-            ),
-            Naming::NumbersDirect => {
-                let idx = match numbers::get_highest_index(&self.config.file_spec) {
-                    None => 0,
-                    Some(idx) => {
-                        if self.config.append {
-                            idx
-                        } else {
-                            idx + 1
-                        }
-                    }
+            Naming::Numbers | Naming::NumbersDirect | Naming::NumbersWith { .. } => {
+                let (direct, numbering) = Numbering::of(rotate_config.naming);
+                if direct {
+                    let o_highest_idx = numbers::get_highest_index(&self.config.file_spec);
+                    let idx = match o_highest_idx {
+                        Some(idx) if self.config.append => std::cmp::max(idx, numbering.next(None)),
+                        _ => numbering.next(o_highest_idx),
+                    };
+                    (
+                        NamingState::NumbersDirect(idx, numbering),
+                        numbering.infix(idx),
+                        None,
+                    )
+                } else {
+                    let (idx, o_rotated_path) = numbers::index_for_rcurrent(
+                        &self.config,
+                        numbering,
+                        None,
+                        !self.config.append,
+                    )?;
+                    (
+                        NamingState::NumbersRCurrent(idx, numbering),
+                        CURRENT_INFIX.to_string(),
+                        o_rotated_path,
+                    )
+                }
+            }
+            Naming::NumbersShifted => {
+                let o_rotated_path = if self.config.append {
+                    None
+                } else {
+                    shifted_numbers::rotate(&self.config.file_spec)?
                 };
-                (NamingState::NumbersDirect(idx), numbers::number_infix(idx))
+                (NamingState::NumbersShifted, String::new(), o_rotated_path)
             }
         };
-        let (write, path) = open_log_file(&self.config, Some(&infix))?;
-        let roll_state = RollState::new(rotate_config.criterion, self.config.append, &path)?;
+        // the rotated file of a previous run is only needed for the manifest
+        #[cfg(feature = "manifest")]
+        if let (Some(manifest), Some(rotated_path)) = (&o_manifest, &o_rotated_path) {
+            manifest.rotated(rotated_path, WriteSpan::default());
+        }
+        #[cfg(not(feature = "manifest"))]
+        drop(o_rotated_path);
+        let (mut write, path) = open_log_file(&self.config, Some(&infix))?;
+        let mut roll_state = RollState::new(
+            rotate_config.criterion,
+            self.config.use_utc_for_rotation,
+            self.config.append,
+            &path,
+        )?;
+        roll_state.increase_size(write_file_header(
+            self.o_file_header.as_ref(),
+            &self.config,
+            &path,
+            &mut write,
+        )?);
+        let a_cleanup_monitor = Arc::new(CleanupMonitor::new(
+            rotate_config.o_cleanup_observer.clone(),
+        ));
         let o_cleanup_thread_handle = if rotate_config.cleanup.do_cleanup() {
             list_and_cleanup::remove_or_compress_too_old_logfiles(
                 None,
                 &rotate_config.cleanup,
+                rotate_config.o_archiver.as_ref(),
+                &rotate_config.compression,
                 &self.config.file_spec,
                 &naming_state.infix_filter(),
                 rotate_config.naming.writes_direct(),
+                #[cfg(feature = "manifest")]
+                o_manifest.as_deref(),
+                &a_cleanup_monitor,
             )?;
-            if cleanup_in_background_thread {
-                Some(list_and_cleanup::start_cleanup_thread(
-                    rotate_config.cleanup,
-                    self.config.file_spec.clone(),
-                    &naming_state.infix_filter(),
-                    rotate_config.naming.writes_direct(),
+            // shifting the rotated files must not interfere with a concurrent cleanup
+            if cleanup_in_background_thread && !matches!(naming_state, NamingState::NumbersShifted)
+            {
+                Some(list_and_cleanup::CleanupThreadHandle::start(
+                    list_and_cleanup::CleanupThreadParams {
+                        cleanup: rotate_config.cleanup,
+                        o_archiver: rotate_config.o_archiver.clone(),
+                        compression: rotate_config.compression.clone(),
+                        file_spec: self.config.file_spec.clone(),
+                        infix_filter: naming_state.infix_filter(),
+                        writes_direct: rotate_config.naming.writes_direct(),
+                        use_rotation_lock: self.config.rotation_lock,
+                        #[cfg(feature = "manifest")]
+                        o_manifest: o_manifest.clone(),
+                        a_cleanup_monitor: Arc::clone(&a_cleanup_monitor),
+                    },
                 )?)
             } else {
                 None
@@ -433,13 +703,35 @@ impl State {
                 naming_state,
                 roll_state,
                 cleanup: rotate_config.cleanup,
+                o_archiver: rotate_config.o_archiver.clone(),
+                compression: rotate_config.compression.clone(),
                 o_cleanup_thread_handle,
+                a_cleanup_monitor,
+                rotate_on_shutdown: rotate_config.rotate_on_shutdown,
+                #[cfg(feature = "manifest")]
+                o_manifest,
+                #[cfg(feature = "manifest")]
+                write_span: WriteSpan::default(),
             }),
             write,
             path,
         ))
     }
 
+    // The infix for the initial output file with direct timestamp naming;
+    // if we don't append, an existing file with the same infix must not be overwritten,
+    // which can happen after a restart within the same second, or if the clock was set back
+    fn new_infix_from_timestamp(&self, ts: &DateTime<Local>, fmt: &InfixFormat) -> String {
+        let infix = infix_from_timestamp(ts, self.config.use_utc, fmt);
+        if self.config.append {
+            infix
+        } else {
+            self.config
+                .file_spec
+                .collision_free_infix_for_rotated_file(&infix)
+        }
+    }
+
     pub fn config(&self) -> &FileLogWriterConfig {
         &self.config
     }
@@ -453,10 +745,17 @@ impl State {
     }
 
     #[inline]
+    #[allow(clippy::too_many_lines)]
     pub(super) fn mount_next_linewriter_if_necessary(
         &mut self,
         force: bool,
     ) -> Result<(), FlexiLoggerError> {
+        if force && self.config.o_provided_output.is_some() {
+            return Err(FlexiLoggerError::OutputIo(
+                not_possible_with_provided_output("rotating the output"),
+            ));
+        }
+        let mut fell_back_to_stderr = false;
         if let Inner::Active(
             Some(ref mut rotation_state),
             ref mut current_write,
@@ -464,6 +763,28 @@ impl State {
         ) = self.inner
         {
             if force || rotation_state.roll_state.rotation_necessary() {
+                let _o_lock = RotationLock::acquire_if_configured(&self.config)?;
+                if self.config.rotation_lock
+                    && !force
+                    && !rotation_state.naming_state.writes_direct()
+                {
+                    // another process might have rotated the current file in the meantime
+                    rotation_state.roll_state.refresh(current_path);
+                    if !rotation_state.roll_state.rotation_necessary() {
+                        let file = OpenOptions::new()
+                            .create(true)
+                            .append(true)
+                            .open(&current_path)?;
+                        *current_write = wrap_log_file(&self.config, file, current_path)?;
+                        if let Some(ref mut detector) = self.o_external_rotation {
+                            detector.observe(current_path);
+                        }
+                        return Ok(());
+                    }
+                }
+                write_file_footer(self.o_file_footer.as_ref(), current_write)?;
+                // the direct namings leave the current file where it is
+                let mut o_rotated_path = Some(current_path.clone());
                 let infix = match rotation_state.naming_state {
                     NamingState::Timestamps {
                         current_timestamp: ref mut ts,
@@ -471,7 +792,7 @@ impl State {
                         infix_format: ref fmt,
                     } => {
                         if let Some(current_infix) = o_current_infix {
-                            *ts = creation_timestamp_of_currentfile(
+                            (*ts, o_rotated_path) = creation_timestamp_of_currentfile(
                                 &self.config,
                                 current_infix,
                                 true,
@@ -480,45 +801,239 @@ impl State {
                             )?;
                             current_infix.clone()
                         } else {
-                            *ts = Local::now();
+                            *ts = clock_now();
                             self.config.file_spec.collision_free_infix_for_rotated_file(
                                 &infix_from_timestamp(ts, self.config.use_utc, fmt),
                             )
                         }
                     }
-                    NamingState::NumbersRCurrent(ref mut idx_state) => {
-                        *idx_state =
-                            numbers::index_for_rcurrent(&self.config, Some(*idx_state), true)?;
+                    NamingState::NumbersRCurrent(ref mut idx_state, numbering) => {
+                        // with the lock, other processes might have rotated as well, so we
+                        // determine the next index from the file system
+                        let o_idx_state = if self.config.rotation_lock {
+                            None
+                        } else {
+                            Some(*idx_state)
+                        };
+                        (*idx_state, o_rotated_path) = numbers::index_for_rcurrent(
+                            &self.config,
+                            numbering,
+                            o_idx_state,
+                            true,
+                        )?;
                         CURRENT_INFIX.to_string()
                     }
-                    NamingState::NumbersDirect(ref mut idx_state) => {
+                    NamingState::NumbersDirect(ref mut idx_state, numbering) => {
                         *idx_state += 1;
-                        numbers::number_infix(*idx_state)
+                        if self.config.rotation_lock {
+                            if let Some(highest_idx) =
+                                numbers::get_highest_index(&self.config.file_spec)
+                            {
+                                *idx_state = std::cmp::max(*idx_state, highest_idx + 1);
+                            }
+                        }
+                        numbering.infix(*idx_state)
+                    }
+                    NamingState::NumbersShifted => {
+                        o_rotated_path = shifted_numbers::rotate(&self.config.file_spec)?;
+                        String::new()
                     }
                 };
-                let (new_write, new_path) = open_log_file(&self.config, Some(&infix))?;
+                let (new_write, new_path) =
+                    open_log_file_or_fall_back(&mut self.config, Some(&infix))?;
+                fell_back_to_stderr = self.config.o_provided_output.is_some();
 
+                // the old output file is closed here
                 *current_write = new_write;
                 *current_path = new_path;
+                if let Some(ref mut detector) = self.o_external_rotation {
+                    detector.observe(current_path);
+                }
+                if let Some(rotated_path) = o_rotated_path {
+                    #[cfg(feature = "manifest")]
+                    rotation_state.add_to_manifest(&rotated_path);
+                    if rotated_path.exists() {
+                        self.rotation_subscribers
+                            .notify(&rotated_path, Some(current_path));
+                    }
+                }
 
                 rotation_state.roll_state.reset_size_and_date(current_path);
 
+                let written = write_file_header(
+                    self.o_file_header.as_ref(),
+                    &self.config,
+                    current_path,
+                    current_write,
+                )? + write_banner(
+                    self.o_banner.as_ref(),
+                    self.config.line_ending,
+                    current_write,
+                )?;
+                rotation_state.roll_state.increase_size(written);
+
                 list_and_cleanup::remove_or_compress_too_old_logfiles(
-                    rotation_state.o_cleanup_thread_handle.as_ref(),
+                    rotation_state.o_cleanup_thread_handle.as_mut(),
                     &rotation_state.cleanup,
+                    rotation_state.o_archiver.as_ref(),
+                    &rotation_state.compression,
                     &self.config.file_spec,
                     &rotation_state.naming_state.infix_filter(),
                     rotation_state.naming_state.writes_direct(),
+                    #[cfg(feature = "manifest")]
+                    rotation_state.o_manifest.as_deref(),
+                    &rotation_state.a_cleanup_monitor,
                 )?;
             }
         }
+        if fell_back_to_stderr {
+            self.stop_rotation();
+        }
 
         Ok(())
     }
 
+    // A provided output can't be rotated.
+    fn stop_rotation(&mut self) {
+        match self.inner {
+            Inner::Initial(ref mut o_rotation_config, _) => *o_rotation_config = None,
+            Inner::Active(ref mut o_rotation_state, _, _)
+            | Inner::Closed(ref mut o_rotation_state, _) => {
+                if let Some(mut rotation_state) = o_rotation_state.take() {
+                    rotation_state.shutdown();
+                }
+            }
+        }
+        self.o_external_rotation = None;
+    }
+
+    // Renames the current output file to a file with the given infix and continues
+    // with a fresh output file.
+    // With namings that write directly to files with changing infixes, the fresh file
+    // gets the next regular infix, otherwise it is the (new) current file.
+    pub(super) fn rotate_to(&mut self, infix: &str) -> Result<(), FlexiLoggerError> {
+        if infix.is_empty() || infix.contains(['/', '\\']) {
+            return Err(FlexiLoggerError::OutputIo(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("\"{infix}\" is not usable as infix"),
+            )));
+        }
+        if self.config.o_provided_output.is_some() {
+            return Err(FlexiLoggerError::OutputIo(
+                not_possible_with_provided_output("rotating the output"),
+            ));
+        }
+        match self.inner {
+            Inner::Initial(_, _) => self.initialize()?,
+            Inner::Closed(_, _) => self.reopen_after_shutdown()?,
+            Inner::Active(_, _, _) => {}
+        }
+
+        let o_lock = RotationLock::acquire_if_configured(&self.config)?;
+        let (writes_direct, o_rotated_path) = if let Inner::Active(
+            ref mut o_rotation_state,
+            ref mut current_write,
+            ref mut current_path,
+        ) = self.inner
+        {
+            // close the current file before renaming it
+            write_file_footer(self.o_file_footer.as_ref(), current_write)?;
+            current_write.flush()?;
+            *current_write = Box::new(std::io::sink());
+            let target_path = self.config.file_spec.as_pathbuf(Some(
+                &self
+                    .config
+                    .file_spec
+                    .collision_free_infix_for_rotated_file(infix),
+            ));
+            std::fs::rename(&current_path, &target_path)?;
+            #[cfg(feature = "manifest")]
+            if let Some(ref mut rotation_state) = o_rotation_state {
+                rotation_state.add_to_manifest(&target_path);
+            }
+
+            let o_current_infix = match o_rotation_state {
+                None => None,
+                Some(ref mut rotation_state) => match rotation_state.naming_state {
+                    NamingState::Timestamps {
+                        current_timestamp: ref mut ts,
+                        the_current_infix: Some(ref current_infix),
+                        infix_format: _,
+                    } => {
+                        *ts = clock_now();
+                        Some(current_infix.clone())
+                    }
+                    NamingState::NumbersRCurrent(..) => Some(CURRENT_INFIX.to_string()),
+                    NamingState::NumbersShifted => Some(String::new()),
+                    NamingState::Timestamps {
+                        the_current_infix: None,
+                        ..
+                    }
+                    | NamingState::NumbersDirect(..) => None,
+                },
+            };
+            let writes_direct = o_rotation_state
+                .as_ref()
+                .is_some_and(|rs| rs.naming_state.writes_direct());
+            if !writes_direct {
+                let (new_write, new_path) =
+                    open_log_file(&self.config, o_current_infix.as_deref())?;
+                *current_write = new_write;
+                *current_path = new_path;
+                if let Some(ref mut detector) = self.o_external_rotation {
+                    detector.observe(current_path);
+                }
+                let written = write_file_header(
+                    self.o_file_header.as_ref(),
+                    &self.config,
+                    current_path,
+                    current_write,
+                )? + write_banner(
+                    self.o_banner.as_ref(),
+                    self.config.line_ending,
+                    current_write,
+                )?;
+                if let Some(ref mut rotation_state) = o_rotation_state {
+                    rotation_state.roll_state.reset_size_and_date(current_path);
+                    rotation_state.roll_state.increase_size(written);
+                }
+            }
+            (writes_direct, Some(target_path))
+        } else {
+            (false, None)
+        };
+        drop(o_lock);
+
+        if writes_direct {
+            self.mount_next_linewriter_if_necessary(true)?;
+        }
+        if let (Some(rotated_path), Inner::Active(_, _, ref current_path)) =
+            (o_rotated_path, &self.inner)
+        {
+            self.rotation_subscribers
+                .notify(&rotated_path, Some(current_path));
+        }
+        Ok(())
+    }
+
     pub(super) fn write_buffer(&mut self, buf: &[u8]) -> std::io::Result<()> {
-        if let Inner::Initial(_, _) = self.inner {
-            self.initialize()?;
+        if let Some(ref mut watchdog) = self.o_failure_watchdog {
+            if watchdog.divert_unless_probing(buf) {
+                return Ok(());
+            }
+        }
+        let result = self.write_buffer_to_file(buf);
+        match self.o_failure_watchdog {
+            Some(ref mut watchdog) => watchdog.check(result, buf),
+            None => result,
+        }
+    }
+
+    fn write_buffer_to_file(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        match self.inner {
+            Inner::Initial(_, _) => self.initialize()?,
+            Inner::Closed(_, _) => self.reopen_after_shutdown()?,
+            Inner::Active(_, _, _) => {}
         }
 
         // rotate if necessary
@@ -527,39 +1042,168 @@ impl State {
                 eprint_err(ErrorCode::LogFile, "can't open file", &e);
             });
 
+        self.reopen_if_rotated_externally().unwrap_or_else(|e| {
+            eprint_err(
+                ErrorCode::LogFile,
+                "can't reopen the externally rotated file",
+                &e,
+            );
+        });
+
         if let Inner::Active(ref mut o_rotation_state, ref mut log_file, ref _path) = self.inner {
             log_file.write_all(buf)?;
 
             if let Some(ref mut rotation_state) = o_rotation_state {
                 rotation_state.roll_state.increase_size(buf.len() as u64);
-            };
+                #[cfg(feature = "manifest")]
+                if rotation_state.o_manifest.is_some() {
+                    rotation_state.write_span.record();
+                }
+            }
+        }
+        Ok(())
+    }
+
+    // Runs the cleanup of the rotated log files, independent of a rotation.
+    // In the initial state, the initialization does the cleanup.
+    pub(super) fn trigger_cleanup(&mut self) -> Result<(), FlexiLoggerError> {
+        match self.inner {
+            Inner::Initial(_, _) => self.initialize()?,
+            Inner::Active(Some(ref mut rotation_state), _, _) => {
+                list_and_cleanup::remove_or_compress_too_old_logfiles(
+                    rotation_state.o_cleanup_thread_handle.as_mut(),
+                    &rotation_state.cleanup,
+                    rotation_state.o_archiver.as_ref(),
+                    &rotation_state.compression,
+                    &self.config.file_spec,
+                    &rotation_state.naming_state.infix_filter(),
+                    rotation_state.naming_state.writes_direct(),
+                    #[cfg(feature = "manifest")]
+                    rotation_state.o_manifest.as_deref(),
+                    &rotation_state.a_cleanup_monitor,
+                )?;
+            }
+            Inner::Active(None, _, _) | Inner::Closed(_, _) => {}
         }
         Ok(())
     }
 
     pub fn reopen_outputfile(&mut self) -> Result<(), std::io::Error> {
+        if self.config.o_provided_output.is_some() {
+            return Err(not_possible_with_provided_output(
+                "reopening the output file",
+            ));
+        }
         if let Inner::Active(_, ref mut file, ref p_path) = self.inner {
             match OpenOptions::new().create(true).append(true).open(p_path) {
                 Ok(f) => {
                     // proved to work on standard windows, linux, mac
-                    *file = Box::new(f);
+                    let o_sync_handle = sync_handle(&self.config, &f)?;
+                    *file = register_log_file(&self.config, f, o_sync_handle, p_path);
                 }
                 Err(_unexpected_error) => {
                     // there are environments, like github's windows container,
                     // where this extra step helps to overcome the _unexpected_error
                     let mut dummy = PathBuf::from(p_path);
-                    dummy.set_extension("ShortLivingTempFileForReOpen");
+                    dummy.set_extension(hygiene::REOPEN_TEMP_EXTENSION);
                     *file = Box::new(OpenOptions::new().create(true).append(true).open(&dummy)?);
                     remove_file(&dummy)?;
 
-                    *file = Box::new(OpenOptions::new().create(true).append(true).open(p_path)?);
+                    let f = OpenOptions::new().create(true).append(true).open(p_path)?;
+                    let o_sync_handle = sync_handle(&self.config, &f)?;
+                    *file = register_log_file(&self.config, f, o_sync_handle, p_path);
                 }
             }
+            if let Some(ref mut detector) = self.o_external_rotation {
+                detector.observe(p_path);
+            }
+        }
+        Ok(())
+    }
+
+    // Reopens the output file if it was renamed, deleted or truncated by someone else,
+    // see FileLogWriterBuilder::detect_external_rotation.
+    fn reopen_if_rotated_externally(&mut self) -> Result<(), std::io::Error> {
+        let rotated_externally = match (&mut self.o_external_rotation, &self.inner) {
+            (Some(detector), Inner::Active(_, _, path)) => detector.rotated_externally(path),
+            _ => false,
+        };
+        if rotated_externally {
+            self.reopen_outputfile()?;
+            if let Inner::Active(Some(ref mut rotation_state), _, ref path) = self.inner {
+                rotation_state.roll_state.refresh(path);
+            }
         }
         Ok(())
     }
 
+    // Checks, without writing, if the output can be written.
+    pub(super) fn healthcheck(&self) -> Result<(), std::io::Error> {
+        if let Some(ref watchdog) = self.o_failure_watchdog {
+            watchdog.healthcheck()?;
+        }
+        if self.config.o_provided_output.is_some() {
+            return Ok(());
+        }
+        match self.inner {
+            Inner::Initial(_, _) => {
+                let directory = self.config.file_spec.get_directory();
+                match std::fs::metadata(&directory) {
+                    Ok(metadata) if metadata.is_dir() => check_writable(&directory, &metadata),
+                    Ok(_) => Err(std::io::Error::new(
+                        std::io::ErrorKind::Other,
+                        format!("{} is not a directory", directory.display()),
+                    )),
+                    // the directory is created when the output file is opened
+                    Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+                    Err(e) => Err(e),
+                }
+            }
+            Inner::Active(_, _, ref path) => match std::fs::metadata(path) {
+                Ok(metadata) => check_writable(path, &metadata),
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                    if self.o_external_rotation.is_some() {
+                        // the file is reopened with the next write
+                        Ok(())
+                    } else {
+                        Err(std::io::Error::new(
+                            std::io::ErrorKind::NotFound,
+                            format!("log file {} does not exist anymore", path.display()),
+                        ))
+                    }
+                }
+                Err(e) => Err(e),
+            },
+            Inner::Closed(_, _) => Ok(()),
+        }
+    }
+
+    pub(crate) fn cleanup_status(&self) -> CleanupStatus {
+        match self.inner {
+            Inner::Initial(_, _) => CleanupStatus::default(),
+            Inner::Active(ref o_rotation_state, _, _) | Inner::Closed(ref o_rotation_state, _) => {
+                o_rotation_state
+                    .as_ref()
+                    .map_or_else(CleanupStatus::default, |rs| rs.a_cleanup_monitor.status())
+            }
+        }
+    }
+
+    // None before the output file is opened, and for provided outputs
+    pub(super) fn current_log_file(&self) -> Option<PathBuf> {
+        if self.config.o_provided_output.is_some() {
+            return None;
+        }
+        match self.inner {
+            Inner::Initial(_, _) => None,
+            Inner::Active(_, _, ref path) | Inner::Closed(_, ref path) => Some(path.clone()),
+        }
+    }
+
     pub(crate) fn existing_log_files(&self, selector: &LogfileSelector) -> Vec<PathBuf> {
+        if self.config.o_provided_output.is_some() {
+            return Vec::new();
+        }
         list_and_cleanup::existing_log_files(
             &self.config.file_spec,
             self.inner.uses_rotation(),
@@ -571,8 +1215,10 @@ impl State {
     pub fn validate_logs(&mut self, expected: &[(&'static str, &'static str, &'static str)]) {
         if let Inner::Initial(_, _) = self.inner {
             self.initialize().expect("validate_logs: initialize failed");
-        };
-        if let Inner::Active(ref o_rotation_state, _, ref path) = self.inner {
+        }
+        if let Inner::Active(ref o_rotation_state, _, ref path)
+        | Inner::Closed(ref o_rotation_state, ref path) = self.inner
+        {
             let rotation_possible = o_rotation_state.is_some();
             let f = File::open(path.clone()).unwrap_or_else(|e| {
                 panic!(
@@ -587,13 +1233,120 @@ impl State {
         }
     }
 
+    // Stops the cleanup thread, if any, and flushes and closes the output file.
+    // With rotate_on_shutdown, the closed file is finally rotated and cleaned up.
     pub fn shutdown(&mut self) {
+        let mut rotate_finally = false;
         if let Inner::Active(ref mut o_rotation_state, ref mut writer, _) = self.inner {
             if let Some(ref mut rotation_state) = o_rotation_state {
                 rotation_state.shutdown();
+                rotate_finally = rotation_state.rotate_on_shutdown;
             }
+            write_file_footer(self.o_file_footer.as_ref(), writer).unwrap_or_else(|e| {
+                eprint_err(ErrorCode::LogFile, "writing the file footer failed", &e);
+            });
             writer.flush().ok();
         }
+        self.inner = match std::mem::replace(&mut self.inner, Inner::Initial(None, false)) {
+            Inner::Active(o_rotation_state, _writer, path) => Inner::Closed(o_rotation_state, path),
+            inner => inner,
+        };
+        if rotate_finally {
+            self.rotate_finally().unwrap_or_else(|e| {
+                eprint_err(
+                    ErrorCode::LogFile,
+                    "rotating the log file on shutdown failed",
+                    &e,
+                );
+            });
+        }
+        // other FileLogWriters may now use the files
+        self.o_registration = None;
+    }
+
+    // Renames the closed rCURRENT file like a rotation does, and runs the cleanup
+    // synchronously, also for the closed file; the cleanup thread is already stopped.
+    fn rotate_finally(&mut self) -> Result<(), std::io::Error> {
+        if let Inner::Closed(Some(ref mut rotation_state), ref path) = self.inner {
+            let _o_lock = RotationLock::acquire_if_configured(&self.config)?;
+            let o_rotated_path = match rotation_state.naming_state {
+                NamingState::Timestamps {
+                    current_timestamp: ref mut ts,
+                    the_current_infix: Some(ref current_infix),
+                    infix_format: ref fmt,
+                } => {
+                    let o_rotated_path;
+                    (*ts, o_rotated_path) = creation_timestamp_of_currentfile(
+                        &self.config,
+                        current_infix,
+                        true,
+                        Some(ts),
+                        fmt,
+                    )?;
+                    o_rotated_path
+                }
+                NamingState::NumbersRCurrent(ref mut idx_state, numbering) => {
+                    let o_idx_state = if self.config.rotation_lock {
+                        None
+                    } else {
+                        Some(*idx_state)
+                    };
+                    let o_rotated_path;
+                    (*idx_state, o_rotated_path) =
+                        numbers::index_for_rcurrent(&self.config, numbering, o_idx_state, true)?;
+                    o_rotated_path
+                }
+                NamingState::NumbersShifted => shifted_numbers::rotate(&self.config.file_spec)?,
+                // the file has already its final name
+                NamingState::Timestamps {
+                    the_current_infix: None,
+                    ..
+                }
+                | NamingState::NumbersDirect(..) => Some(path.clone()),
+            };
+            if let Some(rotated_path) = o_rotated_path {
+                #[cfg(feature = "manifest")]
+                rotation_state.add_to_manifest(&rotated_path);
+                if rotated_path.exists() {
+                    self.rotation_subscribers.notify(&rotated_path, None);
+                }
+            }
+            // a file that is written after the shutdown is a fresh one
+            rotation_state.roll_state.reset_size_and_date(path);
+
+            list_and_cleanup::remove_or_compress_too_old_logfiles(
+                None,
+                &rotation_state.cleanup,
+                rotation_state.o_archiver.as_ref(),
+                &rotation_state.compression,
+                &self.config.file_spec,
+                &rotation_state.naming_state.infix_filter(),
+                false,
+                #[cfg(feature = "manifest")]
+                rotation_state.o_manifest.as_deref(),
+                &rotation_state.a_cleanup_monitor,
+            )?;
+        }
+        Ok(())
+    }
+
+    fn reopen_after_shutdown(&mut self) -> Result<(), std::io::Error> {
+        if let Inner::Closed(_, ref path) = self.inner {
+            let w = if let Some(ref provided_output) = self.config.o_provided_output {
+                wrap_provided_output(&self.config, provided_output)?.0
+            } else {
+                let file = OpenOptions::new().create(true).append(true).open(path)?;
+                if let Some(ref mut detector) = self.o_external_rotation {
+                    detector.observe(path);
+                }
+                wrap_log_file(&self.config, file, path)?
+            };
+            self.inner = match std::mem::replace(&mut self.inner, Inner::Initial(None, false)) {
+                Inner::Closed(o_rotation_state, path) => Inner::Active(o_rotation_state, w, path),
+                inner => inner,
+            };
+        }
+        Ok(())
     }
 }
 
@@ -648,13 +1401,26 @@ fn open_log_file(
     config: &FileLogWriterConfig,
     o_infix: Option<&str>,
 ) -> Result<(Box<dyn Write + Send>, PathBuf), std::io::Error> {
+    if let Some(ref provided_output) = config.o_provided_output {
+        return wrap_provided_output(config, provided_output);
+    }
     let path = config.file_spec.as_pathbuf(o_infix);
 
     if config.print_message {
         println!("Log is written to {}", &path.display());
     }
-    if let Some(ref link) = config.o_create_symlink {
-        self::platform::create_symlink_if_possible(link, &path);
+    if config.append && config.recover_on_append && !config.multi_process {
+        match recovery::trim_corrupt_tail(&path, config.keep_corrupt_tail) {
+            Ok(0) => {}
+            Ok(n) => eprint_msg(
+                ErrorCode::Recovery,
+                &format!(
+                    "removed {n} bytes of an incomplete last line from {}",
+                    path.display()
+                ),
+            ),
+            Err(e) => eprint_err(ErrorCode::Recovery, "repairing the log file failed", &e),
+        }
     }
 
     let logfile = OpenOptions::new()
@@ -664,15 +1430,303 @@ fn open_log_file(
         .truncate(!config.append)
         .open(&path)?;
 
-    let w: Box<dyn Write + Send> = if let Some(capacity) = config.write_mode.buffersize() {
-        Box::new(BufWriter::with_capacity(capacity, logfile))
-    } else {
-        Box::new(logfile)
-    };
+    // on windows, a hard link can only be created to an existing file
+    if let Some(ref link) = config.o_create_symlink {
+        self::platform::create_symlink_if_possible(link, &path);
+    }
+
+    let w = wrap_log_file(config, logfile, &path)?;
     Ok((w, path))
 }
 
+// If the output file can't be opened, the configured fallbacks are tried.
+fn open_log_file_or_fall_back(
+    config: &mut FileLogWriterConfig,
+    o_infix: Option<&str>,
+) -> Result<(Box<dyn Write + Send>, PathBuf), std::io::Error> {
+    loop {
+        match open_log_file(config, o_infix) {
+            Ok(result) => return Ok(result),
+            Err(e) => {
+                if !fall_back(config, &e) {
+                    return Err(e);
+                }
+            }
+        }
+    }
+}
+
+// Checks if the file or directory is writable.
+// Only evaluates the permissions, which is cheap, but can't detect all problems,
+// e.g. a full disk, a read-only mount, or a non-writable parent directory.
+fn check_writable(path: &Path, metadata: &std::fs::Metadata) -> Result<(), std::io::Error> {
+    if metadata.permissions().readonly() {
+        Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            format!("{} is read-only", path.display()),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+// Writes the file header, if one is configured and the file is still empty,
+// and returns the number of written bytes.
+fn write_file_header(
+    o_file_header: Option<&FileHeader>,
+    config: &FileLogWriterConfig,
+    path: &Path,
+    w: &mut dyn Write,
+) -> Result<u64, std::io::Error> {
+    if let Some(file_header) = o_file_header {
+        let len = match config.o_provided_output {
+            Some(ProvidedOutput::File(ref file)) => file.metadata().map(|md| md.len()),
+            Some(ProvidedOutput::Memory(ref buffer)) => {
+                Ok(buffer.lock().map_or(0, |b| b.len() as u64))
+            }
+            // stderr is not a log file
+            Some(ProvidedOutput::Stderr) => return Ok(0),
+            None => std::fs::metadata(path).map(|md| md.len()),
+        };
+        if len.map_or(true, |len| len == 0) {
+            let mut buffer = Vec::<u8>::with_capacity(200);
+            file_header.write(&mut buffer, config.line_ending)?;
+            w.write_all(&buffer)?;
+            return Ok(buffer.len() as u64);
+        }
+    }
+    Ok(0)
+}
+
+// Writes the file footer, if one is configured.
+fn write_file_footer(o_file_footer: Option<&FileFooter>, w: &mut dyn Write) -> std::io::Result<()> {
+    if let Some(FileFooter(f)) = o_file_footer {
+        let mut buffer = Vec::<u8>::with_capacity(200);
+        f(&mut buffer)?;
+        w.write_all(&buffer)?;
+    }
+    Ok(())
+}
+
+// Writes the banner, if one is configured, and returns the number of written bytes.
+fn write_banner(
+    o_banner: Option<&Banner>,
+    line_ending: &[u8],
+    w: &mut dyn Write,
+) -> Result<u64, FlexiLoggerError> {
+    if let Some(banner) = o_banner {
+        // the thread-local buffer might be in use already
+        let mut buffer = Vec::<u8>::with_capacity(200);
+        with_banner_record(&banner.text, |record| {
+            (banner.format)(&mut buffer, &mut DeferredNow::new(), record)?;
+            buffer.write_all(line_ending)?;
+            w.write_all(&buffer)?;
+            if let Some(ref forward) = banner.o_forward {
+                forward(record);
+            }
+            Ok::<_, std::io::Error>(())
+        })?;
+        Ok(buffer.len() as u64)
+    } else {
+        Ok(0)
+    }
+}
+
+fn wrap_log_file<F: SyncableFile + Write + Send + 'static>(
+    config: &FileLogWriterConfig,
+    file: F,
+    path: &Path,
+) -> Result<Box<dyn Write + Send>, std::io::Error> {
+    let o_sync_handle = sync_handle(config, &file)?;
+    // with multi_process_append, each log line must reach the file with a single write
+    let o_buffer_capacity = config
+        .write_mode
+        .buffersize()
+        .filter(|_| !config.multi_process);
+    Ok(if let Some(capacity) = o_buffer_capacity {
+        register_log_file(
+            config,
+            BufWriter::with_capacity(capacity, file),
+            o_sync_handle,
+            path,
+        )
+    } else {
+        register_log_file(config, file, o_sync_handle, path)
+    })
+}
+
+// Registers the output file and adds the syncing according to the configured SyncPolicy.
+fn register_log_file<W: Write + Send + 'static>(
+    config: &FileLogWriterConfig,
+    w: W,
+    o_sync_handle: Option<File>,
+    path: &Path,
+) -> Box<dyn Write + Send> {
+    match o_sync_handle {
+        Some(file) => Box::new(RegisteredFile::new(
+            SyncingWriter {
+                writer: w,
+                file,
+                sync_policy: config.sync_policy,
+                unsynced_records: 0,
+                unsynced_bytes: 0,
+            },
+            path,
+        )),
+        None => Box::new(RegisteredFile::new(w, path)),
+    }
+}
+
+// Provides a separate handle to the output file, which is used for syncing.
+fn sync_handle<F: SyncableFile>(
+    config: &FileLogWriterConfig,
+    file: &F,
+) -> Result<Option<File>, std::io::Error> {
+    if config.sync_policy == SyncPolicy::Never {
+        Ok(None)
+    } else {
+        file.try_clone_for_sync()
+    }
+}
+
+trait SyncableFile {
+    // Returns a separate handle to the file, if the output is a file.
+    fn try_clone_for_sync(&self) -> Result<Option<File>, std::io::Error>;
+}
+impl SyncableFile for File {
+    fn try_clone_for_sync(&self) -> Result<Option<File>, std::io::Error> {
+        self.try_clone().map(Some)
+    }
+}
+impl SyncableFile for OpenFile {
+    fn try_clone_for_sync(&self) -> Result<Option<File>, std::io::Error> {
+        self.0.try_clone().map(Some)
+    }
+}
+impl SyncableFile for MemoryOutput {
+    fn try_clone_for_sync(&self) -> Result<Option<File>, std::io::Error> {
+        Ok(None)
+    }
+}
+
+// Syncs the output file to the storage device according to the configured SyncPolicy,
+// and before the file is closed.
+struct SyncingWriter<W: Write> {
+    writer: W,
+    file: File,
+    sync_policy: SyncPolicy,
+    unsynced_records: u64,
+    unsynced_bytes: u64,
+}
+impl<W: Write> SyncingWriter<W> {
+    fn sync(&mut self) -> std::io::Result<()> {
+        self.writer.flush()?;
+        self.unsynced_records = 0;
+        self.unsynced_bytes = 0;
+        self.file.sync_data()
+    }
+}
+impl<W: Write> Write for SyncingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.writer.write(buf)?;
+        self.unsynced_bytes += written as u64;
+        Ok(written)
+    }
+    // is called once per log line
+    fn write_all(&mut self, buf: &[u8]) -> std::io::Result<()> {
+        self.writer.write_all(buf)?;
+        self.unsynced_records += 1;
+        self.unsynced_bytes += buf.len() as u64;
+        match self.sync_policy {
+            SyncPolicy::Records(n) if self.unsynced_records >= n => self.sync(),
+            SyncPolicy::Bytes(n) if self.unsynced_bytes >= n => self.sync(),
+            _ => Ok(()),
+        }
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        if self.sync_policy == SyncPolicy::OnFlush {
+            self.sync()
+        } else {
+            self.writer.flush()
+        }
+    }
+}
+impl<W: Write> Drop for SyncingWriter<W> {
+    fn drop(&mut self) {
+        self.sync().unwrap_or_else(|e| {
+            eprint_err(ErrorCode::Flush, "syncing the log file failed", &e);
+        });
+    }
+}
+
+// Writes to a file handle that was provided with `FileLogWriterBuilder::with_open_file`.
+struct OpenFile(Arc<File>);
+impl Write for OpenFile {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        (&*self.0).write(buf)
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        (&*self.0).flush()
+    }
+}
+
+// Writes to the in-memory buffer of `Logger::capture_file_output`.
+struct MemoryOutput(Arc<Mutex<Vec<u8>>>);
+impl Write for MemoryOutput {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.0
+            .lock()
+            .map_err(|_| io_err("Poison"))?
+            .extend_from_slice(buf);
+        Ok(buf.len())
+    }
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+fn wrap_provided_output(
+    config: &FileLogWriterConfig,
+    provided_output: &ProvidedOutput,
+) -> Result<(Box<dyn Write + Send>, PathBuf), std::io::Error> {
+    Ok(match provided_output {
+        ProvidedOutput::File(file) => {
+            let path = PathBuf::from(PROVIDED_FILE_PATH);
+            (
+                wrap_log_file(config, OpenFile(Arc::clone(file)), &path)?,
+                path,
+            )
+        }
+        ProvidedOutput::Memory(buffer) => {
+            let path = PathBuf::from(MEMORY_PATH);
+            (
+                wrap_log_file(config, MemoryOutput(Arc::clone(buffer)), &path)?,
+                path,
+            )
+        }
+        ProvidedOutput::Stderr => (Box::new(std::io::stderr()), PathBuf::from(STDERR_PATH)),
+    })
+}
+
+fn not_possible_with_provided_output(what: &str) -> std::io::Error {
+    std::io::Error::new(
+        std::io::ErrorKind::Unsupported,
+        format!("{what} is not possible, the log is not written to a file path"),
+    )
+}
+
+// The date of the first day of the week that contains the given date
+fn start_of_week(date: NaiveDate, first_weekday: Weekday) -> NaiveDate {
+    let days_since_start =
+        (date.weekday().num_days_from_monday() + 7 - first_weekday.num_days_from_monday()) % 7;
+    date - chrono::Duration::days(i64::from(days_since_start))
+}
+
 fn get_creation_timestamp(path: &Path) -> DateTime<Local> {
+    // The file system knows nothing about a clock that was set with DeferredNow::set_clock
+    if clock_is_set() {
+        return get_current_timestamp();
+    }
     // On windows, we know that try_get_creation_date() returns a result, but it is wrong.
     if cfg!(target_os = "windows") {
         get_current_timestamp()
@@ -693,7 +1747,7 @@ fn try_get_modification_timestamp(path: &Path) -> Result<DateTime<Local>, FlexiL
     Ok(d.into())
 }
 fn get_current_timestamp() -> DateTime<Local> {
-    Local::now()
+    clock_now()
 }
 
 #[cfg(feature = "async")]
@@ -701,37 +1755,48 @@ pub(super) fn start_async_fs_writer(
     am_state: Arc<Mutex<State>>,
     message_capa: usize,
     a_pool: Arc<ArrayQueue<Vec<u8>>>,
+    a_flush_acks: Arc<FlushAcks>,
 ) -> (CrossbeamSender<Vec<u8>>, Mutex<Option<JoinHandle<()>>>) {
     let (sender, receiver) = crossbeam_channel::unbounded::<Vec<u8>>();
+    let registration = ThreadRegistration::new(ASYNC_WRITER);
     (
         sender,
         Mutex::new(Some(
             std::thread::Builder::new()
                 .name(ASYNC_WRITER.to_string())
-                .spawn(move || loop {
-                    match receiver.recv() {
-                        Err(_) => break,
-                        Ok(mut message) => {
-                            let mut state = am_state.lock().unwrap(/* ok */);
-                            match message.as_ref() {
-                                ASYNC_FLUSH => {
-                                    state.flush().unwrap_or_else(|e| {
-                                        eprint_err(ErrorCode::Flush, "flushing failed", &e);
-                                    });
+                .spawn(move || {
+                    let _registration = registration;
+                    loop {
+                        match receiver.recv() {
+                            Err(_) => break,
+                            Ok(mut message) => {
+                                let mut state = am_state.lock().unwrap(/* ok */);
+                                match message.as_ref() {
+                                    ASYNC_FLUSH => {
+                                        state.flush().unwrap_or_else(|e| {
+                                            eprint_err(ErrorCode::Flush, "flushing failed", &e);
+                                        });
+                                    }
+                                    ASYNC_FLUSH_AND_ACK => {
+                                        state.flush().unwrap_or_else(|e| {
+                                            eprint_err(ErrorCode::Flush, "flushing failed", &e);
+                                        });
+                                        a_flush_acks.acknowledge();
+                                    }
+                                    ASYNC_SHUTDOWN => {
+                                        state.shutdown();
+                                        break;
+                                    }
+                                    _ => {
+                                        state.write_buffer(&message).unwrap_or_else(|e| {
+                                            eprint_err(ErrorCode::Write, "writing failed", &e);
+                                        });
+                                    }
                                 }
-                                ASYNC_SHUTDOWN => {
-                                    state.shutdown();
-                                    break;
+                                if message.capacity() <= message_capa {
+                                    message.clear();
+                                    a_pool.push(message).ok();
                                 }
-                                _ => {
-                                    state.write_buffer(&message).unwrap_or_else(|e| {
-                                        eprint_err(ErrorCode::Write, "writing failed", &e);
-                                    });
-                                }
-                            }
-                            if message.capacity() <= message_capa {
-                                message.clear();
-                                a_pool.push(message).ok();
                             }
                         }
                     }
@@ -741,66 +1806,59 @@ pub(super) fn start_async_fs_writer(
     )
 }
 
-pub(super) fn start_sync_flusher(am_state: Arc<Mutex<State>>, flush_interval: std::time::Duration) {
-    let builder = std::thread::Builder::new().name("flexi_logger-file_flusher".to_string());
-    #[cfg(not(feature = "dont_minimize_extra_stacks"))]
-    let builder = builder.stack_size(1024);
-    builder.spawn(move || {
-        let (_tx, rx) = std::sync::mpsc::channel::<()>();
-            loop {
-                rx.recv_timeout(flush_interval).ok();
-                (*am_state).lock().map_or_else(
-                    |_e| (),
-                    |mut state| {
-                        state.flush().ok();
-                    },
-                );
-            }
-        })
-        .unwrap(/* yes, let's panic if the thread can't be spawned */);
+pub(super) fn start_sync_flusher(
+    am_state: Arc<Mutex<State>>,
+    flush_interval: std::time::Duration,
+) -> FlusherHandle {
+    spawn_flusher(SYNC_FLUSHER, flush_interval, move || {
+        (*am_state).lock().map_or_else(
+            |_e| (),
+            |mut state| {
+                state.flush().ok();
+            },
+        );
+    })
+    .unwrap(/* yes, let's panic if the thread can't be spawned */)
 }
 
 #[cfg(feature = "async")]
 pub(crate) fn start_async_fs_flusher(
     async_writer: CrossbeamSender<Vec<u8>>,
     flush_interval: std::time::Duration,
-) {
-    let builder = std::thread::Builder::new().name(ASYNC_FLUSHER.to_string());
-    #[cfg(not(feature = "dont_minimize_extra_stacks"))]
-    let builder = builder.stack_size(1024);
-    builder.spawn(move || {
-            let (_tx, rx) = std::sync::mpsc::channel::<()>();
-            loop {
-                if let Err(std::sync::mpsc::RecvTimeoutError::Disconnected) =
-                    rx.recv_timeout(flush_interval)
-                {
-                    eprint_msg(ErrorCode::Flush, "Flushing unexpectedly stopped working");
-                    break;
-                }
-
-                async_writer.send(ASYNC_FLUSH.to_vec()).ok();
-            }
-        })
-        .unwrap(/* yes, let's panic if the thread can't be spawned */);
+) -> FlusherHandle {
+    spawn_flusher(ASYNC_FLUSHER, flush_interval, move || {
+        async_writer.send(ASYNC_FLUSH.to_vec()).ok();
+    })
+    .unwrap(/* yes, let's panic if the thread can't be spawned */)
 }
 
 mod platform {
-    #[cfg(target_family = "unix")]
+    #[cfg(any(target_family = "unix", target_os = "windows"))]
     use crate::util::{eprint_err, ErrorCode};
     use std::path::Path;
 
     pub fn create_symlink_if_possible(link: &Path, path: &Path) {
+        #[cfg(target_family = "unix")]
         unix_create_symlink(link, path);
+        #[cfg(target_os = "windows")]
+        windows_create_link(link, path);
+        #[cfg(not(any(target_family = "unix", target_os = "windows")))]
+        let _ = (link, path);
     }
 
-    #[cfg(target_family = "unix")]
-    fn unix_create_symlink(link: &Path, logfile: &Path) {
+    #[cfg(any(target_family = "unix", target_os = "windows"))]
+    fn remove_old_link(link: &Path) {
         if std::fs::symlink_metadata(link).is_ok() {
             // remove old symlink before creating a new one
             if let Err(e) = std::fs::remove_file(link) {
                 eprint_err(ErrorCode::Symlink, "cannot delete symlink to log file", &e);
             }
         }
+    }
+
+    #[cfg(target_family = "unix")]
+    fn unix_create_symlink(link: &Path, logfile: &Path) {
+        remove_old_link(link);
 
         // create new symlink
         if let Err(e) = std::os::unix::fs::symlink(logfile, link) {
@@ -808,6 +1866,415 @@ mod platform {
         }
     }
 
-    #[cfg(not(target_family = "unix"))]
-    fn unix_create_symlink(_: &Path, _: &Path) {}
+    // Creating symbolic links needs a privilege or the developer mode on windows,
+    // so we fall back to a hard link (which needs the link on the same volume),
+    // and finally to a small file that contains the path of the current log file.
+    #[cfg(target_os = "windows")]
+    fn windows_create_link(link: &Path, logfile: &Path) {
+        remove_old_link(link);
+
+        if std::os::windows::fs::symlink_file(logfile, link).is_ok()
+            || std::fs::hard_link(logfile, link).is_ok()
+        {
+            return;
+        }
+        if let Err(e) = std::fs::write(link, format!("{}\n", logfile.display())) {
+            eprint_err(ErrorCode::Symlink, "cannot create link to logfile", &e);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{RollState, SyncingWriter};
+    use crate::{Age, SyncPolicy};
+    use chrono::{DateTime, FixedOffset, TimeZone, Weekday};
+    use std::io::{BufWriter, Write};
+
+    // CET and CEST, to simulate the daylight saving time changes
+    fn cet(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<FixedOffset> {
+        FixedOffset::east_opt(3600)
+            .unwrap()
+            .with_ymd_and_hms(y, m, d, h, min, 0)
+            .unwrap()
+    }
+    fn cest(y: i32, m: u32, d: u32, h: u32, min: u32) -> DateTime<FixedOffset> {
+        FixedOffset::east_opt(7200)
+            .unwrap()
+            .with_ymd_and_hms(y, m, d, h, min, 0)
+            .unwrap()
+    }
+
+    fn crossed(age: Age, created_at: &DateTime<FixedOffset>, now: &DateTime<FixedOffset>) -> bool {
+        RollState::age_boundary_crossed(age, created_at, now)
+    }
+
+    #[test]
+    fn test_age_month() {
+        // months with 31, 30, 29, and 28 days
+        assert!(!crossed(
+            Age::Month,
+            &cet(2024, 1, 1, 0, 0),
+            &cet(2024, 1, 31, 23, 59)
+        ));
+        assert!(crossed(
+            Age::Month,
+            &cet(2024, 1, 31, 23, 59),
+            &cet(2024, 2, 1, 0, 0)
+        ));
+        assert!(!crossed(
+            Age::Month,
+            &cet(2024, 2, 28, 12, 0),
+            &cet(2024, 2, 29, 12, 0)
+        ));
+        assert!(crossed(
+            Age::Month,
+            &cet(2024, 2, 29, 12, 0),
+            &cet(2024, 3, 1, 0, 0)
+        ));
+        assert!(crossed(
+            Age::Month,
+            &cet(2023, 2, 28, 23, 59),
+            &cet(2023, 3, 1, 0, 0)
+        ));
+        assert!(!crossed(
+            Age::Month,
+            &cest(2024, 4, 1, 0, 0),
+            &cest(2024, 4, 30, 23, 59)
+        ));
+        assert!(crossed(
+            Age::Month,
+            &cest(2024, 4, 30, 23, 59),
+            &cest(2024, 5, 1, 0, 0)
+        ));
+        // same month number in a different year
+        assert!(crossed(
+            Age::Month,
+            &cet(2023, 12, 31, 23, 59),
+            &cet(2024, 1, 1, 0, 0)
+        ));
+        assert!(crossed(
+            Age::Month,
+            &cet(2023, 3, 15, 12, 0),
+            &cet(2024, 3, 15, 12, 0)
+        ));
+        // daylight saving time starts and ends within the month
+        assert!(!crossed(
+            Age::Month,
+            &cet(2024, 3, 1, 0, 0),
+            &cest(2024, 3, 31, 23, 59)
+        ));
+        assert!(crossed(
+            Age::Month,
+            &cest(2024, 3, 31, 23, 59),
+            &cest(2024, 4, 1, 0, 0)
+        ));
+        assert!(!crossed(
+            Age::Month,
+            &cest(2024, 10, 27, 2, 30),
+            &cet(2024, 10, 27, 2, 30)
+        ));
+    }
+
+    #[test]
+    fn test_age_week() {
+        let monday = Age::Week(Weekday::Mon);
+        let sunday = Age::Week(Weekday::Sun);
+
+        // 2024-03-24 is a Sunday
+        assert!(crossed(
+            monday,
+            &cet(2024, 3, 24, 23, 59),
+            &cet(2024, 3, 25, 0, 0)
+        ));
+        assert!(!crossed(
+            sunday,
+            &cet(2024, 3, 24, 23, 59),
+            &cet(2024, 3, 25, 0, 0)
+        ));
+        assert!(!crossed(
+            monday,
+            &cet(2024, 3, 18, 0, 0),
+            &cet(2024, 3, 24, 23, 59)
+        ));
+        assert!(crossed(
+            sunday,
+            &cet(2024, 3, 23, 23, 59),
+            &cet(2024, 3, 24, 0, 0)
+        ));
+        // exactly one week later, and more than one week later
+        assert!(crossed(
+            monday,
+            &cet(2024, 3, 20, 12, 0),
+            &cet(2024, 3, 27, 12, 0)
+        ));
+        assert!(crossed(
+            sunday,
+            &cet(2024, 1, 3, 12, 0),
+            &cet(2024, 3, 6, 12, 0)
+        ));
+        // a week that spans the end of the month and the end of the year
+        assert!(!crossed(
+            monday,
+            &cet(2024, 2, 26, 0, 0),
+            &cet(2024, 3, 3, 23, 59)
+        ));
+        assert!(!crossed(
+            monday,
+            &cet(2024, 12, 30, 0, 0),
+            &cet(2025, 1, 5, 23, 59)
+        ));
+        assert!(crossed(
+            monday,
+            &cet(2025, 1, 5, 23, 59),
+            &cet(2025, 1, 6, 0, 0)
+        ));
+        // daylight saving time starts on Sunday 2024-03-31, and ends on Sunday 2024-10-27
+        assert!(!crossed(
+            monday,
+            &cet(2024, 3, 31, 1, 59),
+            &cest(2024, 3, 31, 3, 0)
+        ));
+        assert!(crossed(
+            monday,
+            &cest(2024, 3, 31, 23, 59),
+            &cest(2024, 4, 1, 0, 0)
+        ));
+        assert!(!crossed(
+            monday,
+            &cest(2024, 10, 27, 2, 59),
+            &cet(2024, 10, 27, 2, 0)
+        ));
+        assert!(crossed(
+            sunday,
+            &cest(2024, 10, 26, 23, 59),
+            &cet(2024, 10, 27, 2, 0)
+        ));
+    }
+
+    #[test]
+    fn test_age_in_utc() {
+        fn crossed_in_utc(
+            age: Age,
+            created_at: &DateTime<FixedOffset>,
+            now: &DateTime<FixedOffset>,
+        ) -> bool {
+            RollState::age_rotation_necessary_at(age, true, created_at, now)
+        }
+        fn crossed_locally(
+            age: Age,
+            created_at: &DateTime<FixedOffset>,
+            now: &DateTime<FixedOffset>,
+        ) -> bool {
+            RollState::age_rotation_necessary_at(age, false, created_at, now)
+        }
+
+        // local midnight is not UTC midnight
+        assert!(crossed_locally(
+            Age::Day,
+            &cet(2024, 1, 10, 23, 30),
+            &cet(2024, 1, 11, 0, 30)
+        ));
+        assert!(!crossed_in_utc(
+            Age::Day,
+            &cet(2024, 1, 10, 23, 30),
+            &cet(2024, 1, 11, 0, 30)
+        ));
+        assert!(!crossed_locally(
+            Age::Day,
+            &cet(2024, 1, 11, 0, 30),
+            &cet(2024, 1, 11, 1, 30)
+        ));
+        assert!(crossed_in_utc(
+            Age::Day,
+            &cet(2024, 1, 11, 0, 30),
+            &cet(2024, 1, 11, 1, 30)
+        ));
+
+        // with daylight saving time, UTC midnight is at 1:00 or 2:00 local time
+        assert!(!crossed_in_utc(
+            Age::Day,
+            &cet(2024, 3, 31, 1, 0),
+            &cest(2024, 3, 31, 3, 0)
+        ));
+        assert!(crossed_in_utc(
+            Age::Day,
+            &cest(2024, 4, 1, 1, 59),
+            &cest(2024, 4, 1, 2, 0)
+        ));
+        assert!(crossed_in_utc(
+            Age::Day,
+            &cest(2024, 10, 27, 1, 59),
+            &cest(2024, 10, 27, 2, 0)
+        ));
+        assert!(!crossed_in_utc(
+            Age::Day,
+            &cest(2024, 10, 27, 2, 0),
+            &cet(2024, 10, 27, 2, 0)
+        ));
+        assert!(!crossed_in_utc(
+            Age::Day,
+            &cet(2024, 10, 27, 2, 0),
+            &cet(2024, 10, 28, 0, 59)
+        ));
+        assert!(crossed_in_utc(
+            Age::Day,
+            &cet(2024, 10, 28, 0, 59),
+            &cet(2024, 10, 28, 1, 0)
+        ));
+
+        // the hour that is repeated locally at the end of daylight saving time
+        // is a new hour in UTC
+        assert!(!crossed_locally(
+            Age::Hour,
+            &cest(2024, 10, 27, 2, 30),
+            &cet(2024, 10, 27, 2, 10)
+        ));
+        assert!(crossed_in_utc(
+            Age::Hour,
+            &cest(2024, 10, 27, 2, 30),
+            &cet(2024, 10, 27, 2, 10)
+        ));
+
+        // months and weeks
+        assert!(!crossed_in_utc(
+            Age::Month,
+            &cest(2024, 4, 30, 23, 0),
+            &cest(2024, 5, 1, 1, 59)
+        ));
+        assert!(crossed_in_utc(
+            Age::Month,
+            &cest(2024, 5, 1, 1, 59),
+            &cest(2024, 5, 1, 2, 0)
+        ));
+        assert!(!crossed_in_utc(
+            Age::Week(Weekday::Mon),
+            &cet(2024, 3, 24, 23, 0),
+            &cet(2024, 3, 25, 0, 59)
+        ));
+    }
+
+    #[test]
+    fn test_age_with_clock_changes() {
+        fn necessary(
+            age: Age,
+            created_at: &DateTime<FixedOffset>,
+            now: &DateTime<FixedOffset>,
+        ) -> bool {
+            RollState::age_rotation_necessary_at(age, false, created_at, now)
+        }
+
+        // the clock is set back: no rotation until it has caught up
+        assert!(!necessary(
+            Age::Day,
+            &cet(2024, 1, 11, 0, 30),
+            &cet(2024, 1, 10, 23, 30)
+        ));
+        assert!(!necessary(
+            Age::Hour,
+            &cet(2024, 1, 11, 10, 0),
+            &cet(2024, 1, 11, 9, 59)
+        ));
+        assert!(!necessary(
+            Age::Second,
+            &cet(2024, 1, 11, 10, 0),
+            &cet(2024, 1, 11, 9, 0)
+        ));
+        assert!(!RollState::age_rotation_necessary_at(
+            Age::Day,
+            true,
+            &cet(2024, 1, 11, 1, 30),
+            &cet(2024, 1, 10, 23, 30),
+        ));
+        assert!(necessary(
+            Age::Day,
+            &cet(2024, 1, 11, 0, 30),
+            &cet(2024, 1, 12, 0, 0)
+        ));
+        // the clock is set forward: a single rotation
+        assert!(necessary(
+            Age::Day,
+            &cet(2024, 1, 11, 0, 30),
+            &cet(2024, 6, 11, 0, 30)
+        ));
+    }
+
+    #[test]
+    fn test_age_day_with_dst() {
+        // the days of the daylight saving time changes are not split
+        assert!(!crossed(
+            Age::Day,
+            &cet(2024, 3, 31, 0, 0),
+            &cest(2024, 3, 31, 23, 59)
+        ));
+        assert!(!crossed(
+            Age::Day,
+            &cest(2024, 10, 27, 0, 0),
+            &cet(2024, 10, 27, 23, 59)
+        ));
+        assert!(crossed(
+            Age::Day,
+            &cet(2024, 10, 27, 23, 59),
+            &cet(2024, 10, 28, 0, 0)
+        ));
+        // the repeated hour does not trigger a rotation
+        assert!(!crossed(
+            Age::Hour,
+            &cest(2024, 10, 27, 2, 30),
+            &cet(2024, 10, 27, 2, 10)
+        ));
+    }
+
+    #[test]
+    fn test_syncing_writer() {
+        // the buffered lines reach the file when the file is synced
+        fn syncing_writer(
+            path: &std::path::Path,
+            sync_policy: SyncPolicy,
+        ) -> SyncingWriter<BufWriter<std::fs::File>> {
+            let file = std::fs::File::create(path).unwrap();
+            SyncingWriter {
+                file: file.try_clone().unwrap(),
+                writer: BufWriter::new(file),
+                sync_policy,
+                unsynced_records: 0,
+                unsynced_bytes: 0,
+            }
+        }
+        let len = |path: &std::path::Path| std::fs::metadata(path).unwrap().len();
+
+        let directory = std::path::PathBuf::from("log_files/test_syncing_writer");
+        std::fs::create_dir_all(&directory).unwrap();
+
+        let path = directory.join("records.log");
+        let mut w = syncing_writer(&path, SyncPolicy::Records(2));
+        w.write_all(b"line 1\n").unwrap();
+        assert_eq!(len(&path), 0);
+        w.write_all(b"line 2\n").unwrap();
+        assert_eq!(len(&path), 14);
+        w.write_all(b"line 3\n").unwrap();
+        assert_eq!(len(&path), 14);
+
+        let path = directory.join("bytes.log");
+        let mut w = syncing_writer(&path, SyncPolicy::Bytes(10));
+        w.write_all(b"line 1\n").unwrap();
+        assert_eq!(len(&path), 0);
+        w.write_all(b"line 2\n").unwrap();
+        assert_eq!(len(&path), 14);
+
+        let path = directory.join("flush.log");
+        let mut w = syncing_writer(&path, SyncPolicy::OnFlush);
+        w.write_all(b"line 1\n").unwrap();
+        assert_eq!(len(&path), 0);
+        w.flush().unwrap();
+        assert_eq!(len(&path), 7);
+
+        let path = directory.join("rotation.log");
+        let mut w = syncing_writer(&path, SyncPolicy::OnRotation);
+        w.write_all(b"line 1\n").unwrap();
+        w.write_all(b"line 2\n").unwrap();
+        assert_eq!(len(&path), 0);
+        drop(w);
+        assert_eq!(len(&path), 14);
+    }
 }
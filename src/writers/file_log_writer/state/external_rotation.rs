@@ -0,0 +1,82 @@
+//! Detection of output files that were rotated by external tools like `logrotate`.
+use std::{
+    fs::Metadata,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+// Checks periodically if the path of the output file still refers to the file
+// that is written to.
+#[derive(Debug)]
+pub(super) struct ExternalRotationDetector {
+    check_interval: Duration,
+    last_check: Instant,
+    o_observed: Option<ObservedFile>,
+}
+
+#[derive(Debug)]
+struct ObservedFile {
+    file_id: FileId,
+    len: u64,
+}
+
+impl ExternalRotationDetector {
+    pub(super) fn new(check_interval: Duration) -> Self {
+        Self {
+            check_interval,
+            last_check: Instant::now(),
+            o_observed: None,
+        }
+    }
+
+    // Remembers the file that was just opened at the given path.
+    pub(super) fn observe(&mut self, path: &Path) {
+        self.last_check = Instant::now();
+        self.o_observed = std::fs::metadata(path).ok().map(|md| ObservedFile {
+            file_id: file_id(&md),
+            len: md.len(),
+        });
+    }
+
+    // Returns true if, since the last check, the file was renamed or deleted
+    // (it is gone or was replaced by another file),
+    // or if it was truncated (like `logrotate` does with `copytruncate`).
+    //
+    // Does nothing if the check interval has not yet elapsed since the last check.
+    pub(super) fn rotated_externally(&mut self, path: &Path) -> bool {
+        if self.last_check.elapsed() < self.check_interval {
+            return false;
+        }
+        let Some(ref mut observed) = self.o_observed else {
+            self.observe(path);
+            return false;
+        };
+        self.last_check = Instant::now();
+        match std::fs::metadata(path) {
+            Err(_) => true,
+            Ok(md) => {
+                if file_id(&md) != observed.file_id || md.len() < observed.len {
+                    true
+                } else {
+                    observed.len = md.len();
+                    false
+                }
+            }
+        }
+    }
+}
+
+// On unix, a file is identified by its device and inode.
+#[cfg(unix)]
+type FileId = (u64, u64);
+#[cfg(unix)]
+fn file_id(md: &Metadata) -> FileId {
+    use std::os::unix::fs::MetadataExt;
+    (md.dev(), md.ino())
+}
+
+// Elsewhere, only the disappearance or truncation of the file is detected.
+#[cfg(not(unix))]
+type FileId = ();
+#[cfg(not(unix))]
+fn file_id(_md: &Metadata) -> FileId {}
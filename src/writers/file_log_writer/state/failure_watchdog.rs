@@ -0,0 +1,180 @@
+//! Degraded mode for output files that can't be written, e.g. because the disk is full.
+use crate::{
+    util::{eprint_err, eprint_msg, ErrorCode},
+    WriteFailurePolicy,
+};
+use std::{
+    io::Write,
+    time::{Duration, Instant},
+};
+
+// Counts consecutive write failures and diverts the log lines while the output is degraded,
+// see WriteFailurePolicy.
+#[derive(Debug)]
+pub(super) struct WriteFailureWatchdog {
+    after_failures: u32,
+    probe_interval: Duration,
+    redirect_to_stderr: bool,
+    consecutive_failures: u32,
+    o_degraded: Option<Degraded>,
+}
+
+#[derive(Debug)]
+struct Degraded {
+    since: Instant,
+    last_probe: Instant,
+    diverted_lines: u64,
+}
+
+impl WriteFailureWatchdog {
+    pub(super) fn new(policy: WriteFailurePolicy) -> Option<Self> {
+        let (after_failures, probe_interval, redirect_to_stderr) = match policy {
+            WriteFailurePolicy::Report => return None,
+            WriteFailurePolicy::Drop {
+                after_failures,
+                probe_interval,
+            } => (after_failures, probe_interval, false),
+            WriteFailurePolicy::RedirectToStderr {
+                after_failures,
+                probe_interval,
+            } => (after_failures, probe_interval, true),
+        };
+        Some(Self {
+            after_failures: after_failures.max(1),
+            probe_interval,
+            redirect_to_stderr,
+            consecutive_failures: 0,
+            o_degraded: None,
+        })
+    }
+
+    // In the degraded mode, diverts the log line, unless writing to the file should be probed.
+    // Returns true if the log line was diverted.
+    pub(super) fn divert_unless_probing(&mut self, buf: &[u8]) -> bool {
+        match self.o_degraded {
+            Some(ref degraded) if degraded.last_probe.elapsed() < self.probe_interval => {
+                self.divert(buf);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Evaluates the result of writing the log line to the file.
+    // Errors are only handed back until the degraded mode is entered.
+    pub(super) fn check(&mut self, result: std::io::Result<()>, buf: &[u8]) -> std::io::Result<()> {
+        match result {
+            Ok(()) => {
+                self.consecutive_failures = 0;
+                if let Some(degraded) = self.o_degraded.take() {
+                    eprint_msg(
+                        ErrorCode::WriteResumed,
+                        &format!(
+                            "writing to the log file works again, after {:?}; \
+                             {} log lines were {} meanwhile",
+                            degraded.since.elapsed(),
+                            degraded.diverted_lines,
+                            self.diverted_to(),
+                        ),
+                    );
+                }
+                Ok(())
+            }
+            Err(e) => {
+                self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+                if let Some(ref mut degraded) = self.o_degraded {
+                    degraded.last_probe = Instant::now();
+                } else if self.consecutive_failures >= self.after_failures {
+                    eprint_err(
+                        ErrorCode::Write,
+                        &format!(
+                            "writing failed {} times in a row, further log lines are {} \
+                             until writing works again",
+                            self.consecutive_failures,
+                            self.diverted_to(),
+                        ),
+                        &e,
+                    );
+                    let now = Instant::now();
+                    self.o_degraded = Some(Degraded {
+                        since: now,
+                        last_probe: now,
+                        diverted_lines: 0,
+                    });
+                } else {
+                    return Err(e);
+                }
+                self.divert(buf);
+                Ok(())
+            }
+        }
+    }
+
+    // Fails while the output is degraded.
+    pub(super) fn healthcheck(&self) -> std::io::Result<()> {
+        match self.o_degraded {
+            Some(ref degraded) => Err(std::io::Error::new(
+                std::io::ErrorKind::Other,
+                format!(
+                    "writing to the log file fails, log lines are {} since {:?}",
+                    self.diverted_to(),
+                    degraded.since.elapsed(),
+                ),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn divert(&mut self, buf: &[u8]) {
+        if self.redirect_to_stderr {
+            std::io::stderr().write_all(buf).ok();
+        }
+        if let Some(ref mut degraded) = self.o_degraded {
+            degraded.diverted_lines += 1;
+        }
+    }
+
+    fn diverted_to(&self) -> &'static str {
+        if self.redirect_to_stderr {
+            "written to stderr"
+        } else {
+            "dropped"
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::WriteFailureWatchdog;
+    use crate::WriteFailurePolicy;
+    use std::time::Duration;
+
+    #[test]
+    fn degrades_and_recovers() {
+        let mut watchdog = WriteFailureWatchdog::new(WriteFailurePolicy::Drop {
+            after_failures: 2,
+            probe_interval: Duration::from_millis(50),
+        })
+        .unwrap();
+        let failure = || Err(std::io::Error::new(std::io::ErrorKind::Other, "disk full"));
+
+        assert!(!watchdog.divert_unless_probing(b"1"));
+        assert!(watchdog.check(failure(), b"1").is_err());
+        assert!(!watchdog.divert_unless_probing(b"2"));
+        assert!(watchdog.check(failure(), b"2").is_ok());
+
+        // degraded: lines are dropped until the probe interval has elapsed
+        assert!(watchdog.divert_unless_probing(b"3"));
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(!watchdog.divert_unless_probing(b"4"));
+        assert!(watchdog.check(failure(), b"4").is_ok());
+        assert!(watchdog.divert_unless_probing(b"5"));
+        assert_eq!(watchdog.o_degraded.as_ref().unwrap().diverted_lines, 4);
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(!watchdog.divert_unless_probing(b"6"));
+        assert!(watchdog.check(Ok(()), b"6").is_ok());
+        assert!(watchdog.o_degraded.is_none());
+        assert!(!watchdog.divert_unless_probing(b"7"));
+    }
+}
@@ -0,0 +1,48 @@
+//! Alternative outputs for log files that can't be written, see `Fallback`.
+use super::super::config::{FileLogWriterConfig, ProvidedOutput};
+use crate::{
+    util::{eprint_err, ErrorCode},
+    Fallback,
+};
+
+// Switches the configuration to the next usable fallback, and reports this.
+// Returns false if no fallback is left.
+pub(crate) fn fall_back(config: &mut FileLogWriterConfig, error: &std::io::Error) -> bool {
+    while !config.fallbacks.is_empty() {
+        let failed = config.file_spec.get_directory();
+        let (fallback_desc, result) = match config.fallbacks.remove(0) {
+            Fallback::Directory(directory) => {
+                let desc = format!("directory {}", directory.display());
+                let result = std::fs::create_dir_all(&directory).map(|()| {
+                    config.file_spec = config.file_spec.clone().directory(directory);
+                });
+                (desc, result)
+            }
+            Fallback::Stderr => {
+                config.o_provided_output = Some(ProvidedOutput::Stderr);
+                ("stderr".to_string(), Ok(()))
+            }
+        };
+        match result {
+            Ok(()) => {
+                eprint_err(
+                    ErrorCode::LogFile,
+                    &format!(
+                        "can't write log files to directory {}, falling back to {fallback_desc}",
+                        failed.display()
+                    ),
+                    error,
+                );
+                return true;
+            }
+            Err(e) => {
+                eprint_err(
+                    ErrorCode::LogFile,
+                    &format!("can't use fallback {fallback_desc}"),
+                    &e,
+                );
+            }
+        }
+    }
+    false
+}
@@ -0,0 +1,54 @@
+//! Removal of artifacts that an earlier run left behind,
+//! see `FileLogWriterBuilder::remove_stale_artifacts`.
+use super::super::config::FileLogWriterConfig;
+use crate::util::{eprint_err, ErrorCode};
+use std::path::Path;
+
+// Extension of the temporary file that is used when reopening the output file fails.
+pub(super) const REOPEN_TEMP_EXTENSION: &str = "ShortLivingTempFileForReOpen";
+
+// Removes, in the log directory, the empty temporary files of reopening the output,
+// and the symbolic links that point to no longer existing files, and the configured
+// symbolic link if it points to a no longer existing file.
+// Only artifacts with names that start with the fixed name part of the file spec are removed.
+pub(super) fn remove_stale_artifacts(config: &FileLogWriterConfig) {
+    let fixed_name_part = config.file_spec.fixed_name_part();
+    let belongs_to_spec = |path: &Path| {
+        path.file_name()
+            .is_some_and(|name| name.to_string_lossy().starts_with(&fixed_name_part))
+    };
+    let is_stale_link = |link: &Path| {
+        std::fs::symlink_metadata(link).is_ok_and(|md| md.file_type().is_symlink())
+            && std::fs::read_link(link).is_ok_and(|target| belongs_to_spec(&target))
+            && !link.exists()
+    };
+
+    let Ok(entries) = std::fs::read_dir(config.file_spec.get_directory()) else {
+        return;
+    };
+    for path in entries.flatten().map(|entry| entry.path()) {
+        let is_reopen_temp_file = belongs_to_spec(&path)
+            && path
+                .extension()
+                .is_some_and(|ext| ext == REOPEN_TEMP_EXTENSION)
+            && std::fs::symlink_metadata(&path).is_ok_and(|md| md.is_file() && md.len() == 0);
+        if is_reopen_temp_file || is_stale_link(&path) {
+            remove(&path);
+        }
+    }
+    if let Some(ref link) = config.o_create_symlink {
+        if is_stale_link(link) {
+            remove(link);
+        }
+    }
+}
+
+fn remove(path: &Path) {
+    if let Err(e) = std::fs::remove_file(path) {
+        eprint_err(
+            ErrorCode::LogFile,
+            &format!("removing the stale artifact {} failed", path.display()),
+            &e,
+        );
+    }
+}
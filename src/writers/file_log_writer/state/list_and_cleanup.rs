@@ -1,10 +1,25 @@
-use super::InfixFilter;
-use crate::{Cleanup, FileSpec, LogfileSelector};
-#[cfg(feature = "compress")]
-use std::fs::File;
+#[cfg(feature = "manifest")]
+use super::manifest::Manifest;
+use super::{rotation_lock::RotationLock, InfixFilter};
+use crate::{
+    deferred_now::clock_now,
+    parameters::{Archiver, CleanupMonitor, Compression},
+    resources::ThreadRegistration,
+    util::{eprint_err, eprint_msg, ErrorCode},
+    Cleanup, FileSpec, LogfileSelector,
+};
+use chrono::{DateTime, Local};
 use std::{
-    path::PathBuf,
+    cmp::Reverse,
+    path::{Path, PathBuf},
+    sync::{mpsc::Sender, Arc},
     thread::{Builder as ThreadBuilder, JoinHandle},
+    time::Duration,
+};
+#[cfg(feature = "compress")]
+use std::{
+    fs::File,
+    sync::atomic::{AtomicUsize, Ordering},
 };
 
 pub(super) fn list_of_log_and_compressed_files(
@@ -25,18 +40,35 @@ pub(super) fn existing_log_files(
     infix_filter: &InfixFilter,
     selector: &LogfileSelector,
 ) -> Vec<PathBuf> {
+    if use_rotation {
+        if let InfixFilter::ShiftedNumbrs = infix_filter {
+            return super::shifted_numbers::existing_log_files(file_spec, selector);
+        }
+    }
     let mut result = Vec::new();
     let related_files = file_spec.read_dir_related_files();
     if use_rotation {
         if selector.with_plain_files {
-            result.append(&mut file_spec.filter_files(
-                &related_files,
+            result.append(&mut newest_first(
+                file_spec,
                 infix_filter,
-                file_spec.get_suffix().as_deref(),
+                file_spec.filter_files(
+                    &related_files,
+                    infix_filter,
+                    file_spec.get_suffix().as_deref(),
+                ),
             ));
         }
         if selector.with_compressed_files {
-            result.append(&mut file_spec.filter_files(&related_files, infix_filter, Some("gz")));
+            result.append(&mut newest_first(
+                file_spec,
+                infix_filter,
+                file_spec.filter_files(
+                    &related_files,
+                    infix_filter,
+                    Some(&file_spec.compressed_suffix),
+                ),
+            ));
         }
         if selector.with_r_current {
             result.append(&mut file_spec.filter_files(
@@ -48,7 +80,7 @@ pub(super) fn existing_log_files(
         if let Some(ref custom_current) = selector.with_configured_current {
             result.append(&mut file_spec.filter_files(
                 &related_files,
-                &InfixFilter::Equls(custom_current.to_string()),
+                &InfixFilter::Equls(custom_current.clone()),
                 file_spec.get_suffix().as_deref(),
             ));
         }
@@ -58,50 +90,74 @@ pub(super) fn existing_log_files(
     result
 }
 
+// The files are ordered by name, descending, which does not fit for numbers of different widths.
+fn newest_first(
+    file_spec: &FileSpec,
+    infix_filter: &InfixFilter,
+    mut files: Vec<PathBuf>,
+) -> Vec<PathBuf> {
+    if let InfixFilter::Numbrs = infix_filter {
+        files.sort_by_cached_key(|file| Reverse(super::numbers::index_of(file_spec, file)));
+    }
+    files
+}
+
+#[allow(clippy::too_many_arguments)]
 pub(super) fn remove_or_compress_too_old_logfiles(
-    o_cleanup_thread_handle: Option<&CleanupThreadHandle>,
+    o_cleanup_thread_handle: Option<&mut CleanupThreadHandle>,
     cleanup_config: &Cleanup,
+    o_archiver: Option<&Archiver>,
+    compression: &Compression,
     file_spec: &FileSpec,
     infix_filter: &InfixFilter,
     writes_direct: bool,
+    #[cfg(feature = "manifest")] o_manifest: Option<&Manifest>,
+    cleanup_monitor: &CleanupMonitor,
 ) -> Result<(), std::io::Error> {
-    o_cleanup_thread_handle.as_ref().map_or_else(
-        || {
-            remove_or_compress_too_old_logfiles_impl(
-                cleanup_config,
-                file_spec,
-                infix_filter,
-                writes_direct,
-            )
-        },
-        |cleanup_thread_handle| {
-            cleanup_thread_handle
-                .sender
-                .send(MessageToCleanupThread::Act)
-                .ok();
-            Ok(())
-        },
-    )
+    if let Some(cleanup_thread_handle) = o_cleanup_thread_handle {
+        cleanup_thread_handle.trigger();
+        Ok(())
+    } else {
+        remove_or_compress_too_old_logfiles_impl(
+            cleanup_config,
+            o_archiver,
+            compression,
+            file_spec,
+            infix_filter,
+            writes_direct,
+            #[cfg(feature = "manifest")]
+            o_manifest,
+        )
+        .map_err(|e| {
+            cleanup_monitor.failure(e.to_string());
+            e
+        })
+    }
 }
 
+#[cfg_attr(not(feature = "compress"), allow(unused_variables))]
 pub(crate) fn remove_or_compress_too_old_logfiles_impl(
     cleanup_config: &Cleanup,
+    o_archiver: Option<&Archiver>,
+    compression: &Compression,
     file_spec: &FileSpec,
     infix_filter: &InfixFilter,
     writes_direct: bool,
+    #[cfg(feature = "manifest")] o_manifest: Option<&Manifest>,
 ) -> Result<(), std::io::Error> {
-    let (mut log_limit, compress_limit) = match *cleanup_config {
+    let (mut log_limit, compress_limit, o_retention) = match *cleanup_config {
         Cleanup::Never => {
             return Ok(());
         }
-        Cleanup::KeepLogFiles(log_limit) => (log_limit, 0),
+        Cleanup::KeepLogFiles(log_limit) => (log_limit, 0, None),
+        Cleanup::KeepLogFilesAndDuration(log_limit, retention) => (log_limit, 0, Some(retention)),
 
         #[cfg(feature = "compress")]
-        Cleanup::KeepCompressedFiles(compress_limit) => (0, compress_limit),
+        Cleanup::KeepCompressedFiles(compress_limit) => (0, compress_limit, None),
 
         #[cfg(feature = "compress")]
         Cleanup::KeepLogAndCompressedFiles(log_limit, compress_limit) => {
-            (log_limit, compress_limit)
+            (log_limit, compress_limit, None)
         }
     };
 
@@ -110,53 +166,210 @@ pub(crate) fn remove_or_compress_too_old_logfiles_impl(
         log_limit = 1;
     }
 
+    #[cfg(feature = "compress")]
+    remove_partial_files(file_spec, &file_spec.compressed_suffix);
+
+    #[cfg(feature = "compress")]
+    let mut files_to_compress = Vec::new();
     for (index, file) in list_of_log_and_compressed_files(file_spec, infix_filter)
         .into_iter()
         .enumerate()
     {
         if index >= log_limit + compress_limit {
-            // delete (log or log.gz)
-            std::fs::remove_file(file)?;
+            if o_retention.is_some_and(|retention| is_younger_than(&file, retention)) {
+                continue;
+            }
+            // archive, if configured, and delete (log or log.gz)
+            if let Some(archiver) = o_archiver {
+                if let Err(e) = archiver.archive(&file) {
+                    eprint_err(
+                        ErrorCode::Archive,
+                        &format!("archiving {} failed, file is kept", file.display()),
+                        &e,
+                    );
+                    continue;
+                }
+            }
+            std::fs::remove_file(&file)?;
+            #[cfg(feature = "manifest")]
+            if let Some(manifest) = o_manifest {
+                manifest.deleted(&file);
+            }
         } else if index >= log_limit {
+            // compress, if not yet compressed
             #[cfg(feature = "compress")]
+            if file
+                .extension()
+                .is_some_and(|extension| *extension != *file_spec.compressed_suffix)
             {
-                // compress, if not yet compressed
-                if let Some(extension) = file.extension() {
-                    if extension != "gz" {
-                        let mut compressed_file = file.clone();
-                        match compressed_file.extension() {
-                            Some(oss) => {
-                                let mut oss_gz = oss.to_os_string();
-                                oss_gz.push(".gz");
-                                compressed_file.set_extension(oss_gz.as_os_str());
-                            }
-                            None => {
-                                compressed_file.set_extension("gz");
-                            }
-                        }
-
-                        let mut gz_encoder = flate2::write::GzEncoder::new(
-                            File::create(compressed_file)?,
-                            flate2::Compression::fast(),
-                        );
-                        let mut old_file = File::open(file.clone())?;
-                        std::io::copy(&mut old_file, &mut gz_encoder)?;
-                        gz_encoder.finish()?;
-                        std::fs::remove_file(&file)?;
-                    }
-                }
+                files_to_compress.push(file);
             }
         }
     }
 
+    #[cfg(feature = "compress")]
+    compress_files(
+        compression,
+        &file_spec.compressed_suffix,
+        &files_to_compress,
+        #[cfg(feature = "manifest")]
+        o_manifest,
+    )?;
+
+    Ok(())
+}
+
+// The age is measured with the clock of the logger, see DeferredNow::set_clock.
+// A file whose age can't be determined is considered young.
+fn is_younger_than(file: &Path, retention: Duration) -> bool {
+    std::fs::metadata(file)
+        .and_then(|metadata| metadata.modified())
+        .map_or(true, |modified| {
+            clock_now()
+                .signed_duration_since(DateTime::<Local>::from(modified))
+                .to_std()
+                .map_or(true, |age| age < retention)
+        })
+}
+
+// Compresses the files with at most the configured number of worker threads.
+#[cfg(feature = "compress")]
+fn compress_files(
+    compression: &Compression,
+    compressed_suffix: &str,
+    files: &[PathBuf],
+    #[cfg(feature = "manifest")] o_manifest: Option<&Manifest>,
+) -> Result<(), std::io::Error> {
+    let workers = std::cmp::min(compression.workers, files.len());
+    if workers <= 1 {
+        for file in files {
+            compress_file(
+                compression,
+                compressed_suffix,
+                file,
+                #[cfg(feature = "manifest")]
+                o_manifest,
+            )?;
+        }
+        return Ok(());
+    }
+
+    let next_file = AtomicUsize::new(0);
+    let work = || -> Result<(), std::io::Error> {
+        let _registration = ThreadRegistration::new(COMPRESSOR);
+        while let Some(file) = files.get(next_file.fetch_add(1, Ordering::Relaxed)) {
+            compress_file(
+                compression,
+                compressed_suffix,
+                file,
+                #[cfg(feature = "manifest")]
+                o_manifest,
+            )?;
+        }
+        Ok(())
+    };
+    std::thread::scope(|scope| {
+        let mut join_handles = Vec::with_capacity(workers);
+        for _ in 0..workers {
+            let builder = ThreadBuilder::new().name(COMPRESSOR.to_string());
+            #[cfg(not(feature = "dont_minimize_extra_stacks"))]
+            let builder = builder.stack_size(512 * 1024);
+            join_handles.push(builder.spawn_scoped(scope, work)?);
+        }
+        // all workers are joined before the first error is returned
+        let results = join_handles
+            .into_iter()
+            .map(|join_handle| {
+                join_handle.join().unwrap_or_else(|payload| {
+                    Err(std::io::Error::new(
+                        std::io::ErrorKind::Other,
+                        format!("compression thread panicked: {}", panic_message(&payload)),
+                    ))
+                })
+            })
+            .collect::<Vec<_>>();
+        results.into_iter().collect()
+    })
+}
+
+// Removes the incomplete compressed files that an interrupted cleanup has left behind.
+#[cfg(feature = "compress")]
+fn remove_partial_files(file_spec: &FileSpec, compressed_suffix: &str) {
+    let partial_ending = format!(".{compressed_suffix}{PARTIAL}");
+    for file in file_spec.read_dir_related_files() {
+        if file.to_string_lossy().ends_with(&partial_ending) {
+            std::fs::remove_file(&file).unwrap_or_else(|e| {
+                eprint_err(
+                    ErrorCode::LogFile,
+                    &format!("cannot remove {}", file.display()),
+                    &e,
+                );
+            });
+        }
+    }
+}
+
+// The compressed file is written under a temporary name and renamed when it is complete.
+// A compressed file that exists already, e.g. after an interrupted cleanup, is not trusted,
+// but replaced.
+#[cfg(feature = "compress")]
+fn compress_file(
+    compression: &Compression,
+    compressed_suffix: &str,
+    file: &Path,
+    #[cfg(feature = "manifest")] o_manifest: Option<&Manifest>,
+) -> Result<(), std::io::Error> {
+    let mut compressed_file = file.as_os_str().to_os_string();
+    compressed_file.push(".");
+    compressed_file.push(compressed_suffix);
+    let compressed_file = PathBuf::from(compressed_file);
+
+    let mut temp_file = compressed_file.clone().into_os_string();
+    temp_file.push(PARTIAL);
+    let temp_file = PathBuf::from(temp_file);
+    let mut target = File::create(&temp_file)?;
+    let result = File::open(file)
+        .and_then(|mut source| compression.compress(&mut source, &mut target))
+        .and_then(|()| target.sync_all());
+    drop(target);
+    if let Err(e) = result.and_then(|()| std::fs::rename(&temp_file, &compressed_file)) {
+        std::fs::remove_file(&temp_file).ok();
+        return Err(e);
+    }
+    std::fs::remove_file(file)?;
+    #[cfg(feature = "manifest")]
+    if let Some(manifest) = o_manifest {
+        manifest.compressed(file, &compressed_file);
+    }
     Ok(())
 }
 
 const CLEANER: &str = "flexi_logger-fs-cleanup";
+#[cfg(feature = "compress")]
+const COMPRESSOR: &str = "flexi_logger-fs-compressor";
+// the additional suffix of a compressed file while it is written
+#[cfg(feature = "compress")]
+const PARTIAL: &str = ".partial";
+
+// Everything the cleanup thread needs, also for restarting it.
+#[derive(Debug)]
+pub(super) struct CleanupThreadParams {
+    pub(super) cleanup: Cleanup,
+    pub(super) o_archiver: Option<Archiver>,
+    pub(super) compression: Compression,
+    pub(super) file_spec: FileSpec,
+    pub(super) infix_filter: InfixFilter,
+    pub(super) writes_direct: bool,
+    pub(super) use_rotation_lock: bool,
+    #[cfg(feature = "manifest")]
+    pub(super) o_manifest: Option<Arc<Manifest>>,
+    pub(super) a_cleanup_monitor: Arc<CleanupMonitor>,
+}
 
 #[derive(Debug)]
 pub(super) struct CleanupThreadHandle {
-    sender: std::sync::mpsc::Sender<MessageToCleanupThread>,
+    a_params: Arc<CleanupThreadParams>,
+    sender: Sender<MessageToCleanupThread>,
     join_handle: JoinHandle<()>,
 }
 
@@ -165,35 +378,95 @@ enum MessageToCleanupThread {
     Die,
 }
 impl CleanupThreadHandle {
+    pub(super) fn start(params: CleanupThreadParams) -> Result<Self, std::io::Error> {
+        let a_params = Arc::new(params);
+        let (sender, join_handle) = spawn_cleanup_thread(&a_params)?;
+        Ok(Self {
+            a_params,
+            sender,
+            join_handle,
+        })
+    }
+
+    // Lets the cleanup thread do its work, after restarting it if it died.
+    fn trigger(&mut self) {
+        if !self.join_handle.is_finished() && self.sender.send(MessageToCleanupThread::Act).is_ok()
+        {
+            return;
+        }
+        match spawn_cleanup_thread(&self.a_params) {
+            Ok((sender, join_handle)) => {
+                self.sender = sender;
+                let dead_thread = std::mem::replace(&mut self.join_handle, join_handle);
+                let reason = match dead_thread.join() {
+                    Ok(()) => "cleanup thread terminated unexpectedly".to_string(),
+                    Err(payload) => format!("cleanup thread panicked: {}", panic_message(&payload)),
+                };
+                eprint_msg(ErrorCode::Cleanup, &format!("{reason}; restarted it"));
+                self.a_params.a_cleanup_monitor.thread_restart(reason);
+                self.sender.send(MessageToCleanupThread::Act).ok();
+            }
+            Err(e) => {
+                eprint_err(
+                    ErrorCode::Cleanup,
+                    "restarting the cleanup thread failed",
+                    &e,
+                );
+                self.a_params
+                    .a_cleanup_monitor
+                    .failure(format!("restarting the cleanup thread failed: {e}"));
+            }
+        }
+    }
+
     pub(super) fn shutdown(self) {
         self.sender.send(MessageToCleanupThread::Die).ok();
         self.join_handle.join().ok();
     }
 }
 
-pub(super) fn start_cleanup_thread(
-    cleanup: Cleanup,
-    file_spec: FileSpec,
-    infix_filter: &InfixFilter,
-    writes_direct: bool,
-) -> Result<CleanupThreadHandle, std::io::Error> {
+fn spawn_cleanup_thread(
+    a_params: &Arc<CleanupThreadParams>,
+) -> Result<(Sender<MessageToCleanupThread>, JoinHandle<()>), std::io::Error> {
     let (sender, receiver) = std::sync::mpsc::channel();
     let builder = ThreadBuilder::new().name(CLEANER.to_string());
     #[cfg(not(feature = "dont_minimize_extra_stacks"))]
     let builder = builder.stack_size(512 * 1024);
-    let infix_filter_cp = infix_filter.clone();
-    Ok(CleanupThreadHandle {
-        sender,
-        join_handle: builder.spawn(move || {
-            while let Ok(MessageToCleanupThread::Act) = receiver.recv() {
-                remove_or_compress_too_old_logfiles_impl(
-                    &cleanup,
-                    &file_spec,
-                    &infix_filter_cp,
-                    writes_direct,
-                )
-                .ok();
+    let params = Arc::clone(a_params);
+    let registration = ThreadRegistration::new(CLEANER);
+    let join_handle = builder.spawn(move || {
+        let _registration = registration;
+        while let Ok(MessageToCleanupThread::Act) = receiver.recv() {
+            let _o_lock = if params.use_rotation_lock {
+                match RotationLock::acquire(&params.file_spec) {
+                    Ok(lock) => Some(lock),
+                    Err(_) => continue,
+                }
+            } else {
+                None
+            };
+            if let Err(e) = remove_or_compress_too_old_logfiles_impl(
+                &params.cleanup,
+                params.o_archiver.as_ref(),
+                &params.compression,
+                &params.file_spec,
+                &params.infix_filter,
+                params.writes_direct,
+                #[cfg(feature = "manifest")]
+                params.o_manifest.as_deref(),
+            ) {
+                eprint_err(ErrorCode::Cleanup, "cleanup of log files failed", &e);
+                params.a_cleanup_monitor.failure(e.to_string());
             }
-        })?,
-    })
+        }
+    })?;
+    Ok((sender, join_handle))
+}
+
+fn panic_message(payload: &Box<dyn std::any::Any + Send>) -> String {
+    payload
+        .downcast_ref::<&str>()
+        .map(ToString::to_string)
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown reason".to_string())
 }
@@ -0,0 +1,172 @@
+//! Manifest of the rotated log files, see `FileLogWriterBuilder::manifest`.
+use crate::{
+    deferred_now::clock_now,
+    util::{eprint_err, ErrorCode},
+    FileSpec,
+};
+use chrono::{DateTime, Local, SecondsFormat};
+use std::{
+    fmt::Write as _,
+    fs::OpenOptions,
+    io::Write,
+    path::{Path, PathBuf},
+    sync::Mutex,
+};
+
+// Appends an entry for each rotated, compressed, or deleted log file to the manifest file.
+// Is shared by the writer and the cleanup thread.
+#[derive(Debug)]
+pub(crate) struct Manifest {
+    path: PathBuf,
+    lock: Mutex<()>,
+}
+impl Manifest {
+    pub(super) fn new(file_spec: &FileSpec) -> Self {
+        Self {
+            path: manifest_path(file_spec),
+            lock: Mutex::new(()),
+        }
+    }
+
+    // The file was rotated after the given writes.
+    pub(super) fn rotated(&self, path: &Path, write_span: WriteSpan) {
+        self.append_or_report(|| {
+            let mut entry = Entry::new("rotated", path).with_content(path)?;
+            if let Some((first, last)) = write_span.0 {
+                entry.add_str("first_write", &rfc3339(&first));
+                entry.add_str("last_write", &rfc3339(&last));
+            }
+            Ok(entry)
+        });
+    }
+
+    // The source file was compressed into the given path, and then deleted.
+    #[cfg(feature = "compress")]
+    pub(crate) fn compressed(&self, source: &Path, path: &Path) {
+        self.append_or_report(|| {
+            let mut entry = Entry::new("compressed", path).with_content(path)?;
+            entry.add_str("source", &file_name(source));
+            Ok(entry)
+        });
+    }
+
+    // The file was deleted by the cleanup.
+    pub(crate) fn deleted(&self, path: &Path) {
+        self.append_or_report(|| Ok(Entry::new("deleted", path)));
+    }
+
+    fn append_or_report<F>(&self, f: F)
+    where
+        F: FnOnce() -> Result<Entry, std::io::Error>,
+    {
+        let result = f().and_then(|entry| {
+            let _guard = self
+                .lock
+                .lock()
+                .unwrap_or_else(std::sync::PoisonError::into_inner);
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&self.path)?
+                .write_all(entry.finish().as_bytes())
+        });
+        if let Err(e) = result {
+            eprint_err(ErrorCode::LogFile, "writing the manifest failed", &e);
+        }
+    }
+}
+
+// The times of the first and the last write into the current output file.
+#[derive(Clone, Copy, Debug, Default)]
+pub(super) struct WriteSpan(Option<(DateTime<Local>, DateTime<Local>)>);
+impl WriteSpan {
+    pub(super) fn record(&mut self) {
+        let now = clock_now();
+        match self.0 {
+            Some((_, ref mut last)) => *last = now,
+            None => self.0 = Some((now, now)),
+        }
+    }
+
+    pub(super) fn take(&mut self) -> Self {
+        Self(self.0.take())
+    }
+}
+
+// The manifest file does not match the filters for log files, so it is never cleaned up.
+pub(super) fn manifest_path(file_spec: &FileSpec) -> PathBuf {
+    let mut path = file_spec.get_directory();
+    path.push(format!("{}.manifest.jsonl", file_spec.fixed_name_part()));
+    path
+}
+
+// A line of the manifest, a JSON object.
+struct Entry(String);
+impl Entry {
+    fn new(event: &str, path: &Path) -> Self {
+        let mut entry = Self(String::with_capacity(256));
+        entry.add_str("event", event);
+        entry.add_str("time", &rfc3339(&clock_now()));
+        entry.add_str("file", &file_name(path));
+        entry
+    }
+
+    fn with_content(mut self, path: &Path) -> Result<Self, std::io::Error> {
+        self.add_raw("size", &std::fs::metadata(path)?.len().to_string());
+        self.add_str("sha256", &sha256(path)?);
+        Ok(self)
+    }
+
+    fn add_str(&mut self, key: &str, value: &str) {
+        let mut quoted = String::with_capacity(value.len() + 2);
+        quoted.push('"');
+        for c in value.chars() {
+            match c {
+                '"' => quoted.push_str("\\\""),
+                '\\' => quoted.push_str("\\\\"),
+                c if c.is_control() => {
+                    write!(quoted, "\\u{:04x}", u32::from(c)).ok();
+                }
+                c => quoted.push(c),
+            }
+        }
+        quoted.push('"');
+        self.add_raw(key, &quoted);
+    }
+
+    fn add_raw(&mut self, key: &str, value: &str) {
+        self.0.push(if self.0.is_empty() { '{' } else { ',' });
+        self.0.push('"');
+        self.0.push_str(key);
+        self.0.push_str("\":");
+        self.0.push_str(value);
+    }
+
+    fn finish(mut self) -> String {
+        self.0.push_str("}\n");
+        self.0
+    }
+}
+
+fn file_name(path: &Path) -> String {
+    path.file_name()
+        .map_or_else(String::new, |name| name.to_string_lossy().to_string())
+}
+
+fn rfc3339(ts: &DateTime<Local>) -> String {
+    ts.to_rfc3339_opts(SecondsFormat::Micros, false)
+}
+
+fn sha256(path: &Path) -> Result<String, std::io::Error> {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut std::fs::File::open(path)?, &mut hasher)?;
+    Ok(hasher
+        .finalize()
+        .iter()
+        .fold(String::with_capacity(64), |mut hex, byte| {
+            write!(hex, "{byte:02x}").ok();
+            hex
+        }))
+}
@@ -1,32 +1,69 @@
 //! The infix for rotated files contains an index number.
 use super::{InfixFilter, CURRENT_INFIX};
-use crate::{writers::FileLogWriterConfig, FileSpec};
-use std::cmp::max;
+use crate::{writers::file_log_writer::FileLogWriterConfig, FileSpec, Naming};
+use std::{cmp::max, path::PathBuf};
 
-pub(super) fn number_infix(idx: u32) -> String {
-    format!("r{idx:0>5}")
+// The width of the number infix and the index of the first file
+#[derive(Clone, Copy, Debug)]
+pub(super) struct Numbering {
+    width: usize,
+    start: u32,
 }
+impl Numbering {
+    const DEFAULT: Self = Self { width: 5, start: 0 };
 
+    // Returns also if the logs are written directly to the numbered files.
+    pub(super) fn of(naming: Naming) -> (bool, Self) {
+        match naming {
+            Naming::Numbers => (false, Self::DEFAULT),
+            Naming::NumbersDirect => (true, Self::DEFAULT),
+            Naming::NumbersWith {
+                direct,
+                width,
+                start,
+            } => (direct, Self { width, start }),
+            Naming::Timestamps
+            | Naming::NumbersShifted
+            | Naming::TimestampsDirect
+            | Naming::TimestampsCustomFormat { .. } => unreachable!("not a numbering"),
+        }
+    }
+
+    pub(super) fn infix(self, idx: u32) -> String {
+        format!("r{idx:0>width$}", width = self.width)
+    }
+
+    // The index of the next file, if the highest existing index is known
+    pub(super) fn next(self, o_highest_idx: Option<u32>) -> u32 {
+        o_highest_idx.map_or(self.start, |idx| max(idx + 1, self.start))
+    }
+}
+
+// Returns the index to which rCURRENT will be rotated next, and, if rCURRENT is rotated now,
+// the path of the rotated file.
 pub(super) fn index_for_rcurrent(
     config: &FileLogWriterConfig,
+    numbering: Numbering,
     o_index_for_rcurrent: Option<u32>,
     rotate_rcurrent: bool,
-) -> Result<u32, std::io::Error> {
+) -> Result<(u32, Option<PathBuf>), std::io::Error> {
     // we believe what we get - but if we get nothing, we determine what's next
     // according to the filesystem
     let mut index_for_rcurrent = o_index_for_rcurrent
-        .or_else(|| get_highest_index(&config.file_spec).map(|idx| idx + 1))
-        .unwrap_or(0);
+        .unwrap_or_else(|| numbering.next(get_highest_index(&config.file_spec)));
 
+    let mut o_rotated_path = None;
     if rotate_rcurrent {
+        let rotated_path = config
+            .file_spec
+            .as_pathbuf(Some(&numbering.infix(index_for_rcurrent)));
         match std::fs::rename(
             config.file_spec.as_pathbuf(Some(CURRENT_INFIX)),
-            config
-                .file_spec
-                .as_pathbuf(Some(&number_infix(index_for_rcurrent))),
+            &rotated_path,
         ) {
             Ok(()) => {
                 index_for_rcurrent += 1;
+                o_rotated_path = Some(rotated_path);
             }
             Err(e) => {
                 if e.kind() != std::io::ErrorKind::NotFound {
@@ -35,34 +72,23 @@ pub(super) fn index_for_rcurrent(
             }
         }
     }
-    Ok(index_for_rcurrent)
+    Ok((index_for_rcurrent, o_rotated_path))
 }
 
+// Files with number infixes of any width are considered.
 pub(super) fn get_highest_index(file_spec: &FileSpec) -> Option<u32> {
-    let mut o_highest_idx = None;
-    for file in
-        super::list_and_cleanup::list_of_log_and_compressed_files(file_spec, &InfixFilter::Numbrs)
-    {
-        let name = file.file_stem().unwrap(/*ok*/).to_string_lossy();
-        let infix = if file_spec.has_basename()
-            || file_spec.has_discriminant()
-            || file_spec.uses_timestamp()
-        {
-            // infix is the last, but not the first part of the name, starts with _r
-            match name.rsplit("_r").next() {
-                Some(infix) => infix,
-                None => continue, // ignore unexpected files
-            }
-        } else {
-            // infix is the only part of the name, just skip over the r
-            &name[1..]
-        };
+    super::list_and_cleanup::list_of_log_and_compressed_files(file_spec, &InfixFilter::Numbrs)
+        .iter()
+        .filter_map(|file| index_of(file_spec, file))
+        .max()
+}
 
-        let idx: u32 = infix.parse().unwrap_or(0);
-        o_highest_idx = match o_highest_idx {
-            None => Some(idx),
-            Some(prev) => Some(max(prev, idx)),
-        };
-    }
-    o_highest_idx
+// The index in the name of a rotated file, also of a compressed one
+pub(super) fn index_of(file_spec: &FileSpec, file: &std::path::Path) -> Option<u32> {
+    let file_name = file.file_name()?.to_string_lossy();
+    file_spec
+        .infix_of(&file_spec.fixed_name_part(), &file_name)?
+        .strip_prefix('r')?
+        .parse()
+        .ok()
 }
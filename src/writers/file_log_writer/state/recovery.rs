@@ -0,0 +1,115 @@
+//! Repairs the end of a log file that was left over by a crashed process.
+use std::{
+    ffi::OsString,
+    fs::{File, OpenOptions},
+    io::{Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+const CHUNK_SIZE: u64 = 8 * 1024;
+
+// Removes everything behind the last line break of the file, i.e. a partially written line
+// or the NUL padding that some file systems produce after a power loss.
+// If `keep_corrupt_tail` is true, the removed bytes are appended to a sidecar file.
+// Returns the number of removed bytes.
+pub(super) fn trim_corrupt_tail(path: &Path, keep_corrupt_tail: bool) -> std::io::Result<u64> {
+    let mut file = match OpenOptions::new().read(true).write(true).open(path) {
+        Ok(file) => file,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+    let len = file.metadata()?.len();
+    let valid_len = valid_length(&mut file, len)?;
+    if valid_len == len {
+        return Ok(0);
+    }
+
+    if keep_corrupt_tail {
+        let mut tail = Vec::new();
+        file.seek(SeekFrom::Start(valid_len))?;
+        Read::by_ref(&mut file)
+            .take(len - valid_len)
+            .read_to_end(&mut tail)?;
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(sidecar_path(path))?
+            .write_all(&tail)?;
+    }
+    file.set_len(valid_len)?;
+    file.sync_all()?;
+    Ok(len - valid_len)
+}
+
+pub(super) fn sidecar_path(path: &Path) -> PathBuf {
+    let mut s: OsString = path.as_os_str().to_owned();
+    s.push(".corrupt");
+    PathBuf::from(s)
+}
+
+// Scans the file backwards for the last line break.
+fn valid_length(file: &mut File, len: u64) -> std::io::Result<u64> {
+    let mut end = len;
+    let mut buf = vec![0_u8; usize::try_from(CHUNK_SIZE).unwrap_or(8192)];
+    while end > 0 {
+        let start = end.saturating_sub(CHUNK_SIZE);
+        let chunk = &mut buf[0..usize::try_from(end - start).unwrap_or_default()];
+        file.seek(SeekFrom::Start(start))?;
+        file.read_exact(chunk)?;
+        if let Some(pos) = chunk.iter().rposition(|b| *b == b'\n') {
+            return Ok(start + pos as u64 + 1);
+        }
+        end = start;
+    }
+    Ok(0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::{sidecar_path, trim_corrupt_tail, CHUNK_SIZE};
+    use std::io::Write;
+
+    fn file_with(dir: &temp_dir::TempDir, name: &str, content: &[u8]) -> std::path::PathBuf {
+        let path = dir.child(name);
+        std::fs::File::create(&path)
+            .unwrap()
+            .write_all(content)
+            .unwrap();
+        path
+    }
+
+    #[test]
+    fn test_trim_corrupt_tail() {
+        let dir = temp_dir::TempDir::new().unwrap();
+
+        let intact = file_with(&dir, "intact.log", b"line 1\nline 2\n");
+        assert_eq!(trim_corrupt_tail(&intact, true).unwrap(), 0);
+        assert_eq!(std::fs::read(&intact).unwrap(), b"line 1\nline 2\n");
+        assert!(!sidecar_path(&intact).exists());
+
+        let partial = file_with(&dir, "partial.log", b"line 1\nline 2\nline");
+        assert_eq!(trim_corrupt_tail(&partial, false).unwrap(), 4);
+        assert_eq!(std::fs::read(&partial).unwrap(), b"line 1\nline 2\n");
+        assert!(!sidecar_path(&partial).exists());
+
+        let padded = file_with(&dir, "padded.log", b"line 1\n\0\0\0\0\0");
+        assert_eq!(trim_corrupt_tail(&padded, true).unwrap(), 5);
+        assert_eq!(std::fs::read(&padded).unwrap(), b"line 1\n");
+        assert_eq!(std::fs::read(sidecar_path(&padded)).unwrap(), b"\0\0\0\0\0");
+
+        let mut content = b"line 1\n".to_vec();
+        content.resize(3 * usize::try_from(CHUNK_SIZE).unwrap(), 0);
+        let long = file_with(&dir, "long.log", &content);
+        trim_corrupt_tail(&long, false).unwrap();
+        assert_eq!(std::fs::read(&long).unwrap(), b"line 1\n");
+
+        let garbage = file_with(&dir, "garbage.log", b"\0\0\0");
+        trim_corrupt_tail(&garbage, false).unwrap();
+        assert!(std::fs::read(&garbage).unwrap().is_empty());
+
+        assert_eq!(
+            trim_corrupt_tail(&dir.child("missing.log"), true).unwrap(),
+            0
+        );
+    }
+}
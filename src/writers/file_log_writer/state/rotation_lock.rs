@@ -0,0 +1,68 @@
+//! Advisory file lock that serializes rotation and cleanup across processes.
+use crate::{writers::file_log_writer::FileLogWriterConfig, FileSpec};
+use std::{
+    fs::{File, OpenOptions},
+    path::PathBuf,
+};
+
+// The lock is held until the value is dropped.
+pub(super) struct RotationLock(#[allow(dead_code)] File);
+impl RotationLock {
+    pub(super) fn acquire_if_configured(
+        config: &FileLogWriterConfig,
+    ) -> Result<Option<Self>, std::io::Error> {
+        if config.rotation_lock {
+            Self::acquire(&config.file_spec).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+
+    // Blocks until the lock is obtained.
+    pub(super) fn acquire(file_spec: &FileSpec) -> Result<Self, std::io::Error> {
+        let file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(false)
+            .open(lock_file_path(file_spec))?;
+        #[cfg(all(feature = "rotation_lock", unix))]
+        rustix::fs::flock(&file, rustix::fs::FlockOperation::LockExclusive)?;
+        Ok(Self(file))
+    }
+}
+
+// The lock file does not match the filters for log files, so it is never cleaned up.
+pub(super) fn lock_file_path(file_spec: &FileSpec) -> PathBuf {
+    let mut path = file_spec.get_directory();
+    path.push(format!("{}.lock", file_spec.fixed_name_part()));
+    path
+}
+
+#[cfg(test)]
+mod test {
+    use super::{
+        super::{InfixFilter, InfixFormat, CURRENT_INFIX},
+        lock_file_path, RotationLock,
+    };
+    use crate::FileSpec;
+
+    #[test]
+    fn lock_file_is_no_log_file() {
+        let dir = temp_dir::TempDir::new().unwrap();
+        let file_spec = FileSpec::default()
+            .directory(dir.path())
+            .basename("test")
+            .suppress_timestamp();
+        let _lock = RotationLock::acquire(&file_spec).unwrap();
+        assert!(lock_file_path(&file_spec).exists());
+
+        for infix_filter in [
+            InfixFilter::Numbrs,
+            InfixFilter::Timstmps(InfixFormat::Std),
+            InfixFilter::Equls(CURRENT_INFIX.to_string()),
+        ] {
+            let files = file_spec.list_of_files(&infix_filter, None);
+            assert!(files.is_empty(), "{files:?}");
+        }
+    }
+}
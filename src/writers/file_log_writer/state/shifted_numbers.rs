@@ -0,0 +1,151 @@
+//! The current file has no infix; the rotated files get the number of their age as
+//! additional extension, like `app.log.1`, `app.log.2.gz`, and are shifted by each rotation.
+use crate::{
+    util::{eprint_msg, ErrorCode},
+    FileSpec, LogfileSelector,
+};
+use std::path::{Path, PathBuf};
+
+// The name of the current file
+fn current_name(file_spec: &FileSpec) -> String {
+    file_spec
+        .as_pathbuf(None)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+fn numbered_path(file_spec: &FileSpec, number: u32, compressed: bool) -> PathBuf {
+    let mut file_name = format!("{}.{number}", current_name(file_spec));
+    if compressed {
+        file_name.push('.');
+        file_name.push_str(&file_spec.compressed_suffix);
+    }
+    file_spec.get_directory().join(file_name)
+}
+
+// The number of a rotated file, and if it is compressed
+fn number_of(current_name: &str, compressed_suffix: &str, path: &Path) -> Option<(u32, bool)> {
+    let rest = path
+        .file_name()?
+        .to_str()?
+        .strip_prefix(current_name)?
+        .strip_prefix('.')?;
+    let (number, compressed) = match rest
+        .strip_suffix(compressed_suffix)
+        .and_then(|r| r.strip_suffix('.'))
+    {
+        Some(number) => (number, true),
+        None => (rest, false),
+    };
+    if number.is_empty() || !number.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    number.parse().ok().map(|number| (number, compressed))
+}
+
+// The rotated files with their numbers and if they are compressed, ordered by number
+fn rotated_files(file_spec: &FileSpec) -> Vec<(u32, bool, PathBuf)> {
+    let current_name = current_name(file_spec);
+    let mut files = std::fs::read_dir(file_spec.get_directory())
+        .map(|read_dir| {
+            read_dir
+                .filter_map(Result::ok)
+                .map(|entry| entry.path())
+                .filter_map(|path| {
+                    number_of(&current_name, &file_spec.compressed_suffix, &path)
+                        .map(|(number, compressed)| (number, compressed, path))
+                })
+                .collect::<Vec<_>>()
+        })
+        .unwrap_or_default();
+    files.sort_unstable();
+    files
+}
+
+// The lowest number is the newest file.
+pub(super) fn existing_log_files(file_spec: &FileSpec, selector: &LogfileSelector) -> Vec<PathBuf> {
+    let files = rotated_files(file_spec);
+    let mut result = Vec::new();
+    if selector.with_plain_files {
+        result.extend(
+            files
+                .iter()
+                .filter(|(_, compressed, _)| !compressed)
+                .map(|(_, _, path)| path.clone()),
+        );
+    }
+    if selector.with_compressed_files {
+        result.extend(
+            files
+                .iter()
+                .filter(|(_, compressed, _)| *compressed)
+                .map(|(_, _, path)| path.clone()),
+        );
+    }
+    if selector.with_r_current {
+        let current = file_spec.as_pathbuf(None);
+        if current.exists() {
+            result.push(current);
+        }
+    }
+    result
+}
+
+// Shifts all rotated files by one, starting with the oldest, so that no file is overwritten,
+// and renames the current file to number 1.
+// A file with the highest possible number can't be shifted and is left alone,
+// and so is every file whose successor is left alone.
+// Returns the path of the rotated file, if the current file exists.
+pub(super) fn rotate(file_spec: &FileSpec) -> Result<Option<PathBuf>, std::io::Error> {
+    // the number of the lowest file that is left alone
+    let mut o_lowest_kept = None;
+    for (number, compressed, path) in rotated_files(file_spec).into_iter().rev() {
+        let Some(next_number) = number
+            .checked_add(1)
+            .filter(|next_number| o_lowest_kept != Some(*next_number))
+        else {
+            eprint_msg(
+                ErrorCode::LogFile,
+                &format!("{} can't be shifted, it is left alone", path.display()),
+            );
+            o_lowest_kept = Some(number);
+            continue;
+        };
+        std::fs::rename(path, numbered_path(file_spec, next_number, compressed))?;
+    }
+    if o_lowest_kept == Some(1) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::Other,
+            "the rotated files can't be shifted, the highest number is reached",
+        ));
+    }
+    let rotated_path = numbered_path(file_spec, 1, false);
+    match std::fs::rename(file_spec.as_pathbuf(None), &rotated_path) {
+        Ok(()) => Ok(Some(rotated_path)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::number_of;
+    use std::path::Path;
+
+    #[test]
+    fn test_number_of() {
+        assert_eq!(
+            number_of("app.log", "gz", Path::new("d/app.log.3")),
+            Some((3, false))
+        );
+        assert_eq!(
+            number_of("app.log", "gz", Path::new("d/app.log.12.gz")),
+            Some((12, true))
+        );
+        assert_eq!(number_of("app.log", "gz", Path::new("d/app.log")), None);
+        assert_eq!(number_of("app.log", "gz", Path::new("d/app.log.gz")), None);
+        assert_eq!(number_of("app.log", "gz", Path::new("d/app.log.x1")), None);
+        assert_eq!(number_of("app.log", "gz", Path::new("d/app.logs.1")), None);
+    }
+}
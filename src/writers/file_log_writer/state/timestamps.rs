@@ -1,6 +1,9 @@
 use super::{get_creation_timestamp, InfixFilter, InfixFormat};
-use crate::{writers::FileLogWriterConfig, FileSpec};
-use chrono::{format::ParseErrorKind, DateTime, Local, NaiveDate, NaiveDateTime, TimeZone};
+use crate::{deferred_now::clock_now, writers::file_log_writer::FileLogWriterConfig, FileSpec};
+use chrono::{
+    format::{Item, Parsed, StrftimeItems},
+    DateTime, FixedOffset, Local, NaiveDate, TimeZone, Utc,
+};
 use std::path::{Path, PathBuf};
 
 pub(super) fn infix_from_timestamp(
@@ -9,7 +12,7 @@ pub(super) fn infix_from_timestamp(
     fmt: &InfixFormat,
 ) -> String {
     if use_utc {
-        ts.naive_utc().format(fmt.format())
+        ts.with_timezone(&Utc).format(fmt.format())
     } else {
         ts.format(fmt.format())
     }
@@ -17,47 +20,77 @@ pub(super) fn infix_from_timestamp(
 }
 
 fn ts_infix_from_path(path: &Path, file_spec: &FileSpec) -> String {
-    let idx = file_spec
-        .as_pathbuf(Some("rXXXXX"))
-        .to_string_lossy()
-        .find("rXXXXX")
-        .unwrap();
-    String::from_utf8_lossy(&path.to_string_lossy().as_bytes()[idx..idx + 20]).to_string()
+    path.file_name()
+        .and_then(|file_name| {
+            file_spec
+                .infix_of(&file_spec.fixed_name_part(), &file_name.to_string_lossy())
+                .map(ToString::to_string)
+        })
+        .unwrap_or_default()
 }
 
+// Parses the infix with the given format; formats with a lower resolution than seconds,
+// like "%Y-%m-%d" or "%Y%m%d%H", are completed with the earliest values of the missing parts,
+// except that a missing hour is set to 10 (to avoid issues with daylight saving time).
+//
+// If the format contains an offset (e.g. "%z"), the infix is interpreted with that offset,
+// otherwise as UTC or local time, depending on `use_utc`.
 pub(crate) fn timestamp_from_ts_infix(
     infix: &str,
+    use_utc: bool,
     fmt: &InfixFormat,
 ) -> Result<DateTime<Local>, String> {
-    match NaiveDateTime::parse_from_str(infix, fmt.format()) {
-        Ok(dt1) => Local
-            .from_local_datetime(&dt1)
+    let mut parsed = Parsed::new();
+    chrono::format::parse(&mut parsed, infix, StrftimeItems::new(fmt.format()))
+        .map_err(|e| format!("Broken: {e:?}"))?;
+
+    let broken = |e| format!("Broken: {e:?}");
+    if parsed.month.is_none()
+        && parsed.ordinal.is_none()
+        && parsed.isoweek.is_none()
+        && parsed.week_from_sun.is_none()
+        && parsed.week_from_mon.is_none()
+    {
+        parsed.set_month(1).map_err(broken)?;
+    }
+    if parsed.day.is_none() && parsed.ordinal.is_none() && parsed.weekday.is_none() {
+        parsed.set_day(1).map_err(broken)?;
+    }
+    if parsed.hour_div_12.is_none() && parsed.hour_mod_12.is_none() {
+        parsed.set_hour(10).map_err(broken)?;
+    }
+    if parsed.minute.is_none() {
+        parsed.set_minute(0).map_err(broken)?;
+    }
+
+    let dt = parsed.to_naive_datetime_with_offset(0).map_err(broken)?;
+    if let Some(offset) = parsed.offset {
+        FixedOffset::east_opt(offset)
+            .and_then(|offset| offset.from_local_datetime(&dt).single())
+            .map(|ts| ts.with_timezone(&Local))
+            .ok_or("Can't determine timestamp from infix with offset".to_string())
+    } else if use_utc {
+        Ok(Utc.from_utc_datetime(&dt).with_timezone(&Local))
+    } else {
+        Local
+            .from_local_datetime(&dt)
             .earliest()
-            .ok_or("Can't determine local time from infix".to_string()),
-        Err(e) if e.kind() == ParseErrorKind::NotEnough => {
-            match NaiveDate::parse_from_str(infix, fmt.format()) {
-                Ok(d1) => {
-                    Local
-                        .from_local_datetime(&d1.and_hms_opt(10, 0, 0).unwrap(/*OK*/))
-                        .earliest()
-                        .ok_or("Can't determine local time from infix".to_string())
-                }
-                Err(e) => Err(format!("Broken: {e:?}")),
-            }
-        }
-        Err(e) => Err(format!("Broken: {e:?}")),
+            .ok_or("Can't determine local time from infix".to_string())
     }
 }
 
+// Returns the creation timestamp of the current file, and, if the current file
+// is rotated now, the path of the rotated file.
 pub(super) fn creation_timestamp_of_currentfile(
     config: &FileLogWriterConfig,
     current_infix: &str,
     rotate_rcurrent: bool,
     o_date_for_rotated_file: Option<&DateTime<Local>>,
     fmt: &InfixFormat,
-) -> Result<DateTime<Local>, std::io::Error> {
+) -> Result<(DateTime<Local>, Option<PathBuf>), std::io::Error> {
     let current_path = config.file_spec.as_pathbuf(Some(current_infix));
 
+    let mut o_rotated_path = None;
     if rotate_rcurrent {
         let date_for_rotated_file = o_date_for_rotated_file
             .copied()
@@ -70,7 +103,7 @@ pub(super) fn creation_timestamp_of_currentfile(
         );
 
         match std::fs::rename(current_path.clone(), rotated_path.clone()) {
-            Ok(()) => {}
+            Ok(()) => o_rotated_path = Some(rotated_path),
             Err(e) => {
                 if e.kind() != std::io::ErrorKind::NotFound {
                     return Err(e);
@@ -78,7 +111,7 @@ pub(super) fn creation_timestamp_of_currentfile(
             }
         }
     }
-    Ok(get_creation_timestamp(&current_path))
+    Ok((get_creation_timestamp(&current_path), o_rotated_path))
 }
 
 // determine the timestamp to which we want to write (file needn't exist)
@@ -88,24 +121,99 @@ pub(super) fn latest_timestamp_file(
     fmt: &InfixFormat,
 ) -> DateTime<Local> {
     if rotate {
-        Local::now()
+        clock_now()
     } else {
         // find all file paths that fit the pattern
         config
             .file_spec
             .list_of_files(
-                &InfixFilter::Numbrs,
+                &InfixFilter::Timstmps(fmt.clone()),
                 config.file_spec.get_suffix().as_deref(),
             )
             .into_iter()
             // retrieve the infix
             .map(|path| ts_infix_from_path(&path, &config.file_spec))
             // parse infix as date, ignore all infixes where this fails
-            .filter_map(|infix| timestamp_from_ts_infix(&infix, fmt).ok())
+            .filter_map(|infix| timestamp_from_ts_infix(&infix, config.use_utc, fmt).ok())
             // take the newest of these dates
             .reduce(|acc, e| if acc > e { acc } else { e })
-            // if nothing is found, take Local::now()
-            .unwrap_or_else(Local::now)
+            // if nothing is found, take the current time
+            .unwrap_or_else(clock_now)
+    }
+}
+
+// Checks that a custom format for timestamp infixes produces infixes that are usable in
+// file names and that can be parsed back into the timestamp they were produced from,
+// with a precision that has no gaps (e.g. "%Y-%d" would produce the same infix for many months).
+// Returns the list of problems, if any.
+pub(crate) fn validate_infix_format(format: &str) -> Result<(), String> {
+    let mut problems = Vec::<String>::new();
+    if StrftimeItems::new(format).any(|item| matches!(item, Item::Error)) {
+        return Err(format!("\"{format}\": contains an unsupported specifier"));
+    }
+
+    let sample = NaiveDate::from_ymd_opt(2024, 11, 23)
+        .and_then(|date| date.and_hms_nano_opt(13, 45, 56, 123_456_789))
+        .and_then(|naive| Local.from_local_datetime(&naive).earliest())
+        .ok_or_else(|| format!("\"{format}\": can't build a sample timestamp"))?;
+    let fmt = InfixFormat::custom(format);
+    let infix = infix_from_timestamp(&sample, false, &fmt);
+
+    if infix.is_empty() {
+        problems.push("produces an empty infix".to_string());
+    }
+    let bad_chars: String = infix
+        .chars()
+        .filter(|c| c.is_control() || ['/', '\\', ':', '*', '?', '"', '<', '>', '|'].contains(c))
+        .collect();
+    if !bad_chars.is_empty() {
+        problems.push(format!(
+            "produces characters that are not allowed in file names: {bad_chars:?}"
+        ));
+    }
+    if infix.contains(".restart-") {
+        problems.push("produces \".restart-\", which is used for collision extensions".to_string());
+    }
+
+    let mut parsed = Parsed::new();
+    if chrono::format::parse(&mut parsed, &infix, StrftimeItems::new(format)).is_err()
+        || !timestamp_from_ts_infix(&infix, false, &fmt)
+            .is_ok_and(|ts| infix_from_timestamp(&ts, false, &fmt) == infix)
+    {
+        problems.push("the infixes can't be parsed back into timestamps".to_string());
+    } else {
+        let levels = [
+            ("year", parsed.year.is_some() || parsed.isoyear.is_some()),
+            (
+                "month",
+                parsed.month.is_some()
+                    || parsed.ordinal.is_some()
+                    || parsed.isoweek.is_some()
+                    || parsed.week_from_sun.is_some()
+                    || parsed.week_from_mon.is_some(),
+            ),
+            (
+                "day",
+                parsed.day.is_some() || parsed.ordinal.is_some() || parsed.weekday.is_some(),
+            ),
+            ("hour", parsed.hour_mod_12.is_some()),
+            ("minute", parsed.minute.is_some()),
+            ("second", parsed.second.is_some()),
+        ];
+        let o_finest = levels.iter().rposition(|(_, present)| *present);
+        for (name, present) in &levels[0..o_finest.unwrap_or(0)] {
+            if !present {
+                problems.push(format!(
+                    "the {name} is missing, so the infixes are not unique and can't be ordered"
+                ));
+            }
+        }
+    }
+
+    if problems.is_empty() {
+        Ok(())
+    } else {
+        Err(format!("\"{format}\": {}", problems.join("; ")))
     }
 }
 
@@ -164,7 +272,9 @@ mod test {
             // retrieve the infix
             .map(|path| super::ts_infix_from_path(path, &file_spec))
             // parse infix as date, ignore all files where this fails,
-            .filter_map(|infix| super::timestamp_from_ts_infix(&infix, &InfixFormat::Std).ok())
+            .filter_map(|infix| {
+                super::timestamp_from_ts_infix(&infix, false, &InfixFormat::Std).ok()
+            })
             // take the newest of these dates
             .reduce(|acc, e| if acc > e { acc } else { e })
             // if nothing is found, take Local::now()
@@ -178,4 +288,104 @@ mod test {
             newest
         );
     }
+
+    #[test]
+    fn test_timestamp_from_custom_ts_infix() {
+        for (format, infix, expected) in [
+            (
+                "%Y-%m-%d_%H-%M-%S",
+                "2024-03-07_13-45-12",
+                "2024-03-07 13:45:12",
+            ),
+            (
+                "r%Y.%m.%d_%H.%M.%S",
+                "r2024.03.07_13.45.12",
+                "2024-03-07 13:45:12",
+            ),
+            ("%Y-%m-%d", "2024-03-07", "2024-03-07 10:00:00"),
+            ("%Y%m%d%H", "2024030713", "2024-03-07 13:00:00"),
+            ("%Y-%m", "2024-03", "2024-03-01 10:00:00"),
+        ] {
+            let fmt = InfixFormat::Custom(format.to_string());
+            assert_eq!(
+                super::timestamp_from_ts_infix(infix, false, &fmt)
+                    .unwrap()
+                    .format("%Y-%m-%d %H:%M:%S")
+                    .to_string(),
+                expected,
+                "format {format}"
+            );
+        }
+        assert!(super::timestamp_from_ts_infix(
+            "2024-03-07",
+            false,
+            &InfixFormat::Custom("%Y%m%d%H".to_string())
+        )
+        .is_err());
+    }
+
+    #[test]
+    fn test_infix_with_subseconds_and_offset() {
+        let ts = chrono::NaiveDate::from_ymd_opt(2024, 3, 7)
+            .unwrap()
+            .and_hms_milli_opt(13, 45, 12, 345)
+            .unwrap()
+            .and_utc()
+            .with_timezone(&Local);
+
+        for (format, use_utc) in [
+            ("%Y-%m-%d_%H-%M-%S.%3f", false),
+            ("%Y-%m-%d_%H-%M-%S.%3f", true),
+            ("%Y-%m-%dT%H%M%S%.3f%z", false),
+            ("%Y-%m-%dT%H%M%S%.3f%z", true),
+        ] {
+            let fmt = InfixFormat::Custom(format.to_string());
+            let infix = super::infix_from_timestamp(&ts, use_utc, &fmt);
+            assert_eq!(
+                super::timestamp_from_ts_infix(&infix, use_utc, &fmt).unwrap(),
+                ts,
+                "format {format}, use_utc {use_utc}"
+            );
+        }
+
+        let fmt = InfixFormat::Custom("%Y-%m-%dT%H%M%S%.3f%z".to_string());
+        assert_eq!(
+            super::infix_from_timestamp(&ts, true, &fmt),
+            "2024-03-07T134512.345+0000"
+        );
+    }
+
+    #[test]
+    fn test_validate_infix_format() {
+        for format in [
+            "r%Y-%m-%d_%H-%M-%S",
+            "%Y-%m-%d",
+            "%Y.%m.%d",
+            "%Y%m%d%H",
+            "%Y-%m",
+            "%Y-%j_%H",
+            "%Y-%m-%d_%H-%M-%S.%3f",
+            "%Y-%m-%dT%H%M%S%z",
+        ] {
+            assert!(
+                super::validate_infix_format(format).is_ok(),
+                "{format}: {:?}",
+                super::validate_infix_format(format)
+            );
+        }
+        for (format, problem) in [
+            ("%Y-%m-%d_%Q", "unsupported specifier"),
+            ("", "empty infix"),
+            ("%Y/%m/%d", "not allowed in file names"),
+            ("%H:%M:%S", "not allowed in file names"),
+            ("%m-%d", "can't be parsed back"),
+            ("%Y-%d", "the month is missing"),
+            ("%Y-%m-%d_%M", "the hour is missing"),
+            ("%Y-%m-%d_%H-%M-%3f", "can't be parsed back"),
+            ("%Y-%m-%d_%H-%M-%S%:z", "not allowed in file names"),
+        ] {
+            let msg = super::validate_infix_format(format).unwrap_err();
+            assert!(msg.contains(problem), "{format}: {msg}");
+        }
+    }
 }
@@ -1,18 +1,22 @@
-use super::{builder::FileLogWriterBuilder, config::FileLogWriterConfig, state::State};
+use super::{
+    builder::FileLogWriterBuilder, config::FileLogWriterConfig, registry::Registration,
+    state::State, RotationEvent,
+};
 #[cfg(feature = "async")]
-use crate::util::{ASYNC_FLUSH, ASYNC_SHUTDOWN};
+use crate::util::{FlushAcks, ASYNC_FLUSH, ASYNC_FLUSH_AND_ACK, ASYNC_SHUTDOWN};
 use crate::{
-    util::{buffer_with, eprint_err, io_err, ErrorCode},
+    threads::FlusherHandle,
+    util::{buffer_with, eprint_err, format_line, io_err, ErrorCode, PostFormat},
     LogfileSelector, ZERO_DURATION,
 };
-use crate::{DeferredNow, FlexiLoggerError, FormatFunction};
+use crate::{CleanupStatus, DeferredNow, EffectiveWriteMode, FlexiLoggerError, FormatFunction};
 use log::Record;
 #[cfg(feature = "async")]
 use std::thread::JoinHandle;
 use std::{
     io::Write,
     path::PathBuf,
-    sync::{Arc, Mutex},
+    sync::{mpsc::Receiver, Arc, Mutex},
 };
 #[cfg(feature = "async")]
 use {crossbeam_channel::Sender, crossbeam_queue::ArrayQueue};
@@ -27,22 +31,32 @@ pub(super) enum StateHandle {
 pub(super) struct SyncHandle {
     am_state: Arc<Mutex<State>>,
     format_function: FormatFunction,
+    post_format: PostFormat,
     line_ending: &'static [u8],
+    o_flusher: Option<FlusherHandle>,
 }
 impl SyncHandle {
     fn new(state: State, format_function: FormatFunction) -> Self {
         let line_ending = state.config().line_ending;
+        let post_format = state.config().post_format();
         let flush_interval = state.config().write_mode.get_flush_interval();
         let am_state = Arc::new(Mutex::new(state));
 
-        if flush_interval != ZERO_DURATION {
-            super::state::start_sync_flusher(Arc::clone(&am_state), flush_interval);
-        }
+        let o_flusher = if flush_interval == ZERO_DURATION {
+            None
+        } else {
+            Some(super::state::start_sync_flusher(
+                Arc::clone(&am_state),
+                flush_interval,
+            ))
+        };
 
         Self {
             am_state,
             format_function,
+            post_format,
             line_ending,
+            o_flusher,
         }
     }
 }
@@ -51,7 +65,9 @@ impl std::fmt::Debug for SyncHandle {
         f.debug_struct("SyncHandle")
             .field("am_state", &self.am_state)
             .field("format", &"<..>")
+            .field("post_format", &self.post_format)
             .field("line_ending", &self.line_ending)
+            .field("o_flusher", &self.o_flusher)
             .finish_non_exhaustive()
     }
 }
@@ -64,7 +80,10 @@ pub(super) struct AsyncHandle {
     a_pool: Arc<ArrayQueue<Vec<u8>>>,
     message_capa: usize,
     format_function: FormatFunction,
+    post_format: PostFormat,
     line_ending: &'static [u8],
+    o_flusher: Option<FlusherHandle>,
+    a_flush_acks: Arc<FlushAcks>,
 }
 #[cfg(feature = "async")]
 impl AsyncHandle {
@@ -76,18 +95,26 @@ impl AsyncHandle {
     ) -> Self {
         let flush_interval = state.config().write_mode.get_flush_interval();
         let line_ending = state.config().line_ending;
+        let post_format = state.config().post_format();
         let am_state = Arc::new(Mutex::new(state));
         let a_pool = Arc::new(ArrayQueue::new(pool_capa));
+        let a_flush_acks = Arc::new(FlushAcks::default());
 
         let (sender, mo_thread_handle) = super::state::start_async_fs_writer(
             Arc::clone(&am_state),
             message_capa,
             Arc::clone(&a_pool),
+            Arc::clone(&a_flush_acks),
         );
 
-        if flush_interval != ZERO_DURATION {
-            super::state::start_async_fs_flusher(sender.clone(), flush_interval);
-        }
+        let o_flusher = if flush_interval == ZERO_DURATION {
+            None
+        } else {
+            Some(super::state::start_async_fs_flusher(
+                sender.clone(),
+                flush_interval,
+            ))
+        };
 
         Self {
             am_state,
@@ -96,21 +123,61 @@ impl AsyncHandle {
             a_pool,
             message_capa,
             format_function,
+            post_format,
             line_ending,
+            o_flusher,
+            a_flush_acks,
         }
     }
 
     fn write(&self, now: &mut DeferredNow, record: &Record) -> Result<(), std::io::Error> {
-        let mut buffer = self.pop_buffer();
-        (self.format_function)(&mut buffer, now, record).map_err(|e| {
+        buffer_with(|tl_buf| match tl_buf.try_borrow_mut() {
+            Ok(mut tl_buffer) => {
+                // Formatting into the thread-local buffer avoids growing the pooled buffers;
+                // the message is then copied with a single allocation at most
+                let result = self
+                    .format_into(&mut tl_buffer, now, record)
+                    .and_then(|()| {
+                        let mut message = self.buffer_for(tl_buffer.len());
+                        message.extend_from_slice(&tl_buffer);
+                        self.sender.send(message).map_err(|_e| io_err("Send"))
+                    });
+                tl_buffer.clear();
+                result
+            }
+            Err(_e) => {
+                // recursive logging, see StateHandle::write
+                let mut buffer = self.pop_buffer();
+                self.format_into(&mut buffer, now, record)
+                    .and_then(|()| self.sender.send(buffer).map_err(|_e| io_err("Send")))
+            }
+        })
+    }
+
+    fn format_into(
+        &self,
+        buffer: &mut Vec<u8>,
+        now: &mut DeferredNow,
+        record: &Record,
+    ) -> Result<(), std::io::Error> {
+        format_line(self.format_function, self.post_format, buffer, now, record).map_err(|e| {
             eprint_err(ErrorCode::Format, "formatting failed", &e);
             e
         })?;
         buffer.write_all(self.line_ending).map_err(|e| {
             eprint_err(ErrorCode::Write, "writing failed", &e);
             e
-        })?;
-        self.sender.send(buffer).map_err(|_e| io_err("Send"))
+        })
+    }
+
+    // Messages that don't fit into a pooled buffer get an own buffer of the right size,
+    // which the writer thread doesn't return to the pool.
+    fn buffer_for(&self, len: usize) -> Vec<u8> {
+        if len <= self.message_capa {
+            self.pop_buffer()
+        } else {
+            Vec::with_capacity(len)
+        }
     }
 
     fn pop_buffer(&self) -> Vec<u8> {
@@ -130,20 +197,39 @@ impl std::fmt::Debug for AsyncHandle {
             .field("a_pool", &self.a_pool)
             .field("message_capa", &self.message_capa)
             .field("format", &"<..>")
+            .field("post_format", &self.post_format)
             .field("line_ending", &self.line_ending)
+            .field("o_flusher", &self.o_flusher)
+            .field("a_flush_acks", &self.a_flush_acks)
             .finish_non_exhaustive()
     }
 }
 
 impl StateHandle {
+    // produce a StateHandle that fits to the write mode of the state
+    pub(super) fn new(state: State, format_function: FormatFunction) -> Self {
+        match state.config().write_mode.effective_write_mode() {
+            EffectiveWriteMode::Direct
+            | EffectiveWriteMode::BufferAndFlushWith(_)
+            | EffectiveWriteMode::BufferDontFlushWith(_) => Self::new_sync(state, format_function),
+
+            #[cfg(feature = "async")]
+            EffectiveWriteMode::AsyncWith {
+                pool_capa,
+                message_capa,
+                flush_interval: _,
+            } => Self::new_async(pool_capa, message_capa, state, format_function),
+        }
+    }
+
     // produce a StateHandle::Sync, optionally with an own flusher-thread
-    pub(super) fn new_sync(state: State, format_function: FormatFunction) -> StateHandle {
+    fn new_sync(state: State, format_function: FormatFunction) -> StateHandle {
         StateHandle::Sync(SyncHandle::new(state, format_function))
     }
 
     // produce a StateHandle::Async with its writer-thread, and optionally an own flusher-thread
     #[cfg(feature = "async")]
-    pub(super) fn new_async(
+    fn new_async(
         pool_capa: usize,
         message_capa: usize,
         state: State,
@@ -190,7 +276,14 @@ impl StateHandle {
             StateHandle::Sync(handle) => {
                 buffer_with(|tl_buf| match tl_buf.try_borrow_mut() {
                     Ok(mut buffer) => {
-                        (handle.format_function)(&mut *buffer, now, record).unwrap_or_else(|e| {
+                        format_line(
+                            handle.format_function,
+                            handle.post_format,
+                            &mut *buffer,
+                            now,
+                            record,
+                        )
+                        .unwrap_or_else(|e| {
                             eprint_err(ErrorCode::Format, "formatting failed", &e);
                         });
                         buffer
@@ -210,7 +303,14 @@ impl StateHandle {
                         // we print the inner calls, in chronological order, before finally the
                         // outer most message is printed
                         let mut tmp_buf = Vec::<u8>::with_capacity(200);
-                        (handle.format_function)(&mut tmp_buf, now, record).unwrap_or_else(|e| {
+                        format_line(
+                            handle.format_function,
+                            handle.post_format,
+                            &mut tmp_buf,
+                            now,
+                            record,
+                        )
+                        .unwrap_or_else(|e| {
                             eprint_err(ErrorCode::Format, "formatting failed", &e);
                         });
                         let mut state_guard = handle
@@ -251,7 +351,25 @@ impl StateHandle {
         Ok(())
     }
 
-    // Replaces parts of the configuration of the file log writer.
+    pub(super) fn flush_and_wait(&self, timeout: std::time::Duration) -> std::io::Result<()> {
+        match &self {
+            StateHandle::Sync(_) => {
+                _ = timeout;
+                self.flush()
+            }
+            #[cfg(feature = "async")]
+            StateHandle::Async(handle) => handle.a_flush_acks.request_and_wait(
+                || {
+                    let mut buffer = handle.pop_buffer();
+                    buffer.extend(ASYNC_FLUSH_AND_ACK);
+                    handle.sender.send(buffer).map_err(|_e| io_err("Send"))
+                },
+                timeout,
+            ),
+        }
+    }
+
+    // Replaces parts of the configuration of the file log writer, with the same write mode.
     pub(super) fn reset(&self, flwb: &FileLogWriterBuilder) -> Result<(), FlexiLoggerError> {
         let mut state = match self {
             StateHandle::Sync(handle) => handle.am_state.lock(),
@@ -259,11 +377,22 @@ impl StateHandle {
             StateHandle::Async(handle) => handle.am_state.lock(),
         }
         .map_err(|_| FlexiLoggerError::Poison)?;
-        flwb.assert_write_mode((*state).config().write_mode)?;
-        *state = flwb.try_build_state()?;
+        let mut new_state = flwb.try_build_state(state.registration())?;
+        new_state.take_rotation_subscribers(&mut state);
+        *state = new_state;
         Ok(())
     }
 
+    pub(super) fn registration(&self) -> Result<Option<Registration>, FlexiLoggerError> {
+        let state = match self {
+            StateHandle::Sync(handle) => handle.am_state.lock(),
+            #[cfg(feature = "async")]
+            StateHandle::Async(handle) => handle.am_state.lock(),
+        }
+        .map_err(|_| FlexiLoggerError::Poison)?;
+        Ok(state.registration().cloned())
+    }
+
     pub(super) fn reopen_outputfile(&self) -> Result<(), FlexiLoggerError> {
         let mut state = match self {
             StateHandle::Sync(handle) => handle.am_state.lock(),
@@ -284,6 +413,36 @@ impl StateHandle {
         state.mount_next_linewriter_if_necessary(true)
     }
 
+    pub(super) fn rotate_to(&self, infix: &str) -> Result<(), FlexiLoggerError> {
+        let mut state = match self {
+            StateHandle::Sync(handle) => handle.am_state.lock(),
+            #[cfg(feature = "async")]
+            StateHandle::Async(handle) => handle.am_state.lock(),
+        }
+        .map_err(|_| FlexiLoggerError::Poison)?;
+        state.rotate_to(infix)
+    }
+
+    pub(super) fn subscribe_rotation(&self) -> Result<Receiver<RotationEvent>, FlexiLoggerError> {
+        let mut state = match self {
+            StateHandle::Sync(handle) => handle.am_state.lock(),
+            #[cfg(feature = "async")]
+            StateHandle::Async(handle) => handle.am_state.lock(),
+        }
+        .map_err(|_| FlexiLoggerError::Poison)?;
+        Ok(state.subscribe_rotation())
+    }
+
+    pub(super) fn trigger_cleanup(&self) -> Result<(), FlexiLoggerError> {
+        let mut state = match self {
+            StateHandle::Sync(handle) => handle.am_state.lock(),
+            #[cfg(feature = "async")]
+            StateHandle::Async(handle) => handle.am_state.lock(),
+        }
+        .map_err(|_| FlexiLoggerError::Poison)?;
+        state.trigger_cleanup()
+    }
+
     pub(crate) fn config(&self) -> Result<FileLogWriterConfig, FlexiLoggerError> {
         let state = match self {
             StateHandle::Sync(handle) => handle.am_state.lock(),
@@ -295,6 +454,39 @@ impl StateHandle {
         Ok(state.config().clone())
     }
 
+    pub(super) fn healthcheck(&self) -> Result<(), FlexiLoggerError> {
+        let state = match self {
+            StateHandle::Sync(handle) => handle.am_state.lock(),
+            #[cfg(feature = "async")]
+            StateHandle::Async(handle) => handle.am_state.lock(),
+        }
+        .map_err(|_| FlexiLoggerError::Poison)?;
+
+        Ok(state.healthcheck()?)
+    }
+
+    pub(super) fn cleanup_status(&self) -> Result<CleanupStatus, FlexiLoggerError> {
+        let state = match self {
+            StateHandle::Sync(handle) => handle.am_state.lock(),
+            #[cfg(feature = "async")]
+            StateHandle::Async(handle) => handle.am_state.lock(),
+        }
+        .map_err(|_| FlexiLoggerError::Poison)?;
+
+        Ok(state.cleanup_status())
+    }
+
+    pub(super) fn current_log_file(&self) -> Result<Option<PathBuf>, FlexiLoggerError> {
+        let state = match self {
+            StateHandle::Sync(handle) => handle.am_state.lock(),
+            #[cfg(feature = "async")]
+            StateHandle::Async(handle) => handle.am_state.lock(),
+        }
+        .map_err(|_| FlexiLoggerError::Poison)?;
+
+        Ok(state.current_log_file())
+    }
+
     pub(super) fn existing_log_files(
         &self,
         selector: &LogfileSelector,
@@ -322,6 +514,9 @@ impl StateHandle {
     pub(super) fn shutdown(&self) {
         match &self {
             StateHandle::Sync(handle) => {
+                if let Some(ref flusher) = handle.o_flusher {
+                    flusher.stop();
+                }
                 // do nothing in case of poison errors
                 if let Ok(ref mut state) = handle.am_state.lock() {
                     state.shutdown();
@@ -329,6 +524,9 @@ impl StateHandle {
             }
             #[cfg(feature = "async")]
             StateHandle::Async(handle) => {
+                if let Some(ref flusher) = handle.o_flusher {
+                    flusher.stop();
+                }
                 let mut buffer = handle.pop_buffer();
                 buffer.extend(ASYNC_SHUTDOWN);
                 handle.sender.send(buffer).ok();
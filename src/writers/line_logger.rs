@@ -0,0 +1,108 @@
+use std::{
+    io::{Result as IoResult, Write},
+    panic::Location,
+};
+
+/// An adapter that implements [`std::io::Write`] on top of the logger: each line that is
+/// written to it is logged as a separate record, with the chosen level and target.
+///
+/// This allows logging the output of child processes, or of libraries that only
+/// accept a writer.
+///
+/// Lines can be written in several parts; a line is logged when its line break is written.
+/// The line break itself, and a preceding carriage return, are not part of the logged
+/// message; bytes that are no valid UTF-8 are replaced with `U+FFFD`.
+/// An incomplete line is logged when the `LineLogger` is flushed or dropped.
+///
+/// The records are handed to the global logger, like those of the log macros,
+/// so they are subject to the log specification and to the
+/// routing to [additional writers](crate::writers).
+/// As file and line, they carry the location where the `LineLogger` was created,
+/// and as module path the target.
+///
+/// ```rust,no_run
+/// use flexi_logger::{writers::LineLogger, Logger};
+/// use std::process::{Command, Stdio};
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let _logger = Logger::try_with_str("info")?.start()?;
+///
+/// let mut child = Command::new("make").stdout(Stdio::piped()).spawn()?;
+/// let mut child_stdout = child.stdout.take().unwrap();
+/// std::io::copy(&mut child_stdout, &mut LineLogger::new(log::Level::Info, "make"))?;
+/// child.wait()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct LineLogger {
+    level: log::Level,
+    target: String,
+    location: &'static Location<'static>,
+    line: Vec<u8>,
+}
+impl LineLogger {
+    /// Creates a `LineLogger` that logs with the given level and target.
+    #[must_use]
+    #[track_caller]
+    pub fn new<T: Into<String>>(level: log::Level, target: T) -> Self {
+        Self {
+            level,
+            target: target.into(),
+            location: Location::caller(),
+            line: Vec::new(),
+        }
+    }
+
+    fn log_line(&self, mut line: &[u8]) {
+        if let Some(stripped) = line.strip_suffix(b"\r") {
+            line = stripped;
+        }
+        // like the log macros, but with the location of the creator
+        if self.level <= log::STATIC_MAX_LEVEL && self.level <= log::max_level() {
+            log::logger().log(
+                &log::Record::builder()
+                    .args(format_args!("{}", String::from_utf8_lossy(line)))
+                    .level(self.level)
+                    .target(&self.target)
+                    .module_path(Some(&self.target))
+                    .file_static(Some(self.location.file()))
+                    .line(Some(self.location.line()))
+                    .build(),
+            );
+        }
+    }
+
+    fn log_incomplete_line(&mut self) {
+        if !self.line.is_empty() {
+            self.log_line(&self.line);
+            self.line.clear();
+        }
+    }
+}
+impl Write for LineLogger {
+    fn write(&mut self, buf: &[u8]) -> IoResult<usize> {
+        let mut rest = buf;
+        while let Some(pos) = rest.iter().position(|b| *b == b'\n') {
+            if self.line.is_empty() {
+                self.log_line(&rest[..pos]);
+            } else {
+                self.line.extend_from_slice(&rest[..pos]);
+                self.log_line(&self.line);
+                self.line.clear();
+            }
+            rest = &rest[pos + 1..];
+        }
+        self.line.extend_from_slice(rest);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> IoResult<()> {
+        self.log_incomplete_line();
+        log::logger().flush();
+        Ok(())
+    }
+}
+impl Drop for LineLogger {
+    fn drop(&mut self) {
+        self.log_incomplete_line();
+    }
+}
@@ -1,4 +1,8 @@
-use crate::{DeferredNow, FlexiLoggerError, FormatFunction};
+use crate::{
+    util::{format_line, PostFormat},
+    writers::buffer_with,
+    DeferredNow, FlexiLoggerError, FormatFunction,
+};
 use log::Record;
 
 /// Writes to a single log output stream.
@@ -8,11 +12,87 @@ use log::Record;
 pub trait LogWriter: Sync + Send {
     /// Writes out a log line.
     ///
+    /// `now` is shared by all outputs of the record, so that all of them use the same
+    /// timestamp, see [`DeferredNow`]. Implementations should take the timestamp from `now`,
+    /// rather than determining the current time themselves.
+    ///
     /// # Errors
     ///
     /// [`std::io::Error`]
     fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()>;
 
+    /// Returns the format function with which `flexi_logger` formats the log lines
+    /// for [`LogWriter::write_formatted`].
+    ///
+    /// With the default, `None`, `flexi_logger` hands the records to [`LogWriter::write`].
+    /// With `Some`, it formats each record into a buffer that is reused for all records
+    /// of the current thread (see [`buffer_with`](crate::writers::buffer_with)),
+    /// and calls [`LogWriter::write_formatted`] instead.
+    fn formatted_with(&self) -> Option<FormatFunction> {
+        None
+    }
+
+    /// Returns the maximum length of the log lines that `flexi_logger` formats
+    /// for [`LogWriter::write_formatted`].
+    ///
+    /// With `Some`, longer log lines are cut off after the given number of bytes
+    /// (at a character boundary), and a marker like `… [truncated 12345 bytes]` is appended.
+    /// The default is `None`.
+    fn max_line_length(&self) -> Option<usize> {
+        None
+    }
+
+    /// Returns `true` if `flexi_logger` should escape control characters in the log lines
+    /// that it formats for [`LogWriter::write_formatted`].
+    ///
+    /// Line breaks e.g. are then written as `\n`, and the escape character that starts
+    /// ANSI escape sequences is written as `\u{1b}`. The default is `false`.
+    fn escape_control_chars(&self) -> bool {
+        false
+    }
+
+    /// Writes out a log line that was formatted with the format function
+    /// from [`LogWriter::formatted_with`]; the line has no line ending.
+    ///
+    /// The buffer is reused after the call, so the writer must copy what it wants to keep.
+    ///
+    /// The default implementation calls [`LogWriter::write`] with a record that has
+    /// the formatted line as its message, and no further metadata,
+    /// so that writers that only implement [`LogWriter::write`] can also be used with
+    /// [`AsyncLogWriter`](crate::writers::AsyncLogWriter).
+    ///
+    /// # Errors
+    ///
+    /// [`std::io::Error`]
+    fn write_formatted(&self, line: &[u8]) -> std::io::Result<()> {
+        self.write(
+            &mut DeferredNow::new(),
+            &Record::builder()
+                .args(format_args!("{}", String::from_utf8_lossy(line)))
+                .build(),
+        )
+    }
+
+    /// Writes out several log lines that were formatted with the format function
+    /// from [`LogWriter::formatted_with`]; the lines have no line ending.
+    ///
+    /// This method is called by [`AsyncLogWriter`](crate::writers::AsyncLogWriter)
+    /// with all log lines that are queued when its background thread becomes ready.
+    /// Writers that send the log lines over the network e.g. can override it
+    /// to send all lines of a batch with a single request.
+    ///
+    /// The default implementation calls [`LogWriter::write_formatted`] for each line.
+    ///
+    /// # Errors
+    ///
+    /// [`std::io::Error`]
+    fn write_batch(&self, lines: &mut dyn Iterator<Item = &[u8]>) -> std::io::Result<()> {
+        for line in lines {
+            self.write_formatted(line)?;
+        }
+        Ok(())
+    }
+
     /// Flushes any buffered records.
     ///
     /// # Errors
@@ -20,6 +100,24 @@ pub trait LogWriter: Sync + Send {
     /// [`std::io::Error`]
     fn flush(&self) -> std::io::Result<()>;
 
+    /// Flushes any buffered records and waits until all records that were written before
+    /// have reached the output, but at most for the given timeout.
+    ///
+    /// This method is called from
+    /// [`LoggerHandle::flush_and_wait`](crate::LoggerHandle::flush_and_wait)
+    /// for all registered additional writers.
+    ///
+    /// The default implementation just calls [`LogWriter::flush`],
+    /// which is sufficient for writers that do not write asynchronously.
+    ///
+    /// # Errors
+    ///
+    /// [`std::io::Error`], with [`std::io::ErrorKind::TimedOut`] if the timeout was reached.
+    fn flush_and_wait(&self, timeout: std::time::Duration) -> std::io::Result<()> {
+        _ = timeout;
+        self.flush()
+    }
+
     /// Provides the maximum log level that is to be written.
     fn max_log_level(&self) -> log::LevelFilter {
         log::LevelFilter::Trace
@@ -65,6 +163,56 @@ pub trait LogWriter: Sync + Send {
         Ok(())
     }
 
+    /// Rotate the current output to the given infix, if meaningful.
+    ///
+    /// This method is called from
+    /// [`LoggerHandle::rotate_to`](crate::LoggerHandle::rotate_to)
+    /// for all registered additional writers.
+    ///
+    /// # Errors
+    ///
+    /// Depend on registered writers.
+    fn rotate_to(&self, _infix: &str) -> Result<(), FlexiLoggerError> {
+        Ok(())
+    }
+
+    /// Clean up old outputs, if meaningful.
+    ///
+    /// This method is called from
+    /// [`LoggerHandle::trigger_cleanup`](crate::LoggerHandle::trigger_cleanup)
+    /// for all registered additional writers.
+    ///
+    /// # Errors
+    ///
+    /// Depend on registered writers.
+    fn trigger_cleanup(&self) -> Result<(), FlexiLoggerError> {
+        Ok(())
+    }
+
+    /// A name for the writer, used in reports about it,
+    /// e.g. by [`LoggerHandle::healthcheck`](crate::LoggerHandle::healthcheck).
+    ///
+    /// The default implementation returns the type name of the writer.
+    fn name(&self) -> &str {
+        std::any::type_name::<Self>()
+    }
+
+    /// Checks cheaply if the writer is able to write its output,
+    /// e.g. if the output file is still writable or the syslog is reachable.
+    ///
+    /// This method is called from
+    /// [`LoggerHandle::healthcheck`](crate::LoggerHandle::healthcheck)
+    /// for all registered writers.
+    ///
+    /// The default implementation reports the writer as healthy.
+    ///
+    /// # Errors
+    ///
+    /// Depend on registered writers.
+    fn healthcheck(&self) -> Result<(), FlexiLoggerError> {
+        Ok(())
+    }
+
     // Takes a vec with three patterns per line that represent the log line,
     // compares the written log with the expected lines,
     // and asserts that both are in sync.
@@ -75,3 +223,26 @@ pub trait LogWriter: Sync + Send {
         unimplemented!("only useful for tests");
     }
 }
+
+// Hands the record to the writer, formatted if the writer asks for it.
+pub(crate) fn dispatch(
+    writer: &dyn LogWriter,
+    now: &mut DeferredNow,
+    record: &Record,
+) -> std::io::Result<()> {
+    match writer.formatted_with() {
+        Some(format) => buffer_with(|buffer| {
+            format_line(format, post_format_of(writer), buffer, now, record)?;
+            writer.write_formatted(buffer)
+        }),
+        None => writer.write(now, record),
+    }
+}
+
+// The post-format processing the writer asks for.
+pub(crate) fn post_format_of(writer: &dyn LogWriter) -> PostFormat {
+    PostFormat {
+        o_max_line_length: writer.max_line_length(),
+        escape_control_chars: writer.escape_control_chars(),
+    }
+}
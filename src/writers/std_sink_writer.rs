@@ -0,0 +1,306 @@
+use crate::{
+    default_format,
+    util::{eprint_err, eprint_msg, io_err, ErrorCode},
+    writers::{buffer_with, LogWriter},
+    DeferredNow, FlexiLoggerError, FormatFunction,
+};
+use log::Record;
+use std::{
+    ffi::{OsStr, OsString},
+    io::{Error as IoError, ErrorKind, Result as IoResult, Write},
+    process::{Child, Command, Stdio},
+    sync::Mutex,
+};
+
+/// A [`LogWriter`] implementation that starts a child process and writes the log lines
+/// to its stdin.
+///
+/// This allows handing over the log to tools like `logger`, `multilog`, or `svlogd`,
+/// or to a custom log shipper.
+/// If the child process exits, it is by default restarted when the next log line is written;
+/// only the first restart is reported on stderr.
+///
+/// The child process inherits stdout and stderr of the program.
+/// When the writer is shut down or dropped, the stdin of the child process is closed,
+/// and the writer waits until the child process has terminated.
+///
+/// ```rust,no_run
+/// use flexi_logger::{writers::StdSinkWriter, Logger};
+///
+/// let sink_writer = StdSinkWriter::builder("logger")
+///     .args(["-t", "my_prog"])
+///     .try_build()
+///     .unwrap();
+/// Logger::try_with_str("info")
+///     .unwrap()
+///     .log_to_writer(Box::new(sink_writer))
+///     .start()
+///     .unwrap();
+/// ```
+///
+/// See the [writers](crate::writers) module for guidance how to use additional log writers.
+pub struct StdSinkWriter {
+    program: OsString,
+    args: Vec<OsString>,
+    restart_on_exit: bool,
+    format: FormatFunction,
+    max_log_level: log::LevelFilter,
+    m_sink: Mutex<Sink>,
+}
+impl StdSinkWriter {
+    /// Instantiates the builder for a `StdSinkWriter` that runs the given program.
+    #[must_use]
+    pub fn builder<P: Into<OsString>>(program: P) -> StdSinkWriterBuilder {
+        StdSinkWriterBuilder {
+            program: program.into(),
+            args: Vec::new(),
+            restart_on_exit: true,
+            format: default_format,
+            max_log_level: log::LevelFilter::Trace,
+        }
+    }
+
+    // Makes sure that a child process is running, if possible.
+    //
+    // Only the first restart is reported, to not flood stderr with a child process
+    // that exits repeatedly.
+    fn ensure_running(&self, sink: &mut Sink) -> IoResult<()> {
+        if let Some(ref mut child) = sink.o_child {
+            if let Some(status) = child.try_wait()? {
+                let msg = format!("the child process of the StdSinkWriter exited with {status}");
+                if !self.restart_on_exit {
+                    return Err(IoError::new(ErrorKind::BrokenPipe, msg));
+                }
+                if !sink.restarted {
+                    eprint_msg(
+                        ErrorCode::Write,
+                        &format!("{msg}, restarting it; further restarts are not reported"),
+                    );
+                }
+                sink.restarted = true;
+                sink.o_child = None;
+            }
+        }
+        if sink.o_child.is_none() {
+            sink.o_child = Some(spawn(&self.program, &self.args)?);
+        }
+        Ok(())
+    }
+}
+impl LogWriter for StdSinkWriter {
+    fn write(&self, now: &mut DeferredNow, record: &Record) -> IoResult<()> {
+        buffer_with(|buffer| {
+            (self.format)(buffer, now, record)?;
+            self.write_formatted(buffer)
+        })
+    }
+
+    fn formatted_with(&self) -> Option<FormatFunction> {
+        Some(self.format)
+    }
+
+    fn write_formatted(&self, line: &[u8]) -> IoResult<()> {
+        self.write_batch(&mut std::iter::once(line))
+    }
+
+    // Writes all lines of the batch with a single write to the child process.
+    fn write_batch(&self, lines: &mut dyn Iterator<Item = &[u8]>) -> IoResult<()> {
+        let mut sink_guard = self
+            .m_sink
+            .lock()
+            .map_err(|_| io_err("StdSinkWriter is poisoned"))?;
+        let sink = &mut *sink_guard;
+        sink.buf.clear();
+        for line in lines {
+            sink.buf.extend_from_slice(line);
+            sink.buf.push(b'\n');
+        }
+        if sink.buf.is_empty() {
+            return Ok(());
+        }
+
+        self.ensure_running(sink)?;
+        match write_to_child(sink.o_child.as_mut(), &sink.buf) {
+            Err(e) if e.kind() == ErrorKind::BrokenPipe && self.restart_on_exit => {
+                // the child process has exited in the meantime
+                if let Some(ref mut child) = sink.o_child {
+                    child.wait().ok();
+                }
+                self.ensure_running(sink)?;
+                write_to_child(sink.o_child.as_mut(), &sink.buf)
+            }
+            result => result,
+        }
+    }
+
+    fn flush(&self) -> IoResult<()> {
+        let mut sink_guard = self
+            .m_sink
+            .lock()
+            .map_err(|_| io_err("StdSinkWriter is poisoned"))?;
+        if let Some(stdin) = sink_guard
+            .o_child
+            .as_mut()
+            .and_then(|child| child.stdin.as_mut())
+        {
+            stdin.flush()?;
+        }
+        Ok(())
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.max_log_level
+    }
+
+    fn format(&mut self, format: FormatFunction) {
+        self.format = format;
+    }
+
+    fn shutdown(&self) {
+        if let Ok(mut sink_guard) = self.m_sink.lock() {
+            sink_guard.close();
+        }
+    }
+
+    fn name(&self) -> &'static str {
+        "StdSinkWriter"
+    }
+
+    // A child process that exited is only a problem if it is not restarted.
+    fn healthcheck(&self) -> Result<(), FlexiLoggerError> {
+        let mut sink_guard = self.m_sink.lock().map_err(|_| FlexiLoggerError::Poison)?;
+        if let Some(ref mut child) = sink_guard.o_child {
+            if let Some(status) = child.try_wait()? {
+                if !self.restart_on_exit {
+                    return Err(FlexiLoggerError::OutputIo(IoError::new(
+                        ErrorKind::BrokenPipe,
+                        format!("the child process of the StdSinkWriter exited with {status}"),
+                    )));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+impl Drop for StdSinkWriter {
+    fn drop(&mut self) {
+        if let Ok(sink) = self.m_sink.get_mut() {
+            sink.close();
+        }
+    }
+}
+
+struct Sink {
+    o_child: Option<Child>,
+    buf: Vec<u8>,
+    restarted: bool,
+}
+impl Sink {
+    // Closes the stdin of the child process and waits until the child process has terminated.
+    fn close(&mut self) {
+        if let Some(mut child) = self.o_child.take() {
+            drop(child.stdin.take());
+            if let Err(e) = child.wait() {
+                eprint_err(
+                    ErrorCode::Write,
+                    "waiting for the child process of the StdSinkWriter failed",
+                    &e,
+                );
+            }
+        }
+    }
+}
+
+fn spawn(program: &OsStr, args: &[OsString]) -> IoResult<Child> {
+    Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .spawn()
+}
+
+fn write_to_child(o_child: Option<&mut Child>, buf: &[u8]) -> IoResult<()> {
+    o_child
+        .and_then(|child| child.stdin.as_mut())
+        .ok_or_else(|| io_err("StdSinkWriter has no running child process"))?
+        .write_all(buf)
+}
+
+/// Builder for the [`StdSinkWriter`].
+///
+/// Is created with [`StdSinkWriter::builder`].
+#[allow(clippy::module_name_repetitions)]
+pub struct StdSinkWriterBuilder {
+    program: OsString,
+    args: Vec<OsString>,
+    restart_on_exit: bool,
+    format: FormatFunction,
+    max_log_level: log::LevelFilter,
+}
+impl StdSinkWriterBuilder {
+    /// Adds an argument for the child process.
+    #[must_use]
+    pub fn arg<A: Into<OsString>>(mut self, arg: A) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Adds arguments for the child process.
+    #[must_use]
+    pub fn args<I, A>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = A>,
+        A: Into<OsString>,
+    {
+        self.args.extend(args.into_iter().map(Into::into));
+        self
+    }
+
+    /// With false, the child process is not restarted when it exits;
+    /// all further writes then fail.
+    ///
+    /// Default is true.
+    #[must_use]
+    pub fn restart_on_exit(mut self, restart_on_exit: bool) -> Self {
+        self.restart_on_exit = restart_on_exit;
+        self
+    }
+
+    /// Use the given format function to write the log lines.
+    ///
+    /// By default, [`default_format`] is used.
+    #[must_use]
+    pub fn format(mut self, format: FormatFunction) -> Self {
+        self.format = format;
+        self
+    }
+
+    /// Specify up to which level log messages should be written to the child process.
+    ///
+    /// Default is: all levels.
+    #[must_use]
+    pub fn max_log_level(mut self, max_log_level: log::LevelFilter) -> Self {
+        self.max_log_level = max_log_level;
+        self
+    }
+
+    /// Starts the child process and returns the `StdSinkWriter`.
+    ///
+    /// # Errors
+    ///
+    /// `FlexiLoggerError::OutputIo` if the child process cannot be started.
+    pub fn try_build(self) -> Result<StdSinkWriter, FlexiLoggerError> {
+        let child = spawn(&self.program, &self.args)?;
+        Ok(StdSinkWriter {
+            program: self.program,
+            args: self.args,
+            restart_on_exit: self.restart_on_exit,
+            format: self.format,
+            max_log_level: self.max_log_level,
+            m_sink: Mutex::new(Sink {
+                o_child: Some(child),
+                buf: Vec::with_capacity(200),
+                restarted: false,
+            }),
+        })
+    }
+}
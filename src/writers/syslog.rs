@@ -7,6 +7,9 @@ mod severity;
 mod syslog_connection;
 mod writer;
 
+#[cfg(feature = "kv")]
+pub use self::line::DEFAULT_SD_ID;
+
 #[allow(clippy::module_name_repetitions)]
 pub use self::{
     builder::SyslogWriterBuilder,
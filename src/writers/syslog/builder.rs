@@ -14,9 +14,14 @@ pub struct SyslogWriterBuilder {
     syslog_line_header: SyslogLineHeader,
     syslog_facility: SyslogFacility,
     custom_process_name: Option<String>,
+    o_hostname: Option<String>,
+    o_pid: Option<u32>,
+    o_max_message_length: Option<usize>,
     determine_severity: LevelToSyslogSeverity,
     max_log_level: log::LevelFilter,
     format: FormatFunction,
+    #[cfg(feature = "kv")]
+    sd_id: String,
 }
 impl SyslogWriterBuilder {
     #[must_use]
@@ -30,9 +35,14 @@ impl SyslogWriterBuilder {
             syslog_line_header,
             syslog_facility,
             custom_process_name: None,
+            o_hostname: None,
+            o_pid: None,
+            o_max_message_length: None,
             determine_severity: default_mapping,
             max_log_level: log::LevelFilter::Warn,
             format: syslog_default_format,
+            #[cfg(feature = "kv")]
+            sd_id: super::DEFAULT_SD_ID.to_string(),
         }
     }
 
@@ -43,6 +53,37 @@ impl SyslogWriterBuilder {
         self
     }
 
+    /// Specify the hostname that is written into the syslog entries.
+    ///
+    /// By default, the hostname is determined from the operating system.
+    /// Spaces and other characters that RFC 5424 does not allow in the HOSTNAME field
+    /// are replaced with `_`.
+    #[must_use]
+    pub fn hostname<S: Into<String>>(mut self, hostname: S) -> Self {
+        self.o_hostname = Some(hostname.into());
+        self
+    }
+
+    /// Specify the process id that is written into the syslog entries.
+    ///
+    /// By default, the id of the current process is used.
+    #[must_use]
+    pub fn pid(mut self, pid: u32) -> Self {
+        self.o_pid = Some(pid);
+        self
+    }
+
+    /// Specify the maximal length (in bytes) of a syslog entry; longer entries are truncated.
+    ///
+    /// By default, syslog entries are not truncated, but note that syslog servers
+    /// are not obliged to accept entries that are longer than 480 bytes (RFC 3164: 1024 bytes,
+    /// RFC 5424 recommends to accept at least 2048 bytes).
+    #[must_use]
+    pub fn max_message_length(mut self, max_message_length: usize) -> Self {
+        self.o_max_message_length = Some(max_message_length);
+        self
+    }
+
     /// Use the given function to map the rust log levels to the syslog severities.
     /// By default a trivial mapping is used, which should be good enough in most cases.
     #[must_use]
@@ -73,15 +114,51 @@ impl SyslogWriterBuilder {
         self
     }
 
+    /// Specify the SD-ID of the STRUCTURED-DATA element in which the key-value pairs
+    /// of log records are written, if [`SyslogLineHeader::Rfc5424`] is used.
+    ///
+    /// By default, [`DEFAULT_SD_ID`](crate::writers::DEFAULT_SD_ID) is used.
+    ///
+    /// According to RFC 5424, the SD-ID must have the form `name@<private enterprise number>`,
+    /// consist of at most 32 printable US-ASCII characters, and must not contain
+    /// `=`, ` `, `]`, or `"`.
+    #[cfg(feature = "kv")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "kv")))]
+    #[must_use]
+    pub fn sd_id<S: Into<String>>(mut self, sd_id: S) -> Self {
+        self.sd_id = sd_id.into();
+        self
+    }
+
+    /// Specify your IANA private enterprise number, which is then used in the SD-ID
+    /// of the STRUCTURED-DATA element in which the key-value pairs are written.
+    ///
+    /// Is a shortcut for `sd_id(format!("flexi@{enterprise_id}"))`.
+    #[cfg(feature = "kv")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "kv")))]
+    #[must_use]
+    pub fn enterprise_id(self, enterprise_id: u32) -> Self {
+        self.sd_id(format!("flexi@{enterprise_id}"))
+    }
+
     /// Returns a boxed instance of `SysLogWriter`.
     ///
     /// # Errors
     ///
     /// `std::io::Error` if the program's argument list is empty so that the process
-    /// identifier for the syslog cannot be determined
+    /// identifier for the syslog cannot be determined,
+    /// or if the configured SD-ID is not valid.
     pub fn build(self) -> IoResult<Box<SyslogWriter>> {
+        #[cfg(feature = "kv")]
+        if !is_valid_sd_id(&self.sd_id) {
+            return Err(IoError::new(
+                ErrorKind::InvalidInput,
+                format!("Invalid SD-ID: {}", self.sd_id),
+            ));
+        }
+
         Ok(Box::new(SyslogWriter::new(
-            std::process::id(),
+            self.o_pid.unwrap_or_else(std::process::id),
             self.custom_process_name
                 .or(std::env::args().next())
                 .ok_or_else(|| {
@@ -92,12 +169,49 @@ impl SyslogWriterBuilder {
                             .to_owned(),
                     )
                 })?,
+            self.o_hostname,
             self.syslog_line_header,
             self.syslog_facility,
             self.determine_severity,
             self.syslog_connection,
             self.max_log_level,
+            self.o_max_message_length,
             self.format,
+            #[cfg(feature = "kv")]
+            self.sd_id,
         )?))
     }
 }
+
+// RFC 5424, section 6.3.2: an SD-ID that is not registered with IANA must have the form
+// name@<private enterprise number>, with at most 32 printable characters except '=', ' ', ']',
+// and '"'.
+#[cfg(feature = "kv")]
+fn is_valid_sd_id(sd_id: &str) -> bool {
+    sd_id.len() <= 32
+        && sd_id
+            .chars()
+            .all(|c| c.is_ascii_graphic() && !matches!(c, '=' | ']' | '"'))
+        && sd_id.split_once('@').is_some_and(|(name, pen)| {
+            !name.is_empty() && !pen.is_empty() && pen.bytes().all(|b| b.is_ascii_digit())
+        })
+}
+
+#[cfg(all(test, feature = "kv"))]
+mod test {
+    use super::is_valid_sd_id;
+
+    #[test]
+    fn test_is_valid_sd_id() {
+        assert!(is_valid_sd_id(super::super::DEFAULT_SD_ID));
+        assert!(is_valid_sd_id("myapp@1"));
+        assert!(!is_valid_sd_id("myapp"));
+        assert!(!is_valid_sd_id("@32473"));
+        assert!(!is_valid_sd_id("myapp@"));
+        assert!(!is_valid_sd_id("myapp@32473x"));
+        assert!(!is_valid_sd_id("my@app@32473"));
+        assert!(!is_valid_sd_id("my app@32473"));
+        assert!(!is_valid_sd_id("my=app@32473"));
+        assert!(!is_valid_sd_id(&format!("{}@32473", "x".repeat(30))));
+    }
+}
@@ -27,6 +27,34 @@ pub(super) enum Connection {
     Tcp(TcpStream),
 }
 
+impl Connection {
+    // Checks, without sending anything, that the socket is still connected
+    // and has no pending error.
+    pub(super) fn check(&self) -> IoResult<()> {
+        let o_error = match *self {
+            #[cfg(target_family = "unix")]
+            Self::Datagram(ref ud) => {
+                ud.peer_addr()?;
+                ud.take_error()?
+            }
+            #[cfg(target_family = "unix")]
+            Self::Stream(ref w) => {
+                w.peer_addr()?;
+                w.take_error()?
+            }
+            Self::Tcp(ref w) => {
+                w.peer_addr()?;
+                w.take_error()?
+            }
+            Self::Udp(ref socket) => {
+                socket.peer_addr()?;
+                socket.take_error()?
+            }
+        };
+        o_error.map_or(Ok(()), Err)
+    }
+}
+
 impl Write for Connection {
     fn write(&mut self, buf: &[u8]) -> IoResult<usize> {
         match *self {
@@ -4,9 +4,22 @@ use crate::{DeferredNow, FormatFunction};
 
 use super::{LevelToSyslogSeverity, SyslogFacility};
 
+/// Default SD-ID that is used for the STRUCTURED-DATA element in which the key-value pairs
+/// of a log record are written, if [`SyslogLineHeader::Rfc5424`] is used.
+///
+/// Can be overridden with [`SyslogWriterBuilder::sd_id`](crate::writers::SyslogWriterBuilder::sd_id).
+#[cfg(feature = "kv")]
+#[cfg_attr(docsrs, doc(cfg(feature = "kv")))]
+pub const DEFAULT_SD_ID: &str = "flexi@32473";
+
 /// Defines the format of the header of a syslog line.
 pub enum SyslogLineHeader {
     /// Line header according to RFC 5424.
+    ///
+    /// The parameter is used as MSGID.
+    ///
+    /// With feature `kv`, the key-value pairs of a log record are written as
+    /// STRUCTURED-DATA element, e.g. `[flexi@32473 key="value"]`.
     Rfc5424(String),
     /// Line header according to RFC 3164.
     Rfc3164,
@@ -19,20 +32,25 @@ pub(crate) struct LineWriter {
     format: FormatFunction,
     determine_severity: LevelToSyslogSeverity,
     facility: SyslogFacility,
+    #[cfg(feature = "kv")]
+    sd_id: String,
 }
 impl LineWriter {
+    #[allow(clippy::too_many_arguments)]
     pub(crate) fn new(
         header: SyslogLineHeader,
         determine_severity: LevelToSyslogSeverity,
         facility: SyslogFacility,
         process: String,
+        o_hostname: Option<String>,
         pid: u32,
         format: FormatFunction,
+        #[cfg(feature = "kv")] sd_id: String,
     ) -> IoResult<LineWriter> {
         const UNKNOWN_HOSTNAME: &str = "<unknown_hostname>";
-        Ok(LineWriter {
-            header,
-            hostname: hostname::get().map_or_else(
+        let hostname = match o_hostname {
+            Some(hostname) => hostname,
+            None => hostname::get().map_or_else(
                 |_| Ok(UNKNOWN_HOSTNAME.to_owned()),
                 |s| {
                     s.into_string().map_err(|_| {
@@ -43,11 +61,17 @@ impl LineWriter {
                     })
                 },
             )?,
+        };
+        Ok(LineWriter {
+            header,
+            hostname: header_hostname(&hostname),
             process,
             pid,
             format,
             determine_severity,
             facility,
+            #[cfg(feature = "kv")]
+            sd_id,
         })
     }
 
@@ -85,37 +109,40 @@ impl LineWriter {
                     procid = self.pid,
                     msgid = message_id,
                 )?;
-                write_key_value_pairs(buffer, record)?;
+                self.write_structured_data(buffer, record)?;
                 (self.format)(buffer, now, record)?;
             }
         }
         Ok(())
     }
-}
 
-// Helpers for printing key-value pairs
-fn write_key_value_pairs(
-    w: &mut dyn std::io::Write,
-    record: &log::Record<'_>,
-) -> Result<(), std::io::Error> {
-    let mut kv_written = false;
-    #[cfg(feature = "kv")]
-    if record.key_values().count() > 0 {
-        write!(w, "[log_kv ",)?;
-        let mut kv_stream = KvStream(w, false);
-        record.key_values().visit(&mut kv_stream).ok();
-        write!(w, "] ")?;
-        kv_written = true;
-    }
+    // Writes the STRUCTURED-DATA part of an RFC 5424 line, including the trailing space.
+    // Without key-value pairs, this is the NILVALUE `-`.
+    #[allow(clippy::unused_self)]
+    fn write_structured_data(
+        &self,
+        w: &mut dyn Write,
+        #[allow(unused_variables)] record: &log::Record<'_>,
+    ) -> IoResult<()> {
+        #[cfg(feature = "kv")]
+        if record.key_values().count() > 0 {
+            write!(w, "[{}", self.sd_id)?;
+            let mut kv_stream = KvStream(w);
+            record
+                .key_values()
+                .visit(&mut kv_stream)
+                .map_err(|e| IoError::new(ErrorKind::Other, e.to_string()))?;
+            write!(w, "] ")?;
+            return Ok(());
+        }
 
-    if !kv_written {
-        write!(w, "- ")?;
+        write!(w, "- ")
     }
-    Ok(())
 }
 
+// Writes each key-value pair as SD-PARAM, i.e., as ` name="value"`.
 #[cfg(feature = "kv")]
-struct KvStream<'a>(&'a mut dyn std::io::Write, bool);
+struct KvStream<'a>(&'a mut dyn Write);
 #[cfg(feature = "kv")]
 impl<'kvs, 'a> log::kv::VisitSource<'kvs> for KvStream<'a>
 where
@@ -126,11 +153,128 @@ where
         key: log::kv::Key<'kvs>,
         value: log::kv::Value<'kvs>,
     ) -> Result<(), log::kv::Error> {
-        if self.1 {
-            write!(self.0, " ")?;
-        }
-        write!(self.0, "{key}=\"{value:?}\"")?;
-        self.1 = true;
+        write!(self.0, " {}=\"", sd_name(key.as_str()))?;
+        write_escaped_param_value(self.0, &value.to_string())?;
+        write!(self.0, "\"")?;
         Ok(())
     }
 }
+
+// RFC 5424 (section 6.3.3): SD-NAME consists of 1 to 32 printable US-ASCII characters,
+// except '=', ' ', ']', and '"'. Invalid characters are replaced with '_'.
+#[cfg(feature = "kv")]
+fn sd_name(key: &str) -> String {
+    let name: String = key
+        .chars()
+        .map(|c| {
+            if c.is_ascii_graphic() && !matches!(c, '=' | ']' | '"') {
+                c
+            } else {
+                '_'
+            }
+        })
+        .take(32)
+        .collect();
+    if name.is_empty() {
+        "_".to_string()
+    } else {
+        name
+    }
+}
+
+// RFC 5424 (section 6.3.3): within PARAM-VALUE, the characters '"', '\\', and ']'
+// must be escaped with a backslash.
+#[cfg(feature = "kv")]
+fn write_escaped_param_value(w: &mut dyn Write, value: &str) -> IoResult<()> {
+    let mut start = 0;
+    for (idx, c) in value.char_indices() {
+        if matches!(c, '"' | '\\' | ']') {
+            w.write_all(&value.as_bytes()[start..idx])?;
+            w.write_all(b"\\")?;
+            start = idx;
+        }
+    }
+    w.write_all(&value.as_bytes()[start..])
+}
+
+// RFC 5424, section 6: the HOSTNAME consists of at most 255 printable US-ASCII characters,
+// without spaces, or is the NILVALUE `-`.
+fn header_hostname(hostname: &str) -> String {
+    if hostname.is_empty() {
+        return "-".to_string();
+    }
+    hostname
+        .chars()
+        .map(|c| if c.is_ascii_graphic() { c } else { '_' })
+        .take(255)
+        .collect()
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "kv")]
+    use super::{LineWriter, SyslogLineHeader};
+    #[cfg(feature = "kv")]
+    use crate::{
+        writers::{syslog_default_format, SyslogFacility},
+        DeferredNow,
+    };
+
+    #[test]
+    fn test_header_hostname() {
+        assert_eq!(
+            super::header_hostname("myhost.example.com"),
+            "myhost.example.com"
+        );
+        assert_eq!(super::header_hostname("my host	ä"), "my_host__");
+        assert_eq!(super::header_hostname(""), "-");
+        assert_eq!(super::header_hostname(&"x".repeat(300)).len(), 255);
+    }
+
+    #[cfg(feature = "kv")]
+    fn line_for(sd_id: &str, kvs: &[(&str, &str)]) -> String {
+        let line_writer = LineWriter::new(
+            SyslogLineHeader::Rfc5424("MSGID".to_string()),
+            crate::writers::syslog::severity::default_mapping,
+            SyslogFacility::LocalUse0,
+            "proc".to_string(),
+            None,
+            4711,
+            syslog_default_format,
+            sd_id.to_string(),
+        )
+        .unwrap();
+        let mut buf = Vec::<u8>::new();
+        line_writer
+            .write_syslog_entry(
+                &mut buf,
+                &mut DeferredNow::new(),
+                &log::Record::builder()
+                    .args(format_args!("the message"))
+                    .level(log::Level::Error)
+                    .key_values(&kvs)
+                    .build(),
+            )
+            .unwrap();
+        String::from_utf8(buf).unwrap()
+    }
+
+    #[cfg(feature = "kv")]
+    #[test]
+    fn test_structured_data() {
+        let line = line_for(super::DEFAULT_SD_ID, &[]);
+        assert!(line.ends_with(" proc 4711 MSGID - the message"), "{line}");
+
+        let line = line_for(super::DEFAULT_SD_ID, &[("a", "1"), ("b", "x y")]);
+        assert!(
+            line.ends_with(r#" MSGID [flexi@32473 a="1" b="x y"] the message"#),
+            "{line}"
+        );
+
+        let line = line_for("myid@1234", &[("k e=y", r#"q"u\o]te"#)]);
+        assert!(
+            line.ends_with(r#" MSGID [myid@1234 k_e_y="q\"u\\o\]te"] the message"#),
+            "{line}"
+        );
+    }
+}
@@ -2,7 +2,7 @@ use super::{
     connection::Connection, line::LineWriter, LevelToSyslogSeverity, SyslogConnection,
     SyslogFacility, SyslogLineHeader, SyslogWriterBuilder,
 };
-use crate::{writers::log_writer::LogWriter, DeferredNow, FormatFunction};
+use crate::{writers::log_writer::LogWriter, DeferredNow, FlexiLoggerError, FormatFunction};
 #[cfg(test)]
 use std::io::BufRead;
 use std::{
@@ -20,6 +20,7 @@ pub struct SyslogWriter {
     line_writer: LineWriter,
     m_conn_buf: Mutex<ConnectorAndBuffer>,
     max_log_level: log::LevelFilter,
+    o_max_message_length: Option<usize>,
     #[cfg(test)]
     validation_buffer: Mutex<Cursor<Vec<u8>>>,
 }
@@ -37,12 +38,15 @@ impl SyslogWriter {
     pub(super) fn new(
         pid: u32,
         process: String,
+        o_hostname: Option<String>,
         syslog_line_header: SyslogLineHeader,
         facility: SyslogFacility,
         determine_severity: LevelToSyslogSeverity,
         syslog_connection: SyslogConnection,
         max_log_level: log::LevelFilter,
+        o_max_message_length: Option<usize>,
         format: FormatFunction,
+        #[cfg(feature = "kv")] sd_id: String,
     ) -> IoResult<SyslogWriter> {
         Ok(SyslogWriter {
             line_writer: LineWriter::new(
@@ -50,14 +54,18 @@ impl SyslogWriter {
                 determine_severity,
                 facility,
                 process,
+                o_hostname,
                 pid,
                 format,
+                #[cfg(feature = "kv")]
+                sd_id,
             )?,
             m_conn_buf: Mutex::new(ConnectorAndBuffer {
                 conn: syslog_connection.into_inner(),
                 buf: Vec::with_capacity(200),
             }),
             max_log_level,
+            o_max_message_length,
             #[cfg(test)]
             validation_buffer: Mutex::new(Cursor::new(Vec::new())),
         })
@@ -76,6 +84,10 @@ impl LogWriter for SyslogWriter {
         self.line_writer
             .write_syslog_entry(&mut buffer, now, record)?;
 
+        if let Some(max_len) = self.o_max_message_length {
+            truncate_at_char_boundary(&mut cb.buf, max_len);
+        }
+
         #[cfg(test)]
         {
             let mut valbuf = self.validation_buffer.lock().unwrap();
@@ -99,6 +111,19 @@ impl LogWriter for SyslogWriter {
         self.max_log_level
     }
 
+    fn name(&self) -> &'static str {
+        "SyslogWriter"
+    }
+
+    fn healthcheck(&self) -> Result<(), FlexiLoggerError> {
+        Ok(self
+            .m_conn_buf
+            .lock()
+            .map_err(|_| FlexiLoggerError::Poison)?
+            .conn
+            .check()?)
+    }
+
     #[doc(hidden)]
     fn validate_logs(&self, _expected: &[(&'static str, &'static str, &'static str)]) {
         #[cfg(test)]
@@ -121,7 +146,7 @@ impl LogWriter for SyslogWriter {
             }
             buf.clear();
             reader.read_line(&mut buf).unwrap();
-            assert!(buf.is_empty(), "Found more log lines than expected: {buf} ",);
+            assert!(buf.is_empty(), "Found more log lines than expected: {buf} ");
         }
     }
 }
@@ -131,6 +156,18 @@ struct ConnectorAndBuffer {
     buf: Vec<u8>,
 }
 
+// Truncates to at most max_len bytes, without splitting a UTF-8 encoded character.
+fn truncate_at_char_boundary(buf: &mut Vec<u8>, max_len: usize) {
+    if buf.len() > max_len {
+        let mut len = max_len;
+        // continuation bytes of a multi-byte character have the bit pattern 10xxxxxx
+        while len > 0 && (buf[len] & 0b1100_0000) == 0b1000_0000 {
+            len -= 1;
+        }
+        buf.truncate(len);
+    }
+}
+
 /////////////////////////////
 
 #[cfg(test)]
@@ -255,6 +292,47 @@ mod test {
         );
     }
 
+    #[test]
+    fn test_overrides_and_truncation() {
+        use crate::{writers::LogWriter, DeferredNow};
+        use std::io::Write;
+
+        let syslog_writer = SyslogWriter::builder(
+            SyslogConnection::try_udp("127.0.0.1:5557", "127.0.0.1:514").unwrap(),
+            SyslogLineHeader::Rfc5424("ID".to_owned()),
+            SyslogFacility::LocalUse0,
+        )
+        .custom_process_name(Some("myapp"))
+        .hostname("myhost")
+        .pid(42)
+        .max_message_length(100)
+        .build()
+        .unwrap();
+
+        syslog_writer
+            .write(
+                &mut DeferredNow::new(),
+                &log::Record::builder()
+                    .args(format_args!(
+                        "äöü and a long message that gets truncated: {}",
+                        "xyz".repeat(30)
+                    ))
+                    .level(log::Level::Error)
+                    .build(),
+            )
+            .ok();
+        let buf = syslog_writer.validation_buffer.lock().unwrap();
+        let line = String::from_utf8(buf.get_ref().clone()).unwrap();
+        assert!(line.starts_with("<131>1 "), "{line}");
+        assert!(line.contains(" myhost myapp 42 ID - äöü"), "{line}");
+        assert_eq!(line.len(), 100 + 1, "{line}");
+
+        let mut v = Vec::new();
+        v.write_all("aä".as_bytes()).unwrap();
+        super::truncate_at_char_boundary(&mut v, 2);
+        assert_eq!(v, b"a");
+    }
+
     fn dir() -> PathBuf {
         let mut d = PathBuf::new();
         d.push("log_files");
@@ -1,5 +1,6 @@
 mod test_utils;
 
+use chrono::{DateTime, Duration, TimeZone, Utc};
 use flexi_logger::{Age, Cleanup, Criterion, Duplicate, FileSpec, Logger, Naming};
 use glob::glob;
 use log::*;
@@ -7,18 +8,23 @@ use std::fs::File;
 use std::io::{BufRead, BufReader};
 use std::ops::Add;
 use std::path::Path;
+use std::sync::Mutex;
+
+// the simulated time, which the test lets pass instead of sleeping
+static SIMULATED_NOW: Mutex<Option<DateTime<Utc>>> = Mutex::new(None);
 
 #[test]
 fn test_age_or_size() {
     let directory = test_utils::dir();
 
-    test_utils::wait_for_start_of_second();
+    *SIMULATED_NOW.lock().unwrap() = Some(Utc.with_ymd_and_hms(2024, 2, 29, 12, 0, 0).unwrap());
 
     Logger::try_with_str("trace")
         .unwrap()
         .format_for_files(flexi_logger::detailed_format)
         .log_to_file(FileSpec::default().directory(&directory))
         .duplicate_to_stderr(Duplicate::Info)
+        .clock(|| SIMULATED_NOW.lock().unwrap().unwrap())
         .rotate(
             Criterion::AgeOrSize(Age::Second, 265),
             Naming::Numbers,
@@ -35,8 +41,8 @@ fn test_age_or_size() {
 
 fn write_log_lines() {
     trace!("{}", 'A');
-    // wait to enforce a rotation
-    std::thread::sleep(std::time::Duration::from_millis(1100));
+    // let time pass to enforce a rotation
+    let_time_pass(Duration::milliseconds(1100));
 
     // Fill first three files by size
     trace!("{}", 'a');
@@ -53,12 +59,12 @@ fn write_log_lines() {
 
     trace!("{}", 'j');
 
-    // now wait to enforce a rotation with a smaller file
-    std::thread::sleep(std::time::Duration::from_secs(2));
+    // now let time pass to enforce a rotation with a smaller file
+    let_time_pass(Duration::seconds(2));
     trace!("{}", 'k');
 
-    // now wait to enforce a rotation with a smaller file
-    std::thread::sleep(std::time::Duration::from_secs(2));
+    // now let time pass to enforce a rotation with a smaller file
+    let_time_pass(Duration::seconds(2));
     trace!("{}", 'l');
 
     // then again fill a file by size
@@ -69,6 +75,11 @@ fn write_log_lines() {
     trace!("{}", 'o');
 }
 
+fn let_time_pass(duration: Duration) {
+    let mut simulated_now = SIMULATED_NOW.lock().unwrap();
+    *simulated_now = simulated_now.map(|now| now + duration);
+}
+
 fn verify_logs(directory: &Path) {
     let mut error_detected = false;
     let expected_line_counts = [1, 3, 3, 3, 1, 1, 3, 1];
@@ -0,0 +1,55 @@
+mod test_utils;
+
+use flexi_logger::{flexi_log_to, FileSpec, Logger};
+use log::*;
+use std::sync::{Arc, Mutex};
+use test_utils::MessageCollector;
+
+#[test]
+fn test_all_target() {
+    let file_spec = FileSpec::default()
+        .directory(self::test_utils::dir())
+        .suppress_timestamp();
+    let file_path = file_spec.as_pathbuf(None);
+    let sec_messages = Arc::new(Mutex::new(Vec::new()));
+    let alert_messages = Arc::new(Mutex::new(Vec::new()));
+    {
+        let _logger = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(file_spec)
+            .add_writer("Sec", Box::new(MessageCollector::new(&sec_messages)))
+            .add_writer(
+                "Alert",
+                Box::new(
+                    MessageCollector::new(&alert_messages).with_max_log_level(LevelFilter::Warn),
+                ),
+            )
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+        flexi_log_to!(Sec, info!, "only to {}", "Sec");
+        flexi_log_to!([Alert, _Default], warn!, "to Alert and the default");
+        flexi_log_to!(_All, error!, "to all");
+        error!(target: "{_All,Sec}", "to all, once");
+        // Alert only takes warnings and errors
+        flexi_log_to!(_All, info!, "to all but Alert");
+        info!("only to the default");
+    }
+
+    assert_eq!(
+        std::fs::read_to_string(file_path).unwrap(),
+        "WARN [test_all_target] to Alert and the default\n\
+         ERROR [test_all_target] to all\n\
+         ERROR [test_all_target] to all, once\n\
+         INFO [test_all_target] to all but Alert\n\
+         INFO [test_all_target] only to the default\n"
+    );
+    assert_eq!(
+        *sec_messages.lock().unwrap(),
+        vec!["only to Sec", "to all", "to all, once", "to all but Alert"]
+    );
+    assert_eq!(
+        *alert_messages.lock().unwrap(),
+        vec!["to Alert and the default", "to all", "to all, once"]
+    );
+}
@@ -0,0 +1,70 @@
+use flexi_logger::{
+    default_format,
+    writers::{ArcLogWriter, LogWriter},
+    DeferredNow, FormatFunction, Logger,
+};
+use log::*;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_arc_log_writer_format() {
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    let (arc_writer, _writer_handle) = ArcLogWriter::new_with_handle(Collector {
+        format: default_format,
+        lines: Arc::clone(&lines),
+    });
+    {
+        let _logger = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_writer(Box::new(arc_writer.clone()))
+            .format_for_writer(|w, _now, record| write!(w, "custom: {}", record.args()))
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+        info!("via the logger");
+        // the clone that is kept outside the logger uses the same format
+        let record = Record::builder()
+            .args(format_args!("via the clone"))
+            .level(Level::Info)
+            .build();
+        arc_writer.write(&mut DeferredNow::new(), &record).unwrap();
+    }
+
+    assert_eq!(
+        *lines.lock().unwrap(),
+        vec!["custom: via the logger", "custom: via the clone"]
+    );
+}
+
+// Lets flexi_logger format the log lines, and collects them.
+struct Collector {
+    format: FormatFunction,
+    lines: Arc<Mutex<Vec<String>>>,
+}
+impl LogWriter for Collector {
+    fn write(&self, now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        let mut line = Vec::new();
+        (self.format)(&mut line, now, record)?;
+        self.write_formatted(&line)
+    }
+
+    fn formatted_with(&self) -> Option<FormatFunction> {
+        Some(self.format)
+    }
+
+    fn write_formatted(&self, line: &[u8]) -> std::io::Result<()> {
+        self.lines
+            .lock()
+            .unwrap()
+            .push(String::from_utf8_lossy(line).into_owned());
+        Ok(())
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn format(&mut self, format: FormatFunction) {
+        self.format = format;
+    }
+}
@@ -0,0 +1,41 @@
+#[cfg(feature = "async")]
+mod test_utils;
+
+#[cfg(feature = "async")]
+#[test]
+fn test_async_duplication() {
+    use flexi_logger::{Duplicate, FileSpec, Logger, WriteMode};
+    use log::*;
+
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(test_utils::dir()))
+        .write_mode(WriteMode::Async)
+        .duplicate_to_stderr(Duplicate::Warn)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+    info!("This is an info message that is not duplicated");
+    let report = logger.debug_resources();
+    assert!(
+        !report
+            .threads
+            .iter()
+            .any(|name| name == "flexi_logger-async_std_writer"),
+        "{report:?}"
+    );
+
+    warn!("This is a warning that is duplicated by a background thread");
+    let report = logger.debug_resources();
+    assert!(
+        report
+            .threads
+            .iter()
+            .any(|name| name == "flexi_logger-async_std_writer"),
+        "{report:?}"
+    );
+
+    logger.shutdown();
+    let report = logger.debug_resources();
+    assert!(report.is_empty(), "{report:?}");
+}
@@ -0,0 +1,103 @@
+#![cfg(feature = "async")]
+mod test_utils;
+
+use flexi_logger::{
+    writers::{AsyncLogWriter, LogWriter},
+    DeferredNow, Logger,
+};
+use log::*;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use test_utils::MessageCollector;
+
+#[test]
+fn test_async_log_writer() {
+    let batches = Arc::new(Mutex::new(Vec::<Vec<String>>::new()));
+
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .do_not_log()
+        .add_writer(
+            "Batched",
+            Box::new(AsyncLogWriter::new(BatchCollector {
+                batches: Arc::clone(&batches),
+            })),
+        )
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+    for i in 0..200 {
+        info!(target: "{Batched}", "line {i}");
+    }
+    logger.flush_and_wait(Duration::from_secs(10)).unwrap();
+
+    let batches = batches.lock().unwrap();
+    let lines: Vec<&String> = batches.iter().flatten().collect();
+    assert_eq!(lines.len(), 200);
+    for (i, line) in lines.iter().enumerate() {
+        assert!(line.ends_with(&format!("line {i}")), "{line}");
+    }
+    // the first batch is slow, so the following lines are queued meanwhile
+    assert!(batches.len() < 200, "{} batches", batches.len());
+    assert!(batches.iter().all(|batch| batch.len() <= 50));
+}
+
+// A writer that only implements write and flush gets the formatted lines as messages
+#[test]
+fn test_async_log_writer_with_plain_writer() {
+    let messages = Arc::new(Mutex::new(Vec::new()));
+    let async_writer = AsyncLogWriter::new(MessageCollector::new(&messages));
+
+    for i in 0..3 {
+        async_writer
+            .write(
+                &mut DeferredNow::new(),
+                &Record::builder()
+                    .level(Level::Warn)
+                    .module_path(Some("plain"))
+                    .args(format_args!("line {i}"))
+                    .build(),
+            )
+            .unwrap();
+    }
+    async_writer
+        .flush_and_wait(Duration::from_secs(10))
+        .unwrap();
+
+    assert_eq!(
+        *messages.lock().unwrap(),
+        vec![
+            "WARN [plain] line 0".to_string(),
+            "WARN [plain] line 1".to_string(),
+            "WARN [plain] line 2".to_string(),
+        ]
+    );
+}
+
+struct BatchCollector {
+    batches: Arc<Mutex<Vec<Vec<String>>>>,
+}
+impl LogWriter for BatchCollector {
+    fn write(&self, _now: &mut DeferredNow, _record: &Record) -> std::io::Result<()> {
+        panic!("write must not be called");
+    }
+
+    fn write_batch(&self, lines: &mut dyn Iterator<Item = &[u8]>) -> std::io::Result<()> {
+        let mut batches = self.batches.lock().unwrap();
+        if batches.is_empty() {
+            std::thread::sleep(Duration::from_millis(100));
+        }
+        batches.push(
+            lines
+                .map(|line| String::from_utf8_lossy(line).to_string())
+                .collect(),
+        );
+        Ok(())
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
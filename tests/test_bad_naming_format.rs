@@ -0,0 +1,30 @@
+mod test_utils;
+
+use flexi_logger::{Cleanup, Criterion, FileSpec, FlexiLoggerError, Logger, Naming};
+
+#[test]
+fn test_bad_naming_format() {
+    let result = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(test_utils::dir()))
+        .rotate(
+            Criterion::Size(100),
+            Naming::TimestampsCustomFormat {
+                current_infix: None,
+                format: "%d/%m",
+            },
+            Cleanup::Never,
+        )
+        .start();
+    match result {
+        Err(FlexiLoggerError::BadNamingFormat(msg)) => {
+            assert!(msg.contains("not allowed in file names"), "{msg}");
+            assert!(
+                msg.contains("the year is missing") || msg.contains("parsed back"),
+                "{msg}"
+            );
+        }
+        Err(e) => panic!("unexpected error: {e}"),
+        Ok(_) => panic!("bad format was not detected"),
+    }
+}
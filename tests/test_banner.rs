@@ -0,0 +1,68 @@
+mod test_utils;
+
+use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming};
+use log::*;
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+    sync::{Arc, Mutex},
+};
+use test_utils::MessageCollector;
+
+const BANNER: &str = "test_banner 1.0; pid: ";
+
+#[test]
+fn test_banner() {
+    let directory = test_utils::dir();
+    let collected = Arc::new(Mutex::new(Vec::new()));
+    {
+        let logger = Logger::try_with_str("error")
+            .unwrap()
+            .log_to_file(FileSpec::default().directory(&directory))
+            .format(|w, _now, record| write!(w, "{}", record.args()))
+            .rotate(Criterion::Size(500), Naming::Numbers, Cleanup::Never)
+            .banner("test_banner 1.0")
+            .add_writer("Collector", Box::new(MessageCollector::new(&collected)))
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+        for idx in 0..50 {
+            error!("This is error message {idx:0>2}, with some padding to force rotations");
+        }
+
+        // the additional writer gets the banner right with the rotation
+        let banners = collected.lock().unwrap().len();
+        logger.trigger_rotation().unwrap();
+        assert_eq!(collected.lock().unwrap().len(), banners + 1);
+    }
+
+    let files = std::fs::read_dir(&directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    assert!(files.len() > 1, "no rotation happened: {files:?}");
+
+    // the additional writer gets the banner at start and after each rotation
+    let collected = collected.lock().unwrap();
+    assert_eq!(collected.len(), files.len(), "{collected:?}");
+    assert!(collected.iter().all(|line| line.starts_with(BANNER)));
+
+    let mut messages = 0;
+    for file in files {
+        let lines = BufReader::new(File::open(&file).unwrap())
+            .lines()
+            .map(Result::unwrap)
+            .collect::<Vec<_>>();
+        assert!(
+            lines[0].starts_with(BANNER) && lines[0].ends_with("; log spec: error"),
+            "file {file:?} does not start with the banner: {}",
+            lines[0]
+        );
+        assert_eq!(
+            lines.iter().filter(|line| line.starts_with(BANNER)).count(),
+            1
+        );
+        messages += lines.len() - 1;
+    }
+    assert_eq!(messages, 50);
+}
@@ -0,0 +1,35 @@
+mod test_utils;
+
+use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming};
+use log::*;
+
+#[test]
+fn test_capture_file_output() {
+    let directory = test_utils::dir();
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(&directory))
+        .rotate(Criterion::Size(10), Naming::Numbers, Cleanup::Never)
+        .capture_file_output()
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+    error!("This is an error message");
+    info!("This is an info message");
+    debug!("This is a debug message - you must not see it!");
+
+    let captured = logger.captured_output().unwrap();
+    assert_eq!(captured.lines().count(), 2, "{captured}");
+    assert!(captured.contains("This is an error message"));
+    assert!(captured.contains("This is an info message"));
+
+    logger.clear_captured_output();
+    warn!("This is a warning");
+    assert!(logger
+        .captured_output()
+        .unwrap()
+        .ends_with("This is a warning\n"));
+
+    // the filesystem was not touched
+    assert!(!directory.exists());
+}
@@ -0,0 +1,72 @@
+use flexi_logger::{
+    writers::FileLogWriter, Cleanup, ConfigWarning, Criterion, Duplicate, FileSpec, Logger, Naming,
+};
+
+#[test]
+fn test_check() {
+    // a sensible configuration
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().suppress_timestamp())
+        .append()
+        .duplicate_to_stderr(Duplicate::Warn);
+    assert!(logger.check().is_empty());
+
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default())
+        .append();
+    assert_eq!(logger.check(), vec![ConfigWarning::AppendWithTimestamp]);
+
+    // with rotation, the timestamp is suppressed by default
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default())
+        .rotate(Criterion::Size(1000), Naming::Numbers, Cleanup::Never)
+        .append();
+    assert!(logger.check().is_empty());
+
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_stdout()
+        .duplicate_to_stderr(Duplicate::Warn)
+        .rotate(Criterion::Size(1000), Naming::Numbers, Cleanup::Never);
+    assert_eq!(
+        logger.check(),
+        vec![
+            ConfigWarning::DuplicationWithoutFile,
+            ConfigWarning::FileOptionWithoutFile("rotate")
+        ]
+    );
+
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default())
+        .cleanup_archiver(|_| Ok(()))
+        .rotate_on_shutdown(true);
+    assert_eq!(
+        logger.check(),
+        vec![
+            ConfigWarning::OptionWithoutRotation("cleanup_archiver"),
+            ConfigWarning::OptionWithoutRotation("rotate_on_shutdown"),
+        ]
+    );
+
+    let warnings = FileLogWriter::builder(FileSpec::default())
+        .rotate(
+            Criterion::Size(1000),
+            Naming::TimestampsCustomFormat {
+                current_infix: None,
+                format: "%Y-%d",
+            },
+            Cleanup::KeepLogFiles(3),
+        )
+        .check();
+    assert!(
+        matches!(
+            warnings.as_slice(),
+            [ConfigWarning::UnparsableNamingFormat(_)]
+        ),
+        "{warnings:?}"
+    );
+}
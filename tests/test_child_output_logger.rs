@@ -0,0 +1,51 @@
+#![cfg(unix)]
+mod test_utils;
+
+use flexi_logger::{writers::ChildOutputLogger, FileSpec, Logger};
+use log::*;
+use std::process::Command;
+use test_utils::level_target_message;
+
+#[test]
+fn test_child_output_logger() {
+    let file_spec = FileSpec::default()
+        .directory(self::test_utils::dir())
+        .suppress_timestamp();
+    let file_path = file_spec.as_pathbuf(None);
+    {
+        let _logger = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(file_spec)
+            .format(level_target_message)
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+        let status = ChildOutputLogger::new("child")
+            .spawn(Command::new("sh").args([
+                "-c",
+                "echo out1; echo out2; sleep 0.1; echo err1 >&2; sleep 0.1; printf incomplete",
+            ]))
+            .unwrap()
+            .wait()
+            .unwrap();
+        assert!(status.success());
+
+        let status = ChildOutputLogger::new("child")
+            .stdout(Level::Debug, "child")
+            .stderr(Level::Error, "failing_child")
+            .spawn(Command::new("sh").args(["-c", "echo hidden; echo broken >&2; exit 3"]))
+            .unwrap()
+            .wait()
+            .unwrap();
+        assert_eq!(status.code(), Some(3));
+    }
+
+    assert_eq!(
+        std::fs::read_to_string(file_path).unwrap(),
+        "INFO child: out1\n\
+         INFO child: out2\n\
+         WARN child: err1\n\
+         INFO child: incomplete\n\
+         ERROR failing_child: broken\n"
+    );
+}
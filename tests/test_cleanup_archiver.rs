@@ -0,0 +1,71 @@
+mod test_utils;
+
+use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming};
+use log::*;
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+};
+
+const NO_OF_LINES: usize = 100;
+
+// Files are only deleted after the archiver succeeded; no line must get lost
+#[test]
+fn test_cleanup_archiver() {
+    let directory = test_utils::dir();
+    let archive = directory.join("archive");
+    std::fs::create_dir_all(&archive).unwrap();
+
+    let archiver_fails = Arc::new(AtomicBool::new(true));
+    {
+        let fails = Arc::clone(&archiver_fails);
+        let archive = archive.clone();
+        let _logger = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(FileSpec::default().directory(&directory))
+            .format(|w, _now, record| write!(w, "{}", record.args()))
+            .rotate(
+                Criterion::Size(500),
+                Naming::Numbers,
+                Cleanup::KeepLogFiles(2),
+            )
+            .cleanup_in_background_thread(false)
+            .cleanup_archiver(move |path: &Path| {
+                if fails.load(Ordering::SeqCst) {
+                    Err(std::io::Error::new(std::io::ErrorKind::Other, "offline"))
+                } else {
+                    std::fs::copy(path, archive.join(path.file_name().unwrap())).map(|_| ())
+                }
+            })
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+        for idx in 0..NO_OF_LINES {
+            if idx == NO_OF_LINES / 2 {
+                assert!(log_files(&directory).len() > 3);
+                archiver_fails.store(false, Ordering::SeqCst);
+            }
+            info!("This is info message {idx:0>3}, with some padding to force rotations");
+        }
+    }
+
+    assert_eq!(log_files(&directory).len(), 3);
+    assert!(!log_files(&archive).is_empty());
+    let line_count: usize = log_files(&directory)
+        .into_iter()
+        .chain(log_files(&archive))
+        .map(|file| std::fs::read_to_string(file).unwrap().lines().count())
+        .sum();
+    assert_eq!(line_count, NO_OF_LINES);
+}
+
+fn log_files(directory: &Path) -> Vec<std::path::PathBuf> {
+    std::fs::read_dir(directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .filter(|path| path.is_file())
+        .collect()
+}
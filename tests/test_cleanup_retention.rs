@@ -0,0 +1,91 @@
+mod test_utils;
+
+use chrono::{DateTime, Local, Utc};
+use flexi_logger::{
+    writers::{FileLogWriter, LogWriter},
+    Cleanup, Criterion, DeferredNow, FileSpec, Naming,
+};
+use log::{Level, Record};
+use std::{path::Path, time::Duration};
+
+#[test]
+fn test_cleanup_retention() {
+    let directory = test_utils::dir();
+    std::fs::create_dir_all(&directory).unwrap();
+    for i in 0..3 {
+        std::fs::write(directory.join(format!("test_r{i:0>5}.log")), "old").unwrap();
+    }
+    let old_modified = modified(&directory, 2);
+    // the young files must be visibly younger, also with a coarse file system clock
+    let mut young_modified = old_modified;
+    while young_modified <= old_modified {
+        std::thread::sleep(Duration::from_millis(10));
+        for i in 3..5 {
+            std::fs::write(directory.join(format!("test_r{i:0>5}.log")), "young").unwrap();
+        }
+        young_modified = modified(&directory, 3);
+    }
+
+    // the cleanup measures the age of the files with the clock of the logger
+    let now = young_modified + chrono::Duration::hours(1);
+    DeferredNow::set_clock(move || now);
+
+    // all files are younger than the retention period
+    cleanup(
+        &directory,
+        Cleanup::KeepLogFilesAndDuration(1, Duration::from_secs(2 * 3600)),
+    );
+    assert_eq!(test_utils::file_names(&directory).len(), 5);
+
+    // the old files are deleted, the young ones are kept although there are more than one
+    let between = old_modified + (young_modified - old_modified) / 2;
+    cleanup(
+        &directory,
+        Cleanup::KeepLogFilesAndDuration(1, (now - between).to_std().unwrap()),
+    );
+    assert_eq!(
+        test_utils::file_names(&directory),
+        vec!["test_r00003.log", "test_r00004.log"]
+    );
+
+    // the count is respected if files are older
+    cleanup(
+        &directory,
+        Cleanup::KeepLogFilesAndDuration(1, Duration::ZERO),
+    );
+    assert_eq!(test_utils::file_names(&directory), vec!["test_r00004.log"]);
+
+    DeferredNow::reset_clock();
+}
+
+fn modified(directory: &Path, i: usize) -> DateTime<Utc> {
+    std::fs::metadata(directory.join(format!("test_r{i:0>5}.log")))
+        .unwrap()
+        .modified()
+        .map(DateTime::<Local>::from)
+        .unwrap()
+        .with_timezone(&Utc)
+}
+
+// runs the initial cleanup, which is done synchronously
+fn cleanup(directory: &Path, cleanup: Cleanup) {
+    let flw = FileLogWriter::builder(
+        FileSpec::default()
+            .directory(directory)
+            .basename("test")
+            .suppress_timestamp(),
+    )
+    .rotate(Criterion::Size(1_000_000), Naming::NumbersDirect, cleanup)
+    .append()
+    .try_build()
+    .unwrap();
+    flw.write(
+        &mut DeferredNow::new(),
+        &Record::builder()
+            .args(format_args!("a line"))
+            .level(Level::Info)
+            .build(),
+    )
+    .unwrap();
+    flw.shutdown();
+}
@@ -0,0 +1,63 @@
+mod test_utils;
+
+use flexi_logger::{Cleanup, CleanupStatus, Criterion, FileSpec, Logger, Naming};
+use log::*;
+use std::{
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+// A panic in the cleanup thread is detected, reported, and the thread is restarted
+#[test]
+fn test_cleanup_thread_restart() {
+    let directory = test_utils::dir();
+    let panic_once = Arc::new(AtomicBool::new(true));
+    let reported_failures = Arc::new(AtomicUsize::new(0));
+
+    let reported = Arc::clone(&reported_failures);
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(&directory))
+        .rotate(
+            Criterion::Size(100_000),
+            Naming::Numbers,
+            Cleanup::KeepLogFiles(1),
+        )
+        .cleanup_archiver(move |_path: &Path| {
+            assert!(!panic_once.swap(false, Ordering::SeqCst), "archiver panic");
+            Ok(())
+        })
+        .on_cleanup_failure(move |_status: &CleanupStatus| {
+            reported.fetch_add(1, Ordering::SeqCst);
+        })
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+    assert_eq!(logger.cleanup_status().unwrap(), CleanupStatus::default());
+    for idx in 0..5 {
+        info!("before rotation {idx}");
+        logger.trigger_rotation().unwrap();
+        std::thread::sleep(Duration::from_millis(100));
+    }
+
+    let status = logger.cleanup_status().unwrap();
+    assert_eq!(status.thread_restarts, 1);
+    assert_eq!(status.failures, 1);
+    assert!(
+        status
+            .last_failure
+            .as_ref()
+            .unwrap()
+            .contains("archiver panic"),
+        "{status:?}"
+    );
+    assert_eq!(reported_failures.load(Ordering::SeqCst), 1);
+
+    // the restarted thread continues with the cleanup
+    let log_files = std::fs::read_dir(&directory).unwrap().count();
+    assert!(log_files <= 2, "too many log files: {log_files}");
+}
@@ -0,0 +1,46 @@
+mod test_utils;
+
+use chrono::{DateTime, TimeZone, Utc};
+use flexi_logger::{writers::LogWriter, DeferredNow, Logger};
+use log::*;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_clock() {
+    let timestamps = Arc::new(Mutex::new(Vec::new()));
+    {
+        let _logger = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_writer(Box::new(TimestampCollector {
+                timestamps: Arc::clone(&timestamps),
+            }))
+            .clock(fixed_clock)
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+        info!("first");
+        info!("second");
+    }
+    assert_eq!(
+        *timestamps.lock().unwrap(),
+        vec![fixed_clock(), fixed_clock()]
+    );
+}
+
+fn fixed_clock() -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(2024, 2, 29, 12, 34, 56).unwrap()
+}
+
+struct TimestampCollector {
+    timestamps: Arc<Mutex<Vec<DateTime<Utc>>>>,
+}
+impl LogWriter for TimestampCollector {
+    fn write(&self, now: &mut DeferredNow, _record: &Record) -> std::io::Result<()> {
+        self.timestamps.lock().unwrap().push(now.now_utc_owned());
+        Ok(())
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
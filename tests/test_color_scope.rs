@@ -0,0 +1,46 @@
+#![cfg(feature = "colors")]
+
+mod test_utils;
+
+use flexi_logger::{colored_opt_format, ColorScope, FileSpec, Logger};
+use log::*;
+
+#[test]
+fn test_color_scope() {
+    let directory = test_utils::dir();
+    let handle = Logger::try_with_str("info")
+        .unwrap()
+        .set_palette("196;208;-;27;8".to_string())
+        .color_scope(ColorScope::Level)
+        .format_for_files(colored_opt_format)
+        .log_to_file(FileSpec::default().directory(&directory))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+    error!("This is an error message");
+    warn!("This is a warning");
+    handle.flush();
+
+    let file = handle
+        .existing_log_files(&flexi_logger::LogfileSelector::default())
+        .unwrap()
+        .pop()
+        .unwrap();
+    let content = std::fs::read_to_string(file).unwrap();
+    let mut lines = content.lines();
+
+    let line = lines.next().unwrap();
+    assert!(line.starts_with('['), "{line:?}");
+    assert!(
+        line.contains(" \u{1b}[38;5;196mERROR\u{1b}[0m ["),
+        "{line:?}"
+    );
+    assert!(line.ends_with("] This is an error message"), "{line:?}");
+
+    let line = lines.next().unwrap();
+    assert!(
+        line.contains(" \u{1b}[38;5;208mWARN\u{1b}[0m ["),
+        "{line:?}"
+    );
+    assert!(line.ends_with("] This is a warning"), "{line:?}");
+}
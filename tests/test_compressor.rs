@@ -0,0 +1,136 @@
+#[cfg(feature = "compress")]
+mod test_utils;
+
+// "compresses" by reversing the content
+#[cfg(feature = "compress")]
+struct Reverse;
+#[cfg(feature = "compress")]
+impl flexi_logger::Compressor for Reverse {
+    fn suffix(&self) -> &str {
+        "rev"
+    }
+    fn compress(
+        &self,
+        source: &mut std::fs::File,
+        target: &mut std::fs::File,
+    ) -> std::io::Result<()> {
+        use std::io::{Read, Write};
+        let mut content = Vec::new();
+        source.read_to_end(&mut content)?;
+        content.reverse();
+        target.write_all(&content)
+    }
+}
+
+#[cfg(feature = "compress")]
+#[test]
+fn test_compressor() {
+    use flexi_logger::{
+        writers::{FileLogWriter, LogWriter},
+        Cleanup, Criterion, DeferredNow, FileSpec, LogfileSelector, Naming,
+    };
+    use log::{Level, Record};
+
+    let directory = test_utils::dir();
+    std::fs::create_dir_all(&directory).unwrap();
+    // is not recognized as a compressed log file
+    std::fs::write(directory.join("test_r00099.log.gz"), "").unwrap();
+
+    let flw = FileLogWriter::builder(
+        FileSpec::default()
+            .directory(&directory)
+            .basename("test")
+            .suppress_timestamp(),
+    )
+    .rotate(
+        Criterion::Size(1_000_000),
+        Naming::Numbers,
+        Cleanup::KeepLogAndCompressedFiles(1, 2),
+    )
+    .compressor(Reverse)
+    .cleanup_in_background_thread(false)
+    .try_build()
+    .unwrap();
+    for _ in 0..4 {
+        flw.write(
+            &mut DeferredNow::new(),
+            &Record::builder()
+                .args(format_args!("abc"))
+                .level(Level::Info)
+                .build(),
+        )
+        .unwrap();
+        flw.rotate().unwrap();
+    }
+
+    let mut compressed_files = flw
+        .existing_log_files(&LogfileSelector::none().with_compressed_files())
+        .unwrap()
+        .into_iter()
+        .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    compressed_files.sort();
+    assert_eq!(
+        compressed_files,
+        vec!["test_r00001.log.rev", "test_r00002.log.rev"]
+    );
+    assert!(
+        std::fs::read_to_string(directory.join("test_r00001.log.rev"))
+            .unwrap()
+            .contains("cba"),
+    );
+    assert!(directory.join("test_r00003.log").exists());
+    assert!(!directory.join("test_r00000.log.rev").exists());
+    assert!(directory.join("test_r00099.log.gz").exists());
+    flw.shutdown();
+}
+
+// the compressor is kept when the file spec is set afterwards
+#[cfg(feature = "compress")]
+#[test]
+fn test_compressor_before_log_to_file() {
+    use flexi_logger::{Cleanup, Criterion, FileSpec, LogfileSelector, Logger, Naming};
+    use log::{Level, Record};
+
+    let directory = test_utils::dir().join("logger");
+    let (logger, handle) = Logger::try_with_str("info")
+        .unwrap()
+        .compressor(Reverse)
+        .log_to_file(
+            FileSpec::default()
+                .directory(&directory)
+                .basename("test")
+                .suppress_timestamp(),
+        )
+        .rotate(
+            Criterion::Size(1_000_000),
+            Naming::Numbers,
+            Cleanup::KeepLogAndCompressedFiles(1, 2),
+        )
+        .cleanup_in_background_thread(false)
+        .build_nested()
+        .unwrap();
+    for _ in 0..4 {
+        logger.log(
+            &Record::builder()
+                .args(format_args!("abc"))
+                .level(Level::Info)
+                .build(),
+        );
+        handle.trigger_rotation().unwrap();
+    }
+
+    let mut compressed_files = handle
+        .existing_log_files(&LogfileSelector::none().with_compressed_files())
+        .unwrap()
+        .into_iter()
+        .map(|path| path.file_name().unwrap().to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    compressed_files.sort();
+    assert_eq!(
+        compressed_files,
+        vec!["test_r00001.log.rev", "test_r00002.log.rev"]
+    );
+    assert!(!directory.join("test_r00001.log.gz").exists());
+    handle.shutdown();
+}
@@ -0,0 +1,43 @@
+mod test_utils;
+
+use flexi_logger::{detailed_format, Cleanup, Criterion, FileSpec, Logger, Naming};
+use log::*;
+
+#[test]
+fn test_config_file_header() {
+    let directory = test_utils::dir();
+    let handle = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(
+            FileSpec::default()
+                .directory(&directory)
+                .basename("test")
+                .suppress_timestamp(),
+        )
+        .format(detailed_format)
+        .rotate(Criterion::Size(10_000), Naming::Numbers, Cleanup::Never)
+        .config_file_header()
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+    info!("first file");
+    handle.parse_new_spec("debug").unwrap();
+    handle.trigger_rotation().unwrap();
+    debug!("second file");
+    handle.shutdown();
+
+    let header = |spec: &str| {
+        format!(
+            "flexi_logger version: {}\n\
+             log spec: {spec}\n\
+             format: detailed_format\n\
+             rotation: Size(10000), Numbers, Never\n\
+             write mode: Direct\n",
+            env!("CARGO_PKG_VERSION")
+        )
+    };
+    for (file, spec) in [("test_r00000.log", "info"), ("test_rCURRENT.log", "debug")] {
+        let content = std::fs::read_to_string(directory.join(file)).unwrap();
+        assert!(content.starts_with(&header(spec)), "{file}: {content}");
+    }
+}
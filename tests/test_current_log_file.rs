@@ -0,0 +1,72 @@
+mod test_utils;
+
+use flexi_logger::{
+    writers::{FileLogWriter, LogWriter},
+    Cleanup, Criterion, DeferredNow, FileSpec, Logger, Naming,
+};
+use log::*;
+
+#[test]
+fn test_current_log_file() {
+    let directory = test_utils::dir();
+    let handle = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(
+            FileSpec::default()
+                .directory(&directory)
+                .basename("test")
+                .suppress_timestamp(),
+        )
+        .rotate(Criterion::Size(10_000), Naming::Numbers, Cleanup::Never)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+    // the file is opened with the first log line
+    assert_eq!(handle.current_log_file().unwrap(), None);
+    info!("first line");
+    let current = handle.current_log_file().unwrap().unwrap();
+    assert_eq!(current.file_name().unwrap(), "test_rCURRENT.log");
+    assert!(current.exists());
+
+    handle.trigger_rotation().unwrap();
+    assert_eq!(handle.current_log_file().unwrap(), Some(current));
+    handle.shutdown();
+
+    // with direct naming, the path changes with the rotation
+    let flw = FileLogWriter::builder(
+        FileSpec::default()
+            .directory(directory.join("direct"))
+            .basename("test")
+            .suppress_timestamp(),
+    )
+    .rotate(
+        Criterion::Size(10_000),
+        Naming::NumbersDirect,
+        Cleanup::Never,
+    )
+    .try_build()
+    .unwrap();
+    flw.write(
+        &mut DeferredNow::new(),
+        &Record::builder()
+            .args(format_args!("first line"))
+            .level(Level::Info)
+            .build(),
+    )
+    .unwrap();
+    let first = flw.current_log_file().unwrap().unwrap();
+    assert_eq!(first.file_name().unwrap(), "test_r00000.log");
+    flw.rotate().unwrap();
+    let second = flw.current_log_file().unwrap().unwrap();
+    assert_eq!(second.file_name().unwrap(), "test_r00001.log");
+    flw.shutdown();
+
+    // no file is written
+    let handle = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_stderr()
+        .build()
+        .unwrap()
+        .1;
+    assert_eq!(handle.current_log_file().unwrap(), None);
+}
@@ -0,0 +1,56 @@
+mod test_utils;
+
+use flexi_logger::{
+    writers::{FileLogWriter, LogWriter},
+    Cleanup, Criterion, DeferredNow, FileSpec, Naming,
+};
+
+// The cleanup recognizes the rotated files for various custom timestamp formats
+#[test]
+fn test_custom_format_cleanup() {
+    let directory = test_utils::dir();
+    for (idx, (current_infix, format, expected_files)) in [
+        (Some(""), "%Y-%m-%d", 3),
+        (Some("myCURRENT"), "%Y%m%d%H", 3),
+        (None, "%Y.%m.%d_%H.%M.%S", 2),
+        (None, "%Y-%m", 2),
+    ]
+    .into_iter()
+    .enumerate()
+    {
+        let subdir = directory.join(idx.to_string());
+        let flw = FileLogWriter::builder(FileSpec::default().directory(&subdir))
+            .rotate(
+                Criterion::Size(u64::MAX),
+                Naming::TimestampsCustomFormat {
+                    current_infix,
+                    format,
+                },
+                Cleanup::KeepLogFiles(2),
+            )
+            .cleanup_in_background_thread(false)
+            .try_build()
+            .unwrap();
+        for round in 0..6 {
+            flw.write(
+                &mut DeferredNow::new(),
+                &log::Record::builder()
+                    .args(format_args!("round {round}"))
+                    .build(),
+            )
+            .unwrap();
+            flw.rotate().unwrap();
+        }
+        flw.shutdown();
+
+        let files = std::fs::read_dir(&subdir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            files.len(),
+            expected_files,
+            "format {format}, current_infix {current_infix:?}: {files:?}"
+        );
+    }
+}
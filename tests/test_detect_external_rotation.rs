@@ -0,0 +1,58 @@
+mod test_utils;
+
+use flexi_logger::{FileSpec, Logger};
+use log::*;
+use std::{fs::OpenOptions, path::Path, time::Duration};
+
+#[test]
+fn test_detect_external_rotation() {
+    let file_spec = FileSpec::default()
+        .directory(self::test_utils::dir())
+        .suppress_timestamp()
+        .basename("myprog");
+    let file_path = file_spec.as_pathbuf(None);
+    let moved_path = file_spec.as_pathbuf(Some("moved"));
+    {
+        let _logger = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(file_spec)
+            .detect_external_rotation(Duration::ZERO)
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+        info!("XXX 1 AAA");
+        info!("XXX 2 AAA");
+
+        // like logrotate's default: rename the file
+        std::fs::rename(&file_path, &moved_path).unwrap();
+        info!("YYY 3 AAA");
+        info!("YYY 4 AAA");
+        info!("YYY 5 AAA");
+
+        // like logrotate's copytruncate: copy the file and truncate it
+        let copied_path = file_path.with_extension("copied");
+        std::fs::copy(&file_path, copied_path).unwrap();
+        OpenOptions::new()
+            .write(true)
+            .open(&file_path)
+            .unwrap()
+            .set_len(0)
+            .unwrap();
+        info!("ZZZ 6 AAA");
+    }
+
+    assert_eq!(count_lines(&moved_path), 2);
+    let content = std::fs::read_to_string(&file_path).unwrap();
+    assert!(
+        !content.contains('\0'),
+        "output file has a gap: {content:?}"
+    );
+    assert_eq!(count_lines(&file_path), 1);
+}
+
+fn count_lines(path: &Path) -> usize {
+    match std::fs::read_to_string(path) {
+        Ok(s) => s.lines().filter(|line| line.contains("AAA")).count(),
+        Err(_e) => 0,
+    }
+}
@@ -0,0 +1,58 @@
+mod test_utils;
+
+use flexi_logger::{writers::FileLogWriter, FileSpec, Logger};
+use log::*;
+
+#[test]
+fn test_disabled_levels() {
+    let directory = test_utils::dir();
+    let alert_writer = FileLogWriter::builder(
+        FileSpec::default()
+            .directory(&directory)
+            .discriminant("Alert"),
+    )
+    .max_level(LevelFilter::Debug)
+    .try_build()
+    .unwrap();
+    let (flexi_logger, handle) = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(&directory))
+        .add_writer("Alert", Box::new(alert_writer))
+        .capture_file_output()
+        .build_nested()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+    let metadata = |level: Level, target: &'static str| {
+        Metadata::builder().level(level).target(target).build()
+    };
+    let log = |level: Level, text: &str| {
+        flexi_logger.log(
+            &Record::builder()
+                .level(level)
+                .target("test_disabled_levels")
+                .args(format_args!("{text}"))
+                .build(),
+        );
+    };
+
+    // the additional writer accepts debug messages, so they must not be discarded early
+    assert!(flexi_logger.enabled(&metadata(Level::Debug, "{Alert}")));
+    assert!(!flexi_logger.enabled(&metadata(Level::Trace, "{Alert}")));
+    assert!(!flexi_logger.enabled(&metadata(Level::Debug, "test_disabled_levels")));
+    log(Level::Info, "info 1");
+    log(Level::Trace, "trace - you must not see it!");
+
+    // the fast path follows changes of the log specification
+    handle.parse_new_spec("trace").unwrap();
+    assert!(flexi_logger.enabled(&metadata(Level::Trace, "test_disabled_levels")));
+    log(Level::Trace, "trace 1");
+
+    handle.parse_new_spec("error").unwrap();
+    assert!(!flexi_logger.enabled(&metadata(Level::Info, "test_disabled_levels")));
+    log(Level::Info, "info - you must not see it!");
+
+    let captured = handle.captured_output().unwrap();
+    assert!(!captured.contains("you must not see it"), "{captured}");
+    assert!(captured.contains("info 1"), "{captured}");
+    assert!(captured.contains("trace 1"), "{captured}");
+}
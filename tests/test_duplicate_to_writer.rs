@@ -0,0 +1,51 @@
+mod test_utils;
+
+use flexi_logger::{ConfigWarning, Duplicate, FileSpec, Logger};
+use log::*;
+use std::sync::{Arc, Mutex};
+use test_utils::MessageCollector;
+
+#[test]
+fn test_duplicate_to_writer() {
+    let file_spec = FileSpec::default()
+        .directory(self::test_utils::dir())
+        .suppress_timestamp();
+    let file_path = file_spec.as_pathbuf(None);
+    let messages = Arc::new(Mutex::new(Vec::new()));
+    {
+        let logger = Logger::try_with_str("info, noisy=error")
+            .unwrap()
+            .log_to_file(file_spec)
+            .add_writer("Alert", Box::new(MessageCollector::new(&messages)))
+            .duplicate_to_writer_named("Alert", Duplicate::Warn)
+            .duplicate_to_writer_named("Missing", Duplicate::Error);
+        assert_eq!(
+            logger.check(),
+            vec![ConfigWarning::DuplicationToUnknownWriter(
+                "Missing".to_string()
+            )]
+        );
+        let _logger = logger
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+        error!("error");
+        warn!("warn");
+        info!("info is not duplicated");
+        warn!(target: "noisy", "not written at all");
+        warn!(target: "{Alert,_Default}", "addressed explicitly");
+        info!(target: "{Alert}", "only to Alert");
+    }
+
+    assert_eq!(
+        std::fs::read_to_string(file_path).unwrap(),
+        "ERROR [test_duplicate_to_writer] error\n\
+         WARN [test_duplicate_to_writer] warn\n\
+         INFO [test_duplicate_to_writer] info is not duplicated\n\
+         WARN [test_duplicate_to_writer] addressed explicitly\n"
+    );
+    assert_eq!(
+        *messages.lock().unwrap(),
+        vec!["error", "warn", "addressed explicitly", "only to Alert"]
+    );
+}
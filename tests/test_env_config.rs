@@ -0,0 +1,35 @@
+mod test_utils;
+
+use flexi_logger::Logger;
+use log::*;
+
+#[test]
+fn test_env_config() {
+    let directory = self::test_utils::dir();
+    std::env::set_var(
+        "FLEXI_LOGGER_CONF",
+        format!(
+            "spec=warn; file={}/; rotate=10M,numbers,keep_log_files:3; format=detailed",
+            directory.display()
+        ),
+    );
+    // the dedicated variable overrides the entry in FLEXI_LOGGER_CONF
+    std::env::set_var("RUST_LOG", "info");
+
+    let logger = Logger::try_from_env_config()
+        .unwrap_or_else(|e| panic!("Logger configuration failed with {e}"))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+    error!("This is an error message");
+    info!("This is an info message");
+    debug!("This is a debug message - you must not see it!");
+
+    logger.validate_logs(&[
+        ("ERROR", "[test_env_config]", "error message"),
+        ("INFO", "[test_env_config]", "info message"),
+    ]);
+
+    std::env::set_var("FLEXI_LOGGER_ROTATE", "10M,sometimes");
+    assert!(Logger::try_from_env_config().is_err());
+}
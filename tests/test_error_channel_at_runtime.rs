@@ -0,0 +1,36 @@
+mod test_utils;
+
+use flexi_logger::{ErrorChannel, Logger};
+use log::*;
+use std::{
+    fs::File,
+    io::{BufRead, BufReader},
+};
+
+#[test]
+fn test_error_channel_at_runtime() {
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .error_channel(ErrorChannel::DevNull)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+    assert_eq!(logger.error_channel(), ErrorChannel::DevNull);
+
+    // provoke an error of flexi_logger, which is swallowed
+    info!(target: "{_Default,missing_writer}", "This message cannot be written");
+
+    std::fs::create_dir_all(test_utils::dir()).unwrap();
+    let err_file = test_utils::file("flexi_logger_error_channel.err");
+    logger.set_error_channel(ErrorChannel::File(err_file.clone()));
+    assert_eq!(logger.error_channel(), ErrorChannel::File(err_file.clone()));
+
+    // provoke an error of flexi_logger, which now goes to the file
+    info!(target: "{_Default,missing_writer}", "This message cannot be written");
+
+    let lines = BufReader::new(File::open(err_file).unwrap())
+        .lines()
+        .map(Result::unwrap)
+        .collect::<Vec<_>>();
+    assert_eq!(lines.len(), 2, "{lines:?}");
+    assert!(lines[0].contains("missing_writer"), "{lines:?}");
+}
@@ -0,0 +1,57 @@
+mod test_utils;
+
+use flexi_logger::{ErrorChannel, FileSpec, LogfileSelector, Logger};
+use log::*;
+
+#[test]
+fn test_error_file_rotation() {
+    let directory = test_utils::dir();
+    let err_file = directory.join("flexi_logger.err");
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(&directory))
+        .error_channel(ErrorChannel::File(err_file.clone()))
+        .rotate_error_file(500, 2)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+    // each call produces an error message of more than 200 bytes,
+    // because the writer does not exist
+    for idx in 0..10 {
+        error!(target: "{_Default,no_such_writer}", "error {idx}");
+    }
+
+    let error_files = logger
+        .existing_log_files(&LogfileSelector::none().with_error_files())
+        .unwrap();
+    let mut err_file_1 = err_file.clone().into_os_string();
+    err_file_1.push(".1");
+    let mut err_file_2 = err_file.clone().into_os_string();
+    err_file_2.push(".2");
+    for file in [err_file.clone(), err_file_1.into(), err_file_2.into()] {
+        assert!(
+            error_files.contains(&file),
+            "{file:?} not in {error_files:?}"
+        );
+        assert!(std::fs::metadata(file).unwrap().len() < 1000);
+    }
+    // older files were deleted
+    let no_of_error_files = std::fs::read_dir(&directory)
+        .unwrap()
+        .filter(|entry| {
+            entry
+                .as_ref()
+                .unwrap()
+                .file_name()
+                .to_string_lossy()
+                .starts_with("flexi_logger.err")
+        })
+        .count();
+    assert_eq!(no_of_error_files, 3);
+
+    // without with_error_files, the error files are not listed
+    assert!(!logger
+        .existing_log_files(&LogfileSelector::default().with_r_current())
+        .unwrap()
+        .contains(&err_file));
+}
@@ -0,0 +1,43 @@
+mod test_utils;
+
+use flexi_logger::{FileSpec, Logger};
+use log::*;
+use std::sync::{Arc, Mutex};
+use test_utils::{message_only, LineCollector};
+
+#[test]
+fn test_escape_control_chars() {
+    let file_spec = FileSpec::default()
+        .directory(self::test_utils::dir())
+        .suppress_timestamp();
+    let file_path = file_spec.as_pathbuf(None);
+    let raw_lines = Arc::new(Mutex::new(Vec::new()));
+    {
+        let _logger = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(file_spec)
+            .format(message_only)
+            .escape_control_chars_for_file()
+            // doesn't ask for escaping
+            .add_writer(
+                "Raw",
+                Box::new(LineCollector::new(&raw_lines, message_only)),
+            )
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+        info!("plain äöü");
+        info!("forged\nINFO [admin] logged in");
+        info!("\u{1b}[31mred\u{1b}[0m\ttab");
+        error!(target: "{Raw,_Default}", "a\nb");
+    }
+
+    assert_eq!(
+        std::fs::read_to_string(file_path).unwrap(),
+        "plain äöü\n\
+         forged\\nINFO [admin] logged in\n\
+         \\u{1b}[31mred\\u{1b}[0m\\ttab\n\
+         a\\nb\n"
+    );
+    assert_eq!(*raw_lines.lock().unwrap(), vec!["a\nb".to_string()]);
+}
@@ -0,0 +1,105 @@
+mod test_utils;
+
+use flexi_logger::{
+    writers::{FileLogWriter, LogWriter},
+    Cleanup, Criterion, DeferredNow, Fallback, FileSpec, Naming,
+};
+
+#[test]
+fn test_fallback_at_startup() {
+    let directory = test_utils::dir().join("startup");
+    std::fs::create_dir_all(&directory).unwrap();
+    // a directory can't be created below a file
+    let blocker = directory.join("blocker");
+    std::fs::write(&blocker, "not a directory").unwrap();
+    let fallback_dir = directory.join("fallback");
+
+    let flw = FileLogWriter::builder(
+        FileSpec::default()
+            .directory(blocker.join("logs"))
+            .basename("app")
+            .suppress_timestamp(),
+    )
+    .fallback(Fallback::Directory(blocker.join("also_not_possible")))
+    .fallback(Fallback::Directory(fallback_dir.clone()))
+    .try_build()
+    .unwrap();
+    write_line(&flw, "goes to the fallback directory");
+    flw.shutdown();
+
+    assert_eq!(
+        std::fs::read_to_string(fallback_dir.join("app.log")).unwrap(),
+        "INFO [test_fallback] goes to the fallback directory\n"
+    );
+
+    // without fallback, building fails
+    assert!(
+        FileLogWriter::builder(FileSpec::default().directory(blocker.join("logs")))
+            .try_build()
+            .is_err()
+    );
+}
+
+#[test]
+fn test_fallback_after_rotation() {
+    let directory = test_utils::dir().join("rotation");
+    let log_dir = directory.join("logs");
+    let fallback_dir = directory.join("fallback");
+
+    let flw = FileLogWriter::builder(
+        FileSpec::default()
+            .directory(&log_dir)
+            .basename("app")
+            .suppress_timestamp(),
+    )
+    .rotate(Criterion::Size(50), Naming::NumbersDirect, Cleanup::Never)
+    .fallback(Fallback::Directory(fallback_dir.clone()))
+    .fallback(Fallback::Stderr)
+    .try_build()
+    .unwrap();
+    write_line(&flw, "this line is long enough to fill a file alone");
+    assert!(log_dir.join("app_r00000.log").exists());
+
+    // the log directory becomes unusable
+    std::fs::remove_dir_all(&log_dir).unwrap();
+    std::fs::write(&log_dir, "not a directory").unwrap();
+
+    write_line(&flw, "this line is written after the rotation");
+    flw.shutdown();
+    assert_eq!(
+        test_utils::file_names(&fallback_dir),
+        vec!["app_r00001.log"]
+    );
+}
+
+#[test]
+fn test_fallback_to_stderr() {
+    let directory = test_utils::dir().join("stderr");
+    std::fs::create_dir_all(&directory).unwrap();
+    let blocker = directory.join("blocker");
+    std::fs::write(&blocker, "not a directory").unwrap();
+
+    let flw = FileLogWriter::builder(FileSpec::default().directory(blocker.join("logs")))
+        .rotate(Criterion::Size(50), Naming::Numbers, Cleanup::Never)
+        .fallback(Fallback::Stderr)
+        .try_build()
+        .unwrap();
+    write_line(&flw, "this line is long enough to fill a file alone");
+    write_line(&flw, "and is not rotated on stderr");
+    // stderr is not a file
+    assert!(flw.rotate_to("marked").is_err());
+    flw.shutdown();
+    assert_eq!(test_utils::file_names(&directory), vec!["blocker"]);
+}
+
+fn write_line(flw: &FileLogWriter, text: &str) {
+    flw.write(
+        &mut DeferredNow::new(),
+        &log::Record::builder()
+            .args(format_args!("{text}"))
+            .level(log::Level::Info)
+            .module_path(Some("test_fallback"))
+            .build(),
+    )
+    .unwrap();
+}
@@ -0,0 +1,49 @@
+mod test_utils;
+
+use flexi_logger::{
+    writers::{FileLogWriter, LogWriter},
+    Cleanup, Criterion, DeferredNow, FileSpec, LogfileSelector, Naming,
+};
+use log::{Level, Record};
+
+#[test]
+fn test_file_footer() {
+    let writer = FileLogWriter::builder(
+        FileSpec::default()
+            .directory(test_utils::dir())
+            .basename("test")
+            .suffix("xml"),
+    )
+    .format(|w, _now, record| write!(w, "<line>{}</line>", record.args()))
+    .file_header(|w| w.write_all(b"<log>\n"))
+    .file_footer(|w| w.write_all(b"</log>\n"))
+    .rotate(Criterion::Size(100), Naming::Numbers, Cleanup::Never)
+    .try_build()
+    .unwrap();
+
+    for i in 0..20 {
+        writer
+            .write(
+                &mut DeferredNow::new(),
+                &Record::builder()
+                    .args(format_args!("{i}"))
+                    .level(Level::Info)
+                    .build(),
+            )
+            .unwrap();
+    }
+    writer.rotate_to("snapshot").unwrap();
+    writer.shutdown();
+
+    let files = writer
+        .existing_log_files(&LogfileSelector::default().with_r_current())
+        .unwrap();
+    assert!(files.len() > 2, "no rotation happened");
+    let snapshot = files[0].with_file_name("test_snapshot.xml");
+    for path in files.iter().chain(std::iter::once(&snapshot)) {
+        let content = std::fs::read_to_string(path).unwrap();
+        assert!(content.starts_with("<log>\n"), "{}", path.display());
+        assert!(content.ends_with("</log>\n"), "{}", path.display());
+        assert_eq!(content.matches("</log>").count(), 1, "{}", path.display());
+    }
+}
@@ -0,0 +1,67 @@
+mod test_utils;
+
+use flexi_logger::{
+    writers::{FileLogWriter, LogWriter},
+    Cleanup, Criterion, DeferredNow, FileSpec, Naming,
+};
+use log::{Level, Record};
+
+#[test]
+fn test_file_header() {
+    let directory = test_utils::dir();
+    let csv_writer = FileLogWriter::builder(
+        FileSpec::default()
+            .directory(directory.join("csv"))
+            .basename("test")
+            .suffix("csv"),
+    )
+    .format(|w, _now, record| write!(w, "{},{}", record.level(), record.args()))
+    .file_header(|w| w.write_all(b"level,message\n"))
+    .rotate(Criterion::Size(100), Naming::Numbers, Cleanup::Never)
+    .try_build()
+    .unwrap();
+
+    let app_writer = FileLogWriter::builder(
+        FileSpec::default()
+            .directory(directory.join("app"))
+            .basename("test"),
+    )
+    .app_info_file_header("test_file_header", "1.2.3")
+    .try_build()
+    .unwrap();
+
+    for i in 0..20 {
+        for writer in [&csv_writer, &app_writer] {
+            writer
+                .write(
+                    &mut DeferredNow::new(),
+                    &Record::builder()
+                        .args(format_args!("line {i}"))
+                        .level(Level::Info)
+                        .build(),
+                )
+                .unwrap();
+        }
+    }
+    csv_writer.shutdown();
+    app_writer.shutdown();
+
+    let csv_files = csv_writer
+        .existing_log_files(&flexi_logger::LogfileSelector::default().with_r_current())
+        .unwrap();
+    assert!(csv_files.len() > 2, "no rotation happened");
+    for path in csv_files {
+        let content = std::fs::read_to_string(&path).unwrap();
+        let mut lines = content.lines();
+        assert_eq!(lines.next(), Some("level,message"), "{}", path.display());
+        assert!(lines.all(|line| line.starts_with("INFO,line ")));
+    }
+
+    let app_files = app_writer
+        .existing_log_files(&flexi_logger::LogfileSelector::default())
+        .unwrap();
+    assert_eq!(app_files.len(), 1);
+    let content = std::fs::read_to_string(&app_files[0]).unwrap();
+    assert!(content.starts_with("test_file_header 1.2.3; started: "));
+    assert_eq!(content.lines().count(), 21);
+}
@@ -0,0 +1,43 @@
+mod test_utils;
+
+use flexi_logger::{Cleanup, Criterion, FileSpec, LevelFilter, Logger, Naming};
+use log::*;
+
+#[test]
+fn test_file_split() {
+    let directory = test_utils::dir();
+    let handle = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file_split(
+            FileSpec::default()
+                .directory(&directory)
+                .basename("all")
+                .suppress_timestamp(),
+            FileSpec::default()
+                .directory(&directory)
+                .basename("warnings")
+                .suppress_timestamp(),
+            LevelFilter::Warn,
+        )
+        .rotate(Criterion::Size(100_000), Naming::Numbers, Cleanup::Never)
+        .rotate_split(Criterion::Size(10), Naming::Numbers, Cleanup::Never)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+    error!("This is an error message");
+    warn!("This is a warning");
+    info!("This is an info message");
+    debug!("This is a debug message that is filtered out");
+    handle.flush();
+
+    let read = |name: &str| std::fs::read_to_string(directory.join(name)).unwrap();
+    let all = read("all_rCURRENT.log");
+    assert_eq!(all.lines().count(), 3, "{all}");
+    // the second file set is rotated with its own criterion
+    let errors = read("warnings_r00000.log");
+    assert_eq!(errors.lines().count(), 1, "{errors}");
+    assert!(errors.contains("error message"));
+    let warnings = read("warnings_rCURRENT.log");
+    assert_eq!(warnings.lines().count(), 1, "{warnings}");
+    assert!(warnings.contains("warning"));
+}
@@ -123,6 +123,18 @@ mod platform {
         }
     }
 
-    #[cfg(not(target_family = "unix"))]
+    // a symbolic link, a hard link, or a file that contains the path of the log file
+    #[cfg(target_os = "windows")]
+    pub fn check_link(link_name: &str) {
+        let content =
+            std::fs::read_to_string(link_name).unwrap_or_else(|e| panic!("error with link: {e}"));
+        assert!(
+            content.contains("This is an error message")
+                || std::path::Path::new(content.trim_end()).is_file(),
+            "link does not lead to the log file"
+        );
+    }
+
+    #[cfg(not(any(target_family = "unix", target_os = "windows")))]
     pub fn check_link(_: &str) {}
 }
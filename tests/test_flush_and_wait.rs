@@ -0,0 +1,42 @@
+#[cfg(feature = "async")]
+mod test_utils;
+
+#[cfg(feature = "async")]
+#[test]
+fn test_flush_and_wait() {
+    use flexi_logger::{FileSpec, Logger, WriteMode, DEFAULT_MESSAGE_CAPA, DEFAULT_POOL_CAPA};
+    use log::*;
+    use std::time::Duration;
+
+    let directory = test_utils::dir();
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(
+            FileSpec::default()
+                .directory(&directory)
+                .suppress_timestamp(),
+        )
+        .write_mode(WriteMode::AsyncWith {
+            pool_capa: DEFAULT_POOL_CAPA,
+            message_capa: DEFAULT_MESSAGE_CAPA,
+            // the log lines must not reach the file with the automatic flushing
+            flush_interval: Duration::from_secs(3600),
+        })
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+    for i in 0..1000 {
+        info!("This is line {i}");
+    }
+    logger.flush_and_wait(Duration::from_secs(10)).unwrap();
+
+    let path = std::fs::read_dir(&directory)
+        .unwrap()
+        .next()
+        .unwrap()
+        .unwrap()
+        .path();
+    let content = std::fs::read_to_string(path).unwrap();
+    assert_eq!(content.lines().count(), 1000);
+    assert!(content.contains("This is line 999"));
+}
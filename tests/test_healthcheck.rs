@@ -0,0 +1,70 @@
+mod test_utils;
+
+use flexi_logger::{writers::LogWriter, DeferredNow, FileSpec, FlexiLoggerError, Logger};
+use log::*;
+use std::sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+};
+
+// Is healthy as long as the flag is set.
+struct ToggledWriter(Arc<AtomicBool>);
+impl LogWriter for ToggledWriter {
+    fn write(&self, _now: &mut DeferredNow, _record: &Record) -> std::io::Result<()> {
+        Ok(())
+    }
+    fn flush(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+    fn healthcheck(&self) -> Result<(), FlexiLoggerError> {
+        if self.0.load(Ordering::Relaxed) {
+            Ok(())
+        } else {
+            Err(FlexiLoggerError::OutputIo(std::io::Error::new(
+                std::io::ErrorKind::NotConnected,
+                "backend is gone",
+            )))
+        }
+    }
+}
+
+#[test]
+fn test_healthcheck() {
+    let healthy = Arc::new(AtomicBool::new(true));
+    let file_spec = FileSpec::default()
+        .directory(test_utils::dir())
+        .suppress_timestamp()
+        .basename("healthcheck");
+    let file_path = file_spec.as_pathbuf(None);
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(file_spec)
+        .add_writer("Remote", Box::new(ToggledWriter(Arc::clone(&healthy))))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+    info!("the log file is created");
+    logger.healthcheck().unwrap();
+
+    healthy.store(false, Ordering::Relaxed);
+    assert_eq!(unhealthy_writers(&logger), vec!["Remote"]);
+
+    std::fs::remove_file(&file_path).unwrap();
+    assert_eq!(unhealthy_writers(&logger), vec!["FileLogWriter", "Remote"]);
+    let report = logger.healthcheck().unwrap_err().render_verbose();
+    assert!(report.contains("does not exist anymore"), "{report}");
+    assert!(report.contains("backend is gone"), "{report}");
+
+    healthy.store(true, Ordering::Relaxed);
+    logger.reopen_output().unwrap();
+    logger.healthcheck().unwrap();
+}
+
+fn unhealthy_writers(logger: &flexi_logger::LoggerHandle) -> Vec<String> {
+    match logger.healthcheck() {
+        Err(FlexiLoggerError::Unhealthy(unhealthy)) => {
+            unhealthy.into_iter().map(|(name, _)| name).collect()
+        }
+        other => panic!("unexpected result {other:?}"),
+    }
+}
@@ -0,0 +1,65 @@
+mod test_utils;
+
+use flexi_logger::{writers::LineLogger, FileSpec, Logger};
+use log::*;
+use std::io::Write;
+
+#[test]
+fn test_line_logger() {
+    let file_spec = FileSpec::default()
+        .directory(self::test_utils::dir())
+        .suppress_timestamp();
+    let file_path = file_spec.as_pathbuf(None);
+    let (location, other_location) = {
+        let _logger = Logger::try_with_str("info, child=warn")
+            .unwrap()
+            .log_to_file(file_spec)
+            .format(|w, _now, record| {
+                write!(
+                    w,
+                    "{} {} [{}:{}]: {}",
+                    record.level(),
+                    record.target(),
+                    record.file().unwrap_or_default(),
+                    record.line().unwrap_or_default(),
+                    record.args()
+                )
+            })
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+        let (mut writer, line) = (LineLogger::new(Level::Warn, "child"), line!());
+        let location = format!("[{}:{line}]", file!());
+        writer.write_all(b"first line\nsecond ").unwrap();
+        writer.write_all(b"line\r\nthird").unwrap();
+        writer.write_all(b" line\n\n").unwrap();
+        writer.write_all(b"incomplete").unwrap();
+        writer.flush().unwrap();
+        writer.write_all(b"invalid \xff\n").unwrap();
+        writer.write_all(b"pending at drop").unwrap();
+        drop(writer);
+
+        let mut filtered = LineLogger::new(Level::Info, "child");
+        writeln!(filtered, "not logged").unwrap();
+
+        let (mut other, line) = (LineLogger::new(Level::Info, "other"), line!());
+        let other_location = format!("[{}:{line}]", file!());
+        writeln!(other, "answer: {}", 42).unwrap();
+
+        (location, other_location)
+    };
+
+    assert_eq!(
+        std::fs::read_to_string(file_path).unwrap(),
+        format!(
+            "WARN child {location}: first line\n\
+             WARN child {location}: second line\n\
+             WARN child {location}: third line\n\
+             WARN child {location}: \n\
+             WARN child {location}: incomplete\n\
+             WARN child {location}: invalid \u{fffd}\n\
+             WARN child {location}: pending at drop\n\
+             INFO other {other_location}: answer: 42\n"
+        )
+    );
+}
@@ -0,0 +1,45 @@
+mod test_utils;
+
+use flexi_logger::{Cleanup, Criterion, LogfileSelector, Logger, Naming};
+use log::*;
+
+#[test]
+fn test_log_to_file_handle() {
+    let directory = test_utils::dir();
+    std::fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("inherited.log");
+    let file = std::fs::File::create(&path).unwrap();
+
+    let (logger, handle) = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file_handle(file)
+        // is ignored, a provided file handle can't be rotated
+        .rotate(Criterion::Size(10), Naming::Numbers, Cleanup::Never)
+        .build_nested()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+    for msg in ["first line", "second line"] {
+        logger.log(
+            &Record::builder()
+                .args(format_args!("{msg}"))
+                .level(Level::Info)
+                .build(),
+        );
+    }
+    handle.flush();
+
+    assert!(handle.reopen_output().is_err());
+    assert!(handle.trigger_rotation().is_err());
+    assert!(handle.rotate_to("manual").is_err());
+    assert!(handle
+        .existing_log_files(&LogfileSelector::default())
+        .unwrap()
+        .is_empty());
+    handle.shutdown();
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    assert!(content.contains("first line"), "{content}");
+    assert!(content.contains("second line"), "{content}");
+    // no other files were created
+    assert_eq!(std::fs::read_dir(&directory).unwrap().count(), 1);
+}
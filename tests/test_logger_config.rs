@@ -0,0 +1,79 @@
+#[cfg(feature = "config")]
+mod test_utils;
+
+#[cfg(feature = "config")]
+mod d {
+    use flexi_logger::{FlexiLoggerError, Logger, LoggerConfig};
+    use log::*;
+
+    #[test]
+    fn test_logger_config() {
+        let directory = super::test_utils::dir();
+        let config: LoggerConfig = toml::from_str(&format!(
+            r#"
+            spec = "info"
+            duplicate_to_stderr = "warn"
+            write_mode = "direct"
+
+            [file]
+            directory = "{}"
+            basename = "configured"
+            suppress_timestamp = true
+
+            [rotate]
+            criterion = "1K"
+            naming = "numbers"
+            cleanup = "keep_log_files:2"
+            "#,
+            directory.display()
+        ))
+        .unwrap();
+
+        {
+            let _logger = Logger::try_from_config(config)
+                .unwrap()
+                .format(|w, _now, record| write!(w, "{}", record.args()))
+                .start()
+                .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+            for idx in 0..100 {
+                info!("This is info message {idx:0>3}, with some padding to force rotations");
+            }
+        }
+
+        // cleanup keeps two rotated files besides the current one
+        let files = std::fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().into_string().unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(files.len(), 3, "{files:?}");
+        assert!(files.contains(&"configured_rCURRENT.log".to_string()));
+        assert!(files
+            .iter()
+            .all(|f| f.starts_with("configured_r") && f.ends_with(".log")));
+    }
+
+    #[test]
+    fn test_invalid_logger_config() {
+        for (toml, field) in [
+            (r#"rotate = { criterion = "day" }"#, "rotate"),
+            (
+                r#"file = {} \n rotate = { criterion = "fortnight" }"#,
+                "rotate",
+            ),
+            (r#"format = "fancy""#, "format"),
+            (
+                r#"duplicate_to_stdout = "sometimes""#,
+                "duplicate_to_stdout",
+            ),
+            (r#"write_mode = "eventually""#, "write_mode"),
+        ] {
+            let config: LoggerConfig = toml::from_str(&toml.replace("\\n", "\n")).unwrap();
+            match Logger::try_from_config(config) {
+                Err(FlexiLoggerError::Config(msg)) => assert!(msg.starts_with(field), "{msg}"),
+                Err(e) => panic!("unexpected error: {e}"),
+                Ok(_) => panic!("invalid config was accepted: {toml}"),
+            }
+        }
+        assert!(toml::from_str::<LoggerConfig>("colour = \"blue\"").is_err());
+    }
+}
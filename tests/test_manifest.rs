@@ -0,0 +1,83 @@
+#![cfg(feature = "manifest")]
+mod test_utils;
+
+use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming};
+use log::*;
+
+#[test]
+fn test_manifest() {
+    let directory = test_utils::dir();
+
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(
+            FileSpec::default()
+                .directory(&directory)
+                .basename("audited")
+                .suppress_timestamp(),
+        )
+        .rotate(
+            Criterion::Size(50),
+            Naming::Numbers,
+            Cleanup::KeepLogFiles(2),
+        )
+        .cleanup_in_background_thread(false)
+        .rotate_on_shutdown(true)
+        .manifest(true)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+    for i in 0..4 {
+        info!("this line is long enough to fill a file alone: {i}");
+    }
+    logger.shutdown();
+
+    let manifest = std::fs::read_to_string(directory.join("audited.manifest.jsonl")).unwrap();
+    let events = manifest
+        .lines()
+        .map(|line| {
+            let event = field(line, "event");
+            let file = field(line, "file");
+            format!("{event} {file}")
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        events,
+        vec![
+            "rotated audited_r00000.log",
+            "rotated audited_r00001.log",
+            "rotated audited_r00002.log",
+            "deleted audited_r00000.log",
+            "rotated audited_r00003.log",
+            "deleted audited_r00001.log",
+        ]
+    );
+
+    for line in manifest.lines().filter(|line| line.contains("\"rotated\"")) {
+        assert_eq!(field(line, "sha256").len(), 64, "bad checksum in {line}");
+        assert!(
+            line.contains("\"first_write\":"),
+            "no first_write in {line}"
+        );
+        assert!(line.contains("\"last_write\":"), "no last_write in {line}");
+    }
+    let last_rotated = manifest.lines().nth(4).unwrap();
+    let size = std::fs::metadata(directory.join("audited_r00003.log"))
+        .unwrap()
+        .len();
+    assert!(
+        last_rotated.contains(&format!("\"size\":{size},")),
+        "wrong size in {last_rotated}"
+    );
+}
+
+// the value of a string field in a manifest line
+fn field<'a>(line: &'a str, name: &str) -> &'a str {
+    let start = line
+        .find(&format!("\"{name}\":\""))
+        .unwrap_or_else(|| panic!("no field {name} in {line}"))
+        + name.len()
+        + 4;
+    let len = line[start..].find('"').unwrap();
+    &line[start..start + len]
+}
@@ -0,0 +1,47 @@
+mod test_utils;
+
+use flexi_logger::{FileSpec, Logger};
+use log::*;
+use std::sync::{Arc, Mutex};
+use test_utils::{message_only, LineCollector};
+
+#[test]
+fn test_max_line_length() {
+    let file_spec = FileSpec::default()
+        .directory(self::test_utils::dir())
+        .suppress_timestamp();
+    let file_path = file_spec.as_pathbuf(None);
+    let alert_lines = Arc::new(Mutex::new(Vec::new()));
+    {
+        let _logger = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(file_spec)
+            .format(message_only)
+            .max_line_length(10)
+            .add_writer(
+                "Alert",
+                Box::new(LineCollector::new(&alert_lines, message_only).with_max_line_length(5)),
+            )
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+        info!("short");
+        info!("0123456789");
+        info!("{}", "x".repeat(100_000));
+        info!("0123456789ü");
+        error!(target: "{Alert,_Default}", "ABCDEFGH");
+    }
+
+    assert_eq!(
+        std::fs::read_to_string(file_path).unwrap(),
+        "short\n\
+         0123456789\n\
+         xxxxxxxxxx… [truncated 99990 bytes]\n\
+         0123456789… [truncated 2 bytes]\n\
+         ABCDEFGH\n"
+    );
+    assert_eq!(
+        *alert_lines.lock().unwrap(),
+        vec!["ABCDE… [truncated 3 bytes]".to_string()]
+    );
+}
@@ -0,0 +1,37 @@
+#[cfg(feature = "kv")]
+mod test_utils;
+
+#[cfg(feature = "kv")]
+mod d {
+    use flexi_logger::{log_with_id, FileSpec, Logger};
+    use log::*;
+
+    #[test]
+    fn test_message_id() {
+        let logger = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(FileSpec::default().directory(super::test_utils::dir()))
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+        log_with_id!(Level::Error, E1234, "This is an error message {}", 5);
+        error!(msg_id = "E2345", a = 1; "This is another error message");
+        warn!(a = 1; "This is a warning message");
+        info!("This is an info message");
+
+        logger.validate_logs(&[
+            (
+                "ERROR",
+                "[test_message_id::d] [E1234] This",
+                "error message 5",
+            ),
+            (
+                "ERROR",
+                "[test_message_id::d] [E2345] {a=1} This",
+                "another",
+            ),
+            ("WARN", "[test_message_id::d] {a=1} This", "warning"),
+            ("INFO", "[test_message_id::d] This", "info"),
+        ]);
+    }
+}
@@ -0,0 +1,89 @@
+mod test_utils;
+
+#[cfg(all(feature = "rotation_lock", unix))]
+mod d {
+    use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming};
+    use log::*;
+    use std::{
+        fs::File,
+        io::{BufRead, BufReader},
+        path::PathBuf,
+        process::Command,
+    };
+
+    const NO_OF_PROCESSES: usize = 4;
+    const NO_OF_LOGLINES_PER_PROCESS: usize = 20_000;
+    const CTRL_PROCESS: &str = "CTRL_PROCESS";
+    const CTRL_DIR: &str = "CTRL_DIR";
+
+    // Several processes write concurrently into the same files and rotate them;
+    // no line must get lost or mixed up
+    #[test]
+    fn test_multi_process_rotation() {
+        match (std::env::var(CTRL_PROCESS), std::env::var(CTRL_DIR)) {
+            (Ok(process), Ok(directory)) => write_loglines(&process, PathBuf::from(directory)),
+            _ => {
+                let directory = super::test_utils::dir();
+                let progname = std::env::args().next().unwrap();
+                let children: Vec<_> = (0..NO_OF_PROCESSES)
+                    .map(|process| {
+                        Command::new(&progname)
+                            .env(CTRL_PROCESS, process.to_string())
+                            .env(CTRL_DIR, &directory)
+                            .spawn()
+                            .expect("Command failed to start")
+                    })
+                    .collect();
+                for mut child in children {
+                    assert!(child.wait().unwrap().success());
+                }
+                verify_logs(directory);
+            }
+        }
+    }
+
+    fn write_loglines(process: &str, directory: PathBuf) {
+        let _logger = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(
+                FileSpec::default()
+                    .directory(directory)
+                    .suppress_timestamp(),
+            )
+            .format(|w, _now, record| write!(w, "{}", record.args()))
+            .multi_process_append()
+            .rotation_lock()
+            .rotate(Criterion::Size(100_000), Naming::Numbers, Cleanup::Never)
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+        let padding = "x".repeat(200);
+        for idx in 0..NO_OF_LOGLINES_PER_PROCESS {
+            info!("XXXXX process {process} line {idx:0>5} {padding} YYYYY");
+        }
+    }
+
+    fn verify_logs(directory: PathBuf) {
+        let files = std::fs::read_dir(directory)
+            .unwrap()
+            .map(|entry| entry.unwrap().path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "log"))
+            .collect::<Vec<_>>();
+        assert!(files.len() > 1, "no rotation happened: {files:?}");
+
+        let mut line_count = 0;
+        let mut line = String::new();
+        for file in files {
+            let mut reader = BufReader::new(File::open(file).unwrap());
+            while reader.read_line(&mut line).unwrap() > 0 {
+                assert!(
+                    line.starts_with("XXXXX process ") && line.ends_with(" YYYYY\n"),
+                    "corrupted line: {line}"
+                );
+                line_count += 1;
+                line.clear();
+            }
+        }
+        assert_eq!(line_count, NO_OF_PROCESSES * NO_OF_LOGLINES_PER_PROCESS);
+    }
+}
@@ -0,0 +1,44 @@
+#![cfg(feature = "textfilter")]
+
+mod test_utils;
+
+use flexi_logger::{FileSpec, Logger};
+use log::*;
+
+#[test]
+fn test_negated_and_scoped_textfilter() {
+    let logger = Logger::try_with_str("info/!heartbeat/{myapp::net}!timeout")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(test_utils::dir()))
+        .capture_file_output()
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+    info!(target: "myapp::db", "db connected");
+    info!(target: "myapp::db", "heartbeat - you must not see it!");
+    info!(target: "myapp::db", "db timeout");
+    info!(target: "myapp::net::tcp", "tcp heartbeat - you must not see it!");
+    info!(target: "myapp::net::tcp", "tcp timeout - you must not see it!");
+    info!(target: "myapp::net::tcp", "tcp connected");
+
+    logger.parse_new_spec("info/{myapp::db}connected").unwrap();
+    info!(target: "myapp::db", "db timeout - you must not see it!");
+    info!(target: "myapp::db", "db connected again");
+    info!(target: "myapp::net", "net heartbeat");
+
+    let captured = logger.captured_output().unwrap();
+    assert!(!captured.contains("you must not see it"), "{captured}");
+    for expected in [
+        "db connected",
+        "db timeout",
+        "tcp connected",
+        "db connected again",
+        "net heartbeat",
+    ] {
+        assert!(
+            captured.contains(expected),
+            "{expected} missing in {captured}"
+        );
+    }
+    assert_eq!(captured.lines().count(), 5, "{captured}");
+}
@@ -0,0 +1,57 @@
+mod test_utils;
+
+use flexi_logger::{FileSpec, Logger};
+use log::*;
+
+// A simple logger that dispatches each record to several loggers
+struct FanOut(Vec<Box<dyn Log>>);
+impl Log for FanOut {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.0.iter().any(|logger| logger.enabled(metadata))
+    }
+    fn log(&self, record: &Record) {
+        for logger in &self.0 {
+            logger.log(record);
+        }
+    }
+    fn flush(&self) {
+        for logger in &self.0 {
+            logger.flush();
+        }
+    }
+}
+
+#[test]
+fn test_nested_logger() {
+    let (flexi_logger, handle) = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(test_utils::dir()))
+        .build_nested()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+    assert_eq!(handle.max_level(), LevelFilter::Info);
+
+    log::set_boxed_logger(Box::new(FanOut(vec![flexi_logger]))).unwrap();
+    log::set_max_level(LevelFilter::Trace);
+
+    let debug_metadata = Metadata::builder()
+        .level(Level::Debug)
+        .target("test_nested_logger")
+        .build();
+    assert!(!log::logger().enabled(&debug_metadata));
+
+    error!("This is an error message");
+    debug!("This is a debug message that is filtered out");
+
+    // the global max level is not touched
+    handle.parse_new_spec("debug").unwrap();
+    assert_eq!(log::max_level(), LevelFilter::Trace);
+    assert_eq!(handle.max_level(), LevelFilter::Debug);
+    assert!(log::logger().enabled(&debug_metadata));
+    debug!("This is a debug message");
+    trace!("This is a trace message that is filtered out");
+
+    handle.validate_logs(&[
+        ("ERROR", "test_nested_logger", "error message"),
+        ("DEBUG", "test_nested_logger", "This is a debug message"),
+    ]);
+}
@@ -0,0 +1,127 @@
+mod test_utils;
+
+use flexi_logger::{
+    writers::{FileLogWriter, LogWriter},
+    Cleanup, Criterion, DeferredNow, FileSpec, Naming,
+};
+use log::{Level, Record};
+use std::path::Path;
+
+#[test]
+fn test_numbers_shifted() {
+    let directory = test_utils::dir();
+
+    let plain_dir = directory.join("plain");
+    let flw = writer(&plain_dir, Cleanup::KeepLogFiles(2));
+    for i in 1..=4 {
+        write_line(&flw, i);
+        flw.rotate().unwrap();
+    }
+    write_line(&flw, 5);
+    flw.shutdown();
+    assert_eq!(
+        test_utils::file_names(&plain_dir),
+        vec!["app.log", "app.log.1", "app.log.2"]
+    );
+    // the lower the number, the newer the file
+    for (file, line) in [("app.log", 5), ("app.log.1", 4), ("app.log.2", 3)] {
+        let content = std::fs::read_to_string(plain_dir.join(file)).unwrap();
+        assert!(
+            content.contains(&format!("line {line}")),
+            "{file}: {content}"
+        );
+    }
+
+    // a restart without appending rotates the file of the previous run
+    let flw = writer(&plain_dir, Cleanup::KeepLogFiles(2));
+    write_line(&flw, 6);
+    flw.shutdown();
+    let content = std::fs::read_to_string(plain_dir.join("app.log.1")).unwrap();
+    assert!(content.contains("line 5"), "{content}");
+
+    // a file with the highest possible number is left alone
+    let overflow_dir = directory.join("overflow");
+    std::fs::create_dir_all(&overflow_dir).unwrap();
+    std::fs::write(overflow_dir.join(format!("app.log.{}", u32::MAX)), "").unwrap();
+    let flw = writer(&overflow_dir, Cleanup::Never);
+    for i in 1..=2 {
+        write_line(&flw, i);
+        flw.rotate().unwrap();
+    }
+    flw.shutdown();
+    assert_eq!(
+        test_utils::file_names(&overflow_dir),
+        vec!["app.log", "app.log.1", "app.log.2", "app.log.4294967295"]
+    );
+
+    // the file below the highest possible number can't be shifted either
+    let overflow_dir = directory.join("overflow_2");
+    std::fs::create_dir_all(&overflow_dir).unwrap();
+    for number in [u32::MAX - 1, u32::MAX] {
+        std::fs::write(
+            overflow_dir.join(format!("app.log.{number}")),
+            number.to_string(),
+        )
+        .unwrap();
+    }
+    let flw = writer(&overflow_dir, Cleanup::Never);
+    for i in 1..=2 {
+        write_line(&flw, i);
+        flw.rotate().unwrap();
+    }
+    flw.shutdown();
+    assert_eq!(
+        test_utils::file_names(&overflow_dir),
+        vec![
+            "app.log",
+            "app.log.1",
+            "app.log.2",
+            "app.log.4294967294",
+            "app.log.4294967295"
+        ]
+    );
+    for number in [u32::MAX - 1, u32::MAX] {
+        let content =
+            std::fs::read_to_string(overflow_dir.join(format!("app.log.{number}"))).unwrap();
+        assert_eq!(content, number.to_string());
+    }
+
+    // compressed files are shifted as well
+    #[cfg(feature = "compress")]
+    {
+        let compressed_dir = directory.join("compressed");
+        let flw = writer(&compressed_dir, Cleanup::KeepLogAndCompressedFiles(1, 2));
+        for i in 1..=5 {
+            write_line(&flw, i);
+            flw.rotate().unwrap();
+        }
+        flw.shutdown();
+        assert_eq!(
+            test_utils::file_names(&compressed_dir),
+            vec!["app.log", "app.log.1", "app.log.2.gz", "app.log.3.gz"]
+        );
+    }
+}
+
+fn writer(directory: &Path, cleanup: Cleanup) -> FileLogWriter {
+    FileLogWriter::builder(
+        FileSpec::default()
+            .directory(directory)
+            .basename("app")
+            .suppress_timestamp(),
+    )
+    .rotate(Criterion::Size(1_000_000), Naming::NumbersShifted, cleanup)
+    .try_build()
+    .unwrap()
+}
+
+fn write_line(flw: &FileLogWriter, i: usize) {
+    flw.write(
+        &mut DeferredNow::new(),
+        &Record::builder()
+            .args(format_args!("line {i}"))
+            .level(Level::Info)
+            .build(),
+    )
+    .unwrap();
+}
@@ -0,0 +1,82 @@
+mod test_utils;
+
+use flexi_logger::{
+    writers::{FileLogWriter, LogWriter},
+    Cleanup, Criterion, DeferredNow, FileSpec, Naming,
+};
+use log::{Level, Record};
+
+#[test]
+fn test_numbers_with() {
+    let directory = test_utils::dir();
+
+    // narrow numbers are cleaned up by their value, not by their name
+    let flw = FileLogWriter::builder(
+        FileSpec::default()
+            .directory(directory.join("narrow"))
+            .basename("test")
+            .suppress_timestamp(),
+    )
+    .rotate(
+        Criterion::Size(1_000_000),
+        Naming::NumbersWith {
+            direct: false,
+            width: 1,
+            start: 8,
+        },
+        Cleanup::KeepLogFiles(2),
+    )
+    .cleanup_in_background_thread(false)
+    .try_build()
+    .unwrap();
+    for _ in 0..4 {
+        write_line(&flw);
+        flw.rotate().unwrap();
+    }
+    flw.shutdown();
+    assert_eq!(
+        test_utils::file_names(&directory.join("narrow")),
+        vec!["test_r10.log", "test_r11.log", "test_rCURRENT.log"]
+    );
+
+    // the numbering continues after existing files of another width
+    let direct_dir = directory.join("direct");
+    std::fs::create_dir_all(&direct_dir).unwrap();
+    std::fs::write(direct_dir.join("test_r00007.log"), "").unwrap();
+    let flw = FileLogWriter::builder(
+        FileSpec::default()
+            .directory(&direct_dir)
+            .basename("test")
+            .suppress_timestamp(),
+    )
+    .rotate(
+        Criterion::Size(1_000_000),
+        Naming::NumbersWith {
+            direct: true,
+            width: 2,
+            start: 1,
+        },
+        Cleanup::Never,
+    )
+    .try_build()
+    .unwrap();
+    write_line(&flw);
+    flw.rotate().unwrap();
+    write_line(&flw);
+    flw.shutdown();
+    assert_eq!(
+        test_utils::file_names(&direct_dir),
+        vec!["test_r00007.log", "test_r08.log", "test_r09.log"]
+    );
+}
+
+fn write_line(flw: &FileLogWriter) {
+    flw.write(
+        &mut DeferredNow::new(),
+        &Record::builder()
+            .args(format_args!("a line"))
+            .level(Level::Info)
+            .build(),
+    )
+    .unwrap();
+}
@@ -0,0 +1,66 @@
+mod test_utils;
+
+use flexi_logger::{
+    writers::{FileLogWriter, LogWriter},
+    FileSpec, FlexiLoggerError, Logger,
+};
+
+#[test]
+fn test_output_in_use() {
+    let directory = test_utils::dir();
+    let file_spec = FileSpec::default()
+        .directory(&directory)
+        .basename("app")
+        .suppress_timestamp();
+
+    let first = FileLogWriter::builder(file_spec.clone())
+        .try_build()
+        .unwrap();
+
+    // the same files, also with a differently written directory
+    for spec in [
+        file_spec.clone(),
+        file_spec
+            .clone()
+            .directory(directory.join("sub").join("..")),
+    ] {
+        std::fs::create_dir_all(directory.join("sub")).unwrap();
+        assert!(matches!(
+            FileLogWriter::builder(spec).try_build(),
+            Err(FlexiLoggerError::OutputInUse(_))
+        ));
+    }
+    // a logger that writes to the same files as an additional writer
+    assert!(matches!(
+        Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(file_spec.clone())
+            .add_writer(
+                "Other",
+                Box::new(
+                    FileLogWriter::builder(file_spec.clone().discriminant("other"))
+                        .try_build()
+                        .unwrap()
+                )
+            )
+            .add_writer("Same", Box::new(first))
+            .build(),
+        Err(FlexiLoggerError::OutputInUse(_))
+    ));
+
+    // other files are fine
+    let other = FileLogWriter::builder(file_spec.clone().discriminant("other"))
+        .try_build()
+        .unwrap();
+
+    // resetting a writer with its own files is fine
+    other
+        .reset(&FileLogWriter::builder(file_spec.clone().discriminant("other")).append())
+        .unwrap();
+
+    // after the shutdown, the files can be used by another writer
+    other.shutdown();
+    FileLogWriter::builder(file_spec.discriminant("other"))
+        .try_build()
+        .unwrap();
+}
@@ -0,0 +1,74 @@
+#[cfg(feature = "compress")]
+mod test_utils;
+
+#[cfg(feature = "compress")]
+#[test]
+fn test_parallel_compression() {
+    use flexi_logger::{
+        writers::{FileLogWriter, LogWriter},
+        Cleanup, Criterion, DeferredNow, FileSpec, Naming,
+    };
+    use log::{Level, Record};
+    use std::io::Read;
+
+    let directory = test_utils::dir();
+    std::fs::create_dir_all(&directory).unwrap();
+    for i in 0..10 {
+        std::fs::write(
+            directory.join(format!("test_r{i:0>5}.log")),
+            format!("content of file {i}\n"),
+        )
+        .unwrap();
+    }
+    // left behind by an interrupted cleanup
+    std::fs::write(directory.join("test_r00003.log.gz"), "not compressed").unwrap();
+    std::fs::write(directory.join("test_r00004.log.gz.partial"), "incomplete").unwrap();
+    std::fs::write(directory.join("test_r00042.log.gz.partial"), "incomplete").unwrap();
+
+    let flw = FileLogWriter::builder(
+        FileSpec::default()
+            .directory(&directory)
+            .basename("test")
+            .suppress_timestamp(),
+    )
+    .rotate(
+        Criterion::Size(1_000_000),
+        Naming::Numbers,
+        Cleanup::KeepCompressedFiles(100),
+    )
+    .compression_workers(4)
+    .try_build()
+    .unwrap();
+    // the initialization runs the first cleanup synchronously
+    flw.write(
+        &mut DeferredNow::new(),
+        &Record::builder()
+            .args(format_args!("a line"))
+            .level(Level::Info)
+            .build(),
+    )
+    .unwrap();
+    flw.shutdown();
+
+    let mut file_names = std::fs::read_dir(&directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    file_names.sort();
+    let mut expected = (0..10)
+        .map(|i| format!("test_r{i:0>5}.log.gz"))
+        .collect::<Vec<_>>();
+    expected.push("test_rCURRENT.log".to_string());
+    assert_eq!(file_names, expected);
+
+    // the existing compressed file was replaced
+    for i in [0, 3, 4, 9] {
+        let mut content = String::new();
+        flate2::read::GzDecoder::new(
+            std::fs::File::open(directory.join(format!("test_r{i:0>5}.log.gz"))).unwrap(),
+        )
+        .read_to_string(&mut content)
+        .unwrap();
+        assert_eq!(content, format!("content of file {i}\n"));
+    }
+}
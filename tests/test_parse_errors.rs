@@ -47,22 +47,70 @@ fn parse_errors_logspec() {
         _ => panic!("Wrong error from parsing (3)"),
     }
 
-    match LogSpecification::parse("INFO, ene / mene / dubbedene")
+    match LogSpecification::parse("INFO, ene / {mene / dubbedene")
         .err()
         .unwrap()
     {
         FlexiLoggerError::Parse(_, logspec) => {
             assert_eq!(
                 logspec.module_filters(),
-                LogSpecification::off().module_filters()
+                LogSpecification::parse("info, ene")
+                    .unwrap()
+                    .module_filters()
             );
             #[cfg(feature = "textfilter")]
-            assert!(logspec.text_filter().is_none());
+            assert_eq!(logspec.text_filters().len(), 1);
         }
         _ => panic!("Wrong error from parsing (4)"),
     }
 }
 
+#[test]
+fn parse_issues() {
+    let spec = "info, foo=bar, a b, x=1=2, crate-a, foo = debug, foo = trace";
+    match LogSpecification::parse(spec).err().unwrap() {
+        FlexiLoggerError::Parse(description, logspec) => {
+            let issues = logspec.issues();
+            assert_eq!(issues.len(), 5, "{issues:?}");
+            assert_eq!(
+                issues.iter().filter(|issue| issue.is_error()).count(),
+                3,
+                "{issues:?}"
+            );
+            for (issue, fragment, is_error) in [
+                (&issues[0], "foo=bar", true),
+                (&issues[1], "a b", true),
+                (&issues[2], "x=1=2", true),
+                (&issues[3], "crate-a", false),
+                (&issues[4], "foo = trace", false),
+            ] {
+                assert_eq!(issue.fragment(), fragment);
+                assert_eq!(&spec[issue.position()..][..fragment.len()], fragment);
+                assert_eq!(issue.is_error(), is_error);
+            }
+            assert!(description.contains(&issues[0].to_string()));
+            assert!(!description.contains(&issues[3].to_string()));
+            assert_eq!(
+                logspec.module_filters(),
+                LogSpecification::parse("info, crate-a, foo = debug")
+                    .unwrap()
+                    .module_filters()
+            );
+        }
+        _ => panic!("Wrong error from parsing"),
+    }
+
+    // warnings don't make the parsing fail
+    let logspec = LogSpecification::parse("info, foo, foo = debug").unwrap();
+    assert_eq!(logspec.issues().len(), 1);
+    assert!(!logspec.issues()[0].is_error());
+    assert_eq!(logspec.issues()[0].position(), 11);
+    assert!(LogSpecification::parse("info, foo")
+        .unwrap()
+        .issues()
+        .is_empty());
+}
+
 #[test]
 fn parse_errors_logger() {
     let result = Logger::try_with_str("info, foo=baz");
@@ -17,6 +17,7 @@ fn test_reconfigure_methods() {
 
     test_parse_new_spec(&logger);
     test_push_new_spec(&mut logger);
+    test_scoped_spec(&logger);
     validate_logs(&logger);
 }
 
@@ -83,6 +84,31 @@ fn test_push_new_spec(logger: &mut LoggerHandle) {
     logger.pop_temp_spec(); // should be a no-op
 }
 
+fn test_scoped_spec(logger: &LoggerHandle) {
+    {
+        let _guard = logger.parse_scoped_spec("error").unwrap();
+        error!("3-error message");
+        warn!("3-warning - you must not see it!");
+        {
+            let _guard = logger.parse_scoped_spec("trace").unwrap();
+            debug!("3-debug message");
+        }
+        // we should be back on error
+        warn!("3-warning - you must not see it!");
+    }
+    // we should be back on info
+    info!("3-info message");
+    debug!("3-debug message - you must not see it!");
+
+    // the guard also restores the spec if the scope is left early
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let _guard = logger.parse_scoped_spec("off").unwrap();
+        panic!("leave the scope early");
+    }));
+    assert!(result.is_err());
+    info!("3-info message");
+}
+
 fn validate_logs(logger: &LoggerHandle) {
     logger.validate_logs(&[
         ("ERROR", "test_reconfigure_methods", "1-error"),
@@ -114,5 +140,10 @@ fn validate_logs(logger: &LoggerHandle) {
         ("ERROR", "test_reconfigure_methods", "2-error"),
         ("WARN", "test_reconfigure_methods", "2-warning"),
         ("INFO", "test_reconfigure_methods", "2-info"),
+        // -----
+        ("ERROR", "test_reconfigure_methods", "3-error"),
+        ("DEBUG", "test_reconfigure_methods", "3-debug"),
+        ("INFO", "test_reconfigure_methods", "3-info"),
+        ("INFO", "test_reconfigure_methods", "3-info"),
     ]);
 }
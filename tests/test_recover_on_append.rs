@@ -0,0 +1,39 @@
+mod test_utils;
+
+use flexi_logger::{FileSpec, Logger};
+use log::*;
+
+#[test]
+fn test_recover_on_append() {
+    let directory = test_utils::dir();
+    let file_spec = FileSpec::default()
+        .directory(&directory)
+        .basename("recover")
+        .suppress_timestamp();
+    let path = file_spec.as_pathbuf(None);
+
+    // simulate a crash in the middle of writing a line, and some NUL padding
+    std::fs::create_dir_all(&directory).unwrap();
+    std::fs::write(&path, b"first run 1\nfirst run 2\nfirst ru\0\0\0\0").unwrap();
+
+    {
+        let _logger = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(file_spec)
+            .format(|w, _now, record| write!(w, "{}", record.args()))
+            .append()
+            .recover_on_append(true)
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+        info!("second run 1");
+        info!("second run 2");
+    }
+
+    assert_eq!(
+        std::fs::read_to_string(&path).unwrap(),
+        "first run 1\nfirst run 2\nsecond run 1\nsecond run 2\n"
+    );
+    let mut sidecar = path.into_os_string();
+    sidecar.push(".corrupt");
+    assert_eq!(std::fs::read(sidecar).unwrap(), b"first ru\0\0\0\0");
+}
@@ -0,0 +1,75 @@
+mod test_utils;
+
+use flexi_logger::{
+    writers::{FileLogWriter, LogWriter},
+    Cleanup, Criterion, DeferredNow, FileSpec, Naming,
+};
+
+#[test]
+fn test_remove_stale_artifacts() {
+    let directory = test_utils::dir();
+    std::fs::create_dir_all(&directory).unwrap();
+
+    // left behind by an earlier run
+    std::fs::write(
+        directory.join("app_rCURRENT.ShortLivingTempFileForReOpen"),
+        "",
+    )
+    .unwrap();
+    // foreign files
+    std::fs::write(directory.join("other.ShortLivingTempFileForReOpen"), "").unwrap();
+    std::fs::write(
+        directory.join("app_x.ShortLivingTempFileForReOpen"),
+        "not empty",
+    )
+    .unwrap();
+    #[cfg(target_family = "unix")]
+    {
+        std::os::unix::fs::symlink(directory.join("app_r00003.log"), directory.join("stale"))
+            .unwrap();
+        std::os::unix::fs::symlink(
+            directory.join("app_rCURRENT.log"),
+            directory.join("current"),
+        )
+        .unwrap();
+        std::os::unix::fs::symlink(directory.join("other.log"), directory.join("foreign")).unwrap();
+    }
+
+    let flw = FileLogWriter::builder(
+        FileSpec::default()
+            .directory(&directory)
+            .basename("app")
+            .suppress_timestamp(),
+    )
+    .rotate(Criterion::Size(1000), Naming::Numbers, Cleanup::Never)
+    .create_symlink(directory.join("current"))
+    .remove_stale_artifacts(true)
+    .try_build()
+    .unwrap();
+    flw.write(
+        &mut DeferredNow::new(),
+        &log::Record::builder()
+            .args(format_args!("the output is opened lazily"))
+            .level(log::Level::Info)
+            .build(),
+    )
+    .unwrap();
+    flw.shutdown();
+
+    let mut expected = vec![
+        "app_rCURRENT.log",
+        "app_x.ShortLivingTempFileForReOpen",
+        "other.ShortLivingTempFileForReOpen",
+    ];
+    #[cfg(target_family = "unix")]
+    {
+        expected.extend(["current", "foreign"]);
+        // the configured symlink was created anew
+        assert_eq!(
+            std::fs::read_link(directory.join("current")).unwrap(),
+            directory.join("app_rCURRENT.log")
+        );
+    }
+    expected.sort_unstable();
+    assert_eq!(test_utils::file_names(&directory), expected);
+}
@@ -0,0 +1,53 @@
+mod test_utils;
+
+use flexi_logger::{writers::FileLogWriter, FileSpec, Logger};
+
+#[test]
+fn test_render_verbose() {
+    // a log directory that cannot be created because a file is in the way
+    let directory = test_utils::dir();
+    std::fs::create_dir_all(&directory).unwrap();
+    let blocking_file = directory.join("blocking_file");
+    std::fs::write(&blocking_file, b"").unwrap();
+    let log_dir = blocking_file.join("logs");
+    let rendered = FileLogWriter::builder(FileSpec::default().directory(&log_dir))
+        .try_build()
+        .err()
+        .unwrap()
+        .render_verbose();
+    assert!(rendered.lines().count() > 1, "{rendered}");
+    assert!(
+        rendered.contains(&format!("value: {}", log_dir.display())),
+        "{rendered}"
+    );
+    assert!(
+        rendered.contains("set with: FileSpec::directory"),
+        "{rendered}"
+    );
+    assert!(rendered.contains("help: "), "{rendered}");
+
+    // a malformed log specification
+    let rendered = Logger::try_with_str("info, foo = verbose, a b")
+        .err()
+        .unwrap()
+        .render_verbose();
+    assert!(
+        rendered.contains("error: unknown level filter 'verbose' at position 6"),
+        "{rendered}"
+    );
+    assert!(rendered.contains("at position 21 ('a b')"), "{rendered}");
+    assert!(
+        rendered.contains("set with: Logger::try_with_str"),
+        "{rendered}"
+    );
+
+    // an unusable basename
+    let rendered =
+        FileLogWriter::builder(FileSpec::default().directory(&directory).basename("a/b"))
+            .try_build()
+            .err()
+            .unwrap()
+            .render_verbose();
+    assert!(rendered.contains("\"a/b\""), "{rendered}");
+    assert!(rendered.contains("FileSpec::sanitize"), "{rendered}");
+}
@@ -0,0 +1,57 @@
+#![cfg(all(feature = "signal_hook", unix))]
+mod test_utils;
+
+use flexi_logger::{FileSpec, Logger};
+use log::*;
+use std::{path::Path, time::Duration};
+
+#[test]
+fn test_reopen_on_sighup() {
+    let file_spec = FileSpec::default()
+        .directory(self::test_utils::dir())
+        .suppress_timestamp()
+        .basename("myprog");
+    let file_path = file_spec.as_pathbuf(None);
+    let moved_path = file_spec.as_pathbuf(Some("moved"));
+
+    {
+        let _logger = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(file_spec)
+            .reopen_on_sighup(true)
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+        info!("XXX 1 AAA");
+        info!("XXX 2 AAA");
+
+        // act like logrotate: rename the file and send SIGHUP
+        std::fs::rename(&file_path, &moved_path).unwrap();
+        let status = std::process::Command::new("kill")
+            .args(["-HUP", &std::process::id().to_string()])
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        // the output is re-opened in the background
+        for _ in 0..100 {
+            if file_path.exists() {
+                break;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        assert!(file_path.exists(), "log file was not re-opened");
+
+        info!("YYY 3 AAA");
+    }
+
+    assert_eq!(count_lines(&moved_path), 2);
+    assert_eq!(count_lines(&file_path), 1);
+}
+
+fn count_lines(path: &Path) -> usize {
+    match std::fs::read_to_string(path) {
+        Ok(s) => s.lines().filter(|line| line.contains("AAA")).count(),
+        Err(_e) => 0,
+    }
+}
@@ -0,0 +1,59 @@
+mod test_utils;
+
+use flexi_logger::{writers::FileLogWriter, FileSpec, Logger, WriteMode};
+use log::*;
+
+// The write mode of the file log writer can be changed while the program is running
+#[test]
+fn test_reset_write_mode() {
+    let directory = test_utils::dir();
+    let file_spec = FileSpec::default()
+        .directory(&directory)
+        .suppress_timestamp();
+    #[cfg(feature = "async")]
+    let initial_write_mode = WriteMode::Async;
+    #[cfg(not(feature = "async"))]
+    let initial_write_mode = WriteMode::BufferDontFlush;
+
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(file_spec.clone())
+        .write_mode(initial_write_mode)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+    info!("written with the initial write mode");
+
+    // switching to Direct flushes the buffered output
+    logger
+        .reset_flw(
+            &FileLogWriter::builder(file_spec.clone())
+                .append()
+                .write_mode(WriteMode::Direct),
+        )
+        .unwrap();
+    assert_eq!(lines(&file_spec), 1);
+
+    // with Direct, each line is immediately visible
+    info!("written directly");
+    assert_eq!(lines(&file_spec), 2);
+
+    logger
+        .reset_flw(
+            &FileLogWriter::builder(file_spec.clone())
+                .append()
+                .write_mode(WriteMode::BufferDontFlush),
+        )
+        .unwrap();
+    info!("written with buffering");
+    assert_eq!(lines(&file_spec), 2);
+
+    logger.flush();
+    assert_eq!(lines(&file_spec), 3);
+}
+
+fn lines(file_spec: &FileSpec) -> usize {
+    std::fs::read_to_string(file_spec.as_pathbuf(None))
+        .unwrap()
+        .lines()
+        .count()
+}
@@ -0,0 +1,48 @@
+mod test_utils;
+
+use flexi_logger::{
+    writers::{FileLogWriter, LogWriter},
+    Age, Cleanup, Criterion, DeferredNow, FileSpec, Naming,
+};
+use log::*;
+
+// A restart within the lifetime of a timestamp infix (or after the clock was set back)
+// must not overwrite the existing file
+#[test]
+fn test_restart_with_same_timestamp() {
+    let directory = test_utils::dir();
+
+    for run in 0..3 {
+        let writer = FileLogWriter::builder(FileSpec::default().directory(&directory))
+            .rotate(
+                Criterion::Age(Age::Day),
+                Naming::TimestampsCustomFormat {
+                    current_infix: None,
+                    format: "%Y-%m-%d",
+                },
+                Cleanup::Never,
+            )
+            .try_build()
+            .unwrap();
+        writer
+            .write(
+                &mut DeferredNow::new(),
+                &Record::builder()
+                    .level(Level::Info)
+                    .args(format_args!("This is run {run}"))
+                    .build(),
+            )
+            .unwrap();
+        writer.shutdown();
+    }
+
+    let mut names: Vec<String> = std::fs::read_dir(&directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    names.sort_unstable();
+    assert_eq!(names.len(), 3, "{names:?}");
+    assert!(names[1].contains(".restart-0000"), "{names:?}");
+    assert!(names[2].contains(".restart-0001"), "{names:?}");
+    assert_eq!(test_utils::count_log_lines(&directory), 3);
+}
@@ -0,0 +1,122 @@
+mod test_utils;
+
+use flexi_logger::{filter::LogLineRewriter, FileSpec, Logger};
+use log::*;
+use std::sync::{Arc, Mutex};
+use test_utils::MessageCollector;
+
+#[test]
+fn test_rewriter() {
+    let messages = Arc::new(Mutex::new(Vec::new()));
+    let handle = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(test_utils::dir()))
+        .format_for_files(|w, _now, record| write!(w, "{} {}", record.level(), record.args()))
+        .add_writer("Collect", Box::new(MessageCollector::new(&messages)))
+        .rewriter(Box::new(ApiKeyRedactor))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+    info!(target: "{Collect,_Default}", "connecting with api_key=abc123 to server");
+    warn!("nothing to redact");
+    handle.flush();
+
+    let file = handle
+        .existing_log_files(&flexi_logger::LogfileSelector::default())
+        .unwrap()
+        .pop()
+        .unwrap();
+    assert_eq!(
+        std::fs::read_to_string(file).unwrap(),
+        "INFO connecting with api_key=<redacted> to server\nWARN nothing to redact\n"
+    );
+    assert_eq!(
+        *messages.lock().unwrap(),
+        vec!["connecting with api_key=<redacted> to server".to_string()]
+    );
+}
+
+#[cfg(feature = "textfilter")]
+#[test]
+fn test_redactor() {
+    let redactor =
+        flexi_logger::filter::Redactor::new(&[(r"api_key=\S+", "api_key=<redacted>")]).unwrap();
+    let (logger, handle) = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(test_utils::dir().join("redactor")))
+        .format_for_files(|w, _now, record| write!(w, "{} {}", record.level(), record.args()))
+        .rewriter(Box::new(redactor))
+        .build_nested()
+        .unwrap();
+
+    for message in [
+        format_args!("connecting with api_key=abc123 to server"),
+        format_args!("nothing to redact"),
+    ] {
+        logger.log(&Record::builder().args(message).level(Level::Info).build());
+    }
+    handle.flush();
+
+    let file = handle
+        .existing_log_files(&flexi_logger::LogfileSelector::default())
+        .unwrap()
+        .pop()
+        .unwrap();
+    assert_eq!(
+        std::fs::read_to_string(file).unwrap(),
+        "INFO connecting with api_key=<redacted> to server\nINFO nothing to redact\n"
+    );
+}
+
+#[cfg(all(feature = "kv", feature = "textfilter"))]
+#[test]
+fn test_rewriter_with_kv() {
+    let redactor = flexi_logger::filter::Redactor::new(&[(r"^\d{4}$", "<pin>")])
+        .unwrap()
+        .hashing();
+    assert_eq!(redactor.rewrite_value("pin", "no pin"), None);
+    let (logger, handle) = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(test_utils::dir().join("kv")))
+        .format_for_files(flexi_logger::default_format)
+        .rewriter(Box::new(redactor))
+        .build_nested()
+        .unwrap();
+
+    let kvs = [("pin", "1234"), ("user", "alice")];
+    logger.log(
+        &Record::builder()
+            .args(format_args!("pin changed"))
+            .module_path(Some("test"))
+            .level(Level::Info)
+            .key_values(&kvs)
+            .build(),
+    );
+    handle.flush();
+
+    let file = handle
+        .existing_log_files(&flexi_logger::LogfileSelector::default())
+        .unwrap()
+        .pop()
+        .unwrap();
+    let content = std::fs::read_to_string(file).unwrap();
+    assert!(
+        content.starts_with("INFO [test] {pin=\"<pin>:"),
+        "{content}"
+    );
+    assert!(
+        content.ends_with("\", user=\"alice\"} pin changed\n"),
+        "{content}"
+    );
+    assert!(!content.contains("1234"), "{content}");
+}
+
+struct ApiKeyRedactor;
+impl LogLineRewriter for ApiKeyRedactor {
+    fn rewrite(&self, record: &Record) -> Option<String> {
+        let text = record.args().to_string();
+        let (before, after) = text.split_once("api_key=")?;
+        let rest = after.find(' ').map_or("", |i| &after[i..]);
+        Some(format!("{before}api_key=<redacted>{rest}"))
+    }
+}
@@ -0,0 +1,41 @@
+mod test_utils;
+
+use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming};
+use log::*;
+
+#[test]
+fn test_rotate_on_shutdown() {
+    let directory = test_utils::dir();
+
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(
+            FileSpec::default()
+                .directory(&directory)
+                .basename("batch")
+                .suppress_timestamp(),
+        )
+        .rotate(
+            Criterion::Size(10_000),
+            Naming::Numbers,
+            Cleanup::KeepLogFiles(5),
+        )
+        .rotate_on_shutdown(true)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+    info!("first line");
+    info!("second line");
+    assert!(directory.join("batch_rCURRENT.log").exists());
+
+    logger.shutdown();
+
+    // only the rotated file is left
+    let mut files = std::fs::read_dir(&directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    files.sort();
+    assert_eq!(files, vec!["batch_r00000.log".to_string()]);
+    assert_eq!(test_utils::count_log_lines(&directory), 2);
+}
@@ -0,0 +1,51 @@
+mod test_utils;
+
+use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming};
+use log::*;
+
+#[test]
+fn test_rotate_to() {
+    let base_directory = test_utils::dir();
+    for (naming, current_file) in [
+        (Naming::Numbers, "test_rCURRENT.log"),
+        (Naming::NumbersDirect, "test_r00002.log"),
+    ] {
+        let directory = base_directory.join(format!("{naming:?}"));
+        let (logger, handle) = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(
+                FileSpec::default()
+                    .directory(&directory)
+                    .basename("test")
+                    .suppress_timestamp(),
+            )
+            .rotate(Criterion::Size(100_000), naming, Cleanup::KeepLogFiles(1))
+            .build_nested()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+        let info = |msg: &str| {
+            logger.log(
+                &Record::builder()
+                    .args(format_args!("{msg}"))
+                    .level(Level::Info)
+                    .build(),
+            );
+        };
+
+        info("before the upgrade");
+        handle.rotate_to("before-upgrade").unwrap();
+        info("after the upgrade");
+        handle.rotate_to("before-upgrade").unwrap();
+        info("after the second upgrade");
+        handle.flush();
+
+        let read = |name: &str| std::fs::read_to_string(directory.join(name)).unwrap();
+        assert!(read("test_before-upgrade.log").contains("before the upgrade"));
+        assert!(read("test_before-upgrade.restart-0000.log").contains("after the upgrade"));
+        assert!(read(current_file).contains("after the second upgrade"));
+
+        assert!(handle.rotate_to("").is_err());
+        assert!(handle.rotate_to("a/b").is_err());
+        handle.shutdown();
+    }
+}
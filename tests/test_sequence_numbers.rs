@@ -0,0 +1,57 @@
+mod test_utils;
+
+use flexi_logger::{writers::LogWriter, DeferredNow, FileSpec, Logger};
+use log::*;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_sequence_numbers() {
+    let numbers = Arc::new(Mutex::new(Vec::new()));
+    let handle = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(test_utils::dir()))
+        .format_for_files(|w, now, record| {
+            write!(w, "{:?} {}", now.sequence_number(), record.args())
+        })
+        .add_writer(
+            "Numbers",
+            Box::new(NumberWriter {
+                numbers: Arc::clone(&numbers),
+            }),
+        )
+        .use_sequence_numbers()
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+    info!("first");
+    info!(target: "{Numbers,_Default}", "second");
+    info!(target: "{Numbers}", "third");
+    debug!("not logged");
+    info!("fourth");
+    handle.flush();
+
+    let file = handle
+        .existing_log_files(&flexi_logger::LogfileSelector::default())
+        .unwrap()
+        .pop()
+        .unwrap();
+    assert_eq!(
+        std::fs::read_to_string(file).unwrap(),
+        "Some(0) first\nSome(1) second\nSome(3) fourth\n"
+    );
+    assert_eq!(*numbers.lock().unwrap(), vec![Some(1), Some(2)]);
+}
+
+struct NumberWriter {
+    numbers: Arc<Mutex<Vec<Option<u64>>>>,
+}
+impl LogWriter for NumberWriter {
+    fn write(&self, now: &mut DeferredNow, _record: &Record) -> std::io::Result<()> {
+        self.numbers.lock().unwrap().push(now.sequence_number());
+        Ok(())
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+mod test_utils;
+
+use chrono::{DateTime, Local};
+use flexi_logger::{writers::LogWriter, DeferredNow, FileSpec, LogfileSelector, Logger};
+use log::*;
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+// All outputs of a record see the same timestamp, even if some of them are slow
+#[test]
+fn test_shared_timestamp() {
+    let timestamps = Arc::new(Mutex::new(Vec::new()));
+    let handle = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(test_utils::dir()))
+        .format_for_files(|w, now, record| write!(w, "{} {}", now.format(TS), record.args()))
+        .add_writer("Slow", ClockWriter::boxed("slow", &timestamps))
+        .add_writer("Other", ClockWriter::boxed("other", &timestamps))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+    error!(target: "{Slow,Other,_Default}", "first");
+    error!(target: "{Slow,Other,_Default}", "second");
+    handle.flush();
+
+    let timestamps = timestamps.lock().unwrap();
+    let names = timestamps.iter().map(|(name, _)| *name).collect::<Vec<_>>();
+    assert_eq!(names, vec!["slow", "other", "slow", "other"]);
+    assert_eq!(timestamps[0].1, timestamps[1].1);
+    assert_eq!(timestamps[2].1, timestamps[3].1);
+    assert_ne!(timestamps[0].1, timestamps[2].1);
+
+    // the file output shows the same timestamps
+    let file = handle
+        .existing_log_files(&LogfileSelector::default())
+        .unwrap()
+        .pop()
+        .unwrap();
+    let content = std::fs::read_to_string(file).unwrap();
+    assert_eq!(
+        content.lines().collect::<Vec<_>>(),
+        vec![
+            format!("{} first", timestamps[0].1.format(TS)),
+            format!("{} second", timestamps[2].1.format(TS)),
+        ]
+    );
+}
+
+const TS: &str = "%Y-%m-%d %H:%M:%S%.9f";
+
+type Timestamps = Arc<Mutex<Vec<(&'static str, DateTime<Local>)>>>;
+
+struct ClockWriter {
+    name: &'static str,
+    timestamps: Timestamps,
+}
+impl ClockWriter {
+    fn boxed(name: &'static str, timestamps: &Timestamps) -> Box<Self> {
+        Box::new(Self {
+            name,
+            timestamps: Arc::clone(timestamps),
+        })
+    }
+}
+impl LogWriter for ClockWriter {
+    fn write(&self, now: &mut DeferredNow, _record: &Record) -> std::io::Result<()> {
+        let timestamp = *now.now();
+        if self.name == "slow" {
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        self.timestamps.lock().unwrap().push((self.name, timestamp));
+        Ok(())
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,59 @@
+mod test_utils;
+
+use flexi_logger::{FileSpec, Logger, WriteMode};
+use log::*;
+use std::{
+    future::Future,
+    pin::pin,
+    sync::Arc,
+    task::{Context, Poll, Wake},
+    thread::Thread,
+};
+
+#[test]
+fn test_shutdown_async() {
+    let file_spec = FileSpec::default()
+        .directory(self::test_utils::dir())
+        .suppress_timestamp();
+    let file_path = file_spec.as_pathbuf(None);
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(file_spec)
+        .write_mode(WriteMode::BufferDontFlush)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+    for i in 0..100 {
+        info!("line {i}");
+    }
+    assert!(block_on(logger.shutdown_async()) > 0);
+
+    let content = std::fs::read_to_string(file_path).unwrap();
+    assert_eq!(content.lines().count(), 100);
+    assert!(
+        logger.debug_resources().is_empty(),
+        "{:?}",
+        logger.debug_resources()
+    );
+}
+
+// A minimal executor: parks the thread until the future wakes it,
+// and returns how often the future was polled in vain
+fn block_on<F: Future<Output = ()>>(future: F) -> usize {
+    struct ThreadWaker(Thread);
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Arc::new(ThreadWaker(std::thread::current())).into();
+    let mut cx = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    let mut pending_polls = 0;
+    while future.as_mut().poll(&mut cx) == Poll::Pending {
+        pending_polls += 1;
+        std::thread::park();
+    }
+    pending_polls
+}
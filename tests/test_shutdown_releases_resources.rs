@@ -0,0 +1,65 @@
+mod test_utils;
+
+use flexi_logger::{
+    writers::FileLogWriter, Cleanup, Criterion, FileSpec, Logger, Naming, WriteMode,
+};
+use log::*;
+
+#[test]
+fn test_shutdown_releases_resources() {
+    let directory = test_utils::dir();
+
+    let additional_writer = FileLogWriter::builder(
+        FileSpec::default()
+            .directory(&directory)
+            .discriminant("additional"),
+    )
+    .write_mode(additional_write_mode())
+    .try_build()
+    .unwrap();
+
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(&directory))
+        .write_mode(WriteMode::BufferAndFlush)
+        .rotate(
+            Criterion::Size(100_000),
+            Naming::Numbers,
+            Cleanup::KeepLogFiles(3),
+        )
+        .add_writer("Additional", Box::new(additional_writer))
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+    info!("to the main log");
+    info!(target: "{Additional}", "to the additional log");
+    logger.flush();
+
+    let report = logger.debug_resources();
+    assert!(!report.open_files.is_empty(), "{report:?}");
+    assert!(
+        report
+            .threads
+            .iter()
+            .any(|name| name == "flexi_logger-fs-cleanup"),
+        "{report:?}"
+    );
+    assert!(
+        report.threads.iter().any(|name| name.contains("flusher")),
+        "{report:?}"
+    );
+
+    logger.shutdown();
+
+    let report = logger.debug_resources();
+    assert!(report.is_empty(), "{report:?}");
+}
+
+#[cfg(feature = "async")]
+fn additional_write_mode() -> WriteMode {
+    WriteMode::Async
+}
+#[cfg(not(feature = "async"))]
+fn additional_write_mode() -> WriteMode {
+    WriteMode::BufferAndFlush
+}
@@ -28,6 +28,12 @@ mod a {
             .unwrap_or_else(|e| panic!("Logger initialization failed because: {e}"));
 
         assert!(specfile.exists());
+        let content = std::fs::read_to_string(&specfile).unwrap();
+        assert!(
+            content.contains("duplicate_to_stderr = 'none'"),
+            "{content}"
+        );
+        assert!(content.contains("global_level = 'info'"), "{content}");
 
         error!("This is an error-0");
         warn!("This is a warning-0");
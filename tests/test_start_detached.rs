@@ -0,0 +1,27 @@
+mod test_utils;
+
+use flexi_logger::{FileSpec, Logger, WriteMode};
+use log::*;
+
+#[test]
+fn test_start_detached() {
+    let directory = test_utils::dir();
+    let file_spec = FileSpec::default()
+        .directory(&directory)
+        .suppress_timestamp();
+    let path = file_spec.as_pathbuf(None);
+    {
+        let _guard = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_file(file_spec)
+            .format(|w, _now, record| write!(w, "{}", record.args()))
+            .write_mode(WriteMode::BufferDontFlush)
+            .start_detached()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+        info!("info 1");
+        debug!("debug 1");
+        info!("info 2");
+    }
+    // dropping the guard has flushed the buffer
+    assert_eq!(std::fs::read_to_string(path).unwrap(), "info 1\ninfo 2\n");
+}
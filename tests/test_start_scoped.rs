@@ -0,0 +1,48 @@
+use flexi_logger::{FileSpec, Logger};
+use log::*;
+
+#[test]
+fn test_start_scoped() {
+    let start = |spec: &str| {
+        Logger::try_with_str(spec)
+            .unwrap()
+            .log_to_file(FileSpec::default())
+            .capture_file_output()
+            .start_scoped()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"))
+    };
+
+    let outer = start("info");
+    info!("outer 1");
+    {
+        let inner = start("debug");
+        debug!("inner 1");
+        assert_eq!(log::max_level(), LevelFilter::Debug);
+
+        inner.parse_new_spec("trace").unwrap();
+        trace!("inner 2");
+        assert!(inner.captured_output().unwrap().contains("inner 2"));
+    }
+    assert_eq!(log::max_level(), LevelFilter::Info);
+    debug!("outer - you must not see it");
+    info!("outer 2");
+
+    let captured = outer.captured_output().unwrap();
+    assert_eq!(captured.lines().count(), 2, "{captured}");
+    assert!(captured.contains("outer 1") && captured.contains("outer 2"));
+
+    // a logger that ends before a later started one hands over its saved level
+    let middle = start("warn");
+    let inner = start("debug");
+    middle.parse_new_spec("error").unwrap();
+    drop(middle);
+    assert_eq!(log::max_level(), LevelFilter::Error);
+    drop(inner);
+    assert_eq!(log::max_level(), LevelFilter::Info);
+
+    // another global logger can't be installed
+    drop(outer);
+    assert_eq!(log::max_level(), LevelFilter::Off);
+    assert!(Logger::try_with_str("info").unwrap().start().is_err());
+    let _again = start("info");
+}
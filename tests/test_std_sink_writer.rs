@@ -0,0 +1,84 @@
+#![cfg(target_family = "unix")]
+mod test_utils;
+
+use flexi_logger::{
+    writers::{LogWriter, StdSinkWriter},
+    DeferredNow,
+};
+use log::*;
+use std::time::{Duration, Instant};
+
+#[test]
+fn test_std_sink_writer() {
+    let directory = test_utils::dir();
+    std::fs::create_dir_all(&directory).unwrap();
+    let path = directory.join("sink.log");
+    let marker = directory.join("closed");
+    // the directory is only unique per second
+    std::fs::remove_file(&path).ok();
+    std::fs::remove_file(&marker).ok();
+
+    // the child process exits after two lines and has to be restarted;
+    // the marker file is created once the child process has closed its stdin
+    let writer = StdSinkWriter::builder("sh")
+        .arg("-c")
+        .arg(format!(
+            "head -n 2 >> {}; exec <&-; touch {marker}",
+            path.display(),
+            marker = marker.display()
+        ))
+        .try_build()
+        .unwrap();
+
+    let write = |msg: &str| {
+        writer
+            .write(
+                &mut DeferredNow::new(),
+                &Record::builder()
+                    .level(Level::Info)
+                    .args(format_args!("{msg}"))
+                    .build(),
+            )
+            .unwrap();
+    };
+    write("first line");
+    write("second line");
+    wait_for(|| marker.exists());
+    write("third line");
+    writer.shutdown();
+
+    let content = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(content.lines().count(), 3, "{content}");
+    assert!(content.contains("third line"), "{content}");
+}
+
+#[test]
+fn test_std_sink_writer_without_restart() {
+    let writer = StdSinkWriter::builder("true")
+        .restart_on_exit(false)
+        .try_build()
+        .unwrap();
+    wait_for(|| writer.healthcheck().is_err());
+    assert!(writer
+        .write(
+            &mut DeferredNow::new(),
+            &Record::builder()
+                .level(Level::Info)
+                .args(format_args!("lost"))
+                .build(),
+        )
+        .is_err());
+
+    assert!(StdSinkWriter::builder("/this/program/does/not/exist")
+        .try_build()
+        .is_err());
+}
+
+// Waits until the condition is fulfilled, for at most ten seconds
+fn wait_for<F: Fn() -> bool>(condition: F) {
+    let start = Instant::now();
+    while !condition() {
+        assert!(start.elapsed() < Duration::from_secs(10), "timeout");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
@@ -0,0 +1,55 @@
+mod test_utils;
+
+use flexi_logger::{writers::RotationEvent, Cleanup, Criterion, FileSpec, Logger, Naming};
+use log::*;
+use std::path::Path;
+
+#[test]
+fn test_subscribe_rotation() {
+    let directory = test_utils::dir();
+
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(
+            FileSpec::default()
+                .directory(&directory)
+                .basename("upload")
+                .suppress_timestamp(),
+        )
+        .rotate(Criterion::Size(50), Naming::Numbers, Cleanup::Never)
+        .rotate_on_shutdown(true)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+    let receiver = logger.subscribe_rotation().unwrap();
+
+    info!("this line is long enough to fill a file alone: 0");
+    info!("this line is long enough to fill a file alone: 1");
+    logger.rotate_to("marked").unwrap();
+    info!("short");
+    logger.shutdown();
+
+    let events = receiver.try_iter().collect::<Vec<_>>();
+    let current = Some("upload_rCURRENT.log");
+    assert_eq!(
+        events.iter().map(names).collect::<Vec<_>>(),
+        vec![
+            ("upload_r00000.log", current),
+            ("upload_marked.log", current),
+            ("upload_r00001.log", None),
+        ]
+    );
+    for event in &events {
+        assert!(event.old_path.exists(), "{event:?}");
+    }
+}
+
+fn names(event: &RotationEvent) -> (&str, Option<&str>) {
+    (
+        file_name(&event.old_path),
+        event.new_path.as_deref().map(file_name),
+    )
+}
+
+fn file_name(path: &Path) -> &str {
+    path.file_name().unwrap().to_str().unwrap()
+}
@@ -0,0 +1,55 @@
+mod test_utils;
+
+use flexi_logger::{FileSpec, Logger};
+use log::*;
+
+#[test]
+fn test_subtree_handle() {
+    let logger = Logger::try_with_str("info, myapp::db = warn")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(test_utils::dir()))
+        .capture_file_output()
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+    let mut net_handle = logger.scoped("myapp::net");
+    assert_eq!(net_handle.module_prefix(), "myapp::net");
+
+    net_handle.parse_new_spec("debug, tcp = trace").unwrap();
+    trace!(target: "myapp::net::tcp", "tcp trace 1");
+    debug!(target: "myapp::net::udp", "udp debug 1");
+    trace!(target: "myapp::net::udp", "udp trace - you must not see it!");
+    info!(target: "myapp::db", "db info - you must not see it!");
+    warn!(target: "myapp::db", "db warn 1");
+
+    net_handle.parse_and_push_temp_spec("off").unwrap();
+    error!(target: "myapp::net::tcp", "tcp error - you must not see it!");
+    info!(target: "myapp::other", "other info 1");
+
+    // the global spec controls everything outside the subtree
+    logger.parse_new_spec("warn, myapp::net = trace").unwrap();
+    info!(target: "myapp::other", "other info - you must not see it!");
+
+    // and the subtree handle still restores its own filters only
+    net_handle.pop_temp_spec();
+    trace!(target: "myapp::net::tcp", "tcp trace 2");
+    debug!(target: "myapp::net::udp", "udp debug 2");
+    info!(target: "myapp::other", "other info - you must not see it!");
+
+    let captured = logger.captured_output().unwrap();
+    assert!(!captured.contains("you must not see it"), "{captured}");
+    for expected in [
+        "tcp trace 1",
+        "udp debug 1",
+        "db warn 1",
+        "other info 1",
+        "tcp trace 2",
+        "udp debug 2",
+    ] {
+        assert!(
+            captured.contains(expected),
+            "{expected} missing in {captured}"
+        );
+    }
+    assert_eq!(captured.lines().count(), 6, "{captured}");
+}
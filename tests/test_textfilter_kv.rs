@@ -0,0 +1,25 @@
+mod test_utils;
+
+#[cfg(all(feature = "textfilter", feature = "kv"))]
+#[test]
+fn test_textfilter_kv() {
+    use flexi_logger::{FileSpec, Logger};
+    use log::*;
+
+    let logger = Logger::try_with_str("info/tenant=acme")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(self::test_utils::dir()))
+        .textfilter_with_kv()
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+    error!(tenant = "acme", user = 17; "This is an error message for acme");
+    error!(tenant = "other"; "This is an error message for other - you must not see it!");
+    warn!("This is a warning without kv - you must not see it!");
+    info!(user = 4, tenant = "acme"; "This is an info message for acme");
+
+    logger.validate_logs(&[
+        ("ERROR", "[test_textfilter_kv]", "message for acme"),
+        ("INFO", "[test_textfilter_kv]", "message for acme"),
+    ]);
+}
@@ -0,0 +1,52 @@
+#[cfg(feature = "trc")]
+mod test_utils;
+
+#[cfg(feature = "trc")]
+mod d {
+    use flexi_logger::{writers::FileLogWriter, FileSpec, LogSpecification};
+    use tracing::{debug, info, info_span};
+    use tracing_subscriber::{layer::SubscriberExt, Registry};
+
+    #[test]
+    fn test_trc_flexi_layer() {
+        let directory = super::test_utils::dir();
+        let file_spec = FileSpec::default()
+            .directory(&directory)
+            .suppress_timestamp();
+        let path = file_spec.as_pathbuf(None);
+        let (file_layer, fw_handle, _spec_file_notifier) = flexi_logger::trc::flexi_layer(
+            LogSpecification::info(),
+            None,
+            FileLogWriter::builder(file_spec).format(|w, _now, record| {
+                write!(
+                    w,
+                    "{} [{}] {}",
+                    record.level(),
+                    record.module_path().unwrap_or("<unnamed>"),
+                    record.args()
+                )
+            }),
+            true,
+        )
+        .unwrap();
+
+        let subscriber = Registry::default().with(file_layer);
+        tracing::subscriber::with_default(subscriber, || {
+            info!("no span");
+            let outer = info_span!("outer", a = 1);
+            let _outer = outer.enter();
+            let inner = info_span!("inner", b = "x", c = tracing::field::Empty);
+            inner.record("c", 3);
+            let _inner = inner.enter();
+            info!(d = true, "in spans");
+            debug!("not logged");
+        });
+        drop(fw_handle);
+
+        assert_eq!(
+            std::fs::read_to_string(path).unwrap(),
+            "INFO [test_trc_flexi_layer::d] no span\n\
+             INFO [test_trc_flexi_layer::d] outer{a=1}:inner{b=\"x\" c=3}: in spans d=true\n"
+        );
+    }
+}
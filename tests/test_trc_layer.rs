@@ -0,0 +1,50 @@
+#[cfg(feature = "trc")]
+mod test_utils;
+
+#[cfg(feature = "trc")]
+mod d {
+    use flexi_logger::{writers::FileLogWriter, FileSpec, LogSpecification};
+    use tracing::{debug, error, info, trace, warn};
+    use tracing_subscriber::{layer::SubscriberExt, Registry};
+
+    #[test]
+    fn test_trc_layer() {
+        let directory = super::test_utils::dir();
+        let (file_layer, fw_handle, _spec_file_notifier) = flexi_logger::trc::layer(
+            LogSpecification::parse("info, test_trc_layer::d::quiet = error").unwrap(),
+            None,
+            FileLogWriter::builder(FileSpec::default().directory(&directory)),
+        )
+        .unwrap();
+
+        let subscriber = Registry::default().with(file_layer);
+        tracing::subscriber::with_default(subscriber, || {
+            error!("This is an error");
+            warn!("This is a warning");
+            info!("This is an info");
+            debug!("This is a debug");
+            trace!("This is a trace");
+            quiet::log();
+        });
+        drop(fw_handle);
+
+        let content = std::fs::read_dir(&directory)
+            .unwrap()
+            .map(|entry| std::fs::read_to_string(entry.unwrap().path()).unwrap())
+            .collect::<String>();
+        assert!(content.contains("This is an error"));
+        assert!(content.contains("This is a warning"));
+        assert!(content.contains("This is an info"));
+        assert!(!content.contains("This is a debug"));
+        assert!(!content.contains("This is a trace"));
+        assert!(content.contains("quiet error"));
+        assert!(!content.contains("quiet warning"));
+    }
+
+    mod quiet {
+        pub(super) fn log() {
+            tracing::error!("quiet error");
+            tracing::warn!("quiet warning");
+        }
+    }
+}
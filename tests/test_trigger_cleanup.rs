@@ -0,0 +1,52 @@
+mod test_utils;
+
+use flexi_logger::{writers::FileLogWriter, Cleanup, Criterion, FileSpec, Logger, Naming};
+use log::*;
+use std::path::Path;
+
+#[test]
+fn test_trigger_cleanup() {
+    let directory = test_utils::dir();
+
+    let logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(&directory))
+        .rotate(
+            Criterion::Size(50),
+            Naming::Numbers,
+            Cleanup::KeepLogFiles(5),
+        )
+        .cleanup_in_background_thread(false)
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+    for i in 0..4 {
+        info!("this line is long enough to fill a file alone: {i}");
+    }
+    logger.flush();
+    // three rotated files and the current one
+    assert_eq!(file_count(&directory), 4);
+
+    // reduce the number of files to keep, and clean up immediately
+    logger
+        .reset_flw(
+            &FileLogWriter::builder(FileSpec::default().directory(&directory))
+                .rotate(
+                    Criterion::Size(50),
+                    Naming::Numbers,
+                    Cleanup::KeepLogFiles(1),
+                )
+                .cleanup_in_background_thread(false),
+        )
+        .unwrap();
+    logger.trigger_cleanup().unwrap();
+    assert_eq!(file_count(&directory), 2);
+
+    // nothing more to clean up
+    logger.trigger_cleanup().unwrap();
+    assert_eq!(file_count(&directory), 2);
+}
+
+fn file_count(directory: &Path) -> usize {
+    std::fs::read_dir(directory).unwrap().count()
+}
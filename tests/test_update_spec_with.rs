@@ -0,0 +1,33 @@
+#![cfg(feature = "textfilter")]
+
+mod test_utils;
+
+use flexi_logger::{FileSpec, Logger};
+use log::*;
+
+#[test]
+fn test_update_spec_with() {
+    let logger = Logger::try_with_str("info, mod1 = debug /important")
+        .unwrap()
+        .log_to_file(FileSpec::default().directory(test_utils::dir()))
+        .capture_file_output()
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+    debug!(target: "mod1", "important debug 1");
+    trace!(target: "mod2", "important trace - you must not see it!");
+
+    logger.update_spec_with(|builder| {
+        builder.module("mod2", LevelFilter::Trace).remove("mod1");
+    });
+    debug!(target: "mod1", "important debug - you must not see it!");
+    trace!(target: "mod2", "important trace 1");
+    // the text filter is retained
+    trace!(target: "mod2", "trace - you must not see it!");
+
+    let captured = logger.captured_output().unwrap();
+    assert!(!captured.contains("you must not see it"), "{captured}");
+    assert!(captured.contains("important debug 1"), "{captured}");
+    assert!(captured.contains("important trace 1"), "{captured}");
+    assert_eq!(captured.lines().count(), 2, "{captured}");
+}
@@ -0,0 +1,45 @@
+mod test_utils;
+
+use flexi_logger::{Cleanup, Criterion, FileSpec, Logger, Naming};
+use log::*;
+
+#[test]
+fn test_utc_file_names() {
+    let directory = test_utils::dir();
+
+    let handle = Logger::try_with_str("info")
+        .unwrap()
+        .use_utc_for_file_names()
+        .log_to_file(
+            FileSpec::default()
+                .directory(&directory)
+                .basename("test")
+                .suppress_timestamp(),
+        )
+        .rotate(
+            Criterion::Size(100),
+            Naming::TimestampsCustomFormat {
+                current_infix: Some(""),
+                format: "%Y-%m-%d_%H-%M-%S.%3f%z",
+            },
+            Cleanup::Never,
+        )
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+    for i in 0..5 {
+        info!("line {i} that is long enough to trigger a rotation after a few lines");
+        std::thread::sleep(std::time::Duration::from_millis(5));
+    }
+    handle.flush();
+
+    let mut rotated = 0;
+    for entry in std::fs::read_dir(&directory).unwrap() {
+        let name = entry.unwrap().file_name().to_string_lossy().to_string();
+        if name != "test.log" {
+            assert!(name.ends_with("+0000.log"), "unexpected file name {name}");
+            rotated += 1;
+        }
+    }
+    assert!(rotated > 0, "no rotated files found");
+}
@@ -3,6 +3,8 @@
 use chrono::{DateTime, Local};
 use either::Either;
 use flate2::read::GzDecoder;
+use flexi_logger::{writers::LogWriter, DeferredNow, FormatFunction};
+use log::Record;
 #[cfg(feature = "compress")]
 use std::ffi::OsStr;
 use std::{
@@ -10,6 +12,7 @@ use std::{
     io::{BufRead, BufReader},
     ops::Add,
     path::{Path, PathBuf},
+    sync::{Arc, Mutex},
 };
 
 const CTRL_INDEX: &str = "CTRL_INDEX";
@@ -147,3 +150,119 @@ pub fn count_log_lines(directory: &Path) -> usize {
 
     total_line_count
 }
+
+// The sorted names of the files in the given folder
+pub fn file_names(directory: &Path) -> Vec<String> {
+    let mut names = std::fs::read_dir(directory)
+        .unwrap()
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect::<Vec<_>>();
+    names.sort();
+    names
+}
+
+// Collects the messages of the records it gets
+pub struct MessageCollector {
+    messages: Arc<Mutex<Vec<String>>>,
+    max_log_level: log::LevelFilter,
+}
+impl MessageCollector {
+    pub fn new(messages: &Arc<Mutex<Vec<String>>>) -> Self {
+        Self {
+            messages: Arc::clone(messages),
+            max_log_level: log::LevelFilter::Trace,
+        }
+    }
+
+    pub fn with_max_log_level(mut self, max_log_level: log::LevelFilter) -> Self {
+        self.max_log_level = max_log_level;
+        self
+    }
+}
+impl LogWriter for MessageCollector {
+    fn write(&self, _now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        self.messages
+            .lock()
+            .unwrap()
+            .push(record.args().to_string());
+        Ok(())
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn max_log_level(&self) -> log::LevelFilter {
+        self.max_log_level
+    }
+}
+
+// Writes only the message of the record
+pub fn message_only(
+    w: &mut dyn std::io::Write,
+    _now: &mut DeferredNow,
+    record: &Record,
+) -> std::io::Result<()> {
+    write!(w, "{}", record.args())
+}
+
+// Writes the level, the target and the message of the record
+pub fn level_target_message(
+    w: &mut dyn std::io::Write,
+    _now: &mut DeferredNow,
+    record: &Record,
+) -> std::io::Result<()> {
+    write!(
+        w,
+        "{} {}: {}",
+        record.level(),
+        record.target(),
+        record.args()
+    )
+}
+
+// Only accepts log lines that are formatted with the given format function, and collects them
+pub struct LineCollector {
+    lines: Arc<Mutex<Vec<String>>>,
+    format: FormatFunction,
+    o_max_line_length: Option<usize>,
+}
+impl LineCollector {
+    pub fn new(lines: &Arc<Mutex<Vec<String>>>, format: FormatFunction) -> Self {
+        Self {
+            lines: Arc::clone(lines),
+            format,
+            o_max_line_length: None,
+        }
+    }
+
+    pub fn with_max_line_length(mut self, max_line_length: usize) -> Self {
+        self.o_max_line_length = Some(max_line_length);
+        self
+    }
+}
+impl LogWriter for LineCollector {
+    fn write(&self, _now: &mut DeferredNow, _record: &Record) -> std::io::Result<()> {
+        panic!("write must not be called");
+    }
+
+    fn formatted_with(&self) -> Option<FormatFunction> {
+        Some(self.format)
+    }
+
+    fn max_line_length(&self) -> Option<usize> {
+        self.o_max_line_length
+    }
+
+    fn write_formatted(&self, line: &[u8]) -> std::io::Result<()> {
+        self.lines
+            .lock()
+            .unwrap()
+            .push(String::from_utf8_lossy(line).to_string());
+        Ok(())
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
@@ -0,0 +1,49 @@
+#![cfg(target_os = "linux")]
+mod test_utils;
+
+use flexi_logger::{writers::FileLogWriter, ErrorChannel, FileSpec, Logger, WriteFailurePolicy};
+use log::*;
+use std::time::Duration;
+
+#[test]
+fn test_write_failure_policy() {
+    let error_file = test_utils::file("flexi_logger_errors.log");
+    std::fs::create_dir_all(error_file.parent().unwrap()).unwrap();
+    // every write to /dev/full fails with "No space left on device"
+    let full_disk = std::fs::OpenOptions::new()
+        .write(true)
+        .open("/dev/full")
+        .unwrap();
+    {
+        let _logger = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_writer(Box::new(
+                FileLogWriter::builder(FileSpec::default())
+                    .with_open_file(full_disk)
+                    .write_failure_policy(WriteFailurePolicy::Drop {
+                        after_failures: 3,
+                        probe_interval: Duration::from_secs(3600),
+                    })
+                    .try_build()
+                    .unwrap(),
+            ))
+            .error_channel(ErrorChannel::File(error_file.clone()))
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+        for i in 0..20 {
+            info!("line {i}");
+        }
+    }
+
+    let errors = std::fs::read_to_string(&error_file).unwrap();
+    let error_lines: Vec<&str> = errors
+        .lines()
+        .filter(|line| line.contains("ERRCODE::Write"))
+        .collect();
+    assert_eq!(error_lines.len(), 3, "{errors}");
+    assert!(
+        error_lines[2].contains("writing failed 3 times in a row"),
+        "{errors}"
+    );
+}
@@ -0,0 +1,51 @@
+mod test_utils;
+
+use flexi_logger::{default_format, Logger};
+use log::*;
+use std::sync::{Arc, Mutex};
+use test_utils::LineCollector;
+
+#[test]
+fn test_write_formatted() {
+    let primary_lines = Arc::new(Mutex::new(Vec::new()));
+    let alert_lines = Arc::new(Mutex::new(Vec::new()));
+
+    let _logger = Logger::try_with_str("info")
+        .unwrap()
+        .log_to_writer(Box::new(LineCollector::new(&primary_lines, default_format)))
+        .add_writer(
+            "Alert",
+            Box::new(LineCollector::new(&alert_lines, default_format)),
+        )
+        .start()
+        .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+    info!("first");
+    warn!(target: "{Alert,_Default}", "second");
+    error!(target: "{Alert}", "third {}", Recursive);
+
+    assert_eq!(
+        *primary_lines.lock().unwrap(),
+        vec![
+            format!("INFO [{}] first", module_path!()),
+            format!("WARN [{}] second", module_path!()),
+        ]
+    );
+    assert_eq!(
+        *alert_lines.lock().unwrap(),
+        vec![
+            format!("WARN [{}] second", module_path!()),
+            format!("INFO [{}] from display", module_path!()),
+            format!("ERROR [{}] third recursive", module_path!()),
+        ]
+    );
+}
+
+// Writes a log line while being formatted
+struct Recursive;
+impl std::fmt::Display for Recursive {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        info!(target: "{Alert}", "from display");
+        write!(f, "recursive")
+    }
+}
@@ -0,0 +1,46 @@
+mod test_utils;
+
+use flexi_logger::{FileSpec, Logger};
+use log::*;
+use std::sync::{Arc, Mutex};
+use test_utils::{level_target_message, LineCollector};
+
+#[test]
+fn test_write_record() {
+    let file_spec = FileSpec::default()
+        .directory(self::test_utils::dir())
+        .suppress_timestamp();
+    let file_path = file_spec.as_pathbuf(None);
+    let alerts = Arc::new(Mutex::new(Vec::new()));
+    {
+        let logger = Logger::try_with_str("info, libfoo=warn")
+            .unwrap()
+            .log_to_file(file_spec)
+            .format(level_target_message)
+            .add_writer(
+                "Alert",
+                Box::new(LineCollector::new(&alerts, level_target_message)),
+            )
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+        info!("from the log macros");
+        logger.write_record(Level::Info, "libfoo", "filtered out");
+        logger.write_record(Level::Warn, "libfoo", "disk almost full");
+        logger.write_record(Level::Info, "libbar", "started");
+        logger.write_record(Level::Debug, "libbar", "filtered out");
+        logger.write_record(Level::Error, "{Alert,_Default}libfoo", "disk full");
+    }
+
+    assert_eq!(
+        std::fs::read_to_string(file_path).unwrap(),
+        "INFO test_write_record: from the log macros\n\
+         WARN libfoo: disk almost full\n\
+         INFO libbar: started\n\
+         ERROR libfoo: disk full\n"
+    );
+    assert_eq!(
+        *alerts.lock().unwrap(),
+        vec!["ERROR libfoo: disk full".to_string()]
+    );
+}
@@ -0,0 +1,68 @@
+mod test_utils;
+
+use flexi_logger::{writers::LogWriter, DeferredNow, FileSpec, Logger};
+use log::*;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_writer_target() {
+    let file_spec = FileSpec::default()
+        .directory(self::test_utils::dir())
+        .suppress_timestamp();
+    let file_path = file_spec.as_pathbuf(None);
+    let targets = Arc::new(Mutex::new(Vec::new()));
+    {
+        let _logger = Logger::try_with_str("info, audit=warn")
+            .unwrap()
+            .log_to_file(file_spec)
+            .format(flexi_logger::with_target)
+            .add_writer(
+                "Sec",
+                Box::new(TargetCollector {
+                    targets: Arc::clone(&targets),
+                }),
+            )
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+        info!("plain");
+        info!(target: "audit", "filtered out");
+        warn!(target: "audit", "direct");
+        info!(target: "{Sec,_Default}", "to both, with module path");
+        info!(target: "{Sec,_Default}audit", "only to Sec, because of the spec");
+        warn!(target: "{Sec,_Default}audit", "to both, with target");
+    }
+
+    assert_eq!(
+        std::fs::read_to_string(file_path).unwrap(),
+        "INFO [test_writer_target] plain\n\
+         WARN [test_writer_target => audit] direct\n\
+         INFO [test_writer_target] to both, with module path\n\
+         WARN [test_writer_target => audit] to both, with target\n"
+    );
+    assert_eq!(
+        *targets.lock().unwrap(),
+        vec![
+            "test_writer_target".to_string(),
+            "audit".to_string(),
+            "audit".to_string()
+        ]
+    );
+}
+
+struct TargetCollector {
+    targets: Arc<Mutex<Vec<String>>>,
+}
+impl LogWriter for TargetCollector {
+    fn write(&self, _now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        self.targets
+            .lock()
+            .unwrap()
+            .push(record.target().to_string());
+        Ok(())
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
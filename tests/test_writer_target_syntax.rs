@@ -0,0 +1,58 @@
+mod test_utils;
+
+use flexi_logger::{writers::LogWriter, DeferredNow, Logger};
+use log::*;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_writer_target_syntax() {
+    let primary = Collector::default();
+    let sec = Collector::default();
+    {
+        let _logger = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_writer(Box::new(primary.clone()))
+            .add_writer("Sec", Box::new(sec.clone()))
+            .writer_target_syntax(Some(('<', '>')))
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+        info!(target: "{Sec}", "braces are no special syntax");
+        info!(target: "<Sec>", "only to Sec");
+        info!(target: "<Sec,_Default>audit", "to both");
+        info!(target: "<html>", "no writer is named");
+    }
+    assert_eq!(
+        primary.lines(),
+        vec![
+            "{Sec}: braces are no special syntax",
+            "audit: to both",
+            "<html>: no writer is named",
+        ]
+    );
+    assert_eq!(
+        sec.lines(),
+        vec!["test_writer_target_syntax: only to Sec", "audit: to both"]
+    );
+}
+
+#[derive(Clone, Default)]
+struct Collector(Arc<Mutex<Vec<String>>>);
+impl Collector {
+    fn lines(&self) -> Vec<String> {
+        self.0.lock().unwrap().clone()
+    }
+}
+impl LogWriter for Collector {
+    fn write(&self, _now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        self.0
+            .lock()
+            .unwrap()
+            .push(format!("{}: {}", record.target(), record.args()));
+        Ok(())
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
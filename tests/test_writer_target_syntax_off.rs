@@ -0,0 +1,45 @@
+mod test_utils;
+
+use flexi_logger::{writers::LogWriter, DeferredNow, Logger};
+use log::*;
+use std::sync::{Arc, Mutex};
+
+#[test]
+fn test_writer_target_syntax_off() {
+    let primary = Collector::default();
+    let sec = Collector::default();
+    {
+        let _logger = Logger::try_with_str("info")
+            .unwrap()
+            .log_to_writer(Box::new(primary.clone()))
+            .add_writer("Sec", Box::new(sec.clone()))
+            .writer_target_syntax(None)
+            .start()
+            .unwrap_or_else(|e| panic!("Logger initialization failed with {e}"));
+
+        info!(target: "{Sec}", "no special syntax");
+    }
+    assert_eq!(primary.lines(), vec!["{Sec}: no special syntax"]);
+    assert!(sec.lines().is_empty());
+}
+
+#[derive(Clone, Default)]
+struct Collector(Arc<Mutex<Vec<String>>>);
+impl Collector {
+    fn lines(&self) -> Vec<String> {
+        self.0.lock().unwrap().clone()
+    }
+}
+impl LogWriter for Collector {
+    fn write(&self, _now: &mut DeferredNow, record: &Record) -> std::io::Result<()> {
+        self.0
+            .lock()
+            .unwrap()
+            .push(format!("{}: {}", record.target(), record.args()));
+        Ok(())
+    }
+
+    fn flush(&self) -> std::io::Result<()> {
+        Ok(())
+    }
+}